@@ -0,0 +1,116 @@
+# User-facing text produced by the backend. Every message here must also exist,
+# translated, in the other catalogs; missing ones fall back to this file.
+
+## Desktop notifications
+
+notification-session-finished = Claude session finished
+notification-session-failed = Claude session failed
+notification-hook-failed = Hook failed
+notification-hooks-failed-body = { $failed } of { $total } { $event } hooks failed in { $project }
+
+## Pre-commit review
+
+commit-review-disabled = Code review functionality has been disabled (Agent functionality removed)
+
+## Command policy
+
+policy-blocked = Blocked by command policy: { $reason }
+policy-fork-bomb = Fork bomb
+policy-rm-root = Recursive delete of { $target }
+policy-raw-disk-write = dd onto a device
+policy-format-disk = { $binary } formats a disk
+policy-pipe-to-shell = Downloaded script piped into { $shell }
+policy-blocked-pattern = Matches blocked pattern { $pattern }
+policy-blocked-binary = { $binary } is blocked
+policy-not-allowed = { $binary } is not on the allowlist
+
+## Environment doctor
+
+doctor-claude-cli = Claude CLI
+doctor-git = Git
+doctor-shell = Shell
+doctor-keychain = Keychain
+doctor-write-permissions = Write permissions
+doctor-mcp-servers = MCP servers
+doctor-network = Network
+
+doctor-unknown-version = unknown version
+doctor-no-response = no response
+doctor-does-not-run = { $path } does not run
+
+doctor-claude-cli-found = Claude CLI { $version }
+doctor-claude-cli-install-hint = Install it with `npm install -g @anthropic-ai/claude-code`, or pick an installation in Settings
+doctor-claude-cli-pick-hint = Pick a Claude CLI installation in Settings
+
+doctor-git-missing = git is not on PATH; checkpoints and git statistics are unavailable
+doctor-git-hint = Install git from https://git-scm.com and make sure it is on PATH
+
+doctor-shell-at = { $shell } at { $path }
+doctor-shell-none = Neither bash nor PowerShell is on PATH
+doctor-shell-no-bash = bash is not on PATH
+doctor-shell-no-bash-found = bash is not on PATH; found { $found }
+doctor-shell-windows-hint = Install Git for Windows, or set CLAUDE_CODE_GIT_BASH_PATH to its bash.exe
+doctor-shell-hint = Install bash with your package manager
+
+doctor-keychain-ok = Keychain is accessible
+doctor-keychain-hint = Unlock the keychain, or on Linux start a Secret Service provider such as gnome-keyring; credentials cannot be stored until then
+doctor-keychain-portable = Not used in portable mode; credentials are kept out of the system
+
+doctor-write-permissions-ok = ~/.claude, app data, logs and temp are writable
+doctor-write-permissions-hint = Fix the ownership or permissions of these directories
+
+doctor-mcp-servers-ok =
+    { $count ->
+        [0] No stdio servers configured
+        [one] The stdio server command was found
+       *[other] All { $count } stdio server commands found
+    }
+doctor-mcp-servers-missing = Commands not found: { $commands }
+doctor-mcp-servers-hint = Install the missing programs or fix the server commands in the MCP settings
+
+doctor-network-ok = { $endpoint } answered in { $latency } ms
+doctor-network-api-unreachable = Cannot reach { $endpoint }: { $error }
+doctor-network-api-hint = Check the proxy settings and that your firewall allows the API endpoint
+doctor-network-offline = No internet connection: { $error }
+doctor-network-offline-hint = Connect to the internet, or configure a proxy in Settings
+
+## Activity summaries for screen readers
+
+summary-project = { $project }: { $details }.
+summary-separator = { ", " }
+summary-tools =
+    { $count ->
+        [one] 1 tool call ({ $tools })
+       *[other] { $count } tool calls ({ $tools })
+    }
+summary-messages =
+    { $count ->
+        [one] 1 message
+       *[other] { $count } messages
+    }
+summary-hooks-passed =
+    { $count ->
+        [one] 1 hook passed
+       *[other] { $count } hooks passed
+    }
+summary-hooks-failed =
+    { $count ->
+        [one] 1 hook failed
+       *[other] { $count } hooks failed
+    }
+summary-blocked =
+    { $count ->
+        [one] 1 command blocked
+       *[other] { $count } commands blocked
+    }
+summary-checkpoints =
+    { $count ->
+        [one] 1 checkpoint created
+       *[other] { $count } checkpoints created
+    }
+summary-session-finished = session finished
+summary-session-failed = session failed
+summary-mcp-down = MCP server { $name } is down.
+summary-mcp-recovered = MCP server { $name } is back.
+summary-network-down = Connection to the API lost.
+summary-network-up = Connection to the API restored.
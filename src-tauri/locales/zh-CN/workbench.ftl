@@ -0,0 +1,90 @@
+# 后端生成的用户可见文本，与 en-US/workbench.ftl 一一对应
+
+## 桌面通知
+
+notification-session-finished = Claude 会话已完成
+notification-session-failed = Claude 会话失败
+notification-hook-failed = Hook 执行失败
+notification-hooks-failed-body = { $project } 中 { $total } 个 { $event } Hook 有 { $failed } 个失败
+
+## 提交前审查
+
+commit-review-disabled = 代码审查功能已停用（Agent 功能已移除）
+
+## 命令策略
+
+policy-blocked = 已被命令策略拦截：{ $reason }
+policy-fork-bomb = Fork 炸弹
+policy-rm-root = 递归删除 { $target }
+policy-raw-disk-write = 使用 dd 写入设备
+policy-format-disk = { $binary } 会格式化磁盘
+policy-pipe-to-shell = 下载的脚本被管道传给 { $shell }
+policy-blocked-pattern = 匹配被禁止的模式 { $pattern }
+policy-blocked-binary = { $binary } 已被禁止
+policy-not-allowed = { $binary } 不在允许列表中
+
+## 环境诊断
+
+doctor-claude-cli = Claude CLI
+doctor-git = Git
+doctor-shell = Shell
+doctor-keychain = 系统钥匙串
+doctor-write-permissions = 写入权限
+doctor-mcp-servers = MCP 服务器
+doctor-network = 网络
+
+doctor-unknown-version = 未知版本
+doctor-no-response = 无响应
+doctor-does-not-run = { $path } 无法运行
+
+doctor-claude-cli-found = Claude CLI { $version }
+doctor-claude-cli-install-hint = 使用 `npm install -g @anthropic-ai/claude-code` 安装，或在设置中选择已安装的版本
+doctor-claude-cli-pick-hint = 在设置中选择 Claude CLI 的安装位置
+
+doctor-git-missing = PATH 中没有 git，检查点和 Git 统计不可用
+doctor-git-hint = 从 https://git-scm.com 安装 git，并确保它在 PATH 中
+
+doctor-shell-at = { $shell } 位于 { $path }
+doctor-shell-none = PATH 中既没有 bash 也没有 PowerShell
+doctor-shell-no-bash = PATH 中没有 bash
+doctor-shell-no-bash-found = PATH 中没有 bash；找到 { $found }
+doctor-shell-windows-hint = 安装 Git for Windows，或将 CLAUDE_CODE_GIT_BASH_PATH 设为其 bash.exe
+doctor-shell-hint = 使用包管理器安装 bash
+
+doctor-keychain-ok = 系统钥匙串可以访问
+doctor-keychain-hint = 请解锁钥匙串；在 Linux 上需启动 gnome-keyring 等 Secret Service 服务，否则无法保存凭据
+doctor-keychain-portable = 便携模式下不使用钥匙串，凭据不会写入系统
+
+doctor-write-permissions-ok = ~/.claude、应用数据、日志和临时目录均可写入
+doctor-write-permissions-hint = 请修正这些目录的所有者或权限
+
+doctor-mcp-servers-ok =
+    { $count ->
+        [0] 未配置 stdio 服务器
+       *[other] 全部 { $count } 个 stdio 服务器命令均已找到
+    }
+doctor-mcp-servers-missing = 找不到命令：{ $commands }
+doctor-mcp-servers-hint = 请安装缺少的程序，或在 MCP 设置中修正服务器命令
+
+doctor-network-ok = { $endpoint } 在 { $latency } 毫秒内响应
+doctor-network-api-unreachable = 无法连接 { $endpoint }：{ $error }
+doctor-network-api-hint = 请检查代理设置，并确认防火墙允许访问 API 端点
+doctor-network-offline = 没有网络连接：{ $error }
+doctor-network-offline-hint = 请连接网络，或在设置中配置代理
+
+## 屏幕阅读器的活动摘要
+
+summary-project = { $project }：{ $details }。
+summary-separator = ，
+summary-tools = { $count } 次工具调用（{ $tools }）
+summary-messages = { $count } 条消息
+summary-hooks-passed = { $count } 个 Hook 通过
+summary-hooks-failed = { $count } 个 Hook 失败
+summary-blocked = { $count } 条命令被拦截
+summary-checkpoints = 创建了 { $count } 个检查点
+summary-session-finished = 会话已完成
+summary-session-failed = 会话失败
+summary-mcp-down = MCP 服务器 { $name } 已停止响应。
+summary-mcp-recovered = MCP 服务器 { $name } 已恢复。
+summary-network-down = 与 API 的连接已断开。
+summary-network-up = 与 API 的连接已恢复。
@@ -0,0 +1,302 @@
+//! workbench-cli: headless companion to Claude Workbench
+//!
+//! Talks to the running app through its local control API (enabled in the app's
+//! settings) and works directly on the Claude data directory otherwise. Hook chains
+//! and the list of running sessions need the app; usage, checkpoints and session
+//! history work either way. Results are printed as JSON.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use claude_workbench_lib::commands::usage::UsageRange;
+use claude_workbench_lib::commands::{checkpoints, claude, control_api, usage};
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+
+/// Must match `identifier` in tauri.conf.json, which names the app data directory
+const APP_IDENTIFIER: &str = "claude.workbench.app";
+
+const USAGE: &str = "\
+Usage: workbench-cli <command> [options]
+
+Commands:
+  status                                     Whether the app's control API is reachable
+  hook <event> [--project P] [--session S] [--data JSON]
+                                             Run an event's hook chain (needs the app)
+  usage [--days N]                           Usage statistics
+  usage export --format csv|json --out PATH [--from DATE] [--to DATE]
+                                             Write a per-session/per-project usage report
+  checkpoint create <project> [--message M]  Snapshot a project's working tree
+  checkpoint list <project>                  A project's checkpoints, newest first
+  sessions [--project P]                     Session history, optionally for one project
+  sessions --running                         Running sessions (needs the app)
+
+Set WORKBENCH_DATA_DIR to use a data directory other than the app's default.";
+
+/// Positional arguments and `--name value` options
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse(raw: impl Iterator<Item = String>) -> Self {
+        let mut positional = Vec::new();
+        let mut options = HashMap::new();
+        let mut raw = raw.peekable();
+        while let Some(arg) = raw.next() {
+            match arg.strip_prefix("--") {
+                Some(name) => {
+                    // A flag without a value, such as --running, is stored as empty
+                    let value = match raw.peek() {
+                        Some(next) if !next.starts_with("--") => raw.next().unwrap_or_default(),
+                        _ => String::new(),
+                    };
+                    options.insert(name.to_string(), value);
+                }
+                None => positional.push(arg),
+            }
+        }
+        Self {
+            positional,
+            options,
+        }
+    }
+
+    fn positional(&self, index: usize, name: &str) -> Result<&str, String> {
+        self.positional
+            .get(index)
+            .map(String::as_str)
+            .ok_or_else(|| format!("Missing <{}>\n\n{}", name, USAGE))
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options
+            .get(name)
+            .map(String::as_str)
+            .filter(|value| !value.is_empty())
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.options.contains_key(name)
+    }
+}
+
+/// The app's data directory, which holds agents.db
+fn data_dir() -> Result<PathBuf, String> {
+    if let Some(dir) = std::env::var_os("WORKBENCH_DATA_DIR") {
+        return Ok(PathBuf::from(dir));
+    }
+    dirs::data_dir()
+        .map(|dir| dir.join(APP_IDENTIFIER))
+        .ok_or_else(|| "Could not determine the data directory".to_string())
+}
+
+/// Client for the running app's control API
+struct ApiClient {
+    http: reqwest::Client,
+    base_url: String,
+    token: String,
+}
+
+impl ApiClient {
+    /// Connect to the app if its control API is enabled and answering
+    async fn connect() -> Option<Self> {
+        let db_path = data_dir().ok()?.join("agents.db");
+        let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY).ok()?;
+        let config = control_api::read_config(&conn).ok()??;
+        if !config.enabled {
+            return None;
+        }
+        let client = Self {
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(300))
+                .build()
+                .ok()?,
+            base_url: format!("http://127.0.0.1:{}", config.port),
+            token: config.token,
+        };
+        client.get("/api/health").await.ok()?;
+        Some(client)
+    }
+
+    /// Connect, or explain how to make the app reachable
+    async fn require() -> Result<Self, String> {
+        Self::connect().await.ok_or_else(|| {
+            "Claude Workbench is not reachable: start the app and enable its control API"
+                .to_string()
+        })
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<serde_json::Value, String> {
+        let response = request
+            .bearer_auth(&self.token)
+            .send()
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?;
+        let status = response.status();
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| format!("Invalid response: {}", e))?;
+        if !status.is_success() {
+            let error = body["error"].as_str().unwrap_or("request failed");
+            return Err(format!("{} ({})", error, status));
+        }
+        Ok(body)
+    }
+
+    async fn get(&self, path: &str) -> Result<serde_json::Value, String> {
+        self.send(self.http.get(format!("{}{}", self.base_url, path)))
+            .await
+    }
+
+    async fn post(&self, path: &str, body: serde_json::Value) -> Result<serde_json::Value, String> {
+        self.send(
+            self.http
+                .post(format!("{}{}", self.base_url, path))
+                .json(&body),
+        )
+        .await
+    }
+}
+
+fn print_json(value: &impl Serialize) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    println!("{}", json);
+    Ok(())
+}
+
+async fn status() -> Result<(), String> {
+    match ApiClient::connect().await {
+        Some(client) => {
+            let mut health = client.get("/api/health").await?;
+            health["url"] = client.base_url.into();
+            print_json(&health)
+        }
+        None => print_json(&serde_json::json!({ "status": "unreachable" })),
+    }
+}
+
+async fn hook(args: &Args) -> Result<(), String> {
+    let event = args.positional(1, "event")?;
+    let data = match args.option("data") {
+        Some(data) => {
+            serde_json::from_str(data).map_err(|e| format!("--data is not valid JSON: {}", e))?
+        }
+        None => serde_json::json!({}),
+    };
+    let project_path = match args.option("project") {
+        Some(project) => project.to_string(),
+        None => std::env::current_dir()
+            .map_err(|e| e.to_string())?
+            .to_string_lossy()
+            .to_string(),
+    };
+    let body = serde_json::json!({
+        "project_path": project_path,
+        "session_id": args.option("session"),
+        "data": data,
+    });
+    let client = ApiClient::require().await?;
+    print_json(&client.post(&format!("/api/hooks/{}", event), body).await?)
+}
+
+async fn usage_stats(args: &Args) -> Result<(), String> {
+    let days = args
+        .option("days")
+        .map(|days| {
+            days.parse::<u32>()
+                .map_err(|_| format!("Invalid --days: {}", days))
+        })
+        .transpose()?;
+    // The app answers from its usage index; without it the transcripts are scanned
+    if let Some(client) = ApiClient::connect().await {
+        let path = match days {
+            Some(days) => format!("/api/usage?days={}", days),
+            None => "/api/usage".to_string(),
+        };
+        return print_json(&client.get(&path).await?);
+    }
+    print_json(&usage::get_usage_stats(days)?)
+}
+
+fn usage_export(args: &Args) -> Result<(), String> {
+    let format = args.option("format").unwrap_or("csv");
+    let path = args
+        .option("out")
+        .ok_or_else(|| "usage export needs --out PATH".to_string())?;
+    let range = UsageRange {
+        start_date: args.option("from").map(str::to_string),
+        end_date: args.option("to").map(str::to_string),
+    };
+    let result =
+        usage::export_usage_report(Some(range), format.to_string(), path.to_string(), None)?;
+    print_json(&result)
+}
+
+async fn checkpoint(args: &Args) -> Result<(), String> {
+    let action = args.positional(1, "create|list")?;
+    let project = args.positional(2, "project")?.to_string();
+    match action {
+        "create" => {
+            let message = args.option("message").map(str::to_string);
+            print_json(&checkpoints::create_checkpoint(project, message).await?)
+        }
+        "list" => print_json(&checkpoints::list_checkpoints(project).await?),
+        _ => Err(format!(
+            "Unknown checkpoint action: {}\n\n{}",
+            action, USAGE
+        )),
+    }
+}
+
+async fn sessions(args: &Args) -> Result<(), String> {
+    if args.flag("running") {
+        let client = ApiClient::require().await?;
+        return print_json(&client.get("/api/sessions").await?);
+    }
+
+    let projects = claude::list_projects().await?;
+    let mut sessions = Vec::new();
+    for project in projects {
+        if let Some(path) = args.option("project") {
+            if project.path != path {
+                continue;
+            }
+        }
+        sessions.extend(claude::get_project_sessions(project.id).await?);
+    }
+    print_json(&sessions)
+}
+
+async fn run(args: Args) -> Result<(), String> {
+    let command = match args.positional.first() {
+        Some(command) if !args.flag("help") => command.as_str(),
+        _ => "help",
+    };
+    match command {
+        "status" => status().await,
+        "hook" => hook(&args).await,
+        "usage" if args.positional.get(1).map(String::as_str) == Some("export") => {
+            usage_export(&args)
+        }
+        "usage" => usage_stats(&args).await,
+        "checkpoint" => checkpoint(&args).await,
+        "sessions" => sessions(&args).await,
+        "help" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(format!("Unknown command: {}\n\n{}", command, USAGE)),
+    }
+}
+
+fn main() {
+    let args = Args::parse(std::env::args().skip(1));
+    if let Err(e) = tauri::async_runtime::block_on(run(args)) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
@@ -7,7 +7,8 @@ use std::cmp::Ordering;
 /// Cross-platform support for Windows and macOS
 use std::path::PathBuf;
 use std::process::Command;
-use tauri::Manager;
+
+use crate::process::audit::AuditedCommand;
 
 /// Get user home directory (cross-platform)
 fn get_home_dir() -> Result<String, String> {
@@ -51,7 +52,7 @@ pub fn find_claude_binary(app_handle: &tauri::AppHandle) -> Result<String, Strin
     info!("Searching for system Claude CLI...");
 
     // First check if we have a stored path in the database
-    if let Ok(app_data_dir) = app_handle.path().app_data_dir() {
+    if let Ok(app_data_dir) = crate::paths::app_data_dir(app_handle) {
         let db_path = app_data_dir.join("agents.db");
         if db_path.exists() {
             if let Ok(conn) = rusqlite::Connection::open(&db_path) {
@@ -60,6 +61,7 @@ pub fn find_claude_binary(app_handle: &tauri::AppHandle) -> Result<String, Strin
                     [],
                     |row| row.get::<_, String>(0),
                 ) {
+                    let stored_path = crate::paths::from_stored(&stored_path);
                     info!("Found stored claude path in database: {}", stored_path);
 
                     // Verify the stored path still exists and is accessible
@@ -126,7 +128,7 @@ pub fn find_claude_binary(app_handle: &tauri::AppHandle) -> Result<String, Strin
 
 /// Store Claude CLI path in database for future use
 fn store_claude_path(app_handle: &tauri::AppHandle, path: &str) -> Result<(), String> {
-    if let Ok(app_data_dir) = app_handle.path().app_data_dir() {
+    if let Ok(app_data_dir) = crate::paths::app_data_dir(app_handle) {
         if let Err(e) = std::fs::create_dir_all(&app_data_dir) {
             return Err(format!("Failed to create app data directory: {}", e));
         }
@@ -148,7 +150,7 @@ fn store_claude_path(app_handle: &tauri::AppHandle, path: &str) -> Result<(), St
                 // Store the path
                 if let Err(e) = conn.execute(
                     "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
-                    rusqlite::params!["claude_binary_path", path],
+                    rusqlite::params!["claude_binary_path", crate::paths::to_stored(path)],
                 ) {
                     return Err(format!("Failed to store claude path: {}", e));
                 }
@@ -178,7 +180,7 @@ fn test_claude_binary(path: &str) -> bool {
         cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
     }
 
-    match cmd.output() {
+    match cmd.audited_output("claude") {
         Ok(output) => {
             let success = output.status.success();
             debug!("Claude binary test result: success={}", success);
@@ -347,7 +349,7 @@ fn try_where_command() -> Option<ClaudeInstallation> {
         cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
     }
 
-    match cmd.output() {
+    match cmd.audited_output("system") {
         Ok(output) if output.status.success() => {
             let output_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
 
@@ -399,7 +401,7 @@ fn try_which_command() -> Option<ClaudeInstallation> {
         cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
     }
 
-    match cmd.output() {
+    match cmd.audited_output("system") {
         Ok(output) if output.status.success() => {
             let output_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
 
@@ -593,7 +595,7 @@ fn find_standard_installations() -> Vec<ClaudeInstallation> {
             command.creation_flags(0x08000000); // CREATE_NO_WINDOW
         }
 
-        if let Ok(output) = command.output() {
+        if let Ok(output) = command.audited_output("claude") {
             if output.status.success() {
                 debug!("{} is available in PATH", cmd);
                 let version = extract_version_from_output(&output.stdout);
@@ -793,7 +795,7 @@ fn get_claude_version(path: &str) -> Result<Option<String>, String> {
         cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
     }
 
-    match cmd.output() {
+    match cmd.audited_output("claude") {
         Ok(output) => {
             if output.status.success() {
                 let version = extract_version_from_output(&output.stdout);
@@ -961,5 +963,10 @@ pub fn create_command_with_env(program: &str) -> Command {
         }
     }
 
+    // Route network access through the configured proxy, if any
+    cmd.envs(crate::commands::proxy::proxy_env_vars());
+    // Point the CLI at Claude's data under the workbench data root, if one is set
+    cmd.envs(crate::paths::claude_env());
+
     cmd
 }
@@ -0,0 +1,728 @@
+/// Daily and weekly activity reports
+///
+/// `generate_activity_report` sums up a period across all projects: sessions,
+/// tokens and cost from the usage index, lines changed from git, and how the
+/// hooks fared, rendered as Markdown or standalone HTML.
+///
+/// A schedule, off by default, writes the weekly report on a chosen day and hour
+/// (Friday at 17:00 unless changed), emits `activity-report-generated` and fires
+/// the `OnActivityReport` hooks of each project in it, so a hook can mail or
+/// upload the file. A report missed while the app was closed is written at the
+/// next start, as long as that week's day has not passed again.
+use chrono::{Datelike, Local, NaiveDate, Timelike, Weekday};
+use log::{info, warn};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use super::enhanced_hooks::{trigger_hook_event, HookContext};
+use super::git_stats::GitDiffStats;
+use super::project_insights;
+use super::session_export::escape_html;
+use super::storage::AgentDb;
+use super::usage::{self, UsageRange};
+use crate::events::{self, AppEvent};
+
+/// `app_settings` key holding the JSON schedule
+const SCHEDULE_KEY: &str = "activity_report_schedule";
+
+/// `app_settings` key holding the day of the last scheduled report
+const LAST_RUN_KEY: &str = "activity_report_last_run";
+
+/// How often the schedule is checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Hook commands listed under "Failing hooks"
+const TOP_FAILING_HOOKS: usize = 5;
+
+const HTML_STYLE: &str = "\
+    body{font-family:-apple-system,'Segoe UI',sans-serif;max-width:960px;margin:2em auto;\
+    padding:0 1em;color:#1f2328;line-height:1.5}\
+    table{border-collapse:collapse;margin:.5em 0 1.5em}\
+    th,td{border:1px solid #d0d7de;padding:.3em .7em;text-align:left}\
+    th{background:#f6f8fa}td.num{text-align:right;font-variant-numeric:tabular-nums}";
+
+/// Period a report covers, ending today unless custom
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportRange {
+    Day,
+    /// The last seven days
+    Week,
+    /// `YYYY-MM-DD` days, both included
+    Custom {
+        start_date: String,
+        end_date: String,
+    },
+}
+
+impl ReportRange {
+    fn days(&self, today: NaiveDate) -> Result<(NaiveDate, NaiveDate), String> {
+        match self {
+            Self::Day => Ok((today, today)),
+            Self::Week => Ok((today - chrono::Duration::days(6), today)),
+            Self::Custom {
+                start_date,
+                end_date,
+            } => {
+                let (start, end) = UsageRange {
+                    start_date: Some(start_date.clone()),
+                    end_date: Some(end_date.clone()),
+                }
+                .bounds()?;
+                match (start, end) {
+                    (Some(start), Some(end)) if start <= end => Ok((start, end)),
+                    _ => Err(format!(
+                        "Invalid report range: {} to {}",
+                        start_date, end_date
+                    )),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    #[default]
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+        }
+    }
+}
+
+/// Activity of one project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectActivity {
+    pub project_path: String,
+    pub project_name: String,
+    pub sessions: usize,
+    pub messages: u64,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+    /// None when the project is not a git repository
+    pub churn: Option<GitDiffStats>,
+    pub hook_runs: u64,
+    pub hook_failures: u64,
+}
+
+/// Hook runs of one event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookOutcome {
+    pub event: String,
+    pub runs: u64,
+    pub failures: u64,
+    pub average_duration_ms: u64,
+}
+
+/// A hook command that failed within the period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailingHook {
+    pub event: String,
+    pub command: String,
+    pub failures: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityReport {
+    pub start_date: String,
+    pub end_date: String,
+    pub generated_at: String,
+    pub sessions: usize,
+    pub messages: u64,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+    pub hook_runs: u64,
+    pub hook_failures: u64,
+    /// Most expensive first
+    pub projects: Vec<ProjectActivity>,
+    /// Most runs first
+    pub hooks: Vec<HookOutcome>,
+    /// Most failures first
+    pub failing_hooks: Vec<FailingHook>,
+}
+
+/// Result of `generate_activity_report`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderedReport {
+    pub report: ActivityReport,
+    pub format: ReportFormat,
+    pub content: String,
+    /// File the report was written to
+    pub path: Option<String>,
+}
+
+/// When and how the weekly report is written
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportSchedule {
+    pub enabled: bool,
+    pub weekday: Weekday,
+    /// Local hour from which the report is written on `weekday`
+    pub hour: u32,
+    pub range: ReportRange,
+    pub format: ReportFormat,
+    /// Defaults to `reports` in the app data directory
+    pub output_dir: Option<String>,
+}
+
+impl Default for ReportSchedule {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            weekday: Weekday::Fri,
+            hour: 17,
+            range: ReportRange::Week,
+            format: ReportFormat::Markdown,
+            output_dir: None,
+        }
+    }
+}
+
+fn load_setting(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT value FROM app_settings WHERE key = ?1",
+        [key],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+fn load_schedule(conn: &Connection) -> Result<ReportSchedule, String> {
+    match load_setting(conn, SCHEDULE_KEY)? {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid activity report schedule: {}", e)),
+        None => Ok(ReportSchedule::default()),
+    }
+}
+
+#[derive(Default)]
+struct HookTotals {
+    runs: u64,
+    failures: u64,
+    duration_ms: u64,
+}
+
+/// Hook runs within a period
+#[derive(Default)]
+struct HookStats {
+    by_project: BTreeMap<String, HookTotals>,
+    by_event: BTreeMap<String, HookTotals>,
+    /// Failures per event and command
+    failing: BTreeMap<(String, String), u64>,
+}
+
+fn hook_stats(db: &AgentDb, start: NaiveDate, end: NaiveDate) -> Result<HookStats, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT timestamp, project_path, event, command, success, duration_ms FROM hook_runs",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, bool>(4)?,
+                row.get::<_, i64>(5)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut stats = HookStats::default();
+    for row in rows {
+        let (timestamp, project_path, event, command, success, duration_ms) =
+            row.map_err(|e| e.to_string())?;
+        if !usage::in_date_range(&timestamp, Some(start), Some(end)) {
+            continue;
+        }
+        for totals in [
+            stats.by_project.entry(project_path).or_default(),
+            stats.by_event.entry(event.clone()).or_default(),
+        ] {
+            totals.runs += 1;
+            totals.failures += u64::from(!success);
+            totals.duration_ms += duration_ms.max(0) as u64;
+        }
+        if !success {
+            *stats.failing.entry((event, command)).or_default() += 1;
+        }
+    }
+    Ok(stats)
+}
+
+fn project_name(project_path: &str) -> String {
+    project_path
+        .trim_end_matches(['/', '\\'])
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(project_path)
+        .to_string()
+}
+
+async fn build_report(app: &AppHandle, range: &ReportRange) -> Result<ActivityReport, String> {
+    let (start, end) = range.days(Local::now().date_naive())?;
+    let usage_range = UsageRange {
+        start_date: Some(start.to_string()),
+        end_date: Some(end.to_string()),
+    };
+    let (sessions, usage_projects) =
+        tauri::async_runtime::spawn_blocking(move || usage::usage_rows(&usage_range))
+            .await
+            .map_err(|e| format!("Usage scan failed: {}", e))??;
+    let db = app.state::<AgentDb>();
+    let hook_stats = hook_stats(&db, start, end)?;
+
+    let mut projects: BTreeMap<String, ProjectActivity> = BTreeMap::new();
+    for row in &usage_projects {
+        projects.insert(
+            row.project_path.clone(),
+            ProjectActivity {
+                project_path: row.project_path.clone(),
+                project_name: row.project_name.clone(),
+                sessions: sessions
+                    .iter()
+                    .filter(|s| s.project_path == row.project_path)
+                    .count(),
+                messages: row.message_count,
+                total_tokens: row.total_tokens,
+                total_cost: row.total_cost,
+                churn: None,
+                hook_runs: 0,
+                hook_failures: 0,
+            },
+        );
+    }
+    for (project_path, totals) in &hook_stats.by_project {
+        let project = projects
+            .entry(project_path.clone())
+            .or_insert_with(|| ProjectActivity {
+                project_path: project_path.clone(),
+                project_name: project_name(project_path),
+                sessions: 0,
+                messages: 0,
+                total_tokens: 0,
+                total_cost: 0.0,
+                churn: None,
+                hook_runs: 0,
+                hook_failures: 0,
+            });
+        project.hook_runs = totals.runs;
+        project.hook_failures = totals.failures;
+    }
+    for project in projects.values_mut() {
+        project.churn =
+            project_insights::lines_changed(&project.project_path, Some(start), Some(end)).await;
+    }
+
+    let mut projects: Vec<ProjectActivity> = projects.into_values().collect();
+    projects.sort_by(|a, b| {
+        b.total_cost
+            .total_cmp(&a.total_cost)
+            .then_with(|| b.hook_runs.cmp(&a.hook_runs))
+    });
+    let mut hooks: Vec<HookOutcome> = hook_stats
+        .by_event
+        .into_iter()
+        .map(|(event, totals)| HookOutcome {
+            event,
+            runs: totals.runs,
+            failures: totals.failures,
+            average_duration_ms: totals.duration_ms / totals.runs.max(1),
+        })
+        .collect();
+    hooks.sort_by_key(|h| std::cmp::Reverse(h.runs));
+    let mut failing_hooks: Vec<FailingHook> = hook_stats
+        .failing
+        .into_iter()
+        .map(|((event, command), failures)| FailingHook {
+            event,
+            command,
+            failures,
+        })
+        .collect();
+    failing_hooks.sort_by_key(|h| std::cmp::Reverse(h.failures));
+    failing_hooks.truncate(TOP_FAILING_HOOKS);
+
+    let churn = projects.iter().filter_map(|p| p.churn.as_ref());
+    let (lines_added, lines_removed) = churn.fold((0, 0), |(added, removed), c| {
+        (added + c.lines_added, removed + c.lines_removed)
+    });
+    Ok(ActivityReport {
+        start_date: start.to_string(),
+        end_date: end.to_string(),
+        generated_at: Local::now().to_rfc3339(),
+        sessions: sessions.len(),
+        messages: usage_projects.iter().map(|p| p.message_count).sum(),
+        total_tokens: usage_projects.iter().map(|p| p.total_tokens).sum(),
+        total_cost: usage_projects.iter().map(|p| p.total_cost).sum(),
+        lines_added,
+        lines_removed,
+        hook_runs: hooks.iter().map(|h| h.runs).sum(),
+        hook_failures: hooks.iter().map(|h| h.failures).sum(),
+        projects,
+        hooks,
+        failing_hooks,
+    })
+}
+
+/// A titled table shared by both formats
+struct Table {
+    title: &'static str,
+    headers: &'static [&'static str],
+    /// Columns after the first are numbers, aligned right
+    numeric: bool,
+    rows: Vec<Vec<String>>,
+}
+
+fn title(report: &ActivityReport) -> String {
+    if report.start_date == report.end_date {
+        format!("Activity report for {}", report.start_date)
+    } else {
+        format!(
+            "Activity report {} to {}",
+            report.start_date, report.end_date
+        )
+    }
+}
+
+fn summary_lines(report: &ActivityReport) -> Vec<String> {
+    vec![
+        format!(
+            "{} sessions, {} messages across {} projects",
+            report.sessions,
+            report.messages,
+            report.projects.len()
+        ),
+        format!("{} tokens, ${:.2}", report.total_tokens, report.total_cost),
+        format!(
+            "+{} / -{} lines committed",
+            report.lines_added, report.lines_removed
+        ),
+        format!(
+            "{} hook runs, {} failed",
+            report.hook_runs, report.hook_failures
+        ),
+    ]
+}
+
+fn tables(report: &ActivityReport) -> Vec<Table> {
+    let projects = report
+        .projects
+        .iter()
+        .map(|p| {
+            vec![
+                p.project_name.clone(),
+                p.sessions.to_string(),
+                p.messages.to_string(),
+                p.total_tokens.to_string(),
+                format!("${:.2}", p.total_cost),
+                p.churn.as_ref().map_or("-".to_string(), |c| {
+                    format!("+{} / -{}", c.lines_added, c.lines_removed)
+                }),
+                format!("{} ({} failed)", p.hook_runs, p.hook_failures),
+            ]
+        })
+        .collect();
+    let hooks = report
+        .hooks
+        .iter()
+        .map(|h| {
+            vec![
+                h.event.clone(),
+                h.runs.to_string(),
+                h.failures.to_string(),
+                format!("{} ms", h.average_duration_ms),
+            ]
+        })
+        .collect();
+    let failing = report
+        .failing_hooks
+        .iter()
+        .map(|h| vec![h.command.clone(), h.event.clone(), h.failures.to_string()])
+        .collect();
+    vec![
+        Table {
+            title: "Projects",
+            headers: &[
+                "Project",
+                "Sessions",
+                "Messages",
+                "Tokens",
+                "Cost",
+                "Lines",
+                "Hook runs",
+            ],
+            numeric: true,
+            rows: projects,
+        },
+        Table {
+            title: "Hooks",
+            headers: &["Event", "Runs", "Failures", "Average duration"],
+            numeric: true,
+            rows: hooks,
+        },
+        Table {
+            title: "Failing hooks",
+            headers: &["Command", "Event", "Failures"],
+            numeric: false,
+            rows: failing,
+        },
+    ]
+}
+
+fn render_markdown(report: &ActivityReport) -> String {
+    let cell = |text: &str| text.replace('|', "\\|").replace('\n', " ");
+    let mut out = format!("# {}\n\n", title(report));
+    for line in summary_lines(report) {
+        out.push_str(&format!("- {}\n", line));
+    }
+    for table in tables(report).into_iter().filter(|t| !t.rows.is_empty()) {
+        out.push_str(&format!("\n## {}\n\n", table.title));
+        out.push_str(&format!("| {} |\n", table.headers.join(" | ")));
+        let align: Vec<&str> = (0..table.headers.len())
+            .map(|i| {
+                if table.numeric && i > 0 {
+                    "---:"
+                } else {
+                    "---"
+                }
+            })
+            .collect();
+        out.push_str(&format!("| {} |\n", align.join(" | ")));
+        for row in &table.rows {
+            let cells: Vec<String> = row.iter().map(|c| cell(c)).collect();
+            out.push_str(&format!("| {} |\n", cells.join(" | ")));
+        }
+    }
+    out.push_str(&format!("\n_Generated {}_\n", report.generated_at));
+    out
+}
+
+fn render_html(report: &ActivityReport) -> String {
+    let title = escape_html(&title(report));
+    let mut out = String::from("<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\">\n");
+    out.push_str(&format!("<title>{}</title>\n", title));
+    out.push_str(&format!("<style>{}</style></head><body>\n", HTML_STYLE));
+    out.push_str(&format!("<h1>{}</h1>\n<ul>\n", title));
+    for line in summary_lines(report) {
+        out.push_str(&format!("<li>{}</li>\n", escape_html(&line)));
+    }
+    out.push_str("</ul>\n");
+    for table in tables(report).into_iter().filter(|t| !t.rows.is_empty()) {
+        out.push_str(&format!("<h2>{}</h2>\n<table>\n<tr>", table.title));
+        for header in table.headers {
+            out.push_str(&format!("<th>{}</th>", header));
+        }
+        out.push_str("</tr>\n");
+        for row in &table.rows {
+            out.push_str("<tr>");
+            for (i, cell) in row.iter().enumerate() {
+                let class = if table.numeric && i > 0 {
+                    " class=\"num\""
+                } else {
+                    ""
+                };
+                out.push_str(&format!("<td{}>{}</td>", class, escape_html(cell)));
+            }
+            out.push_str("</tr>\n");
+        }
+        out.push_str("</table>\n");
+    }
+    out.push_str(&format!(
+        "<p><em>Generated {}</em></p>\n</body></html>\n",
+        escape_html(&report.generated_at)
+    ));
+    out
+}
+
+async fn generate(
+    app: &AppHandle,
+    range: &ReportRange,
+    format: ReportFormat,
+    path: Option<PathBuf>,
+) -> Result<RenderedReport, String> {
+    let report = build_report(app, range).await?;
+    let content = match format {
+        ReportFormat::Markdown => render_markdown(&report),
+        ReportFormat::Html => render_html(&report),
+    };
+    if let Some(path) = &path {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create report directory: {}", e))?;
+        }
+        fs::write(path, &content).map_err(|e| format!("Failed to write report: {}", e))?;
+    }
+    Ok(RenderedReport {
+        report,
+        format,
+        content,
+        path: path.map(|p| p.to_string_lossy().to_string()),
+    })
+}
+
+/// Day of the latest scheduled report time at or before `now`
+fn last_occurrence(schedule: &ReportSchedule, now: chrono::DateTime<Local>) -> NaiveDate {
+    let today = now.date_naive();
+    let days_back =
+        (7 + today.weekday().num_days_from_monday() - schedule.weekday.num_days_from_monday()) % 7;
+    let day = today - chrono::Duration::days(days_back as i64);
+    if days_back == 0 && now.hour() < schedule.hour {
+        day - chrono::Duration::days(7)
+    } else {
+        day
+    }
+}
+
+/// Write the scheduled report if one is due
+async fn run_schedule(app: &AppHandle) -> Result<(), String> {
+    let (schedule, last_run) = {
+        let db = app.state::<AgentDb>();
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        (load_schedule(&conn)?, load_setting(&conn, LAST_RUN_KEY)?)
+    };
+    if !schedule.enabled {
+        return Ok(());
+    }
+    let now = Local::now();
+    let due = last_occurrence(&schedule, now);
+    let last_run = last_run.and_then(|day| day.parse::<NaiveDate>().ok());
+    // Without a previous run only today's report is due, so enabling the
+    // schedule doesn't write last week's right away
+    let missed = match last_run {
+        Some(last_run) => last_run < due,
+        None => due == now.date_naive(),
+    };
+    if !missed {
+        return Ok(());
+    }
+
+    let dir = match &schedule.output_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => crate::paths::app_data_dir(app)
+            .map_err(|e| e.to_string())?
+            .join("reports"),
+    };
+    let path = dir.join(format!(
+        "activity-report-{}.{}",
+        now.date_naive(),
+        schedule.format.extension()
+    ));
+    let rendered = generate(app, &schedule.range, schedule.format, Some(path)).await?;
+    {
+        let db = app.state::<AgentDb>();
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+            params![LAST_RUN_KEY, now.date_naive().to_string()],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    info!(
+        "Wrote scheduled activity report to {}",
+        rendered.path.as_deref().unwrap_or_default()
+    );
+
+    events::emit(app, AppEvent::ActivityReportGenerated(rendered.clone()));
+    for project in &rendered.report.projects {
+        let context = HookContext {
+            event: "OnActivityReport".to_string(),
+            session_id: String::new(),
+            project_path: project.project_path.clone(),
+            data: serde_json::json!({
+                "path": rendered.path,
+                "format": rendered.format,
+                "start_date": rendered.report.start_date,
+                "end_date": rendered.report.end_date,
+                "project": project,
+            }),
+        };
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) =
+                trigger_hook_event(app, "OnActivityReport".to_string(), context, None).await
+            {
+                warn!("OnActivityReport hooks failed: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Write scheduled reports in the background
+pub fn start_report_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = run_schedule(&app).await {
+                warn!("Scheduled activity report failed: {}", e);
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+// ============ Tauri Commands ============
+
+/// Render the activity of a period as Markdown or HTML, and write it to `path`
+/// if given
+#[tauri::command]
+pub async fn generate_activity_report(
+    app: AppHandle,
+    range: ReportRange,
+    format: Option<ReportFormat>,
+    path: Option<String>,
+) -> Result<RenderedReport, String> {
+    generate(
+        &app,
+        &range,
+        format.unwrap_or_default(),
+        path.map(PathBuf::from),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn get_report_schedule(db: State<'_, AgentDb>) -> Result<ReportSchedule, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_schedule(&conn)
+}
+
+/// Replace the report schedule; it is checked every few minutes
+#[tauri::command]
+pub async fn update_report_schedule(
+    db: State<'_, AgentDb>,
+    schedule: ReportSchedule,
+) -> Result<(), String> {
+    if schedule.hour > 23 {
+        return Err(format!("Invalid hour: {}", schedule.hour));
+    }
+    schedule.range.days(Local::now().date_naive())?;
+    let json = serde_json::to_string(&schedule).map_err(|e| e.to_string())?;
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+        params![SCHEDULE_KEY, json],
+    )
+    .map_err(|e| format!("Failed to save activity report schedule: {}", e))?;
+    info!("Activity report schedule updated");
+    Ok(())
+}
@@ -0,0 +1,402 @@
+use log::warn;
+/// Condensed activity summaries for screen readers
+///
+/// Sessions can call dozens of tools and run whole hook chains within seconds,
+/// far more than a screen reader can announce. This channel counts what
+/// happened per project (tool calls by tool, assistant messages, hook results,
+/// blocked commands, checkpoints, finished sessions) plus app-wide changes
+/// (MCP servers, connectivity), and every `interval_secs` emits at most one
+/// `activity-summary` with a short localized text for an ARIA live region.
+/// Summaries mentioning failures are marked urgent so they can be announced
+/// assertively.
+///
+/// Off by default; the frontend enables it when the user asks for it.
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::Notify;
+
+use super::network::Connectivity;
+use super::storage::AgentDb;
+use crate::events::{self, ActivitySummary, AppEvent};
+use crate::i18n::{self, FluentValue};
+
+/// `app_settings` key holding the JSON config
+const SETTINGS_KEY: &str = "activity_summaries";
+
+/// Shortest interval allowed, so summaries never come in bursts themselves
+const MIN_INTERVAL_SECS: u64 = 3;
+
+/// Tools named individually in a summary; the rest are only counted
+const MAX_NAMED_TOOLS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SummaryConfig {
+    pub enabled: bool,
+    /// Seconds between summaries (default: 10, at least 3)
+    pub interval_secs: u64,
+}
+
+impl Default for SummaryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 10,
+        }
+    }
+}
+
+/// What happened in one project since the last summary
+#[derive(Debug, Default)]
+struct Activity {
+    sessions: BTreeSet<String>,
+    tools: BTreeMap<String, u32>,
+    messages: u32,
+    hooks_passed: u32,
+    hooks_failed: u32,
+    blocked: u32,
+    checkpoints: u32,
+    /// Sessions that finished, and whether they succeeded
+    finished: Vec<bool>,
+}
+
+impl Activity {
+    fn urgent(&self) -> bool {
+        self.hooks_failed > 0 || self.blocked > 0 || self.finished.contains(&false)
+    }
+}
+
+#[derive(Default)]
+struct Pending {
+    /// Keyed by project path
+    projects: BTreeMap<String, Activity>,
+    /// App-wide changes, already worded
+    notes: Vec<String>,
+    /// Whether a note reports a failure
+    urgent: bool,
+}
+
+#[derive(Default)]
+pub struct SummaryState {
+    config: Mutex<Option<SummaryConfig>>,
+    pending: Mutex<Pending>,
+    /// Whether the last connectivity change was to offline, so a return to
+    /// online is worth announcing
+    network_down: AtomicBool,
+    /// Wakes the summary loop after a config change
+    wake: Notify,
+}
+
+fn load_config(conn: &Connection) -> Result<SummaryConfig, String> {
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT value FROM app_settings WHERE key = ?1",
+            [SETTINGS_KEY],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    match stored {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid activity summary settings: {}", e)),
+        None => Ok(SummaryConfig::default()),
+    }
+}
+
+fn current_config(app: &AppHandle) -> SummaryConfig {
+    let state = match app.try_state::<SummaryState>() {
+        Some(state) => state,
+        None => return SummaryConfig::default(),
+    };
+    if let Some(config) = state.config.lock().ok().and_then(|c| c.clone()) {
+        return config;
+    }
+    let loaded = match app.try_state::<AgentDb>() {
+        Some(db) => {
+            db.0.lock()
+                .map_err(|e| e.to_string())
+                .and_then(|conn| load_config(&conn))
+        }
+        None => Ok(SummaryConfig::default()),
+    };
+    let config = loaded.unwrap_or_else(|e| {
+        warn!("{}", e);
+        SummaryConfig::default()
+    });
+    if let Ok(mut cached) = state.config.lock() {
+        *cached = Some(config.clone());
+    }
+    config
+}
+
+/// Add to the pending summary if the channel is on
+fn record(app: &AppHandle, update: impl FnOnce(&mut Pending)) {
+    if !current_config(app).enabled {
+        return;
+    }
+    if let Some(state) = app.try_state::<SummaryState>() {
+        if let Ok(mut pending) = state.pending.lock() {
+            update(&mut pending);
+        }
+    }
+}
+
+/// Count the tool calls, messages and results of a session's stream record
+pub fn observe_message(app: &AppHandle, session_id: &str, project_path: &str, msg: &Value) {
+    let record_type = msg["type"].as_str().unwrap_or_default();
+    if !matches!(record_type, "assistant" | "result") {
+        return;
+    }
+    record(app, |pending| {
+        let activity = pending
+            .projects
+            .entry(project_path.to_string())
+            .or_default();
+        activity.sessions.insert(session_id.to_string());
+        if record_type == "result" {
+            let success = !msg["is_error"].as_bool().unwrap_or(false);
+            activity.finished.push(success);
+            return;
+        }
+        let blocks = msg["message"]["content"].as_array().cloned();
+        let mut has_text = false;
+        for block in blocks.unwrap_or_default() {
+            match block["type"].as_str() {
+                Some("tool_use") => {
+                    let name = block["name"].as_str().unwrap_or("tool").to_string();
+                    *activity.tools.entry(name).or_default() += 1;
+                }
+                Some("text") => has_text = true,
+                _ => {}
+            }
+        }
+        if has_text {
+            activity.messages += 1;
+        }
+    });
+}
+
+/// Event bus subscriber collecting hook, policy and health events
+pub fn on_app_event(app: &AppHandle, event: &AppEvent) {
+    match event {
+        AppEvent::HookChainComplete {
+            session_id,
+            project_path,
+            results,
+            ..
+        } => record(app, |pending| {
+            let activity = pending.projects.entry(project_path.clone()).or_default();
+            activity.sessions.insert(session_id.clone());
+            for result in results {
+                if result.success {
+                    activity.hooks_passed += 1;
+                } else {
+                    activity.hooks_failed += 1;
+                }
+            }
+        }),
+        AppEvent::CommandBlocked(blocked) => record(app, |pending| {
+            let activity = pending
+                .projects
+                .entry(blocked.project_path.clone())
+                .or_default();
+            activity.blocked += 1;
+        }),
+        AppEvent::CheckpointCreated(created) => record(app, |pending| {
+            let activity = pending
+                .projects
+                .entry(created.project_path.clone())
+                .or_default();
+            activity.checkpoints += 1;
+        }),
+        AppEvent::McpServerDown(health) => record(app, |pending| {
+            let name = health.name.as_str().into();
+            pending
+                .notes
+                .push(i18n::t_args("summary-mcp-down", &[("name", name)]));
+            pending.urgent = true;
+        }),
+        AppEvent::McpServerRecovered(health) => record(app, |pending| {
+            let name = health.name.as_str().into();
+            pending
+                .notes
+                .push(i18n::t_args("summary-mcp-recovered", &[("name", name)]));
+        }),
+        AppEvent::NetworkStatus(status) => {
+            let down = matches!(
+                status.connectivity,
+                Connectivity::Offline | Connectivity::ApiUnreachable
+            );
+            let was_down = match app.try_state::<SummaryState>() {
+                Some(state) => state.network_down.swap(down, Ordering::Relaxed),
+                None => return,
+            };
+            if down && !was_down {
+                record(app, |pending| {
+                    pending.notes.push(i18n::t("summary-network-down"));
+                    pending.urgent = true;
+                });
+            } else if was_down && status.connectivity == Connectivity::Online {
+                record(app, |pending| {
+                    pending.notes.push(i18n::t("summary-network-up"))
+                });
+            }
+        }
+        _ => {}
+    }
+}
+
+fn count(id: &str, count: u32) -> String {
+    i18n::t_args(id, &[("count", count.into())])
+}
+
+/// One sentence for a project's activity
+fn describe(project_path: &str, activity: &Activity) -> Option<String> {
+    let mut details = Vec::new();
+    let tool_calls: u32 = activity.tools.values().sum();
+    if tool_calls > 0 {
+        let mut tools: Vec<(&String, &u32)> = activity.tools.iter().collect();
+        tools.sort_by(|a, b| b.1.cmp(a.1));
+        let named: Vec<String> = tools
+            .iter()
+            .take(MAX_NAMED_TOOLS)
+            .map(|(name, n)| format!("{} {}", name, n))
+            .collect();
+        details.push(i18n::t_args(
+            "summary-tools",
+            &[
+                ("count", tool_calls.into()),
+                ("tools", named.join(", ").into()),
+            ],
+        ));
+    }
+    let counts = [
+        ("summary-messages", activity.messages),
+        ("summary-hooks-passed", activity.hooks_passed),
+        ("summary-hooks-failed", activity.hooks_failed),
+        ("summary-blocked", activity.blocked),
+        ("summary-checkpoints", activity.checkpoints),
+    ];
+    for (id, n) in counts {
+        if n > 0 {
+            details.push(count(id, n));
+        }
+    }
+    for success in &activity.finished {
+        details.push(i18n::t(if *success {
+            "summary-session-finished"
+        } else {
+            "summary-session-failed"
+        }));
+    }
+    if details.is_empty() {
+        return None;
+    }
+
+    let project = std::path::Path::new(project_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| project_path.to_string());
+    let args: [(&str, FluentValue); 2] = [
+        ("project", project.into()),
+        (
+            "details",
+            details.join(&i18n::t("summary-separator")).into(),
+        ),
+    ];
+    Some(i18n::t_args("summary-project", &args))
+}
+
+/// Emit what piled up since the last summary, if anything did
+fn flush(app: &AppHandle) {
+    let pending = match app.try_state::<SummaryState>() {
+        Some(state) => match state.pending.lock() {
+            Ok(mut pending) => std::mem::take(&mut *pending),
+            Err(_) => return,
+        },
+        None => return,
+    };
+
+    let mut sentences = pending.notes;
+    let mut session_ids = BTreeSet::new();
+    let mut urgent = pending.urgent;
+    for (project_path, activity) in &pending.projects {
+        if let Some(sentence) = describe(project_path, activity) {
+            sentences.push(sentence);
+            session_ids.extend(activity.sessions.iter().cloned());
+            urgent |= activity.urgent();
+        }
+    }
+    if sentences.is_empty() {
+        return;
+    }
+    events::emit(
+        app,
+        AppEvent::ActivitySummary(ActivitySummary {
+            text: sentences.join(" "),
+            urgent,
+            session_ids: session_ids.into_iter().collect(),
+            at: chrono::Utc::now().to_rfc3339(),
+        }),
+    );
+}
+
+/// Emit summaries in the background, at most one per interval
+pub fn start_summary_channel(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let config = current_config(&app);
+            if config.enabled {
+                flush(&app);
+            } else if let Some(state) = app.try_state::<SummaryState>() {
+                // Nothing recorded while off should surface once switched on
+                if let Ok(mut pending) = state.pending.lock() {
+                    *pending = Pending::default();
+                }
+            }
+            let state = app.state::<SummaryState>();
+            let wait = Duration::from_secs(config.interval_secs.max(MIN_INTERVAL_SECS));
+            let _ = tokio::time::timeout(wait, state.wake.notified()).await;
+        }
+    });
+}
+
+// ============ Tauri Commands ============
+
+#[tauri::command]
+pub async fn get_activity_summary_config(app: AppHandle) -> Result<SummaryConfig, String> {
+    Ok(current_config(&app))
+}
+
+/// Update the summary settings; a new interval applies from the next summary
+#[tauri::command]
+pub async fn update_activity_summary_config(
+    db: State<'_, AgentDb>,
+    state: State<'_, SummaryState>,
+    config: SummaryConfig,
+) -> Result<(), String> {
+    if config.interval_secs < MIN_INTERVAL_SECS {
+        return Err(format!(
+            "The summary interval must be at least {} seconds",
+            MIN_INTERVAL_SECS
+        ));
+    }
+    let json = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+    {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+            params![SETTINGS_KEY, json],
+        )
+        .map_err(|e| format!("Failed to save activity summary settings: {}", e))?;
+    }
+    *state.config.lock().map_err(|e| e.to_string())? = Some(config);
+    state.wake.notify_one();
+    Ok(())
+}
@@ -0,0 +1,452 @@
+/// Pasted and dropped attachments
+///
+/// Images and files the user pastes or drops into the prompt are stored under
+/// `<app data>/attachments/<session id>/`, named after a hash of their content, so
+/// pasting the same screenshot twice keeps one copy and a file's path never
+/// changes. Images in formats the Claude CLI does not read (anything but PNG,
+/// JPEG, GIF and WebP) are converted to PNG, and every image gets a small PNG
+/// thumbnail under `thumbs/`. Each directory keeps an `index.json` with the
+/// original names and sizes.
+///
+/// Sessions that do not have an id yet can use any draft id; the frontend passes
+/// the returned `path` to Claude like any other image path.
+use base64::{engine::general_purpose, Engine};
+use image::{ImageFormat, RgbaImage};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::{WorkbenchError, WorkbenchResult};
+use crate::paths;
+
+/// Largest single attachment
+const MAX_ATTACHMENT_BYTES: usize = 20 * 1024 * 1024;
+/// Largest total per session
+const MAX_SESSION_BYTES: u64 = 256 * 1024 * 1024;
+/// Longest edge of a thumbnail
+const THUMBNAIL_SIZE: u32 = 256;
+
+/// A stored attachment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    /// Start of the content hash; also the file name
+    pub id: String,
+    pub session_id: String,
+    /// Name the attachment had when it was pasted or dropped
+    pub file_name: String,
+    pub mime_type: String,
+    pub size: u64,
+    /// Absolute path to pass to Claude
+    pub path: String,
+    pub thumbnail_path: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub is_image: bool,
+    pub created_at: String,
+}
+
+impl Attachment {
+    fn map_paths(&mut self, map: fn(&str) -> String) {
+        self.path = map(&self.path);
+        self.thumbnail_path = self.thumbnail_path.as_deref().map(map);
+    }
+}
+
+/// Serializes changes to the attachment indexes
+#[derive(Default)]
+pub struct AttachmentState(Mutex<()>);
+
+fn attachments_root(app: &AppHandle) -> WorkbenchResult<PathBuf> {
+    let dir = paths::app_data_dir(app).map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    Ok(dir.join("attachments"))
+}
+
+/// Session ids become directory names, so only plain ids are accepted
+fn session_dir(app: &AppHandle, session_id: &str) -> WorkbenchResult<PathBuf> {
+    let valid = !session_id.is_empty()
+        && session_id.len() <= 128
+        && session_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(WorkbenchError::InvalidInput(format!(
+            "Invalid session id '{}'",
+            session_id
+        )));
+    }
+    Ok(attachments_root(app)?.join(session_id))
+}
+
+fn load_index(dir: &Path) -> WorkbenchResult<Vec<Attachment>> {
+    let path = dir.join("index.json");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path)
+        .map_err(|e| WorkbenchError::io("Failed to read attachment index", e))?;
+    let mut index: Vec<Attachment> = serde_json::from_str(&content)
+        .map_err(|e| WorkbenchError::json("Invalid attachment index", e))?;
+    for attachment in &mut index {
+        attachment.map_paths(paths::from_stored);
+    }
+    Ok(index)
+}
+
+fn save_index(dir: &Path, index: &[Attachment]) -> WorkbenchResult<()> {
+    // Portable installs keep the paths relative, see `crate::paths`
+    let index: Vec<Attachment> = index
+        .iter()
+        .cloned()
+        .map(|mut attachment| {
+            attachment.map_paths(paths::to_stored);
+            attachment
+        })
+        .collect();
+    let content = serde_json::to_string_pretty(&index)
+        .map_err(|e| WorkbenchError::json("Failed to serialize attachment index", e))?;
+    fs::write(dir.join("index.json"), content)
+        .map_err(|e| WorkbenchError::io("Failed to write attachment index", e))
+}
+
+/// Path as a plain string, without the `\\?\` prefix Windows adds to canonical paths
+fn path_string(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    path.strip_prefix(r"\\?\").unwrap_or(&path).to_string()
+}
+
+fn extension_for(mime_type: &str) -> &str {
+    match mime_type {
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "application/pdf" => "pdf",
+        "text/plain" => "txt",
+        "application/json" => "json",
+        _ => "bin",
+    }
+}
+
+fn mime_for_extension(file_name: &str) -> Option<&'static str> {
+    let extension = Path::new(file_name)
+        .extension()?
+        .to_string_lossy()
+        .to_lowercase();
+    Some(match extension.as_str() {
+        "pdf" => "application/pdf",
+        "txt" | "md" | "log" | "csv" => "text/plain",
+        "json" => "application/json",
+        _ => return None,
+    })
+}
+
+/// Content and MIME type to store; unsupported image formats become PNG
+fn normalize(bytes: Vec<u8>, file_name: &str, mime_type: Option<&str>) -> (Vec<u8>, String) {
+    let format = match image::guess_format(&bytes) {
+        Ok(format) => format,
+        Err(_) => {
+            let mime = mime_type
+                .filter(|mime| !mime.is_empty())
+                .or_else(|| mime_for_extension(file_name))
+                .unwrap_or("application/octet-stream");
+            return (bytes, mime.to_string());
+        }
+    };
+    match format {
+        ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::Gif | ImageFormat::WebP => {
+            (bytes, format.to_mime_type().to_string())
+        }
+        _ => {
+            let mut png = Vec::new();
+            let converted = image::load_from_memory(&bytes)
+                .and_then(|img| img.write_to(&mut Cursor::new(&mut png), ImageFormat::Png));
+            match converted {
+                Ok(()) => (png, "image/png".to_string()),
+                Err(e) => {
+                    warn!("Keeping {} as is, conversion failed: {}", file_name, e);
+                    (bytes, format.to_mime_type().to_string())
+                }
+            }
+        }
+    }
+}
+
+/// Write a thumbnail; returns the image's dimensions
+fn write_thumbnail(bytes: &[u8], thumb: &Path) -> Option<(u32, u32)> {
+    let img = match image::load_from_memory(bytes) {
+        Ok(img) => img,
+        Err(e) => {
+            debug!("No thumbnail for {:?}: {}", thumb, e);
+            return None;
+        }
+    };
+    let dimensions = (img.width(), img.height());
+    let saved = thumb
+        .parent()
+        .map(fs::create_dir_all)
+        .unwrap_or(Ok(()))
+        .map_err(|e| e.to_string())
+        .and_then(|_| {
+            img.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+                .save_with_format(thumb, ImageFormat::Png)
+                .map_err(|e| e.to_string())
+        });
+    if let Err(e) = saved {
+        warn!("Failed to write thumbnail {:?}: {}", thumb, e);
+    }
+    Some(dimensions)
+}
+
+/// Store `bytes` for a session, or return the existing copy of the same content
+fn store(
+    app: &AppHandle,
+    state: &AttachmentState,
+    session_id: &str,
+    file_name: &str,
+    mime_type: Option<&str>,
+    bytes: Vec<u8>,
+) -> WorkbenchResult<Attachment> {
+    if bytes.is_empty() {
+        return Err(WorkbenchError::InvalidInput(
+            "Attachment is empty".to_string(),
+        ));
+    }
+    if bytes.len() > MAX_ATTACHMENT_BYTES {
+        return Err(WorkbenchError::InvalidInput(format!(
+            "{} is {} MB; attachments are limited to {} MB",
+            file_name,
+            bytes.len() / (1024 * 1024),
+            MAX_ATTACHMENT_BYTES / (1024 * 1024)
+        )));
+    }
+    let (bytes, mime_type) = normalize(bytes, file_name, mime_type);
+    let digest = Sha256::digest(&bytes);
+    let id: String = digest
+        .iter()
+        .take(8)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+
+    let dir = session_dir(app, session_id)?;
+    let _guard = state.0.lock().map_err(|e| e.to_string())?;
+    let mut index = load_index(&dir)?;
+    if let Some(existing) = index.iter().find(|a| a.id == id) {
+        if Path::new(&existing.path).exists() {
+            debug!("Attachment {} already stored for {}", id, session_id);
+            return Ok(existing.clone());
+        }
+    }
+    index.retain(|a| a.id != id);
+
+    let total: u64 = index.iter().map(|a| a.size).sum();
+    if total + bytes.len() as u64 > MAX_SESSION_BYTES {
+        return Err(WorkbenchError::InvalidInput(format!(
+            "Session {} has reached its attachment limit of {} MB",
+            session_id,
+            MAX_SESSION_BYTES / (1024 * 1024)
+        )));
+    }
+
+    fs::create_dir_all(&dir)
+        .map_err(|e| WorkbenchError::io("Failed to create attachment directory", e))?;
+    let path = dir.join(format!("{}.{}", id, extension_for(&mime_type)));
+    fs::write(&path, &bytes).map_err(|e| WorkbenchError::io("Failed to write attachment", e))?;
+    let path = path.canonicalize().unwrap_or(path);
+
+    let is_image = mime_type.starts_with("image/");
+    let thumb = dir.join("thumbs").join(format!("{}.png", id));
+    let dimensions = if is_image {
+        write_thumbnail(&bytes, &thumb)
+    } else {
+        None
+    };
+
+    let attachment = Attachment {
+        id,
+        session_id: session_id.to_string(),
+        file_name: file_name.to_string(),
+        mime_type,
+        size: bytes.len() as u64,
+        path: path_string(&path),
+        thumbnail_path: Some(thumb)
+            .filter(|thumb| thumb.exists())
+            .map(|thumb| path_string(&thumb.canonicalize().unwrap_or(thumb))),
+        width: dimensions.map(|(width, _)| width),
+        height: dimensions.map(|(_, height)| height),
+        is_image,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    index.push(attachment.clone());
+    save_index(&dir, &index)?;
+    info!(
+        "Stored attachment {} ({}, {} bytes) for session {}",
+        attachment.id, attachment.mime_type, attachment.size, session_id
+    );
+    Ok(attachment)
+}
+
+/// Split a data URL into its MIME type and base64 payload
+fn parse_data(data: &str) -> (Option<&str>, &str) {
+    match data
+        .strip_prefix("data:")
+        .and_then(|rest| rest.split_once(','))
+    {
+        Some((meta, payload)) => {
+            let mime = meta.split(';').next().filter(|mime| !mime.is_empty());
+            (mime, payload)
+        }
+        None => (None, data),
+    }
+}
+
+async fn store_blocking(
+    app: AppHandle,
+    session_id: String,
+    file_name: String,
+    mime_type: Option<String>,
+    bytes: Vec<u8>,
+) -> WorkbenchResult<Attachment> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AttachmentState>();
+        store(
+            &app,
+            &state,
+            &session_id,
+            &file_name,
+            mime_type.as_deref(),
+            bytes,
+        )
+    })
+    .await
+    .unwrap_or_else(|_| Err(WorkbenchError::Dropped("Storing attachment".to_string())))
+}
+
+// ============ Tauri Commands ============
+
+/// Store pasted content, given as base64 or a data URL
+#[tauri::command]
+pub async fn add_attachment(
+    app: AppHandle,
+    session_id: String,
+    data: String,
+    file_name: Option<String>,
+    mime_type: Option<String>,
+) -> Result<Attachment, WorkbenchError> {
+    let (data_mime, payload) = parse_data(&data);
+    let mime_type = mime_type.or_else(|| data_mime.map(str::to_string));
+    let bytes = general_purpose::STANDARD
+        .decode(payload.trim())
+        .map_err(|e| WorkbenchError::InvalidInput(format!("Invalid base64 data: {}", e)))?;
+    let file_name = file_name.unwrap_or_else(|| "pasted".to_string());
+    store_blocking(app, session_id, file_name, mime_type, bytes).await
+}
+
+/// Store a copy of a file dropped from disk
+#[tauri::command]
+pub async fn add_attachment_file(
+    app: AppHandle,
+    session_id: String,
+    path: String,
+) -> Result<Attachment, WorkbenchError> {
+    let source = PathBuf::from(&path);
+    let size = fs::metadata(&source)
+        .map_err(|e| WorkbenchError::io(format!("Failed to read {}", path), e))?
+        .len();
+    if size > MAX_ATTACHMENT_BYTES as u64 {
+        return Err(WorkbenchError::InvalidInput(format!(
+            "{} is larger than {} MB",
+            path,
+            MAX_ATTACHMENT_BYTES / (1024 * 1024)
+        )));
+    }
+    let bytes =
+        fs::read(&source).map_err(|e| WorkbenchError::io(format!("Failed to read {}", path), e))?;
+    let file_name = source
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or(path);
+    store_blocking(app, session_id, file_name, None, bytes).await
+}
+
+/// Store the image currently on the system clipboard, such as a screenshot
+#[tauri::command]
+pub async fn paste_clipboard_attachment(
+    app: AppHandle,
+    session_id: String,
+) -> Result<Attachment, WorkbenchError> {
+    let png = tauri::async_runtime::spawn_blocking(|| -> WorkbenchResult<Vec<u8>> {
+        let mut clipboard =
+            arboard::Clipboard::new().map_err(|e| format!("Failed to access clipboard: {}", e))?;
+        let image = clipboard
+            .get_image()
+            .map_err(|_| WorkbenchError::NotFound("No image on the clipboard".to_string()))?;
+        let rgba = RgbaImage::from_raw(
+            image.width as u32,
+            image.height as u32,
+            image.bytes.into_owned(),
+        )
+        .ok_or("Clipboard image has an unexpected size")?;
+        let mut png = Vec::new();
+        rgba.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .map_err(|e| format!("Failed to encode clipboard image: {}", e))?;
+        Ok(png)
+    })
+    .await
+    .unwrap_or_else(|_| Err(WorkbenchError::Dropped("Reading the clipboard".to_string())))?;
+    let file_name = format!(
+        "screenshot-{}.png",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    );
+    store_blocking(
+        app,
+        session_id,
+        file_name,
+        Some("image/png".to_string()),
+        png,
+    )
+    .await
+}
+
+/// A session's attachments, oldest first
+#[tauri::command]
+pub async fn list_attachments(
+    app: AppHandle,
+    session_id: String,
+) -> Result<Vec<Attachment>, WorkbenchError> {
+    load_index(&session_dir(&app, &session_id)?)
+}
+
+/// Remove an attachment and its thumbnail
+#[tauri::command]
+pub async fn delete_attachment(
+    app: AppHandle,
+    state: State<'_, AttachmentState>,
+    session_id: String,
+    id: String,
+) -> Result<(), WorkbenchError> {
+    let dir = session_dir(&app, &session_id)?;
+    let _guard = state.0.lock().map_err(|e| e.to_string())?;
+    let mut index = load_index(&dir)?;
+    let position = index
+        .iter()
+        .position(|a| a.id == id)
+        .ok_or_else(|| WorkbenchError::NotFound(format!("Attachment {} not found", id)))?;
+    let attachment = index.remove(position);
+    for path in [Some(&attachment.path), attachment.thumbnail_path.as_ref()]
+        .into_iter()
+        .flatten()
+    {
+        if let Err(e) = fs::remove_file(path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                return Err(WorkbenchError::io("Failed to delete attachment", e));
+            }
+        }
+    }
+    save_index(&dir, &index)
+}
@@ -0,0 +1,240 @@
+use log::debug;
+/// Spoken announcements and sounds
+///
+/// Backs the `speak` and `play_sound` hook actions, so a session can announce
+/// that it finished or is waiting for approval while the user is in another
+/// window. Both use what the OS ships: `say` / `afplay` on macOS,
+/// System.Speech / System.Media through PowerShell on Windows, and speech-dispatcher,
+/// espeak, libcanberra or PulseAudio tools on Linux, whichever is installed. The
+/// player is started in the background and the call returns immediately.
+///
+/// Sounds are named `done`, `attention` or `error`, which map to a fitting system
+/// sound on each platform. Any other name is taken as a path to an audio file, or
+/// on macOS as the name of a sound in `/System/Library/Sounds`.
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::error::{WorkbenchError, WorkbenchResult};
+use crate::process::audit::AuditedCommand;
+
+/// Longest text spoken at once
+const MAX_SPEECH_CHARS: usize = 1000;
+
+/// Start the first candidate whose program exists; the process finishes on its own
+fn spawn_first(what: &str, candidates: Vec<Command>) -> WorkbenchResult<()> {
+    for mut cmd in candidates {
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+        }
+        match cmd.audited_spawn("system") {
+            Ok((mut child, audit)) => {
+                std::thread::spawn(move || audit.finish(&child.wait()));
+                return Ok(());
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                debug!("{:?} not available for {}", cmd.get_program(), what);
+            }
+            Err(e) => return Err(WorkbenchError::io(format!("Failed to start {}", what), e)),
+        }
+    }
+    Err(WorkbenchError::NotFound(format!(
+        "No program for {} is installed",
+        what
+    )))
+}
+
+/// Read `text` aloud; `rate` is in words per minute
+pub fn speak(text: &str, voice: Option<&str>, rate: Option<u32>) -> WorkbenchResult<()> {
+    let text: String = text.trim().chars().take(MAX_SPEECH_CHARS).collect();
+    if text.is_empty() {
+        return Err(WorkbenchError::InvalidInput("Nothing to speak".to_string()));
+    }
+    let mut candidates = Vec::new();
+
+    #[cfg(target_os = "macos")]
+    {
+        let mut cmd = Command::new("say");
+        if let Some(voice) = voice {
+            cmd.args(["-v", voice]);
+        }
+        if let Some(rate) = rate {
+            cmd.args(["-r", &rate.to_string()]);
+        }
+        cmd.arg("--").arg(&text);
+        candidates.push(cmd);
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        // Text and voice go through the environment to avoid quoting them
+        let mut cmd = Command::new("powershell");
+        cmd.args([
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            "Add-Type -AssemblyName System.Speech; \
+             $s = New-Object System.Speech.Synthesis.SpeechSynthesizer; \
+             if ($env:WORKBENCH_VOICE) { $s.SelectVoice($env:WORKBENCH_VOICE) }; \
+             if ($env:WORKBENCH_RATE) { $s.Rate = [int]$env:WORKBENCH_RATE }; \
+             $s.Speak($env:WORKBENCH_SPEECH)",
+        ]);
+        cmd.env("WORKBENCH_SPEECH", &text);
+        if let Some(voice) = voice {
+            cmd.env("WORKBENCH_VOICE", voice);
+        }
+        if let Some(rate) = rate {
+            // System.Speech rates run from -10 to 10 around about 180 words per minute
+            let rate = ((rate as i64 - 180) / 20).clamp(-10, 10);
+            cmd.env("WORKBENCH_RATE", rate.to_string());
+        }
+        candidates.push(cmd);
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let mut cmd = Command::new("spd-say");
+        if let Some(voice) = voice {
+            cmd.args(["-y", voice]);
+        }
+        cmd.arg("--").arg(&text);
+        candidates.push(cmd);
+        for program in ["espeak-ng", "espeak"] {
+            let mut cmd = Command::new(program);
+            if let Some(voice) = voice {
+                cmd.args(["-v", voice]);
+            }
+            if let Some(rate) = rate {
+                cmd.args(["-s", &rate.to_string()]);
+            }
+            cmd.arg("--").arg(&text);
+            candidates.push(cmd);
+        }
+    }
+
+    spawn_first("speech", candidates)
+}
+
+/// Play a named system sound or an audio file
+pub fn play_sound(name: &str) -> WorkbenchResult<()> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(WorkbenchError::InvalidInput("No sound given".to_string()));
+    }
+    let file = Some(Path::new(name)).filter(|path| path.is_file());
+    let mut candidates = Vec::new();
+
+    #[cfg(target_os = "macos")]
+    {
+        let path = match file {
+            Some(path) => path.to_path_buf(),
+            None => {
+                let system = match name {
+                    "done" => "Glass",
+                    "attention" => "Ping",
+                    "error" => "Basso",
+                    other => other,
+                };
+                Path::new("/System/Library/Sounds").join(format!("{}.aiff", system))
+            }
+        };
+        if !path.is_file() {
+            return Err(WorkbenchError::NotFound(format!(
+                "Unknown sound '{}'",
+                name
+            )));
+        }
+        let mut cmd = Command::new("afplay");
+        cmd.arg(path);
+        candidates.push(cmd);
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let script = match (file, name) {
+            // SoundPlayer only plays WAV files
+            (Some(_), _) => "(New-Object System.Media.SoundPlayer $env:WORKBENCH_SOUND).PlaySync()",
+            (None, "done") => {
+                "[System.Media.SystemSounds]::Asterisk.Play(); Start-Sleep -Milliseconds 1500"
+            }
+            (None, "attention") => {
+                "[System.Media.SystemSounds]::Exclamation.Play(); Start-Sleep -Milliseconds 1500"
+            }
+            (None, "error") => {
+                "[System.Media.SystemSounds]::Hand.Play(); Start-Sleep -Milliseconds 1500"
+            }
+            (None, _) => {
+                return Err(WorkbenchError::NotFound(format!(
+                    "Unknown sound '{}'",
+                    name
+                )));
+            }
+        };
+        let mut cmd = Command::new("powershell");
+        cmd.args(["-NoProfile", "-NonInteractive", "-Command", script]);
+        cmd.env("WORKBENCH_SOUND", name);
+        candidates.push(cmd);
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        match file {
+            Some(path) => {
+                for program in ["paplay", "pw-play", "aplay"] {
+                    let mut cmd = Command::new(program);
+                    cmd.arg(path);
+                    candidates.push(cmd);
+                }
+            }
+            None => {
+                let id = match name {
+                    "done" => "complete",
+                    "attention" => "dialog-warning",
+                    "error" => "dialog-error",
+                    _ => {
+                        return Err(WorkbenchError::NotFound(format!(
+                            "Unknown sound '{}'",
+                            name
+                        )));
+                    }
+                };
+                let mut cmd = Command::new("canberra-gtk-play");
+                cmd.args(["-i", id]);
+                candidates.push(cmd);
+                let freedesktop = format!("/usr/share/sounds/freedesktop/stereo/{}.oga", id);
+                if Path::new(&freedesktop).is_file() {
+                    for program in ["paplay", "pw-play"] {
+                        let mut cmd = Command::new(program);
+                        cmd.arg(&freedesktop);
+                        candidates.push(cmd);
+                    }
+                }
+            }
+        }
+    }
+
+    spawn_first("sound", candidates)
+}
+
+// ============ Tauri Commands ============
+
+/// Read text aloud, e.g. to try a voice before using it in a hook
+#[tauri::command]
+pub async fn speak_text(
+    text: String,
+    voice: Option<String>,
+    rate: Option<u32>,
+) -> Result<(), WorkbenchError> {
+    speak(&text, voice.as_deref(), rate)
+}
+
+/// Play a named system sound or an audio file
+#[tauri::command]
+pub async fn play_system_sound(name: String) -> Result<(), WorkbenchError> {
+    play_sound(&name)
+}
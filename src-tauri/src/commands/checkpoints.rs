@@ -0,0 +1,572 @@
+use log::{debug, info, warn};
+/// Checkpoints and automatic checkpoint policies
+///
+/// A checkpoint is a snapshot of the project's working tree (tracked and untracked,
+/// minus ignored files) committed to a hidden `refs/checkpoints/<id>` ref, so the
+/// user's branch, index and stash stay untouched. Policies create checkpoints when a
+/// hook event fires (e.g. before every Bash tool use) or every N prompts, and the
+/// retention settings prune old checkpoints after each new one.
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use super::enhanced_hooks::{matcher_accepts, HookContext};
+use super::git_stats::{git_file_diffs, summarize_file_diffs, FileDiff, GitDiffStats};
+use super::simple_git::is_git_repo;
+use super::storage::AgentDb;
+use crate::events::{self, AppEvent, CheckpointCreated};
+use crate::process::audit::AuditedCommand;
+
+/// `app_settings` key holding the JSON settings
+const SETTINGS_KEY: &str = "checkpoint_policies";
+
+const REF_PREFIX: &str = "refs/checkpoints/";
+
+/// What makes a policy create a checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CheckpointTrigger {
+    /// A hook event, optionally narrowed with a hook-style tool matcher
+    /// such as `Bash` or `Edit|Write`
+    HookEvent {
+        event: String,
+        #[serde(default)]
+        matcher: Option<String>,
+    },
+    /// Every `count` prompts sent in the project
+    EveryNMessages { count: u32 },
+}
+
+/// A rule for creating checkpoints automatically
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CheckpointPolicy {
+    pub name: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Limit the policy to one project; applies everywhere when unset
+    #[serde(default)]
+    pub project_path: Option<String>,
+    pub trigger: CheckpointTrigger,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// How many checkpoints to keep per project
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct CheckpointRetention {
+    #[serde(default)]
+    pub max_checkpoints: Option<usize>,
+    #[serde(default)]
+    pub max_age_days: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct CheckpointSettings {
+    #[serde(default)]
+    pub policies: Vec<CheckpointPolicy>,
+    #[serde(default)]
+    pub retention: CheckpointRetention,
+}
+
+/// A stored checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub id: String,
+    pub commit: String,
+    /// Unix seconds
+    pub created_at: i64,
+    pub message: String,
+}
+
+/// Result of `diff_checkpoints`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointDiff {
+    pub from: String,
+    /// `None` when compared against the working tree
+    pub to: Option<String>,
+    pub stats: GitDiffStats,
+    pub files: Vec<FileDiff>,
+}
+
+/// Prompts counted per project for `EveryNMessages` policies
+#[derive(Default)]
+pub struct CheckpointCounters(pub Mutex<HashMap<String, u64>>);
+
+#[tracing::instrument(name = "git", skip_all, fields(command = args[0], repo = project_path))]
+fn git(project_path: &str, args: &[&str], index_file: Option<&Path>) -> Result<String, String> {
+    let mut cmd = Command::new("git");
+    cmd.args(args).current_dir(project_path);
+    if let Some(index_file) = index_file {
+        cmd.env("GIT_INDEX_FILE", index_file);
+    }
+
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+    let output = cmd
+        .audited_output("git")
+        .map_err(|e| format!("Failed to run git {}: {}", args[0], e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Write the working tree (minus ignored files) as a tree object
+pub(super) fn snapshot_tree(project_path: &str) -> Result<String, String> {
+    if !is_git_repo(project_path) {
+        return Err(format!("{} is not a git repository", project_path));
+    }
+
+    // Stage into a copy of the index so the user's staging area is left alone;
+    // starting from the real index keeps `git add` from rehashing every file
+    let index_file = crate::paths::temp_dir().join(format!(
+        "workbench-checkpoint-{}.index",
+        uuid::Uuid::new_v4()
+    ));
+    if let Ok(real_index) = git(project_path, &["rev-parse", "--git-path", "index"], None) {
+        let _ = std::fs::copy(Path::new(project_path).join(real_index), &index_file);
+    }
+    let tree = git(project_path, &["add", "-A"], Some(&index_file))
+        .and_then(|_| git(project_path, &["write-tree"], Some(&index_file)));
+    let _ = std::fs::remove_file(&index_file);
+    tree
+}
+
+/// Snapshot the working tree into a new checkpoint ref
+fn create_git_checkpoint(project_path: &str, message: &str) -> Result<Checkpoint, String> {
+    let tree = snapshot_tree(project_path)?;
+    let head = git(project_path, &["rev-parse", "--verify", "-q", "HEAD"], None).ok();
+    let mut args = vec![
+        "-c",
+        "user.name=Claude Workbench",
+        "-c",
+        "user.email=workbench@localhost",
+        "commit-tree",
+        &tree,
+        "-m",
+        message,
+    ];
+    if let Some(head) = &head {
+        args.extend(["-p", head]);
+    }
+    let commit = git(project_path, &args, None)?;
+
+    let id = chrono::Utc::now().format("%Y%m%d-%H%M%S-%3f").to_string();
+    git(
+        project_path,
+        &["update-ref", &format!("{}{}", REF_PREFIX, id), &commit],
+        None,
+    )?;
+
+    Ok(Checkpoint {
+        id,
+        commit,
+        created_at: chrono::Utc::now().timestamp(),
+        message: message.to_string(),
+    })
+}
+
+/// Checkpoints of a project, newest first
+pub(super) fn list_git_checkpoints(project_path: &str) -> Result<Vec<Checkpoint>, String> {
+    if !is_git_repo(project_path) {
+        return Ok(Vec::new());
+    }
+    let output = git(
+        project_path,
+        &[
+            "for-each-ref",
+            "--sort=-refname",
+            "--format=%(refname)%00%(objectname)%00%(creatordate:unix)%00%(contents:subject)",
+            REF_PREFIX,
+        ],
+        None,
+    )?;
+
+    Ok(output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\0');
+            Some(Checkpoint {
+                id: fields.next()?.strip_prefix(REF_PREFIX)?.to_string(),
+                commit: fields.next()?.to_string(),
+                created_at: fields.next()?.parse().ok()?,
+                message: fields.next().unwrap_or("").to_string(),
+            })
+        })
+        .collect())
+}
+
+fn delete_git_checkpoint(project_path: &str, id: &str) -> Result<(), String> {
+    git(
+        project_path,
+        &["update-ref", "-d", &format!("{}{}", REF_PREFIX, id)],
+        None,
+    )
+    .map(|_| ())
+}
+
+/// Commit a checkpoint id points at
+fn resolve_checkpoint(project_path: &str, id: &str) -> Result<String, String> {
+    let reference = format!("{}{}^{{commit}}", REF_PREFIX, id);
+    git(
+        project_path,
+        &["rev-parse", "--verify", "-q", &reference],
+        None,
+    )
+    .map_err(|_| format!("Checkpoint {} not found", id))
+}
+
+/// Make the working tree match a checkpoint. The current state is saved as a new
+/// checkpoint first and returned, so the restore can be undone. Ignored files are
+/// left alone, as they are not part of checkpoints either.
+pub(super) fn restore_git_checkpoint(project_path: &str, id: &str) -> Result<Checkpoint, String> {
+    let target = resolve_checkpoint(project_path, id)?;
+    let before = create_git_checkpoint(
+        project_path,
+        &format!("checkpoint: before restoring {}", id),
+    )?;
+
+    // Files created since the checkpoint have no version to go back to
+    let added = git(
+        project_path,
+        &[
+            "diff",
+            "--name-only",
+            "-z",
+            "--no-renames",
+            "--diff-filter=A",
+            &target,
+            &before.commit,
+        ],
+        None,
+    )?;
+    for path in added.split('\0').filter(|path| !path.is_empty()) {
+        let path = Path::new(project_path).join(path);
+        if let Err(e) = std::fs::remove_file(&path) {
+            warn!("Failed to remove {}: {}", path.display(), e);
+        }
+    }
+
+    // Check out through a separate index so the user's staging area is left alone
+    let index_file =
+        crate::paths::temp_dir().join(format!("workbench-restore-{}.index", uuid::Uuid::new_v4()));
+    let restored = git(project_path, &["read-tree", &target], Some(&index_file)).and_then(|_| {
+        git(
+            project_path,
+            &["checkout-index", "--all", "--force"],
+            Some(&index_file),
+        )
+    });
+    let _ = std::fs::remove_file(&index_file);
+    restored?;
+
+    info!(
+        "Restored checkpoint {} in {} (previous state saved as {})",
+        id, project_path, before.id
+    );
+    Ok(before)
+}
+
+/// Delete checkpoints beyond the retention limits; returns how many were removed
+fn prune_git_checkpoints(
+    project_path: &str,
+    retention: &CheckpointRetention,
+) -> Result<usize, String> {
+    let cutoff = retention
+        .max_age_days
+        .map(|days| chrono::Utc::now().timestamp() - i64::from(days) * 86_400);
+    let expired: Vec<Checkpoint> = list_git_checkpoints(project_path)?
+        .into_iter()
+        .enumerate()
+        .filter(|(i, c)| {
+            retention.max_checkpoints.is_some_and(|max| *i >= max)
+                || cutoff.is_some_and(|cutoff| c.created_at < cutoff)
+        })
+        .map(|(_, c)| c)
+        .collect();
+
+    for checkpoint in &expired {
+        delete_git_checkpoint(project_path, &checkpoint.id)?;
+    }
+    if !expired.is_empty() {
+        info!("Pruned {} checkpoint(s) in {}", expired.len(), project_path);
+    }
+    Ok(expired.len())
+}
+
+fn load_settings(app: &AppHandle) -> Result<CheckpointSettings, String> {
+    let db = app
+        .try_state::<AgentDb>()
+        .ok_or("Database not initialized")?;
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT value FROM app_settings WHERE key = ?1",
+            [SETTINGS_KEY],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    match stored {
+        Some(json) => {
+            serde_json::from_str(&json).map_err(|e| format!("Invalid checkpoint settings: {}", e))
+        }
+        None => Ok(CheckpointSettings::default()),
+    }
+}
+
+/// Create a checkpoint for a policy and apply retention; failures are only logged,
+/// since a missed checkpoint must never block the event that triggered it
+async fn run_policy(
+    app: &AppHandle,
+    project_path: &str,
+    policy: &CheckpointPolicy,
+    reason: String,
+) {
+    let settings = match load_settings(app) {
+        Ok(settings) => settings,
+        Err(e) => {
+            warn!("Skipping checkpoint: {}", e);
+            return;
+        }
+    };
+    let project = project_path.to_string();
+    let message = format!("checkpoint: {} ({})", policy.name, reason);
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let checkpoint = create_git_checkpoint(&project, &message)?;
+        prune_git_checkpoints(&project, &settings.retention)?;
+        Ok::<_, String>(checkpoint)
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|r| r);
+
+    match result {
+        Ok(checkpoint) => {
+            super::telemetry::record(app, "checkpoint.policy");
+            info!(
+                "Created checkpoint {} in {} for policy {}",
+                checkpoint.id, project_path, policy.name
+            );
+            events::emit(
+                app,
+                AppEvent::CheckpointCreated(CheckpointCreated {
+                    project_path: project_path.to_string(),
+                    policy: policy.name.clone(),
+                    checkpoint,
+                }),
+            );
+        }
+        Err(e) => warn!(
+            "Policy {} could not create a checkpoint: {}",
+            policy.name, e
+        ),
+    }
+}
+
+fn applies_to(policy: &CheckpointPolicy, project_path: &str) -> bool {
+    policy.enabled
+        && !project_path.is_empty()
+        && policy
+            .project_path
+            .as_deref()
+            .is_none_or(|p| p == project_path)
+}
+
+/// Run the policies subscribed to a hook event. Called before the event's hooks,
+/// so a `PreToolUse` checkpoint captures the tree the tool is about to change.
+pub async fn on_hook_event(app: &AppHandle, event: &str, context: &HookContext) {
+    let settings = match load_settings(app) {
+        Ok(settings) => settings,
+        Err(e) => {
+            debug!("No checkpoint policies: {}", e);
+            return;
+        }
+    };
+    let policy = settings.policies.iter().find(|p| {
+        applies_to(p, &context.project_path)
+            && match &p.trigger {
+                CheckpointTrigger::HookEvent {
+                    event: trigger,
+                    matcher,
+                } => {
+                    trigger == event
+                        && matcher
+                            .as_deref()
+                            .is_none_or(|m| matcher_accepts(m, context).unwrap_or(false))
+                }
+                CheckpointTrigger::EveryNMessages { .. } => false,
+            }
+    });
+
+    // One snapshot covers every matching policy
+    if let Some(policy) = policy {
+        let reason = match context.data.get("tool_name").and_then(|t| t.as_str()) {
+            Some(tool) => format!("{} {}", event, tool),
+            None => event.to_string(),
+        };
+        run_policy(app, &context.project_path, policy, reason).await;
+    }
+}
+
+/// Count a prompt sent in a project and run any `EveryNMessages` policy it completes
+pub async fn on_user_prompt(app: &AppHandle, project_path: &str) {
+    let settings = match load_settings(app) {
+        Ok(settings) => settings,
+        Err(e) => {
+            debug!("No checkpoint policies: {}", e);
+            return;
+        }
+    };
+    let counting = settings.policies.iter().any(|p| {
+        applies_to(p, project_path) && matches!(p.trigger, CheckpointTrigger::EveryNMessages { .. })
+    });
+    if !counting {
+        return;
+    }
+
+    let count = match app.try_state::<CheckpointCounters>() {
+        Some(counters) => match counters.0.lock() {
+            Ok(mut counters) => {
+                let count = counters.entry(project_path.to_string()).or_insert(0);
+                *count += 1;
+                *count
+            }
+            Err(_) => return,
+        },
+        None => return,
+    };
+
+    let policy = settings.policies.iter().find(|p| {
+        applies_to(p, project_path)
+            && matches!(p.trigger, CheckpointTrigger::EveryNMessages { count: n } if n > 0 && count % u64::from(n) == 0)
+    });
+    if let Some(policy) = policy {
+        run_policy(app, project_path, policy, format!("message {}", count)).await;
+    }
+}
+
+// ============ Tauri Commands ============
+
+/// Get the checkpoint policies and retention settings
+#[tauri::command]
+pub async fn get_checkpoint_settings(app: AppHandle) -> Result<CheckpointSettings, String> {
+    load_settings(&app)
+}
+
+/// Save the checkpoint policies and retention settings
+#[tauri::command]
+pub async fn update_checkpoint_settings(
+    db: State<'_, AgentDb>,
+    settings: CheckpointSettings,
+) -> Result<(), String> {
+    for policy in &settings.policies {
+        if policy.name.trim().is_empty() {
+            return Err("Checkpoint policy name cannot be empty".to_string());
+        }
+        if policy.trigger == (CheckpointTrigger::EveryNMessages { count: 0 }) {
+            return Err(format!(
+                "Policy {}: message count must be at least 1",
+                policy.name
+            ));
+        }
+    }
+    info!(
+        "Updating checkpoint settings ({} policies)",
+        settings.policies.len()
+    );
+
+    let json = serde_json::to_string(&settings).map_err(|e| e.to_string())?;
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+        params![SETTINGS_KEY, json],
+    )
+    .map_err(|e| format!("Failed to save checkpoint settings: {}", e))?;
+    Ok(())
+}
+
+/// List a project's checkpoints, newest first
+#[tauri::command]
+pub async fn list_checkpoints(project_path: String) -> Result<Vec<Checkpoint>, String> {
+    tauri::async_runtime::spawn_blocking(move || list_git_checkpoints(&project_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Create a checkpoint by hand
+#[tauri::command]
+pub async fn create_checkpoint(
+    project_path: String,
+    message: Option<String>,
+) -> Result<Checkpoint, String> {
+    let message = message.unwrap_or_else(|| "checkpoint: manual".to_string());
+    tauri::async_runtime::spawn_blocking(move || create_git_checkpoint(&project_path, &message))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Delete a checkpoint
+#[tauri::command]
+pub async fn delete_checkpoint(project_path: String, id: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || delete_git_checkpoint(&project_path, &id))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Make the working tree match a checkpoint; returns the checkpoint of the state
+/// it replaced, to undo the restore with
+#[tauri::command]
+pub async fn restore_checkpoint(project_path: String, id: String) -> Result<Checkpoint, String> {
+    tauri::async_runtime::spawn_blocking(move || restore_git_checkpoint(&project_path, &id))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// What changed between two checkpoints; `to` defaults to the current working tree
+#[tauri::command]
+pub async fn diff_checkpoints(
+    project_path: String,
+    from: String,
+    to: Option<String>,
+) -> Result<CheckpointDiff, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let from_commit = resolve_checkpoint(&project_path, &from)?;
+        let to_tree = match &to {
+            Some(id) => resolve_checkpoint(&project_path, id)?,
+            None => snapshot_tree(&project_path)?,
+        };
+        let files = git_file_diffs(&project_path, &from_commit, &to_tree)?;
+        Ok(CheckpointDiff {
+            from,
+            to,
+            stats: summarize_file_diffs(&files),
+            files,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Apply the retention settings to a project now; returns how many were removed
+#[tauri::command]
+pub async fn prune_checkpoints(app: AppHandle, project_path: String) -> Result<usize, String> {
+    let retention = load_settings(&app)?.retention;
+    tauri::async_runtime::spawn_blocking(move || prune_git_checkpoints(&project_path, &retention))
+        .await
+        .map_err(|e| e.to_string())?
+}
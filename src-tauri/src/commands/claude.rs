@@ -7,15 +7,20 @@ use super::permission_config::{
 // Agent functionality removed
 use std::fs;
 use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::Arc;
 use std::time::SystemTime;
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::{AppHandle, Manager};
 use tokio::process::{Child, Command};
 use tokio::sync::Mutex;
+use tracing::Instrument;
 use tauri_plugin_shell::ShellExt;
 use regex;
+use super::retry::RetryPolicy;
+use crate::error::WorkbenchError;
+use crate::events::{self, AppEvent};
+use crate::process::audit::{AuditedAsyncCommand, AuditedCommand, ProcessAudit};
 
 // Windows-specific imports
 #[cfg(target_os = "windows")]
@@ -206,9 +211,7 @@ fn find_claude_binary(app_handle: &AppHandle) -> Result<String, String> {
 
 /// Gets the path to the ~/.claude directory
 pub fn get_claude_dir() -> Result<PathBuf> {
-    let claude_dir = dirs::home_dir()
-        .context("Could not find home directory")?
-        .join(".claude");
+    let claude_dir = crate::paths::claude_dir().map_err(anyhow::Error::msg)?;
     
     // Ensure the directory exists
     fs::create_dir_all(&claude_dir)
@@ -267,7 +270,7 @@ fn get_project_path_from_sessions(project_dir: &PathBuf) -> Result<String, Strin
 
 /// Encodes a project path to match Claude CLI's encoding scheme
 /// Uses single hyphens to separate path components
-fn encode_project_path(path: &str) -> String {
+pub(crate) fn encode_project_path(path: &str) -> String {
     path.replace("\\", "-")
         .replace("/", "-")
         .replace(":", "")
@@ -563,6 +566,11 @@ fn create_command_with_env(program: &str) -> Command {
         }
     }
 
+    // Route network access through the configured proxy, if any
+    tokio_cmd.envs(crate::commands::proxy::proxy_env_vars());
+    // Point the CLI at Claude's data under the workbench data root, if one is set
+    tokio_cmd.envs(crate::paths::claude_env());
+
     tokio_cmd
 }
 
@@ -1191,7 +1199,7 @@ pub async fn open_new_session(app: AppHandle, path: Option<String>) -> Result<St
         }
 
         // Execute the command
-        match cmd.spawn() {
+        match cmd.audited_spawn("claude") {
             Ok(_) => {
                 log::info!("Successfully launched Claude Code");
                 Ok("Claude Code session started".to_string())
@@ -1241,7 +1249,7 @@ pub async fn check_claude_version(app: AppHandle) -> Result<ClaudeVersionStatus,
         use tauri_plugin_shell::process::CommandEvent;
         
         // Create a temporary directory for the sidecar to run in
-        let temp_dir = std::env::temp_dir();
+        let temp_dir = crate::paths::temp_dir();
         
         // Create sidecar command with --version flag
         let sidecar_cmd = match app
@@ -1335,7 +1343,7 @@ pub async fn check_claude_version(app: AppHandle) -> Result<ClaudeVersionStatus,
         cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
     }
     
-    let output = cmd.output();
+    let output = cmd.audited_output("claude");
 
     match output {
         Ok(output) => {
@@ -1437,17 +1445,7 @@ pub async fn save_claude_settings(settings: serde_json::Value) -> Result<String,
         existing_settings = actual_settings.clone();
     }
 
-    // Pretty print the JSON with 2-space indentation
-    let json_string = serde_json::to_string_pretty(&existing_settings)
-        .map_err(|e| {
-            let error_msg = format!("Failed to serialize settings: {}", e);
-            log::error!("{}", error_msg);
-            error_msg
-        })?;
-
-    log::info!("Serialized JSON length: {} characters", json_string.len());
-
-    fs::write(&settings_path, &json_string)
+    super::config_io::write_json_atomic(&settings_path, &existing_settings)
         .map_err(|e| {
             let error_msg = format!("Failed to write settings file: {}", e);
             log::error!("{}", error_msg);
@@ -1507,10 +1505,7 @@ pub async fn update_thinking_mode(enabled: bool, tokens: Option<u32>) -> Result<
     }
 
     // Write back to file
-    let json_string = serde_json::to_string_pretty(&settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-
-    fs::write(&settings_path, &json_string)
+    super::config_io::write_json_atomic(&settings_path, &settings)
         .map_err(|e| format!("Failed to write settings: {}", e))?;
 
     log::info!("Thinking mode updated successfully");
@@ -1732,6 +1727,11 @@ pub async fn execute_claude_code(
         plan_mode
     );
 
+    super::network::ensure_online(&app).await?;
+    super::telemetry::record(&app, "session.start");
+    super::slash_commands::run_user_command_hooks(&app, &project_path, "", &prompt).await?;
+    super::checkpoints::on_user_prompt(&app, &project_path).await;
+
     let claude_path = find_claude_binary(&app)?;
     
     // 获取当前执行配置
@@ -1741,6 +1741,8 @@ pub async fn execute_claude_code(
             ClaudeExecutionConfig::default()
         });
     
+    let thinking_preset = super::thinking_presets::apply(&app, &project_path, &mut execution_config);
+
     // 设置 maxThinkingTokens（如果提供）
     if let Some(tokens) = max_thinking_tokens {
         execution_config.max_thinking_tokens = Some(tokens);
@@ -1764,10 +1766,38 @@ pub async fn execute_claude_code(
     let args = build_execution_args(&execution_config, &prompt, &mapped_model, escape_prompt_for_cli);
 
     // Create command
-    let cmd = create_system_command(&claude_path, args, &project_path, Some(&mapped_model), max_thinking_tokens)?;
+    let mut cmd = create_system_command(&claude_path, args, &project_path, Some(&mapped_model), max_thinking_tokens)?;
+    super::thinking_presets::set_env(&mut cmd, thinking_preset);
     spawn_claude_process(app, cmd, prompt, model, project_path).await
 }
 
+/// Build the command for a new non-interactive session with the current execution
+/// config, for runs that manage their own process instead of ClaudeProcessState
+pub(crate) async fn new_session_command(
+    app: &AppHandle,
+    project_path: &str,
+    prompt: &str,
+    model: &str,
+    plan_mode: bool,
+) -> Result<Command, String> {
+    let claude_path = find_claude_binary(app)?;
+    let mut execution_config = get_claude_execution_config(app.clone()).await
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to load execution config, using default: {}", e);
+            ClaudeExecutionConfig::default()
+        });
+    let thinking_preset = super::thinking_presets::apply(app, project_path, &mut execution_config);
+    if plan_mode {
+        execution_config.permissions = ClaudePermissionConfig::plan_mode();
+    }
+
+    let mapped_model = map_model_to_claude_alias(model);
+    let args = build_execution_args(&execution_config, prompt, &mapped_model, escape_prompt_for_cli);
+    let mut cmd = create_system_command(&claude_path, args, project_path, Some(&mapped_model), None)?;
+    super::thinking_presets::set_env(&mut cmd, thinking_preset);
+    Ok(cmd)
+}
+
 /// Continue an existing Claude Code conversation with streaming output
 /// Enhanced for Windows with better error handling
 #[tauri::command]
@@ -1787,6 +1817,11 @@ pub async fn continue_claude_code(
         plan_mode
     );
 
+    super::network::ensure_online(&app).await?;
+    super::telemetry::record(&app, "session.continue");
+    super::slash_commands::run_user_command_hooks(&app, &project_path, "", &prompt).await?;
+    super::checkpoints::on_user_prompt(&app, &project_path).await;
+
     let claude_path = find_claude_binary(&app)?;
     
     // 获取当前执行配置
@@ -1796,6 +1831,8 @@ pub async fn continue_claude_code(
             ClaudeExecutionConfig::default()
         });
 
+    let thinking_preset = super::thinking_presets::apply(&app, &project_path, &mut execution_config);
+
     // 设置 maxThinkingTokens（如果提供）
     if let Some(tokens) = max_thinking_tokens {
         execution_config.max_thinking_tokens = Some(tokens);
@@ -1822,7 +1859,8 @@ pub async fn continue_claude_code(
     args.insert(0, "-c".to_string());
 
     // Create command
-    let cmd = create_system_command(&claude_path, args, &project_path, Some(&mapped_model), max_thinking_tokens)?;
+    let mut cmd = create_system_command(&claude_path, args, &project_path, Some(&mapped_model), max_thinking_tokens)?;
+    super::thinking_presets::set_env(&mut cmd, thinking_preset);
     spawn_claude_process(app, cmd, prompt, model, project_path).await
 }
 
@@ -1856,6 +1894,16 @@ pub async fn resume_claude_code(
     log::info!("Expected session file directory: {}", session_dir);
     log::info!("Session ID to resume: {}", session_id);
 
+    let model = super::models::resume_model(&app, &session_id, model);
+    if super::prompt_queue::enqueue_if_busy(&app, &project_path, &session_id, &prompt, &model, Some(plan_mode), max_thinking_tokens)? {
+        return Ok(());
+    }
+
+    super::network::ensure_online(&app).await?;
+    super::telemetry::record(&app, "session.resume");
+    super::slash_commands::run_user_command_hooks(&app, &project_path, &session_id, &prompt).await?;
+    super::checkpoints::on_user_prompt(&app, &project_path).await;
+
     let claude_path = find_claude_binary(&app)?;
     
     // 获取当前执行配置
@@ -1865,6 +1913,8 @@ pub async fn resume_claude_code(
             ClaudeExecutionConfig::default()
         });
 
+    let thinking_preset = super::thinking_presets::apply(&app, &project_path, &mut execution_config);
+
     // 设置 maxThinkingTokens（如果提供）
     if let Some(tokens) = max_thinking_tokens {
         execution_config.max_thinking_tokens = Some(tokens);
@@ -1894,13 +1944,23 @@ pub async fn resume_claude_code(
     log::info!("Resume command: claude {}", args.join(" "));
 
     // Create command
-    let cmd = create_system_command(&claude_path, args, &project_path, Some(&mapped_model), max_thinking_tokens)?;
+    let mut cmd = create_system_command(&claude_path, args, &project_path, Some(&mapped_model), max_thinking_tokens)?;
+    super::thinking_presets::set_env(&mut cmd, thinking_preset);
     
+    super::session_state::transition(
+        &app,
+        &session_id,
+        super::session_state::SessionPhase::Starting,
+        Some(&project_path),
+    )?;
+
     // Try to spawn the process - if it fails, fall back to continue mode
     match spawn_claude_process(app.clone(), cmd, prompt.clone(), model.clone(), project_path.clone()).await {
         Ok(_) => Ok(()),
         Err(resume_error) => {
             log::warn!("Resume failed: {}, trying continue mode as fallback", resume_error);
+            // The fallback reports its own session once it starts
+            super::session_state::observe(&app, &session_id, super::session_state::SessionPhase::Idle, None);
             // Fallback to continue mode
             continue_claude_code(app, project_path, prompt, model, Some(plan_mode), max_thinking_tokens).await
         }
@@ -1920,6 +1980,7 @@ pub async fn cancel_claude_execution(
 
     let mut killed = false;
     let mut attempted_methods = Vec::new();
+    let mut project_path = None;
 
     // Method 1: Try to find and kill via ProcessRegistry using session ID
     if let Some(sid) = &session_id {
@@ -1928,6 +1989,7 @@ pub async fn cancel_claude_execution(
             Ok(Some(process_info)) => {
                 log::info!("Found process in registry for session {}: run_id={}, PID={}", 
                     sid, process_info.run_id, process_info.pid);
+                project_path = Some(process_info.project_path.clone());
                 match registry.0.kill_process(process_info.run_id).await {
                     Ok(success) => {
                         if success {
@@ -1981,7 +2043,7 @@ pub async fn cancel_claude_execution(
                                 std::process::Command::new("taskkill")
                                     .args(["/F", "/T", "/PID", &pid.to_string()]) // Added /T to kill process tree
                                     .creation_flags(0x08000000) // CREATE_NO_WINDOW
-                                    .output()
+                                    .audited_output("system")
                             }
                             #[cfg(not(target_os = "windows"))]
                             {
@@ -1989,12 +2051,12 @@ pub async fn cancel_claude_execution(
                                 // but is needed for compilation on non-Windows platforms
                                 std::process::Command::new("kill")
                                     .args(["-KILL", &pid.to_string()])
-                                    .output()
+                                    .audited_output("system")
                             }
                         } else {
                             std::process::Command::new("kill")
                                 .args(["-KILL", &pid.to_string()])
-                                .output()
+                                .audited_output("system")
                         };
                         
                         match kill_result {
@@ -2025,15 +2087,15 @@ pub async fn cancel_claude_execution(
 
     // Always emit cancellation events for UI consistency
     if let Some(sid) = session_id {
-        let _ = app.emit(&format!("claude-cancelled:{}", sid), true);
+        events::emit(&app, AppEvent::ClaudeCancelled { session_id: Some(sid.clone()), project_path: project_path.clone() });
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-        let _ = app.emit(&format!("claude-complete:{}", sid), false);
+        events::emit(&app, AppEvent::ClaudeComplete { session_id: Some(sid), project_path: project_path.clone(), success: false });
     }
     
     // Also emit generic events for backward compatibility
-    let _ = app.emit("claude-cancelled", true);
+    events::emit(&app, AppEvent::ClaudeCancelled { session_id: None, project_path: project_path.clone() });
     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-    let _ = app.emit("claude-complete", false);
+    events::emit(&app, AppEvent::ClaudeComplete { session_id: None, project_path, success: false });
     
     if killed {
         log::info!("Claude process cancellation completed successfully");
@@ -2066,15 +2128,41 @@ pub async fn get_claude_session_output(
     }
 }
 
+/// Spawn Claude, retrying as the execution config's `spawn_retry` says
+pub(crate) async fn spawn_with_retry(
+    app: &AppHandle,
+    cmd: &mut Command,
+) -> Result<(Child, ProcessAudit), String> {
+    let policy = get_claude_execution_config(app.clone())
+        .await
+        .ok()
+        .and_then(|config| config.spawn_retry)
+        .unwrap_or_else(RetryPolicy::none);
+    policy
+        .run("Spawning Claude", |_| {
+            let spawned = cmd
+                .audited_spawn("claude")
+                .map_err(|e| WorkbenchError::io("Failed to spawn Claude", e));
+            async move { spawned }
+        })
+        .await
+        .map_err(|e| e.to_string())
+}
+
 /// Helper function to spawn Claude process and handle streaming
 async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String, model: String, project_path: String) -> Result<(), String> {
     use tokio::io::{AsyncBufReadExt, BufReader};
     use std::sync::Mutex;
 
+    // Inject keychain credentials, then the project's active environment profile
+    cmd.envs(crate::commands::credentials::credential_env(&app).await);
+    let env_profile = crate::commands::env_profiles::apply_active_profile(&app, &project_path, &mut cmd);
+    if let Some(name) = &env_profile {
+        log::info!("Using env profile: {}", name);
+    }
+
     // Spawn the process
-    let mut child = cmd
-        .spawn()
-        .map_err(|e| format!("Failed to spawn Claude: {}", e))?;
+    let (mut child, audit) = spawn_with_retry(&app, &mut cmd).await?;
 
     // Get stdout and stderr
     let stdout = child.stdout.take().ok_or("Failed to get stdout")?;
@@ -2119,10 +2207,36 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
     let project_path_clone = project_path.clone();
     let prompt_clone = prompt.clone();
     let model_clone = model.clone();
+    let env_profile_clone = env_profile.clone();
+    // Times the whole stream; each record gets a child span
+    let stream_span = tracing::info_span!(
+        "claude_stream",
+        project = project_path.as_str(),
+        pid,
+        session_id = tracing::field::Empty,
+    );
     let stdout_task = tokio::spawn(async move {
         let mut lines = stdout_reader.lines();
-        while let Ok(Some(line)) = lines.next_line().await {
-            log::debug!("Claude stdout: {}", line);
+        // Lines are checked and repaired before anything else sees them
+        let mut validator = super::claude_stream::StreamValidator::new(&app_handle);
+        let mut records = std::collections::VecDeque::new();
+        loop {
+            let line = match records.pop_front() {
+                Some(line) => line,
+                None => match lines.next_line().await {
+                    Ok(Some(raw)) => {
+                        log::debug!("Claude stdout: {}", raw);
+                        records.extend(validator.push_line(&raw));
+                        continue;
+                    }
+                    _ => {
+                        validator.finish();
+                        break;
+                    }
+                },
+            };
+            // Not entered, as it lives across awaits; the timing runs until it drops
+            let _record_span = tracing::info_span!("stream_record", bytes = line.len());
             
             // Parse the line to check for init message with session ID
             if let Ok(msg) = serde_json::from_str::<serde_json::Value>(&line) {
@@ -2132,6 +2246,23 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
                         if session_id_guard.is_none() {
                             *session_id_guard = Some(claude_session_id.to_string());
                             log::info!("Extracted Claude session ID: {}", claude_session_id);
+                            tracing::Span::current().record("session_id", claude_session_id);
+                            crate::commands::env_profiles::record_session_profile(
+                                &app_handle,
+                                claude_session_id,
+                                env_profile_clone.as_deref(),
+                            );
+                            super::cost_attribution::record_session_start(
+                                &app_handle,
+                                claude_session_id,
+                                &project_path_clone,
+                            );
+                            super::session_state::observe(
+                                &app_handle,
+                                claude_session_id,
+                                super::session_state::SessionPhase::Streaming,
+                                Some(&project_path_clone),
+                            );
 
                             // Register with auto-compact manager
                             if auto_compact_available {
@@ -2160,19 +2291,18 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
                                     *run_id_guard = Some(run_id);
 
                                     // ✨ Phase 2: Emit event for real-time session tracking
-                                    let event_payload = serde_json::json!({
-                                        "session_id": claude_session_id,
-                                        "project_path": project_path_clone,
-                                        "model": model_clone,
-                                        "status": "started",
-                                        "pid": pid,
-                                        "run_id": run_id,
-                                    });
-                                    if let Err(e) = app_handle.emit("claude-session-state", &event_payload) {
-                                        log::warn!("Failed to emit claude-session-state event: {}", e);
-                                    } else {
-                                        log::info!("Emitted claude-session-started event for session: {}", claude_session_id);
-                                    }
+                                    events::emit(&app_handle, AppEvent::ClaudeSessionState(events::ClaudeSessionState {
+                                        session_id: claude_session_id.to_string(),
+                                        project_path: project_path_clone.clone(),
+                                        status: "started".to_string(),
+                                        model: Some(model_clone.clone()),
+                                        pid: Some(pid),
+                                        run_id: Some(run_id),
+                                        env_profile: env_profile_clone.clone(),
+                                        success: None,
+                                        error: None,
+                                    }));
+                                    log::info!("Emitted claude-session-started event for session: {}", claude_session_id);
 
                                     log::info!("Claude CLI will handle project creation for session: {}", claude_session_id);
                                 }
@@ -2184,51 +2314,47 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
                     }
                 }
 
-                // Check for usage information and update context tracking
-                if let Some(usage) = msg.get("usage") {
-                    if let (Some(input_tokens), Some(output_tokens)) =
-                        (usage.get("input_tokens").and_then(|t| t.as_u64()),
-                         usage.get("output_tokens").and_then(|t| t.as_u64())) {
-
-                        let total_tokens = (input_tokens + output_tokens) as usize;
-
-                        // Extract cache tokens if available
-                        let _cache_creation_tokens = usage.get("cache_creation_input_tokens").and_then(|t| t.as_u64());
-                        let _cache_read_tokens = usage.get("cache_read_input_tokens").and_then(|t| t.as_u64());
+                // Feed usage and compaction markers to the context monitor
+                if auto_compact_available {
+                    let session_id_for_update = {
+                        session_id_holder_clone.lock().unwrap().as_ref().cloned()
+                    };
 
-                        // Store usage data in database for real-time token statistics
-                        let session_id_for_update = {
-                            session_id_holder_clone.lock().unwrap().as_ref().cloned()
-                        };
-
-                        if let Some(session_id_str) = &session_id_for_update {
-                            // Agent database functionality removed - usage tracking disabled
-                            
-                            // Update auto-compact manager with token count
-                            if auto_compact_available {
-                                if let Some(auto_compact_state) = app_handle.try_state::<crate::commands::context_manager::AutoCompactState>() {
-                                    let auto_compact_state_clone = auto_compact_state.inner().clone();
-                                    let session_id_for_compact = session_id_str.clone();
-
-                                    // Spawn async task to avoid blocking main output loop
-                                    tokio::spawn(async move {
-                                        match auto_compact_state_clone.0.update_session_tokens(&session_id_for_compact, total_tokens).await {
-                                            Ok(compaction_triggered) => {
-                                                if compaction_triggered {
-                                                    log::info!("Auto-compaction triggered for session {}", session_id_for_compact);
-                                                    // The actual compaction will be handled by the background monitoring thread
-                                                }
-                                            }
-                                            Err(e) => {
-                                                log::warn!("Failed to update session tokens for auto-compact: {}", e);
-                                            }
-                                        }
-                                    });
-                                }
+                    if let (Some(session_id_str), Some(auto_compact_state)) = (
+                        &session_id_for_update,
+                        app_handle.try_state::<crate::commands::context_manager::AutoCompactState>(),
+                    ) {
+                        match auto_compact_state
+                            .0
+                            .observe_stream_message(&app_handle, session_id_str, &project_path_clone, &msg)
+                            .await
+                        {
+                            Ok(true) => {
+                                log::info!("Auto-compaction triggered for session {}", session_id_str);
+                                // The actual compaction will be handled by the background monitoring thread
+                            }
+                            Ok(false) => {}
+                            Err(e) => {
+                                log::warn!("Failed to update session tokens for auto-compact: {}", e);
                             }
                         }
                     }
                 }
+
+                if let Some(session_id_str) = session_id_holder_clone.lock().unwrap().as_deref() {
+                    crate::commands::context_monitor::observe_message(
+                        &app_handle,
+                        session_id_str,
+                        &project_path_clone,
+                        &msg,
+                    );
+                    super::activity_summaries::observe_message(
+                        &app_handle,
+                        session_id_str,
+                        &project_path_clone,
+                        &msg,
+                    );
+                }
             }
             
             // Store live output in registry if we have a run_id
@@ -2238,25 +2364,37 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
             
             // Emit the line to the frontend with session isolation if we have session ID
             if let Some(ref session_id) = *session_id_holder_clone.lock().unwrap() {
-                let _ = app_handle.emit(&format!("claude-output:{}", session_id), &line);
+                let channel = format!("claude-output:{}", session_id);
+                events::emit_stream(&app_handle, Some(&project_path_clone), &channel, &line);
+                super::output_mirror::mirror_line(&app_handle, session_id, &line);
             }
             // Also emit to the generic event for backward compatibility and early messages
-            let _ = app_handle.emit("claude-output", &line);
+            events::emit_stream(&app_handle, Some(&project_path_clone), "claude-output", &line);
         }
-    });
+    }.instrument(stream_span));
 
     let app_handle_stderr = app.clone();
     let session_id_holder_clone2 = session_id_holder.clone();
+    let project_path_stderr = project_path.clone();
     let stderr_task = tokio::spawn(async move {
         let mut lines = stderr_reader.lines();
         while let Ok(Some(line)) = lines.next_line().await {
             log::error!("Claude stderr: {}", line);
             // Emit error lines to the frontend with session isolation if we have session ID
-            if let Some(ref session_id) = *session_id_holder_clone2.lock().unwrap() {
-                let _ = app_handle_stderr.emit(&format!("claude-error:{}", session_id), &line);
+            let session_id = session_id_holder_clone2.lock().unwrap().clone();
+            if session_id.is_some() {
+                events::emit(&app_handle_stderr, AppEvent::ClaudeError {
+                    session_id,
+                    project_path: Some(project_path_stderr.clone()),
+                    message: line.clone(),
+                });
             }
             // Also emit to the generic event for backward compatibility
-            let _ = app_handle_stderr.emit("claude-error", &line);
+            events::emit(&app_handle_stderr, AppEvent::ClaudeError {
+                session_id: None,
+                project_path: Some(project_path_stderr.clone()),
+                message: line,
+            });
         }
     });
 
@@ -2272,28 +2410,46 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
 
         // Get the child from the state to wait on it
         let mut current_process = claude_state_wait.lock().await;
+        let mut exited_cleanly = false;
         if let Some(mut child) = current_process.take() {
-            match child.wait().await {
+            let waited = child.wait().await;
+            if let Ok(status) = waited {
+                audit.finish(&Ok(status));
+            }
+            match waited {
                 Ok(status) => {
+                    exited_cleanly = status.success();
                     log::info!("Claude process exited with status: {}", status);
                     // Add a small delay to ensure all messages are processed
                     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
                     if let Some(ref session_id) = *session_id_holder_clone3.lock().unwrap() {
                         // ✨ Phase 2: Emit state change event
-                        let event_payload = serde_json::json!({
-                            "session_id": session_id,
-                            "status": "stopped",
-                            "success": status.success(),
-                        });
-                        let _ = app_handle_wait.emit("claude-session-state", &event_payload);
+                        events::emit(&app_handle_wait, AppEvent::ClaudeSessionState(events::ClaudeSessionState {
+                            session_id: session_id.clone(),
+                            project_path: project_path.clone(),
+                            status: "stopped".to_string(),
+                            model: None,
+                            pid: None,
+                            run_id: None,
+                            env_profile: None,
+                            success: Some(status.success()),
+                            error: None,
+                        }));
                         
-                        let _ = app_handle_wait.emit(
-                            &format!("claude-complete:{}", session_id),
+                        events::emit(&app_handle_wait, AppEvent::ClaudeComplete {
+                            session_id: Some(session_id.clone()),
+                            project_path: Some(project_path.clone()),
+                            success: status.success(),
+                        });
+                        super::notifications::notify_session_finished(
+                            &app_handle_wait,
+                            session_id,
+                            &project_path,
                             status.success(),
                         );
                     }
                     // Also emit to the generic event for backward compatibility
-                    let _ = app_handle_wait.emit("claude-complete", status.success());
+                    events::emit(&app_handle_wait, AppEvent::ClaudeComplete { session_id: None, project_path: Some(project_path.clone()), success: status.success() });
                 }
                 Err(e) => {
                     log::error!("Failed to wait for Claude process: {}", e);
@@ -2301,23 +2457,51 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
                     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
                     if let Some(ref session_id) = *session_id_holder_clone3.lock().unwrap() {
                         // ✨ Phase 2: Emit state change event for error case
-                        let event_payload = serde_json::json!({
-                            "session_id": session_id,
-                            "status": "stopped",
-                            "success": false,
-                            "error": e.to_string(),
-                        });
-                        let _ = app_handle_wait.emit("claude-session-state", &event_payload);
+                        events::emit(&app_handle_wait, AppEvent::ClaudeSessionState(events::ClaudeSessionState {
+                            session_id: session_id.clone(),
+                            project_path: project_path.clone(),
+                            status: "stopped".to_string(),
+                            model: None,
+                            pid: None,
+                            run_id: None,
+                            env_profile: None,
+                            success: Some(false),
+                            error: Some(e.to_string()),
+                        }));
                         
-                        let _ = app_handle_wait
-                            .emit(&format!("claude-complete:{}", session_id), false);
+                        events::emit(&app_handle_wait, AppEvent::ClaudeComplete {
+                            session_id: Some(session_id.clone()),
+                            project_path: Some(project_path.clone()),
+                            success: false,
+                        });
+                        super::notifications::notify_session_finished(
+                            &app_handle_wait,
+                            session_id,
+                            &project_path,
+                            false,
+                        );
                     }
                     // Also emit to the generic event for backward compatibility
-                    let _ = app_handle_wait.emit("claude-complete", false);
+                    events::emit(&app_handle_wait, AppEvent::ClaudeComplete { session_id: None, project_path: Some(project_path.clone()), success: false });
                 }
             }
         }
 
+        // The turn is over; a session whose end hooks already ran stays ended
+        let exited_session = session_id_holder_clone3.lock().unwrap().clone();
+        if let Some(session_id) = exited_session {
+            if super::session_state::phase(&app_handle_wait, &session_id)
+                != Some(super::session_state::SessionPhase::Ended)
+            {
+                super::session_state::observe(
+                    &app_handle_wait,
+                    &session_id,
+                    super::session_state::SessionPhase::Idle,
+                    None,
+                );
+            }
+        }
+
         // Unregister from ProcessRegistry if we have a run_id
         if let Some(run_id) = *run_id_holder_clone2.lock().unwrap() {
             let _ = registry_clone2.unregister_process(run_id);
@@ -2325,6 +2509,13 @@ async fn spawn_claude_process(app: AppHandle, mut cmd: Command, prompt: String,
 
         // Clear the process from state
         *current_process = None;
+        drop(current_process);
+
+        // Send the next prompt queued while this turn was running
+        let finished_session = session_id_holder_clone3.lock().unwrap().clone();
+        if let Some(session_id) = finished_session {
+            super::prompt_queue::on_session_stopped(&app_handle_wait, &session_id, exited_cleanly);
+        }
     });
 
     Ok(())
@@ -2524,95 +2715,110 @@ fn search_files_recursive(
     Ok(())
 }
 
-/// Gets hooks configuration from settings at specified scope
-#[tauri::command]
-pub async fn get_hooks_config(scope: String, project_path: Option<String>) -> Result<serde_json::Value, String> {
-    log::info!("Getting hooks config for scope: {}, project: {:?}", scope, project_path);
-
-    let settings_path = match scope.as_str() {
+/// Path of the settings file holding hooks at a scope
+fn hooks_settings_path(scope: &str, project_path: Option<String>) -> Result<PathBuf, String> {
+    match scope {
         "user" => {
-            get_claude_dir()
+            Ok(get_claude_dir()
                 .map_err(|e| e.to_string())?
-                .join("settings.json")
+                .join("settings.json"))
         },
         "project" => {
             let path = project_path.ok_or("Project path required for project scope")?;
-            PathBuf::from(path).join(".claude").join("settings.json")
+            Ok(PathBuf::from(path).join(".claude").join("settings.json"))
         },
         "local" => {
             let path = project_path.ok_or("Project path required for local scope")?;
-            PathBuf::from(path).join(".claude").join("settings.local.json")
+            Ok(PathBuf::from(path).join(".claude").join("settings.local.json"))
         },
-        _ => return Err("Invalid scope".to_string())
-    };
+        _ => Err("Invalid scope".to_string())
+    }
+}
 
+/// Reads the hooks section of the settings at a scope, with the raw file content
+fn read_hooks_config(settings_path: &Path) -> Result<(serde_json::Value, Option<String>), String> {
     if !settings_path.exists() {
         log::info!("Settings file does not exist at {:?}, returning empty hooks", settings_path);
-        return Ok(serde_json::json!({}));
+        return Ok((serde_json::json!({}), None));
     }
 
-    let content = fs::read_to_string(&settings_path)
+    let content = fs::read_to_string(settings_path)
         .map_err(|e| format!("Failed to read settings: {}", e))?;
     
     let settings: serde_json::Value = serde_json::from_str(&content)
         .map_err(|e| format!("Failed to parse settings: {}", e))?;
     
-    Ok(settings.get("hooks").cloned().unwrap_or(serde_json::json!({})))
+    let hooks = settings.get("hooks").cloned().unwrap_or(serde_json::json!({}));
+    Ok((hooks, Some(content)))
 }
 
-/// Updates hooks configuration in settings at specified scope
+/// Gets hooks configuration at a scope without recording it as an editor's base
+pub(crate) fn load_hooks_config(scope: &str, project_path: Option<String>) -> Result<serde_json::Value, String> {
+    let settings_path = hooks_settings_path(scope, project_path)?;
+    read_hooks_config(&settings_path).map(|(hooks, _)| hooks)
+}
+
+/// Hooks of a settings file, with the hash of the file they came from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HooksConfig {
+    pub hooks: serde_json::Value,
+    /// Passed back to `update_hooks_config` as the version the edit is based on
+    pub hash: String,
+    /// Whether the save was merged with changes made on disk
+    #[serde(default)]
+    pub merged: bool,
+}
+
+/// Reads the hooks at a scope and records the file as an editor's base
+fn load_hooks_for_editor(settings_path: &Path) -> Result<HooksConfig, String> {
+    let (hooks, content) = read_hooks_config(settings_path)?;
+    let hash = super::config_merge::record_base(settings_path, content.as_deref());
+    Ok(HooksConfig { hooks, hash, merged: false })
+}
+
+/// Gets hooks configuration from settings at specified scope, with the hash of the
+/// file to pass to `update_hooks_config`
+#[tauri::command]
+pub async fn get_hooks_config(scope: String, project_path: Option<String>) -> Result<HooksConfig, String> {
+    log::info!("Getting hooks config for scope: {}, project: {:?}", scope, project_path);
+
+    let settings_path = hooks_settings_path(&scope, project_path)?;
+    load_hooks_for_editor(&settings_path)
+}
+
+/// Updates hooks configuration in settings at specified scope. `base_hash` is the
+/// hash `get_hooks_config` returned for the version the edit started from; if the
+/// file changed since, the edit is merged with those changes, and when both touched
+/// the same hooks the save fails with a conflict to resolve with
+/// `resolve_config_conflict`. Returns the hooks as saved with the file's new hash.
 #[tauri::command]
 pub async fn update_hooks_config(
     scope: String, 
     hooks: serde_json::Value,
+    base_hash: String,
     project_path: Option<String>
-) -> Result<String, String> {
+) -> Result<HooksConfig, WorkbenchError> {
     log::info!("Updating hooks config for scope: {}, project: {:?}", scope, project_path);
 
-    let settings_path = match scope.as_str() {
-        "user" => {
-            get_claude_dir()
-                .map_err(|e| e.to_string())?
-                .join("settings.json")
-        },
-        "project" => {
-            let path = project_path.ok_or("Project path required for project scope")?;
-            let claude_dir = PathBuf::from(path).join(".claude");
-            fs::create_dir_all(&claude_dir)
-                .map_err(|e| format!("Failed to create .claude directory: {}", e))?;
-            claude_dir.join("settings.json")
-        },
-        "local" => {
-            let path = project_path.ok_or("Project path required for local scope")?;
-            let claude_dir = PathBuf::from(path).join(".claude");
-            fs::create_dir_all(&claude_dir)
-                .map_err(|e| format!("Failed to create .claude directory: {}", e))?;
-            claude_dir.join("settings.local.json")
-        },
-        _ => return Err("Invalid scope".to_string())
-    };
+    let settings_path = hooks_settings_path(&scope, project_path)?;
+    if let Some(claude_dir) = settings_path.parent() {
+        fs::create_dir_all(claude_dir)
+            .map_err(|e| format!("Failed to create .claude directory: {}", e))?;
+    }
 
-    // Read existing settings or create new
-    let mut settings = if settings_path.exists() {
-        let content = fs::read_to_string(&settings_path)
-            .map_err(|e| format!("Failed to read settings: {}", e))?;
-        serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse settings: {}", e))?
-    } else {
-        serde_json::json!({})
-    };
+    // Edit the settings as the editor loaded them, so the merge sees only its change
+    let mut settings = super::config_merge::base_version(&settings_path, &base_hash)?;
+    if !settings.is_object() {
+        settings = serde_json::json!({});
+    }
 
     // Update hooks section
     settings["hooks"] = hooks;
 
-    // Write back with pretty formatting
-    let json_string = serde_json::to_string_pretty(&settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-    
-    fs::write(&settings_path, json_string)
-        .map_err(|e| format!("Failed to write settings: {}", e))?;
-
-    Ok("Hooks configuration updated successfully".to_string())
+    let outcome = super::config_merge::save_checked(&settings_path, &base_hash, settings)?;
+    let mut saved = load_hooks_for_editor(&settings_path)?;
+    saved.merged = outcome == super::config_merge::SaveOutcome::Merged;
+    Ok(saved)
 }
 
 /// Validates a hook command by dry-running it
@@ -2632,7 +2838,7 @@ pub async fn validate_hook_command(command: String) -> Result<serde_json::Value,
         cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
     }
     
-    match cmd.output() {
+    match cmd.audited_output("hook") {
         Ok(output) => {
             if output.status.success() {
                 Ok(serde_json::json!({
@@ -2676,7 +2882,7 @@ pub async fn set_custom_claude_path(app: AppHandle, custom_path: String) -> Resu
         cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
     }
     
-    match cmd.output() {
+    match cmd.audited_output("claude") {
         Ok(output) => {
             if !output.status.success() {
                 return Err("File is not a valid Claude CLI executable".to_string());
@@ -2688,7 +2894,7 @@ pub async fn set_custom_claude_path(app: AppHandle, custom_path: String) -> Resu
     }
     
     // Store the custom path in database
-    if let Ok(app_data_dir) = app.path().app_data_dir() {
+    if let Ok(app_data_dir) = crate::paths::app_data_dir(&app) {
         if let Err(e) = std::fs::create_dir_all(&app_data_dir) {
             return Err(format!("Failed to create app data directory: {}", e));
         }
@@ -2710,7 +2916,7 @@ pub async fn set_custom_claude_path(app: AppHandle, custom_path: String) -> Resu
                 // Store the custom path
                 if let Err(e) = conn.execute(
                     "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
-                    rusqlite::params!["claude_binary_path", custom_path],
+                    rusqlite::params!["claude_binary_path", crate::paths::to_stored(&custom_path)],
                 ) {
                     return Err(format!("Failed to store custom Claude path: {}", e));
                 }
@@ -2731,7 +2937,7 @@ pub async fn get_claude_path(app: AppHandle) -> Result<String, String> {
     log::info!("Getting current Claude CLI path");
     
     // Try to get from database first
-    if let Ok(app_data_dir) = app.path().app_data_dir() {
+    if let Ok(app_data_dir) = crate::paths::app_data_dir(&app) {
         let db_path = app_data_dir.join("agents.db");
         if db_path.exists() {
             if let Ok(conn) = rusqlite::Connection::open(&db_path) {
@@ -2740,6 +2946,7 @@ pub async fn get_claude_path(app: AppHandle) -> Result<String, String> {
                     [],
                     |row| row.get::<_, String>(0),
                 ) {
+                    let stored_path = crate::paths::from_stored(&stored_path);
                     log::info!("Found stored Claude path: {}", stored_path);
                     return Ok(stored_path);
                 }
@@ -2762,7 +2969,7 @@ pub async fn get_claude_path(app: AppHandle) -> Result<String, String> {
 pub async fn clear_custom_claude_path(app: AppHandle) -> Result<(), String> {
     log::info!("Clearing custom Claude CLI path");
     
-    if let Ok(app_data_dir) = app.path().app_data_dir() {
+    if let Ok(app_data_dir) = crate::paths::app_data_dir(&app) {
         let db_path = app_data_dir.join("agents.db");
         if db_path.exists() {
             match rusqlite::Connection::open(&db_path) {
@@ -2891,8 +3098,8 @@ pub async fn enhance_prompt(
     }
 
     // 启动进程
-    let mut child = command
-        .spawn()
+    let (mut child, audit) = command
+        .audited_spawn("claude")
         .map_err(|e| format!("无法启动Claude Code命令: {}. 请确保Claude Code已正确安装并登录。", e))?;
 
     // 写入增强请求到stdin
@@ -2905,7 +3112,9 @@ pub async fn enhance_prompt(
     }
 
     // 等待命令完成并获取输出
-    let output = child.wait_with_output().await
+    let output = child.wait_with_output().await;
+    audit.finish_output(&output);
+    let output = output
         .map_err(|e| format!("等待Claude Code命令完成失败: {}", e))?;
 
     if !output.status.success() {
@@ -3029,8 +3238,8 @@ pub async fn enhance_prompt_with_gemini(
     log::info!("=== ENHANCE_PROMPT_WITH_GEMINI DEBUG: Attempting to spawn Gemini CLI process...");
 
     // 启动进程
-    let mut child = command
-        .spawn()
+    let (mut child, audit) = command
+        .audited_spawn("gemini")
         .map_err(|e| format!("无法启动Gemini CLI命令: {}. 请确保Gemini CLI已正确安装并配置。可以运行 'npm install -g @google/gemini-cli' 进行安装。", e))?;
 
     log::info!("=== ENHANCE_PROMPT_WITH_GEMINI DEBUG: Gemini CLI process spawned successfully");
@@ -3045,7 +3254,9 @@ pub async fn enhance_prompt_with_gemini(
     }
 
     // 等待命令完成并获取输出
-    let output = child.wait_with_output().await
+    let output = child.wait_with_output().await;
+    audit.finish_output(&output);
+    let output = output
         .map_err(|e| format!("等待Gemini CLI命令完成失败: {}", e))?;
 
     if !output.status.success() {
@@ -3135,7 +3346,7 @@ async fn find_gemini_executable() -> Result<String, String> {
             cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW flag
         }
         
-        if let Ok(output) = cmd.output().await {
+        if let Ok(output) = cmd.audited_output("gemini").await {
             if output.status.success() {
                 log::info!("=== ENHANCE_PROMPT_WITH_GEMINI DEBUG: Found Gemini CLI at: {}", path);
                 return Ok(path.clone());
@@ -3165,7 +3376,7 @@ async fn find_gemini_executable() -> Result<String, String> {
                         cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW flag
                     }
                     
-                    if let Ok(output) = cmd.output().await {
+                    if let Ok(output) = cmd.audited_output("gemini").await {
                         if output.status.success() {
                             log::info!("=== ENHANCE_PROMPT_WITH_GEMINI DEBUG: Found Gemini CLI at: {}", path_str);
                             return Ok(path_str.to_string());
@@ -3186,7 +3397,7 @@ async fn find_gemini_executable() -> Result<String, String> {
         npm_cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW flag
     }
     
-    if let Ok(output) = npm_cmd.output().await {
+    if let Ok(output) = npm_cmd.audited_output("gemini").await {
         if output.status.success() {
             let prefix_string = String::from_utf8_lossy(&output.stdout);
             let prefix = prefix_string.trim();
@@ -3223,7 +3434,7 @@ async fn find_claude_executable() -> Result<String, String> {
             cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW flag
         }
         
-        if let Ok(output) = cmd.output().await {
+        if let Ok(output) = cmd.audited_output("claude").await {
             if output.status.success() {
                 log::info!("Found Claude Code at: {}", path);
                 return Ok(path.clone());
@@ -3253,7 +3464,7 @@ async fn find_claude_executable() -> Result<String, String> {
                         cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW flag
                     }
                     
-                    if let Ok(output) = cmd.output().await {
+                    if let Ok(output) = cmd.audited_output("claude").await {
                         if output.status.success() {
                             log::info!("Found Claude Code at: {}", path_str);
                             return Ok(path_str.to_string());
@@ -3274,7 +3485,7 @@ async fn find_claude_executable() -> Result<String, String> {
         npm_cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW flag
     }
     
-    if let Ok(output) = npm_cmd.output().await
+    if let Ok(output) = npm_cmd.audited_output("claude").await
     {
         if output.status.success() {
             let prefix_string = String::from_utf8_lossy(&output.stdout);
@@ -0,0 +1,302 @@
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+use super::claude::get_claude_dir;
+use super::config_io;
+
+/// Directories never searched for nested memory files
+const SKIPPED_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    ".git",
+    "dist",
+    "build",
+    ".next",
+    "__pycache__",
+];
+
+/// Maximum directory depth searched for nested CLAUDE.md files
+const MAX_NESTED_DEPTH: usize = 8;
+
+/// A CLAUDE.md memory file with its place in the hierarchy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaudeMdEntry {
+    /// Scope: "user", "parent", "project", "local" or "nested"
+    pub scope: String,
+    /// Absolute path to the file
+    pub path: String,
+    /// Path relative to the project root (None for user/parent scope)
+    pub relative_path: Option<String>,
+    /// Whether the file exists yet (the user/project/local slots are always listed)
+    pub exists: bool,
+    /// File size in bytes
+    pub size: u64,
+    /// Last modified timestamp
+    pub modified: u64,
+}
+
+/// A CLAUDE.md memory file together with its content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaudeMdDocument {
+    #[serde(flatten)]
+    pub entry: ClaudeMdEntry,
+    pub content: String,
+}
+
+/// Result of saving a memory file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaudeMdSaveResult {
+    pub path: String,
+    /// Backup of the previous content, if there was any
+    pub backup_path: Option<String>,
+}
+
+/// Builds a hierarchy entry for a path, whether or not it exists
+fn build_entry(scope: &str, path: &Path, project_root: Option<&Path>) -> ClaudeMdEntry {
+    let metadata = fs::metadata(path).ok();
+    let modified = metadata
+        .as_ref()
+        .and_then(|m| m.modified().ok())
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    ClaudeMdEntry {
+        scope: scope.to_string(),
+        path: path.to_string_lossy().to_string(),
+        relative_path: project_root
+            .and_then(|root| path.strip_prefix(root).ok())
+            .map(|p| p.to_string_lossy().to_string()),
+        exists: metadata.as_ref().map(|m| m.is_file()).unwrap_or(false),
+        size: metadata.as_ref().map(|m| m.len()).unwrap_or(0),
+        modified,
+    }
+}
+
+/// Resolves the file path for a scope
+///
+/// `path` is the project root for "project"/"local" and the file itself for
+/// "parent"/"nested"; it is ignored for "user". A "parent" file must sit in an
+/// ancestor of `project_path` and a "nested" one inside it.
+fn resolve_claude_md_path(
+    scope: &str,
+    path: Option<&str>,
+    project_path: Option<&str>,
+) -> Result<PathBuf, String> {
+    match scope {
+        "user" => Ok(get_claude_dir()
+            .map_err(|e| e.to_string())?
+            .join("CLAUDE.md")),
+        "project" => {
+            let root = PathBuf::from(path.ok_or("Project path required for project scope")?);
+            // Prefer .claude/CLAUDE.md only when it is the file the project already uses
+            let dotted = root.join(".claude").join("CLAUDE.md");
+            if !root.join("CLAUDE.md").exists() && dotted.exists() {
+                Ok(dotted)
+            } else {
+                Ok(root.join("CLAUDE.md"))
+            }
+        }
+        "local" => Ok(
+            PathBuf::from(path.ok_or("Project path required for local scope")?)
+                .join("CLAUDE.local.md"),
+        ),
+        "parent" | "nested" => {
+            let file = PathBuf::from(path.ok_or("File path required for this scope")?);
+            let root = project_path.ok_or("Project path required for this scope")?;
+            memory_file_in_hierarchy(scope, &file, Path::new(root))
+        }
+        _ => Err(format!("Invalid scope: {}", scope)),
+    }
+}
+
+/// Checks that `file` is a CLAUDE.md or CLAUDE.local.md in an ancestor of the
+/// project ("parent") or inside it ("nested"), resolving symlinks. The file
+/// itself may not exist yet.
+fn memory_file_in_hierarchy(
+    scope: &str,
+    file: &Path,
+    project_root: &Path,
+) -> Result<PathBuf, String> {
+    let name = file
+        .file_name()
+        .and_then(|n| n.to_str())
+        .filter(|n| {
+            n.eq_ignore_ascii_case("CLAUDE.md") || n.eq_ignore_ascii_case("CLAUDE.local.md")
+        })
+        .ok_or_else(|| format!("Not a CLAUDE.md file: {}", file.display()))?;
+    let resolve = |dir: &Path| {
+        dir.canonicalize()
+            .map_err(|e| format!("Failed to resolve {}: {}", dir.display(), e))
+    };
+    let root = resolve(project_root)?;
+    let dir = resolve(file.parent().unwrap_or(Path::new("")))?;
+    let in_hierarchy = match scope {
+        "parent" => root.starts_with(&dir),
+        _ => dir.starts_with(&root),
+    };
+    if !in_hierarchy {
+        return Err(format!(
+            "{} is not a {} memory file of {}",
+            file.display(),
+            scope,
+            project_root.display()
+        ));
+    }
+    Ok(dir.join(name))
+}
+
+/// Collects CLAUDE.md files in ancestor directories of the project (what Claude reads upward)
+fn find_parent_claude_md(project_root: &Path, user_file: &Path) -> Vec<ClaudeMdEntry> {
+    let mut entries = Vec::new();
+
+    for ancestor in project_root.ancestors().skip(1) {
+        let candidate = ancestor.join("CLAUDE.md");
+        if candidate.is_file() && candidate != user_file {
+            entries.push(build_entry("parent", &candidate, None));
+        }
+    }
+
+    // Outermost first, matching the order Claude loads them in
+    entries.reverse();
+    entries
+}
+
+/// Collects CLAUDE.md files in subdirectories of the project
+fn find_nested_claude_md(project_root: &Path) -> Vec<ClaudeMdEntry> {
+    let mut entries: Vec<ClaudeMdEntry> = WalkDir::new(project_root)
+        .min_depth(1)
+        .max_depth(MAX_NESTED_DEPTH)
+        .into_iter()
+        .filter_entry(|e| {
+            let name = e.file_name().to_string_lossy();
+            !(e.file_type().is_dir()
+                && (name.starts_with('.') || SKIPPED_DIRS.contains(&name.as_ref())))
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            e.file_type().is_file()
+                && e.depth() > 1
+                && e.file_name()
+                    .to_string_lossy()
+                    .eq_ignore_ascii_case("CLAUDE.md")
+        })
+        .map(|e| build_entry("nested", e.path(), Some(project_root)))
+        .collect();
+
+    entries.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+    entries
+}
+
+/// Writes content atomically, keeping the previous version as `.bak`
+fn write_with_backup(path: &Path, content: &str) -> Result<Option<PathBuf>, String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create parent directory: {}", e))?;
+    }
+
+    let backup_path = if path.exists() {
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "CLAUDE.md".to_string());
+        let backup = path.with_file_name(format!("{}.bak", file_name));
+        fs::copy(path, &backup).map_err(|e| format!("Failed to create backup: {}", e))?;
+        Some(backup)
+    } else {
+        None
+    };
+
+    config_io::write_atomic(path, content.as_bytes())?;
+    Ok(backup_path)
+}
+
+/// Lists the full memory hierarchy for a project: user, parents, project, local and nested
+#[tauri::command]
+pub async fn list_claude_md_hierarchy(
+    project_path: Option<String>,
+) -> Result<Vec<ClaudeMdEntry>, String> {
+    info!(
+        "Listing CLAUDE.md hierarchy for project: {:?}",
+        project_path
+    );
+
+    let user_file = resolve_claude_md_path("user", None, None)?;
+    let mut entries = vec![build_entry("user", &user_file, None)];
+
+    if let Some(path) = project_path.as_deref() {
+        let root = PathBuf::from(path);
+        if !root.is_dir() {
+            return Err(format!("Project path does not exist: {}", path));
+        }
+
+        entries.extend(find_parent_claude_md(&root, &user_file));
+        entries.push(build_entry(
+            "project",
+            &resolve_claude_md_path("project", Some(path), None)?,
+            Some(&root),
+        ));
+        entries.push(build_entry(
+            "local",
+            &resolve_claude_md_path("local", Some(path), None)?,
+            Some(&root),
+        ));
+        entries.extend(find_nested_claude_md(&root));
+    }
+
+    Ok(entries)
+}
+
+/// Reads a memory file for a scope; missing files return empty content
+#[tauri::command]
+pub async fn get_claude_md(
+    scope: String,
+    path: Option<String>,
+    project_path: Option<String>,
+) -> Result<ClaudeMdDocument, String> {
+    let file = resolve_claude_md_path(&scope, path.as_deref(), project_path.as_deref())?;
+    info!("Reading {} CLAUDE.md: {:?}", scope, file);
+
+    let content = if file.is_file() {
+        fs::read_to_string(&file).map_err(|e| format!("Failed to read file: {}", e))?
+    } else {
+        String::new()
+    };
+
+    let project_root = match scope.as_str() {
+        "project" | "local" => path.as_deref().map(Path::new),
+        _ => None,
+    };
+
+    Ok(ClaudeMdDocument {
+        entry: build_entry(&scope, &file, project_root),
+        content,
+    })
+}
+
+/// Saves a memory file atomically, backing up the previous content
+#[tauri::command]
+pub async fn save_claude_md(
+    scope: String,
+    path: Option<String>,
+    project_path: Option<String>,
+    content: String,
+) -> Result<ClaudeMdSaveResult, String> {
+    let file = resolve_claude_md_path(&scope, path.as_deref(), project_path.as_deref())?;
+    info!("Saving {} CLAUDE.md: {:?}", scope, file);
+
+    let backup_path = write_with_backup(&file, &content).map_err(|e| {
+        warn!("Failed to save {:?}: {}", file, e);
+        e
+    })?;
+
+    Ok(ClaudeMdSaveResult {
+        path: file.to_string_lossy().to_string(),
+        backup_path: backup_path.map(|p| p.to_string_lossy().to_string()),
+    })
+}
@@ -0,0 +1,397 @@
+use log::{debug, warn};
+/// Validation of the Claude CLI's stream-json output
+///
+/// Every stdout line of a session goes through a `StreamValidator` before the
+/// frontend sees it. Records are checked against the shapes the app understands
+/// (`system`, `assistant`, `user`, `result`, `stream_event`), and the common ways
+/// the stream goes wrong are repaired where possible:
+///
+/// - text printed before a record on the same line (warnings, escape codes) is cut
+/// - several records written onto one line are split
+/// - a record broken over several lines is buffered until it parses
+///
+/// What cannot be repaired, or parses but has no known shape, is quarantined: kept
+/// out of the output and listed with the reason in the session's diagnostics,
+/// which `get_stream_diagnostics` returns.
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+
+use crate::error::WorkbenchError;
+
+/// Quarantined records kept per session
+const MAX_QUARANTINED: usize = 50;
+
+/// Characters of a quarantined line kept for inspection
+const MAX_EXCERPT_CHARS: usize = 2000;
+
+/// Lines a broken record may span before it is given up
+const MAX_PARTIAL_LINES: usize = 64;
+
+/// Bytes a broken record may grow to before it is given up
+const MAX_PARTIAL_BYTES: usize = 4 * 1024 * 1024;
+
+/// Sessions whose diagnostics are kept
+const MAX_SESSIONS: usize = 100;
+
+/// A record kept out of the output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantinedRecord {
+    pub at: String,
+    /// Stdout line the record started on, counted from 1
+    pub line_number: u64,
+    pub reason: String,
+    /// Start of the raw text
+    pub excerpt: String,
+}
+
+/// What happened to one session's output
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StreamDiagnostics {
+    pub session_id: Option<String>,
+    /// Stdout lines read
+    pub lines: u64,
+    /// Records passed on, by type
+    pub records: BTreeMap<String, u64>,
+    /// Repairs made, by kind: `stripped_prefix`, `split_line`, `joined_lines`
+    pub repairs: BTreeMap<String, u64>,
+    pub quarantined_count: u64,
+    /// The most recent quarantined records, oldest first
+    pub quarantined: Vec<QuarantinedRecord>,
+}
+
+impl StreamDiagnostics {
+    fn merge(&mut self, other: &StreamDiagnostics) {
+        self.lines += other.lines;
+        for (kind, count) in &other.records {
+            *self.records.entry(kind.clone()).or_default() += count;
+        }
+        for (kind, count) in &other.repairs {
+            *self.repairs.entry(kind.clone()).or_default() += count;
+        }
+        self.quarantined_count += other.quarantined_count;
+        self.quarantined.extend(other.quarantined.iter().cloned());
+        let excess = self.quarantined.len().saturating_sub(MAX_QUARANTINED);
+        self.quarantined.drain(..excess);
+    }
+}
+
+/// Diagnostics by session ID, oldest session first
+#[derive(Default)]
+pub struct StreamDiagnosticsState(Mutex<VecDeque<(String, Arc<Mutex<StreamDiagnostics>>)>>);
+
+/// Why a parsed record is not passed on, if it isn't
+fn schema_error(record: &Value) -> Option<String> {
+    let object = match record.as_object() {
+        Some(object) => object,
+        None => return Some("record is not a JSON object".to_string()),
+    };
+    let record_type = match object.get("type").and_then(Value::as_str) {
+        Some(record_type) => record_type,
+        None => return Some("record has no type".to_string()),
+    };
+    let has_str = |key: &str| object.get(key).is_some_and(Value::is_string);
+    match record_type {
+        "system" if !has_str("subtype") => Some("system record has no subtype".to_string()),
+        "system" if object["subtype"] == "init" && !has_str("session_id") => {
+            Some("init record has no session_id".to_string())
+        }
+        "system" => None,
+        "assistant" | "user" => {
+            let content = object
+                .get("message")
+                .and_then(|message| message.get("content"));
+            match content {
+                Some(content) if content.is_array() || content.is_string() => None,
+                Some(_) => Some(format!("{} message content is not a list", record_type)),
+                None => Some(format!("{} record has no message content", record_type)),
+            }
+        }
+        "result" if !has_str("subtype") => Some("result record has no subtype".to_string()),
+        "result" => None,
+        "stream_event" if !object.get("event").is_some_and(Value::is_object) => {
+            Some("stream_event record has no event".to_string())
+        }
+        "stream_event" => None,
+        other => Some(format!("unknown record type '{}'", other)),
+    }
+}
+
+/// Every JSON value in `text`, if it is nothing but JSON values
+fn parse_all(text: &str) -> Option<Vec<Value>> {
+    serde_json::Deserializer::from_str(text)
+        .into_iter::<Value>()
+        .collect::<Result<Vec<_>, _>>()
+        .ok()
+        .filter(|values| !values.is_empty())
+}
+
+/// Result of reading one line
+enum Parsed {
+    /// The values, and the repairs made to get them
+    Values(Vec<Value>, Vec<&'static str>),
+    /// A record that starts here but does not end on this line
+    Partial(String),
+    Invalid(String),
+}
+
+fn parse_line(line: &str) -> Parsed {
+    if let Ok(value) = serde_json::from_str::<Value>(line) {
+        return Parsed::Values(vec![value], Vec::new());
+    }
+    // Records are objects, so anything before the first brace is noise
+    let start = match line.find('{') {
+        Some(start) => start,
+        None => return Parsed::Invalid("not JSON".to_string()),
+    };
+    let (text, mut repairs) = match start {
+        0 => (line, Vec::new()),
+        _ => (&line[start..], vec!["stripped_prefix"]),
+    };
+    if let Some(values) = parse_all(text) {
+        if values.len() > 1 {
+            repairs.push("split_line");
+        }
+        return Parsed::Values(values, repairs);
+    }
+    match serde_json::from_str::<Value>(text) {
+        Err(e) if e.is_eof() => Parsed::Partial(text.to_string()),
+        Err(e) => Parsed::Invalid(format!("invalid JSON: {}", e)),
+        Ok(_) => Parsed::Invalid("invalid JSON".to_string()),
+    }
+}
+
+/// Checks and repairs one process's stdout, line by line
+pub struct StreamValidator {
+    app: AppHandle,
+    diagnostics: Arc<Mutex<StreamDiagnostics>>,
+    attached: bool,
+    /// Lines read from this process
+    line_number: u64,
+    /// Lines of a record that has not ended yet
+    partial: Vec<String>,
+    partial_bytes: usize,
+    partial_start: u64,
+}
+
+impl StreamValidator {
+    pub fn new(app: &AppHandle) -> Self {
+        Self {
+            app: app.clone(),
+            diagnostics: Arc::new(Mutex::new(StreamDiagnostics::default())),
+            attached: false,
+            line_number: 0,
+            partial: Vec::new(),
+            partial_bytes: 0,
+            partial_start: 0,
+        }
+    }
+
+    /// Take one stdout line; returns the records to pass on, one JSON line each
+    pub fn push_line(&mut self, line: &str) -> Vec<String> {
+        self.line_number += 1;
+        let line_number = self.line_number;
+        if let Ok(mut diagnostics) = self.diagnostics.lock() {
+            diagnostics.lines += 1;
+        }
+        if line.trim().is_empty() {
+            return Vec::new();
+        }
+
+        if !self.partial.is_empty() {
+            // A complete record on its own means the buffered one was cut off
+            if line.trim_start().starts_with('{') {
+                if let Parsed::Values(values, repairs) = parse_line(line) {
+                    self.give_up_partial("record was cut off");
+                    return self.accept(values, repairs, line, line_number);
+                }
+            }
+            self.partial_bytes += line.len();
+            self.partial.push(line.to_string());
+            // The break was either between tokens or inside a string
+            for separator in ["\n", "\\n"] {
+                let joined = self.partial.join(separator);
+                if let Some(values) = parse_all(&joined) {
+                    let start = self.partial_start;
+                    self.partial.clear();
+                    self.partial_bytes = 0;
+                    return self.accept(values, vec!["joined_lines"], &joined, start);
+                }
+            }
+            if self.partial.len() >= MAX_PARTIAL_LINES || self.partial_bytes >= MAX_PARTIAL_BYTES {
+                self.give_up_partial("record never ended");
+            }
+            return Vec::new();
+        }
+
+        match parse_line(line) {
+            Parsed::Values(values, repairs) => self.accept(values, repairs, line, line_number),
+            Parsed::Partial(text) => {
+                self.partial_bytes = text.len();
+                self.partial.push(text);
+                self.partial_start = line_number;
+                Vec::new()
+            }
+            Parsed::Invalid(reason) => {
+                self.quarantine(line_number, reason, line);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Call at the end of the stream; a record still buffered is quarantined
+    pub fn finish(&mut self) {
+        if !self.partial.is_empty() {
+            self.give_up_partial("stream ended inside a record");
+        }
+    }
+
+    fn accept(
+        &mut self,
+        values: Vec<Value>,
+        repairs: Vec<&'static str>,
+        raw: &str,
+        line_number: u64,
+    ) -> Vec<String> {
+        let repaired = !repairs.is_empty();
+        if let Ok(mut diagnostics) = self.diagnostics.lock() {
+            for repair in &repairs {
+                *diagnostics.repairs.entry(repair.to_string()).or_default() += 1;
+            }
+        }
+        if repaired {
+            debug!(
+                "Repaired stream line {} ({})",
+                line_number,
+                repairs.join(", ")
+            );
+        }
+
+        let mut records = Vec::with_capacity(values.len());
+        for value in values {
+            if let Some(reason) = schema_error(&value) {
+                self.quarantine(line_number, reason, &value.to_string());
+                continue;
+            }
+            let record_type = value["type"].as_str().unwrap_or_default().to_string();
+            if record_type == "system" && value["subtype"] == "init" {
+                if let Some(session_id) = value["session_id"].as_str() {
+                    self.attach(session_id);
+                }
+            }
+            if let Ok(mut diagnostics) = self.diagnostics.lock() {
+                *diagnostics.records.entry(record_type).or_default() += 1;
+            }
+            // Untouched lines are passed on exactly as the CLI wrote them
+            records.push(if repaired {
+                value.to_string()
+            } else {
+                raw.to_string()
+            });
+        }
+        records
+    }
+
+    fn give_up_partial(&mut self, reason: &str) {
+        let text = self.partial.join("\n");
+        self.partial.clear();
+        self.partial_bytes = 0;
+        self.quarantine(self.partial_start, reason.to_string(), &text);
+    }
+
+    fn quarantine(&mut self, line_number: u64, reason: String, raw: &str) {
+        let mut diagnostics = match self.diagnostics.lock() {
+            Ok(diagnostics) => diagnostics,
+            Err(_) => return,
+        };
+        warn!(
+            "Quarantined stream line {}{}: {}",
+            line_number,
+            diagnostics
+                .session_id
+                .as_deref()
+                .map(|id| format!(" of session {}", id))
+                .unwrap_or_default(),
+            reason
+        );
+        diagnostics.quarantined_count += 1;
+        if diagnostics.quarantined.len() == MAX_QUARANTINED {
+            diagnostics.quarantined.remove(0);
+        }
+        diagnostics.quarantined.push(QuarantinedRecord {
+            at: chrono::Utc::now().to_rfc3339(),
+            line_number,
+            reason,
+            excerpt: raw.chars().take(MAX_EXCERPT_CHARS).collect(),
+        });
+    }
+
+    /// File the diagnostics under the session once its ID is known; a resumed
+    /// session adds to what earlier runs recorded
+    fn attach(&mut self, session_id: &str) {
+        if self.attached {
+            return;
+        }
+        self.attached = true;
+        let state = match self.app.try_state::<StreamDiagnosticsState>() {
+            Some(state) => state,
+            None => return,
+        };
+        let mut sessions = match state.0.lock() {
+            Ok(sessions) => sessions,
+            Err(_) => return,
+        };
+        let existing = sessions
+            .iter()
+            .position(|(id, _)| id == session_id)
+            .and_then(|index| sessions.remove(index));
+        let diagnostics = match existing {
+            Some((_, existing)) => {
+                if let (Ok(mut existing), Ok(current)) = (existing.lock(), self.diagnostics.lock())
+                {
+                    existing.merge(&current);
+                }
+                existing
+            }
+            None => {
+                if let Ok(mut current) = self.diagnostics.lock() {
+                    current.session_id = Some(session_id.to_string());
+                }
+                self.diagnostics.clone()
+            }
+        };
+        self.diagnostics = diagnostics.clone();
+        sessions.push_back((session_id.to_string(), diagnostics));
+        if sessions.len() > MAX_SESSIONS {
+            sessions.pop_front();
+        }
+    }
+}
+
+/// Diagnostics of a session, if any are kept
+pub fn session_diagnostics(app: &AppHandle, session_id: &str) -> Option<StreamDiagnostics> {
+    let state = app.try_state::<StreamDiagnosticsState>()?;
+    let sessions = state.0.lock().ok()?;
+    let (_, diagnostics) = sessions.iter().find(|(id, _)| id == session_id)?;
+    let diagnostics = diagnostics.lock().ok()?.clone();
+    Some(diagnostics)
+}
+
+// ============ Tauri Commands ============
+
+/// How a session's CLI output was validated: records by type, repairs made and
+/// the records that were quarantined
+#[tauri::command]
+pub async fn get_stream_diagnostics(
+    app: AppHandle,
+    session_id: String,
+) -> Result<StreamDiagnostics, WorkbenchError> {
+    session_diagnostics(&app, &session_id).ok_or_else(|| {
+        WorkbenchError::NotFound(format!(
+            "Stream diagnostics for session {} not found",
+            session_id
+        ))
+    })
+}
@@ -66,11 +66,8 @@ pub async fn save_clipboard_image(
 
     println!("Decoded image data size: {} bytes", image_data.len());
 
-    // 获取用户临时目录，确保使用完整路径
-    let temp_dir = std::env::var("TEMP")
-        .or_else(|_| std::env::var("TMP"))
-        .map(std::path::PathBuf::from)
-        .unwrap_or_else(|_| std::env::temp_dir());
+    // 获取用户临时目录（便携模式下在程序目录中），确保使用完整路径
+    let temp_dir = crate::paths::temp_dir();
 
     // 规范化路径，确保获得完整的长文件名路径
     let temp_dir = temp_dir.canonicalize().unwrap_or(temp_dir);
@@ -0,0 +1,615 @@
+use log::{info, warn};
+/// Shell command policy for hooks and terminals
+///
+/// When a project's policy is enabled, every hook command (including
+/// on_success / on_failure commands) and every line entered in the project's
+/// terminals is parsed into simple commands before it runs. A command is refused
+/// if it trips a built-in rule (recursive delete of `/` or `~`, piping a download
+/// into a shell, formatting disks, fork bombs), matches a blocked pattern, runs a
+/// blocked binary, or, in allowlist mode, runs a binary that is not allowed.
+/// Refusals are written to `command_audit` and announced as `command-blocked`.
+///
+/// Policies live in the app data dir rather than the project, so a cloned
+/// repository cannot switch its own policy off. Projects without a policy use
+/// the global one.
+///
+/// Parsing is deliberately simple: it splits on shell operators and quotes but
+/// does not expand variables or aliases. Terminal input is tracked keystroke by
+/// keystroke, so lines recalled from history, tab-completed or edited with the
+/// cursor keys cannot be checked; while a policy is enabled such lines are
+/// refused. Hook commands are always checked in full.
+use regex::Regex;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+use super::config_io::write_atomic;
+use super::redaction;
+use super::storage::AgentDb;
+use crate::error::{WorkbenchError, WorkbenchResult};
+use crate::events::{self, AppEvent, CommandBlocked};
+use crate::i18n;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyMode {
+    /// Everything runs except what is blocked
+    #[default]
+    Denylist,
+    /// Only `allowed_binaries` (and basic shell builtins) run
+    Allowlist,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandPolicy {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub mode: PolicyMode,
+    /// Binary names, e.g. `git` or `npm`; only used in allowlist mode
+    #[serde(default)]
+    pub allowed_binaries: Vec<String>,
+    #[serde(default)]
+    pub blocked_binaries: Vec<String>,
+    /// Regexes matched against the whole command
+    #[serde(default)]
+    pub blocked_patterns: Vec<String>,
+    #[serde(default = "default_true")]
+    pub builtin_rules: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for CommandPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: PolicyMode::Denylist,
+            allowed_binaries: Vec::new(),
+            blocked_binaries: Vec::new(),
+            blocked_patterns: Vec::new(),
+            builtin_rules: true,
+        }
+    }
+}
+
+/// Why a command was refused
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyViolation {
+    /// e.g. `rm-root`, `pipe-to-shell`, `blocked-binary`, `not-allowed`
+    pub rule: String,
+    pub reason: String,
+}
+
+/// A refused command, as listed by `list_command_audit`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandAuditEntry {
+    pub id: i64,
+    pub timestamp: String,
+    pub project_path: String,
+    /// "hook" or "terminal"
+    pub source: String,
+    pub command: String,
+    pub rule: String,
+    pub reason: String,
+}
+
+/// On-disk store: the global policy and per-project overrides
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CommandPolicyStore {
+    #[serde(default)]
+    global: CommandPolicy,
+    #[serde(default)]
+    projects: BTreeMap<String, CommandPolicy>,
+}
+
+fn store_file(app: &AppHandle) -> WorkbenchResult<PathBuf> {
+    let dir = crate::paths::app_data_dir(app)
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    Ok(dir.join("command_policies.json"))
+}
+
+fn load_store(app: &AppHandle) -> WorkbenchResult<CommandPolicyStore> {
+    let path = store_file(app)?;
+    if !path.exists() {
+        return Ok(CommandPolicyStore::default());
+    }
+    let content = fs::read_to_string(&path)
+        .map_err(|e| WorkbenchError::io("Failed to read command policies", e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| WorkbenchError::json("Failed to parse command policies", e))
+}
+
+fn save_store(app: &AppHandle, store: &CommandPolicyStore) -> WorkbenchResult<()> {
+    let json = serde_json::to_string_pretty(store)
+        .map_err(|e| WorkbenchError::json("Failed to serialize command policies", e))?;
+    Ok(write_atomic(&store_file(app)?, json.as_bytes())?)
+}
+
+/// The policy that applies to a project
+pub fn effective_policy(app: &AppHandle, project_path: &str) -> CommandPolicy {
+    match load_store(app) {
+        Ok(mut store) => store.projects.remove(project_path).unwrap_or(store.global),
+        Err(e) => {
+            // Fail closed: a broken policy file must not disable enforcement
+            warn!("Failed to load command policies: {}", e);
+            CommandPolicy {
+                enabled: true,
+                ..CommandPolicy::default()
+            }
+        }
+    }
+}
+
+// ============ Parsing ============
+
+/// One simple command of a command line
+struct SimpleCommand {
+    words: Vec<String>,
+    /// Receives the previous command's output through `|`
+    piped: bool,
+}
+
+/// Accumulates words and simple commands while parsing
+#[derive(Default)]
+struct LineParser {
+    commands: Vec<SimpleCommand>,
+    words: Vec<String>,
+    word: Option<String>,
+    piped: bool,
+}
+
+impl LineParser {
+    fn push_char(&mut self, c: char) {
+        self.word.get_or_insert_with(String::new).push(c);
+    }
+
+    fn end_word(&mut self) {
+        if let Some(word) = self.word.take() {
+            self.words.push(word);
+        }
+    }
+
+    /// Close the current simple command; `piped` tells whether the next one
+    /// reads its output
+    fn end_command(&mut self, piped: bool) {
+        self.end_word();
+        if !self.words.is_empty() {
+            self.commands.push(SimpleCommand {
+                words: std::mem::take(&mut self.words),
+                piped: self.piped,
+            });
+        }
+        self.piped = piped;
+    }
+}
+
+/// Split a command line into simple commands at `;`, `&&`, `||`, `|`, `&`,
+/// newlines, subshells and command substitutions, removing quotes
+fn parse_command_line(line: &str) -> Vec<SimpleCommand> {
+    let mut parser = LineParser::default();
+    let mut quote: Option<char> = None;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') => {
+                if let Some(next) = chars.next() {
+                    parser.push_char(next);
+                }
+            }
+            // Command substitutions still run inside double quotes
+            (Some('"'), '$') if chars.peek() == Some(&'(') => {
+                chars.next();
+                quote = None;
+                parser.end_command(false);
+            }
+            (Some(_), c) => parser.push_char(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                parser.word.get_or_insert_with(String::new);
+            }
+            (None, '\\') => {
+                if let Some(next) = chars.next() {
+                    parser.push_char(next);
+                }
+            }
+            (None, '$') if chars.peek() == Some(&'(') => {
+                chars.next();
+                parser.end_command(false);
+            }
+            (None, '|') => {
+                // `|` pipes, `||` does not
+                let piped = chars.next_if_eq(&'|').is_none();
+                parser.end_command(piped);
+            }
+            (None, '&') => {
+                chars.next_if_eq(&'&');
+                parser.end_command(false);
+            }
+            (None, ';' | '\n' | '(' | ')' | '{' | '}' | '`') => parser.end_command(false),
+            (None, c) if c.is_whitespace() => parser.end_word(),
+            (None, c) => parser.push_char(c),
+        }
+    }
+    parser.end_command(false);
+    parser.commands
+}
+
+/// Commands that run their arguments as another command
+const WRAPPERS: &[&str] = &[
+    "sudo", "doas", "env", "nohup", "time", "nice", "exec", "command", "builtin", "xargs",
+];
+
+/// Builtins that are always allowed in allowlist mode
+const SAFE_BUILTINS: &[&str] = &[
+    "cd", "echo", "printf", "export", "unset", "true", "false", "test", "[", "exit", "pwd",
+];
+
+const SHELLS: &[&str] = &["sh", "bash", "zsh", "dash", "ksh", "fish"];
+
+/// The binary a simple command runs, with its arguments. Leading variable
+/// assignments and wrappers like `sudo` are skipped.
+fn binary_of(words: &[String]) -> Option<(String, &[String])> {
+    let mut rest = words;
+    loop {
+        let first = rest.first()?;
+        let is_assignment = first
+            .split_once('=')
+            .is_some_and(|(name, _)| !name.is_empty() && !name.contains('/'));
+        let name = first.rsplit(['/', '\\']).next().unwrap_or(first);
+        if is_assignment {
+            rest = &rest[1..];
+        } else if WRAPPERS.contains(&name) {
+            // Skip the wrapper's own options
+            rest = &rest[1..];
+            while rest.first().is_some_and(|w| w.starts_with('-')) {
+                rest = &rest[1..];
+            }
+        } else {
+            return Some((name.to_string(), &rest[1..]));
+        }
+    }
+}
+
+fn violation(rule: &str, reason: impl Into<String>) -> PolicyViolation {
+    PolicyViolation {
+        rule: rule.to_string(),
+        reason: reason.into(),
+    }
+}
+
+/// Rules that apply whenever `builtin_rules` is on
+fn builtin_violation(line: &str, commands: &[SimpleCommand]) -> Option<PolicyViolation> {
+    static FORK_BOMB: once_cell::sync::Lazy<Regex> = once_cell::sync::Lazy::new(|| {
+        Regex::new(r":\s*\(\s*\)\s*\{\s*:\s*\|\s*:\s*&\s*\}\s*;\s*:").expect("valid regex")
+    });
+    if FORK_BOMB.is_match(line) {
+        return Some(violation("fork-bomb", i18n::t("policy-fork-bomb")));
+    }
+
+    let mut previous: Option<String> = None;
+    for command in commands {
+        let (binary, args) = match binary_of(&command.words) {
+            Some(found) => found,
+            None => continue,
+        };
+        match binary.as_str() {
+            "rm" => {
+                let flags: String = args
+                    .iter()
+                    .filter(|a| a.starts_with('-') && !a.starts_with("--"))
+                    .flat_map(|a| a.chars().skip(1))
+                    .collect();
+                let recursive =
+                    flags.contains(['r', 'R']) || args.iter().any(|a| a == "--recursive");
+                let target = args.iter().find(|a| {
+                    matches!(
+                        a.trim_end_matches('/').trim_end_matches("/*"),
+                        "" | "~" | "$HOME" | "${HOME}" | "/."
+                    ) && !a.starts_with('-')
+                });
+                if let (true, Some(target)) = (recursive, target) {
+                    return Some(violation(
+                        "rm-root",
+                        i18n::t_args("policy-rm-root", &[("target", target.as_str().into())]),
+                    ));
+                }
+            }
+            "dd" if args.iter().any(|a| a.starts_with("of=/dev/")) => {
+                return Some(violation(
+                    "raw-disk-write",
+                    i18n::t("policy-raw-disk-write"),
+                ));
+            }
+            name if name.starts_with("mkfs") => {
+                return Some(violation(
+                    "format-disk",
+                    i18n::t_args("policy-format-disk", &[("binary", name.into())]),
+                ));
+            }
+            name if command.piped
+                && SHELLS.contains(&name)
+                && matches!(previous.as_deref(), Some("curl" | "wget")) =>
+            {
+                return Some(violation(
+                    "pipe-to-shell",
+                    i18n::t_args("policy-pipe-to-shell", &[("shell", name.into())]),
+                ));
+            }
+            _ => {}
+        }
+        previous = Some(binary);
+    }
+    None
+}
+
+/// Check a command line against a policy. Disabled policies allow everything.
+pub fn check_command(policy: &CommandPolicy, line: &str) -> Option<PolicyViolation> {
+    if !policy.enabled || line.trim().is_empty() {
+        return None;
+    }
+    let commands = parse_command_line(line);
+
+    if policy.builtin_rules {
+        if let Some(violation) = builtin_violation(line, &commands) {
+            return Some(violation);
+        }
+    }
+    for pattern in &policy.blocked_patterns {
+        match Regex::new(pattern) {
+            Ok(re) if re.is_match(line) => {
+                return Some(violation(
+                    "blocked-pattern",
+                    i18n::t_args(
+                        "policy-blocked-pattern",
+                        &[("pattern", pattern.as_str().into())],
+                    ),
+                ));
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Ignoring invalid blocked pattern {}: {}", pattern, e),
+        }
+    }
+    for command in &commands {
+        let (binary, _) = match binary_of(&command.words) {
+            Some(found) => found,
+            None => continue,
+        };
+        if policy.blocked_binaries.iter().any(|b| b == &binary) {
+            return Some(violation(
+                "blocked-binary",
+                i18n::t_args(
+                    "policy-blocked-binary",
+                    &[("binary", binary.as_str().into())],
+                ),
+            ));
+        }
+        if policy.mode == PolicyMode::Allowlist
+            && !SAFE_BUILTINS.contains(&binary.as_str())
+            && !policy.allowed_binaries.iter().any(|b| b == &binary)
+        {
+            return Some(violation(
+                "not-allowed",
+                i18n::t_args("policy-not-allowed", &[("binary", binary.as_str().into())]),
+            ));
+        }
+    }
+    None
+}
+
+fn check_policy(policy: &CommandPolicy) -> WorkbenchResult<()> {
+    for pattern in &policy.blocked_patterns {
+        Regex::new(pattern).map_err(|e| {
+            WorkbenchError::InvalidInput(format!("Invalid pattern {}: {}", pattern, e))
+        })?;
+    }
+    let names = policy
+        .allowed_binaries
+        .iter()
+        .chain(&policy.blocked_binaries);
+    for name in names {
+        if name.trim().is_empty() || name.contains(['/', '\\', ' ']) {
+            return Err(WorkbenchError::InvalidInput(format!(
+                "Binary names must be bare names like `git`, got {:?}",
+                name
+            )));
+        }
+    }
+    Ok(())
+}
+
+// ============ Enforcement ============
+
+/// Log a refused command to `command_audit`, passed through redaction, and
+/// announce it
+fn record_block(
+    app: &AppHandle,
+    project_path: &str,
+    source: &str,
+    command: &str,
+    v: &PolicyViolation,
+) {
+    let timestamp = chrono::Utc::now().to_rfc3339();
+    let redactor = redaction::redactor(app);
+    if let Some(db) = app.try_state::<AgentDb>() {
+        if let Ok(conn) = db.0.lock() {
+            if let Err(e) = conn.execute(
+                "INSERT INTO command_audit (timestamp, project_path, source, command, rule, reason)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    timestamp,
+                    project_path,
+                    source,
+                    redactor.redact(command),
+                    v.rule,
+                    redactor.redact(&v.reason)
+                ],
+            ) {
+                warn!("Failed to record blocked command: {}", e);
+            }
+        }
+    }
+    events::emit(
+        app,
+        AppEvent::CommandBlocked(CommandBlocked {
+            project_path: project_path.to_string(),
+            source: source.to_string(),
+            command: command.to_string(),
+            rule: v.rule.clone(),
+            reason: v.reason.clone(),
+            timestamp,
+        }),
+    );
+}
+
+/// Refuse `command` if the project's policy blocks it; `source` ("hook" or
+/// "terminal") is recorded in the audit entry
+pub fn enforce(
+    app: &AppHandle,
+    project_path: &str,
+    command: &str,
+    source: &str,
+) -> WorkbenchResult<()> {
+    let policy = effective_policy(app, project_path);
+    match check_command(&policy, command) {
+        None => Ok(()),
+        Some(v) => Err(block(app, project_path, source, command, v)),
+    }
+}
+
+/// Refuse a terminal line that could not be followed while the project's
+/// policy is enabled; what the shell would run is unknown, so it fails closed
+pub fn enforce_untracked(app: &AppHandle, project_path: &str, source: &str) -> WorkbenchResult<()> {
+    if !effective_policy(app, project_path).enabled {
+        return Ok(());
+    }
+    let v = violation(
+        "untracked-line",
+        "the line was changed with cursor keys, history or tab completion and cannot be checked; type it out in full",
+    );
+    Err(block(app, project_path, source, "(edited line)", v))
+}
+
+fn block(
+    app: &AppHandle,
+    project_path: &str,
+    source: &str,
+    command: &str,
+    v: PolicyViolation,
+) -> WorkbenchError {
+    warn!(
+        "Blocked {} command in {} ({}): {}",
+        source, project_path, v.rule, command
+    );
+    record_block(app, project_path, source, command, &v);
+    WorkbenchError::Blocked(i18n::t_args(
+        "policy-blocked",
+        &[("reason", v.reason.as_str().into())],
+    ))
+}
+
+// ============ Tauri Commands ============
+
+/// The policy for a project (the global one if `project_path` is None), and
+/// whether it is the project's own
+#[tauri::command]
+pub async fn get_command_policy(
+    app: AppHandle,
+    project_path: Option<String>,
+) -> Result<(CommandPolicy, bool), WorkbenchError> {
+    let mut store = load_store(&app)?;
+    Ok(match project_path.and_then(|p| store.projects.remove(&p)) {
+        Some(policy) => (policy, true),
+        None => (store.global, false),
+    })
+}
+
+/// Save the policy of a project, or the global one if `project_path` is None
+#[tauri::command]
+pub async fn save_command_policy(
+    app: AppHandle,
+    project_path: Option<String>,
+    policy: CommandPolicy,
+) -> Result<(), WorkbenchError> {
+    check_policy(&policy)?;
+    let mut store = load_store(&app)?;
+    info!(
+        "Saving command policy for {} (enabled: {})",
+        project_path.as_deref().unwrap_or("all projects"),
+        policy.enabled
+    );
+    match project_path {
+        Some(project_path) => {
+            store.projects.insert(project_path, policy);
+        }
+        None => store.global = policy,
+    }
+    save_store(&app, &store)
+}
+
+/// Drop a project's own policy so the global one applies again
+#[tauri::command]
+pub async fn remove_command_policy(
+    app: AppHandle,
+    project_path: String,
+) -> Result<bool, WorkbenchError> {
+    let mut store = load_store(&app)?;
+    let removed = store.projects.remove(&project_path).is_some();
+    if removed {
+        save_store(&app, &store)?;
+    }
+    Ok(removed)
+}
+
+/// Dry run: what the project's policy would say about `command`
+#[tauri::command]
+pub async fn check_command_policy(
+    app: AppHandle,
+    project_path: String,
+    command: String,
+) -> Result<Option<PolicyViolation>, WorkbenchError> {
+    let policy = effective_policy(&app, &project_path);
+    Ok(check_command(&policy, &command))
+}
+
+/// Blocked commands, most recent first
+#[tauri::command]
+pub async fn list_command_audit(
+    app: AppHandle,
+    project_path: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<CommandAuditEntry>, WorkbenchError> {
+    let db = app
+        .try_state::<AgentDb>()
+        .ok_or("Database not initialized")?;
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare(
+        "SELECT id, timestamp, project_path, source, command, rule, reason FROM command_audit
+         WHERE ?1 IS NULL OR project_path = ?1
+         ORDER BY id DESC LIMIT ?2",
+    )?;
+    let entries = stmt
+        .query_map(params![project_path, limit.unwrap_or(200)], |row| {
+            Ok(CommandAuditEntry {
+                id: row.get(0)?,
+                timestamp: row.get(1)?,
+                project_path: row.get(2)?,
+                source: row.get(3)?,
+                command: row.get(4)?,
+                rule: row.get(5)?,
+                reason: row.get(6)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(entries)
+}
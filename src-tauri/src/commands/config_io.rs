@@ -0,0 +1,311 @@
+use log::{info, warn};
+/// Crash-safe writes for JSON config files
+///
+/// Settings, hooks and MCP configs are written to a temp file in the same
+/// directory, fsynced, re-parsed and only then renamed over the original, so a
+/// crash leaves either the old or the new file but never a truncated one. The
+/// new file keeps the old one's permissions, so a 0600 file holding tokens stays
+/// private. Before each replace the previous contents are copied to
+/// `~/.claude/config-backups/<key>/<timestamp>.json`, keeping the newest
+/// `MAX_BACKUPS` per file; the original path is recorded in `<key>/source`.
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::claude::get_claude_dir;
+
+/// Timestamped backups kept per config file
+const MAX_BACKUPS: usize = 10;
+
+/// Backup file names, in UTC; they sort chronologically
+const BACKUP_NAME_FORMAT: &str = "%Y%m%d-%H%M%S-%3f";
+
+/// File next to the backups recording which config they belong to
+const SOURCE_FILE: &str = "source";
+
+/// A saved copy of a config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigBackup {
+    /// `<key>/<file name>`, passed back to `restore_config_backup`
+    pub id: String,
+    /// Config file the backup was taken from
+    pub source_path: String,
+    pub backup_path: String,
+    pub created_at: String,
+    pub size: u64,
+}
+
+fn backups_root() -> Result<PathBuf, String> {
+    Ok(get_claude_dir()
+        .map_err(|e| e.to_string())?
+        .join("config-backups"))
+}
+
+/// Stable directory name for a config path
+fn backup_key(path: &Path) -> String {
+    let digest = Sha256::digest(path.to_string_lossy().as_bytes());
+    digest[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Copy the current contents of `path` into its backup directory and prune old copies
+fn backup_file(path: &Path) -> Result<Option<PathBuf>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let dir = backups_root()?.join(backup_key(path));
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create backup directory: {}", e))?;
+    fs::write(dir.join(SOURCE_FILE), path.to_string_lossy().as_bytes())
+        .map_err(|e| format!("Failed to create backup: {}", e))?;
+
+    let name = format!("{}.json", chrono::Utc::now().format(BACKUP_NAME_FORMAT));
+    let backup = dir.join(name);
+    fs::copy(path, &backup).map_err(|e| format!("Failed to create backup: {}", e))?;
+    copy_permissions(path, &backup)?;
+
+    // Timestamped names sort chronologically
+    let mut backups = backup_files(&dir);
+    if backups.len() > MAX_BACKUPS {
+        let excess = backups.len() - MAX_BACKUPS;
+        for old in backups.drain(..excess) {
+            if let Err(e) = fs::remove_file(&old) {
+                warn!("Failed to prune config backup {:?}: {}", old, e);
+            }
+        }
+    }
+    Ok(Some(backup))
+}
+
+/// Backup files in one backup directory, oldest first
+fn backup_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+/// Give `to` the permissions of `from`
+fn copy_permissions(from: &Path, to: &Path) -> Result<(), String> {
+    let permissions = fs::metadata(from)
+        .map_err(|e| format!("Failed to read permissions of {}: {}", from.display(), e))?
+        .permissions();
+    fs::set_permissions(to, permissions)
+        .map_err(|e| format!("Failed to set permissions of {}: {}", to.display(), e))
+}
+
+/// Owner-only access for files holding secrets
+fn restrict_to_owner(path: &Path) -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("Failed to set permissions of {}: {}", path.display(), e))?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+/// Write `content` to a synced temp file next to `path`, with the permissions
+/// of the file it replaces, or owner-only when `private`
+fn write_temp(path: &Path, content: &[u8], private: bool) -> Result<PathBuf, String> {
+    let parent = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    fs::create_dir_all(parent)
+        .map_err(|e| format!("Failed to create directory {}: {}", parent.display(), e))?;
+
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| format!("Invalid path: {}", path.display()))?;
+    let tmp_path = parent.join(format!(".{}.{}.tmp", file_name, std::process::id()));
+
+    let written = fs::File::create(&tmp_path)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+        .and_then(|mut file| {
+            // Before the content goes in, so secrets are never readable by others
+            if private {
+                restrict_to_owner(&tmp_path)?;
+            } else if path.exists() {
+                copy_permissions(path, &tmp_path)?;
+            }
+            file.write_all(content)
+                .and_then(|_| file.sync_all())
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+        });
+    match written {
+        Ok(()) => Ok(tmp_path),
+        Err(e) => {
+            let _ = fs::remove_file(&tmp_path);
+            Err(e)
+        }
+    }
+}
+
+/// Rename a temp file from `write_temp` over `path`
+fn replace_with(tmp_path: &Path, path: &Path) -> Result<(), String> {
+    if let Err(e) = fs::rename(tmp_path, path) {
+        let _ = fs::remove_file(tmp_path);
+        return Err(format!("Failed to replace {}: {}", path.display(), e));
+    }
+
+    // Persist the rename itself; directories cannot be opened for sync on Windows
+    #[cfg(unix)]
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        if let Ok(dir) = fs::File::open(parent) {
+            let _ = dir.sync_all();
+        }
+    }
+    Ok(())
+}
+
+/// Replace `path` with `content` without ever leaving a partial file, keeping
+/// its permissions. No backup is taken; config files go through
+/// `write_json_atomic`.
+pub fn write_atomic(path: &Path, content: &[u8]) -> Result<(), String> {
+    let tmp_path = write_temp(path, content, false)?;
+    replace_with(&tmp_path, path)
+}
+
+/// Like `write_atomic`, for files holding secrets: the file ends up owner-only
+/// (0600) whatever its permissions were
+pub fn write_private(path: &Path, content: &[u8]) -> Result<(), String> {
+    let tmp_path = write_temp(path, content, true)?;
+    replace_with(&tmp_path, path)
+}
+
+/// Write `value` as pretty JSON to `path` without ever leaving a partial file.
+/// Returns the backup taken of the previous contents, if there were any.
+pub fn write_json_atomic(path: &Path, value: &Value) -> Result<Option<PathBuf>, String> {
+    let content = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    let tmp_path = write_temp(path, content.as_bytes(), false)?;
+
+    // Make sure what hit the disk parses before replacing anything
+    let parsed = fs::read_to_string(&tmp_path)
+        .map_err(|e| e.to_string())
+        .and_then(|on_disk| {
+            serde_json::from_str::<Value>(&on_disk)
+                .map(|_| ())
+                .map_err(|e| format!("Refusing to save invalid JSON: {}", e))
+        });
+    if let Err(e) = parsed {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+
+    let backup = match backup_file(path) {
+        Ok(backup) => backup,
+        Err(e) => {
+            let _ = fs::remove_file(&tmp_path);
+            return Err(e);
+        }
+    };
+
+    // Our own write is not an external edit for the config watcher
+    super::config_watcher::record_write(path, &content);
+    replace_with(&tmp_path, path)?;
+    Ok(backup)
+}
+
+fn read_backups(dir: &Path) -> Vec<ConfigBackup> {
+    let key = match dir.file_name() {
+        Some(key) => key.to_string_lossy().to_string(),
+        None => return Vec::new(),
+    };
+    let source_path = fs::read_to_string(dir.join(SOURCE_FILE)).unwrap_or_default();
+
+    backup_files(dir)
+        .into_iter()
+        .filter_map(|file| {
+            let metadata = fs::metadata(&file).ok()?;
+            let name = file.file_name()?.to_string_lossy().to_string();
+            // The file name is the backup time; copies may carry the source's mtime
+            let stem = file.file_stem()?.to_string_lossy().to_string();
+            let created_at = chrono::NaiveDateTime::parse_from_str(&stem, BACKUP_NAME_FORMAT)
+                .ok()?
+                .and_utc()
+                .to_rfc3339();
+            Some(ConfigBackup {
+                id: format!("{}/{}", key, name),
+                source_path: source_path.clone(),
+                backup_path: file.to_string_lossy().to_string(),
+                created_at,
+                size: metadata.len(),
+            })
+        })
+        .collect()
+}
+
+// ============ Tauri Commands ============
+
+/// List config backups, newest first; all files unless `path` narrows it to one
+#[tauri::command]
+pub async fn list_config_backups(path: Option<String>) -> Result<Vec<ConfigBackup>, String> {
+    let root = backups_root()?;
+    let dirs: Vec<PathBuf> = match path {
+        Some(path) => vec![root.join(backup_key(Path::new(&path)))],
+        None => fs::read_dir(&root)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .map(|e| e.path())
+                    .filter(|p| p.is_dir())
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+
+    let mut backups: Vec<ConfigBackup> = dirs.iter().flat_map(|dir| read_backups(dir)).collect();
+    backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(backups)
+}
+
+/// Restore a backup over its original file. The current contents are backed up
+/// first, so a restore can itself be undone.
+#[tauri::command]
+pub async fn restore_config_backup(id: String) -> Result<ConfigBackup, String> {
+    let (key, name) = id
+        .split_once('/')
+        .ok_or_else(|| format!("Invalid backup id: {}", id))?;
+    let valid = |part: &str| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.'))
+            && !part.contains("..")
+    };
+    if !valid(key) || !valid(name) {
+        return Err(format!("Invalid backup id: {}", id));
+    }
+
+    let dir = backups_root()?.join(key);
+    let backup = read_backups(&dir)
+        .into_iter()
+        .find(|b| b.id == id)
+        .ok_or_else(|| format!("Backup {} not found", id))?;
+    if backup.source_path.is_empty() {
+        return Err(format!("Backup {} does not record its original file", id));
+    }
+
+    let content = fs::read_to_string(&backup.backup_path)
+        .map_err(|e| format!("Failed to read backup: {}", e))?;
+    let value: Value =
+        serde_json::from_str(&content).map_err(|e| format!("Backup is not valid JSON: {}", e))?;
+    write_json_atomic(Path::new(&backup.source_path), &value)?;
+
+    info!("Restored {} from backup {}", backup.source_path, id);
+    Ok(backup)
+}
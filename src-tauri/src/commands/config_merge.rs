@@ -0,0 +1,349 @@
+use log::{info, warn};
+/// Conflict-safe saves of settings files Claude Code also edits
+///
+/// The app and Claude Code both write `.claude/settings.json`, so saving what
+/// the UI loaded could silently drop a change Claude Code made in between.
+/// Reads that feed an editor are recorded with `record_base`, which returns the
+/// file's hash; the editor passes that hash back when it saves, and
+/// `save_checked` compares it with the file's current hash before writing. Each
+/// editor thus merges against the version it loaded, however often the file was
+/// read since. When the file drifted, the app's edit and the file are
+/// merged against the base key by key: changes on one side are kept, and only
+/// values both sides changed differently are conflicts. A clean merge is
+/// written; otherwise the merge is held as a `ConfigConflict` and the save
+/// fails with `WorkbenchError::Conflict`, which `resolve_config_conflict`
+/// settles.
+///
+/// Objects merge per key; arrays and scalars are compared whole.
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::config_io::write_json_atomic;
+use crate::error::{WorkbenchError, WorkbenchResult};
+
+/// Content of a config file as the app last read or wrote it
+#[derive(Debug, Clone)]
+struct Base {
+    hash: String,
+    value: Value,
+}
+
+/// Versions of each config file loaded into editors, oldest first
+static BASES: Lazy<Mutex<HashMap<PathBuf, Vec<Base>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Loaded versions remembered per file
+const MAX_BASES: usize = 16;
+
+/// Unresolved conflicts by id
+static CONFLICTS: Lazy<Mutex<HashMap<String, ConfigConflict>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// A value both sides changed differently
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictEntry {
+    /// JSON pointer of the value, e.g. `/hooks/PreToolUse`
+    pub pointer: String,
+    /// None where the key is absent
+    pub base: Option<Value>,
+    pub ours: Option<Value>,
+    pub theirs: Option<Value>,
+}
+
+/// A save held back because the file changed since it was read
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigConflict {
+    pub id: String,
+    pub path: String,
+    /// The file as the app read it
+    pub base: Value,
+    /// What the app tried to save
+    pub ours: Value,
+    /// The file as it is now
+    pub theirs: Value,
+    /// Merge taking the app's side of each conflict
+    pub merged: Value,
+    pub conflicts: Vec<ConflictEntry>,
+    pub created_at: String,
+    #[serde(skip)]
+    theirs_hash: String,
+}
+
+/// How `save_checked` wrote the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveOutcome {
+    Written,
+    /// Merged with changes made on disk since the file was read
+    Merged,
+}
+
+fn content_hash(content: &str) -> String {
+    let digest = Sha256::digest(content.as_bytes());
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Hash and content of a config file now; a missing file is empty
+fn read_current(path: &Path) -> WorkbenchResult<(String, Value)> {
+    if !path.exists() {
+        return Ok((content_hash(""), Value::Object(Map::new())));
+    }
+    let content = fs::read_to_string(path)
+        .map_err(|e| WorkbenchError::io(format!("Failed to read {}", path.display()), e))?;
+    let value = serde_json::from_str(&content)
+        .map_err(|e| WorkbenchError::json(format!("Failed to parse {}", path.display()), e))?;
+    Ok((content_hash(&content), value))
+}
+
+/// Remember the content of a config file as loaded into an editor (None when
+/// the file does not exist) and return its hash, which the editor passes to
+/// `save_checked`. Invalid JSON is not remembered.
+pub fn record_base(path: &Path, content: Option<&str>) -> String {
+    let content = content.unwrap_or("");
+    let hash = content_hash(content);
+    let value = if content.is_empty() {
+        Ok(Value::Object(Map::new()))
+    } else {
+        serde_json::from_str(content)
+    };
+    if let (Ok(value), Ok(mut bases)) = (value, BASES.lock()) {
+        let versions = bases.entry(path.to_path_buf()).or_default();
+        versions.retain(|b| b.hash != hash);
+        versions.push(Base {
+            hash: hash.clone(),
+            value,
+        });
+        if versions.len() > MAX_BASES {
+            versions.remove(0);
+        }
+    }
+    hash
+}
+
+/// A version of the file `record_base` saw
+fn find_base(path: &Path, hash: &str) -> Option<Base> {
+    let bases = BASES.lock().ok()?;
+    bases.get(path)?.iter().find(|b| b.hash == hash).cloned()
+}
+
+fn record_written(path: &Path) {
+    match fs::read_to_string(path) {
+        Ok(content) => {
+            record_base(path, Some(&content));
+        }
+        Err(e) => warn!("Failed to re-read {}: {}", path.display(), e),
+    }
+}
+
+fn merge_value(
+    base: Option<&Value>,
+    ours: Option<&Value>,
+    theirs: Option<&Value>,
+    pointer: &str,
+    prefer_ours: bool,
+    conflicts: &mut Vec<ConflictEntry>,
+) -> Option<Value> {
+    if ours == theirs || theirs == base {
+        return ours.cloned();
+    }
+    if ours == base {
+        return theirs.cloned();
+    }
+    if let (Some(Value::Object(o)), Some(Value::Object(t))) = (ours, theirs) {
+        let empty = Map::new();
+        let b = match base {
+            Some(Value::Object(b)) => b,
+            _ => &empty,
+        };
+        let keys: BTreeSet<&String> = b.keys().chain(o.keys()).chain(t.keys()).collect();
+        let mut merged = Map::new();
+        for key in keys {
+            let child = format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"));
+            let value = merge_value(
+                b.get(key),
+                o.get(key),
+                t.get(key),
+                &child,
+                prefer_ours,
+                conflicts,
+            );
+            if let Some(value) = value {
+                merged.insert(key.clone(), value);
+            }
+        }
+        return Some(Value::Object(merged));
+    }
+    conflicts.push(ConflictEntry {
+        pointer: pointer.to_string(),
+        base: base.cloned(),
+        ours: ours.cloned(),
+        theirs: theirs.cloned(),
+    });
+    if prefer_ours {
+        ours.cloned()
+    } else {
+        theirs.cloned()
+    }
+}
+
+/// Three-way merge of JSON documents; conflicting values take `ours` or
+/// `theirs` as `prefer_ours` says
+pub fn three_way_merge(
+    base: &Value,
+    ours: &Value,
+    theirs: &Value,
+    prefer_ours: bool,
+) -> (Value, Vec<ConflictEntry>) {
+    let mut conflicts = Vec::new();
+    let merged = merge_value(
+        Some(base),
+        Some(ours),
+        Some(theirs),
+        "",
+        prefer_ours,
+        &mut conflicts,
+    )
+    .unwrap_or_else(|| Value::Object(Map::new()));
+    (merged, conflicts)
+}
+
+/// Write `ours` unless the file drifted from `base`, merging when it did
+fn save_against(path: &Path, base: Base, ours: Value) -> WorkbenchResult<SaveOutcome> {
+    let (current_hash, theirs) = read_current(path)?;
+    if base.hash == current_hash {
+        write_json_atomic(path, &ours)?;
+        record_written(path);
+        return Ok(SaveOutcome::Written);
+    }
+
+    let (merged, conflicts) = three_way_merge(&base.value, &ours, &theirs, true);
+    if conflicts.is_empty() {
+        info!(
+            "Merged save of {} with changes made on disk",
+            path.display()
+        );
+        write_json_atomic(path, &merged)?;
+        record_written(path);
+        return Ok(SaveOutcome::Merged);
+    }
+
+    let id = uuid::Uuid::new_v4().to_string();
+    warn!(
+        "{} conflicting change(s) in {}, held as conflict {}",
+        conflicts.len(),
+        path.display(),
+        id
+    );
+    let conflict = ConfigConflict {
+        id: id.clone(),
+        path: path.to_string_lossy().to_string(),
+        base: base.value,
+        ours,
+        theirs,
+        merged,
+        conflicts,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        theirs_hash: current_hash,
+    };
+    let mut pending = CONFLICTS.lock().map_err(|e| e.to_string())?;
+    // One open conflict per file; a newer save replaces it
+    pending.retain(|_, c| c.path != conflict.path);
+    pending.insert(id.clone(), conflict);
+    Err(WorkbenchError::Conflict {
+        path: path.to_string_lossy().to_string(),
+        id,
+    })
+}
+
+/// The version of a config file with hash `base_hash`, to apply an edit to.
+/// Fails when that version is no longer known and the file has changed since,
+/// e.g. after a restart; the editor has to load the file again.
+pub fn base_version(path: &Path, base_hash: &str) -> WorkbenchResult<Value> {
+    if let Some(base) = find_base(path, base_hash) {
+        return Ok(base.value);
+    }
+    let (current_hash, current) = read_current(path)?;
+    if current_hash == base_hash {
+        return Ok(current);
+    }
+    Err(WorkbenchError::InvalidState(format!(
+        "{} changed since it was loaded; load it again before saving",
+        path.display()
+    )))
+}
+
+/// Save `ours`, an edit of the version with hash `base_hash`, to a config
+/// file, refusing to overwrite changes made since that version
+pub fn save_checked(path: &Path, base_hash: &str, ours: Value) -> WorkbenchResult<SaveOutcome> {
+    let base = Base {
+        hash: base_hash.to_string(),
+        value: base_version(path, base_hash)?,
+    };
+    save_against(path, base, ours)
+}
+
+// ============ Tauri Commands ============
+
+/// Open conflicts, oldest first
+#[tauri::command]
+pub async fn list_config_conflicts() -> Result<Vec<ConfigConflict>, String> {
+    let pending = CONFLICTS.lock().map_err(|e| e.to_string())?;
+    let mut conflicts: Vec<ConfigConflict> = pending.values().cloned().collect();
+    conflicts.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    Ok(conflicts)
+}
+
+/// Settle a conflict and write the result: `ours` and `theirs` take that side
+/// of each conflicting value and keep every other change, `manual` writes
+/// `settings` as given. If the file changed again meanwhile, a new conflict is
+/// returned. Returns what was written.
+#[tauri::command]
+pub async fn resolve_config_conflict(
+    id: String,
+    resolution: String,
+    settings: Option<Value>,
+) -> Result<Value, WorkbenchError> {
+    let conflict = CONFLICTS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| WorkbenchError::NotFound(format!("Conflict {} not found", id)))?;
+
+    let resolved = match resolution.as_str() {
+        "ours" => conflict.merged.clone(),
+        "theirs" => three_way_merge(&conflict.base, &conflict.ours, &conflict.theirs, false).0,
+        "manual" => settings.ok_or_else(|| {
+            WorkbenchError::InvalidInput("Manual resolution needs the settings to save".into())
+        })?,
+        other => {
+            return Err(WorkbenchError::InvalidInput(format!(
+                "Unknown resolution: {}",
+                other
+            )))
+        }
+    };
+
+    if let Ok(mut pending) = CONFLICTS.lock() {
+        pending.remove(&id);
+    }
+    // The file as seen at the conflict is the base now
+    let path = PathBuf::from(&conflict.path);
+    let base = Base {
+        hash: conflict.theirs_hash,
+        value: conflict.theirs,
+    };
+    save_against(&path, base, resolved.clone())?;
+    info!(
+        "Resolved conflict {} in {} ({})",
+        id, conflict.path, resolution
+    );
+    // Differs from `resolved` when it was merged with newer changes
+    Ok(read_current(&path)
+        .map(|(_, value)| value)
+        .unwrap_or(resolved))
+}
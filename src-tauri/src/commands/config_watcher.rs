@@ -0,0 +1,307 @@
+use log::{debug, info, warn};
+/// Live reload of Claude settings edited outside the app
+///
+/// Watches `~/.claude` and the `.claude` directory of each opened project for
+/// changes to `settings.json` / `settings.local.json` (where hooks live too).
+/// Directories rather than files are watched because editors, and our own atomic
+/// writer, replace files by rename. Each external change is revalidated, pushed
+/// into the HookManager, and announced with a `config-changed` event. Writes made
+/// through `config_io` are recorded first, so the app's own saves are not echoed.
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use super::claude::get_claude_dir;
+use super::enhanced_hooks::HookManager;
+use super::settings_manager::validate_settings_value;
+use crate::events::{self, AppEvent};
+
+/// Editors emit several events per save; wait this long for them to settle
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Files reloaded when they change
+const WATCHED_FILES: &[&str] = &["settings.json", "settings.local.json"];
+
+/// Hash of the content last seen (or written) per config file
+static LAST_SEEN: Lazy<Mutex<HashMap<PathBuf, u64>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Payload of the `config-changed` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigChange {
+    pub path: String,
+    /// "user", "project" or "local"
+    pub scope: String,
+    pub project_path: Option<String>,
+    /// "modified" or "removed"
+    pub kind: String,
+    pub valid: bool,
+    /// Parse or schema errors when `valid` is false
+    pub errors: Vec<String>,
+}
+
+/// The running watcher and the projects it covers
+pub struct ConfigWatcher {
+    watcher: RecommendedWatcher,
+    projects: HashSet<PathBuf>,
+}
+
+/// Config watcher state; `None` when the platform watcher could not start
+#[derive(Default)]
+pub struct ConfigWatcherState(pub Mutex<Option<ConfigWatcher>>);
+
+fn content_hash(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Remember content the app itself wrote, so the resulting event is ignored
+pub fn record_write(path: &Path, content: &str) {
+    if let Ok(mut seen) = LAST_SEEN.lock() {
+        seen.insert(path.to_path_buf(), content_hash(content));
+    }
+}
+
+/// Scope and project of a watched config file, or None for unrelated files
+fn classify(path: &Path, claude_dir: &Path) -> Option<(String, Option<String>)> {
+    let name = path.file_name()?.to_str()?;
+    if !WATCHED_FILES.contains(&name) {
+        return None;
+    }
+    let dir = path.parent()?;
+    if dir == claude_dir {
+        return Some(("user".to_string(), None));
+    }
+    if dir.file_name()? != ".claude" {
+        return None;
+    }
+    let project = dir.parent()?.to_string_lossy().to_string();
+    let scope = if name == "settings.local.json" {
+        "local"
+    } else {
+        "project"
+    };
+    Some((scope.to_string(), Some(project)))
+}
+
+/// Revalidate a changed file, reload hooks and notify the UI
+fn handle_change(app: &AppHandle, path: &Path, claude_dir: &Path) {
+    let (scope, project_path) = match classify(path, claude_dir) {
+        Some(found) => found,
+        None => return,
+    };
+
+    let content = std::fs::read_to_string(path).ok();
+    {
+        let mut seen = match LAST_SEEN.lock() {
+            Ok(seen) => seen,
+            Err(_) => return,
+        };
+        match &content {
+            Some(content) => {
+                let hash = content_hash(content);
+                if seen.get(path) == Some(&hash) {
+                    debug!("Ignoring unchanged config {:?}", path);
+                    return;
+                }
+                seen.insert(path.to_path_buf(), hash);
+            }
+            // Nothing to report for a file that was never seen
+            None => {
+                if seen.remove(path).is_none() {
+                    return;
+                }
+            }
+        }
+    }
+
+    let mut change = ConfigChange {
+        path: path.to_string_lossy().to_string(),
+        scope,
+        project_path,
+        kind: if content.is_some() {
+            "modified"
+        } else {
+            "removed"
+        }
+        .to_string(),
+        valid: true,
+        errors: Vec::new(),
+    };
+
+    let settings = match content.as_deref().map(str::trim) {
+        None | Some("") => serde_json::json!({}),
+        Some(content) => match serde_json::from_str(content) {
+            Ok(settings) => settings,
+            Err(e) => {
+                change.valid = false;
+                change.errors.push(format!("Invalid JSON: {}", e));
+                serde_json::Value::Null
+            }
+        },
+    };
+
+    if change.valid {
+        let validation = validate_settings_value(&settings);
+        change.valid = validation.valid;
+        change.errors = validation
+            .errors
+            .iter()
+            .map(|e| format!("{}: {}", e.pointer, e.message))
+            .collect();
+
+        // Hooks are only loaded from the shared project and user files
+        if change.scope != "local" {
+            if let Some(manager) = app.try_state::<HookManager>() {
+                manager.reload_hooks(change.project_path.as_deref(), &settings);
+            }
+        }
+    } else {
+        warn!("Config {:?} changed on disk but does not parse", path);
+    }
+
+    info!("Config changed on disk: {} ({})", change.path, change.kind);
+    events::emit(app, AppEvent::ConfigChanged(change));
+}
+
+/// Pick up the `.claude` directory of a watched project once it appears
+fn watch_new_claude_dirs(app: &AppHandle, paths: &BTreeSet<PathBuf>) {
+    let state = match app.try_state::<ConfigWatcherState>() {
+        Some(state) => state,
+        None => return,
+    };
+    let mut guard = match state.0.lock() {
+        Ok(guard) => guard,
+        Err(_) => return,
+    };
+    let watcher = match guard.as_mut() {
+        Some(watcher) => watcher,
+        None => return,
+    };
+    for path in paths {
+        let is_new_dir = path.file_name().is_some_and(|n| n == ".claude")
+            && path.is_dir()
+            && path.parent().is_some_and(|p| watcher.projects.contains(p));
+        if is_new_dir {
+            if let Err(e) = watcher.watcher.watch(path, RecursiveMode::NonRecursive) {
+                warn!("Failed to watch {:?}: {}", path, e);
+            }
+        }
+    }
+}
+
+/// Start watching the user settings; project directories are added on request
+pub fn start_config_watcher(app: &AppHandle) -> Result<ConfigWatcher, String> {
+    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    let (tx, rx) = mpsc::channel::<PathBuf>();
+
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| match res {
+        Ok(event) if !matches!(event.kind, EventKind::Access(_)) => {
+            for path in event.paths {
+                let _ = tx.send(path);
+            }
+        }
+        Ok(_) => {}
+        Err(e) => warn!("Config watcher error: {}", e),
+    })
+    .map_err(|e| format!("Failed to create config watcher: {}", e))?;
+    watcher
+        .watch(&claude_dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to watch {:?}: {}", claude_dir, e))?;
+
+    // Seed the current contents so only later edits are reported
+    for name in WATCHED_FILES {
+        let path = claude_dir.join(name);
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            record_write(&path, &content);
+        }
+    }
+
+    let app = app.clone();
+    let watched_dir = claude_dir.clone();
+    std::thread::spawn(move || {
+        while let Ok(first) = rx.recv() {
+            let mut paths = BTreeSet::from([first]);
+            while let Ok(path) = rx.recv_timeout(DEBOUNCE) {
+                paths.insert(path);
+            }
+            watch_new_claude_dirs(&app, &paths);
+            for path in &paths {
+                handle_change(&app, path, &watched_dir);
+            }
+        }
+        debug!("Config watcher stopped");
+    });
+
+    info!("Watching {:?} for config changes", claude_dir);
+    Ok(ConfigWatcher {
+        watcher,
+        projects: HashSet::new(),
+    })
+}
+
+// ============ Tauri Commands ============
+
+/// Also reload a project's settings when they change on disk
+#[tauri::command]
+pub async fn watch_project_config(
+    state: State<'_, ConfigWatcherState>,
+    project_path: String,
+) -> Result<(), String> {
+    let project = PathBuf::from(&project_path);
+    if !project.is_dir() {
+        return Err(format!("Project directory not found: {}", project_path));
+    }
+
+    let mut guard = state.0.lock().map_err(|e| e.to_string())?;
+    let watcher = guard.as_mut().ok_or("Config watcher is not running")?;
+    if !watcher.projects.insert(project.clone()) {
+        return Ok(());
+    }
+
+    // The project root is watched only to notice `.claude` being created
+    watcher
+        .watcher
+        .watch(&project, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to watch {}: {}", project_path, e))?;
+    let claude_dir = project.join(".claude");
+    if claude_dir.is_dir() {
+        watcher
+            .watcher
+            .watch(&claude_dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch {:?}: {}", claude_dir, e))?;
+        for name in WATCHED_FILES {
+            let path = claude_dir.join(name);
+            if let Ok(content) = std::fs::read_to_string(&path) {
+                record_write(&path, &content);
+            }
+        }
+    }
+    info!("Watching project config in {}", project_path);
+    Ok(())
+}
+
+/// Stop watching a project's settings
+#[tauri::command]
+pub async fn unwatch_project_config(
+    state: State<'_, ConfigWatcherState>,
+    project_path: String,
+) -> Result<(), String> {
+    let project = PathBuf::from(&project_path);
+    let mut guard = state.0.lock().map_err(|e| e.to_string())?;
+    let watcher = guard.as_mut().ok_or("Config watcher is not running")?;
+    if !watcher.projects.remove(&project) {
+        return Ok(());
+    }
+    let _ = watcher.watcher.unwatch(&project);
+    let _ = watcher.watcher.unwatch(&project.join(".claude"));
+    Ok(())
+}
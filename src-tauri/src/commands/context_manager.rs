@@ -1,4 +1,4 @@
-use log::{error, info};
+use log::{error, info, warn};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 /// Auto-compact context management system for Claude Code SDK integration
@@ -9,6 +9,10 @@ use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime};
 use tokio::time::sleep;
 
+use super::enhanced_hooks::{trigger_hook_event, HookContext};
+use crate::events::{self, AppEvent};
+use crate::process::audit::AuditedAsyncCommand;
+
 /// Configuration for auto-compact behavior
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AutoCompactConfig {
@@ -28,6 +32,13 @@ pub struct AutoCompactConfig {
     pub preserve_message_count: usize,
     /// Custom compaction instructions
     pub custom_instructions: Option<String>,
+    /// Pass the strategy and custom instructions to `/compact` (default: true)
+    #[serde(default = "default_inject_instructions")]
+    pub inject_instructions: bool,
+}
+
+fn default_inject_instructions() -> bool {
+    true
 }
 
 /// Compaction strategies matching Claude Code SDK
@@ -93,11 +104,33 @@ pub enum SessionStatus {
     CompactionFailed(String),
 }
 
+/// A compaction whose resulting context size is not known yet
+#[derive(Debug, Clone)]
+struct PendingCompaction {
+    tokens_before: usize,
+    /// "auto" or "manual" (reported by the CLI) or "workbench"
+    trigger: String,
+}
+
 /// Auto-compact manager state
 pub struct AutoCompactManager {
     pub sessions: Arc<Mutex<HashMap<String, SessionContext>>>,
     pub config: Arc<Mutex<AutoCompactConfig>>,
     pub is_monitoring: Arc<Mutex<bool>>,
+    pending: Arc<Mutex<HashMap<String, PendingCompaction>>>,
+}
+
+/// Context window size implied by an assistant message's usage block
+pub(crate) fn context_tokens(usage: &serde_json::Value) -> usize {
+    [
+        "input_tokens",
+        "cache_creation_input_tokens",
+        "cache_read_input_tokens",
+        "output_tokens",
+    ]
+    .iter()
+    .filter_map(|key| usage.get(*key).and_then(|t| t.as_u64()))
+    .sum::<u64>() as usize
 }
 
 impl Default for AutoCompactConfig {
@@ -111,6 +144,7 @@ impl Default for AutoCompactConfig {
             preserve_recent_messages: true,
             preserve_message_count: 10,
             custom_instructions: None,
+            inject_instructions: true,
         }
     }
 }
@@ -122,6 +156,7 @@ impl AutoCompactManager {
             sessions: Arc::new(Mutex::new(HashMap::new())),
             config: Arc::new(Mutex::new(AutoCompactConfig::default())),
             is_monitoring: Arc::new(Mutex::new(false)),
+            pending: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -198,6 +233,128 @@ impl AutoCompactManager {
         Ok(false)
     }
 
+    /// Feed one stream-json message from a running session into the monitor.
+    ///
+    /// Assistant usage updates the tracked context size; a `compact_boundary` system
+    /// message marks a compaction, and the next usage fires `OnContextCompact` with
+    /// the before/after token counts. Returns true if auto-compaction should run.
+    pub async fn observe_stream_message(
+        &self,
+        app: &tauri::AppHandle,
+        session_id: &str,
+        project_path: &str,
+        msg: &serde_json::Value,
+    ) -> Result<bool, String> {
+        if msg["type"] == "system" && msg["subtype"] == "compact_boundary" {
+            let metadata = &msg["compact_metadata"];
+            let current = self
+                .get_session_stats(session_id)?
+                .map(|s| s.current_tokens)
+                .unwrap_or(0);
+            let pending = PendingCompaction {
+                tokens_before: metadata["pre_tokens"]
+                    .as_u64()
+                    .map(|t| t as usize)
+                    .unwrap_or(current),
+                trigger: metadata["trigger"].as_str().unwrap_or("auto").to_string(),
+            };
+            info!(
+                "Session {} compacted by CLI ({}) at {} tokens",
+                session_id, pending.trigger, pending.tokens_before
+            );
+            let mut pending_map = self.pending.lock().map_err(|e| e.to_string())?;
+            pending_map.insert(session_id.to_string(), pending);
+            return Ok(false);
+        }
+
+        if msg["type"] != "assistant" {
+            return Ok(false);
+        }
+        let tokens = match msg.get("message").and_then(|m| m.get("usage")) {
+            Some(usage) => context_tokens(usage),
+            None => return Ok(false),
+        };
+        if tokens == 0 {
+            return Ok(false);
+        }
+
+        let pending = {
+            let mut pending_map = self.pending.lock().map_err(|e| e.to_string())?;
+            pending_map.remove(session_id)
+        };
+        if let Some(pending) = pending {
+            self.complete_compaction(app, session_id, project_path, pending, tokens)?;
+        }
+
+        self.update_session_tokens(session_id, tokens).await
+    }
+
+    /// Record the outcome of a compaction and fire `OnContextCompact` hooks
+    fn complete_compaction(
+        &self,
+        app: &tauri::AppHandle,
+        session_id: &str,
+        project_path: &str,
+        pending: PendingCompaction,
+        tokens_after: usize,
+    ) -> Result<(), String> {
+        let (compaction_count, max_context_tokens) = {
+            let mut sessions = self.sessions.lock().map_err(|e| e.to_string())?;
+            let config = self.config.lock().map_err(|e| e.to_string())?;
+            let count = match sessions.get_mut(session_id) {
+                Some(session) => {
+                    // Workbench compactions were already counted when they ran
+                    if pending.trigger != "workbench" {
+                        session.last_compaction = Some(SystemTime::now());
+                        session.compaction_count += 1;
+                    }
+                    session.status = SessionStatus::Active;
+                    session.compaction_count
+                }
+                None => 0,
+            };
+            (count, config.max_context_tokens)
+        };
+
+        info!(
+            "Context compacted for session {}: {} -> {} tokens",
+            session_id, pending.tokens_before, tokens_after
+        );
+
+        let data = serde_json::json!({
+            "trigger": pending.trigger,
+            "tokens_before": pending.tokens_before,
+            "tokens_after": tokens_after,
+            "tokens_saved": pending.tokens_before.saturating_sub(tokens_after),
+            "compaction_count": compaction_count,
+            "max_context_tokens": max_context_tokens,
+        });
+        events::emit(
+            app,
+            AppEvent::ContextCompacted {
+                session_id: session_id.to_string(),
+                data: data.clone(),
+            },
+        );
+
+        let context = HookContext {
+            event: "OnContextCompact".to_string(),
+            session_id: session_id.to_string(),
+            project_path: project_path.to_string(),
+            data,
+        };
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) =
+                trigger_hook_event(app, "OnContextCompact".to_string(), context, None).await
+            {
+                warn!("OnContextCompact hooks failed: {}", e);
+            }
+        });
+
+        Ok(())
+    }
+
     /// Execute compaction for a session
     pub async fn execute_compaction(
         &self,
@@ -206,7 +363,7 @@ impl AutoCompactManager {
     ) -> Result<(), String> {
         info!("Executing auto-compaction for session {}", session_id);
 
-        let (project_path, custom_instructions) = {
+        let (project_path, custom_instructions, inject_instructions, tokens_before) = {
             let sessions = self.sessions.lock().map_err(|e| e.to_string())?;
             let config = self.config.lock().map_err(|e| e.to_string())?;
 
@@ -217,18 +374,36 @@ impl AutoCompactManager {
             (
                 session.project_path.clone(),
                 config.custom_instructions.clone(),
+                config.inject_instructions,
+                session.current_tokens,
             )
         };
 
         // Build compaction command based on strategy
-        let compaction_cmd = self.build_compaction_command(&custom_instructions).await?;
+        let compaction_cmd = if inject_instructions {
+            self.build_compaction_command(&custom_instructions).await?
+        } else {
+            String::new()
+        };
 
         // Execute compaction using Claude CLI
         match self
-            .execute_claude_compaction(&app, &project_path, &compaction_cmd)
+            .execute_claude_compaction(&app, session_id, &project_path, &compaction_cmd)
             .await
         {
             Ok(_) => {
+                // The real post-compaction size arrives with the session's next usage
+                {
+                    let mut pending_map = self.pending.lock().map_err(|e| e.to_string())?;
+                    pending_map.insert(
+                        session_id.to_string(),
+                        PendingCompaction {
+                            tokens_before,
+                            trigger: "workbench".to_string(),
+                        },
+                    );
+                }
+
                 // Update session state after successful compaction
                 let mut sessions = self.sessions.lock().map_err(|e| e.to_string())?;
                 if let Some(session) = sessions.get_mut(session_id) {
@@ -296,44 +471,39 @@ impl AutoCompactManager {
     async fn execute_claude_compaction(
         &self,
         app: &tauri::AppHandle,
+        session_id: &str,
         project_path: &str,
         instructions: &str,
     ) -> Result<(), String> {
         // Find Claude CLI binary
         let claude_path = crate::claude_binary::find_claude_binary(app)?;
 
-        // Build compaction command
+        let compact_prompt = if instructions.is_empty() {
+            "/compact".to_string()
+        } else {
+            format!("/compact {}", instructions)
+        };
+
+        // Resume the session non-interactively and run /compact in it
         let mut cmd = tokio::process::Command::new(&claude_path);
-        cmd.args(&["/compact"])
+        cmd.args(["--resume", session_id, "-p", &compact_prompt])
             .current_dir(project_path)
-            .stdin(std::process::Stdio::piped())
+            .stdin(std::process::Stdio::null())
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped());
+        cmd.envs(crate::commands::proxy::proxy_env_vars());
+        cmd.envs(crate::commands::credentials::credential_env(app).await);
+        crate::commands::env_profiles::apply_active_profile(app, project_path, &mut cmd);
 
         // Execute compaction
-        let mut child = cmd
-            .spawn()
+        let (child, audit) = cmd
+            .audited_spawn("claude")
             .map_err(|e| format!("Failed to spawn compaction process: {}", e))?;
 
-        // Send instructions to stdin
-        if let Some(stdin) = child.stdin.take() {
-            use tokio::io::AsyncWriteExt;
-            let mut stdin = stdin;
-            stdin
-                .write_all(instructions.as_bytes())
-                .await
-                .map_err(|e| format!("Failed to write compaction instructions: {}", e))?;
-            stdin
-                .shutdown()
-                .await
-                .map_err(|e| format!("Failed to close stdin: {}", e))?;
-        }
-
         // Wait for completion
-        let output = child
-            .wait_with_output()
-            .await
-            .map_err(|e| format!("Failed to wait for compaction: {}", e))?;
+        let output = child.wait_with_output().await;
+        audit.finish_output(&output);
+        let output = output.map_err(|e| format!("Failed to wait for compaction: {}", e))?;
 
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
@@ -357,6 +527,7 @@ impl AutoCompactManager {
         let sessions = self.sessions.clone();
         let config = self.config.clone();
         let is_monitoring_flag = self.is_monitoring.clone();
+        let pending = self.pending.clone();
 
         tokio::spawn(async move {
             info!("Starting auto-compact monitoring loop");
@@ -395,6 +566,7 @@ impl AutoCompactManager {
                             sessions: sessions.clone(),
                             config: config.clone(),
                             is_monitoring: is_monitoring_flag.clone(),
+                            pending: pending.clone(),
                         };
 
                         tokio::spawn(async move {
@@ -453,6 +625,9 @@ impl AutoCompactManager {
     pub fn unregister_session(&self, session_id: &str) -> Result<(), String> {
         let mut sessions = self.sessions.lock().map_err(|e| e.to_string())?;
         sessions.remove(session_id);
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.remove(session_id);
+        }
         info!(
             "Unregistered session {} from auto-compact monitoring",
             session_id
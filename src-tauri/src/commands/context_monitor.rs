@@ -0,0 +1,324 @@
+use log::{info, warn};
+/// Live context window monitor
+///
+/// The CLI only reports exact usage on assistant messages, so between two of them
+/// (long tool results, pasted files) the context can grow a lot unseen. The
+/// monitor keeps the last exact figure per session and adds an estimate for every
+/// message streamed since, using a rough BPE approximation. Crossing one of the
+/// configured percentages emits `context-threshold` and, unless disabled, runs
+/// OnContextCompact hooks early so they can act before the CLI compacts.
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use super::context_manager::{context_tokens, AutoCompactState};
+use super::enhanced_hooks::{trigger_hook_event, HookContext};
+use super::storage::AgentDb;
+use crate::events::{self, AppEvent};
+
+/// `app_settings` key holding the JSON config
+const SETTINGS_KEY: &str = "context_monitor";
+
+/// Rough token cost of an image block
+const IMAGE_TOKENS: usize = 1600;
+
+/// Threshold alert settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextMonitorConfig {
+    /// Percentages of the context window that raise an alert, e.g. `[50, 75, 90]`
+    pub thresholds: Vec<u8>,
+    /// Run OnContextCompact hooks when a threshold is crossed
+    pub trigger_hooks: bool,
+}
+
+impl Default for ContextMonitorConfig {
+    fn default() -> Self {
+        Self {
+            thresholds: vec![50, 75, 90],
+            trigger_hooks: true,
+        }
+    }
+}
+
+/// Context size of one session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextStatus {
+    pub session_id: String,
+    pub project_path: String,
+    /// Last size reported by the API
+    pub reported_tokens: usize,
+    /// Estimate for messages streamed since that report
+    pub estimated_pending_tokens: usize,
+    pub estimated_tokens: usize,
+    pub max_context_tokens: usize,
+    pub percent: f64,
+    /// Thresholds crossed and not yet re-armed by the context shrinking
+    pub crossed_thresholds: Vec<u8>,
+    pub updated_at: String,
+}
+
+/// Per-session estimates and the cached config
+#[derive(Default)]
+pub struct ContextMonitorState {
+    sessions: Mutex<HashMap<String, ContextStatus>>,
+    config: Mutex<Option<ContextMonitorConfig>>,
+}
+
+/// Approximate token count of text: about four characters per token for words,
+/// one per punctuation mark and one per non-ASCII character
+pub fn estimate_tokens(text: &str) -> usize {
+    let mut tokens = 0;
+    let mut word_len: usize = 0;
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            word_len += 1;
+            continue;
+        }
+        tokens += word_len.div_ceil(4);
+        word_len = 0;
+        // Non-ASCII characters are never ASCII whitespace, so each counts too
+        if !c.is_ascii_whitespace() {
+            tokens += 1;
+        }
+    }
+    tokens + word_len.div_ceil(4)
+}
+
+/// Estimated tokens of a message's content blocks
+fn estimate_content(content: &serde_json::Value) -> usize {
+    match content {
+        serde_json::Value::String(text) => estimate_tokens(text),
+        serde_json::Value::Array(blocks) => blocks
+            .iter()
+            .map(|block| match block.get("type").and_then(|t| t.as_str()) {
+                Some("text") => estimate_tokens(block["text"].as_str().unwrap_or("")),
+                Some("thinking") => estimate_tokens(block["thinking"].as_str().unwrap_or("")),
+                Some("tool_use") => estimate_tokens(&block["input"].to_string()),
+                Some("tool_result") => estimate_content(&block["content"]),
+                Some("image") => IMAGE_TOKENS,
+                _ => 0,
+            })
+            .sum(),
+        _ => 0,
+    }
+}
+
+fn load_config(app: &AppHandle) -> Result<ContextMonitorConfig, String> {
+    let db = app
+        .try_state::<AgentDb>()
+        .ok_or("Database not initialized")?;
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT value FROM app_settings WHERE key = ?1",
+            [SETTINGS_KEY],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    match stored {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid context monitor settings: {}", e)),
+        None => Ok(ContextMonitorConfig::default()),
+    }
+}
+
+impl ContextMonitorState {
+    /// Latest estimate for a session
+    pub(crate) fn status(&self, session_id: &str) -> Option<ContextStatus> {
+        self.sessions.lock().ok()?.get(session_id).cloned()
+    }
+
+    fn config(&self, app: &AppHandle) -> ContextMonitorConfig {
+        let mut cached = match self.config.lock() {
+            Ok(cached) => cached,
+            Err(_) => return ContextMonitorConfig::default(),
+        };
+        cached
+            .get_or_insert_with(|| {
+                load_config(app).unwrap_or_else(|e| {
+                    warn!("Using default context monitor settings: {}", e);
+                    ContextMonitorConfig::default()
+                })
+            })
+            .clone()
+    }
+}
+
+/// Context window size, shared with the auto-compact settings
+fn max_context_tokens(app: &AppHandle) -> usize {
+    app.try_state::<AutoCompactState>()
+        .and_then(|state| state.0.get_config().ok())
+        .unwrap_or_default()
+        .max_context_tokens
+        .max(1)
+}
+
+/// Feed one stream-json message of a running session into the monitor
+pub fn observe_message(
+    app: &AppHandle,
+    session_id: &str,
+    project_path: &str,
+    msg: &serde_json::Value,
+) {
+    let state = match app.try_state::<ContextMonitorState>() {
+        Some(state) => state,
+        None => return,
+    };
+    let config = state.config(app);
+    let max_tokens = max_context_tokens(app);
+
+    let (status, crossed) = {
+        let mut sessions = match state.sessions.lock() {
+            Ok(sessions) => sessions,
+            Err(_) => return,
+        };
+        let status = sessions
+            .entry(session_id.to_string())
+            .or_insert_with(|| ContextStatus {
+                session_id: session_id.to_string(),
+                project_path: project_path.to_string(),
+                reported_tokens: 0,
+                estimated_pending_tokens: 0,
+                estimated_tokens: 0,
+                max_context_tokens: max_tokens,
+                percent: 0.0,
+                crossed_thresholds: Vec::new(),
+                updated_at: String::new(),
+            });
+
+        let usage = msg
+            .get("message")
+            .and_then(|m| m.get("usage"))
+            .map(context_tokens)
+            .filter(|tokens| *tokens > 0);
+        match (msg["type"].as_str(), usage) {
+            (Some("assistant"), Some(tokens)) => {
+                status.reported_tokens = tokens;
+                status.estimated_pending_tokens = 0;
+            }
+            (Some("user" | "assistant"), _) => {
+                status.estimated_pending_tokens += estimate_content(&msg["message"]["content"]);
+            }
+            (Some("system"), _) if msg["subtype"] == "compact_boundary" => {
+                // Exact again with the next assistant usage
+                status.reported_tokens = 0;
+                status.estimated_pending_tokens = 0;
+            }
+            _ => return,
+        }
+
+        status.estimated_tokens = status.reported_tokens + status.estimated_pending_tokens;
+        status.max_context_tokens = max_tokens;
+        status.percent = status.estimated_tokens as f64 * 100.0 / max_tokens as f64;
+        status.updated_at = chrono::Utc::now().to_rfc3339();
+
+        // Thresholds re-arm once the context shrinks below them again
+        let percent = status.percent;
+        status
+            .crossed_thresholds
+            .retain(|t| percent >= f64::from(*t));
+        let newly_crossed: Vec<u8> = config
+            .thresholds
+            .iter()
+            .copied()
+            .filter(|t| percent >= f64::from(*t) && !status.crossed_thresholds.contains(t))
+            .collect();
+        status.crossed_thresholds.extend(&newly_crossed);
+        status.crossed_thresholds.sort_unstable();
+        (status.clone(), newly_crossed.into_iter().max())
+    };
+
+    // Several thresholds crossed at once raise one alert, for the highest
+    let threshold = match crossed {
+        Some(threshold) => threshold,
+        None => return,
+    };
+    info!(
+        "Session {} context at {:.0}% ({} of {} tokens), crossed {}%",
+        session_id, status.percent, status.estimated_tokens, max_tokens, threshold
+    );
+    let data = serde_json::json!({
+        "trigger": "threshold",
+        "threshold_percent": threshold,
+        "percent": status.percent,
+        "estimated_tokens": status.estimated_tokens,
+        "max_context_tokens": max_tokens,
+    });
+    events::emit(
+        app,
+        AppEvent::ContextThreshold {
+            session_id: session_id.to_string(),
+            project_path: status.project_path.clone(),
+            data: data.clone(),
+        },
+    );
+
+    if config.trigger_hooks {
+        let context = HookContext {
+            event: "OnContextCompact".to_string(),
+            session_id: session_id.to_string(),
+            project_path: status.project_path,
+            data,
+        };
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) =
+                trigger_hook_event(app, "OnContextCompact".to_string(), context, None).await
+            {
+                warn!("Proactive OnContextCompact hooks failed: {}", e);
+            }
+        });
+    }
+}
+
+// ============ Tauri Commands ============
+
+/// Current context size estimate of a session, or None if it has not streamed yet
+#[tauri::command]
+pub async fn get_context_status(
+    state: State<'_, ContextMonitorState>,
+    session_id: String,
+) -> Result<Option<ContextStatus>, String> {
+    let sessions = state.sessions.lock().map_err(|e| e.to_string())?;
+    Ok(sessions.get(&session_id).cloned())
+}
+
+/// Get the threshold alert settings
+#[tauri::command]
+pub async fn get_context_monitor_config(
+    app: AppHandle,
+    state: State<'_, ContextMonitorState>,
+) -> Result<ContextMonitorConfig, String> {
+    Ok(state.config(&app))
+}
+
+/// Replace the threshold alert settings
+#[tauri::command]
+pub async fn update_context_monitor_config(
+    db: State<'_, AgentDb>,
+    state: State<'_, ContextMonitorState>,
+    config: ContextMonitorConfig,
+) -> Result<(), String> {
+    if let Some(bad) = config.thresholds.iter().find(|t| **t == 0 || **t > 100) {
+        return Err(format!("Threshold must be between 1 and 100: {}", bad));
+    }
+    let mut config = config;
+    config.thresholds.sort_unstable();
+    config.thresholds.dedup();
+
+    let json = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+    {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+            params![SETTINGS_KEY, json],
+        )
+        .map_err(|e| format!("Failed to save context monitor settings: {}", e))?;
+    }
+    *state.config.lock().map_err(|e| e.to_string())? = Some(config);
+    info!("Context monitor settings updated");
+    Ok(())
+}
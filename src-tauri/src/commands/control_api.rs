@@ -0,0 +1,595 @@
+use log::{error, info, warn};
+/// Local control API
+///
+/// An opt-in HTTP + WebSocket server on 127.0.0.1 that makes the workbench
+/// scriptable from editors, CI and launchers without going through the frontend.
+/// Every request must carry the API token, as `Authorization: Bearer <token>` or,
+/// for WebSocket clients that cannot set headers, a `token` query parameter.
+///
+/// - `GET  /api/health`
+/// - `POST /api/hooks/{event}` – trigger a hook event
+/// - `GET  /api/sessions` – running Claude sessions
+/// - `POST /api/sessions` – start a headless session, returns its id
+/// - `GET  /api/usage?days=N` – usage statistics
+/// - `GET  /api/sessions/{id}/stream` – WebSocket of the session's output lines
+/// - `GET  /metrics` – hook metrics in the Prometheus text format, if `metrics` is on
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Listener, Manager, State};
+use tokio::sync::{mpsc, oneshot, watch, Notify};
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Query, Request, State as AxumState};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+
+use super::enhanced_hooks::{trigger_hook_event, HookContext};
+use super::headless::{self, ProgressFn, SessionProgress, SessionSpec};
+use super::storage::AgentDb;
+use crate::events::{self, AppEvent};
+
+/// `app_settings` key holding the JSON config
+const SETTINGS_KEY: &str = "control_api";
+
+const DEFAULT_PORT: u16 = 7878;
+
+/// How long `POST /api/sessions` waits for the new session to report its id
+const SESSION_START_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How long a restart waits for the previous server to let go of its port
+const SERVER_STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Persisted server settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlApiConfig {
+    pub enabled: bool,
+    pub port: u16,
+    pub token: String,
+    /// Serve `/metrics` for Prometheus
+    #[serde(default)]
+    pub metrics: bool,
+}
+
+impl Default for ControlApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+            token: generate_token(),
+            metrics: false,
+        }
+    }
+}
+
+/// Server settings plus whether it is listening
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlApiStatus {
+    pub enabled: bool,
+    pub running: bool,
+    pub port: u16,
+    pub token: String,
+    pub metrics: bool,
+    pub url: String,
+    /// Why the server is not running although enabled
+    pub error: Option<String>,
+}
+
+struct RunningServer {
+    port: u16,
+    shutdown: oneshot::Sender<()>,
+    /// Closes open WebSocket streams, which graceful shutdown does not wait for
+    close_streams: watch::Sender<bool>,
+    task: tauri::async_runtime::JoinHandle<()>,
+}
+
+/// The running server, if any, and the last startup error
+#[derive(Default)]
+pub struct ControlApiState {
+    server: tokio::sync::Mutex<Option<RunningServer>>,
+    last_error: Mutex<Option<String>>,
+}
+
+/// Shared with every request handler
+struct ApiContext {
+    app: AppHandle,
+    token: String,
+    /// Changes when the server stops
+    closed: watch::Receiver<bool>,
+}
+
+type ApiError = (StatusCode, Json<serde_json::Value>);
+
+fn api_error(status: StatusCode, message: impl Into<String>) -> ApiError {
+    (status, Json(serde_json::json!({ "error": message.into() })))
+}
+
+fn generate_token() -> String {
+    format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+/// Stored settings, None until the app first loads them. `workbench-cli` reads
+/// them straight from the database to find the running server.
+pub fn read_config(conn: &rusqlite::Connection) -> Result<Option<ControlApiConfig>, String> {
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT value FROM app_settings WHERE key = ?1",
+            [SETTINGS_KEY],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    stored
+        .map(|json| {
+            serde_json::from_str(&json).map_err(|e| format!("Invalid control API settings: {}", e))
+        })
+        .transpose()
+}
+
+fn load_config(app: &AppHandle) -> Result<ControlApiConfig, String> {
+    let db = app
+        .try_state::<AgentDb>()
+        .ok_or("Database not initialized")?;
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    match read_config(&conn)? {
+        Some(config) => Ok(config),
+        None => {
+            // Persist the generated token so it stays stable
+            let config = ControlApiConfig::default();
+            save_config_with(&conn, &config)?;
+            Ok(config)
+        }
+    }
+}
+
+fn save_config_with(conn: &rusqlite::Connection, config: &ControlApiConfig) -> Result<(), String> {
+    let json = serde_json::to_string(config).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+        params![SETTINGS_KEY, json],
+    )
+    .map_err(|e| format!("Failed to save control API settings: {}", e))?;
+    Ok(())
+}
+
+fn save_config(app: &AppHandle, config: &ControlApiConfig) -> Result<(), String> {
+    let db = app
+        .try_state::<AgentDb>()
+        .ok_or("Database not initialized")?;
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    save_config_with(&conn, config)
+}
+
+/// Compare without short-circuiting on the first differing byte
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+async fn require_token(
+    AxumState(ctx): AxumState<Arc<ApiContext>>,
+    Query(query): Query<HashMap<String, String>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let bearer = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    let given = bearer.or(query.get("token").map(String::as_str));
+    if given.is_some_and(|token| tokens_match(token, &ctx.token)) {
+        next.run(request).await
+    } else {
+        api_error(StatusCode::UNAUTHORIZED, "Missing or invalid API token").into_response()
+    }
+}
+
+async fn health() -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "status": "ok",
+        "version": env!("CARGO_PKG_VERSION"),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+struct HookEventRequest {
+    project_path: String,
+    session_id: Option<String>,
+    data: Option<serde_json::Value>,
+}
+
+async fn trigger_hook(
+    AxumState(ctx): AxumState<Arc<ApiContext>>,
+    Path(event): Path<String>,
+    Json(body): Json<HookEventRequest>,
+) -> Result<Json<super::enhanced_hooks::HookChainResult>, ApiError> {
+    let context = HookContext {
+        event: event.clone(),
+        session_id: body.session_id.unwrap_or_default(),
+        project_path: body.project_path,
+        data: body.data.unwrap_or_else(|| serde_json::json!({})),
+    };
+    trigger_hook_event(ctx.app.clone(), event, context, None)
+        .await
+        .map(Json)
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, e))
+}
+
+#[derive(Debug, Deserialize)]
+struct StartSessionRequest {
+    project_path: String,
+    prompt: String,
+    model: Option<String>,
+    plan_mode: Option<bool>,
+    timeout_secs: Option<u64>,
+}
+
+/// Start a headless session; its output is emitted like an interactive session's,
+/// so the stream endpoint and the UI can follow it
+async fn start_session(
+    AxumState(ctx): AxumState<Arc<ApiContext>>,
+    Json(body): Json<StartSessionRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    if body.prompt.trim().is_empty() {
+        return Err(api_error(StatusCode::BAD_REQUEST, "Prompt cannot be empty"));
+    }
+    if !std::path::Path::new(&body.project_path).is_dir() {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            format!("Project directory not found: {}", body.project_path),
+        ));
+    }
+    let model = body
+        .model
+        .or_else(|| super::models::project_default(&ctx.app, &body.project_path))
+        .unwrap_or_else(|| "sonnet".to_string());
+    let spec = SessionSpec {
+        project_path: body.project_path,
+        prompt: body.prompt,
+        model,
+        plan_mode: body.plan_mode.unwrap_or(false),
+        timeout: body.timeout_secs.map(Duration::from_secs),
+    };
+
+    let (started_tx, started_rx) = oneshot::channel::<Result<String, String>>();
+    let started_tx = Arc::new(Mutex::new(Some(started_tx)));
+    let session_id: Arc<Mutex<Option<String>>> = Arc::default();
+
+    let progress_app = ctx.app.clone();
+    let progress_started = started_tx.clone();
+    let progress_session = session_id.clone();
+    let progress_project = spec.project_path.clone();
+    let progress: ProgressFn = Arc::new(move |progress| match progress {
+        SessionProgress::SessionId(id) => {
+            if let Ok(mut current) = progress_session.lock() {
+                *current = Some(id.to_string());
+            }
+            if let Some(tx) = progress_started.lock().ok().and_then(|mut tx| tx.take()) {
+                let _ = tx.send(Ok(id.to_string()));
+            }
+        }
+        SessionProgress::Line(line) => {
+            let current = progress_session.lock().ok().and_then(|id| id.clone());
+            if let Some(id) = current {
+                let channel = format!("claude-output:{}", id);
+                events::emit_stream(&progress_app, Some(&progress_project), &channel, line);
+            }
+        }
+        SessionProgress::Started { .. } => {}
+    });
+
+    let app = ctx.app.clone();
+    tauri::async_runtime::spawn(async move {
+        let session = headless::run_session(&app, &spec, Arc::new(Notify::new()), progress).await;
+        if let Some(tx) = started_tx.lock().ok().and_then(|mut tx| tx.take()) {
+            let error = session
+                .error()
+                .unwrap_or_else(|| "Session ended without an id".to_string());
+            let _ = tx.send(Err(error));
+        }
+        if let Some(id) = &session.session_id {
+            events::emit(
+                &app,
+                AppEvent::ClaudeComplete {
+                    session_id: Some(id.clone()),
+                    project_path: Some(spec.project_path.clone()),
+                    success: session.succeeded(),
+                },
+            );
+        }
+    });
+
+    match tokio::time::timeout(SESSION_START_TIMEOUT, started_rx).await {
+        Ok(Ok(Ok(session_id))) => Ok(Json(serde_json::json!({ "session_id": session_id }))),
+        Ok(Ok(Err(e))) => Err(api_error(StatusCode::INTERNAL_SERVER_ERROR, e)),
+        Ok(Err(_)) | Err(_) => Err(api_error(
+            StatusCode::GATEWAY_TIMEOUT,
+            "Session did not start in time",
+        )),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct UsageQuery {
+    days: Option<u32>,
+}
+
+async fn usage(Query(query): Query<UsageQuery>) -> Result<Json<serde_json::Value>, ApiError> {
+    let stats =
+        tauri::async_runtime::spawn_blocking(move || super::usage::get_usage_stats(query.days))
+            .await
+            .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    serde_json::to_value(stats)
+        .map(Json)
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+async fn running_sessions(
+    AxumState(ctx): AxumState<Arc<ApiContext>>,
+) -> Result<Json<Vec<crate::process::ProcessInfo>>, ApiError> {
+    ctx.app
+        .state::<crate::process::ProcessRegistryState>()
+        .0
+        .get_running_claude_sessions()
+        .map(Json)
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e))
+}
+
+async fn stream_session(
+    AxumState(ctx): AxumState<Arc<ApiContext>>,
+    Path(session_id): Path<String>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    ws.on_upgrade(move |socket| {
+        forward_output(ctx.app.clone(), session_id, socket, ctx.closed.clone())
+    })
+}
+
+/// Relay a session's output events to the socket until the session completes,
+/// the client disconnects or the server stops
+async fn forward_output(
+    app: AppHandle,
+    session_id: String,
+    mut socket: WebSocket,
+    mut closed: watch::Receiver<bool>,
+) {
+    // None marks the end of the session
+    let (tx, mut rx) = mpsc::unbounded_channel::<Option<String>>();
+    let output_tx = tx.clone();
+    let output_listener = app.listen(format!("claude-output:{}", session_id), move |event| {
+        // Payloads are JSON-encoded lines
+        let line = serde_json::from_str::<String>(event.payload())
+            .unwrap_or_else(|_| event.payload().to_string());
+        let _ = output_tx.send(Some(line));
+    });
+    let complete_subscription = events::subscribe(&app, move |_, event| {
+        if let AppEvent::ClaudeComplete {
+            session_id: Some(id),
+            ..
+        } = event
+        {
+            if *id == session_id {
+                let _ = tx.send(None);
+            }
+        }
+    });
+
+    loop {
+        tokio::select! {
+            next = rx.recv() => match next {
+                Some(Some(line)) => {
+                    if socket.send(Message::Text(line.into())).await.is_err() {
+                        break;
+                    }
+                }
+                _ => {
+                    let _ = socket.send(Message::Close(None)).await;
+                    break;
+                }
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+            // The token it authenticated with may no longer be valid
+            _ = closed.changed() => {
+                let _ = socket.send(Message::Close(None)).await;
+                break;
+            }
+        }
+    }
+
+    app.unlisten(output_listener);
+    if let Some(id) = complete_subscription {
+        events::unsubscribe(&app, id);
+    }
+}
+
+async fn metrics(AxumState(ctx): AxumState<Arc<ApiContext>>) -> Result<Response, ApiError> {
+    let body = super::hook_metrics::render_prometheus(&ctx.app)
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    Ok(([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body).into_response())
+}
+
+fn router(ctx: Arc<ApiContext>, serve_metrics: bool) -> Router {
+    let mut router = Router::new()
+        .route("/api/health", get(health))
+        .route("/api/hooks/{event}", post(trigger_hook))
+        .route("/api/sessions", get(running_sessions).post(start_session))
+        .route("/api/sessions/{id}/stream", get(stream_session))
+        .route("/api/usage", get(usage));
+    if serve_metrics {
+        router = router.route("/metrics", get(metrics));
+    }
+    router
+        .layer(middleware::from_fn_with_state(ctx.clone(), require_token))
+        .with_state(ctx)
+}
+
+/// Stop a server and wait until it has released its port
+async fn stop(running: RunningServer) {
+    let _ = running.close_streams.send(true);
+    let _ = running.shutdown.send(());
+    let mut task = running.task;
+    if tokio::time::timeout(SERVER_STOP_TIMEOUT, &mut task)
+        .await
+        .is_err()
+    {
+        warn!(
+            "Control API on port {} did not stop in time, aborting it",
+            running.port
+        );
+        // Dropping the aborted server drops its listener
+        task.abort();
+        let _ = task.await;
+    }
+    info!("Control API on port {} stopped", running.port);
+}
+
+/// Stop the running server, then start it again if the config enables it
+async fn restart(app: &AppHandle, state: &ControlApiState, config: &ControlApiConfig) {
+    let mut server = state.server.lock().await;
+    if let Some(running) = server.take() {
+        stop(running).await;
+    }
+    if let Ok(mut last_error) = state.last_error.lock() {
+        *last_error = None;
+    }
+    if !config.enabled {
+        return;
+    }
+
+    let listener = match tokio::net::TcpListener::bind(("127.0.0.1", config.port)).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            let message = format!("Failed to listen on port {}: {}", config.port, e);
+            error!("{}", message);
+            if let Ok(mut last_error) = state.last_error.lock() {
+                *last_error = Some(message);
+            }
+            return;
+        }
+    };
+    let (shutdown, shutdown_rx) = oneshot::channel::<()>();
+    let (close_streams, closed) = watch::channel(false);
+    let ctx = Arc::new(ApiContext {
+        app: app.clone(),
+        token: config.token.clone(),
+        closed,
+    });
+    let port = config.port;
+    let serve_metrics = config.metrics;
+    let task = tauri::async_runtime::spawn(async move {
+        let served = axum::serve(listener, router(ctx, serve_metrics))
+            .with_graceful_shutdown(async {
+                let _ = shutdown_rx.await;
+            })
+            .await;
+        if let Err(e) = served {
+            warn!("Control API server on port {} failed: {}", port, e);
+        }
+    });
+    info!("Control API listening on http://127.0.0.1:{}", port);
+    *server = Some(RunningServer {
+        port,
+        shutdown,
+        close_streams,
+        task,
+    });
+}
+
+/// Start the server at launch if it was left enabled
+pub fn start_control_api(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let config = match load_config(&app) {
+            Ok(config) => config,
+            Err(e) => {
+                warn!("Control API not started: {}", e);
+                return;
+            }
+        };
+        if config.enabled {
+            let state = app.state::<ControlApiState>();
+            restart(&app, &state, &config).await;
+        }
+    });
+}
+
+async fn status(state: &ControlApiState, config: ControlApiConfig) -> ControlApiStatus {
+    let running = state.server.lock().await.is_some();
+    ControlApiStatus {
+        enabled: config.enabled,
+        running,
+        port: config.port,
+        metrics: config.metrics,
+        url: format!("http://127.0.0.1:{}", config.port),
+        token: config.token,
+        error: state.last_error.lock().ok().and_then(|e| e.clone()),
+    }
+}
+
+// ============ Tauri Commands ============
+
+/// Server settings, token and whether it is listening
+#[tauri::command]
+pub async fn get_control_api_status(
+    app: AppHandle,
+    state: State<'_, ControlApiState>,
+) -> Result<ControlApiStatus, String> {
+    let config = load_config(&app)?;
+    Ok(status(&state, config).await)
+}
+
+/// Enable or disable the server, optionally on another port
+#[tauri::command]
+pub async fn update_control_api_config(
+    app: AppHandle,
+    state: State<'_, ControlApiState>,
+    enabled: bool,
+    port: Option<u16>,
+    metrics: Option<bool>,
+) -> Result<ControlApiStatus, String> {
+    let mut config = load_config(&app)?;
+    config.enabled = enabled;
+    if let Some(metrics) = metrics {
+        config.metrics = metrics;
+    }
+    if let Some(port) = port {
+        if port < 1024 {
+            return Err(format!("Port must be 1024 or higher: {}", port));
+        }
+        config.port = port;
+    }
+    save_config(&app, &config)?;
+    restart(&app, &state, &config).await;
+    Ok(status(&state, config).await)
+}
+
+/// Replace the API token; clients using the old one are rejected from now on
+/// and open streams are closed
+#[tauri::command]
+pub async fn regenerate_control_api_token(
+    app: AppHandle,
+    state: State<'_, ControlApiState>,
+) -> Result<ControlApiStatus, String> {
+    let mut config = load_config(&app)?;
+    config.token = generate_token();
+    save_config(&app, &config)?;
+    restart(&app, &state, &config).await;
+    Ok(status(&state, config).await)
+}
@@ -0,0 +1,355 @@
+use log::{debug, info, warn};
+/// Cost attribution to git branches and tickets
+///
+/// When a session starts, the branch checked out in its project is recorded, and
+/// a ticket ID is taken from the branch name with `ticket_pattern` (keys such as
+/// `ABC-123` by default). Both can be overridden per session. Sessions without a
+/// record fall back to the branch Claude logged in the transcript.
+///
+/// `get_cost_by_branch` and `get_cost_by_ticket` sum usage per branch and per
+/// ticket, so spend can be put against the work it was for.
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use tauri::{AppHandle, Manager, State};
+
+use super::simple_git;
+use super::storage::AgentDb;
+use super::usage::{self, UsageRange};
+
+/// `app_settings` key holding the JSON config
+const SETTINGS_KEY: &str = "cost_attribution";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AttributionConfig {
+    /// Regex whose first match in a branch name is the ticket ID
+    pub ticket_pattern: String,
+}
+
+impl Default for AttributionConfig {
+    fn default() -> Self {
+        Self {
+            ticket_pattern: r"[A-Z][A-Z0-9]+-\d+".to_string(),
+        }
+    }
+}
+
+/// Branch and ticket a session's cost is attributed to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionAttribution {
+    pub session_id: String,
+    pub project_path: Option<String>,
+    pub branch: Option<String>,
+    pub ticket: Option<String>,
+    /// "detected" at session start, "manual" when overridden, or "transcript"
+    /// when taken from the transcript because nothing was recorded
+    pub source: String,
+    pub updated_at: Option<String>,
+}
+
+/// Usage summed for one branch or ticket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostBucket {
+    /// Branch or ticket; None for sessions attributed to none
+    pub key: Option<String>,
+    /// Repository of a branch; None for tickets, which may span repositories
+    pub project_path: Option<String>,
+    pub sessions: usize,
+    pub messages: u64,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+    pub first_used: String,
+    pub last_used: String,
+    pub session_ids: Vec<String>,
+}
+
+fn row_to_attribution(row: &Row) -> rusqlite::Result<SessionAttribution> {
+    Ok(SessionAttribution {
+        session_id: row.get(0)?,
+        project_path: row.get(1)?,
+        branch: row.get(2)?,
+        ticket: row.get(3)?,
+        source: row.get(4)?,
+        updated_at: row.get(5)?,
+    })
+}
+
+fn load_attribution(
+    conn: &Connection,
+    session_id: &str,
+) -> Result<Option<SessionAttribution>, String> {
+    conn.query_row(
+        "SELECT session_id, project_path, branch, ticket, source, updated_at
+         FROM session_attributions WHERE session_id = ?1",
+        [session_id],
+        row_to_attribution,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+fn load_config(conn: &Connection) -> Result<AttributionConfig, String> {
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT value FROM app_settings WHERE key = ?1",
+            [SETTINGS_KEY],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    match stored {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid cost attribution settings: {}", e)),
+        None => Ok(AttributionConfig::default()),
+    }
+}
+
+fn ticket_regex(config: &AttributionConfig) -> Result<Regex, String> {
+    Regex::new(&config.ticket_pattern)
+        .map_err(|e| format!("Invalid ticket pattern '{}': {}", config.ticket_pattern, e))
+}
+
+fn ticket_in(pattern: Option<&Regex>, branch: &str) -> Option<String> {
+    pattern?.find(branch).map(|m| m.as_str().to_string())
+}
+
+/// Record the branch of a session that just started; a session that already
+/// has a record, such as a resumed one, keeps it
+pub fn record_session_start(app: &AppHandle, session_id: &str, project_path: &str) {
+    let app = app.clone();
+    let session_id = session_id.to_string();
+    let project_path = project_path.to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        if !simple_git::is_git_repo(&project_path) {
+            return;
+        }
+        let branch = match simple_git::git_current_branch(&project_path) {
+            Ok(Some(branch)) => branch,
+            Ok(None) => return,
+            Err(e) => {
+                debug!("No branch for session {}: {}", session_id, e);
+                return;
+            }
+        };
+        let db = match app.try_state::<AgentDb>() {
+            Some(db) => db,
+            None => return,
+        };
+        let conn = match db.0.lock() {
+            Ok(conn) => conn,
+            Err(_) => return,
+        };
+        let pattern = load_config(&conn)
+            .and_then(|config| ticket_regex(&config))
+            .map_err(|e| warn!("{}", e))
+            .ok();
+        let ticket = ticket_in(pattern.as_ref(), &branch);
+        if let Err(e) = conn.execute(
+            "INSERT OR IGNORE INTO session_attributions (session_id, project_path, branch, ticket, source, updated_at)
+             VALUES (?1, ?2, ?3, ?4, 'detected', ?5)",
+            params![
+                session_id,
+                project_path,
+                branch,
+                ticket,
+                chrono::Utc::now().to_rfc3339()
+            ],
+        ) {
+            warn!("Failed to record branch of session {}: {}", session_id, e);
+        }
+    });
+}
+
+/// Sum per-session usage into buckets, most expensive first
+fn sum_costs<F>(
+    db: &AgentDb,
+    range: &UsageRange,
+    project_path: Option<&str>,
+    key_of: F,
+) -> Result<Vec<CostBucket>, String>
+where
+    F: Fn(&SessionAttribution) -> (Option<String>, Option<String>),
+{
+    let (sessions, _) = usage::usage_rows(range)?;
+    let (stored, pattern) = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT session_id, project_path, branch, ticket, source, updated_at
+                 FROM session_attributions",
+            )
+            .map_err(|e| e.to_string())?;
+        let stored: HashMap<String, SessionAttribution> = stmt
+            .query_map([], row_to_attribution)
+            .map_err(|e| e.to_string())?
+            .filter_map(|row| row.ok())
+            .map(|a| (a.session_id.clone(), a))
+            .collect();
+        (stored, ticket_regex(&load_config(&conn)?).ok())
+    };
+
+    let project_path = project_path.map(|p| p.trim_end_matches(['/', '\\']));
+    let mut buckets: BTreeMap<(Option<String>, Option<String>), CostBucket> = BTreeMap::new();
+    for session in sessions
+        .iter()
+        .filter(|s| project_path.is_none_or(|p| s.project_path.trim_end_matches(['/', '\\']) == p))
+    {
+        let mut attribution =
+            stored
+                .get(&session.session_id)
+                .cloned()
+                .unwrap_or_else(|| SessionAttribution {
+                    session_id: session.session_id.clone(),
+                    project_path: Some(session.project_path.clone()),
+                    branch: session.git_branch.clone(),
+                    ticket: session
+                        .git_branch
+                        .as_deref()
+                        .and_then(|branch| ticket_in(pattern.as_ref(), branch)),
+                    source: "transcript".to_string(),
+                    updated_at: None,
+                });
+        // Branches are grouped by the repository the session actually ran in
+        attribution.project_path = Some(session.project_path.clone());
+        let (key, bucket_project) = key_of(&attribution);
+        let bucket = buckets
+            .entry((key.clone(), bucket_project.clone()))
+            .or_insert_with(|| CostBucket {
+                key,
+                project_path: bucket_project,
+                sessions: 0,
+                messages: 0,
+                total_tokens: 0,
+                total_cost: 0.0,
+                first_used: session.first_used.clone(),
+                last_used: session.last_used.clone(),
+                session_ids: Vec::new(),
+            });
+        bucket.sessions += 1;
+        bucket.messages += session.message_count;
+        bucket.total_tokens += session.total_tokens;
+        bucket.total_cost += session.total_cost;
+        if session.first_used < bucket.first_used {
+            bucket.first_used = session.first_used.clone();
+        }
+        if session.last_used > bucket.last_used {
+            bucket.last_used = session.last_used.clone();
+        }
+        bucket.session_ids.push(session.session_id.clone());
+    }
+
+    let mut buckets: Vec<CostBucket> = buckets.into_values().collect();
+    buckets.sort_by(|a, b| b.total_cost.total_cmp(&a.total_cost));
+    Ok(buckets)
+}
+
+// ============ Tauri Commands ============
+
+/// Recorded branch and ticket of a session
+#[tauri::command]
+pub async fn get_session_attribution(
+    db: State<'_, AgentDb>,
+    session_id: String,
+) -> Result<Option<SessionAttribution>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_attribution(&conn, &session_id)
+}
+
+/// Override the branch and ticket of a session; empty values clear them
+#[tauri::command]
+pub async fn set_session_attribution(
+    db: State<'_, AgentDb>,
+    session_id: String,
+    project_path: Option<String>,
+    branch: Option<String>,
+    ticket: Option<String>,
+) -> Result<SessionAttribution, String> {
+    let clean = |value: Option<String>| {
+        value
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let existing = load_attribution(&conn, &session_id)?;
+    let project_path = clean(project_path).or_else(|| existing.and_then(|a| a.project_path));
+    conn.execute(
+        "INSERT OR REPLACE INTO session_attributions (session_id, project_path, branch, ticket, source, updated_at)
+         VALUES (?1, ?2, ?3, ?4, 'manual', ?5)",
+        params![
+            session_id,
+            project_path,
+            clean(branch),
+            clean(ticket),
+            chrono::Utc::now().to_rfc3339()
+        ],
+    )
+    .map_err(|e| format!("Failed to save session attribution: {}", e))?;
+    info!("Attribution of session {} set manually", session_id);
+    load_attribution(&conn, &session_id)?
+        .ok_or_else(|| format!("Session attribution not found: {}", session_id))
+}
+
+/// Usage per branch and repository within a range, most expensive first
+#[tauri::command]
+pub async fn get_cost_by_branch(
+    app: AppHandle,
+    range: Option<UsageRange>,
+    project_path: Option<String>,
+) -> Result<Vec<CostBucket>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = app.state::<AgentDb>();
+        sum_costs(
+            &db,
+            &range.unwrap_or_default(),
+            project_path.as_deref(),
+            |a| (a.branch.clone(), a.project_path.clone()),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Usage per ticket within a range, most expensive first
+#[tauri::command]
+pub async fn get_cost_by_ticket(
+    app: AppHandle,
+    range: Option<UsageRange>,
+    project_path: Option<String>,
+) -> Result<Vec<CostBucket>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = app.state::<AgentDb>();
+        sum_costs(
+            &db,
+            &range.unwrap_or_default(),
+            project_path.as_deref(),
+            |a| (a.ticket.clone(), None),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn get_attribution_config(db: State<'_, AgentDb>) -> Result<AttributionConfig, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_config(&conn)
+}
+
+/// Replace the ticket pattern; sessions already recorded keep their ticket
+#[tauri::command]
+pub async fn update_attribution_config(
+    db: State<'_, AgentDb>,
+    config: AttributionConfig,
+) -> Result<(), String> {
+    ticket_regex(&config)?;
+    let json = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+        params![SETTINGS_KEY, json],
+    )
+    .map_err(|e| format!("Failed to save cost attribution settings: {}", e))?;
+    Ok(())
+}
@@ -0,0 +1,316 @@
+use log::{info, warn};
+/// Local crash reports
+///
+/// A panic hook writes a JSON report (message, location, backtrace, the last log
+/// lines and a summary of running sessions) to `<log dir>/crashes/` before the
+/// process goes down. Crashes that never reach the hook (native faults, kills)
+/// are caught on the next launch: a marker file lives for as long as the app
+/// runs, so finding it at startup means the previous run ended uncleanly, and an
+/// `unclean-exit` report is written from the tail of the previous log file.
+///
+/// Reports stay on disk until deleted; nothing is sent anywhere. On startup the
+/// number of reports is emitted as `crash-reports-found` when there are any.
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::AppHandle;
+
+use crate::events::{self, AppEvent};
+use crate::logging;
+
+/// Marker present while the app runs
+const RUNNING_MARKER: &str = "running.json";
+
+/// Log lines carried by a report
+const LOG_TAIL: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CrashKind {
+    Panic,
+    /// The previous run ended without a clean shutdown or a panic report
+    UncleanExit,
+}
+
+/// What the app was doing when it crashed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CrashAppState {
+    /// Seconds since launch
+    pub uptime_secs: u64,
+    pub running_sessions: usize,
+    /// Models of the running sessions
+    pub models: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub id: String,
+    pub kind: CrashKind,
+    /// RFC 3339
+    pub timestamp: String,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub thread: Option<String>,
+    pub message: String,
+    /// `file:line:column` of the panic
+    pub location: Option<String>,
+    pub backtrace: Option<String>,
+    pub recent_logs: Vec<String>,
+    /// None for unclean exits, which are detected after the fact
+    pub state: Option<CrashAppState>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RunningMarker {
+    pid: u32,
+    started_at: String,
+}
+
+struct CrashContext {
+    app: AppHandle,
+    dir: PathBuf,
+    started: std::time::Instant,
+}
+
+static CONTEXT: OnceLock<CrashContext> = OnceLock::new();
+
+fn crash_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    crate::paths::app_log_dir(app)
+        .map(|dir| dir.join("crashes"))
+        .map_err(|e| format!("Failed to get log dir: {}", e))
+}
+
+fn new_report(
+    kind: CrashKind,
+    app_version: String,
+    message: String,
+    recent_logs: Vec<String>,
+) -> CrashReport {
+    let now = chrono::Utc::now();
+    let suffix = uuid::Uuid::new_v4().simple().to_string();
+    CrashReport {
+        id: format!("{}-{}", now.format("%Y%m%d-%H%M%S"), &suffix[..8]),
+        kind,
+        timestamp: now.to_rfc3339(),
+        app_version,
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        thread: None,
+        message,
+        location: None,
+        backtrace: None,
+        recent_logs,
+        state: None,
+    }
+}
+
+fn write_report(dir: &Path, report: &CrashReport) -> Result<PathBuf, String> {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("{}.json", report.id));
+    let json = serde_json::to_string_pretty(report).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+fn panic_message(info: &PanicHookInfo) -> String {
+    if let Some(message) = info.payload().downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = info.payload().downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    }
+}
+
+/// Runs inside the panic hook: no blocking locks, no logging through `log`
+fn report_panic(context: &CrashContext, info: &PanicHookInfo) {
+    let sessions = super::session_status::try_snapshot(&context.app).unwrap_or_default();
+    let mut report = new_report(
+        CrashKind::Panic,
+        context.app.package_info().version.to_string(),
+        panic_message(info),
+        logging::recent_lines(),
+    );
+    report.thread = std::thread::current().name().map(str::to_string);
+    report.location = info
+        .location()
+        .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+    report.backtrace = Some(std::backtrace::Backtrace::force_capture().to_string());
+    report.state = Some(CrashAppState {
+        uptime_secs: context.started.elapsed().as_secs(),
+        running_sessions: sessions.len(),
+        models: sessions.into_iter().map(|s| s.model).collect(),
+    });
+    match write_report(&context.dir, &report) {
+        Ok(path) => eprintln!("Crash report written to {}", path.display()),
+        Err(e) => eprintln!("Failed to write crash report: {}", e),
+    }
+}
+
+/// Last `LOG_TAIL` lines of the previous run's log file, as plain text
+fn previous_log_tail(app: &AppHandle) -> Vec<String> {
+    let path = match crate::paths::app_log_dir(app) {
+        Ok(dir) => dir.join("workbench.jsonl"),
+        Err(_) => return Vec::new(),
+    };
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(_) => return Vec::new(),
+    };
+    let mut tail = std::collections::VecDeque::with_capacity(LOG_TAIL);
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        if tail.len() == LOG_TAIL {
+            tail.pop_front();
+        }
+        let text = match serde_json::from_str::<logging::LogRecord>(&line) {
+            Ok(record) => format!(
+                "{} {:5} {}: {}",
+                record.timestamp, record.level, record.module, record.message
+            ),
+            Err(_) => line,
+        };
+        tail.push_back(text);
+    }
+    tail.into()
+}
+
+/// Whether a panic report was written after `since`
+fn has_report_since(dir: &Path, since: &str) -> bool {
+    let since = match chrono::DateTime::parse_from_rfc3339(since) {
+        Ok(since) => since,
+        Err(_) => return false,
+    };
+    read_reports(dir).iter().any(|report| {
+        report.kind == CrashKind::Panic
+            && chrono::DateTime::parse_from_rfc3339(&report.timestamp)
+                .is_ok_and(|timestamp| timestamp >= since)
+    })
+}
+
+fn read_reports(dir: &Path) -> Vec<CrashReport> {
+    let mut reports: Vec<CrashReport> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+                .filter(|p| p.file_name().is_some_and(|name| name != RUNNING_MARKER))
+                .filter_map(|p| {
+                    let content = fs::read_to_string(&p).ok()?;
+                    serde_json::from_str(&content).ok()
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    reports.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    reports
+}
+
+/// Install the panic hook and check how the previous run ended. Call before the
+/// log writer starts, so the previous log file still ends where that run stopped.
+pub fn init_crash_reports(app: &AppHandle) {
+    let dir = match crash_dir(app) {
+        Ok(dir) => dir,
+        Err(e) => {
+            warn!("Crash reporting disabled: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = fs::create_dir_all(&dir) {
+        warn!("Crash reporting disabled: {}", e);
+        return;
+    }
+
+    let marker = dir.join(RUNNING_MARKER);
+    if let Some(previous) = fs::read_to_string(&marker)
+        .ok()
+        .and_then(|content| serde_json::from_str::<RunningMarker>(&content).ok())
+    {
+        if !has_report_since(&dir, &previous.started_at) {
+            let report = new_report(
+                CrashKind::UncleanExit,
+                app.package_info().version.to_string(),
+                format!(
+                    "The previous run (PID {}, started {}) did not shut down cleanly",
+                    previous.pid, previous.started_at
+                ),
+                previous_log_tail(app),
+            );
+            match write_report(&dir, &report) {
+                Ok(_) => warn!(
+                    "Previous run ended uncleanly, wrote crash report {}",
+                    report.id
+                ),
+                Err(e) => warn!("Failed to write crash report: {}", e),
+            }
+        }
+    }
+    let running = RunningMarker {
+        pid: std::process::id(),
+        started_at: chrono::Utc::now().to_rfc3339(),
+    };
+    if let Err(e) = serde_json::to_string(&running)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(&marker, json).map_err(|e| e.to_string()))
+    {
+        warn!("Failed to write run marker: {}", e);
+    }
+
+    let _ = CONTEXT.set(CrashContext {
+        app: app.clone(),
+        dir: dir.clone(),
+        started: std::time::Instant::now(),
+    });
+    let previous_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if let Some(context) = CONTEXT.get() {
+            report_panic(context, info);
+        }
+        previous_hook(info);
+    }));
+
+    let count = read_reports(&dir).len();
+    if count > 0 {
+        info!("{} crash reports on disk", count);
+        events::emit(app, AppEvent::CrashReportsFound(count));
+    }
+}
+
+/// Remove the run marker; called when the app exits normally
+pub fn mark_clean_exit(app: &AppHandle) {
+    if let Ok(dir) = crash_dir(app) {
+        let _ = fs::remove_file(dir.join(RUNNING_MARKER));
+    }
+}
+
+fn check_id(id: &str) -> Result<(), String> {
+    if !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        Ok(())
+    } else {
+        Err(format!("Invalid crash report id: {}", id))
+    }
+}
+
+// ============ Tauri Commands ============
+
+/// Crash reports on disk, newest first
+#[tauri::command]
+pub async fn list_crash_reports(app: AppHandle) -> Result<Vec<CrashReport>, String> {
+    Ok(read_reports(&crash_dir(&app)?))
+}
+
+#[tauri::command]
+pub async fn delete_crash_report(app: AppHandle, id: String) -> Result<(), String> {
+    check_id(&id)?;
+    let path = crash_dir(&app)?.join(format!("{}.json", id));
+    if !path.exists() {
+        return Err(format!("Crash report {} not found", id));
+    }
+    fs::remove_file(&path).map_err(|e| format!("Failed to delete crash report: {}", e))?;
+    info!("Deleted crash report {}", id);
+    Ok(())
+}
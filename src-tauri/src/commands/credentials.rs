@@ -0,0 +1,347 @@
+use log::{info, warn};
+/// Credential vault
+///
+/// Secrets (API keys, tokens) are stored in the OS keychain through `keyring`; only
+/// their names and metadata live in the `credentials` table. A credential bound to
+/// an environment variable is injected into every Claude CLI process, which is also
+/// how provider profiles reference their key without writing it to settings.json.
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use super::storage::AgentDb;
+use crate::paths;
+
+/// Keychain service name all credentials are stored under
+const KEYRING_SERVICE: &str = "claude-workbench";
+
+/// Credential metadata; the secret itself is never returned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialInfo {
+    pub name: String,
+    /// Environment variable the secret is injected as, e.g. `ANTHROPIC_API_KEY`
+    pub env_var: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+fn check_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid credential name '{}': use letters, digits, '-', '_' and '.'",
+            name
+        ))
+    }
+}
+
+fn check_env_var(env_var: &str) -> Result<(), String> {
+    let valid = !env_var.is_empty()
+        && !env_var.starts_with(|c: char| c.is_ascii_digit())
+        && env_var
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid environment variable name: {}", env_var))
+    }
+}
+
+fn keyring_entry(name: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, name)
+        .map_err(|e| format!("Failed to open keychain entry: {}", e))
+}
+
+/// Keychain access blocks (and may talk to D-Bus), so keep it off the async runtime.
+/// Portable installs must not leave secrets on the machine, so they have no keychain.
+async fn with_keyring<T, F>(f: F) -> Result<T, String>
+where
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+    T: Send + 'static,
+{
+    if paths::is_portable() {
+        return Err(
+            "The keychain is not used in portable mode; keep secrets in an environment profile"
+                .to_string(),
+        );
+    }
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("Keychain task failed: {}", e))?
+}
+
+/// Check that the keychain answers, without writing to it
+pub(crate) async fn probe_keychain() -> Result<(), String> {
+    with_keyring(|| match keyring_entry("keychain-probe")?.get_password() {
+        Ok(_) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("Keychain is not accessible: {}", e)),
+    })
+    .await
+}
+
+fn get_info(conn: &Connection, name: &str) -> Result<Option<CredentialInfo>, String> {
+    conn.query_row(
+        "SELECT name, env_var, created_at, updated_at FROM credentials WHERE name = ?1",
+        [name],
+        |row| {
+            Ok(CredentialInfo {
+                name: row.get(0)?,
+                env_var: row.get(1)?,
+                created_at: row.get(2)?,
+                updated_at: row.get(3)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Point `env_var` at a credential, unbinding whichever credential had it before
+pub fn bind_env_var(app: &AppHandle, name: &str, env_var: &str) -> Result<(), String> {
+    check_env_var(env_var)?;
+    let db = app.state::<AgentDb>();
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    if get_info(&conn, name)?.is_none() {
+        return Err(format!("Credential {} not found", name));
+    }
+
+    conn.execute(
+        "UPDATE credentials SET env_var = NULL WHERE env_var = ?1 AND name != ?2",
+        params![env_var, name],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE credentials SET env_var = ?1, updated_at = ?2 WHERE name = ?3",
+        params![env_var, chrono::Utc::now().to_rfc3339(), name],
+    )
+    .map_err(|e| e.to_string())?;
+    info!("Bound credential {} to {}", name, env_var);
+    Ok(())
+}
+
+/// Unbind every credential from `env_vars`, so none of them is injected any more
+pub fn unbind_env_vars(app: &AppHandle, env_vars: &[&str]) -> Result<(), String> {
+    let db = app.state::<AgentDb>();
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let placeholders = vec!["?"; env_vars.len()].join(", ");
+    let unbound = conn
+        .execute(
+            &format!(
+                "UPDATE credentials SET env_var = NULL WHERE env_var IN ({})",
+                placeholders
+            ),
+            rusqlite::params_from_iter(env_vars),
+        )
+        .map_err(|e| e.to_string())?;
+    if unbound > 0 {
+        info!("Unbound {} credential(s) from provider variables", unbound);
+    }
+    Ok(())
+}
+
+/// Environment variables for every bound credential, read from the keychain.
+/// Credentials that cannot be read are skipped with a warning.
+pub async fn credential_env(app: &AppHandle) -> Vec<(String, String)> {
+    let bindings: Vec<(String, String)> = {
+        let db = match app.try_state::<AgentDb>() {
+            Some(db) => db,
+            None => return Vec::new(),
+        };
+        let conn = match db.0.lock() {
+            Ok(conn) => conn,
+            Err(_) => return Vec::new(),
+        };
+        let result = conn
+            .prepare("SELECT name, env_var FROM credentials WHERE env_var IS NOT NULL")
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                    .collect()
+            });
+        match result {
+            Ok(bindings) => bindings,
+            Err(e) => {
+                warn!("Failed to read credential bindings: {}", e);
+                return Vec::new();
+            }
+        }
+    };
+    if bindings.is_empty() {
+        return Vec::new();
+    }
+
+    let secrets = with_keyring(move || {
+        Ok(bindings
+            .into_iter()
+            .filter_map(|(name, env_var)| {
+                let secret = keyring_entry(&name)
+                    .and_then(|entry| entry.get_password().map_err(|e| e.to_string()));
+                match secret {
+                    Ok(secret) => Some((env_var, secret)),
+                    Err(e) => {
+                        warn!("Failed to read credential {} from keychain: {}", name, e);
+                        None
+                    }
+                }
+            })
+            .collect())
+    })
+    .await;
+    secrets.unwrap_or_default()
+}
+
+/// A credential with its secret, as carried by an encrypted profile export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedCredential {
+    pub name: String,
+    pub env_var: Option<String>,
+    pub secret: String,
+}
+
+/// Every stored credential with its secret. Fails if any secret cannot be read,
+/// so an export never silently drops one.
+pub(crate) async fn export_credentials(app: &AppHandle) -> Result<Vec<ExportedCredential>, String> {
+    let entries: Vec<(String, Option<String>)> = {
+        let db = app.state::<AgentDb>();
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare("SELECT name, env_var FROM credentials ORDER BY name")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        rows
+    };
+
+    with_keyring(move || {
+        entries
+            .into_iter()
+            .map(|(name, env_var)| {
+                let secret = keyring_entry(&name)?.get_password().map_err(|e| {
+                    format!("Failed to read credential {} from keychain: {}", name, e)
+                })?;
+                Ok(ExportedCredential {
+                    name,
+                    env_var,
+                    secret,
+                })
+            })
+            .collect()
+    })
+    .await
+}
+
+/// Store exported credentials, replacing same-named ones. Returns how many were stored.
+pub(crate) async fn import_credentials(
+    app: &AppHandle,
+    credentials: Vec<ExportedCredential>,
+) -> Result<usize, String> {
+    let mut imported = 0;
+    for credential in credentials {
+        let db = app.state::<AgentDb>();
+        set_credential(db, credential.name, credential.secret, credential.env_var).await?;
+        imported += 1;
+    }
+    Ok(imported)
+}
+
+// ============ Tauri Commands ============
+
+/// Store a secret in the keychain, creating or replacing the credential
+#[tauri::command]
+pub async fn set_credential(
+    db: State<'_, AgentDb>,
+    name: String,
+    secret: String,
+    env_var: Option<String>,
+) -> Result<CredentialInfo, String> {
+    check_name(&name)?;
+    let env_var = env_var.filter(|v| !v.trim().is_empty());
+    if let Some(env_var) = &env_var {
+        check_env_var(env_var)?;
+    }
+    if secret.is_empty() {
+        return Err("Secret cannot be empty".to_string());
+    }
+
+    let entry_name = name.clone();
+    with_keyring(move || {
+        keyring_entry(&entry_name)?
+            .set_password(&secret)
+            .map_err(|e| format!("Failed to store credential in keychain: {}", e))
+    })
+    .await?;
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    if let Some(env_var) = &env_var {
+        conn.execute(
+            "UPDATE credentials SET env_var = NULL WHERE env_var = ?1 AND name != ?2",
+            params![env_var, name],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    conn.execute(
+        "INSERT INTO credentials (name, env_var, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?3)
+         ON CONFLICT(name) DO UPDATE SET env_var = ?2, updated_at = ?3",
+        params![name, env_var, now],
+    )
+    .map_err(|e| format!("Failed to save credential: {}", e))?;
+
+    info!("Stored credential {}", name);
+    get_info(&conn, &name)?.ok_or_else(|| format!("Credential {} not found", name))
+}
+
+/// List stored credentials (names and bindings only)
+#[tauri::command]
+pub async fn list_credentials(db: State<'_, AgentDb>) -> Result<Vec<CredentialInfo>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare("SELECT name, env_var, created_at, updated_at FROM credentials ORDER BY name")
+        .map_err(|e| e.to_string())?;
+    let credentials = stmt
+        .query_map([], |row| {
+            Ok(CredentialInfo {
+                name: row.get(0)?,
+                env_var: row.get(1)?,
+                created_at: row.get(2)?,
+                updated_at: row.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(credentials)
+}
+
+/// Remove a credential from the keychain and the index
+#[tauri::command]
+pub async fn delete_credential(db: State<'_, AgentDb>, name: String) -> Result<(), String> {
+    check_name(&name)?;
+
+    // A portable install has nothing in the keychain, only the index entry
+    if !paths::is_portable() {
+        let entry_name = name.clone();
+        with_keyring(
+            move || match keyring_entry(&entry_name)?.delete_credential() {
+                Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+                Err(e) => Err(format!("Failed to delete credential from keychain: {}", e)),
+            },
+        )
+        .await?;
+    }
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM credentials WHERE name = ?1", [&name])
+        .map_err(|e| format!("Failed to delete credential: {}", e))?;
+    info!("Deleted credential {}", name);
+    Ok(())
+}
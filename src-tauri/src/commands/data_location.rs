@@ -0,0 +1,377 @@
+use log::{info, warn};
+/// Where the workbench keeps its data
+///
+/// By default the app database and files live in the platform app-data
+/// directory and Claude's data in `~/.claude`. A data root moves both, to
+/// `<root>/workbench` and `<root>/claude` (see `crate::paths`), e.g. onto an
+/// encrypted or synced volume. The root is kept in `data_location.json` in the
+/// app config directory, since it cannot live in the database it moves, and is
+/// read once at startup. Claude CLI processes started by the workbench get
+/// `CLAUDE_CONFIG_DIR`; a `claude` run from a terminal needs the same variable
+/// to see the moved data.
+///
+/// `move_data_directory` copies both directories under a new root, verifies the
+/// copy against the originals and then switches by replacing the location file
+/// in one rename. The new root is used from the next launch; the old
+/// directories are left in place for the user to remove.
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime, State};
+use walkdir::WalkDir;
+
+use super::claude::get_claude_dir;
+use super::config_io::write_json_atomic;
+use super::storage::AgentDb;
+use crate::paths::{self, APP_DATA_SUBDIR, CLAUDE_JSON, CLAUDE_SUBDIR};
+use crate::process::ProcessRegistryState;
+
+/// File in the app config directory holding the data root
+const LOCATION_FILE: &str = "data_location.json";
+
+const DATABASE_FILE: &str = "agents.db";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct LocationFile {
+    data_root: Option<PathBuf>,
+    moved_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataLocation {
+    pub portable: bool,
+    /// Data root in use, None for the platform defaults
+    pub data_root: Option<String>,
+    pub app_data_dir: String,
+    pub claude_dir: String,
+    /// Data root that takes effect at the next launch, if it differs
+    pub pending_root: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveResult {
+    pub data_root: String,
+    pub app_data_dir: String,
+    pub claude_dir: String,
+    pub files_copied: u64,
+    pub bytes_copied: u64,
+    /// Directories the data was copied from, left in place
+    pub previous_app_data_dir: String,
+    pub previous_claude_dir: String,
+    pub restart_required: bool,
+}
+
+fn location_file<R: Runtime>(app: &impl Manager<R>) -> Result<PathBuf, String> {
+    paths::app_config_dir(app)
+        .map(|dir| dir.join(LOCATION_FILE))
+        .map_err(|e| format!("Failed to get app config dir: {}", e))
+}
+
+fn read_location_file(path: &Path) -> Result<LocationFile, String> {
+    match fs::read_to_string(path) {
+        Ok(content) => {
+            serde_json::from_str(&content).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(LocationFile::default()),
+        Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+    }
+}
+
+/// Replace the location file in one rename, so it is never half written
+fn write_location_file(path: &Path, location: &LocationFile) -> Result<(), String> {
+    let value = serde_json::to_value(location).map_err(|e| e.to_string())?;
+    write_json_atomic(path, &value).map(|_| ())
+}
+
+/// Read the data root; must run before anything resolves a data directory. A
+/// root that is missing, e.g. an unmounted volume, falls back to the defaults.
+/// Portable mode has its own layout and ignores it.
+pub fn init(app: &AppHandle) {
+    if paths::is_portable() {
+        info!(
+            "Portable mode, keeping data under {:?}",
+            paths::portable_root()
+        );
+        return;
+    }
+    let location = match location_file(app).and_then(|path| read_location_file(&path)) {
+        Ok(location) => location,
+        Err(e) => {
+            warn!("Failed to read the data location, using defaults: {}", e);
+            return;
+        }
+    };
+    let Some(root) = location.data_root else {
+        return;
+    };
+    if !root.join(APP_DATA_SUBDIR).is_dir() {
+        warn!(
+            "Data root {} is not available, using the default data directories",
+            root.display()
+        );
+        return;
+    }
+    info!("Using data root {}", root.display());
+    paths::set_data_root(root);
+}
+
+fn hash_file(path: &Path) -> io::Result<Vec<u8>> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(hasher.finalize().to_vec())
+}
+
+/// Copy a file and check the copy hashes the same as the original
+fn copy_verified(from: &Path, to: &Path) -> Result<u64, String> {
+    if let Some(dir) = to.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let bytes =
+        fs::copy(from, to).map_err(|e| format!("Failed to copy {}: {}", from.display(), e))?;
+    let original =
+        hash_file(from).map_err(|e| format!("Failed to read {}: {}", from.display(), e))?;
+    let copy = hash_file(to).map_err(|e| format!("Failed to read {}: {}", to.display(), e))?;
+    if original != copy {
+        return Err(format!(
+            "{} changed while it was copied; close other Claude sessions and retry",
+            from.display()
+        ));
+    }
+    Ok(bytes)
+}
+
+#[derive(Default)]
+struct CopyStats {
+    files: u64,
+    bytes: u64,
+}
+
+/// Copy a directory tree file by file, skipping the entries `skip` rejects.
+/// Symlinks are not followed or copied.
+fn copy_tree(
+    from: &Path,
+    to: &Path,
+    skip: impl Fn(&Path) -> bool,
+    stats: &mut CopyStats,
+) -> Result<(), String> {
+    fs::create_dir_all(to).map_err(|e| format!("Failed to create {}: {}", to.display(), e))?;
+    if !from.exists() {
+        return Ok(());
+    }
+    for entry in WalkDir::new(from).min_depth(1) {
+        let entry = entry.map_err(|e| format!("Failed to read {}: {}", from.display(), e))?;
+        let relative = entry.path().strip_prefix(from).unwrap_or(entry.path());
+        if skip(relative) {
+            continue;
+        }
+        let target = to.join(relative);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)
+                .map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+        } else if entry.file_type().is_file() {
+            stats.bytes += copy_verified(entry.path(), &target)?;
+            stats.files += 1;
+        } else {
+            warn!("Not moving {}: not a regular file", entry.path().display());
+        }
+    }
+    Ok(())
+}
+
+/// The live database and its journal files, which are copied through SQLite
+fn is_database_file(relative: &Path) -> bool {
+    relative
+        .to_str()
+        .is_some_and(|name| name.starts_with(DATABASE_FILE) && !name.contains(['/', '\\']))
+}
+
+/// Copy the database through SQLite, consistent even while it is written, and
+/// check the copy
+fn copy_database(db: &AgentDb, to: &Path) -> Result<u64, String> {
+    {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        conn.execute("VACUUM INTO ?1", [to.to_string_lossy()])
+            .map_err(|e| format!("Failed to copy the database: {}", e))?;
+    }
+    let copy = rusqlite::Connection::open(to).map_err(|e| e.to_string())?;
+    let check: String = copy
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to check the database copy: {}", e))?;
+    if check != "ok" {
+        return Err(format!("The database copy is damaged: {}", check));
+    }
+    fs::metadata(to)
+        .map(|meta| meta.len())
+        .map_err(|e| e.to_string())
+}
+
+fn validate_root(new_root: &Path, current: &[&Path]) -> Result<(), String> {
+    if !new_root.is_absolute() {
+        return Err(format!("{} is not an absolute path", new_root.display()));
+    }
+    for dir in current {
+        if new_root.starts_with(dir) {
+            return Err(format!(
+                "{} overlaps the current data directory {}",
+                new_root.display(),
+                dir.display()
+            ));
+        }
+    }
+    for subdir in [APP_DATA_SUBDIR, CLAUDE_SUBDIR] {
+        if new_root.join(subdir).exists() {
+            return Err(format!(
+                "{} already exists; pick a root without it",
+                new_root.join(subdir).display()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Copy everything under a staging directory in `new_root`, then move the two
+/// directories into place
+fn copy_to_root(
+    db: &AgentDb,
+    app_data: &Path,
+    claude_dir: &Path,
+    claude_json: Option<PathBuf>,
+    new_root: &Path,
+) -> Result<CopyStats, String> {
+    let staging = new_root.join(format!(".workbench-move-{}", uuid::Uuid::new_v4()));
+    let result = (|| {
+        let mut stats = CopyStats::default();
+        let staged_app_data = staging.join(APP_DATA_SUBDIR);
+        let staged_claude = staging.join(CLAUDE_SUBDIR);
+
+        copy_tree(app_data, &staged_app_data, is_database_file, &mut stats)?;
+        stats.bytes += copy_database(db, &staged_app_data.join(DATABASE_FILE))?;
+        stats.files += 1;
+        copy_tree(claude_dir, &staged_claude, |_| false, &mut stats)?;
+        if let Some(claude_json) = claude_json.filter(|path| path.is_file()) {
+            stats.bytes += copy_verified(&claude_json, &staged_claude.join(CLAUDE_JSON))?;
+            stats.files += 1;
+        }
+
+        // Same filesystem as the targets, so these are renames, not copies
+        for (staged, subdir) in [
+            (&staged_app_data, APP_DATA_SUBDIR),
+            (&staged_claude, CLAUDE_SUBDIR),
+        ] {
+            fs::rename(staged, new_root.join(subdir))
+                .map_err(|e| format!("Failed to move {} into place: {}", subdir, e))?;
+        }
+        Ok(stats)
+    })();
+    if let Err(e) = fs::remove_dir_all(&staging) {
+        if e.kind() != io::ErrorKind::NotFound {
+            warn!("Failed to remove {}: {}", staging.display(), e);
+        }
+    }
+    result
+}
+
+// ============ Tauri Commands ============
+
+#[tauri::command]
+pub async fn get_data_location(app: AppHandle) -> Result<DataLocation, String> {
+    let configured = read_location_file(&location_file(&app)?)?.data_root;
+    let current = paths::data_root();
+    Ok(DataLocation {
+        portable: paths::is_portable(),
+        data_root: current
+            .as_ref()
+            .map(|root| root.to_string_lossy().to_string()),
+        app_data_dir: paths::app_data_dir(&app)
+            .map_err(|e| e.to_string())?
+            .to_string_lossy()
+            .to_string(),
+        claude_dir: get_claude_dir()
+            .map_err(|e| e.to_string())?
+            .to_string_lossy()
+            .to_string(),
+        pending_root: configured
+            .filter(|root| Some(root) != current.as_ref())
+            .map(|root| root.to_string_lossy().to_string()),
+    })
+}
+
+/// Copy the app data and Claude's data under `new_path`, verify the copy and
+/// switch to it from the next launch. Refused while processes are running.
+#[tauri::command]
+pub async fn move_data_directory(
+    app: AppHandle,
+    registry: State<'_, ProcessRegistryState>,
+    new_path: String,
+) -> Result<MoveResult, String> {
+    if paths::is_portable() {
+        return Err(
+            "Portable mode keeps data next to the executable; it cannot be moved".to_string(),
+        );
+    }
+    let running = registry.0.get_running_processes()?;
+    if !running.is_empty() {
+        return Err(format!(
+            "{} processes are running; stop them before moving the data directory",
+            running.len()
+        ));
+    }
+
+    let new_root = PathBuf::from(new_path.trim());
+    let app_data = paths::app_data_dir(&app).map_err(|e| e.to_string())?;
+    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    validate_root(&new_root, &[&app_data, &claude_dir])?;
+    fs::create_dir_all(&new_root)
+        .map_err(|e| format!("Failed to create {}: {}", new_root.display(), e))?;
+
+    // With the default layout the CLI keeps `.claude.json` beside `~/.claude`
+    let claude_json = match paths::data_root() {
+        Some(_) => None,
+        None => paths::claude_json_path().ok(),
+    };
+    info!(
+        "Moving data from {} and {} to {}",
+        app_data.display(),
+        claude_dir.display(),
+        new_root.display()
+    );
+    let stats = {
+        let app = app.clone();
+        let (app_data, claude_dir, new_root) =
+            (app_data.clone(), claude_dir.clone(), new_root.clone());
+        tauri::async_runtime::spawn_blocking(move || {
+            let db = app.state::<AgentDb>();
+            copy_to_root(&db, &app_data, &claude_dir, claude_json, &new_root)
+        })
+        .await
+        .map_err(|e| e.to_string())??
+    };
+
+    write_location_file(
+        &location_file(&app)?,
+        &LocationFile {
+            data_root: Some(new_root.clone()),
+            moved_at: Some(chrono::Utc::now().to_rfc3339()),
+        },
+    )?;
+    info!(
+        "Copied {} files ({} bytes) to {}; it is used from the next launch",
+        stats.files,
+        stats.bytes,
+        new_root.display()
+    );
+
+    Ok(MoveResult {
+        data_root: new_root.to_string_lossy().to_string(),
+        app_data_dir: new_root.join(APP_DATA_SUBDIR).to_string_lossy().to_string(),
+        claude_dir: new_root.join(CLAUDE_SUBDIR).to_string_lossy().to_string(),
+        files_copied: stats.files,
+        bytes_copied: stats.bytes,
+        previous_app_data_dir: app_data.to_string_lossy().to_string(),
+        previous_claude_dir: claude_dir.to_string_lossy().to_string(),
+        restart_required: true,
+    })
+}
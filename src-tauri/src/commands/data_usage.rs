@@ -0,0 +1,385 @@
+use log::{info, warn};
+/// Disk usage of `~/.claude` and the workbench's own data
+///
+/// `analyze_data_usage` breaks the space down by category: transcripts per
+/// project, attachments pasted into prompts, the CLI's file checkpoints
+/// (`file-history`), logs, caches, retention archives and everything else.
+/// `clean_category` reclaims the space of one category. It removes whole
+/// entries (a session's checkpoint directory, an old log file) and leaves
+/// alone anything modified in the last day or belonging to a running session.
+/// Transcripts and archives are never removed here; retention rules and the
+/// batch commands handle those.
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::AppHandle;
+use walkdir::WalkDir;
+
+use super::claude::get_claude_dir;
+use super::session_state;
+
+/// Entries modified more recently than this are kept by `clean_category`
+const MIN_AGE: Duration = Duration::from_secs(24 * 3600);
+
+/// Log file the workbench is writing to
+const ACTIVE_LOG: &str = "workbench.jsonl";
+
+/// Directories of `~/.claude` that belong to a category
+const CLAUDE_SUBDIRS: &[(&str, DataCategory)] = &[
+    ("projects", DataCategory::Transcripts),
+    ("file-history", DataCategory::Checkpoints),
+    ("debug", DataCategory::Logs),
+    ("shell-snapshots", DataCategory::Caches),
+    ("statsig", DataCategory::Caches),
+    ("todos", DataCategory::Caches),
+    ("archive", DataCategory::Archives),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataCategory {
+    Transcripts,
+    Attachments,
+    Checkpoints,
+    Logs,
+    Caches,
+    Archives,
+    /// Settings, plugins and anything else in `~/.claude`
+    Other,
+}
+
+impl DataCategory {
+    const ALL: [DataCategory; 7] = [
+        DataCategory::Transcripts,
+        DataCategory::Attachments,
+        DataCategory::Checkpoints,
+        DataCategory::Logs,
+        DataCategory::Caches,
+        DataCategory::Archives,
+        DataCategory::Other,
+    ];
+
+    fn cleanable(self) -> bool {
+        matches!(
+            self,
+            DataCategory::Attachments
+                | DataCategory::Checkpoints
+                | DataCategory::Logs
+                | DataCategory::Caches
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryUsage {
+    pub category: DataCategory,
+    pub bytes: u64,
+    pub files: u64,
+    /// Directories the category covers
+    pub locations: Vec<String>,
+    pub cleanable: bool,
+    /// What `clean_category` would remove now
+    pub reclaimable_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectDataUsage {
+    pub project_id: String,
+    pub sessions: usize,
+    pub bytes: u64,
+}
+
+/// Result of `analyze_data_usage`; categories and projects largest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataUsageReport {
+    pub claude_dir: String,
+    pub total_bytes: u64,
+    pub categories: Vec<CategoryUsage>,
+    pub projects: Vec<ProjectDataUsage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanResult {
+    pub category: DataCategory,
+    pub dry_run: bool,
+    /// Entries removed, or that would be with `dry_run`
+    pub removed: usize,
+    pub files: u64,
+    pub bytes: u64,
+    /// Entries kept because they are recent or their session is running
+    pub kept: usize,
+    pub errors: Vec<String>,
+}
+
+/// Size, file count and last modification of a file or directory tree
+#[derive(Default)]
+struct Measure {
+    bytes: u64,
+    files: u64,
+    newest: Option<SystemTime>,
+}
+
+impl Measure {
+    fn of(path: &Path) -> Self {
+        let mut measure = Measure::default();
+        for entry in WalkDir::new(path).into_iter().filter_map(|e| e.ok()) {
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+            if let Ok(modified) = metadata.modified() {
+                measure.newest = Some(measure.newest.map_or(modified, |n| n.max(modified)));
+            }
+            if metadata.is_file() {
+                measure.bytes += metadata.len();
+                measure.files += 1;
+            }
+        }
+        measure
+    }
+
+    fn add(&mut self, other: &Measure) {
+        self.bytes += other.bytes;
+        self.files += other.files;
+    }
+}
+
+fn claude_dir() -> Result<PathBuf, String> {
+    get_claude_dir().map_err(|e| e.to_string())
+}
+
+/// Directories holding a category's data; Other is the rest of `~/.claude`
+fn locations(app: &AppHandle, category: DataCategory) -> Result<Vec<PathBuf>, String> {
+    let claude = claude_dir()?;
+    let mut dirs: Vec<PathBuf> = CLAUDE_SUBDIRS
+        .iter()
+        .filter(|(_, c)| *c == category)
+        .map(|(name, _)| claude.join(name))
+        .collect();
+    match category {
+        DataCategory::Attachments => {
+            if let Ok(dir) = crate::paths::app_data_dir(app) {
+                dirs.push(dir.join("attachments"));
+            }
+        }
+        DataCategory::Logs => {
+            if let Ok(dir) = crate::paths::app_log_dir(app) {
+                dirs.push(dir);
+            }
+        }
+        DataCategory::Caches => {
+            dirs.push(crate::paths::temp_dir().join("claude_workbench_clipboard_images"));
+        }
+        _ => {}
+    }
+    Ok(dirs)
+}
+
+/// Top-level entries of `~/.claude` that no category claims
+fn other_entries() -> Result<Vec<PathBuf>, String> {
+    let claimed: HashSet<&str> = CLAUDE_SUBDIRS.iter().map(|(name, _)| *name).collect();
+    Ok(match fs::read_dir(claude_dir()?) {
+        Ok(entries) => entries
+            .flatten()
+            .filter(|e| !claimed.contains(e.file_name().to_string_lossy().as_ref()))
+            .map(|e| e.path())
+            .collect(),
+        Err(_) => Vec::new(),
+    })
+}
+
+/// Session an entry belongs to: `<id>` directories and `<id>-agent-<id>.json`
+/// todo files
+fn entry_session_id(path: &Path) -> Option<String> {
+    let name = if path.is_dir() {
+        path.file_name()?
+    } else {
+        path.file_stem()?
+    }
+    .to_string_lossy();
+    let id = name.split("-agent-").next().unwrap_or(&name);
+    Some(id.to_string())
+}
+
+/// Top-level entries `clean_category` may remove, with their measures, and
+/// how many it keeps
+fn clean_plan(
+    app: &AppHandle,
+    category: DataCategory,
+) -> Result<(Vec<(PathBuf, Measure)>, usize), String> {
+    let now = SystemTime::now();
+    let mut removable = Vec::new();
+    let mut kept = 0;
+    for dir in locations(app, category)? {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if category == DataCategory::Logs && entry.file_name() == ACTIVE_LOG {
+                kept += 1;
+                continue;
+            }
+            let measure = Measure::of(&path);
+            let recent = measure
+                .newest
+                .is_some_and(|t| now.duration_since(t).unwrap_or_default() < MIN_AGE);
+            let live = entry_session_id(&path)
+                .and_then(|id| session_state::phase(app, &id))
+                .is_some_and(|p| p.is_live());
+            if recent || live {
+                kept += 1;
+            } else {
+                removable.push((path, measure));
+            }
+        }
+    }
+    Ok((removable, kept))
+}
+
+/// Transcripts per project under `~/.claude/projects`
+fn project_usage() -> Result<Vec<ProjectDataUsage>, String> {
+    let projects_dir = claude_dir()?.join("projects");
+    let mut projects: Vec<ProjectDataUsage> = match fs::read_dir(&projects_dir) {
+        Ok(entries) => entries
+            .flatten()
+            .filter(|e| e.path().is_dir())
+            .map(|project| {
+                let sessions = fs::read_dir(project.path())
+                    .map(|entries| {
+                        entries
+                            .flatten()
+                            .filter(|e| {
+                                e.path().extension().and_then(|x| x.to_str()) == Some("jsonl")
+                            })
+                            .count()
+                    })
+                    .unwrap_or(0);
+                ProjectDataUsage {
+                    project_id: project.file_name().to_string_lossy().to_string(),
+                    sessions,
+                    bytes: Measure::of(&project.path()).bytes,
+                }
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    projects.sort_by_key(|p| std::cmp::Reverse(p.bytes));
+    Ok(projects)
+}
+
+fn analyze(app: &AppHandle) -> Result<DataUsageReport, String> {
+    let mut categories = Vec::new();
+    for category in DataCategory::ALL {
+        let paths = match category {
+            DataCategory::Other => other_entries()?,
+            _ => locations(app, category)?,
+        };
+        let mut total = Measure::default();
+        for path in &paths {
+            total.add(&Measure::of(path));
+        }
+        let reclaimable_bytes = if category.cleanable() {
+            let (removable, _) = clean_plan(app, category)?;
+            removable.iter().map(|(_, m)| m.bytes).sum()
+        } else {
+            0
+        };
+        let locations = match category {
+            DataCategory::Other => vec![claude_dir()?.to_string_lossy().to_string()],
+            _ => paths
+                .iter()
+                .map(|p| p.to_string_lossy().to_string())
+                .collect(),
+        };
+        categories.push(CategoryUsage {
+            category,
+            bytes: total.bytes,
+            files: total.files,
+            locations,
+            cleanable: category.cleanable(),
+            reclaimable_bytes,
+        });
+    }
+    categories.sort_by_key(|c| std::cmp::Reverse(c.bytes));
+    Ok(DataUsageReport {
+        claude_dir: claude_dir()?.to_string_lossy().to_string(),
+        total_bytes: categories.iter().map(|c| c.bytes).sum(),
+        categories,
+        projects: project_usage()?,
+    })
+}
+
+fn clean(app: &AppHandle, category: DataCategory, dry_run: bool) -> Result<CleanResult, String> {
+    if !category.cleanable() {
+        return Err(match category {
+            DataCategory::Transcripts | DataCategory::Archives => format!(
+                "{:?} are not cleaned here; use retention rules or delete sessions instead",
+                category
+            ),
+            _ => format!("{:?} data cannot be cleaned", category),
+        });
+    }
+    let (removable, kept) = clean_plan(app, category)?;
+    let mut result = CleanResult {
+        category,
+        dry_run,
+        removed: 0,
+        files: 0,
+        bytes: 0,
+        kept,
+        errors: Vec::new(),
+    };
+    for (path, measure) in removable {
+        if !dry_run {
+            let removed = if path.is_dir() {
+                fs::remove_dir_all(&path)
+            } else {
+                fs::remove_file(&path)
+            };
+            if let Err(e) = removed {
+                warn!("Failed to remove {}: {}", path.display(), e);
+                result
+                    .errors
+                    .push(format!("Failed to remove {}: {}", path.display(), e));
+                continue;
+            }
+        }
+        result.removed += 1;
+        result.files += measure.files;
+        result.bytes += measure.bytes;
+    }
+    if !dry_run {
+        info!(
+            "Cleaned {:?}: removed {} entries ({} bytes), kept {}",
+            category, result.removed, result.bytes, result.kept
+        );
+    }
+    Ok(result)
+}
+
+// ============ Tauri Commands ============
+
+/// Disk space taken by `~/.claude` and the workbench's data, by category
+#[tauri::command]
+pub async fn analyze_data_usage(app: AppHandle) -> Result<DataUsageReport, String> {
+    tauri::async_runtime::spawn_blocking(move || analyze(&app))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Remove the data of one category, or with `dry_run` report what would go
+#[tauri::command]
+pub async fn clean_category(
+    app: AppHandle,
+    category: DataCategory,
+    dry_run: Option<bool>,
+) -> Result<CleanResult, String> {
+    let dry_run = dry_run.unwrap_or(false);
+    tauri::async_runtime::spawn_blocking(move || clean(&app, category, dry_run))
+        .await
+        .map_err(|e| e.to_string())?
+}
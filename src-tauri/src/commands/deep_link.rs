@@ -0,0 +1,237 @@
+use log::{info, warn};
+/// `claude-workbench://` deep links
+///
+/// Links from terminals, docs and issue trackers open the app where they point:
+///
+/// - `claude-workbench://open?project=/path&session=abc` – open a project, and
+///   optionally one of its sessions
+/// - `claude-workbench://run-hook?event=OnSessionStart&project=/path` – run the
+///   hooks of an event
+///
+/// Every link is emitted as `deep-link` and kept as pending until the UI accepts or
+/// dismisses it, so links that arrive before the window is listening (the app was
+/// launched by the link) are not lost. A run-hook link only runs its hooks once it
+/// is accepted: any web page can contain such a link, and hooks run local commands.
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use super::enhanced_hooks::{trigger_hook_event, HookChainResult, HookContext};
+use crate::events::{self, AppEvent};
+
+pub const SCHEME: &str = "claude-workbench";
+
+/// What a link asks for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum DeepLinkAction {
+    Open {
+        project: Option<String>,
+        session: Option<String>,
+    },
+    RunHook {
+        event: String,
+        project: Option<String>,
+        session: Option<String>,
+    },
+}
+
+/// A received link waiting for the UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeepLink {
+    pub id: String,
+    pub url: String,
+    pub action: DeepLinkAction,
+    pub received_at: String,
+}
+
+/// Links received but not yet accepted or dismissed
+#[derive(Default)]
+pub struct DeepLinkState(Mutex<Vec<DeepLink>>);
+
+fn validate_project(project: Option<String>) -> Result<Option<String>, String> {
+    match project {
+        Some(project) if !Path::new(&project).is_dir() => {
+            Err(format!("Project directory not found: {}", project))
+        }
+        project => Ok(project),
+    }
+}
+
+fn validate_identifier(kind: &str, value: Option<String>) -> Result<Option<String>, String> {
+    match value {
+        Some(value)
+            if value.is_empty()
+                || !value
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') =>
+        {
+            Err(format!("Invalid {}: {}", kind, value))
+        }
+        value => Ok(value),
+    }
+}
+
+/// Parse a `claude-workbench://` URL
+pub fn parse_deep_link(url: &Url) -> Result<DeepLinkAction, String> {
+    if url.scheme() != SCHEME {
+        return Err(format!("Unsupported scheme: {}", url.scheme()));
+    }
+    // `claude-workbench://open` carries the action as host, `claude-workbench:open` as path
+    let action = url
+        .host_str()
+        .unwrap_or_else(|| url.path())
+        .trim_matches('/')
+        .to_lowercase();
+
+    let mut project = None;
+    let mut session = None;
+    let mut event = None;
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "project" => project = Some(value.into_owned()),
+            "session" => session = Some(value.into_owned()),
+            "event" => event = Some(value.into_owned()),
+            _ => {}
+        }
+    }
+    let project = validate_project(project)?;
+    let session = validate_identifier("session id", session)?;
+
+    match action.as_str() {
+        "open" => Ok(DeepLinkAction::Open { project, session }),
+        "run-hook" => {
+            let event = validate_identifier("hook event", event)?
+                .ok_or_else(|| "run-hook link is missing the event parameter".to_string())?;
+            Ok(DeepLinkAction::RunHook {
+                event,
+                project,
+                session,
+            })
+        }
+        _ => Err(format!("Unknown deep link action: {}", action)),
+    }
+}
+
+/// Bring the main window to the front
+pub fn focus_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Queue received links and tell the UI about them
+fn handle_urls(app: &AppHandle, urls: Vec<Url>) {
+    let state = match app.try_state::<DeepLinkState>() {
+        Some(state) => state,
+        None => return,
+    };
+    for url in urls {
+        let action = match parse_deep_link(&url) {
+            Ok(action) => action,
+            Err(e) => {
+                warn!("Ignoring deep link {}: {}", url, e);
+                let error = events::DeepLinkError {
+                    url: url.to_string(),
+                    error: e,
+                };
+                events::emit(app, AppEvent::DeepLinkError(error));
+                continue;
+            }
+        };
+        let link = DeepLink {
+            id: uuid::Uuid::new_v4().to_string(),
+            url: url.to_string(),
+            action,
+            received_at: chrono::Utc::now().to_rfc3339(),
+        };
+        info!("Received deep link {}", link.url);
+        if let Ok(mut pending) = state.0.lock() {
+            pending.push(link.clone());
+        }
+        events::emit(app, AppEvent::DeepLink(link));
+    }
+    focus_main_window(app);
+}
+
+/// Register the URL scheme and start listening for links, including the one that
+/// launched the app
+pub fn init_deep_links(app: &AppHandle) {
+    let deep_link = app.deep_link();
+
+    // Installed bundles register the scheme themselves; dev builds and AppImages
+    // have to do it at runtime. Portable installs leave the system untouched.
+    #[cfg(any(windows, target_os = "linux"))]
+    if crate::paths::is_portable() {
+        info!("Portable mode, not registering {}:// links", SCHEME);
+    } else if let Err(e) = deep_link.register_all() {
+        warn!("Failed to register {}:// links: {}", SCHEME, e);
+    }
+
+    let handle = app.clone();
+    deep_link.on_open_url(move |event| handle_urls(&handle, event.urls()));
+
+    match deep_link.get_current() {
+        Ok(Some(urls)) => handle_urls(app, urls),
+        Ok(None) => {}
+        Err(e) => warn!("Failed to read launch deep link: {}", e),
+    }
+}
+
+fn take_pending(state: &DeepLinkState, id: &str) -> Result<Option<DeepLink>, String> {
+    let mut pending = state.0.lock().map_err(|e| e.to_string())?;
+    Ok(pending
+        .iter()
+        .position(|link| link.id == id)
+        .map(|index| pending.remove(index)))
+}
+
+// ============ Tauri Commands ============
+
+/// Links received but not yet handled, oldest first
+#[tauri::command]
+pub async fn list_pending_deep_links(
+    state: State<'_, DeepLinkState>,
+) -> Result<Vec<DeepLink>, String> {
+    Ok(state.0.lock().map_err(|e| e.to_string())?.clone())
+}
+
+/// Mark a link as handled; a run-hook link runs its hooks and returns the result
+#[tauri::command]
+pub async fn accept_deep_link(
+    app: AppHandle,
+    state: State<'_, DeepLinkState>,
+    id: String,
+) -> Result<Option<HookChainResult>, String> {
+    let link = take_pending(&state, &id)?.ok_or_else(|| format!("Deep link {} not found", id))?;
+    match link.action {
+        DeepLinkAction::Open { .. } => Ok(None),
+        DeepLinkAction::RunHook {
+            event,
+            project,
+            session,
+        } => {
+            info!("Running {} hooks from deep link", event);
+            let context = HookContext {
+                event: event.clone(),
+                session_id: session.unwrap_or_default(),
+                project_path: project.unwrap_or_default(),
+                data: serde_json::json!({ "source": "deep-link", "url": link.url }),
+            };
+            Ok(Some(trigger_hook_event(app, event, context, None).await?))
+        }
+    }
+}
+
+/// Drop a link without acting on it
+#[tauri::command]
+pub async fn dismiss_deep_link(
+    state: State<'_, DeepLinkState>,
+    id: String,
+) -> Result<bool, String> {
+    Ok(take_pending(&state, &id)?.is_some())
+}
@@ -7,10 +7,33 @@ use log::{debug, error, info, warn};
 /// - Deep integration with existing components (AutoCompactManager, etc.)
 /// - Error handling and rollback mechanisms
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, Emitter};
-use tokio::process::Command;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::{mpsc, oneshot};
+use uuid::Uuid;
+
+use super::audio;
+use super::command_policy;
+use super::env_profiles;
+use super::hook_metrics::{self, HookOutcome};
+use super::hook_output::{self, CapturedOutput, OutputSink};
+use super::hook_sandbox::{self, HookSandbox};
+use super::operations;
+use super::project_search;
+use super::redaction;
+use super::retry::{Failure, RetryPolicy};
+use super::session_state::{self, SessionPhase};
+use super::storage::AgentDb;
+use super::terminal::{self, TerminalSpec};
+use crate::error::WorkbenchError;
+use crate::events::{self, AppEvent, HookRunStarted};
+use crate::i18n;
+use crate::process::audit::AuditedAsyncCommand;
+use crate::process::{ProcessRegistryState, ProcessType};
 
 /// Extended hook event types
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -30,6 +53,11 @@ pub enum HookEvent {
     OnSessionStart,   // Triggered at the start of a session
     OnSessionEnd,     // Triggered at the end of a session
     OnTabSwitch,      // Triggered when switching tabs
+    OnUserCommand,    // Triggered when the user runs a custom slash command
+    OnProviderSwitch, // Triggered when the API provider is switched
+    OnIdle,           // Triggered when the user has been away from the computer
+    OnResume,         // Triggered when the user returns after OnIdle
+    OnActivityReport, // Triggered when the scheduled activity report is written
 }
 
 impl HookEvent {
@@ -46,6 +74,11 @@ impl HookEvent {
             HookEvent::OnSessionStart => "OnSessionStart",
             HookEvent::OnSessionEnd => "OnSessionEnd",
             HookEvent::OnTabSwitch => "OnTabSwitch",
+            HookEvent::OnUserCommand => "OnUserCommand",
+            HookEvent::OnProviderSwitch => "OnProviderSwitch",
+            HookEvent::OnIdle => "OnIdle",
+            HookEvent::OnResume => "OnResume",
+            HookEvent::OnActivityReport => "OnActivityReport",
         }
     }
 }
@@ -60,13 +93,33 @@ pub struct HookContext {
 }
 
 /// Hook execution result
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct HookExecutionResult {
     pub success: bool,
     pub output: String,
     pub error: Option<String>,
     pub execution_time_ms: u64,
     pub hook_command: String,
+    /// Process registry id of the run, for `get_hook_output`
+    #[serde(default)]
+    pub run_id: Option<i64>,
+    /// Whether `output` or `error` was cut to the hook's output limit
+    #[serde(default)]
+    pub truncated: bool,
+    /// Files holding the full stdout / stderr when they went over the limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_file: Option<String>,
+}
+
+/// How one attempt at running a hook's command ended
+struct HookRun {
+    success: bool,
+    exit_code: Option<i32>,
+    run_id: Option<i64>,
+    stdout: CapturedOutput,
+    stderr: CapturedOutput,
 }
 
 /// Hook chain execution result
@@ -88,15 +141,250 @@ pub struct ConditionalTrigger {
     pub priority: Option<i32>, // Execution priority
 }
 
+/// Built-in action a hook can run instead of (or before) a shell command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HookAction {
+    /// Desktop notification; `{event}`, `{session_id}` and `{project_path}` are substituted
+    Notify {
+        title: String,
+        body: String,
+        urgency: Option<super::notifications::NotificationUrgency>,
+    },
+    /// Read `text` aloud with the OS voice; placeholders as for `Notify`
+    Speak {
+        text: String,
+        voice: Option<String>,
+        /// Words per minute
+        rate: Option<u32>,
+    },
+    /// Play `done`, `attention`, `error` or an audio file
+    PlaySound { name: String },
+}
+
 /// Enhanced hook definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnhancedHook {
+    /// Stable identity; assigned and persisted the first time hooks are listed
+    #[serde(default = "Uuid::new_v4")]
+    pub id: Uuid,
+    /// Disabled hooks stay in the config but are skipped
+    #[serde(default = "default_hook_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
     pub command: String,
+    #[serde(default)]
+    pub action: Option<HookAction>,
+    /// Tool patterns for tool events, e.g. `Edit|Write` or `Bash(git *)`
+    #[serde(default)]
+    pub matcher: Option<String>,
+    /// Globs the tool's target file must match, e.g. `["*.rs"]`
+    #[serde(default)]
+    pub files: Option<Vec<String>>,
+    /// Run the command (and its on_success / on_failure commands) restricted
+    #[serde(default)]
+    pub sandbox: Option<HookSandbox>,
+    /// Wait until events have been quiet this long, then run once for the whole batch
+    #[serde(default)]
+    pub debounce_ms: Option<u64>,
+    /// Run at most this many times per minute; events over the limit are batched
+    #[serde(default)]
+    pub max_per_minute: Option<u32>,
+    /// Run the command in a visible terminal of the project instead of in the
+    /// background. Sandboxed hooks always run in the background.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub terminal: bool,
     pub timeout: Option<u64>,
+    /// Retries after a failure, one second apart; `retry_policy` takes precedence
     pub retry: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_policy: Option<RetryPolicy>,
+    /// Bytes of stdout and of stderr kept in the result; the rest goes to a file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_bytes: Option<usize>,
     pub condition: Option<ConditionalTrigger>,
     pub on_success: Option<Vec<String>>, // Commands to run on success
     pub on_failure: Option<Vec<String>>, // Commands to run on failure
+    /// Preset this hook was installed from, as `id@version`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+}
+
+fn default_hook_enabled() -> bool {
+    true
+}
+
+impl EnhancedHook {
+    /// Execution priority; higher runs first, unset counts as 0
+    pub fn priority(&self) -> i32 {
+        self.condition
+            .as_ref()
+            .and_then(|c| c.priority)
+            .unwrap_or(0)
+    }
+
+    /// Retry policy of the command, from `retry_policy` or the older `retry` count
+    pub fn retry_policy(&self) -> RetryPolicy {
+        match (&self.retry_policy, self.retry) {
+            (Some(policy), _) => policy.clone(),
+            (None, Some(retries)) => RetryPolicy::fixed(retries + 1, Duration::from_secs(1)),
+            (None, None) => RetryPolicy::none(),
+        }
+    }
+
+    pub fn output_limit(&self) -> usize {
+        self.max_output_bytes
+            .unwrap_or(hook_output::DEFAULT_MAX_OUTPUT_BYTES)
+    }
+
+    /// Whether events for this hook go through debouncing / rate limiting
+    pub fn is_throttled(&self) -> bool {
+        self.debounce_ms.is_some_and(|ms| ms > 0) || self.max_per_minute.is_some()
+    }
+}
+
+/// Split a matcher on top-level `|`, leaving `|` inside parentheses alone
+fn split_matcher(matcher: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in matcher.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            '|' if depth == 0 => {
+                parts.push(matcher[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(matcher[start..].trim());
+    parts.into_iter().filter(|p| !p.is_empty()).collect()
+}
+
+/// Whether a `|`-separated tool matcher accepts the tool call in `context`.
+/// Empty and `*` matchers, and events without a `tool_name`, always match.
+pub(crate) fn matcher_accepts(
+    matcher: &str,
+    context: &HookContext,
+) -> Result<bool, WorkbenchError> {
+    let matcher = matcher.trim();
+    let tool = match context.data.get("tool_name").and_then(|v| v.as_str()) {
+        Some(tool) => tool,
+        None => return Ok(true),
+    };
+    if matcher.is_empty() || matcher == "*" {
+        return Ok(true);
+    }
+
+    let subject = context
+        .data
+        .get("tool_input")
+        .and_then(|i| super::permissions::tool_input_subject(tool, i));
+    let project_path = Some(context.project_path.as_str()).filter(|p| !p.is_empty());
+    for pattern in split_matcher(matcher) {
+        if pattern == "*"
+            || super::permissions::tool_pattern_matches(
+                pattern,
+                tool,
+                subject.as_deref(),
+                project_path,
+            )?
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Whether a hook's matcher and file globs accept the tool call in `context`.
+/// Events without a `tool_name` in their data are never filtered.
+fn hook_matches_tool(hook: &EnhancedHook, context: &HookContext) -> Result<bool, WorkbenchError> {
+    if context
+        .data
+        .get("tool_name")
+        .and_then(|v| v.as_str())
+        .is_none()
+    {
+        return Ok(true);
+    }
+    let tool_input = context.data.get("tool_input");
+    let project_path = Some(context.project_path.as_str()).filter(|p| !p.is_empty());
+
+    if let Some(matcher) = hook.matcher.as_deref() {
+        if !matcher_accepts(matcher, context)? {
+            return Ok(false);
+        }
+    }
+
+    if let Some(globs) = hook.files.as_ref().filter(|g| !g.is_empty()) {
+        let path = tool_input.and_then(|i| {
+            ["file_path", "notebook_path", "path"]
+                .iter()
+                .find_map(|key| i.get(*key).and_then(|v| v.as_str()))
+        });
+        return Ok(match path {
+            Some(path) => globs
+                .iter()
+                .any(|glob| super::permissions::path_glob_matches(glob, path, project_path)),
+            None => false,
+        });
+    }
+
+    Ok(true)
+}
+
+/// One line of output from a running hook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookOutputLine {
+    pub run_id: i64,
+    /// "stdout" or "stderr"
+    pub stream: String,
+    pub line: String,
+}
+
+/// Cancellation handles for hook processes that are still running, keyed by run id
+#[derive(Default)]
+pub struct HookRunState(pub Arc<Mutex<HashMap<i64, tokio::sync::oneshot::Sender<()>>>>);
+
+/// Read a hook's pipe line by line, emitting each line and collecting the output
+/// up to `limit`
+fn spawn_output_reader<R>(
+    app: AppHandle,
+    pipe: Option<R>,
+    run_id: Option<i64>,
+    stream: &'static str,
+    limit: usize,
+) -> tokio::task::JoinHandle<CapturedOutput>
+where
+    R: tokio::io::AsyncRead + Unpin + Send + 'static,
+{
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    tokio::spawn(async move {
+        let mut collected = OutputSink::new(&app, limit, run_id, stream);
+        let pipe = match pipe {
+            Some(pipe) => pipe,
+            None => return collected.finish(),
+        };
+
+        let mut lines = BufReader::new(pipe).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if let Some(run_id) = run_id {
+                events::emit(
+                    &app,
+                    AppEvent::HookOutput(HookOutputLine {
+                        run_id,
+                        stream: stream.to_string(),
+                        line: line.clone(),
+                    }),
+                );
+            }
+            collected.push_line(&line);
+        }
+        collected.finish()
+    })
 }
 
 /// Hook executor
@@ -110,11 +398,16 @@ impl HookExecutor {
     }
 
     /// Execute a single hook
+    #[tracing::instrument(
+        name = "hook",
+        skip_all,
+        fields(event = context.event.as_str(), command = hook.command.as_str())
+    )]
     pub async fn execute_hook(
         &self,
         hook: &EnhancedHook,
         context: &HookContext,
-    ) -> Result<HookExecutionResult, String> {
+    ) -> Result<HookExecutionResult, WorkbenchError> {
         let start_time = std::time::Instant::now();
 
         // Check if the condition is met
@@ -127,107 +420,368 @@ impl HookExecutor {
                     error: None,
                     execution_time_ms: 0,
                     hook_command: hook.command.clone(),
+                    ..Default::default()
+                });
+            }
+        }
+
+        if !hook_matches_tool(hook, context)? {
+            debug!("Hook matcher did not match tool call, skipping execution");
+            return Ok(HookExecutionResult {
+                success: true,
+                output: "Skipped: matcher did not match".to_string(),
+                error: None,
+                execution_time_ms: 0,
+                hook_command: hook.command.clone(),
+                ..Default::default()
+            });
+        }
+
+        // Run the built-in action; action-only hooks stop here
+        if let Some(action) = &hook.action {
+            let action_result = self.execute_action(action, context);
+            if hook.command.trim().is_empty() {
+                let (success, output, error) = match action_result {
+                    Ok(output) => (true, output, None),
+                    Err(e) => (false, String::new(), Some(e.to_string())),
+                };
+                return Ok(HookExecutionResult {
+                    success,
+                    output,
+                    error,
+                    execution_time_ms: start_time.elapsed().as_millis() as u64,
+                    hook_command: hook.command.clone(),
+                    ..Default::default()
                 });
+            } else if let Err(e) = action_result {
+                warn!("Hook action failed: {}", e);
             }
         }
 
+        command_policy::enforce(&self.app, &context.project_path, &hook.command, "hook")?;
+
         // Prepare execution environment
-        let context_json = serde_json::to_string(context).map_err(|e| e.to_string())?;
+        let context_json = serde_json::to_string(context)
+            .map_err(|e| WorkbenchError::json("Failed to serialize hook context", e))?;
 
         // Execute command
-        let mut retry_count = 0;
-        let max_retries = hook.retry.unwrap_or(0);
+        let policy = hook.retry_policy();
+        let mut attempt = 1;
 
         loop {
-            let mut cmd = Command::new("bash");
-            cmd.arg("-c")
-                .arg(&hook.command)
-                .stdin(std::process::Stdio::piped())
-                .stdout(std::process::Stdio::piped())
-                .stderr(std::process::Stdio::piped())
-                .env("HOOK_CONTEXT", &context_json)
-                .env("HOOK_EVENT", &context.event)
-                .env("SESSION_ID", &context.session_id)
-                .env("PROJECT_PATH", &context.project_path);
-
-            #[cfg(target_os = "windows")]
-            {
-                cmd.creation_flags(0x08000000);
-            }
-
-            // Set timeout
             let timeout_duration = tokio::time::Duration::from_secs(hook.timeout.unwrap_or(30));
-
-            // Spawn process and apply timeout
-            let child = cmd
-                .spawn()
-                .map_err(|e| format!("Failed to spawn hook process: {}", e))?;
-
-            let result = tokio::time::timeout(timeout_duration, child.wait_with_output())
-                .await
-                .map_err(|_| "Hook execution timeout".to_string())?
-                .map_err(|e| format!("Hook execution failed: {}", e))?;
+            let run = if hook.terminal && hook.sandbox.is_none() {
+                self.run_in_terminal(hook, context, &context_json, timeout_duration)
+                    .await
+            } else {
+                self.run_headless(hook, context, &context_json, timeout_duration)
+                    .await
+            };
+            let run = match run {
+                Ok(run) => run,
+                Err(e) if policy.should_retry(attempt, &Failure::Error(&e)) => {
+                    warn!(
+                        session_id = context.session_id.as_str(),
+                        event = context.event.as_str(),
+                        command = hook.command.as_str();
+                        "Hook failed ({}), retrying ({}/{})",
+                        e,
+                        attempt,
+                        policy.max_attempts - 1
+                    );
+                    policy.wait(attempt).await?;
+                    attempt += 1;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
 
             let execution_time = start_time.elapsed().as_millis() as u64;
 
-            if result.status.success() {
-                let output = String::from_utf8_lossy(&result.stdout).to_string();
-
+            if run.success {
                 // Hooks after successful execution
                 if let Some(on_success_commands) = &hook.on_success {
                     for cmd in on_success_commands {
-                        let _ = self.execute_simple_command(cmd, context).await;
+                        let _ = self
+                            .execute_simple_command(cmd, context, hook.sandbox.as_ref())
+                            .await;
                     }
                 }
 
                 return Ok(HookExecutionResult {
                     success: true,
-                    output,
+                    output: run.stdout.display_text(),
                     error: None,
                     execution_time_ms: execution_time,
                     hook_command: hook.command.clone(),
+                    run_id: run.run_id,
+                    truncated: run.stdout.truncated,
+                    output_file: run.stdout.file_string(),
+                    error_file: None,
                 });
             } else {
                 // Failure handling
-                let error_output = String::from_utf8_lossy(&result.stderr).to_string();
-
-                if retry_count < max_retries {
+                let exit_code = run.exit_code;
+                let failure = Failure::Exit {
+                    code: exit_code,
+                    stdout: &run.stdout.text,
+                    stderr: &run.stderr.text,
+                };
+                if policy.should_retry(attempt, &failure) {
                     warn!(
+                        session_id = context.session_id.as_str(),
+                        event = context.event.as_str(),
+                        command = hook.command.as_str(),
+                        exit_code = exit_code.unwrap_or(-1);
                         "Hook failed, retrying ({}/{})",
-                        retry_count + 1,
-                        max_retries
+                        attempt,
+                        policy.max_attempts - 1
                     );
-                    retry_count += 1;
-                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                    policy.wait(attempt).await?;
+                    attempt += 1;
                     continue;
                 }
 
+                warn!(
+                    session_id = context.session_id.as_str(),
+                    event = context.event.as_str(),
+                    command = hook.command.as_str(),
+                    exit_code = exit_code.unwrap_or(-1),
+                    stderr = run.stderr.text.trim();
+                    "Hook failed"
+                );
+
                 // Hooks after failure
                 if let Some(on_failure_commands) = &hook.on_failure {
                     for cmd in on_failure_commands {
-                        let _ = self.execute_simple_command(cmd, context).await;
+                        let _ = self
+                            .execute_simple_command(cmd, context, hook.sandbox.as_ref())
+                            .await;
                     }
                 }
 
                 return Ok(HookExecutionResult {
                     success: false,
                     output: String::new(),
-                    error: Some(error_output),
+                    error: Some(run.stderr.display_text()),
                     execution_time_ms: execution_time,
                     hook_command: hook.command.clone(),
+                    run_id: run.run_id,
+                    truncated: run.stderr.truncated,
+                    output_file: run.stdout.file_string(),
+                    error_file: run.stderr.file_string(),
                 });
             }
         }
     }
 
+    /// Run a hook's command as a background process
+    async fn run_headless(
+        &self,
+        hook: &EnhancedHook,
+        context: &HookContext,
+        context_json: &str,
+        timeout_duration: Duration,
+    ) -> Result<HookRun, WorkbenchError> {
+        let mut cmd = hook_sandbox::hook_command(
+            &hook.command,
+            &context.project_path,
+            hook.sandbox.as_ref(),
+        )?;
+        self.apply_env_profile(&mut cmd, &context.project_path, hook.sandbox.as_ref());
+        cmd.stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .env("HOOK_CONTEXT", context_json)
+            .env("HOOK_EVENT", &context.event)
+            .env("SESSION_ID", &context.session_id)
+            .env("PROJECT_PATH", &context.project_path);
+
+        #[cfg(target_os = "windows")]
+        {
+            cmd.creation_flags(0x08000000);
+        }
+
+        // Spawn process and apply timeout
+        let (child, audit) = cmd
+            .kill_on_drop(true)
+            .audited_spawn("hook")
+            .map_err(|e| WorkbenchError::io("Failed to spawn hook process", e))?;
+        let _job = hook_sandbox::limit_process(child.id(), hook.sandbox.as_ref())?;
+        let run_id = self.track_process(child.id(), &hook.command, context);
+        if let Some(run_id) = run_id {
+            events::emit(
+                &self.app,
+                AppEvent::HookRunStarted(HookRunStarted {
+                    run_id,
+                    event: context.event.clone(),
+                    session_id: context.session_id.clone(),
+                    command: hook.command.clone(),
+                }),
+            );
+        }
+
+        let result = tokio::time::timeout(
+            timeout_duration,
+            self.stream_hook_output(child, run_id, hook.output_limit()),
+        )
+        .await;
+        self.untrack_process(run_id);
+        if let Ok(Ok((status, _, _))) = &result {
+            audit.finish(&Ok(*status));
+        }
+        let (status, stdout, stderr) = result.map_err(|_| WorkbenchError::Timeout {
+            what: "Hook execution".to_string(),
+            secs: timeout_duration.as_secs(),
+        })??;
+        Ok(HookRun {
+            success: status.success(),
+            exit_code: status.code(),
+            run_id,
+            stdout,
+            stderr,
+        })
+    }
+
+    /// Run a hook's command in a new terminal of its project. A terminal has a
+    /// single output stream, which is returned as both stdout and stderr.
+    async fn run_in_terminal(
+        &self,
+        hook: &EnhancedHook,
+        context: &HookContext,
+        context_json: &str,
+        timeout_duration: Duration,
+    ) -> Result<HookRun, WorkbenchError> {
+        let mut env: Vec<(String, String)> = super::proxy::proxy_env_vars()
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect();
+        if let Some(profile) = env_profiles::active_profile(&self.app, &context.project_path) {
+            env.extend(profile.vars);
+        }
+        env.extend([
+            ("HOOK_CONTEXT".to_string(), context_json.to_string()),
+            ("HOOK_EVENT".to_string(), context.event.clone()),
+            ("SESSION_ID".to_string(), context.session_id.clone()),
+            ("PROJECT_PATH".to_string(), context.project_path.clone()),
+        ]);
+
+        let (info, exit_rx) = terminal::spawn_terminal(
+            &self.app,
+            TerminalSpec {
+                project_path: context.project_path.clone(),
+                title: format!("Hook: {}", context.event),
+                script: Some(hook.command.clone()),
+                env,
+                cols: terminal::DEFAULT_COLS,
+                rows: terminal::DEFAULT_ROWS,
+                capture: true,
+            },
+        )?;
+        let run_id = self.track_process(info.pid, &hook.command, context);
+        let exit = tokio::select! {
+            exit = tokio::time::timeout(timeout_duration, exit_rx) => match exit {
+                Ok(Ok(exit)) => exit,
+                Ok(Err(_)) => {
+                    self.untrack_process(run_id);
+                    return Err(WorkbenchError::Dropped("Hook terminal".to_string()));
+                }
+                Err(_) => {
+                    self.untrack_process(run_id);
+                    terminal::kill_terminal(&self.app, &info.id)?;
+                    return Err(WorkbenchError::Timeout {
+                        what: "Hook execution".to_string(),
+                        secs: timeout_duration.as_secs(),
+                    });
+                }
+            },
+            _ = operations::cancelled() => {
+                self.untrack_process(run_id);
+                terminal::kill_terminal(&self.app, &info.id)?;
+                return Err(WorkbenchError::Cancelled("Hook run".to_string()));
+            }
+        };
+        self.untrack_process(run_id);
+        // The terminal's single stream counts as both stdout and stderr
+        let output = hook_output::capture_text(
+            &self.app,
+            &exit.output.unwrap_or_default(),
+            hook.output_limit(),
+            run_id,
+            "stdout",
+        );
+        Ok(HookRun {
+            success: exit.success,
+            exit_code: Some(exit.exit_code as i32),
+            run_id,
+            stdout: output.clone(),
+            stderr: output,
+        })
+    }
+
+    /// Run a built-in hook action
+    fn execute_action(
+        &self,
+        action: &HookAction,
+        context: &HookContext,
+    ) -> Result<String, WorkbenchError> {
+        let substitute = |text: &str| {
+            text.replace("{event}", &context.event)
+                .replace("{session_id}", &context.session_id)
+                .replace("{project_path}", &context.project_path)
+        };
+
+        match action {
+            HookAction::Notify {
+                title,
+                body,
+                urgency,
+            } => {
+                let shown = super::notifications::send_notification(
+                    &self.app,
+                    &substitute(title),
+                    &substitute(body),
+                    urgency.unwrap_or_default(),
+                )?;
+                Ok(if shown {
+                    "Notification sent".to_string()
+                } else {
+                    "Notification suppressed".to_string()
+                })
+            }
+            HookAction::Speak { text, voice, rate } => {
+                let text = substitute(text);
+                audio::speak(&text, voice.as_deref(), *rate)?;
+                Ok(format!("Spoke: {}", text))
+            }
+            HookAction::PlaySound { name } => {
+                audio::play_sound(name)?;
+                Ok(format!("Played sound {}", name))
+            }
+        }
+    }
+
     /// Execute a hook chain
+    #[tracing::instrument(
+        name = "hook_chain",
+        skip_all,
+        fields(event = event.as_str(), session_id = context.session_id.as_str(), hooks = hooks.len())
+    )]
     pub async fn execute_hook_chain(
         &self,
         event: HookEvent,
         context: HookContext,
         hooks: Vec<EnhancedHook>,
-    ) -> Result<HookChainResult, String> {
+    ) -> Result<HookChainResult, WorkbenchError> {
+        // Higher priority first; sort_by_key is stable, so ties keep their configured order
+        let mut hooks = hooks;
+        hooks.retain(|h| h.enabled);
+        hooks.sort_by_key(|h| std::cmp::Reverse(h.priority()));
+
         info!(
+            session_id = context.session_id.as_str(),
+            event = event.as_str(),
+            hooks = hooks.len();
             "Executing hook chain for event: {:?}, {} hooks",
             event,
             hooks.len()
@@ -238,7 +792,13 @@ impl HookExecutor {
         let mut failed = 0;
         let mut should_continue = true;
 
+        let mut cancelled = false;
         for (idx, hook) in hooks.iter().enumerate() {
+            if operations::is_cancelled() {
+                info!("Hook chain for {} cancelled", event.as_str());
+                cancelled = true;
+                break;
+            }
             debug!(
                 "Executing hook {}/{}: {}",
                 idx + 1,
@@ -248,36 +808,71 @@ impl HookExecutor {
 
             match self.execute_hook(hook, &context).await {
                 Ok(result) => {
+                    let outcome = if result.output.starts_with("Skipped:") {
+                        HookOutcome::Skipped
+                    } else if result.success {
+                        HookOutcome::Success
+                    } else {
+                        HookOutcome::Failure
+                    };
+                    hook_metrics::observe(
+                        &self.app,
+                        event.as_str(),
+                        hook,
+                        outcome,
+                        result.execution_time_ms,
+                    );
                     if result.success {
                         successful += 1;
                     } else {
                         failed += 1;
-                        // If this is a PreToolUse event and the hook fails, block subsequent operations
-                        if matches!(event, HookEvent::PreToolUse) {
+                        // If this is a PreToolUse or OnUserCommand event and the hook fails, block subsequent operations
+                        if matches!(event, HookEvent::PreToolUse | HookEvent::OnUserCommand) {
                             should_continue = false;
-                            warn!("PreToolUse hook failed, blocking operation");
+                            warn!("{} hook failed, blocking operation", event.as_str());
                         }
                     }
                     results.push(result);
                 }
                 Err(e) => {
-                    error!("Hook execution error: {}", e);
+                    error!(
+                        session_id = context.session_id.as_str(),
+                        event = event.as_str(),
+                        command = hook.command.as_str();
+                        "Hook execution error: {}",
+                        e
+                    );
+                    hook_metrics::observe(&self.app, event.as_str(), hook, HookOutcome::Error, 0);
                     failed += 1;
                     results.push(HookExecutionResult {
                         success: false,
                         output: String::new(),
-                        error: Some(e),
+                        error: Some(e.to_string()),
                         execution_time_ms: 0,
                         hook_command: hook.command.clone(),
+                        ..Default::default()
                     });
                 }
             }
         }
 
+        self.record_runs(&context, &results);
+        if cancelled {
+            return Err(WorkbenchError::Cancelled(format!(
+                "{} hook chain",
+                event.as_str()
+            )));
+        }
+
         // Emit execution result event
-        let _ = self.app.emit(
-            &format!("hook-chain-complete:{}", context.session_id),
-            &results,
+        events::emit(
+            &self.app,
+            AppEvent::HookChainComplete {
+                session_id: context.session_id.clone(),
+                project_path: context.project_path.clone(),
+                event: event.as_str().to_string(),
+                results: results.clone(),
+            },
         );
 
         Ok(HookChainResult {
@@ -295,11 +890,12 @@ impl HookExecutor {
         &self,
         command: &str,
         context: &HookContext,
-    ) -> Result<(), String> {
-        let mut cmd = Command::new("bash");
-        cmd.arg("-c")
-            .arg(command)
-            .env("SESSION_ID", &context.session_id)
+        sandbox: Option<&HookSandbox>,
+    ) -> Result<(), WorkbenchError> {
+        command_policy::enforce(&self.app, &context.project_path, command, "hook")?;
+        let mut cmd = hook_sandbox::hook_command(command, &context.project_path, sandbox)?;
+        self.apply_env_profile(&mut cmd, &context.project_path, sandbox);
+        cmd.env("SESSION_ID", &context.session_id)
             .env("PROJECT_PATH", &context.project_path);
 
         #[cfg(target_os = "windows")]
@@ -307,22 +903,185 @@ impl HookExecutor {
             cmd.creation_flags(0x08000000);
         }
 
-        let _ = cmd
-            .spawn()
-            .map_err(|e| format!("Failed to spawn command: {}", e))?
-            .wait()
-            .await;
+        let (mut child, audit) = cmd
+            .audited_spawn("hook")
+            .map_err(|e| WorkbenchError::io("Failed to spawn command", e))?;
+        let _job = hook_sandbox::limit_process(child.id(), sandbox)?;
+        let run_id = self.track_process(child.id(), command, context);
+        audit.finish(&child.wait().await);
+        self.untrack_process(run_id);
 
         Ok(())
     }
 
+    /// Add the proxy and the project's active env profile. Sandboxed hooks only get
+    /// the proxy if they may use the network, and only profile variables they allow.
+    fn apply_env_profile(
+        &self,
+        cmd: &mut tokio::process::Command,
+        project_path: &str,
+        sandbox: Option<&HookSandbox>,
+    ) {
+        if sandbox.is_none_or(|s| !s.no_network) {
+            cmd.envs(super::proxy::proxy_env_vars());
+        }
+        if let Some(profile) = env_profiles::active_profile(&self.app, project_path) {
+            let vars = profile
+                .vars
+                .iter()
+                .filter(|(key, _)| sandbox.is_none_or(|s| s.allow_env.contains(key)));
+            cmd.envs(vars);
+        }
+    }
+
+    /// Record a hook process in the process registry
+    fn track_process(&self, pid: Option<u32>, command: &str, context: &HookContext) -> Option<i64> {
+        let registry = self.app.try_state::<ProcessRegistryState>()?;
+        registry
+            .0
+            .register_tracked_process(
+                ProcessType::Hook {
+                    event: context.event.clone(),
+                },
+                pid?,
+                context.project_path.clone(),
+                command.to_string(),
+            )
+            .map_err(|e| warn!("Failed to register hook process: {}", e))
+            .ok()
+    }
+
+    /// Append a chain's results to the hook run history, with commands passed
+    /// through redaction
+    fn record_runs(&self, context: &HookContext, results: &[HookExecutionResult]) {
+        let redactor = redaction::redactor(&self.app);
+        let db = match self.app.try_state::<AgentDb>() {
+            Some(db) => db,
+            None => return,
+        };
+        let conn = match db.0.lock() {
+            Ok(conn) => conn,
+            Err(_) => return,
+        };
+        let timestamp = chrono::Utc::now().to_rfc3339();
+        for result in results {
+            if let Err(e) = conn.execute(
+                "INSERT INTO hook_runs (timestamp, project_path, session_id, event, command, success, duration_ms)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                rusqlite::params![
+                    timestamp,
+                    context.project_path,
+                    context.session_id,
+                    context.event,
+                    redactor.redact(&result.hook_command),
+                    result.success,
+                    result.execution_time_ms as i64,
+                ],
+            ) {
+                warn!("Failed to record hook run: {}", e);
+                return;
+            }
+        }
+    }
+
+    fn untrack_process(&self, run_id: Option<i64>) {
+        let run_id = match run_id {
+            Some(run_id) => run_id,
+            None => return,
+        };
+        if let Some(registry) = self.app.try_state::<ProcessRegistryState>() {
+            let _ = registry.0.unregister_process(run_id);
+        }
+        if let Some(runs) = self.app.try_state::<HookRunState>() {
+            if let Ok(mut runs) = runs.0.lock() {
+                runs.remove(&run_id);
+            }
+        }
+    }
+
+    /// Wait for a hook process while streaming its output as `hook-output:{run_id}`
+    /// events; fails early if the run is cancelled via `cancel_hook_run` or its
+    /// operation via `cancel_operation`
+    async fn stream_hook_output(
+        &self,
+        mut child: tokio::process::Child,
+        run_id: Option<i64>,
+        limit: usize,
+    ) -> Result<(std::process::ExitStatus, CapturedOutput, CapturedOutput), WorkbenchError> {
+        let cancel_rx = match (run_id, self.app.try_state::<HookRunState>()) {
+            (Some(run_id), Some(runs)) => {
+                let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+                runs.0.lock().map_err(|e| e.to_string())?.insert(run_id, tx);
+                Some(rx)
+            }
+            _ => None,
+        };
+
+        let stdout_task = spawn_output_reader(
+            self.app.clone(),
+            child.stdout.take(),
+            run_id,
+            "stdout",
+            limit,
+        );
+        let stderr_task = spawn_output_reader(
+            self.app.clone(),
+            child.stderr.take(),
+            run_id,
+            "stderr",
+            limit,
+        );
+
+        let cancel_run = async move {
+            match cancel_rx {
+                Some(cancel_rx) => {
+                    let _ = cancel_rx.await;
+                }
+                None => std::future::pending().await,
+            }
+        };
+        let status = tokio::select! {
+            status = child.wait() => Some(status),
+            _ = cancel_run => None,
+            _ = operations::cancelled() => None,
+        };
+        let status = match status {
+            Some(status) => status.map_err(|e| WorkbenchError::io("Hook execution failed", e))?,
+            None => {
+                let _ = child.kill().await;
+                return Err(WorkbenchError::Cancelled("Hook run".to_string()));
+            }
+        };
+
+        Ok((
+            status,
+            stdout_task.await.unwrap_or_default(),
+            stderr_task.await.unwrap_or_default(),
+        ))
+    }
+
     /// Evaluate a condition expression
-    fn evaluate_condition(&self, condition: &str, context: &HookContext) -> Result<bool, String> {
+    fn evaluate_condition(
+        &self,
+        condition: &str,
+        context: &HookContext,
+    ) -> Result<bool, WorkbenchError> {
         // Simple condition evaluation implementation
         // Supported formats:
         // - "session_id == 'xyz'"
         // - "data.tokens > 100000"
         // - "event == 'OnContextCompact'"
+        // - "project contains 'TODO'" (regex search of the project's files)
+        // - "git.branch == 'main'", "git.dirty == true", "git.ahead > 0" (see evaluate_git_condition)
+
+        if let Some(pattern) = condition.trim().strip_prefix("project contains ") {
+            let pattern = pattern.trim().trim_matches(|c| c == '\'' || c == '"');
+            return project_search::project_contains(&context.project_path, pattern);
+        }
+
+        if let Some(predicate) = condition.trim().strip_prefix("git.") {
+            return evaluate_git_condition(predicate, &context.project_path);
+        }
 
         // This uses basic string matching; a more powerful expression engine can be integrated later
         if condition.contains("==") {
@@ -346,43 +1105,460 @@ impl HookExecutor {
     }
 }
 
+/// Operators of git conditions; two-character ones first so `>=` is not read as `>`
+const GIT_CONDITION_OPERATORS: &[&str] = &["==", "!=", ">=", "<=", ">", "<"];
+
+/// Evaluate `git.<field> <op> <value>` against the project's repository at the
+/// time the hook runs. Fields:
+/// - `branch`: current branch, `HEAD` when detached; `==` / `!=`
+/// - `dirty`: whether anything is staged, modified or untracked; `==` / `!=`
+/// - `ahead` / `behind`: commits relative to the upstream, 0 without one; any operator
+///
+/// Outside a git repository every git condition is false.
+fn evaluate_git_condition(predicate: &str, project_path: &str) -> Result<bool, WorkbenchError> {
+    let invalid =
+        || WorkbenchError::InvalidInput(format!("Invalid git condition: git.{}", predicate));
+    let (field, op, value) = GIT_CONDITION_OPERATORS
+        .iter()
+        .find_map(|op| {
+            predicate.split_once(op).map(|(field, value)| {
+                (
+                    field.trim(),
+                    *op,
+                    value.trim().trim_matches(|c| c == '\'' || c == '"'),
+                )
+            })
+        })
+        .ok_or_else(invalid)?;
+
+    if !super::simple_git::is_git_repo(project_path) {
+        debug!(
+            "{} is not a git repository; git.{} is false",
+            project_path, field
+        );
+        return Ok(false);
+    }
+    let state = super::git_backend::head_state(project_path)?;
+
+    let equality = |equal: bool| match op {
+        "==" => Ok(equal),
+        "!=" => Ok(!equal),
+        _ => Err(invalid()),
+    };
+    match field {
+        "branch" => equality(state.branch.as_deref().unwrap_or("HEAD") == value),
+        "dirty" => {
+            let expected: bool = value.parse().map_err(|_| invalid())?;
+            equality(state.dirty == expected)
+        }
+        "ahead" | "behind" => {
+            let actual = if field == "ahead" {
+                state.ahead
+            } else {
+                state.behind
+            };
+            let expected: usize = value.parse().map_err(|_| invalid())?;
+            Ok(match op {
+                "==" => actual == expected,
+                "!=" => actual != expected,
+                ">=" => actual >= expected,
+                "<=" => actual <= expected,
+                ">" => actual > expected,
+                _ => actual < expected,
+            })
+        }
+        _ => Err(WorkbenchError::InvalidInput(format!(
+            "Unknown git condition field: {} (use branch, dirty, ahead or behind)",
+            field
+        ))),
+    }
+}
+
 // ============ Hook Event Triggerer ============
 
-/// Hook manager – manages registration and triggering of hooks, retained for future extensions
-#[allow(dead_code)]
+/// Window used by `max_per_minute`
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Debounce and rate-limit bookkeeping for one hook
+struct HookThrottle {
+    event: HookEvent,
+    /// Latest definition of the hook, run when the batch flushes
+    hook: EnhancedHook,
+    /// Contexts coalesced since the hook last ran
+    pending: Vec<HookContext>,
+    /// Bumped on every event so only the most recently scheduled flush runs
+    generation: u64,
+    /// Start times of runs within the last minute
+    recent_runs: VecDeque<Instant>,
+}
+
+impl HookThrottle {
+    /// Time until `max_per_minute` allows another run
+    fn rate_wait(&mut self, now: Instant) -> Duration {
+        while self
+            .recent_runs
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= RATE_WINDOW)
+        {
+            self.recent_runs.pop_front();
+        }
+
+        match (self.hook.max_per_minute, self.recent_runs.front()) {
+            (Some(max), Some(oldest)) if self.recent_runs.len() >= max.max(1) as usize => {
+                RATE_WINDOW.saturating_sub(now.duration_since(*oldest))
+            }
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+/// Throttled hooks are tracked per project, event and hook
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ThrottleKey {
+    project_path: String,
+    event: String,
+    hook_id: Uuid,
+}
+
+/// A throttled hook with events waiting to run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedHookBatch {
+    pub project_path: String,
+    pub event: String,
+    pub hook_id: Uuid,
+    pub command: String,
+    /// Events coalesced into the next run
+    pub pending: usize,
+    pub runs_last_minute: usize,
+}
+
+/// Runtime view of the hook manager
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HookManagerStatus {
+    /// Number of registered user hooks per event
+    pub registered: HashMap<String, usize>,
+    pub queued: Vec<QueuedHookBatch>,
+}
+
+/// Messages handled by the hook manager actor
+enum HookMessage {
+    /// Replace the registered hooks of one event
+    Register {
+        event: String,
+        hooks: Vec<EnhancedHook>,
+    },
+    /// Hooks were edited on disk; `project_path` is None for user settings
+    Reload {
+        project_path: Option<String>,
+        hooks: HashMap<String, Vec<EnhancedHook>>,
+    },
+    /// Queue the throttled hooks of an event (the registered ones if `hooks` is
+    /// None) and reply with the hooks to run right away
+    Trigger {
+        event: HookEvent,
+        context: HookContext,
+        hooks: Option<Vec<EnhancedHook>>,
+        reply: oneshot::Sender<Vec<EnhancedHook>>,
+    },
+    /// A batch's debounce or rate-limit delay has passed
+    Flush { key: ThrottleKey, generation: u64 },
+    Query {
+        reply: oneshot::Sender<HookManagerStatus>,
+    },
+}
+
+/// State owned by the hook manager task; only ever touched from that task
+struct HookActor {
+    executor: Arc<HookExecutor>,
+    tx: mpsc::UnboundedSender<HookMessage>,
+    registered: HashMap<String, Vec<EnhancedHook>>,
+    throttles: HashMap<ThrottleKey, HookThrottle>,
+}
+
+impl HookActor {
+    async fn run(mut self, mut rx: mpsc::UnboundedReceiver<HookMessage>) {
+        while let Some(message) = rx.recv().await {
+            match message {
+                HookMessage::Register { event, hooks } => {
+                    self.registered.insert(event, hooks);
+                }
+                HookMessage::Reload {
+                    project_path,
+                    hooks,
+                } => self.reload(project_path, hooks),
+                HookMessage::Trigger {
+                    event,
+                    context,
+                    hooks,
+                    reply,
+                } => {
+                    let hooks = hooks.unwrap_or_else(|| {
+                        self.registered
+                            .get(event.as_str())
+                            .cloned()
+                            .unwrap_or_default()
+                    });
+                    let (throttled, immediate): (Vec<_>, Vec<_>) = hooks
+                        .into_iter()
+                        .partition(|h| h.enabled && h.is_throttled());
+                    for hook in throttled {
+                        // Filter here, since the batch is later matched against its last context only
+                        if hook_matches_tool(&hook, &context).unwrap_or(true) {
+                            self.enqueue(event.clone(), hook, context.clone());
+                        }
+                    }
+                    let _ = reply.send(immediate);
+                }
+                HookMessage::Flush { key, generation } => self.flush(key, generation),
+                HookMessage::Query { reply } => {
+                    let _ = reply.send(self.status());
+                }
+            }
+        }
+    }
+
+    /// Add an event to a throttled hook's batch and schedule the batch to run
+    fn enqueue(&mut self, event: HookEvent, hook: EnhancedHook, context: HookContext) {
+        let key = ThrottleKey {
+            project_path: context.project_path.clone(),
+            event: event.as_str().to_string(),
+            hook_id: hook.id,
+        };
+        let throttle = self
+            .throttles
+            .entry(key.clone())
+            .or_insert_with(|| HookThrottle {
+                event,
+                hook: hook.clone(),
+                pending: Vec::new(),
+                generation: 0,
+                recent_runs: VecDeque::new(),
+            });
+        throttle.hook = hook;
+        throttle.pending.push(context);
+        throttle.generation += 1;
+
+        let debounce = Duration::from_millis(throttle.hook.debounce_ms.unwrap_or(0));
+        let wait = throttle.rate_wait(Instant::now());
+        let generation = throttle.generation;
+        self.schedule_flush(key, generation, debounce.max(wait));
+    }
+
+    fn schedule_flush(&self, key: ThrottleKey, generation: u64, delay: Duration) {
+        let tx = self.tx.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(delay).await;
+            let _ = tx.send(HookMessage::Flush { key, generation });
+        });
+    }
+
+    /// Run a throttled hook once for everything queued, unless a newer event has
+    /// rescheduled the batch in the meantime
+    fn flush(&mut self, key: ThrottleKey, generation: u64) {
+        let throttle = match self.throttles.get_mut(&key) {
+            Some(throttle) if throttle.generation == generation => throttle,
+            _ => return,
+        };
+        let now = Instant::now();
+        let delay = throttle.rate_wait(now);
+        if !delay.is_zero() {
+            self.schedule_flush(key, generation, delay);
+            return;
+        }
+        throttle.recent_runs.push_back(now);
+        let batch = std::mem::take(&mut throttle.pending);
+        let (event, hook) = (throttle.event.clone(), throttle.hook.clone());
+
+        let mut context = match batch.last() {
+            Some(context) => context.clone(),
+            None => return,
+        };
+        let contexts: Vec<Value> = batch
+            .iter()
+            .map(|c| serde_json::to_value(c).unwrap_or_default())
+            .collect();
+        match context.data.as_object_mut() {
+            Some(data) => {
+                data.insert("batch".to_string(), Value::Array(contexts));
+            }
+            None => context.data = serde_json::json!({ "batch": contexts }),
+        }
+
+        debug!(
+            "Running throttled hook {} for {} batched events",
+            hook.id,
+            batch.len()
+        );
+        let executor = self.executor.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = executor
+                .execute_hook_chain(event, context, vec![hook])
+                .await
+            {
+                warn!("Throttled hook failed: {}", e);
+            }
+        });
+    }
+
+    /// User settings replace the registered hooks; for a project, queued batches of
+    /// hooks that were removed or disabled are dropped so a stale definition never
+    /// runs.
+    fn reload(&mut self, project_path: Option<String>, hooks: HashMap<String, Vec<EnhancedHook>>) {
+        let project_path = match project_path {
+            Some(project_path) => project_path,
+            None => {
+                self.registered = hooks;
+                return;
+            }
+        };
+        self.throttles.retain(|key, _| {
+            key.project_path != project_path
+                || hooks
+                    .get(&key.event)
+                    .is_some_and(|hooks| hooks.iter().any(|h| h.enabled && h.id == key.hook_id))
+        });
+    }
+
+    fn status(&mut self) -> HookManagerStatus {
+        let now = Instant::now();
+        let mut queued: Vec<QueuedHookBatch> = self
+            .throttles
+            .iter_mut()
+            .filter(|(_, throttle)| !throttle.pending.is_empty())
+            .map(|(key, throttle)| {
+                throttle.rate_wait(now);
+                QueuedHookBatch {
+                    project_path: key.project_path.clone(),
+                    event: key.event.clone(),
+                    hook_id: key.hook_id,
+                    command: throttle.hook.command.clone(),
+                    pending: throttle.pending.len(),
+                    runs_last_minute: throttle.recent_runs.len(),
+                }
+            })
+            .collect();
+        queued.sort_by(|a, b| (&a.project_path, &a.event).cmp(&(&b.project_path, &b.event)));
+        HookManagerStatus {
+            registered: self
+                .registered
+                .iter()
+                .map(|(event, hooks)| (event.clone(), hooks.len()))
+                .collect(),
+            queued,
+        }
+    }
+}
+
+/// Hook manager – runs hook chains and coalesces events for debounced or
+/// rate-limited hooks. A cheap handle to an actor task that owns the registered
+/// hooks and throttle queues; immediate hooks run in the caller's task, so
+/// they see its cancellation, while batches run in their own tasks.
+#[derive(Clone)]
 pub struct HookManager {
     executor: Arc<HookExecutor>,
-    registered_hooks: Arc<Mutex<HashMap<String, Vec<EnhancedHook>>>>,
+    tx: mpsc::UnboundedSender<HookMessage>,
 }
 
 #[allow(dead_code)]
 impl HookManager {
     pub fn new(app: AppHandle) -> Self {
-        Self {
-            executor: Arc::new(HookExecutor::new(app)),
-            registered_hooks: Arc::new(Mutex::new(HashMap::new())),
-        }
+        let executor = Arc::new(HookExecutor::new(app));
+        let (tx, rx) = mpsc::unbounded_channel();
+        let actor = HookActor {
+            executor: executor.clone(),
+            tx: tx.clone(),
+            registered: HashMap::new(),
+            throttles: HashMap::new(),
+        };
+        tauri::async_runtime::spawn(actor.run(rx));
+        Self { executor, tx }
+    }
+
+    fn send(&self, message: HookMessage) -> Result<(), WorkbenchError> {
+        self.tx
+            .send(message)
+            .map_err(|_| WorkbenchError::Dropped("Hook manager".to_string()))
+    }
+
+    /// Hand an event to the actor and get back the hooks to run now
+    async fn dispatch(
+        &self,
+        event: HookEvent,
+        context: HookContext,
+        hooks: Option<Vec<EnhancedHook>>,
+    ) -> Result<Vec<EnhancedHook>, WorkbenchError> {
+        let (reply, response) = oneshot::channel();
+        self.send(HookMessage::Trigger {
+            event,
+            context,
+            hooks,
+            reply,
+        })?;
+        response
+            .await
+            .map_err(|_| WorkbenchError::Dropped("Hook manager".to_string()))
+    }
+
+    /// Run a hook chain. Throttled hooks are queued and run later in the background
+    /// with every coalesced context in `data.batch`, so they never affect
+    /// `should_continue`.
+    pub async fn run_chain(
+        &self,
+        event: HookEvent,
+        context: HookContext,
+        hooks: Vec<EnhancedHook>,
+    ) -> Result<HookChainResult, WorkbenchError> {
+        let immediate = self
+            .dispatch(event.clone(), context.clone(), Some(hooks))
+            .await?;
+        self.executor
+            .execute_hook_chain(event, context, immediate)
+            .await
     }
 
     /// Register hooks
     pub fn register_hooks(&self, event: HookEvent, hooks: Vec<EnhancedHook>) {
-        let mut registered = self.registered_hooks.lock().unwrap();
-        registered.insert(event.as_str().to_string(), hooks);
+        let _ = self.send(HookMessage::Register {
+            event: event.as_str().to_string(),
+            hooks,
+        });
+    }
+
+    /// Pick up hooks edited on disk. User settings replace the registered hooks;
+    /// for a project, queued batches of hooks that were removed or disabled are
+    /// dropped so a stale definition never runs.
+    pub fn reload_hooks(&self, project_path: Option<&str>, settings: &Value) {
+        let mut hooks: HashMap<String, Vec<EnhancedHook>> = HashMap::new();
+        if let Some(events) = settings.get("hooks").and_then(|h| h.as_object()) {
+            for (event, entries) in events {
+                let event_hooks = entries
+                    .as_array()
+                    .map(|entries| {
+                        entries
+                            .iter()
+                            .filter_map(|v| serde_json::from_value::<EnhancedHook>(v.clone()).ok())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                hooks.insert(event.clone(), event_hooks);
+            }
+        }
+        let _ = self.send(HookMessage::Reload {
+            project_path: project_path.map(str::to_string),
+            hooks,
+        });
     }
 
-    /// Trigger a hook event
+    /// Trigger a hook event with the registered hooks
     pub async fn trigger(
         &self,
         event: HookEvent,
         context: HookContext,
-    ) -> Result<HookChainResult, String> {
-        let hooks = {
-            let registered = self.registered_hooks.lock().unwrap();
-            registered.get(event.as_str()).cloned().unwrap_or_default()
-        };
+    ) -> Result<HookChainResult, WorkbenchError> {
+        let hooks = self.dispatch(event.clone(), context.clone(), None).await?;
 
         if hooks.is_empty() {
-            debug!("No hooks registered for event: {:?}", event);
+            debug!("No hooks to run now for event: {:?}", event);
             return Ok(HookChainResult {
                 event: event.as_str().to_string(),
                 total_hooks: 0,
@@ -397,48 +1573,182 @@ impl HookManager {
             .execute_hook_chain(event, context, hooks)
             .await
     }
+
+    /// Registered hooks and queued batches
+    pub async fn status(&self) -> Result<HookManagerStatus, WorkbenchError> {
+        let (reply, response) = oneshot::channel();
+        self.send(HookMessage::Query { reply })?;
+        response
+            .await
+            .map_err(|_| WorkbenchError::Dropped("Hook manager".to_string()))
+    }
 }
 
 // ============ Tauri Commands ============
 
-/// Trigger a hook event
+/// Trigger a hook event. With a `request_id` the chain can be stopped through
+/// `cancel_operation`: the running hook is killed and no further hooks start.
 #[tauri::command]
 pub async fn trigger_hook_event(
     app: AppHandle,
     event: String,
     context: HookContext,
-) -> Result<HookChainResult, String> {
+    request_id: Option<String>,
+) -> Result<HookChainResult, WorkbenchError> {
+    operations::run(
+        &app,
+        request_id,
+        "hook-chain",
+        run_hook_event(app.clone(), event, context),
+    )
+    .await
+}
+
+async fn run_hook_event(
+    app: AppHandle,
+    event: String,
+    mut context: HookContext,
+) -> Result<HookChainResult, WorkbenchError> {
     let event_enum = match event.as_str() {
+        "PreToolUse" => HookEvent::PreToolUse,
+        "PostToolUse" => HookEvent::PostToolUse,
+        "Stop" => HookEvent::Stop,
+        "SubagentStop" => HookEvent::SubagentStop,
+        "Notification" => HookEvent::Notification,
         "OnContextCompact" => HookEvent::OnContextCompact,
         "OnAgentSwitch" => HookEvent::OnAgentSwitch,
         "OnFileChange" => HookEvent::OnFileChange,
         "OnSessionStart" => HookEvent::OnSessionStart,
         "OnSessionEnd" => HookEvent::OnSessionEnd,
         "OnTabSwitch" => HookEvent::OnTabSwitch,
-        _ => return Err(format!("Unknown hook event: {}", event)),
+        "OnUserCommand" => HookEvent::OnUserCommand,
+        "OnProviderSwitch" => HookEvent::OnProviderSwitch,
+        "OnIdle" => HookEvent::OnIdle,
+        "OnResume" => HookEvent::OnResume,
+        "OnActivityReport" => HookEvent::OnActivityReport,
+        _ => {
+            return Err(WorkbenchError::InvalidInput(format!(
+                "Unknown hook event: {}",
+                event
+            )))
+        }
     };
 
+    // End hooks run once per session
+    if event_enum == HookEvent::OnSessionEnd && !context.session_id.is_empty() {
+        match session_state::transition(
+            &app,
+            &context.session_id,
+            SessionPhase::Ended,
+            Some(&context.project_path),
+        ) {
+            Err(e @ WorkbenchError::InvalidState(_)) => return Err(e),
+            Err(e) => warn!("Failed to record end of session: {}", e),
+            Ok(()) => {}
+        }
+    }
+
+    super::telemetry::record(&app, "hooks.trigger");
+
+    // Checkpoint policies run first so a snapshot precedes anything the hooks do
+    super::checkpoints::on_hook_event(&app, &event, &context).await;
+
+    // Give agent-switch hooks the resolved agent definition
+    if event_enum == HookEvent::OnAgentSwitch {
+        let agent_name = context
+            .data
+            .get("agent")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+        if let Some(name) = agent_name {
+            let definition =
+                crate::commands::extensions::resolve_agent(&name, Some(&context.project_path));
+            if let (Some(definition), Some(data)) = (definition, context.data.as_object_mut()) {
+                data.insert(
+                    "agent_definition".to_string(),
+                    serde_json::to_value(definition).unwrap_or_default(),
+                );
+            }
+        }
+    }
+
+    // Tell hooks which thinking preset the project's sessions run with
+    if let Some(preset) = super::thinking_presets::active_preset(&app, &context.project_path) {
+        if context.data.is_null() {
+            context.data = serde_json::json!({});
+        }
+        if let Some(data) = context.data.as_object_mut() {
+            data.entry("thinking_preset")
+                .or_insert_with(|| serde_json::to_value(preset).unwrap_or_default());
+        }
+    }
+
     // Load hooks from configuration
-    let hooks_config = crate::commands::claude::get_hooks_config(
-        "project".to_string(),
-        Some(context.project_path.clone()),
-    )
-    .await?;
+    let hooks_config =
+        crate::commands::claude::load_hooks_config("project", Some(context.project_path.clone()))?;
 
-    let hooks_array = hooks_config
+    let mut entries = hooks_config
         .get(&event)
         .and_then(|v| v.as_array())
-        .map(|arr| {
-            arr.iter()
-                .filter_map(|v| serde_json::from_value::<EnhancedHook>(v.clone()).ok())
-                .collect()
-        })
+        .cloned()
         .unwrap_or_default();
 
-    let executor = HookExecutor::new(app);
-    executor
-        .execute_hook_chain(event_enum, context, hooks_array)
-        .await
+    // Throttle state is keyed by hook id, so throttled hooks need one that survives reloads
+    let needs_ids = entries.iter().any(|e| {
+        is_enhanced_entry(e)
+            && EventHooks::entry_id(e).is_none()
+            && (e.get("debounce_ms").is_some() || e.get("max_per_minute").is_some())
+    });
+    if needs_ids {
+        let mut stored = EventHooks::load("project", Some(&context.project_path), &event)?;
+        if stored.ensure_ids() {
+            entries = stored.entries.clone();
+            stored.save(&event)?;
+        }
+    }
+
+    let hooks_array = entries
+        .iter()
+        .filter_map(|v| serde_json::from_value::<EnhancedHook>(v.clone()).ok())
+        .collect();
+    let hooks_array =
+        super::hook_approval::filter_approved(&app, &event, &context.project_path, hooks_array);
+
+    match app.try_state::<HookManager>() {
+        Some(manager) => {
+            let manager = manager.inner().clone();
+            manager.run_chain(event_enum, context, hooks_array).await
+        }
+        None => {
+            HookExecutor::new(app)
+                .execute_hook_chain(event_enum, context, hooks_array)
+                .await
+        }
+    }
+}
+
+/// Abort a running hook; returns false if the run already finished
+#[tauri::command]
+pub async fn cancel_hook_run(
+    runs: State<'_, HookRunState>,
+    run_id: i64,
+) -> Result<bool, WorkbenchError> {
+    let sender = runs.0.lock().map_err(|e| e.to_string())?.remove(&run_id);
+    match sender {
+        Some(sender) => {
+            info!("Cancelling hook run {}", run_id);
+            Ok(sender.send(()).is_ok())
+        }
+        None => Ok(false),
+    }
+}
+
+/// Registered hooks and throttled batches waiting to run
+#[tauri::command]
+pub async fn get_hook_manager_status(
+    manager: State<'_, HookManager>,
+) -> Result<HookManagerStatus, WorkbenchError> {
+    manager.status().await
 }
 
 /// Test a hook condition
@@ -447,11 +1757,471 @@ pub async fn test_hook_condition(
     app: tauri::AppHandle,
     condition: String,
     context: HookContext,
-) -> Result<bool, String> {
+) -> Result<bool, WorkbenchError> {
     let executor = HookExecutor::new(app);
     executor.evaluate_condition(&condition, &context)
 }
 
+// ============ Hook Configuration ============
+
+/// Whether a raw hooks entry is an enhanced hook (as opposed to Claude's
+/// native `{matcher, hooks}` groups, which are left untouched)
+pub(crate) fn is_enhanced_entry(entry: &Value) -> bool {
+    entry.get("command").is_some() || entry.get("action").is_some()
+}
+
+/// Raw hook entries for one event in a settings scope
+struct EventHooks {
+    path: PathBuf,
+    settings: Value,
+    entries: Vec<Value>,
+}
+
+impl EventHooks {
+    fn load(scope: &str, project_path: Option<&str>, event: &str) -> Result<Self, WorkbenchError> {
+        let file = super::settings_manager::read_settings_file(scope, project_path)?;
+        if let Some(e) = file.parse_error {
+            return Err(WorkbenchError::InvalidInput(format!(
+                "Settings file is not valid JSON: {}",
+                e
+            )));
+        }
+
+        let entries = file
+            .settings
+            .get("hooks")
+            .and_then(|h| h.get(event))
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        Ok(Self {
+            path: PathBuf::from(file.path),
+            settings: file.settings,
+            entries,
+        })
+    }
+
+    /// Give every enhanced entry an id; returns true if any were added
+    fn ensure_ids(&mut self) -> bool {
+        let mut changed = false;
+        for entry in self.entries.iter_mut().filter(|e| is_enhanced_entry(e)) {
+            if let Some(obj) = entry.as_object_mut() {
+                let has_id = obj
+                    .get("id")
+                    .and_then(|v| v.as_str())
+                    .and_then(|s| Uuid::parse_str(s).ok())
+                    .is_some();
+                if !has_id {
+                    obj.insert("id".to_string(), Value::String(Uuid::new_v4().to_string()));
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+
+    fn hooks(&self) -> Vec<EnhancedHook> {
+        self.entries
+            .iter()
+            .filter(|e| is_enhanced_entry(e))
+            .filter_map(|e| serde_json::from_value(e.clone()).ok())
+            .collect()
+    }
+
+    fn entry_id(entry: &Value) -> Option<Uuid> {
+        entry
+            .get("id")
+            .and_then(|v| v.as_str())
+            .and_then(|s| Uuid::parse_str(s).ok())
+    }
+
+    fn save(mut self, event: &str) -> Result<(), WorkbenchError> {
+        if !self.settings.is_object() {
+            self.settings = serde_json::json!({});
+        }
+        let hooks = self
+            .settings
+            .as_object_mut()
+            .map(|obj| obj.entry("hooks").or_insert_with(|| serde_json::json!({})))
+            .ok_or("Settings root is not an object")?;
+        if !hooks.is_object() {
+            *hooks = serde_json::json!({});
+        }
+        hooks[event] = Value::Array(self.entries);
+
+        super::settings_manager::write_settings_atomically(&self.path, &self.settings)?;
+        Ok(())
+    }
+}
+
+/// Find the event whose hooks contain `id`
+fn find_hook_event(
+    scope: &str,
+    project_path: Option<&str>,
+    id: Uuid,
+) -> Result<String, WorkbenchError> {
+    let file = super::settings_manager::read_settings_file(scope, project_path)?;
+    file.settings
+        .get("hooks")
+        .and_then(|h| h.as_object())
+        .and_then(|events| {
+            events.iter().find_map(|(event, entries)| {
+                entries
+                    .as_array()?
+                    .iter()
+                    .any(|e| EventHooks::entry_id(e) == Some(id))
+                    .then(|| event.clone())
+            })
+        })
+        .ok_or_else(|| {
+            WorkbenchError::NotFound(format!("Hook {} not found in {} settings", id, scope))
+        })
+}
+
+/// Check that a hook has something to run
+fn check_enhanced_hook(hook: &EnhancedHook) -> Result<(), WorkbenchError> {
+    if hook.command.trim().is_empty() && hook.action.is_none() {
+        return Err(WorkbenchError::InvalidInput(
+            "Hook needs a command or an action".to_string(),
+        ));
+    }
+    if let Some(matcher) = &hook.matcher {
+        for pattern in split_matcher(matcher).into_iter().filter(|p| *p != "*") {
+            super::permissions::check_tool_pattern(pattern).map_err(|e| {
+                WorkbenchError::InvalidInput(format!("Invalid matcher '{}': {}", pattern, e))
+            })?;
+        }
+    }
+    for glob in hook.files.iter().flatten() {
+        glob::Pattern::new(glob).map_err(|e| {
+            WorkbenchError::InvalidInput(format!("Invalid file glob '{}': {}", glob, e))
+        })?;
+    }
+    if hook.max_per_minute == Some(0) {
+        return Err(WorkbenchError::InvalidInput(
+            "max_per_minute must be at least 1".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// List the enhanced hooks for an event, persisting ids for hooks that lack one
+#[tauri::command]
+pub async fn list_enhanced_hooks(
+    event: String,
+    scope: String,
+    project_path: Option<String>,
+) -> Result<Vec<EnhancedHook>, WorkbenchError> {
+    let mut event_hooks = EventHooks::load(&scope, project_path.as_deref(), &event)?;
+    let hooks_changed = event_hooks.ensure_ids();
+    let hooks = event_hooks.hooks();
+    if hooks_changed {
+        info!("Assigning ids to {} hooks for {}", event, scope);
+        event_hooks.save(&event)?;
+    }
+    Ok(hooks)
+}
+
+/// Store hooks in the given order. Hooks missing from `ordered_ids` keep their
+/// relative order after the listed ones. Explicit priorities on the reordered
+/// hooks are cleared so the new order is the one that runs.
+#[tauri::command]
+pub async fn reorder_hooks(
+    event: String,
+    ordered_ids: Vec<Uuid>,
+    scope: String,
+    project_path: Option<String>,
+) -> Result<Vec<EnhancedHook>, WorkbenchError> {
+    info!("Reordering {} hooks for {}", ordered_ids.len(), event);
+
+    let mut event_hooks = EventHooks::load(&scope, project_path.as_deref(), &event)?;
+    event_hooks.ensure_ids();
+
+    for id in &ordered_ids {
+        if !event_hooks
+            .entries
+            .iter()
+            .any(|e| EventHooks::entry_id(e) == Some(*id))
+        {
+            return Err(WorkbenchError::NotFound(format!(
+                "Hook {} not found for event {}",
+                id, event
+            )));
+        }
+    }
+
+    let mut reordered: Vec<Value> = Vec::with_capacity(event_hooks.entries.len());
+    for id in &ordered_ids {
+        if let Some(mut entry) = event_hooks
+            .entries
+            .iter()
+            .find(|e| EventHooks::entry_id(e) == Some(*id))
+            .cloned()
+        {
+            if let Some(condition) = entry.get_mut("condition").and_then(|c| c.as_object_mut()) {
+                condition.insert("priority".to_string(), Value::Null);
+            }
+            reordered.push(entry);
+        }
+    }
+    let remaining: Vec<Value> = event_hooks
+        .entries
+        .iter()
+        .filter(|e| match EventHooks::entry_id(e) {
+            Some(id) => !ordered_ids.contains(&id),
+            None => true,
+        })
+        .cloned()
+        .collect();
+    reordered.extend(remaining);
+
+    event_hooks.entries = reordered;
+    let hooks = event_hooks.hooks();
+    event_hooks.save(&event)?;
+    Ok(hooks)
+}
+
+/// Hooks written through the workbench are the user's own, so approve them directly
+fn approve_own_hook(
+    db: &AgentDb,
+    event: &str,
+    hook: &EnhancedHook,
+    scope: &str,
+    project_path: Option<&str>,
+) -> Result<(), WorkbenchError> {
+    if let ("project", Some(project_path)) = (scope, project_path) {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        super::hook_approval::record_approvals(
+            &conn,
+            project_path,
+            &[super::hook_approval::hook_hash(event, hook)],
+        )?;
+    }
+    Ok(())
+}
+
+/// Preset id without its `@version`
+fn preset_id(preset: &str) -> &str {
+    preset.split('@').next().unwrap_or_default()
+}
+
+/// Id of the preset a settings entry was installed from
+fn entry_preset(entry: &Value) -> Option<&str> {
+    entry.get("preset").and_then(|p| p.as_str()).map(preset_id)
+}
+
+/// Presets installed in a settings scope, with whether any of their hooks is enabled
+pub(crate) fn installed_preset_states(
+    scope: &str,
+    project_path: Option<&str>,
+) -> Result<Vec<(String, bool)>, WorkbenchError> {
+    let file = super::settings_manager::read_settings_file(scope, project_path)?;
+    let mut states: Vec<(String, bool)> = Vec::new();
+    let entries = file
+        .settings
+        .get("hooks")
+        .and_then(|h| h.as_object())
+        .into_iter()
+        .flat_map(|events| events.values())
+        .filter_map(|entries| entries.as_array())
+        .flatten();
+    for entry in entries {
+        let preset = match entry_preset(entry) {
+            Some(preset) => preset,
+            None => continue,
+        };
+        let enabled = entry
+            .get("enabled")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+        match states.iter_mut().find(|(id, _)| id == preset) {
+            Some((_, any_enabled)) => *any_enabled |= enabled,
+            None => states.push((preset.to_string(), enabled)),
+        }
+    }
+    Ok(states)
+}
+
+/// Enable or disable every hook installed from a preset; returns how many changed
+pub(crate) fn set_preset_hooks_enabled(
+    scope: &str,
+    project_path: Option<&str>,
+    preset: &str,
+    enabled: bool,
+) -> Result<usize, WorkbenchError> {
+    let file = super::settings_manager::read_settings_file(scope, project_path)?;
+    let events: Vec<String> = file
+        .settings
+        .get("hooks")
+        .and_then(|h| h.as_object())
+        .map(|events| events.keys().cloned().collect())
+        .unwrap_or_default();
+
+    let mut changed = 0;
+    for event in events {
+        let mut event_hooks = EventHooks::load(scope, project_path, &event)?;
+        let mut event_changed = 0;
+        for entry in event_hooks
+            .entries
+            .iter_mut()
+            .filter(|e| entry_preset(e) == Some(preset))
+        {
+            let current = entry
+                .get("enabled")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(true);
+            if let (true, Some(obj)) = (current != enabled, entry.as_object_mut()) {
+                obj.insert("enabled".to_string(), Value::Bool(enabled));
+                event_changed += 1;
+            }
+        }
+        if event_changed > 0 {
+            event_hooks.save(&event)?;
+            changed += event_changed;
+        }
+    }
+    info!(
+        "Set {} hooks of preset {} enabled={} in {} settings",
+        changed, preset, enabled, scope
+    );
+    Ok(changed)
+}
+
+/// Append a hook to an event with a fresh id. A hook from a preset replaces an
+/// earlier install of the same preset in place, keeping its id.
+pub(crate) fn install_enhanced_hook(
+    db: &AgentDb,
+    event: &str,
+    hook: EnhancedHook,
+    scope: &str,
+    project_path: Option<&str>,
+) -> Result<EnhancedHook, WorkbenchError> {
+    check_enhanced_hook(&hook)?;
+
+    let mut event_hooks = EventHooks::load(scope, project_path, event)?;
+    event_hooks.ensure_ids();
+
+    let installed = hook.preset.as_deref().map(preset_id).and_then(|id| {
+        event_hooks
+            .entries
+            .iter()
+            .position(|e| entry_preset(e) == Some(id))
+    });
+
+    let mut hook = hook;
+    match installed {
+        Some(index) => {
+            hook.id =
+                EventHooks::entry_id(&event_hooks.entries[index]).unwrap_or_else(Uuid::new_v4);
+            info!("Replacing {} hook {} in {} settings", event, hook.id, scope);
+            event_hooks.entries[index] = serde_json::to_value(&hook).map_err(|e| e.to_string())?;
+        }
+        None => {
+            hook.id = Uuid::new_v4();
+            info!("Adding {} hook {} to {} settings", event, hook.id, scope);
+            event_hooks
+                .entries
+                .push(serde_json::to_value(&hook).map_err(|e| e.to_string())?);
+        }
+    }
+
+    event_hooks.save(event)?;
+    approve_own_hook(db, event, &hook, scope, project_path)?;
+    Ok(hook)
+}
+
+/// Append a hook to an event; a fresh id is assigned
+#[tauri::command]
+pub async fn add_enhanced_hook(
+    db: State<'_, AgentDb>,
+    event: String,
+    hook: EnhancedHook,
+    scope: String,
+    project_path: Option<String>,
+) -> Result<EnhancedHook, WorkbenchError> {
+    install_enhanced_hook(&db, &event, hook, &scope, project_path.as_deref())
+}
+
+/// Replace a hook in place, keeping its id and position
+#[tauri::command]
+pub async fn update_enhanced_hook(
+    db: State<'_, AgentDb>,
+    id: Uuid,
+    hook: EnhancedHook,
+    scope: String,
+    project_path: Option<String>,
+) -> Result<EnhancedHook, WorkbenchError> {
+    check_enhanced_hook(&hook)?;
+
+    let event = find_hook_event(&scope, project_path.as_deref(), id)?;
+    let mut event_hooks = EventHooks::load(&scope, project_path.as_deref(), &event)?;
+
+    let mut hook = hook;
+    hook.id = id;
+    let value = serde_json::to_value(&hook).map_err(|e| e.to_string())?;
+    let entry = event_hooks
+        .entries
+        .iter_mut()
+        .find(|e| EventHooks::entry_id(e) == Some(id))
+        .ok_or_else(|| WorkbenchError::NotFound(format!("Hook {} not found", id)))?;
+    *entry = value;
+
+    info!("Updated {} hook {}", event, id);
+    event_hooks.save(&event)?;
+    approve_own_hook(&db, &event, &hook, &scope, project_path.as_deref())?;
+    Ok(hook)
+}
+
+/// Remove a hook
+#[tauri::command]
+pub async fn delete_enhanced_hook(
+    id: Uuid,
+    scope: String,
+    project_path: Option<String>,
+) -> Result<(), WorkbenchError> {
+    let event = find_hook_event(&scope, project_path.as_deref(), id)?;
+    let mut event_hooks = EventHooks::load(&scope, project_path.as_deref(), &event)?;
+    event_hooks
+        .entries
+        .retain(|e| EventHooks::entry_id(e) != Some(id));
+
+    info!("Deleted {} hook {}", event, id);
+    event_hooks.save(&event)
+}
+
+/// Enable or disable a hook; flips the current state when `enabled` is omitted
+#[tauri::command]
+pub async fn toggle_enhanced_hook(
+    id: Uuid,
+    enabled: Option<bool>,
+    scope: String,
+    project_path: Option<String>,
+) -> Result<EnhancedHook, WorkbenchError> {
+    let event = find_hook_event(&scope, project_path.as_deref(), id)?;
+    let mut event_hooks = EventHooks::load(&scope, project_path.as_deref(), &event)?;
+
+    let entry = event_hooks
+        .entries
+        .iter_mut()
+        .find(|e| EventHooks::entry_id(e) == Some(id))
+        .ok_or_else(|| WorkbenchError::NotFound(format!("Hook {} not found", id)))?;
+    let current = entry
+        .get("enabled")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    let new_state = enabled.unwrap_or(!current);
+    if let Some(obj) = entry.as_object_mut() {
+        obj.insert("enabled".to_string(), Value::Bool(new_state));
+    }
+    let hook: EnhancedHook = serde_json::from_value(entry.clone())
+        .map_err(|e| WorkbenchError::json("Invalid hook entry", e))?;
+
+    info!("Set {} hook {} enabled={}", event, id, new_state);
+    event_hooks.save(&event)?;
+    Ok(hook)
+}
+
 // ============ Intelligent Automation Scenario Implementation ============
 
 /// Pre‑commit code review hook configuration
@@ -504,10 +2274,10 @@ impl PreCommitCodeReviewHook {
     }
 
     /// Execute pre‑commit code review (Disabled – agent functionality removed)
-    pub async fn execute(&self, _project_path: &str) -> Result<CommitDecision, String> {
+    pub async fn execute(&self, _project_path: &str) -> Result<CommitDecision, WorkbenchError> {
         // Agent functionality removed – always allow commits
         Ok(CommitDecision::Allow {
-            message: "Code review functionality has been disabled (Agent functionality removed)".to_string(),
+            message: i18n::t("commit-review-disabled"),
             suggestions: vec![],
         })
     }
@@ -533,10 +2303,10 @@ pub async fn execute_pre_commit_review(
     _app: tauri::AppHandle,
     _project_path: String,
     _config: Option<PreCommitCodeReviewConfig>,
-) -> Result<CommitDecision, String> {
+) -> Result<CommitDecision, WorkbenchError> {
     // Agent functionality has been removed – return an allow decision
     Ok(CommitDecision::Allow {
-        message: "Code review functionality has been disabled (Agent functionality removed)".to_string(),
+        message: i18n::t("commit-review-disabled"),
         suggestions: vec![],
     })
 }
@@ -0,0 +1,337 @@
+use log::{debug, info, warn};
+/// Per-project environment profiles
+///
+/// A profile is a named set of environment variables (API endpoints, proxies,
+/// feature flags). Each project can have one active profile, which is applied to
+/// Claude CLI processes and hook commands started for that project. Profiles live
+/// in the app data dir rather than the project, since they often hold credentials.
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+use tokio::process::Command;
+
+use super::config_io::write_private;
+use super::profile_archive::is_secret_key;
+use super::redaction::{redactor, Redactor};
+
+/// A named set of environment variables
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvProfile {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub vars: BTreeMap<String, String>,
+}
+
+/// Profiles of one project
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectEnvProfiles {
+    /// Name of the profile applied to new processes, if any
+    pub active: Option<String>,
+    pub profiles: Vec<EnvProfile>,
+}
+
+/// On-disk store, keyed by project path
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct EnvProfileStore {
+    projects: BTreeMap<String, ProjectEnvProfiles>,
+}
+
+/// Which profile each running Claude session was started with
+#[derive(Default)]
+pub struct SessionEnvProfiles(pub Mutex<HashMap<String, String>>);
+
+pub(crate) fn store_file(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = crate::paths::app_data_dir(app)
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    Ok(dir.join("env_profiles.json"))
+}
+
+fn load_store(app: &AppHandle) -> Result<EnvProfileStore, String> {
+    let path = store_file(app)?;
+    if !path.exists() {
+        return Ok(EnvProfileStore::default());
+    }
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read env profiles: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse env profiles: {}", e))
+}
+
+fn save_store(app: &AppHandle, store: &EnvProfileStore) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(store)
+        .map_err(|e| format!("Failed to serialize env profiles: {}", e))?;
+    // Profiles often hold credentials
+    write_private(&store_file(app)?, json.as_bytes())
+}
+
+fn check_profile(profile: &EnvProfile) -> Result<(), String> {
+    if profile.name.trim().is_empty() {
+        return Err("Profile name cannot be empty".to_string());
+    }
+    for (key, value) in &profile.vars {
+        if key.is_empty() || key.contains('=') || key.contains('\0') {
+            return Err(format!("Invalid environment variable name: {:?}", key));
+        }
+        if value.contains('\0') {
+            return Err(format!("Value of {} contains a NUL byte", key));
+        }
+    }
+    Ok(())
+}
+
+/// The project's active profile, if one is set and still exists
+pub fn active_profile(app: &AppHandle, project_path: &str) -> Option<EnvProfile> {
+    let store = match load_store(app) {
+        Ok(store) => store,
+        Err(e) => {
+            warn!("Failed to load env profiles: {}", e);
+            return None;
+        }
+    };
+    let project = store.projects.get(project_path)?;
+    let active = project.active.as_ref()?;
+    project.profiles.iter().find(|p| &p.name == active).cloned()
+}
+
+/// A project's profile by name
+pub(super) fn find_profile(app: &AppHandle, project_path: &str, name: &str) -> Option<EnvProfile> {
+    let store = load_store(app).ok()?;
+    let project = store.projects.get(project_path)?;
+    project.profiles.iter().find(|p| p.name == name).cloned()
+}
+
+/// Whether a variable looks like a credential, by its name or its value
+pub(crate) fn is_secret_var(redactor: &Redactor, key: &str, value: &str) -> bool {
+    is_secret_key(key) || redactor.contains_secret(value)
+}
+
+/// The store as settings sync commits it: secret-looking variables are left
+/// out. None when there is no store.
+pub(crate) fn synced_store(app: &AppHandle) -> Result<Option<Vec<u8>>, String> {
+    if !store_file(app)?.exists() {
+        return Ok(None);
+    }
+    let mut store = load_store(app)?;
+    let redactor = redactor(app);
+    for project in store.projects.values_mut() {
+        for profile in &mut project.profiles {
+            profile
+                .vars
+                .retain(|key, value| !is_secret_var(&redactor, key, value));
+        }
+    }
+    serde_json::to_vec_pretty(&store)
+        .map(Some)
+        .map_err(|e| format!("Failed to serialize env profiles: {}", e))
+}
+
+/// Replace the store with one pulled by settings sync. Secrets stay on this
+/// machine: secret-looking variables in the pulled store are dropped and the
+/// local ones are kept. None deletes the store.
+pub(crate) fn apply_synced_store(app: &AppHandle, content: Option<&[u8]>) -> Result<(), String> {
+    let path = store_file(app)?;
+    let Some(content) = content else {
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| format!("Failed to remove env profiles: {}", e))?;
+        }
+        return Ok(());
+    };
+    let mut store: EnvProfileStore = serde_json::from_slice(content)
+        .map_err(|e| format!("Synced env profiles are not valid: {}", e))?;
+    let local = load_store(app)?;
+    let redactor = redactor(app);
+    for (project_path, project) in &mut store.projects {
+        let local_profiles = local.projects.get(project_path).map(|p| &p.profiles);
+        for profile in &mut project.profiles {
+            profile
+                .vars
+                .retain(|key, value| !is_secret_var(&redactor, key, value));
+            let local_vars = local_profiles
+                .and_then(|profiles| profiles.iter().find(|p| p.name == profile.name))
+                .map(|p| &p.vars);
+            for (key, value) in local_vars.into_iter().flatten() {
+                if is_secret_var(&redactor, key, value) {
+                    profile.vars.insert(key.clone(), value.clone());
+                }
+            }
+        }
+    }
+    save_store(app, &store)
+}
+
+/// Add a profile to a project unless one with its name exists. It is not made
+/// active; that is left to the user. Returns whether it was added.
+pub(super) fn add_profile(
+    app: &AppHandle,
+    project_path: &str,
+    profile: EnvProfile,
+) -> Result<bool, String> {
+    check_profile(&profile)?;
+    let mut store = load_store(app)?;
+    let project = store.projects.entry(project_path.to_string()).or_default();
+    if project.profiles.iter().any(|p| p.name == profile.name) {
+        return Ok(false);
+    }
+    project.profiles.push(profile);
+    save_store(app, &store)?;
+    Ok(true)
+}
+
+/// Apply the project's active profile to a command; returns the profile name
+pub fn apply_active_profile(
+    app: &AppHandle,
+    project_path: &str,
+    cmd: &mut Command,
+) -> Option<String> {
+    let profile = active_profile(app, project_path)?;
+    debug!(
+        "Applying env profile {} ({} vars)",
+        profile.name,
+        profile.vars.len()
+    );
+    cmd.envs(&profile.vars);
+    Some(profile.name)
+}
+
+/// Remember which profile a session was started with
+pub fn record_session_profile(app: &AppHandle, session_id: &str, profile: Option<&str>) {
+    if let (Some(profile), Some(state)) = (profile, app.try_state::<SessionEnvProfiles>()) {
+        if let Ok(mut sessions) = state.0.lock() {
+            sessions.insert(session_id.to_string(), profile.to_string());
+        }
+    }
+}
+
+// ============ Tauri Commands ============
+
+/// List a project's env profiles and which one is active
+#[tauri::command]
+pub async fn list_env_profiles(
+    app: AppHandle,
+    project_path: String,
+) -> Result<ProjectEnvProfiles, String> {
+    let store = load_store(&app)?;
+    Ok(store
+        .projects
+        .get(&project_path)
+        .cloned()
+        .unwrap_or_default())
+}
+
+/// Create a profile, or replace the one with the same name
+#[tauri::command]
+pub async fn save_env_profile(
+    app: AppHandle,
+    project_path: String,
+    profile: EnvProfile,
+) -> Result<ProjectEnvProfiles, String> {
+    check_profile(&profile)?;
+    info!("Saving env profile {} for {}", profile.name, project_path);
+
+    let mut store = load_store(&app)?;
+    let project = store.projects.entry(project_path).or_default();
+    match project.profiles.iter_mut().find(|p| p.name == profile.name) {
+        Some(existing) => *existing = profile,
+        None => project.profiles.push(profile),
+    }
+    let result = project.clone();
+    save_store(&app, &store)?;
+    Ok(result)
+}
+
+/// Rename or edit a profile; the active selection follows a rename
+#[tauri::command]
+pub async fn update_env_profile(
+    app: AppHandle,
+    project_path: String,
+    name: String,
+    profile: EnvProfile,
+) -> Result<ProjectEnvProfiles, String> {
+    check_profile(&profile)?;
+
+    let mut store = load_store(&app)?;
+    let project = store
+        .projects
+        .get_mut(&project_path)
+        .ok_or_else(|| format!("No env profiles for {}", project_path))?;
+    if profile.name != name && project.profiles.iter().any(|p| p.name == profile.name) {
+        return Err(format!("Profile {} already exists", profile.name));
+    }
+    let existing = project
+        .profiles
+        .iter_mut()
+        .find(|p| p.name == name)
+        .ok_or_else(|| format!("Profile {} not found", name))?;
+
+    info!("Updating env profile {} for {}", name, project_path);
+    if project.active.as_deref() == Some(name.as_str()) {
+        project.active = Some(profile.name.clone());
+    }
+    *existing = profile;
+
+    let result = project.clone();
+    save_store(&app, &store)?;
+    Ok(result)
+}
+
+/// Delete a profile, deactivating it first if needed
+#[tauri::command]
+pub async fn delete_env_profile(
+    app: AppHandle,
+    project_path: String,
+    name: String,
+) -> Result<ProjectEnvProfiles, String> {
+    let mut store = load_store(&app)?;
+    let project = store.projects.entry(project_path.clone()).or_default();
+    let before = project.profiles.len();
+    project.profiles.retain(|p| p.name != name);
+    if project.profiles.len() == before {
+        return Err(format!("Profile {} not found", name));
+    }
+    if project.active.as_deref() == Some(name.as_str()) {
+        project.active = None;
+    }
+
+    info!("Deleted env profile {} for {}", name, project_path);
+    let result = project.clone();
+    save_store(&app, &store)?;
+    Ok(result)
+}
+
+/// Make a profile active for new processes; `None` turns profiles off
+#[tauri::command]
+pub async fn activate_env_profile(
+    app: AppHandle,
+    project_path: String,
+    name: Option<String>,
+) -> Result<ProjectEnvProfiles, String> {
+    let mut store = load_store(&app)?;
+    let project = store.projects.entry(project_path.clone()).or_default();
+    if let Some(name) = &name {
+        if !project.profiles.iter().any(|p| &p.name == name) {
+            return Err(format!("Profile {} not found", name));
+        }
+    }
+
+    info!("Activating env profile {:?} for {}", name, project_path);
+    project.active = name;
+    let result = project.clone();
+    save_store(&app, &store)?;
+    Ok(result)
+}
+
+/// The profile a Claude session was started with, if any
+#[tauri::command]
+pub async fn get_session_env_profile(
+    state: State<'_, SessionEnvProfiles>,
+    session_id: String,
+) -> Result<Option<String>, String> {
+    let sessions = state.0.lock().map_err(|e| e.to_string())?;
+    Ok(sessions.get(&session_id).cloned())
+}
@@ -0,0 +1,331 @@
+use log::{info, warn};
+/// Environment checks for the onboarding screen
+///
+/// `run_environment_doctor` checks what sessions depend on: the Claude CLI, git,
+/// a shell, the OS keychain, write access to the data directories, the
+/// executables of configured MCP servers and the API's reachability. Each check
+/// passes, warns or fails, and those that do not pass carry a hint on how to
+/// fix them.
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::AppHandle;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use super::claude::{check_claude_version, get_claude_dir};
+use super::mcp::{read_scope_entries, resolve_executable};
+use super::network::{probe, Connectivity};
+use crate::i18n;
+use crate::process::audit::AuditedCommand;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoctorCheck {
+    /// Stable identifier, e.g. `claude_cli`
+    pub id: String,
+    pub name: String,
+    pub status: CheckStatus,
+    /// What was found
+    pub detail: String,
+    /// How to fix a warning or failure
+    pub fix_hint: Option<String>,
+}
+
+impl DoctorCheck {
+    /// A check named by its `doctor-<id>` message
+    fn new(id: &str, status: CheckStatus, detail: String) -> Self {
+        Self {
+            id: id.to_string(),
+            name: i18n::t(&format!("doctor-{}", id.replace('_', "-"))),
+            status,
+            detail,
+            fix_hint: None,
+        }
+    }
+
+    fn hint(mut self, message_id: &str) -> Self {
+        self.fix_hint = Some(i18n::t(message_id));
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoctorReport {
+    pub checks: Vec<DoctorCheck>,
+    pub passed: usize,
+    pub warnings: usize,
+    pub failures: usize,
+    pub checked_at: String,
+}
+
+/// First line a program prints for `--version`, None if it cannot run
+fn program_version(program: &Path) -> Option<String> {
+    let mut cmd = Command::new(program);
+    cmd.arg("--version");
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    let output = cmd.audited_output("doctor").ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Some(stdout.lines().next().unwrap_or_default().trim().to_string())
+}
+
+async fn check_claude(app: &AppHandle) -> DoctorCheck {
+    let id = "claude_cli";
+    match check_claude_version(app.clone()).await {
+        Ok(status) if status.is_installed => {
+            let version = status
+                .version
+                .unwrap_or_else(|| i18n::t("doctor-unknown-version"));
+            let detail = i18n::t_args("doctor-claude-cli-found", &[("version", version.into())]);
+            DoctorCheck::new(id, CheckStatus::Pass, detail)
+        }
+        Ok(status) => DoctorCheck::new(id, CheckStatus::Fail, status.output)
+            .hint("doctor-claude-cli-install-hint"),
+        Err(e) => DoctorCheck::new(id, CheckStatus::Fail, e).hint("doctor-claude-cli-pick-hint"),
+    }
+}
+
+fn check_git() -> DoctorCheck {
+    let id = "git";
+    let hint = "doctor-git-hint";
+    match resolve_executable("git") {
+        Some(path) => match program_version(&path) {
+            Some(version) => DoctorCheck::new(id, CheckStatus::Pass, version),
+            None => {
+                let path = path.to_string_lossy().to_string();
+                let detail = i18n::t_args("doctor-does-not-run", &[("path", path.into())]);
+                DoctorCheck::new(id, CheckStatus::Fail, detail).hint(hint)
+            }
+        },
+        None => DoctorCheck::new(id, CheckStatus::Warn, i18n::t("doctor-git-missing")).hint(hint),
+    }
+}
+
+/// Bash is what the Claude CLI runs commands with; on Windows it comes with
+/// Git for Windows and PowerShell is reported alongside
+fn check_shell() -> DoctorCheck {
+    let id = "shell";
+    let bash = resolve_executable("bash");
+    let pwsh = if cfg!(target_os = "windows") {
+        resolve_executable("pwsh").or_else(|| resolve_executable("powershell"))
+    } else {
+        None
+    };
+    let mut found: Vec<String> = Vec::new();
+    for (shell, path) in [("bash", &bash), ("PowerShell", &pwsh)] {
+        if let Some(path) = path {
+            let path = path.to_string_lossy().to_string();
+            found.push(i18n::t_args(
+                "doctor-shell-at",
+                &[("shell", shell.into()), ("path", path.into())],
+            ));
+        }
+    }
+    match (bash, cfg!(target_os = "windows")) {
+        (Some(_), _) => DoctorCheck::new(id, CheckStatus::Pass, found.join(", ")),
+        (None, true) => {
+            let detail = if found.is_empty() {
+                i18n::t("doctor-shell-none")
+            } else {
+                i18n::t_args(
+                    "doctor-shell-no-bash-found",
+                    &[("found", found.join(", ").into())],
+                )
+            };
+            DoctorCheck::new(id, CheckStatus::Fail, detail).hint("doctor-shell-windows-hint")
+        }
+        (None, false) => DoctorCheck::new(id, CheckStatus::Warn, i18n::t("doctor-shell-no-bash"))
+            .hint("doctor-shell-hint"),
+    }
+}
+
+async fn check_keychain() -> DoctorCheck {
+    let id = "keychain";
+    if crate::paths::is_portable() {
+        return DoctorCheck::new(id, CheckStatus::Pass, i18n::t("doctor-keychain-portable"));
+    }
+    match super::credentials::probe_keychain().await {
+        Ok(()) => DoctorCheck::new(id, CheckStatus::Pass, i18n::t("doctor-keychain-ok")),
+        Err(e) => DoctorCheck::new(id, CheckStatus::Warn, e).hint("doctor-keychain-hint"),
+    }
+}
+
+/// Create and remove a file in a directory, creating the directory if needed
+fn probe_write(dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let probe = dir.join(format!(".workbench-write-probe-{}", std::process::id()));
+    fs::write(&probe, b"probe").map_err(|e| e.to_string())?;
+    fs::remove_file(&probe).map_err(|e| e.to_string())
+}
+
+fn check_write_permissions(app: &AppHandle) -> DoctorCheck {
+    let id = "write_permissions";
+    let dirs: Vec<(&str, Result<PathBuf, String>)> = vec![
+        ("~/.claude", get_claude_dir().map_err(|e| e.to_string())),
+        (
+            "app data",
+            crate::paths::app_data_dir(app).map_err(|e| e.to_string()),
+        ),
+        (
+            "logs",
+            crate::paths::app_log_dir(app).map_err(|e| e.to_string()),
+        ),
+        ("temp", Ok(crate::paths::temp_dir())),
+    ];
+
+    let mut failures = Vec::new();
+    for (label, dir) in dirs {
+        match dir {
+            Ok(dir) => {
+                if let Err(e) = probe_write(&dir) {
+                    failures.push(format!("{} ({}): {}", label, dir.display(), e));
+                }
+            }
+            Err(e) => failures.push(format!("{}: {}", label, e)),
+        }
+    }
+    if failures.is_empty() {
+        DoctorCheck::new(
+            id,
+            CheckStatus::Pass,
+            i18n::t("doctor-write-permissions-ok"),
+        )
+    } else {
+        DoctorCheck::new(id, CheckStatus::Fail, failures.join("; "))
+            .hint("doctor-write-permissions-hint")
+    }
+}
+
+/// Commands of the enabled stdio servers of the user scope, and of the
+/// project's scopes when a project is given
+fn check_mcp_servers(project_path: Option<&str>) -> DoctorCheck {
+    let id = "mcp_servers";
+    let mut scopes = vec![("user", None)];
+    if let Some(project) = project_path {
+        scopes.push(("project", Some(project)));
+        scopes.push(("local", Some(project)));
+    }
+
+    let mut checked = 0;
+    let mut missing = Vec::new();
+    for (scope, project) in scopes {
+        let entries = match read_scope_entries(scope, project) {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!("Failed to read {} MCP servers: {}", scope, e);
+                continue;
+            }
+        };
+        for entry in entries.iter().filter(|e| e.enabled) {
+            let command = match &entry.definition.command {
+                Some(command) if entry.definition.transport == "stdio" => command,
+                _ => continue,
+            };
+            checked += 1;
+            if resolve_executable(command).is_none() {
+                missing.push(format!("{} ({})", entry.definition.name, command));
+            }
+        }
+    }
+
+    if missing.is_empty() {
+        let detail = i18n::t_args("doctor-mcp-servers-ok", &[("count", checked.into())]);
+        DoctorCheck::new(id, CheckStatus::Pass, detail)
+    } else {
+        let detail = i18n::t_args(
+            "doctor-mcp-servers-missing",
+            &[("commands", missing.join(", ").into())],
+        );
+        DoctorCheck::new(id, CheckStatus::Warn, detail).hint("doctor-mcp-servers-hint")
+    }
+}
+
+async fn check_network() -> DoctorCheck {
+    let id = "network";
+    let endpoint = super::provider::api_endpoint();
+    let (connectivity, latency_ms, error) = probe(&endpoint).await;
+    let error = error.unwrap_or_else(|| i18n::t("doctor-no-response"));
+    match connectivity {
+        Connectivity::Online => {
+            let detail = i18n::t_args(
+                "doctor-network-ok",
+                &[
+                    ("endpoint", endpoint.into()),
+                    ("latency", latency_ms.unwrap_or_default().into()),
+                ],
+            );
+            DoctorCheck::new(id, CheckStatus::Pass, detail)
+        }
+        Connectivity::ApiUnreachable => {
+            let detail = i18n::t_args(
+                "doctor-network-api-unreachable",
+                &[("endpoint", endpoint.into()), ("error", error.into())],
+            );
+            DoctorCheck::new(id, CheckStatus::Fail, detail).hint("doctor-network-api-hint")
+        }
+        Connectivity::Offline => {
+            let detail = i18n::t_args("doctor-network-offline", &[("error", error.into())]);
+            DoctorCheck::new(id, CheckStatus::Fail, detail).hint("doctor-network-offline-hint")
+        }
+        Connectivity::Unknown => DoctorCheck::new(id, CheckStatus::Warn, error),
+    }
+}
+
+// ============ Tauri Commands ============
+
+/// Check the environment sessions need, for the onboarding screen
+#[tauri::command]
+pub async fn run_environment_doctor(
+    app: AppHandle,
+    project_path: Option<String>,
+) -> Result<DoctorReport, String> {
+    let claude = check_claude(&app).await;
+    let blocking_app = app.clone();
+    let (git, shell, write_permissions, mcp_servers) =
+        tauri::async_runtime::spawn_blocking(move || {
+            (
+                check_git(),
+                check_shell(),
+                check_write_permissions(&blocking_app),
+                check_mcp_servers(project_path.as_deref()),
+            )
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+    let checks = vec![
+        claude,
+        git,
+        shell,
+        check_keychain().await,
+        write_permissions,
+        mcp_servers,
+        check_network().await,
+    ];
+
+    let count = |status: CheckStatus| checks.iter().filter(|c| c.status == status).count();
+    let report = DoctorReport {
+        passed: count(CheckStatus::Pass),
+        warnings: count(CheckStatus::Warn),
+        failures: count(CheckStatus::Fail),
+        checked_at: chrono::Utc::now().to_rfc3339(),
+        checks,
+    };
+    info!(
+        "Environment doctor: {} passed, {} warnings, {} failures",
+        report.passed, report.warnings, report.failures
+    );
+    Ok(report)
+}
@@ -2,7 +2,7 @@ use anyhow::Result;
 use log::{debug, info};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
 use super::claude::get_claude_dir;
@@ -463,3 +463,410 @@ pub async fn open_plugins_directory(project_path: Option<String>) -> Result<Stri
 
     Ok(plugins_dir.to_string_lossy().to_string())
 }
+
+// ============ Subagent Definitions ============
+
+/// Built-in tools a subagent may be granted (MCP tools use the `mcp__` prefix)
+const KNOWN_AGENT_TOOLS: &[&str] = &[
+    "Bash",
+    "BashOutput",
+    "Edit",
+    "ExitPlanMode",
+    "Glob",
+    "Grep",
+    "KillShell",
+    "MultiEdit",
+    "NotebookEdit",
+    "Read",
+    "SlashCommand",
+    "Task",
+    "TodoWrite",
+    "WebFetch",
+    "WebSearch",
+    "Write",
+];
+
+/// Model aliases accepted in agent frontmatter
+const AGENT_MODELS: &[&str] = &["inherit", "sonnet", "opus", "haiku"];
+
+/// A parsed subagent definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentDefinition {
+    /// Agent name from frontmatter (falls back to file name)
+    pub name: String,
+    pub description: String,
+    /// Granted tools; None means the agent inherits every tool
+    pub tools: Option<Vec<String>>,
+    /// Model alias or "inherit"
+    pub model: Option<String>,
+    /// Optional UI color
+    pub color: Option<String>,
+    /// Markdown body used as the agent's system prompt
+    pub system_prompt: String,
+    /// Scope: "project" or "user" (ignored when saving)
+    #[serde(default)]
+    pub scope: String,
+    /// Full file path (ignored when saving)
+    #[serde(default)]
+    pub path: String,
+}
+
+/// Agent frontmatter as it appears on disk
+#[derive(Debug, Deserialize)]
+struct AgentFrontmatter {
+    name: Option<String>,
+    description: Option<String>,
+    /// Comma-separated string or YAML list
+    tools: Option<serde_yaml::Value>,
+    model: Option<String>,
+    color: Option<String>,
+}
+
+/// Result of validating an agent definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentValidation {
+    pub valid: bool,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+    /// Tools that are neither built-in nor MCP tools
+    pub unknown_tools: Vec<String>,
+}
+
+/// Resolve the agents directory for a scope
+fn agents_dir(scope: &str, project_path: Option<&str>) -> Result<std::path::PathBuf, String> {
+    match scope {
+        "user" => Ok(get_claude_dir().map_err(|e| e.to_string())?.join("agents")),
+        "project" => project_path
+            .map(|p| Path::new(p).join(".claude").join("agents"))
+            .ok_or_else(|| "Project path required for project scope".to_string()),
+        _ => Err(format!("Invalid scope: {}", scope)),
+    }
+}
+
+/// Parse an agent file into a definition
+fn parse_agent_definition(content: &str, path: &Path, scope: &str) -> AgentDefinition {
+    let file_name = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    let mut frontmatter = None;
+    let mut body = content.to_string();
+
+    let lines: Vec<&str> = content.lines().collect();
+    if lines.first() == Some(&"---") {
+        if let Some(end) = lines
+            .iter()
+            .skip(1)
+            .position(|l| *l == "---")
+            .map(|i| i + 1)
+        {
+            match serde_yaml::from_str::<AgentFrontmatter>(&lines[1..end].join("\n")) {
+                Ok(fm) => {
+                    frontmatter = Some(fm);
+                    body = lines[(end + 1)..].join("\n");
+                }
+                Err(e) => debug!("Failed to parse agent frontmatter in {:?}: {}", path, e),
+            }
+        }
+    }
+
+    let fm = frontmatter.unwrap_or(AgentFrontmatter {
+        name: None,
+        description: None,
+        tools: None,
+        model: None,
+        color: None,
+    });
+
+    let tools = fm.tools.and_then(|value| match value {
+        serde_yaml::Value::String(s) => Some(
+            s.split(',')
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect(),
+        ),
+        serde_yaml::Value::Sequence(seq) => Some(
+            seq.iter()
+                .filter_map(|v| v.as_str().map(|s| s.trim().to_string()))
+                .collect(),
+        ),
+        _ => None,
+    });
+
+    AgentDefinition {
+        name: fm.name.unwrap_or(file_name),
+        description: fm
+            .description
+            .or_else(|| parse_description_from_content(&body))
+            .unwrap_or_default(),
+        tools,
+        model: fm.model,
+        color: fm.color,
+        system_prompt: body.trim_start_matches('\n').to_string(),
+        scope: scope.to_string(),
+        path: path.to_string_lossy().to_string(),
+    }
+}
+
+/// Serialize an agent definition back to markdown with frontmatter
+fn render_agent_file(agent: &AgentDefinition) -> Result<String, String> {
+    let mut frontmatter = serde_yaml::Mapping::new();
+    frontmatter.insert("name".into(), agent.name.as_str().into());
+    frontmatter.insert("description".into(), agent.description.as_str().into());
+    if let Some(tools) = &agent.tools {
+        frontmatter.insert("tools".into(), tools.join(", ").into());
+    }
+    if let Some(model) = &agent.model {
+        frontmatter.insert("model".into(), model.as_str().into());
+    }
+    if let Some(color) = &agent.color {
+        frontmatter.insert("color".into(), color.as_str().into());
+    }
+
+    let yaml = serde_yaml::to_string(&frontmatter)
+        .map_err(|e| format!("Failed to serialize frontmatter: {}", e))?;
+
+    Ok(format!(
+        "---\n{}---\n\n{}\n",
+        yaml,
+        agent.system_prompt.trim_end()
+    ))
+}
+
+/// Whether a tool entry is a known built-in or an MCP tool
+fn is_known_agent_tool(tool: &str) -> bool {
+    tool.starts_with("mcp__") || KNOWN_AGENT_TOOLS.contains(&tool)
+}
+
+/// Validate an agent's name, description, model and tools
+fn check_agent_definition(agent: &AgentDefinition) -> AgentValidation {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    if agent.name.is_empty() {
+        errors.push("Agent name cannot be empty".to_string());
+    } else if !agent
+        .name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        errors.push("Agent name may only contain lowercase letters, digits and '-'".to_string());
+    }
+
+    if agent.description.trim().is_empty() {
+        errors.push("Description is required so Claude knows when to use the agent".to_string());
+    }
+
+    if let Some(model) = &agent.model {
+        if !AGENT_MODELS.contains(&model.as_str()) {
+            warnings.push(format!("Unrecognized model alias: {}", model));
+        }
+    }
+
+    let unknown_tools: Vec<String> = agent
+        .tools
+        .iter()
+        .flatten()
+        .filter(|t| !is_known_agent_tool(t))
+        .cloned()
+        .collect();
+    if !unknown_tools.is_empty() {
+        errors.push(format!("Unknown tools: {}", unknown_tools.join(", ")));
+    }
+    if agent.tools.as_ref().map(|t| t.is_empty()).unwrap_or(false) {
+        warnings.push("Agent has an empty tool list and will not be able to act".to_string());
+    }
+
+    AgentValidation {
+        valid: errors.is_empty(),
+        errors,
+        warnings,
+        unknown_tools,
+    }
+}
+
+/// Load every agent definition in one scope
+fn load_scope_agents(scope: &str, project_path: Option<&str>) -> Vec<AgentDefinition> {
+    let dir = match agents_dir(scope, project_path) {
+        Ok(dir) if dir.exists() => dir,
+        _ => return Vec::new(),
+    };
+
+    WalkDir::new(&dir)
+        .max_depth(2)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            e.path().is_file() && e.path().extension().and_then(|s| s.to_str()) == Some("md")
+        })
+        .filter_map(|e| match fs::read_to_string(e.path()) {
+            Ok(content) => Some(parse_agent_definition(&content, e.path(), scope)),
+            Err(err) => {
+                debug!("Failed to read agent file {:?}: {}", e.path(), err);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Find an agent by name, preferring project scope like Claude does
+pub fn resolve_agent(name: &str, project_path: Option<&str>) -> Option<AgentDefinition> {
+    load_scope_agents("project", project_path)
+        .into_iter()
+        .chain(load_scope_agents("user", None))
+        .find(|agent| agent.name == name)
+}
+
+/// List parsed agent definitions for one scope, or both when scope is None
+#[tauri::command]
+pub async fn list_agents(
+    scope: Option<String>,
+    project_path: Option<String>,
+) -> Result<Vec<AgentDefinition>, String> {
+    info!("Listing agent definitions (scope: {:?})", scope);
+
+    let mut agents = Vec::new();
+    match scope.as_deref() {
+        Some(scope) => {
+            agents_dir(scope, project_path.as_deref())?;
+            agents.extend(load_scope_agents(scope, project_path.as_deref()));
+        }
+        None => {
+            agents.extend(load_scope_agents("project", project_path.as_deref()));
+            agents.extend(load_scope_agents("user", None));
+        }
+    }
+
+    agents.sort_by(|a, b| a.name.cmp(&b.name).then(a.scope.cmp(&b.scope)));
+    Ok(agents)
+}
+
+/// Validate an agent definition without saving it
+#[tauri::command]
+pub async fn validate_agent(agent: AgentDefinition) -> Result<AgentValidation, String> {
+    Ok(check_agent_definition(&agent))
+}
+
+/// The agent file an edit started from, canonicalized. It is removed after a
+/// rename, so anything but a `.md` file in the user or project agents
+/// directory is refused. None when the file no longer exists.
+fn original_agent_file(path: &str, project_path: Option<&str>) -> Result<Option<PathBuf>, String> {
+    let file = match Path::new(path).canonicalize() {
+        Ok(file) => file,
+        Err(_) => return Ok(None),
+    };
+    if file.is_file() && file.extension().is_some_and(|ext| ext == "md") {
+        for scope in ["project", "user"] {
+            let dir = agents_dir(scope, project_path).and_then(|dir| {
+                dir.canonicalize()
+                    .map_err(|e| format!("Failed to resolve {:?}: {}", dir, e))
+            });
+            if let Ok(dir) = dir {
+                if file.parent() == Some(dir.as_path()) {
+                    return Ok(Some(file));
+                }
+            }
+        }
+    }
+    Err(format!("Not an agent file: {}", path))
+}
+
+/// Create or update an agent definition
+///
+/// Pass `original_path` when editing so a rename removes the old file. Saving
+/// under the name of another existing agent is refused.
+#[tauri::command]
+pub async fn save_agent(
+    agent: AgentDefinition,
+    scope: String,
+    project_path: Option<String>,
+    original_path: Option<String>,
+) -> Result<AgentDefinition, String> {
+    info!("Saving agent {} in scope {}", agent.name, scope);
+
+    let validation = check_agent_definition(&agent);
+    if !validation.valid {
+        return Err(validation.errors.join("; "));
+    }
+
+    let dir = agents_dir(&scope, project_path.as_deref())?;
+    let file_path = dir.join(format!("{}.md", agent.name));
+
+    let original = match original_path.as_deref() {
+        Some(path) => original_agent_file(path, project_path.as_deref())?,
+        None => None,
+    };
+    let is_same_file = original.is_some() && file_path.canonicalize().ok() == original;
+    if file_path.exists() && !is_same_file {
+        return Err(format!(
+            "An agent named {} already exists in {} scope",
+            agent.name, scope
+        ));
+    }
+
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create agents directory: {}", e))?;
+    fs::write(&file_path, render_agent_file(&agent)?)
+        .map_err(|e| format!("Failed to write agent file: {}", e))?;
+
+    if let Some(original) = original.filter(|_| !is_same_file) {
+        fs::remove_file(&original)
+            .map_err(|e| format!("Failed to remove previous agent file: {}", e))?;
+    }
+
+    let content =
+        fs::read_to_string(&file_path).map_err(|e| format!("Failed to read saved agent: {}", e))?;
+    Ok(parse_agent_definition(&content, &file_path, &scope))
+}
+
+/// Copy an agent under a new name, optionally into another scope
+#[tauri::command]
+pub async fn duplicate_agent(
+    name: String,
+    scope: String,
+    new_name: String,
+    target_scope: Option<String>,
+    project_path: Option<String>,
+) -> Result<AgentDefinition, String> {
+    let target_scope = target_scope.unwrap_or_else(|| scope.clone());
+    info!(
+        "Duplicating agent {} ({}) as {} ({})",
+        name, scope, new_name, target_scope
+    );
+
+    let mut agent = load_scope_agents(&scope, project_path.as_deref())
+        .into_iter()
+        .find(|a| a.name == name)
+        .ok_or_else(|| format!("Agent not found: {}", name))?;
+
+    let target =
+        agents_dir(&target_scope, project_path.as_deref())?.join(format!("{}.md", new_name));
+    if target.exists() {
+        return Err(format!(
+            "An agent named {} already exists in {} scope",
+            new_name, target_scope
+        ));
+    }
+
+    agent.name = new_name;
+    save_agent(agent, target_scope, project_path, None).await
+}
+
+/// Delete an agent definition file
+#[tauri::command]
+pub async fn delete_agent(
+    name: String,
+    scope: String,
+    project_path: Option<String>,
+) -> Result<String, String> {
+    let agent = load_scope_agents(&scope, project_path.as_deref())
+        .into_iter()
+        .find(|a| a.name == name)
+        .ok_or_else(|| format!("Agent not found: {}", name))?;
+
+    fs::remove_file(&agent.path).map_err(|e| format!("Failed to delete agent file: {}", e))?;
+    Ok(format!("Deleted agent: {}", name))
+}
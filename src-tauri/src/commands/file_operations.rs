@@ -1,5 +1,12 @@
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::process::Command as StdCommand;
 
+use crate::error::{WorkbenchError, WorkbenchResult};
+use crate::process::audit::AuditedCommand;
+
 /// Open a directory in the system file explorer (cross-platform)
 #[tauri::command]
 pub async fn open_directory_in_explorer(directory_path: String) -> Result<(), String> {
@@ -9,7 +16,7 @@ pub async fn open_directory_in_explorer(directory_path: String) -> Result<(), St
         let mut cmd = StdCommand::new("explorer");
         cmd.arg(&directory_path);
         cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-        cmd.spawn()
+        cmd.audited_spawn("system")
             .map_err(|e| format!("Failed to open directory: {}", e))?;
     }
 
@@ -17,7 +24,7 @@ pub async fn open_directory_in_explorer(directory_path: String) -> Result<(), St
     {
         StdCommand::new("open")
             .arg(&directory_path)
-            .spawn()
+            .audited_spawn("system")
             .map_err(|e| format!("Failed to open directory: {}", e))?;
     }
 
@@ -25,7 +32,7 @@ pub async fn open_directory_in_explorer(directory_path: String) -> Result<(), St
     {
         StdCommand::new("xdg-open")
             .arg(&directory_path)
-            .spawn()
+            .audited_spawn("system")
             .map_err(|e| format!("Failed to open directory: {}", e))?;
     }
 
@@ -42,7 +49,7 @@ pub async fn open_file_with_default_app(file_path: String) -> Result<(), String>
         let mut cmd = StdCommand::new("cmd");
         cmd.args(&["/C", "start", "", &file_path]);
         cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-        cmd.spawn()
+        cmd.audited_spawn("system")
             .map_err(|e| format!("Failed to open file: {}", e))?;
     }
 
@@ -50,7 +57,7 @@ pub async fn open_file_with_default_app(file_path: String) -> Result<(), String>
     {
         StdCommand::new("open")
             .arg(&file_path)
-            .spawn()
+            .audited_spawn("system")
             .map_err(|e| format!("Failed to open file: {}", e))?;
     }
 
@@ -58,9 +65,259 @@ pub async fn open_file_with_default_app(file_path: String) -> Result<(), String>
     {
         StdCommand::new("xdg-open")
             .arg(&file_path)
-            .spawn()
+            .audited_spawn("system")
             .map_err(|e| format!("Failed to open file: {}", e))?;
     }
 
     Ok(())
 }
+
+// ============ File Preview ============
+
+/// Bytes returned by `read_project_file` unless the caller asks otherwise
+const DEFAULT_PREVIEW_BYTES: u64 = 1024 * 1024;
+/// Upper bound on `max_bytes`
+const MAX_PREVIEW_BYTES: u64 = 16 * 1024 * 1024;
+/// How much of the start of a file is inspected to tell text from binary
+const SNIFF_BYTES: usize = 8 * 1024;
+
+/// Decoded contents of a file, as shown by the diff viewer and editors
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilePreview {
+    pub path: String,
+    /// Size of the whole file on disk
+    pub size: u64,
+    /// "utf-8", "utf-16le", "utf-16be", "latin-1" or "binary"
+    pub encoding: String,
+    pub is_binary: bool,
+    /// Text of the file, None for binary files. Ends with a marker line when
+    /// `truncated`.
+    pub content: Option<String>,
+    pub truncated: bool,
+    /// Lines in `content`, not counting the truncation marker
+    pub line_count: usize,
+    /// Syntax highlighting hint derived from the file name
+    pub language: Option<String>,
+}
+
+/// Encoding of `bytes` from a byte order mark, with the length of the mark
+fn bom_encoding(bytes: &[u8]) -> Option<(&'static str, usize)> {
+    match bytes {
+        [0xEF, 0xBB, 0xBF, ..] => Some(("utf-8", 3)),
+        [0xFF, 0xFE, ..] => Some(("utf-16le", 2)),
+        [0xFE, 0xFF, ..] => Some(("utf-16be", 2)),
+        _ => None,
+    }
+}
+
+/// UTF-16 without a BOM: ASCII text leaves every other byte zero
+fn guess_utf16(sample: &[u8]) -> Option<&'static str> {
+    if sample.len() < 4 {
+        return None;
+    }
+    let pairs = sample.len() / 2;
+    let zeros_at = |offset: usize| {
+        sample
+            .chunks_exact(2)
+            .filter(|pair| pair[offset] == 0 && pair[1 - offset] != 0)
+            .count()
+    };
+    if zeros_at(1) * 10 >= pairs * 9 {
+        Some("utf-16le")
+    } else if zeros_at(0) * 10 >= pairs * 9 {
+        Some("utf-16be")
+    } else {
+        None
+    }
+}
+
+/// NUL bytes or mostly control characters mean the file is not text
+fn looks_binary(sample: &[u8]) -> bool {
+    if sample.contains(&0) {
+        return true;
+    }
+    let control = sample
+        .iter()
+        .filter(|&&b| b < 0x20 && !matches!(b, b'\n' | b'\r' | b'\t' | 0x0C | 0x1B))
+        .count();
+    control * 10 > sample.len()
+}
+
+fn decode_utf16(bytes: &[u8], big_endian: bool) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| {
+            if big_endian {
+                u16::from_be_bytes([pair[0], pair[1]])
+            } else {
+                u16::from_le_bytes([pair[0], pair[1]])
+            }
+        })
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+/// Decode `bytes`, the first bytes of a file that may continue past them.
+/// Returns the encoding and the text, None for binary data.
+fn decode_text(bytes: &[u8], truncated: bool) -> (&'static str, Option<String>) {
+    if let Some((encoding, bom_len)) = bom_encoding(bytes) {
+        let body = &bytes[bom_len..];
+        let text = match encoding {
+            "utf-16le" => decode_utf16(body, false),
+            "utf-16be" => decode_utf16(body, true),
+            _ => String::from_utf8_lossy(body).to_string(),
+        };
+        return (encoding, Some(text));
+    }
+
+    let sample = &bytes[..bytes.len().min(SNIFF_BYTES)];
+    if let Some(encoding) = guess_utf16(sample) {
+        return (encoding, Some(decode_utf16(bytes, encoding == "utf-16be")));
+    }
+    if looks_binary(sample) {
+        return ("binary", None);
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(text) => ("utf-8", Some(text.to_string())),
+        // A cut in the middle of the last character
+        Err(e) if truncated && e.error_len().is_none() => (
+            "utf-8",
+            Some(String::from_utf8_lossy(&bytes[..e.valid_up_to()]).to_string()),
+        ),
+        // Not UTF-8: every byte is a latin-1 character
+        Err(_) => ("latin-1", Some(bytes.iter().map(|&b| b as char).collect())),
+    }
+}
+
+/// Language hint for syntax highlighting, from the file name
+fn language_hint(path: &Path) -> Option<&'static str> {
+    let file_name = path.file_name()?.to_string_lossy().to_lowercase();
+    match file_name.as_str() {
+        "dockerfile" => return Some("dockerfile"),
+        "makefile" | "gnumakefile" => return Some("makefile"),
+        "cmakelists.txt" => return Some("cmake"),
+        ".gitignore" | ".dockerignore" | ".env" => return Some("ini"),
+        _ => {}
+    }
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+    Some(match extension.as_str() {
+        "rs" => "rust",
+        "ts" | "mts" | "cts" => "typescript",
+        "tsx" => "tsx",
+        "js" | "mjs" | "cjs" => "javascript",
+        "jsx" => "jsx",
+        "py" | "pyi" => "python",
+        "go" => "go",
+        "java" => "java",
+        "kt" | "kts" => "kotlin",
+        "swift" => "swift",
+        "c" | "h" => "c",
+        "cc" | "cpp" | "cxx" | "hpp" | "hh" => "cpp",
+        "cs" => "csharp",
+        "rb" => "ruby",
+        "php" => "php",
+        "sh" | "bash" | "zsh" => "shell",
+        "ps1" => "powershell",
+        "sql" => "sql",
+        "html" | "htm" => "html",
+        "css" => "css",
+        "scss" => "scss",
+        "vue" => "vue",
+        "svelte" => "svelte",
+        "json" | "jsonl" => "json",
+        "toml" => "toml",
+        "yaml" | "yml" => "yaml",
+        "xml" | "svg" => "xml",
+        "md" | "markdown" | "mdx" => "markdown",
+        "ini" | "cfg" | "conf" => "ini",
+        "lua" => "lua",
+        "dart" => "dart",
+        "txt" | "log" => "plaintext",
+        _ => return None,
+    })
+}
+
+/// `path` resolved against `project_path`, refusing paths that leave the project
+fn resolve_project_path(path: &str, project_path: Option<&str>) -> WorkbenchResult<PathBuf> {
+    let project = match project_path {
+        Some(project) => PathBuf::from(project),
+        None => return Ok(PathBuf::from(path)),
+    };
+    let full = project.join(path);
+    let canonical = full
+        .canonicalize()
+        .map_err(|e| WorkbenchError::io(format!("Failed to open {}", full.display()), e))?;
+    let project = project
+        .canonicalize()
+        .map_err(|e| WorkbenchError::io(format!("Failed to open {}", project.display()), e))?;
+    if !canonical.starts_with(&project) {
+        return Err(WorkbenchError::InvalidInput(format!(
+            "{} is outside the project",
+            path
+        )));
+    }
+    Ok(canonical)
+}
+
+/// Read a text file for display, without loading more than `max_bytes`
+/// (1 MiB by default). Relative paths are resolved against `project_path`,
+/// which the file must not leave. Binary files are reported without content;
+/// UTF-16 and non-UTF-8 (read as latin-1) files are decoded.
+#[tauri::command]
+pub async fn read_project_file(
+    path: String,
+    project_path: Option<String>,
+    max_bytes: Option<u64>,
+) -> Result<FilePreview, WorkbenchError> {
+    let full_path = resolve_project_path(&path, project_path.as_deref())?;
+    let max_bytes = max_bytes
+        .unwrap_or(DEFAULT_PREVIEW_BYTES)
+        .clamp(1, MAX_PREVIEW_BYTES);
+
+    let metadata = std::fs::metadata(&full_path)
+        .map_err(|e| WorkbenchError::io(format!("Failed to read {}", path), e))?;
+    if !metadata.is_file() {
+        return Err(WorkbenchError::InvalidInput(format!(
+            "{} is not a file",
+            path
+        )));
+    }
+    let size = metadata.len();
+
+    let mut bytes = Vec::with_capacity(size.min(max_bytes) as usize);
+    File::open(&full_path)
+        .and_then(|file| file.take(max_bytes).read_to_end(&mut bytes))
+        .map_err(|e| WorkbenchError::io(format!("Failed to read {}", path), e))?;
+    let truncated = (bytes.len() as u64) < size;
+
+    let (encoding, content) = decode_text(&bytes, truncated);
+    let line_count = content
+        .as_deref()
+        .map(|text| text.lines().count())
+        .unwrap_or(0);
+    let content = content.map(|text| {
+        if truncated {
+            format!(
+                "{}\n… [truncated: showing {} of {} bytes]",
+                text.trim_end_matches(['\r', '\n']),
+                bytes.len(),
+                size
+            )
+        } else {
+            text
+        }
+    });
+
+    Ok(FilePreview {
+        path: full_path.to_string_lossy().to_string(),
+        size,
+        encoding: encoding.to_string(),
+        is_binary: content.is_none(),
+        content,
+        truncated,
+        line_count,
+        language: language_hint(&full_path).map(str::to_string),
+    })
+}
@@ -0,0 +1,632 @@
+use log::debug;
+/// Git backends
+///
+/// Read-only git queries go through the `GitBackend` trait, implemented on top
+/// of libgit2 (`LibGitBackend`) and the `git` executable (`CliBackend`). The
+/// library needs no git on PATH and no process per query, so it is tried first;
+/// whatever it cannot handle (unusual repository layouts, revision syntax it does
+/// not know) is retried with the CLI. Only when `git` itself cannot be found is
+/// the library's error reported instead.
+///
+/// Backend calls block, so the async wrappers at the bottom run them on the
+/// blocking pool and stop waiting if the surrounding operation is cancelled.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command as StdCommand;
+
+use super::git_stats::GitDiffStats;
+use super::operations;
+use crate::error::{WorkbenchError, WorkbenchResult};
+use crate::process::audit::AuditedCommand;
+
+/// Working tree status of one path
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileStatus {
+    pub path: String,
+    /// Path before a staged rename
+    pub old_path: Option<String>,
+    /// "added", "modified", "deleted", "renamed", "typechange" or "conflicted";
+    /// None if nothing is staged
+    pub staged: Option<String>,
+    /// As `staged`, plus "untracked"; None if the worktree matches the index
+    pub unstaged: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BranchInfo {
+    pub name: String,
+    pub is_head: bool,
+    pub commit: String,
+    pub upstream: Option<String>,
+    /// Commits not on the upstream, and upstream commits not on the branch
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+/// Branch and working tree of HEAD, as hook conditions see them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeadState {
+    /// None when HEAD is detached
+    pub branch: Option<String>,
+    /// Any staged, unstaged or untracked change
+    pub dirty: bool,
+    pub upstream: Option<String>,
+    /// Relative to the upstream; 0 without one
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+/// Consecutive lines last changed by the same commit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlameHunk {
+    /// 1-based line in the current file
+    pub start_line: usize,
+    pub lines: usize,
+    pub commit: String,
+    pub author: String,
+    /// Unix seconds
+    pub author_time: i64,
+    pub summary: String,
+}
+
+/// Read-only git queries on the repository containing `repo`
+pub trait GitBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Full object IDs of `revs`, in order
+    fn resolve(&self, repo: &Path, revs: &[&str]) -> WorkbenchResult<Vec<String>>;
+
+    /// Totals of the diff between two commits or trees, with rename detection
+    fn diff_stats(&self, repo: &Path, from: &str, to: &str) -> WorkbenchResult<GitDiffStats>;
+
+    /// Changed, untracked and conflicted paths, relative to the repository root
+    fn status(&self, repo: &Path) -> WorkbenchResult<Vec<FileStatus>>;
+
+    /// Local branches
+    fn branches(&self, repo: &Path) -> WorkbenchResult<Vec<BranchInfo>>;
+
+    /// Blame of `file`, relative to `repo`, as of HEAD
+    fn blame(&self, repo: &Path, file: &str) -> WorkbenchResult<Vec<BlameHunk>>;
+}
+
+// ============ libgit2 ============
+
+pub struct LibGitBackend;
+
+fn lib_error(what: &str, e: git2::Error) -> WorkbenchError {
+    WorkbenchError::Other(format!("{} failed: {}", what, e.message()))
+}
+
+fn open_repo(repo: &Path) -> WorkbenchResult<git2::Repository> {
+    git2::Repository::discover(repo).map_err(|e| lib_error("Opening repository", e))
+}
+
+fn delta_kind(delta: git2::Delta) -> Option<&'static str> {
+    match delta {
+        git2::Delta::Added | git2::Delta::Copied => Some("added"),
+        git2::Delta::Modified => Some("modified"),
+        git2::Delta::Deleted => Some("deleted"),
+        git2::Delta::Renamed => Some("renamed"),
+        git2::Delta::Typechange => Some("typechange"),
+        git2::Delta::Untracked => Some("untracked"),
+        git2::Delta::Conflicted => Some("conflicted"),
+        _ => None,
+    }
+}
+
+impl GitBackend for LibGitBackend {
+    fn name(&self) -> &'static str {
+        "libgit2"
+    }
+
+    fn resolve(&self, repo: &Path, revs: &[&str]) -> WorkbenchResult<Vec<String>> {
+        let repo = open_repo(repo)?;
+        revs.iter()
+            .map(|rev| {
+                repo.revparse_single(rev)
+                    .map(|object| object.id().to_string())
+                    .map_err(|e| lib_error(&format!("Resolving {}", rev), e))
+            })
+            .collect()
+    }
+
+    fn diff_stats(&self, repo: &Path, from: &str, to: &str) -> WorkbenchResult<GitDiffStats> {
+        let repo = open_repo(repo)?;
+        let tree = |rev: &str| {
+            repo.revparse_single(rev)
+                .and_then(|object| object.peel_to_tree())
+                .map_err(|e| lib_error(&format!("Reading tree of {}", rev), e))
+        };
+        let (from_tree, to_tree) = (tree(from)?, tree(to)?);
+        let mut diff = repo
+            .diff_tree_to_tree(Some(&from_tree), Some(&to_tree), None)
+            .map_err(|e| lib_error("Diff", e))?;
+        diff.find_similar(None)
+            .map_err(|e| lib_error("Rename detection", e))?;
+        let stats = diff.stats().map_err(|e| lib_error("Diff stats", e))?;
+        Ok(GitDiffStats {
+            lines_added: stats.insertions(),
+            lines_removed: stats.deletions(),
+            files_changed: stats.files_changed(),
+        })
+    }
+
+    fn status(&self, repo: &Path) -> WorkbenchResult<Vec<FileStatus>> {
+        let repo = open_repo(repo)?;
+        let mut options = git2::StatusOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .renames_head_to_index(true);
+        let statuses = repo
+            .statuses(Some(&mut options))
+            .map_err(|e| lib_error("Status", e))?;
+
+        Ok(statuses
+            .iter()
+            .filter_map(|entry| {
+                let status = entry.status();
+                let staged = entry.head_to_index();
+                let unstaged = entry.index_to_workdir();
+                let path = unstaged
+                    .as_ref()
+                    .and_then(|d| d.new_file().path())
+                    .or_else(|| staged.as_ref().and_then(|d| d.new_file().path()))
+                    .map(|p| p.to_string_lossy().to_string())
+                    .or_else(|| entry.path().map(str::to_string))?;
+                if status.is_conflicted() {
+                    return Some(FileStatus {
+                        path,
+                        old_path: None,
+                        staged: Some("conflicted".to_string()),
+                        unstaged: Some("conflicted".to_string()),
+                    });
+                }
+                let old_path = staged
+                    .as_ref()
+                    .filter(|d| d.status() == git2::Delta::Renamed)
+                    .and_then(|d| d.old_file().path())
+                    .map(|p| p.to_string_lossy().to_string());
+                Some(FileStatus {
+                    path,
+                    old_path,
+                    staged: staged
+                        .and_then(|d| delta_kind(d.status()))
+                        .map(str::to_string),
+                    unstaged: unstaged
+                        .and_then(|d| delta_kind(d.status()))
+                        .map(str::to_string),
+                })
+            })
+            .filter(|s| s.staged.is_some() || s.unstaged.is_some())
+            .collect())
+    }
+
+    fn branches(&self, repo: &Path) -> WorkbenchResult<Vec<BranchInfo>> {
+        let repo = open_repo(repo)?;
+        let branches = repo
+            .branches(Some(git2::BranchType::Local))
+            .map_err(|e| lib_error("Listing branches", e))?;
+
+        let mut list = Vec::new();
+        for branch in branches {
+            let (branch, _) = branch.map_err(|e| lib_error("Listing branches", e))?;
+            let name = match branch.name() {
+                Ok(Some(name)) => name.to_string(),
+                _ => continue,
+            };
+            let commit = branch.get().target();
+            let upstream = branch.upstream().ok();
+            let (ahead, behind) = match (commit, upstream.as_ref().and_then(|u| u.get().target())) {
+                (Some(local), Some(remote)) => {
+                    repo.graph_ahead_behind(local, remote).unwrap_or((0, 0))
+                }
+                _ => (0, 0),
+            };
+            list.push(BranchInfo {
+                name,
+                is_head: branch.is_head(),
+                commit: commit.map(|id| id.to_string()).unwrap_or_default(),
+                upstream: upstream.and_then(|u| u.name().ok().flatten().map(str::to_string)),
+                ahead,
+                behind,
+            });
+        }
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(list)
+    }
+
+    fn blame(&self, repo: &Path, file: &str) -> WorkbenchResult<Vec<BlameHunk>> {
+        let git_repo = open_repo(repo)?;
+        let workdir = git_repo.workdir().ok_or_else(|| {
+            WorkbenchError::InvalidInput("Repository has no working tree".to_string())
+        })?;
+        let full_path = repo.join(file);
+        let canonical = |p: &Path| p.canonicalize().unwrap_or_else(|_| p.to_path_buf());
+        let relative = canonical(&full_path)
+            .strip_prefix(canonical(workdir))
+            .map(Path::to_path_buf)
+            .map_err(|_| {
+                WorkbenchError::InvalidInput(format!("{} is outside the repository", file))
+            })?;
+
+        let blame = git_repo
+            .blame_file(&relative, None)
+            .map_err(|e| lib_error(&format!("Blame of {}", file), e))?;
+        let mut summaries: HashMap<git2::Oid, String> = HashMap::new();
+        Ok(blame
+            .iter()
+            .map(|hunk| {
+                let id = hunk.final_commit_id();
+                let summary = summaries
+                    .entry(id)
+                    .or_insert_with(|| {
+                        git_repo
+                            .find_commit(id)
+                            .ok()
+                            .and_then(|c| c.summary().map(str::to_string))
+                            .unwrap_or_default()
+                    })
+                    .clone();
+                let signature = hunk.final_signature();
+                BlameHunk {
+                    start_line: hunk.final_start_line(),
+                    lines: hunk.lines_in_hunk(),
+                    commit: id.to_string(),
+                    author: signature.name().unwrap_or_default().to_string(),
+                    author_time: signature.when().seconds(),
+                    summary,
+                }
+            })
+            .collect())
+    }
+}
+
+// ============ git CLI ============
+
+pub struct CliBackend;
+
+#[tracing::instrument(
+    name = "git",
+    skip_all,
+    fields(command = args.first().copied().unwrap_or_default(), repo = %repo.display())
+)]
+fn run_git(repo: &Path, args: &[&str]) -> WorkbenchResult<String> {
+    let mut cmd = StdCommand::new("git");
+    cmd.current_dir(repo);
+    cmd.args(args);
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+
+    let name = args.first().copied().unwrap_or_default();
+    let output = cmd
+        .audited_output("git")
+        .map_err(|e| WorkbenchError::io(format!("Failed to execute git {}", name), e))?;
+    if !output.status.success() {
+        return Err(WorkbenchError::git(name, &output.stderr));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Meaning of one column of `git status --porcelain`
+fn porcelain_kind(code: char) -> Option<&'static str> {
+    match code {
+        'A' | 'C' => Some("added"),
+        'M' => Some("modified"),
+        'D' => Some("deleted"),
+        'R' => Some("renamed"),
+        'T' => Some("typechange"),
+        'U' => Some("conflicted"),
+        '?' => Some("untracked"),
+        _ => None,
+    }
+}
+
+impl GitBackend for CliBackend {
+    fn name(&self) -> &'static str {
+        "cli"
+    }
+
+    fn resolve(&self, repo: &Path, revs: &[&str]) -> WorkbenchResult<Vec<String>> {
+        let mut args = vec!["rev-parse"];
+        args.extend_from_slice(revs);
+        let stdout = run_git(repo, &args)?;
+        let oids: Vec<String> = stdout.lines().map(|l| l.trim().to_string()).collect();
+        if oids.len() != revs.len() {
+            // Ranges and other revision syntax resolve to more than one line
+            return Err(WorkbenchError::InvalidInput(format!(
+                "Cannot resolve {} to single objects",
+                revs.join(", ")
+            )));
+        }
+        Ok(oids)
+    }
+
+    fn diff_stats(&self, repo: &Path, from: &str, to: &str) -> WorkbenchResult<GitDiffStats> {
+        let stdout = run_git(repo, &["diff", "--numstat", "-M", from, to])?;
+
+        // Format: <added>\t<removed>\t<filename>, with `-` for binary files
+        let mut stats = GitDiffStats {
+            lines_added: 0,
+            lines_removed: 0,
+            files_changed: 0,
+        };
+        for line in stdout.lines() {
+            let parts: Vec<&str> = line.split('\t').collect();
+            if parts.len() >= 2 {
+                stats.files_changed += 1;
+                stats.lines_added += parts[0].parse::<usize>().unwrap_or(0);
+                stats.lines_removed += parts[1].parse::<usize>().unwrap_or(0);
+            }
+        }
+        Ok(stats)
+    }
+
+    fn status(&self, repo: &Path) -> WorkbenchResult<Vec<FileStatus>> {
+        let stdout = run_git(
+            repo,
+            &["status", "--porcelain=v1", "-z", "--untracked-files=all"],
+        )?;
+        let mut entries = stdout.split('\0').filter(|e| !e.is_empty());
+        let mut list = Vec::new();
+        while let Some(entry) = entries.next() {
+            let mut codes = entry.chars();
+            let (x, y) = match (codes.next(), codes.next()) {
+                (Some(x), Some(y)) => (x, y),
+                _ => continue,
+            };
+            let path = entry.get(3..).unwrap_or_default().to_string();
+            // With -z the source of a rename follows as its own entry
+            let old_path = if matches!(x, 'R' | 'C') {
+                entries.next().map(str::to_string)
+            } else {
+                None
+            };
+            let conflicted = x == 'U' || y == 'U' || (x == y && matches!(x, 'A' | 'D'));
+            let (staged, unstaged) = if conflicted {
+                (Some("conflicted"), Some("conflicted"))
+            } else if x == '?' {
+                (None, Some("untracked"))
+            } else {
+                (porcelain_kind(x), porcelain_kind(y))
+            };
+            if staged.is_none() && unstaged.is_none() {
+                continue;
+            }
+            list.push(FileStatus {
+                path,
+                old_path,
+                staged: staged.map(str::to_string),
+                unstaged: unstaged.map(str::to_string),
+            });
+        }
+        Ok(list)
+    }
+
+    fn branches(&self, repo: &Path) -> WorkbenchResult<Vec<BranchInfo>> {
+        let stdout = run_git(
+            repo,
+            &[
+                "for-each-ref",
+                "--format=%(refname:short)%00%(HEAD)%00%(objectname)%00%(upstream:short)%00%(upstream:track,nobracket)",
+                "refs/heads",
+            ],
+        )?;
+        let mut list: Vec<BranchInfo> = stdout
+            .lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split('\0').collect();
+                if fields.len() < 5 {
+                    return None;
+                }
+                // e.g. "ahead 2, behind 1"
+                let count = |label: &str| {
+                    fields[4]
+                        .split(", ")
+                        .find_map(|part| part.strip_prefix(label))
+                        .and_then(|n| n.trim().parse().ok())
+                        .unwrap_or(0)
+                };
+                Some(BranchInfo {
+                    name: fields[0].to_string(),
+                    is_head: fields[1] == "*",
+                    commit: fields[2].to_string(),
+                    upstream: Some(fields[3].to_string()).filter(|u| !u.is_empty()),
+                    ahead: count("ahead "),
+                    behind: count("behind "),
+                })
+            })
+            .collect();
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(list)
+    }
+
+    fn blame(&self, repo: &Path, file: &str) -> WorkbenchResult<Vec<BlameHunk>> {
+        let stdout = run_git(repo, &["blame", "--porcelain", "--", file])?;
+
+        // Each line starts with `<sha> <orig line> <final line> [<group size>]`,
+        // and the first line of a commit is followed by its headers
+        let mut hunks: Vec<BlameHunk> = Vec::new();
+        let mut headers: HashMap<String, (String, i64, String)> = HashMap::new();
+        let mut current: Option<String> = None;
+        for line in stdout.lines() {
+            if line.starts_with('\t') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split(' ').collect();
+            let is_header = fields.len() >= 3
+                && fields[0].len() >= 40
+                && fields[0].chars().all(|c| c.is_ascii_hexdigit());
+            if is_header {
+                let commit = fields[0].to_string();
+                if let (Some(start), Some(size)) = (
+                    fields[2].parse::<usize>().ok(),
+                    fields.get(3).and_then(|n| n.parse::<usize>().ok()),
+                ) {
+                    hunks.push(BlameHunk {
+                        start_line: start,
+                        lines: size,
+                        commit: commit.clone(),
+                        author: String::new(),
+                        author_time: 0,
+                        summary: String::new(),
+                    });
+                }
+                current = Some(commit);
+                continue;
+            }
+            let commit = match &current {
+                Some(commit) => commit.clone(),
+                None => continue,
+            };
+            let header = headers.entry(commit).or_default();
+            if let Some(author) = line.strip_prefix("author ") {
+                header.0 = author.to_string();
+            } else if let Some(time) = line.strip_prefix("author-time ") {
+                header.1 = time.parse().unwrap_or(0);
+            } else if let Some(summary) = line.strip_prefix("summary ") {
+                header.2 = summary.to_string();
+            }
+        }
+        for hunk in &mut hunks {
+            if let Some((author, time, summary)) = headers.get(&hunk.commit) {
+                hunk.author = author.clone();
+                hunk.author_time = *time;
+                hunk.summary = summary.clone();
+            }
+        }
+        Ok(hunks)
+    }
+}
+
+// ============ Fallback ============
+
+static LIBRARY: LibGitBackend = LibGitBackend;
+static CLI: CliBackend = CliBackend;
+
+/// Run `query` with the library, retrying with the CLI if that fails
+#[tracing::instrument(name = "git_query", skip_all, fields(query = what))]
+fn with_fallback<T>(
+    what: &str,
+    query: impl Fn(&dyn GitBackend) -> WorkbenchResult<T>,
+) -> WorkbenchResult<T> {
+    let library_error = match query(&LIBRARY) {
+        Ok(result) => return Ok(result),
+        Err(e) => e,
+    };
+    debug!(
+        "{} via {} failed ({}), retrying with {}",
+        what,
+        LIBRARY.name(),
+        library_error,
+        CLI.name()
+    );
+    match query(&CLI) {
+        Err(WorkbenchError::Io { source, .. }) if source.kind() == std::io::ErrorKind::NotFound => {
+            Err(library_error)
+        }
+        result => result,
+    }
+}
+
+/// Run a blocking query on the blocking pool, stopping early on cancellation
+async fn blocking<T: Send + 'static>(
+    what: &'static str,
+    query: impl FnOnce() -> WorkbenchResult<T> + Send + 'static,
+) -> WorkbenchResult<T> {
+    let task = tauri::async_runtime::spawn_blocking(query);
+    tokio::select! {
+        result = task => result.unwrap_or_else(|_| Err(WorkbenchError::Dropped(what.to_string()))),
+        _ = operations::cancelled() => Err(WorkbenchError::Cancelled(what.to_string())),
+    }
+}
+
+pub async fn resolve(repo: &str, revs: Vec<String>) -> WorkbenchResult<Vec<String>> {
+    let repo = PathBuf::from(repo);
+    blocking("Git rev-parse", move || {
+        let revs: Vec<&str> = revs.iter().map(String::as_str).collect();
+        with_fallback("rev-parse", |backend| backend.resolve(&repo, &revs))
+    })
+    .await
+}
+
+pub async fn diff_stats(repo: &str, from: String, to: String) -> WorkbenchResult<GitDiffStats> {
+    let repo = PathBuf::from(repo);
+    blocking("Git diff", move || {
+        with_fallback("diff stats", |backend| {
+            backend.diff_stats(&repo, &from, &to)
+        })
+    })
+    .await
+}
+
+pub async fn status(repo: &str) -> WorkbenchResult<Vec<FileStatus>> {
+    let repo = PathBuf::from(repo);
+    blocking("Git status", move || {
+        with_fallback("status", |backend| backend.status(&repo))
+    })
+    .await
+}
+
+/// State of HEAD, queried on the calling thread
+pub fn head_state(repo: &str) -> WorkbenchResult<HeadState> {
+    let repo = Path::new(repo);
+    let head = with_fallback("branches", |backend| backend.branches(repo))?
+        .into_iter()
+        .find(|b| b.is_head);
+    let dirty = !with_fallback("status", |backend| backend.status(repo))?.is_empty();
+    Ok(match head {
+        Some(branch) => HeadState {
+            branch: Some(branch.name),
+            dirty,
+            upstream: branch.upstream,
+            ahead: branch.ahead,
+            behind: branch.behind,
+        },
+        None => HeadState {
+            branch: None,
+            dirty,
+            upstream: None,
+            ahead: 0,
+            behind: 0,
+        },
+    })
+}
+
+// ============ Tauri Commands ============
+
+#[tauri::command]
+pub async fn get_git_status(project_path: String) -> Result<Vec<FileStatus>, WorkbenchError> {
+    status(&project_path).await
+}
+
+#[tauri::command]
+pub async fn list_git_branches(project_path: String) -> Result<Vec<BranchInfo>, WorkbenchError> {
+    let repo = PathBuf::from(project_path);
+    blocking("Git branches", move || {
+        with_fallback("branches", |backend| backend.branches(&repo))
+    })
+    .await
+}
+
+/// Blame of a file (relative to the project) as of HEAD
+#[tauri::command]
+pub async fn get_git_blame(
+    project_path: String,
+    file_path: String,
+) -> Result<Vec<BlameHunk>, WorkbenchError> {
+    let repo = PathBuf::from(project_path);
+    blocking("Git blame", move || {
+        with_fallback("blame", |backend| backend.blame(&repo, &file_path))
+    })
+    .await
+}
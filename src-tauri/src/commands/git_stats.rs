@@ -1,5 +1,16 @@
+use log::debug;
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
 use std::process::Command as StdCommand;
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::AppHandle;
+
+use super::{git_backend, operations};
+use crate::error::WorkbenchError;
+use crate::process::audit::AuditedCommand;
 
 /// Git code change statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,74 +24,441 @@ pub struct GitDiffStats {
     pub files_changed: usize,
 }
 
-/// Get code change statistics between two commits
+/// Get code change statistics between two commits. Results are cached per
+/// repository and resolved commits; `force_refresh` skips the cache. With a
+/// `request_id` the diff can be aborted through `cancel_operation`.
 #[tauri::command]
 pub async fn get_git_diff_stats(
+    app: AppHandle,
     project_path: String,
     from_commit: String,
     to_commit: Option<String>,
-) -> Result<GitDiffStats, String> {
-    let to_ref = to_commit.unwrap_or_else(|| "HEAD".to_string());
+    force_refresh: Option<bool>,
+    request_id: Option<String>,
+) -> Result<GitDiffStats, WorkbenchError> {
+    operations::run(
+        &app,
+        request_id,
+        "git-diff-stats",
+        diff_stats(
+            project_path,
+            from_commit,
+            to_commit,
+            force_refresh.unwrap_or(false),
+        ),
+    )
+    .await
+}
 
-    // Use `git diff --numstat` to get statistics
-    let mut cmd = StdCommand::new("git");
-    cmd.current_dir(&project_path);
-    cmd.args(&["diff", "--numstat", &from_commit, &to_ref]);
+// ============ Stats cache ============
+//
+// The diff between two commits never changes, so totals are cached
+// under the commits' object IDs. Resolving refs like `HEAD` to IDs opens the
+// repository too, so resolved IDs are cached per repository as well, and dropped
+// whenever HEAD, the checked-out branch, packed refs or the index change on disk.
 
-    #[cfg(target_os = "windows")]
-    {
-        use std::os::windows::process::CommandExt;
-        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-    }
+/// Cached totals kept across all repositories
+const STATS_CACHE_SIZE: usize = 512;
 
-    let output = cmd
-        .output()
-        .map_err(|e| format!("Failed to execute git diff: {}", e))?;
+/// What the resolved refs of a repository depend on
+#[derive(Debug, Clone, PartialEq)]
+struct RepoFingerprint {
+    head: String,
+    branch_modified: Option<SystemTime>,
+    packed_refs_modified: Option<SystemTime>,
+    index_modified: Option<SystemTime>,
+}
 
-    if !output.status.success() {
-        return Err(format!(
-            "Git diff failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
-    }
+#[derive(Default)]
+struct StatsCache {
+    /// Per repository: fingerprint and the object IDs refs resolved to under it
+    refs: HashMap<String, (RepoFingerprint, HashMap<String, String>)>,
+    /// Totals by repository and object IDs, with a use counter for eviction
+    stats: HashMap<(String, String, String), (u64, GitDiffStats)>,
+    uses: u64,
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
+static STATS_CACHE: Lazy<Mutex<StatsCache>> = Lazy::new(|| Mutex::new(StatsCache::default()));
 
-    // Parse `git diff --numstat` output
-    // Format: <added>\t<removed>\t<filename>
-    let mut lines_added = 0;
-    let mut lines_removed = 0;
-    let mut files_changed = 0;
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
 
-    for line in stdout.lines() {
-        let parts: Vec<&str> = line.split('\t').collect();
-        if parts.len() >= 2 {
-            files_changed += 1;
+/// None for repositories whose git dir is not a plain `.git` directory
+/// (worktrees, submodules); those resolve refs on every call
+fn repo_fingerprint(project_path: &str) -> Option<RepoFingerprint> {
+    let git_dir = Path::new(project_path).join(".git");
+    let head = std::fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let branch_modified = head
+        .trim()
+        .strip_prefix("ref: ")
+        .and_then(|branch| modified(&git_dir.join(branch)));
+    Some(RepoFingerprint {
+        branch_modified,
+        packed_refs_modified: modified(&git_dir.join("packed-refs")),
+        index_modified: modified(&git_dir.join("index")),
+        head,
+    })
+}
 
-            // Parse added lines
-            if let Ok(added) = parts[0].parse::<usize>() {
-                lines_added += added;
-            }
+/// Cached object IDs of `refs`, if all are known under `fingerprint`
+fn cached_oids(
+    project_path: &str,
+    fingerprint: &RepoFingerprint,
+    refs: &[&str],
+) -> Option<Vec<String>> {
+    let cache = STATS_CACHE.lock().ok()?;
+    let (cached_fingerprint, oids) = cache.refs.get(project_path)?;
+    if cached_fingerprint != fingerprint {
+        return None;
+    }
+    refs.iter().map(|r| oids.get(*r).cloned()).collect()
+}
+
+fn cache_oids(project_path: &str, fingerprint: RepoFingerprint, resolved: &[(&str, String)]) {
+    if let Ok(mut cache) = STATS_CACHE.lock() {
+        let entry = cache
+            .refs
+            .entry(project_path.to_string())
+            .or_insert_with(|| (fingerprint.clone(), HashMap::new()));
+        if entry.0 != fingerprint {
+            *entry = (fingerprint, HashMap::new());
+        }
+        for (name, oid) in resolved {
+            entry.1.insert(name.to_string(), oid.clone());
+        }
+    }
+}
+
+fn cached_stats(key: &(String, String, String)) -> Option<GitDiffStats> {
+    let mut cache = STATS_CACHE.lock().ok()?;
+    cache.uses += 1;
+    let uses = cache.uses;
+    let (last_use, stats) = cache.stats.get_mut(key)?;
+    *last_use = uses;
+    Some(stats.clone())
+}
 
-            // Parse removed lines
-            if let Ok(removed) = parts[1].parse::<usize>() {
-                lines_removed += removed;
+fn cache_stats(key: (String, String, String), stats: &GitDiffStats) {
+    if let Ok(mut cache) = STATS_CACHE.lock() {
+        cache.uses += 1;
+        let uses = cache.uses;
+        if cache.stats.len() >= STATS_CACHE_SIZE && !cache.stats.contains_key(&key) {
+            let oldest = cache
+                .stats
+                .iter()
+                .min_by_key(|(_, (last_use, _))| *last_use)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                cache.stats.remove(&oldest);
             }
         }
+        cache.stats.insert(key, (uses, stats.clone()));
     }
+}
 
-    Ok(GitDiffStats {
-        lines_added,
-        lines_removed,
-        files_changed,
-    })
+/// Object IDs of `from` and `to`, from the cache while the repository is unchanged
+async fn resolve_refs(
+    project_path: &str,
+    from: &str,
+    to: &str,
+    force_refresh: bool,
+) -> Result<(String, String), WorkbenchError> {
+    let fingerprint = repo_fingerprint(project_path);
+    if let (false, Some(fingerprint)) = (force_refresh, &fingerprint) {
+        if let Some(oids) = cached_oids(project_path, fingerprint, &[from, to]) {
+            return Ok((oids[0].clone(), oids[1].clone()));
+        }
+    }
+
+    let oids = git_backend::resolve(project_path, vec![from.to_string(), to.to_string()]).await?;
+    let (from_oid, to_oid) = match oids.as_slice() {
+        [from_oid, to_oid] => (from_oid.clone(), to_oid.clone()),
+        _ => return Err(format!("Cannot resolve {} and {} to single commits", from, to).into()),
+    };
+    if let Some(fingerprint) = fingerprint {
+        cache_oids(
+            project_path,
+            fingerprint,
+            &[(from, from_oid.clone()), (to, to_oid.clone())],
+        );
+    }
+    Ok((from_oid, to_oid))
+}
+
+/// Diff totals, cached by resolved commits; stops early if the surrounding
+/// operation is cancelled
+pub(crate) async fn diff_stats(
+    project_path: String,
+    from_commit: String,
+    to_commit: Option<String>,
+    force_refresh: bool,
+) -> Result<GitDiffStats, WorkbenchError> {
+    let to_ref = to_commit.unwrap_or_else(|| "HEAD".to_string());
+    let (from_oid, to_oid) =
+        resolve_refs(&project_path, &from_commit, &to_ref, force_refresh).await?;
+    let key = (project_path.clone(), from_oid.clone(), to_oid.clone());
+    if !force_refresh {
+        if let Some(stats) = cached_stats(&key) {
+            debug!(
+                "Git stats cache hit for {} {}..{}",
+                project_path, from_oid, to_oid
+            );
+            return Ok(stats);
+        }
+    }
+
+    let stats = git_backend::diff_stats(&project_path, from_oid, to_oid).await?;
+    cache_stats(key, &stats);
+    Ok(stats)
 }
 
 /// Get code change statistics for the current session (from session start to now)
 #[tauri::command]
 pub async fn get_session_code_changes(
+    app: AppHandle,
     project_path: String,
     session_start_commit: String,
-) -> Result<GitDiffStats, String> {
-    get_git_diff_stats(project_path, session_start_commit, None).await
+    force_refresh: Option<bool>,
+    request_id: Option<String>,
+) -> Result<GitDiffStats, WorkbenchError> {
+    get_git_diff_stats(
+        app,
+        project_path,
+        session_start_commit,
+        None,
+        force_refresh,
+        request_id,
+    )
+    .await
+}
+
+/// One line of a diff hunk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffLine {
+    /// "context", "added" or "removed"
+    pub kind: String,
+    pub content: String,
+    pub old_line: Option<usize>,
+    pub new_line: Option<usize>,
+}
+
+/// A hunk of a unified diff
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffHunk {
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    /// Text after the second `@@`, usually the enclosing function
+    pub header: String,
+    pub lines: Vec<DiffLine>,
+}
+
+/// Changes to one file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileDiff {
+    pub path: String,
+    /// Previous path for renames
+    pub old_path: Option<String>,
+    /// "added", "deleted", "renamed" or "modified"
+    pub status: String,
+    pub binary: bool,
+    pub additions: usize,
+    pub deletions: usize,
+    pub hunks: Vec<DiffHunk>,
+}
+
+impl FileDiff {
+    fn new(path: String) -> Self {
+        Self {
+            path,
+            old_path: None,
+            status: "modified".to_string(),
+            binary: false,
+            additions: 0,
+            deletions: 0,
+            hunks: Vec::new(),
+        }
+    }
+}
+
+/// `-a,b` / `+c,d` range of a hunk header; the count defaults to 1
+fn parse_hunk_range(range: &str) -> Option<(usize, usize)> {
+    let range = &range[1..];
+    match range.split_once(',') {
+        Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+fn parse_hunk_header(line: &str) -> Option<DiffHunk> {
+    let rest = line.strip_prefix("@@ ")?;
+    let (ranges, header) = rest.split_once(" @@")?;
+    let (old, new) = ranges.split_once(' ')?;
+    let (old_start, old_lines) = parse_hunk_range(old)?;
+    let (new_start, new_lines) = parse_hunk_range(new)?;
+    Some(DiffHunk {
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+        header: header.trim().to_string(),
+        lines: Vec::new(),
+    })
+}
+
+/// Parse `git diff` output (unquoted paths) into per-file hunks
+pub fn parse_unified_diff(diff: &str) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+    let (mut old_line, mut new_line) = (0, 0);
+
+    for line in diff.lines() {
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            // `a/<path> b/<path>`; exact paths come from the headers below when present
+            let path = rest
+                .split_once(" b/")
+                .map(|(_, b)| b)
+                .unwrap_or(rest)
+                .to_string();
+            files.push(FileDiff::new(path));
+            continue;
+        }
+        let file = match files.last_mut() {
+            Some(file) => file,
+            None => continue,
+        };
+
+        if file.hunks.is_empty() {
+            if line.starts_with("new file mode") {
+                file.status = "added".to_string();
+            } else if line.starts_with("deleted file mode") {
+                file.status = "deleted".to_string();
+            } else if let Some(from) = line.strip_prefix("rename from ") {
+                file.status = "renamed".to_string();
+                file.old_path = Some(from.to_string());
+            } else if let Some(to) = line.strip_prefix("rename to ") {
+                file.path = to.to_string();
+            } else if line.starts_with("Binary files ") {
+                file.binary = true;
+            } else if let Some(path) = line.strip_prefix("+++ b/") {
+                file.path = path.to_string();
+            } else if let Some(path) = line.strip_prefix("--- a/") {
+                if file.status == "deleted" {
+                    file.path = path.to_string();
+                }
+            }
+        }
+
+        if line.starts_with("@@ ") {
+            if let Some(hunk) = parse_hunk_header(line) {
+                old_line = hunk.old_start;
+                new_line = hunk.new_start;
+                file.hunks.push(hunk);
+            }
+            continue;
+        }
+        let hunk = match file.hunks.last_mut() {
+            Some(hunk) => hunk,
+            None => continue,
+        };
+        let (kind, old, new) = match line.chars().next() {
+            Some('+') => {
+                file.additions += 1;
+                new_line += 1;
+                ("added", None, Some(new_line - 1))
+            }
+            Some('-') => {
+                file.deletions += 1;
+                old_line += 1;
+                ("removed", Some(old_line - 1), None)
+            }
+            Some(' ') | None => {
+                old_line += 1;
+                new_line += 1;
+                ("context", Some(old_line - 1), Some(new_line - 1))
+            }
+            // "\ No newline at end of file"
+            _ => continue,
+        };
+        hunk.lines.push(DiffLine {
+            kind: kind.to_string(),
+            content: line.get(1..).unwrap_or("").to_string(),
+            old_line: old,
+            new_line: new,
+        });
+    }
+    files
+}
+
+/// Structured diff between two commits or trees
+pub fn git_file_diffs(
+    project_path: &str,
+    from: &str,
+    to: &str,
+) -> Result<Vec<FileDiff>, WorkbenchError> {
+    let mut cmd = StdCommand::new("git");
+    cmd.current_dir(project_path);
+    cmd.args([
+        "-c",
+        "core.quotepath=false",
+        "diff",
+        "--no-color",
+        "--no-ext-diff",
+        "-M",
+        from,
+        to,
+    ]);
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+
+    let output = cmd
+        .audited_output("git")
+        .map_err(|e| WorkbenchError::io("Failed to execute git diff", e))?;
+    if !output.status.success() {
+        return Err(WorkbenchError::git("diff", &output.stderr));
+    }
+    Ok(parse_unified_diff(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Totals over a set of file diffs
+pub fn summarize_file_diffs(files: &[FileDiff]) -> GitDiffStats {
+    GitDiffStats {
+        lines_added: files.iter().map(|f| f.additions).sum(),
+        lines_removed: files.iter().map(|f| f.deletions).sum(),
+        files_changed: files.len(),
+    }
+}
+
+/// Git's well-known empty tree, for diffing from before the first commit
+pub const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+/// Last commit reachable from HEAD made before `before` (any date git accepts),
+/// or None if there is none
+pub fn commit_before(project_path: &str, before: &str) -> Result<Option<String>, WorkbenchError> {
+    let mut cmd = StdCommand::new("git");
+    cmd.current_dir(project_path);
+    cmd.args(["rev-list", "-1", &format!("--before={}", before), "HEAD"]);
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+
+    let output = cmd
+        .audited_output("git")
+        .map_err(|e| WorkbenchError::io("Failed to execute git rev-list", e))?;
+    if !output.status.success() {
+        return Err(WorkbenchError::git("rev-list", &output.stderr));
+    }
+    let commit = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(Some(commit).filter(|c| !c.is_empty()))
 }
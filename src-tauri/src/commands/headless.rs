@@ -0,0 +1,280 @@
+use log::{info, warn};
+/// Headless Claude sessions for background work
+///
+/// Orchestrations and scheduled runs start sessions that the UI does not drive, so
+/// several can run at once without touching the interactive process held in
+/// ClaudeProcessState. Each session is registered in the process registry once its
+/// init message names it, can be cancelled or time out, and reports the final
+/// `result` message along with the tail of stderr.
+use std::collections::VecDeque;
+use std::process::ExitStatus;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::Notify;
+
+use super::claude_stream::StreamValidator;
+use crate::process::ProcessRegistryState;
+
+/// Stderr lines kept to explain a failed run
+const STDERR_TAIL: usize = 20;
+
+/// What to run
+#[derive(Debug, Clone)]
+pub(crate) struct SessionSpec {
+    pub(crate) project_path: String,
+    pub(crate) prompt: String,
+    pub(crate) model: String,
+    pub(crate) plan_mode: bool,
+    /// Kill the session after this long
+    pub(crate) timeout: Option<Duration>,
+}
+
+/// Progress reported while a session runs
+pub(crate) enum SessionProgress<'a> {
+    Started { pid: u32 },
+    SessionId(&'a str),
+    Line(&'a str),
+}
+
+pub(crate) type ProgressFn = Arc<dyn Fn(SessionProgress) + Send + Sync>;
+
+/// How the process ended
+pub(crate) enum SessionExit {
+    Exited(ExitStatus),
+    Cancelled,
+    TimedOut,
+    Failed(String),
+}
+
+/// Outcome of a headless session
+pub(crate) struct HeadlessSession {
+    pub(crate) exit: SessionExit,
+    pub(crate) session_id: Option<String>,
+    /// Final text of the `result` message
+    pub(crate) result: Option<String>,
+    pub(crate) is_error: bool,
+    pub(crate) cost_usd: Option<f64>,
+    pub(crate) num_turns: Option<u64>,
+    pub(crate) stderr_tail: Vec<String>,
+}
+
+impl HeadlessSession {
+    fn failed(error: String) -> Self {
+        Self {
+            exit: SessionExit::Failed(error),
+            session_id: None,
+            result: None,
+            is_error: false,
+            cost_usd: None,
+            num_turns: None,
+            stderr_tail: Vec::new(),
+        }
+    }
+
+    pub(crate) fn succeeded(&self) -> bool {
+        matches!(&self.exit, SessionExit::Exited(status) if status.success()) && !self.is_error
+    }
+
+    /// Why the session did not succeed, None when it did
+    pub(crate) fn error(&self) -> Option<String> {
+        if self.succeeded() {
+            return None;
+        }
+        Some(match &self.exit {
+            SessionExit::Cancelled => "Cancelled".to_string(),
+            SessionExit::TimedOut => "Timed out".to_string(),
+            SessionExit::Failed(error) => error.clone(),
+            SessionExit::Exited(status) => {
+                if self.is_error {
+                    self.result
+                        .clone()
+                        .unwrap_or_else(|| "Claude reported an error".to_string())
+                } else if self.stderr_tail.is_empty() {
+                    format!("Claude exited with {}", status)
+                } else {
+                    self.stderr_tail.join("\n")
+                }
+            }
+        })
+    }
+}
+
+/// Stream outcome collected from stdout
+#[derive(Default)]
+struct StreamOutcome {
+    session_id: Option<String>,
+    registry_run_id: Option<i64>,
+    result: Option<String>,
+    is_error: bool,
+    cost_usd: Option<f64>,
+    num_turns: Option<u64>,
+}
+
+/// Run a new session to completion; `cancel` stops it early
+pub(crate) async fn run_session(
+    app: &AppHandle,
+    spec: &SessionSpec,
+    cancel: Arc<Notify>,
+    progress: ProgressFn,
+) -> HeadlessSession {
+    let project_path = spec.project_path.as_str();
+    if let Err(e) = super::network::ensure_online(app).await {
+        return HeadlessSession::failed(e);
+    }
+    let spawned = match super::claude::new_session_command(
+        app,
+        project_path,
+        &spec.prompt,
+        &spec.model,
+        spec.plan_mode,
+    )
+    .await
+    {
+        Ok(mut cmd) => {
+            cmd.envs(super::credentials::credential_env(app).await);
+            super::env_profiles::apply_active_profile(app, project_path, &mut cmd);
+            super::claude::spawn_with_retry(app, &mut cmd).await
+        }
+        Err(e) => Err(e),
+    };
+    let (mut child, audit) = match spawned {
+        Ok(spawned) => spawned,
+        Err(e) => return HeadlessSession::failed(e),
+    };
+
+    let pid = child.id().unwrap_or(0);
+    info!("Headless session started in {} (PID {})", project_path, pid);
+    progress(SessionProgress::Started { pid });
+
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+
+    let stdout_app = app.clone();
+    let stdout_progress = progress.clone();
+    let project = spec.project_path.clone();
+    let task = spec.prompt.clone();
+    let task_model = spec.model.clone();
+    let stdout_task = tauri::async_runtime::spawn(async move {
+        let mut outcome = StreamOutcome::default();
+        let stdout = match stdout {
+            Some(stdout) => stdout,
+            None => return outcome,
+        };
+        let mut lines = BufReader::new(stdout).lines();
+        let mut validator = StreamValidator::new(&stdout_app);
+        let mut records = VecDeque::new();
+        loop {
+            let line = match records.pop_front() {
+                Some(line) => line,
+                None => match lines.next_line().await {
+                    Ok(Some(raw)) => {
+                        records.extend(validator.push_line(&raw));
+                        continue;
+                    }
+                    _ => {
+                        validator.finish();
+                        break;
+                    }
+                },
+            };
+            stdout_progress(SessionProgress::Line(&line));
+            if let Some(session_id) = &outcome.session_id {
+                super::output_mirror::mirror_line(&stdout_app, session_id, &line);
+            }
+            let msg: serde_json::Value = match serde_json::from_str(&line) {
+                Ok(msg) => msg,
+                Err(_) => continue,
+            };
+            match (msg["type"].as_str(), msg["subtype"].as_str()) {
+                (Some("system"), Some("init")) if outcome.session_id.is_none() => {
+                    let session_id = match msg["session_id"].as_str() {
+                        Some(session_id) => session_id.to_string(),
+                        None => continue,
+                    };
+                    outcome.registry_run_id = stdout_app
+                        .state::<ProcessRegistryState>()
+                        .0
+                        .register_claude_session(
+                            session_id.clone(),
+                            pid,
+                            project.clone(),
+                            task.clone(),
+                            task_model.clone(),
+                        )
+                        .map_err(|e| warn!("Failed to register headless session: {}", e))
+                        .ok();
+                    super::cost_attribution::record_session_start(
+                        &stdout_app,
+                        &session_id,
+                        &project,
+                    );
+                    stdout_progress(SessionProgress::SessionId(&session_id));
+                    outcome.session_id = Some(session_id);
+                }
+                (Some("result"), _) => {
+                    outcome.result = msg["result"].as_str().map(str::to_string);
+                    outcome.is_error = msg["is_error"].as_bool().unwrap_or(false);
+                    outcome.cost_usd = msg["total_cost_usd"].as_f64();
+                    outcome.num_turns = msg["num_turns"].as_u64();
+                }
+                _ => {}
+            }
+        }
+        outcome
+    });
+    let stderr_task = tauri::async_runtime::spawn(async move {
+        let mut tail: Vec<String> = Vec::new();
+        if let Some(stderr) = stderr {
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                if tail.len() == STDERR_TAIL {
+                    tail.remove(0);
+                }
+                tail.push(line);
+            }
+        }
+        tail
+    });
+
+    let deadline = async {
+        match spec.timeout {
+            Some(timeout) => tokio::time::sleep(timeout).await,
+            None => std::future::pending().await,
+        }
+    };
+    let exit = tokio::select! {
+        status = child.wait() => match status {
+            Ok(status) => SessionExit::Exited(status),
+            Err(e) => SessionExit::Failed(format!("Failed to wait for Claude: {}", e)),
+        },
+        _ = cancel.notified() => SessionExit::Cancelled,
+        _ = deadline => SessionExit::TimedOut,
+    };
+    if matches!(exit, SessionExit::Cancelled | SessionExit::TimedOut) {
+        if let Err(e) = child.kill().await {
+            warn!("Failed to kill headless session (PID {}): {}", pid, e);
+        }
+    }
+    audit.finish(&child.wait().await);
+
+    let outcome = stdout_task.await.unwrap_or_default();
+    let stderr_tail = stderr_task.await.unwrap_or_default();
+    if let Some(registry_run_id) = outcome.registry_run_id {
+        let _ = app
+            .state::<ProcessRegistryState>()
+            .0
+            .unregister_process(registry_run_id);
+    }
+
+    HeadlessSession {
+        exit,
+        session_id: outcome.session_id,
+        result: outcome.result,
+        is_error: outcome.is_error,
+        cost_usd: outcome.cost_usd,
+        num_turns: outcome.num_turns,
+        stderr_tail,
+    }
+}
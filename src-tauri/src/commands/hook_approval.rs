@@ -0,0 +1,232 @@
+use log::{info, warn};
+/// Approval gate for project hooks
+///
+/// Project hooks come from `.claude/settings.json`, which arrives with whatever the
+/// user clones or pulls. Each hook is identified by a hash of its event and the
+/// fields that decide what it runs (command, action, matcher, files, sandbox,
+/// active condition and follow-up commands); a hook only runs once its hash has
+/// been approved for the project, so any new or edited hook waits for the user.
+/// The hashed fields are listed explicitly and versioned, so adding a field to
+/// hooks does not revoke approvals. Projects are keyed by canonical path.
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use tauri::{AppHandle, Manager, State};
+use uuid::Uuid;
+
+use super::enhanced_hooks::{is_enhanced_entry, EnhancedHook};
+use super::storage::AgentDb;
+use crate::events::{self, AppEvent, HooksPendingApproval};
+
+/// A project hook that has not been approved yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingHook {
+    pub event: String,
+    pub hash: String,
+    pub hook: EnhancedHook,
+}
+
+/// Version of the field set `hook_hash` covers; bump it when the set changes
+const HASH_VERSION: u32 = 1;
+
+/// Hash of everything about a hook that affects what it runs
+pub fn hook_hash(event: &str, hook: &EnhancedHook) -> String {
+    let condition = hook
+        .condition
+        .as_ref()
+        .filter(|c| c.enabled)
+        .map(|c| c.condition.as_str());
+    // Object keys serialize sorted, so the encoding is canonical
+    let identity = json!({
+        "version": HASH_VERSION,
+        "event": event,
+        "command": hook.command,
+        "action": hook.action,
+        "matcher": hook.matcher,
+        "files": hook.files,
+        "sandbox": hook.sandbox,
+        "condition": condition,
+        "on_success": hook.on_success,
+        "on_failure": hook.on_failure,
+    });
+
+    let mut hasher = Sha256::new();
+    hasher.update(identity.to_string().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Hash approvals were recorded under before `HASH_VERSION`: the whole hook
+/// without its id, enabled flag and priority
+fn legacy_hook_hash(event: &str, hook: &EnhancedHook) -> String {
+    let mut hook = hook.clone();
+    hook.id = Uuid::nil();
+    hook.enabled = true;
+    if let Some(condition) = hook.condition.as_mut() {
+        condition.priority = None;
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(event.as_bytes());
+    hasher.update([0]);
+    hasher.update(serde_json::to_string(&hook).unwrap_or_default().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Whether a hook's hash is among `approved`, also under the legacy hash
+pub fn is_approved(approved: &HashSet<String>, event: &str, hook: &EnhancedHook) -> bool {
+    approved.contains(&hook_hash(event, hook)) || approved.contains(&legacy_hook_hash(event, hook))
+}
+
+/// Key approvals are stored under, so one project reached through different
+/// paths (symlinks, trailing separators) shares its approvals
+fn project_key(project_path: &str) -> String {
+    std::fs::canonicalize(project_path)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| project_path.to_string())
+}
+
+/// Approved hashes of a project; rows stored before paths were canonical are
+/// found under the path as given
+pub fn approved_hashes(conn: &Connection, project_path: &str) -> Result<HashSet<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT hash FROM hook_approvals WHERE project_path IN (?1, ?2)")
+        .map_err(|e| e.to_string())?;
+    let hashes = stmt
+        .query_map(params![project_key(project_path), project_path], |row| {
+            row.get::<_, String>(0)
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<HashSet<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(hashes)
+}
+
+/// Mark hashes as approved for a project; returns how many were new
+pub fn record_approvals(
+    conn: &Connection,
+    project_path: &str,
+    hashes: &[String],
+) -> Result<usize, String> {
+    let approved_at = chrono::Utc::now().to_rfc3339();
+    let project_path = project_key(project_path);
+    let mut added = 0;
+    for hash in hashes {
+        added += conn
+            .execute(
+                "INSERT OR IGNORE INTO hook_approvals (project_path, hash, approved_at)
+                 VALUES (?1, ?2, ?3)",
+                params![project_path, hash, approved_at],
+            )
+            .map_err(|e| format!("Failed to approve hook: {}", e))?;
+    }
+    Ok(added)
+}
+
+/// Keep only approved hooks, telling the frontend about the rest. Fails closed:
+/// if approvals cannot be read, nothing runs.
+pub fn filter_approved(
+    app: &AppHandle,
+    event: &str,
+    project_path: &str,
+    hooks: Vec<EnhancedHook>,
+) -> Vec<EnhancedHook> {
+    if hooks.is_empty() {
+        return hooks;
+    }
+
+    let approved = match app.try_state::<AgentDb>() {
+        Some(db) => {
+            db.0.lock()
+                .map_err(|e| e.to_string())
+                .and_then(|conn| approved_hashes(&conn, project_path))
+        }
+        None => Err("Database not initialized".to_string()),
+    };
+    let approved = match approved {
+        Ok(approved) => approved,
+        Err(e) => {
+            warn!("Skipping {} hooks, approvals unavailable: {}", event, e);
+            return Vec::new();
+        }
+    };
+
+    let (allowed, pending): (Vec<_>, Vec<_>) = hooks
+        .into_iter()
+        .partition(|hook| is_approved(&approved, event, hook));
+
+    if !pending.is_empty() {
+        warn!(
+            "Skipping {} unapproved {} hooks in {}",
+            pending.len(),
+            event,
+            project_path
+        );
+        let pending: Vec<PendingHook> = pending
+            .into_iter()
+            .map(|hook| PendingHook {
+                event: event.to_string(),
+                hash: hook_hash(event, &hook),
+                hook,
+            })
+            .collect();
+        events::emit(
+            app,
+            AppEvent::HooksPendingApproval(HooksPendingApproval {
+                project_path: project_path.to_string(),
+                hooks: pending,
+            }),
+        );
+    }
+
+    allowed
+}
+
+// ============ Tauri Commands ============
+
+/// List the project's hooks that are waiting for approval
+#[tauri::command]
+pub async fn list_pending_hooks(
+    db: State<'_, AgentDb>,
+    project_path: String,
+) -> Result<Vec<PendingHook>, String> {
+    let file = super::settings_manager::read_settings_file("project", Some(&project_path))?;
+    let approved = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        approved_hashes(&conn, &project_path)?
+    };
+
+    let mut pending = Vec::new();
+    let events = file.settings.get("hooks").and_then(|h| h.as_object());
+    for (event, entries) in events.into_iter().flatten() {
+        let hooks = entries
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|e| is_enhanced_entry(e))
+            .filter_map(|e| serde_json::from_value::<EnhancedHook>(e.clone()).ok());
+        for hook in hooks {
+            if !is_approved(&approved, event, &hook) {
+                pending.push(PendingHook {
+                    event: event.clone(),
+                    hash: hook_hash(event, &hook),
+                    hook,
+                });
+            }
+        }
+    }
+    Ok(pending)
+}
+
+/// Approve hooks by hash so they are allowed to run in this project
+#[tauri::command]
+pub async fn approve_hooks(
+    db: State<'_, AgentDb>,
+    project_path: String,
+    hashes: Vec<String>,
+) -> Result<usize, String> {
+    info!("Approving {} hooks for {}", hashes.len(), project_path);
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    record_approvals(&conn, &project_path, &hashes)
+}
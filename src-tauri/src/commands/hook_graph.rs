@@ -0,0 +1,288 @@
+/// Hook chains as graphs
+///
+/// `get_hook_chain_graph` describes the hooks of one event as nodes and edges
+/// the frontend can lay out as a flow diagram. The chain starts at an `event`
+/// node; enabled hooks follow in the order they run (priority first, then
+/// configured order) joined by `next` edges, and the edge into a hook with a
+/// condition is `conditional` and carries the expression. Each hook's
+/// `on_success` and `on_failure` commands hang off it as branch nodes. For
+/// events whose failing hooks block the operation (`PreToolUse`,
+/// `OnUserCommand`) failures also lead to a `blocked` node. Disabled hooks are
+/// listed without edges, so they can be shown greyed out and re-enabled.
+///
+/// Node ids of hooks are the hook ids, so edits made in the diagram go through
+/// the existing commands (`update_enhanced_hook`, `reorder_hooks`, ...).
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tauri::State;
+
+use super::enhanced_hooks::{list_enhanced_hooks, EnhancedHook};
+use super::hook_approval::{approved_hashes, is_approved};
+use super::storage::AgentDb;
+use crate::error::WorkbenchError;
+
+/// Events where a failing hook stops the operation
+const BLOCKING_EVENTS: &[&str] = &["PreToolUse", "OnUserCommand"];
+
+const EVENT_NODE: &str = "event";
+const BLOCKED_NODE: &str = "blocked";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphNode {
+    pub id: String,
+    /// `event`, `hook`, `on_success`, `on_failure` or `blocked`
+    pub kind: String,
+    pub label: String,
+    /// Column for a left-to-right layout: one per enabled hook after the event
+    /// at 0; disabled hooks are at 0 as well
+    pub rank: usize,
+    /// Hook the node belongs to, for branch nodes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hook_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hook: Option<HookNodeData>,
+}
+
+/// What the diagram shows and edits on a hook node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookNodeData {
+    pub enabled: bool,
+    pub priority: i32,
+    /// Position in the run order, None for disabled hooks
+    pub order: Option<usize>,
+    pub condition: Option<String>,
+    pub condition_enabled: bool,
+    pub matcher: Option<String>,
+    pub files: Option<Vec<String>>,
+    /// `command`, or the built-in action type
+    pub action: String,
+    pub sandboxed: bool,
+    pub throttled: bool,
+    pub terminal: bool,
+    pub timeout: Option<u64>,
+    pub preset: Option<String>,
+    /// Whether the hook is approved to run in the project; None for user and
+    /// local hooks, which need no approval
+    pub approved: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub id: String,
+    pub source: String,
+    pub target: String,
+    /// `next`, `conditional`, `on_success`, `on_failure` or `blocks`
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookChainGraph {
+    pub event: String,
+    pub scope: String,
+    pub project_path: Option<String>,
+    /// Whether a failing hook blocks the operation
+    pub blocking: bool,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+fn hook_label(hook: &EnhancedHook) -> String {
+    if !hook.command.trim().is_empty() {
+        return hook.command.clone();
+    }
+    match hook
+        .action
+        .as_ref()
+        .and_then(|a| serde_json::to_value(a).ok())
+    {
+        Some(action) => action
+            .get("type")
+            .and_then(|t| t.as_str())
+            .unwrap_or("action")
+            .to_string(),
+        None => "(empty)".to_string(),
+    }
+}
+
+fn active_condition(hook: &EnhancedHook) -> Option<&str> {
+    hook.condition
+        .as_ref()
+        .filter(|c| c.enabled && !c.condition.trim().is_empty())
+        .map(|c| c.condition.as_str())
+}
+
+fn edge(source: &str, target: &str, kind: &str, label: Option<String>) -> GraphEdge {
+    GraphEdge {
+        id: format!("{}->{}:{}", source, target, kind),
+        source: source.to_string(),
+        target: target.to_string(),
+        kind: kind.to_string(),
+        label,
+    }
+}
+
+/// Branch nodes for a hook's follow-up commands, chained in the order they run
+fn branch(
+    graph: &mut HookChainGraph,
+    hook_id: &str,
+    kind: &str,
+    rank: usize,
+    commands: Option<&Vec<String>>,
+) {
+    let mut previous = hook_id.to_string();
+    for (i, command) in commands.into_iter().flatten().enumerate() {
+        let id = format!("{}:{}:{}", hook_id, kind, i);
+        graph.nodes.push(GraphNode {
+            id: id.clone(),
+            kind: kind.to_string(),
+            label: command.clone(),
+            rank,
+            hook_id: Some(hook_id.to_string()),
+            hook: None,
+        });
+        let edge_kind = if i == 0 { kind } else { "next" };
+        graph.edges.push(edge(&previous, &id, edge_kind, None));
+        previous = id;
+    }
+}
+
+fn build_graph(
+    event: String,
+    scope: String,
+    project_path: Option<String>,
+    hooks: Vec<EnhancedHook>,
+    approved: Option<&HashSet<String>>,
+) -> HookChainGraph {
+    let blocking = BLOCKING_EVENTS.contains(&event.as_str());
+    let mut graph = HookChainGraph {
+        nodes: vec![GraphNode {
+            id: EVENT_NODE.to_string(),
+            kind: "event".to_string(),
+            label: event.clone(),
+            rank: 0,
+            hook_id: None,
+            hook: None,
+        }],
+        edges: Vec::new(),
+        event,
+        scope,
+        project_path,
+        blocking,
+    };
+
+    // Same order as execute_hook_chain: higher priority first, stable for ties
+    let (mut enabled, disabled): (Vec<EnhancedHook>, Vec<EnhancedHook>) =
+        hooks.into_iter().partition(|h| h.enabled);
+    enabled.sort_by_key(|h| std::cmp::Reverse(h.priority()));
+
+    let mut previous = EVENT_NODE.to_string();
+    let mut failures = Vec::new();
+    let ordered = enabled.iter().enumerate().map(|(i, h)| (Some(i), h));
+    let skipped = disabled.iter().map(|h| (None, h));
+    for (order, hook) in ordered.chain(skipped) {
+        let id = hook.id.to_string();
+        let rank = order.map_or(0, |i| i + 1);
+        graph.nodes.push(GraphNode {
+            id: id.clone(),
+            kind: "hook".to_string(),
+            label: hook_label(hook),
+            rank,
+            hook_id: None,
+            hook: Some(HookNodeData {
+                enabled: hook.enabled,
+                priority: hook.priority(),
+                order,
+                condition: hook.condition.as_ref().map(|c| c.condition.clone()),
+                condition_enabled: hook.condition.as_ref().is_some_and(|c| c.enabled),
+                matcher: hook.matcher.clone(),
+                files: hook.files.clone(),
+                action: if hook.command.trim().is_empty() && hook.action.is_some() {
+                    hook_label(hook)
+                } else {
+                    "command".to_string()
+                },
+                sandboxed: hook.sandbox.is_some(),
+                throttled: hook.is_throttled(),
+                terminal: hook.terminal,
+                timeout: hook.timeout,
+                preset: hook.preset.clone(),
+                approved: approved.map(|a| is_approved(a, &graph.event, hook)),
+            }),
+        });
+        if order.is_none() {
+            continue;
+        }
+
+        let condition = active_condition(hook).map(str::to_string);
+        let kind = if condition.is_some() {
+            "conditional"
+        } else {
+            "next"
+        };
+        graph.edges.push(edge(&previous, &id, kind, condition));
+        branch(
+            &mut graph,
+            &id,
+            "on_success",
+            rank,
+            hook.on_success.as_ref(),
+        );
+        branch(
+            &mut graph,
+            &id,
+            "on_failure",
+            rank,
+            hook.on_failure.as_ref(),
+        );
+        if blocking {
+            failures.push(id.clone());
+        }
+        previous = id;
+    }
+
+    if !failures.is_empty() {
+        graph.nodes.push(GraphNode {
+            id: BLOCKED_NODE.to_string(),
+            kind: "blocked".to_string(),
+            label: "Operation blocked".to_string(),
+            rank: enabled.len() + 1,
+            hook_id: None,
+            hook: None,
+        });
+        for id in failures {
+            graph.edges.push(edge(
+                &id,
+                BLOCKED_NODE,
+                "blocks",
+                Some("failure".to_string()),
+            ));
+        }
+    }
+    graph
+}
+
+// ============ Tauri Commands ============
+
+/// The hooks of an event as a graph; `scope` defaults to the project's
+/// settings, which are the hooks the chain runs
+#[tauri::command]
+pub async fn get_hook_chain_graph(
+    db: State<'_, AgentDb>,
+    event: String,
+    project: Option<String>,
+    scope: Option<String>,
+) -> Result<HookChainGraph, WorkbenchError> {
+    let scope = scope.unwrap_or_else(|| "project".to_string());
+    let hooks = list_enhanced_hooks(event.clone(), scope.clone(), project.clone()).await?;
+    // Only project hooks are gated by approval
+    let approved = match (scope.as_str(), project.as_deref()) {
+        ("project", Some(project)) => {
+            let conn = db.0.lock().map_err(|e| e.to_string())?;
+            Some(approved_hashes(&conn, project)?)
+        }
+        _ => None,
+    };
+    Ok(build_graph(event, scope, project, hooks, approved.as_ref()))
+}
@@ -0,0 +1,218 @@
+/// Hook execution metrics
+///
+/// In-memory counters and latency histograms per hook, reset when the app
+/// restarts. `get_hook_metrics` returns them as JSON; with `metrics` enabled in
+/// the control API settings they are also served in the Prometheus text format
+/// at `/metrics`, for graphing failure rates and slow hooks.
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use super::enhanced_hooks::EnhancedHook;
+
+/// Upper bounds of the latency histogram buckets, in milliseconds
+pub const LATENCY_BUCKETS_MS: &[u64] =
+    &[10, 50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 30_000];
+
+/// Commands are cut to this many characters in labels
+const COMMAND_LABEL_LEN: usize = 80;
+
+/// How a hook run ended
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HookOutcome {
+    Success,
+    /// The command ran and exited unsuccessfully or timed out
+    Failure,
+    /// The hook could not be run at all
+    Error,
+    /// The condition or matcher did not apply
+    Skipped,
+}
+
+/// Metrics of one hook
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HookMetric {
+    pub event: String,
+    pub hook_id: String,
+    pub command: String,
+    pub preset: Option<String>,
+    /// Executions that ran the hook, successful or not
+    pub runs: u64,
+    pub failures: u64,
+    pub errors: u64,
+    pub skipped: u64,
+    /// Cumulative counts per bucket in `LATENCY_BUCKETS_MS`
+    pub latency_buckets: Vec<u64>,
+    pub latency_sum_ms: u64,
+    pub last_run_at: Option<String>,
+}
+
+/// Since launch, keyed by event and hook id
+#[derive(Default)]
+pub struct HookMetricsState(Mutex<BTreeMap<(String, String), HookMetric>>);
+
+/// Count one execution of `hook`
+pub fn observe(
+    app: &AppHandle,
+    event: &str,
+    hook: &EnhancedHook,
+    outcome: HookOutcome,
+    duration_ms: u64,
+) {
+    let state = match app.try_state::<HookMetricsState>() {
+        Some(state) => state,
+        None => return,
+    };
+    let mut metrics = match state.0.lock() {
+        Ok(metrics) => metrics,
+        Err(_) => return,
+    };
+    let metric = metrics
+        .entry((event.to_string(), hook.id.to_string()))
+        .or_insert_with(|| HookMetric {
+            event: event.to_string(),
+            hook_id: hook.id.to_string(),
+            command: hook.command.chars().take(COMMAND_LABEL_LEN).collect(),
+            preset: hook.preset.clone(),
+            latency_buckets: vec![0; LATENCY_BUCKETS_MS.len()],
+            ..Default::default()
+        });
+
+    match outcome {
+        HookOutcome::Skipped => {
+            metric.skipped += 1;
+            return;
+        }
+        HookOutcome::Error => {
+            metric.errors += 1;
+            return;
+        }
+        HookOutcome::Failure => metric.failures += 1,
+        HookOutcome::Success => {}
+    }
+    metric.runs += 1;
+    for (count, bound) in metric.latency_buckets.iter_mut().zip(LATENCY_BUCKETS_MS) {
+        if duration_ms <= *bound {
+            *count += 1;
+        }
+    }
+    metric.latency_sum_ms += duration_ms;
+    metric.last_run_at = Some(chrono::Utc::now().to_rfc3339());
+}
+
+fn snapshot(state: &HookMetricsState) -> Result<Vec<HookMetric>, String> {
+    Ok(state
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .values()
+        .cloned()
+        .collect())
+}
+
+/// Escape a Prometheus label value
+fn label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Counter name, help text and value
+type Counter = (&'static str, &'static str, fn(&HookMetric) -> u64);
+
+/// All metrics in the Prometheus text exposition format
+pub fn render_prometheus(app: &AppHandle) -> Result<String, String> {
+    let metrics = match app.try_state::<HookMetricsState>() {
+        Some(state) => snapshot(&state)?,
+        None => Vec::new(),
+    };
+    let mut out = String::new();
+    let labels = |m: &HookMetric| {
+        format!(
+            "event=\"{}\",hook_id=\"{}\",command=\"{}\",preset=\"{}\"",
+            label(&m.event),
+            m.hook_id,
+            label(&m.command),
+            label(m.preset.as_deref().unwrap_or_default())
+        )
+    };
+
+    let counters: [Counter; 4] = [
+        ("runs", "Hook executions, successful or not", |m| m.runs),
+        ("failures", "Hook executions that failed", |m| m.failures),
+        ("errors", "Hooks that could not be executed", |m| m.errors),
+        (
+            "skipped",
+            "Hooks skipped by their condition or matcher",
+            |m| m.skipped,
+        ),
+    ];
+    for (name, help, value) in counters {
+        let _ = writeln!(out, "# HELP workbench_hook_{}_total {}", name, help);
+        let _ = writeln!(out, "# TYPE workbench_hook_{}_total counter", name);
+        for metric in &metrics {
+            let _ = writeln!(
+                out,
+                "workbench_hook_{}_total{{{}}} {}",
+                name,
+                labels(metric),
+                value(metric)
+            );
+        }
+    }
+
+    let _ = writeln!(
+        out,
+        "# HELP workbench_hook_duration_seconds Hook execution time"
+    );
+    let _ = writeln!(out, "# TYPE workbench_hook_duration_seconds histogram");
+    for metric in &metrics {
+        let labels = labels(metric);
+        for (count, bound) in metric.latency_buckets.iter().zip(LATENCY_BUCKETS_MS) {
+            let _ = writeln!(
+                out,
+                "workbench_hook_duration_seconds_bucket{{{},le=\"{}\"}} {}",
+                labels,
+                *bound as f64 / 1000.0,
+                count
+            );
+        }
+        let _ = writeln!(
+            out,
+            "workbench_hook_duration_seconds_bucket{{{},le=\"+Inf\"}} {}",
+            labels, metric.runs
+        );
+        let _ = writeln!(
+            out,
+            "workbench_hook_duration_seconds_sum{{{}}} {}",
+            labels,
+            metric.latency_sum_ms as f64 / 1000.0
+        );
+        let _ = writeln!(
+            out,
+            "workbench_hook_duration_seconds_count{{{}}} {}",
+            labels, metric.runs
+        );
+    }
+    Ok(out)
+}
+
+// ============ Tauri Commands ============
+
+/// Counters and latency histograms of every hook run since launch
+#[tauri::command]
+pub async fn get_hook_metrics(
+    state: State<'_, HookMetricsState>,
+) -> Result<Vec<HookMetric>, String> {
+    snapshot(&state)
+}
+
+/// Start counting from zero
+#[tauri::command]
+pub async fn reset_hook_metrics(state: State<'_, HookMetricsState>) -> Result<(), String> {
+    state.0.lock().map_err(|e| e.to_string())?.clear();
+    Ok(())
+}
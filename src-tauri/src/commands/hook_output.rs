@@ -0,0 +1,281 @@
+use log::warn;
+/// Size limits for hook output
+///
+/// Hook results keep at most `max_output_bytes` (per hook, 64 KiB by default) of
+/// each stream in memory. Once a stream goes over the limit, everything it
+/// printed is written to a uniquely named file under `<app cache dir>/hook-output`
+/// instead, the result carries that path, and `get_hook_output` reads it back in
+/// chunks. Run ids restart with every launch, so files are looked up through the
+/// paths recorded for runs of this launch only. Files older than a day are
+/// removed when new ones are created.
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tauri::AppHandle;
+use uuid::Uuid;
+
+use crate::error::{WorkbenchError, WorkbenchResult};
+use crate::paths;
+
+/// In-memory output per stream when a hook sets no limit
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 64 * 1024;
+/// Largest chunk `get_hook_output` returns at once
+const MAX_CHUNK_BYTES: u64 = 4 * 1024 * 1024;
+/// Spilled output is kept this long
+const SPILL_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Output of one stream of a hook run, cut to the hook's limit
+#[derive(Debug, Clone, Default)]
+pub struct CapturedOutput {
+    pub text: String,
+    pub truncated: bool,
+    /// Full output, if it went over the limit and could be saved
+    pub file: Option<PathBuf>,
+}
+
+impl CapturedOutput {
+    /// Text with a note pointing at the full output when it was cut
+    pub fn display_text(&self) -> String {
+        if !self.truncated {
+            return self.text.clone();
+        }
+        let note = match &self.file {
+            Some(path) => format!("[output truncated; full output in {}]", path.display()),
+            None => "[output truncated]".to_string(),
+        };
+        format!("{}\n{}", self.text.trim_end(), note)
+    }
+
+    pub fn file_string(&self) -> Option<String> {
+        self.file
+            .as_ref()
+            .map(|path| path.to_string_lossy().to_string())
+    }
+}
+
+/// Spill files of this launch's runs, by run id and stream
+static SPILLS: Lazy<Mutex<HashMap<(i64, String), PathBuf>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn spill_dir(app: &AppHandle) -> Option<PathBuf> {
+    match paths::app_cache_dir(app) {
+        Ok(dir) => Some(dir.join("hook-output")),
+        Err(e) => {
+            warn!("Failed to resolve the app cache dir: {}", e);
+            None
+        }
+    }
+}
+
+fn record_spill(run_id: i64, stream: &str, path: PathBuf) {
+    if let Ok(mut spills) = SPILLS.lock() {
+        spills.insert((run_id, stream.to_string()), path);
+    }
+}
+
+fn forget_spill(run_id: i64, stream: &str) {
+    if let Ok(mut spills) = SPILLS.lock() {
+        spills.remove(&(run_id, stream.to_string()));
+    }
+}
+
+fn recorded_spill(run_id: i64, stream: &str) -> Option<PathBuf> {
+    SPILLS
+        .lock()
+        .ok()?
+        .get(&(run_id, stream.to_string()))
+        .cloned()
+}
+
+/// Remove spilled output older than `SPILL_MAX_AGE`
+fn prune_spill_dir(dir: &Path) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    let now = SystemTime::now();
+    for entry in entries.flatten() {
+        let expired = entry
+            .metadata()
+            .and_then(|meta| meta.modified())
+            .map(|modified| now.duration_since(modified).unwrap_or_default() > SPILL_MAX_AGE)
+            .unwrap_or(false);
+        if expired {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+    if let Ok(mut spills) = SPILLS.lock() {
+        spills.retain(|_, path| path.exists());
+    }
+}
+
+/// Collects a stream up to a limit and spills all of it to disk past that
+pub struct OutputSink {
+    limit: usize,
+    /// Run, stream and directory to spill to
+    spill: Option<(i64, String, PathBuf)>,
+    captured: CapturedOutput,
+    file: Option<File>,
+}
+
+impl OutputSink {
+    /// Without a run id there is nothing to look the file up by, so output past
+    /// the limit is dropped
+    pub fn new(app: &AppHandle, limit: usize, run_id: Option<i64>, stream: &str) -> Self {
+        let spill =
+            run_id.and_then(|run_id| spill_dir(app).map(|dir| (run_id, stream.to_string(), dir)));
+        Self {
+            limit,
+            spill,
+            captured: CapturedOutput::default(),
+            file: None,
+        }
+    }
+
+    pub fn push_line(&mut self, line: &str) {
+        if !self.captured.truncated {
+            if self.captured.text.len() + line.len() < self.limit {
+                self.captured.text.push_str(line);
+                self.captured.text.push('\n');
+                return;
+            }
+            self.captured.truncated = true;
+            self.file = self.open_spill();
+        }
+        if let Some(file) = self.file.as_mut() {
+            if let Err(e) = writeln!(file, "{}", line) {
+                warn!("Failed to save hook output: {}", e);
+                self.file = None;
+                self.captured.file = None;
+                if let Some((run_id, stream, _)) = &self.spill {
+                    forget_spill(*run_id, stream);
+                }
+            }
+        }
+    }
+
+    /// Start the spill file with what was kept in memory so far
+    fn open_spill(&mut self) -> Option<File> {
+        let (run_id, stream, dir) = self.spill.clone()?;
+        let path = dir.join(format!("{}.log", Uuid::new_v4()));
+        let opened = fs::create_dir_all(&dir).and_then(|_| {
+            prune_spill_dir(&dir);
+            let mut file = File::options().write(true).create_new(true).open(&path)?;
+            file.write_all(self.captured.text.as_bytes())?;
+            Ok(file)
+        });
+        match opened {
+            Ok(file) => {
+                record_spill(run_id, &stream, path.clone());
+                self.captured.file = Some(path);
+                Some(file)
+            }
+            Err(e) => {
+                warn!("Failed to create {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    pub fn finish(self) -> CapturedOutput {
+        self.captured
+    }
+}
+
+/// Apply the limit to output that was collected in one piece
+pub fn capture_text(
+    app: &AppHandle,
+    text: &str,
+    limit: usize,
+    run_id: Option<i64>,
+    stream: &str,
+) -> CapturedOutput {
+    let mut sink = OutputSink::new(app, limit, run_id, stream);
+    for line in text.lines() {
+        sink.push_line(line);
+    }
+    sink.finish()
+}
+
+/// A piece of spilled hook output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookOutputChunk {
+    pub run_id: i64,
+    pub stream: String,
+    pub path: String,
+    pub offset: u64,
+    pub total_size: u64,
+    pub content: String,
+    /// Whether the chunk reaches the end of the file
+    pub eof: bool,
+}
+
+fn read_chunk(
+    run_id: i64,
+    stream: &str,
+    offset: u64,
+    max_bytes: u64,
+) -> WorkbenchResult<HookOutputChunk> {
+    let not_found =
+        || WorkbenchError::NotFound(format!("No saved {} for hook run {}", stream, run_id));
+    let path = recorded_spill(run_id, stream).ok_or_else(not_found)?;
+    let mut file = match File::open(&path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            forget_spill(run_id, stream);
+            return Err(not_found());
+        }
+        Err(e) => return Err(WorkbenchError::io("Failed to open hook output", e)),
+    };
+    let total_size = file
+        .metadata()
+        .map_err(|e| WorkbenchError::io("Failed to read hook output", e))?
+        .len();
+    let offset = offset.min(total_size);
+    file.seek(SeekFrom::Start(offset))
+        .map_err(|e| WorkbenchError::io("Failed to read hook output", e))?;
+    let mut bytes = Vec::new();
+    file.take(max_bytes)
+        .read_to_end(&mut bytes)
+        .map_err(|e| WorkbenchError::io("Failed to read hook output", e))?;
+    Ok(HookOutputChunk {
+        run_id,
+        stream: stream.to_string(),
+        path: path.to_string_lossy().to_string(),
+        offset,
+        total_size,
+        eof: offset + bytes.len() as u64 >= total_size,
+        content: String::from_utf8_lossy(&bytes).to_string(),
+    })
+}
+
+/// Full output of a hook run that went over its output limit
+#[tauri::command]
+pub async fn get_hook_output(
+    run_id: i64,
+    stream: Option<String>,
+    offset: Option<u64>,
+    max_bytes: Option<u64>,
+) -> Result<HookOutputChunk, WorkbenchError> {
+    let stream = stream.unwrap_or_else(|| "stdout".to_string());
+    if stream != "stdout" && stream != "stderr" {
+        return Err(WorkbenchError::InvalidInput(format!(
+            "Unknown output stream '{}'",
+            stream
+        )));
+    }
+    let max_bytes = max_bytes
+        .unwrap_or(MAX_CHUNK_BYTES)
+        .clamp(1, MAX_CHUNK_BYTES);
+    tauri::async_runtime::spawn_blocking(move || {
+        read_chunk(run_id, &stream, offset.unwrap_or(0), max_bytes)
+    })
+    .await
+    .unwrap_or_else(|_| Err(WorkbenchError::Dropped("Reading hook output".to_string())))
+}
@@ -0,0 +1,250 @@
+use log::info;
+/// Built-in hook recipes
+///
+/// Curated, versioned hook configurations that can be installed with one call
+/// instead of hand-writing settings JSON. Installed hooks remember their preset as
+/// `id@version`, so installing a newer version replaces the old entry in place.
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use super::enhanced_hooks::{
+    install_enhanced_hook, installed_preset_states, set_preset_hooks_enabled, EnhancedHook,
+};
+use super::storage::AgentDb;
+
+/// A hook recipe shipped with the workbench
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookPreset {
+    pub id: String,
+    /// Bumped whenever the recipe changes
+    pub version: u32,
+    pub name: String,
+    pub description: String,
+    /// Event the hook is installed under
+    pub event: String,
+    /// Tools the hook relies on being installed
+    pub requires: Vec<String>,
+    pub hook: EnhancedHook,
+}
+
+/// A preset installed in a settings scope
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstalledPreset {
+    pub id: String,
+    pub name: String,
+    /// Whether any of its hooks is enabled
+    pub enabled: bool,
+}
+
+/// Build a preset from its hook config; the JSON is fixed, so parsing cannot fail
+fn preset(
+    id: &str,
+    version: u32,
+    name: &str,
+    description: &str,
+    event: &str,
+    requires: &[&str],
+    hook: serde_json::Value,
+) -> HookPreset {
+    let mut hook: EnhancedHook =
+        serde_json::from_value(hook).expect("built-in hook preset is valid");
+    hook.preset = Some(format!("{}@{}", id, version));
+
+    HookPreset {
+        id: id.to_string(),
+        version,
+        name: name.to_string(),
+        description: description.to_string(),
+        event: event.to_string(),
+        requires: requires.iter().map(|s| s.to_string()).collect(),
+        hook,
+    }
+}
+
+/// All built-in presets
+fn builtin_presets() -> Vec<HookPreset> {
+    vec![
+        preset(
+            "rustfmt-on-edit",
+            1,
+            "Format Rust on edit",
+            "Runs cargo fmt after Claude edits or writes a Rust file.",
+            "PostToolUse",
+            &["cargo"],
+            serde_json::json!({
+                "command": "cd \"$PROJECT_PATH\" && cargo fmt --all",
+                "matcher": "Edit|MultiEdit|Write",
+                "files": ["*.rs"],
+                "timeout": 60,
+            }),
+        ),
+        preset(
+            "eslint-gate",
+            1,
+            "ESLint commit gate",
+            "Blocks git commits made by Claude while ESLint reports errors.",
+            "PreToolUse",
+            &["npx", "eslint"],
+            serde_json::json!({
+                "command": "cd \"$PROJECT_PATH\" && npx --no-install eslint . --max-warnings=0",
+                "matcher": "Bash(git commit*)",
+                "timeout": 120,
+            }),
+        ),
+        preset(
+            "notify-on-stop",
+            1,
+            "Notify when Claude stops",
+            "Shows a desktop notification when a session finishes responding.",
+            "Stop",
+            &[],
+            serde_json::json!({
+                "action": {
+                    "type": "notify",
+                    "title": "Claude finished",
+                    "body": "Session {session_id} in {project_path} is waiting for you",
+                    "urgency": "normal",
+                },
+            }),
+        ),
+        preset(
+            "announce-on-stop",
+            1,
+            "Announce when Claude stops",
+            "Says out loud that a session finished, for when you are in another window.",
+            "Stop",
+            &[],
+            serde_json::json!({
+                "action": {
+                    "type": "speak",
+                    "text": "Claude finished and is waiting for you",
+                },
+            }),
+        ),
+        preset(
+            "sound-on-notification",
+            1,
+            "Sound when Claude needs you",
+            "Plays the attention sound when Claude asks for permission or input.",
+            "Notification",
+            &[],
+            serde_json::json!({
+                "action": {
+                    "type": "play_sound",
+                    "name": "attention",
+                },
+            }),
+        ),
+        preset(
+            "auto-commit-checkpoint",
+            1,
+            "Auto-commit checkpoints",
+            "Commits all changes as a checkpoint whenever Claude stops, so every turn \
+             can be reverted with git.",
+            "Stop",
+            &["git"],
+            serde_json::json!({
+                "command": "cd \"$PROJECT_PATH\" && git add -A && \
+                            (git diff --cached --quiet || \
+                            git commit -q -m \"checkpoint: $(date -u +%Y-%m-%dT%H:%M:%SZ)\")",
+                "timeout": 60,
+            }),
+        ),
+    ]
+}
+
+/// Presets installed in a scope, named after the built-in recipe when it still exists
+pub(crate) fn installed_presets(
+    scope: &str,
+    project_path: Option<&str>,
+) -> Result<Vec<InstalledPreset>, String> {
+    let builtin = builtin_presets();
+    Ok(installed_preset_states(scope, project_path)?
+        .into_iter()
+        .map(|(id, enabled)| InstalledPreset {
+            name: builtin
+                .iter()
+                .find(|p| p.id == id)
+                .map(|p| p.name.clone())
+                .unwrap_or_else(|| id.clone()),
+            id,
+            enabled,
+        })
+        .collect())
+}
+
+/// Whether a built-in preset with this id exists
+pub(crate) fn preset_exists(id: &str) -> bool {
+    builtin_presets().iter().any(|p| p.id == id)
+}
+
+/// Install a built-in preset into a settings scope, replacing an earlier install
+pub(crate) fn install_preset(
+    db: &AgentDb,
+    id: &str,
+    scope: &str,
+    project_path: Option<&str>,
+) -> Result<EnhancedHook, String> {
+    let preset = builtin_presets()
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| format!("Unknown hook preset: {}", id))?;
+
+    info!(
+        "Installing hook preset {}@{} into {} settings",
+        preset.id, preset.version, scope
+    );
+    Ok(install_enhanced_hook(
+        db,
+        &preset.event,
+        preset.hook,
+        scope,
+        project_path,
+    )?)
+}
+
+// ============ Tauri Commands ============
+
+/// List the built-in hook presets
+#[tauri::command]
+pub async fn list_hook_presets() -> Result<Vec<HookPreset>, String> {
+    Ok(builtin_presets())
+}
+
+/// Install a preset into the given settings scope, replacing an earlier install
+#[tauri::command]
+pub async fn install_hook_preset(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    id: String,
+    scope: String,
+    project_path: Option<String>,
+) -> Result<EnhancedHook, String> {
+    let hook = install_preset(&db, &id, &scope, project_path.as_deref())?;
+    super::telemetry::record(&app, "hooks.preset_install");
+    super::tray::refresh_tray(&app);
+    Ok(hook)
+}
+
+/// List the presets installed in a settings scope
+#[tauri::command]
+pub async fn list_installed_hook_presets(
+    scope: String,
+    project_path: Option<String>,
+) -> Result<Vec<InstalledPreset>, String> {
+    installed_presets(&scope, project_path.as_deref())
+}
+
+/// Turn all hooks installed from a preset on or off; returns how many changed
+#[tauri::command]
+pub async fn set_hook_preset_enabled(
+    app: AppHandle,
+    id: String,
+    enabled: bool,
+    scope: String,
+    project_path: Option<String>,
+) -> Result<usize, String> {
+    let changed = set_preset_hooks_enabled(&scope, project_path.as_deref(), &id, enabled)?;
+    super::tray::refresh_tray(&app);
+    Ok(changed)
+}
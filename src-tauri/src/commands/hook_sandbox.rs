@@ -0,0 +1,233 @@
+use log::debug;
+/// Opt-in sandboxing for hook processes
+///
+/// Sandboxed hooks run with a minimal environment and optional CPU / memory limits.
+/// On Linux the filesystem is mounted read-only and networking is cut with bubblewrap
+/// (`bwrap`); on macOS the same is done with a `sandbox-exec` profile. Windows only
+/// enforces the resource limits, through a Job Object. A sandbox that cannot be
+/// enforced refuses to run rather than falling back to full access.
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+use crate::process::JobObject;
+
+/// Variables passed through to sandboxed hooks in addition to `allow_env`
+const BASE_ENV: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LANG",
+    "LC_ALL",
+    "TERM",
+    "TMPDIR",
+    "SYSTEMROOT",
+    "TEMP",
+    "TMP",
+];
+
+/// Restrictions applied to a hook's process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookSandbox {
+    /// Make the project directory read-only
+    #[serde(default = "default_true")]
+    pub read_only_project: bool,
+    /// Block all network access
+    #[serde(default = "default_true")]
+    pub no_network: bool,
+    /// Extra environment variables to pass through
+    #[serde(default)]
+    pub allow_env: Vec<String>,
+    /// CPU time limit in seconds
+    #[serde(default)]
+    pub cpu_seconds: Option<u64>,
+    /// Memory limit in megabytes
+    #[serde(default)]
+    pub memory_mb: Option<u64>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Which restrictions can be enforced on this machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SandboxSupport {
+    /// Tool used for filesystem / network isolation ("bwrap" or "sandbox-exec")
+    pub backend: Option<String>,
+    pub read_only_project: bool,
+    pub no_network: bool,
+    pub resource_limits: bool,
+}
+
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(program))
+        .find(|path| path.is_file())
+}
+
+/// Isolation tool available on this platform
+fn isolation_backend() -> Option<PathBuf> {
+    if cfg!(target_os = "linux") {
+        find_in_path("bwrap")
+    } else if cfg!(target_os = "macos") {
+        find_in_path("sandbox-exec")
+    } else {
+        None
+    }
+}
+
+/// Report which sandbox options this machine supports
+pub fn sandbox_support() -> SandboxSupport {
+    let backend = isolation_backend();
+    SandboxSupport {
+        backend: backend
+            .as_ref()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string()),
+        read_only_project: backend.is_some(),
+        no_network: backend.is_some(),
+        resource_limits: true,
+    }
+}
+
+/// `ulimit` calls prepended to the script; Windows uses a Job Object instead
+fn ulimit_prefix(sandbox: &HookSandbox) -> String {
+    let mut prefix = String::new();
+    if let Some(seconds) = sandbox.cpu_seconds {
+        prefix.push_str(&format!("ulimit -t {} || exit 126; ", seconds));
+    }
+    if let Some(mb) = sandbox.memory_mb {
+        prefix.push_str(&format!("ulimit -v {} || exit 126; ", mb * 1024));
+    }
+    prefix
+}
+
+/// bubblewrap: read-only root, private /tmp, optionally a writable project and no network
+fn bwrap_command(bwrap: &Path, script: &str, project_path: &str, sandbox: &HookSandbox) -> Command {
+    let mut cmd = Command::new(bwrap);
+    cmd.args(["--ro-bind", "/", "/"])
+        .args(["--dev", "/dev"])
+        .args(["--proc", "/proc"])
+        .args(["--tmpfs", "/tmp"])
+        .arg("--die-with-parent");
+    if !sandbox.read_only_project {
+        cmd.arg("--bind").arg(project_path).arg(project_path);
+    }
+    if sandbox.no_network {
+        cmd.arg("--unshare-net");
+    }
+    cmd.args(["--", "bash", "-c", script]);
+    cmd
+}
+
+/// sandbox-exec: allow everything except what the sandbox denies
+fn seatbelt_command(
+    sandbox_exec: &Path,
+    script: &str,
+    project_path: &str,
+    sandbox: &HookSandbox,
+) -> Command {
+    // Seatbelt matches resolved paths (e.g. /private/var rather than /var)
+    let project = std::fs::canonicalize(project_path)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| project_path.to_string());
+
+    let mut profile = String::from("(version 1)\n(allow default)\n");
+    if sandbox.no_network {
+        profile.push_str("(deny network*)\n");
+    }
+    if sandbox.read_only_project {
+        profile.push_str(&format!(
+            "(deny file-write* (subpath \"{}\"))\n",
+            project.replace('\\', "\\\\").replace('"', "\\\"")
+        ));
+    }
+
+    let mut cmd = Command::new(sandbox_exec);
+    cmd.arg("-p").arg(profile).args(["bash", "-c", script]);
+    cmd
+}
+
+/// Build the `bash -c` command for a hook script, wrapped in the sandbox when set
+pub fn hook_command(
+    script: &str,
+    project_path: &str,
+    sandbox: Option<&HookSandbox>,
+) -> Result<Command, String> {
+    let sandbox = match sandbox {
+        Some(sandbox) => sandbox,
+        None => {
+            let mut cmd = Command::new("bash");
+            cmd.arg("-c").arg(script);
+            return Ok(cmd);
+        }
+    };
+
+    let script = if cfg!(windows) {
+        script.to_string()
+    } else {
+        format!("{}{}", ulimit_prefix(sandbox), script)
+    };
+
+    let mut cmd = if sandbox.read_only_project || sandbox.no_network {
+        let backend = isolation_backend().ok_or_else(|| {
+            "Hook sandbox cannot restrict files or network here: it needs bwrap on Linux or \
+             sandbox-exec on macOS. Set read_only_project and no_network to false to use \
+             resource limits only."
+                .to_string()
+        })?;
+        debug!("Sandboxing hook with {}", backend.display());
+        if cfg!(target_os = "linux") {
+            bwrap_command(&backend, &script, project_path, sandbox)
+        } else {
+            seatbelt_command(&backend, &script, project_path, sandbox)
+        }
+    } else {
+        let mut cmd = Command::new("bash");
+        cmd.arg("-c").arg(&script);
+        cmd
+    };
+
+    cmd.env_clear();
+    let allowed = BASE_ENV
+        .iter()
+        .copied()
+        .chain(sandbox.allow_env.iter().map(|s| s.as_str()));
+    for name in allowed {
+        if let Some(value) = std::env::var_os(name) {
+            cmd.env(name, value);
+        }
+    }
+    Ok(cmd)
+}
+
+/// Apply CPU / memory limits to a spawned hook on Windows, where `ulimit` is not
+/// available. The returned job must be kept alive until the process exits.
+pub fn limit_process(
+    pid: Option<u32>,
+    sandbox: Option<&HookSandbox>,
+) -> Result<Option<JobObject>, String> {
+    let sandbox = match sandbox {
+        Some(sandbox) if sandbox.cpu_seconds.is_some() || sandbox.memory_mb.is_some() => sandbox,
+        _ => return Ok(None),
+    };
+    if !cfg!(windows) {
+        return Ok(None);
+    }
+
+    let pid = pid.ok_or("Hook process exited before limits could be applied")?;
+    let job = JobObject::create()?;
+    job.set_resource_limits(sandbox.cpu_seconds, sandbox.memory_mb)?;
+    job.assign_process_by_pid(pid)?;
+    Ok(Some(job))
+}
+
+// ============ Tauri Commands ============
+
+/// Report which hook sandbox options this machine can enforce
+#[tauri::command]
+pub async fn get_hook_sandbox_support() -> Result<SandboxSupport, String> {
+    Ok(sandbox_support())
+}
@@ -0,0 +1,445 @@
+use log::{debug, info, warn};
+/// Idle detection
+///
+/// Polls how long the OS has seen no keyboard or mouse input and fires `OnIdle`
+/// once it passes the configured number of minutes, then `OnResume` on the next
+/// input. Hooks are loaded from project settings, so both events run for every
+/// project with a tab open in the workspace; the hook data carries the idle time
+/// and that project's session IDs.
+///
+/// While the user is away the prompt queue can be held, so queued prompts are not
+/// sent into a session nobody watches, and MCP health checks can be paused. Both
+/// resume with the user.
+///
+/// Idle time comes from CoreGraphics on macOS and `GetLastInputInfo` on Windows.
+/// Linux has no common API, so `xprintidle` (X11) or GNOME's Mutter IdleMonitor
+/// over `gdbus` is asked, whichever works first; without either, detection is
+/// reported as unsupported.
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use super::enhanced_hooks::{trigger_hook_event, HookContext};
+use super::storage::AgentDb;
+use crate::events::{self, AppEvent};
+
+/// `app_settings` key holding the JSON config
+const SETTINGS_KEY: &str = "idle_detection";
+
+/// Poll interval while away, i.e. how quickly a return is noticed
+const AWAY_POLL: Duration = Duration::from_secs(30);
+
+/// Shortest wait between polls while the user is active
+const MIN_ACTIVE_POLL: Duration = Duration::from_secs(15);
+
+/// Wait before asking again after detection is disabled or unavailable
+const INACTIVE_POLL: Duration = Duration::from_secs(5 * 60);
+
+/// Persisted idle detection settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IdleConfig {
+    pub enabled: bool,
+    /// Minutes without input before the user counts as away (default: 10)
+    pub idle_minutes: u32,
+    /// Keep queued prompts waiting until the user is back
+    pub hold_prompt_queue: bool,
+    /// Skip MCP health check rounds while away
+    pub pause_monitors: bool,
+}
+
+impl Default for IdleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            idle_minutes: 10,
+            hold_prompt_queue: false,
+            pause_monitors: false,
+        }
+    }
+}
+
+impl IdleConfig {
+    fn threshold(&self) -> Duration {
+        Duration::from_secs(self.idle_minutes.max(1) as u64 * 60)
+    }
+}
+
+/// Payload of `user-idle` and `user-resumed`, and the data given to the hooks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdleChange {
+    /// When the last input happened (RFC 3339)
+    pub idle_since: String,
+    /// Seconds without input, up to the moment the change was noticed
+    pub idle_secs: u64,
+}
+
+/// Current idle state for the settings page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdleStatus {
+    /// Whether idle time can be read on this system
+    pub supported: bool,
+    pub away: bool,
+    /// Seconds since the last input, if it could be read
+    pub idle_secs: Option<u64>,
+    /// When the current absence started
+    pub away_since: Option<String>,
+}
+
+#[derive(Default)]
+struct Inner {
+    config: Option<IdleConfig>,
+    /// Time of the last input while the user is away
+    away_since: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Default)]
+pub struct IdleState(Mutex<Inner>);
+
+fn load_config(conn: &Connection) -> Result<IdleConfig, String> {
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT value FROM app_settings WHERE key = ?1",
+            [SETTINGS_KEY],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    match stored {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid idle detection settings: {}", e)),
+        None => Ok(IdleConfig::default()),
+    }
+}
+
+/// The saved config, loaded on first use
+fn current_config(app: &AppHandle) -> IdleConfig {
+    let state = match app.try_state::<IdleState>() {
+        Some(state) => state,
+        None => return IdleConfig::default(),
+    };
+    if let Some(config) = state.0.lock().ok().and_then(|inner| inner.config.clone()) {
+        return config;
+    }
+    let loaded = match app.try_state::<AgentDb>() {
+        Some(db) => {
+            db.0.lock()
+                .map_err(|e| e.to_string())
+                .and_then(|conn| load_config(&conn))
+        }
+        None => Ok(IdleConfig::default()),
+    };
+    let config = loaded.unwrap_or_else(|e| {
+        warn!("{}", e);
+        IdleConfig::default()
+    });
+    if let Ok(mut inner) = state.0.lock() {
+        inner.config = Some(config.clone());
+    }
+    config
+}
+
+fn away_since(app: &AppHandle) -> Option<chrono::DateTime<chrono::Utc>> {
+    app.try_state::<IdleState>()
+        .and_then(|state| state.0.lock().ok().and_then(|inner| inner.away_since))
+}
+
+/// Whether the user is currently away
+pub fn is_away(app: &AppHandle) -> bool {
+    away_since(app).is_some()
+}
+
+/// Whether queued prompts should wait for the user to come back
+pub fn holds_prompt_queue(app: &AppHandle) -> bool {
+    is_away(app) && current_config(app).hold_prompt_queue
+}
+
+/// Whether background monitors should skip their work for now
+pub fn pauses_monitors(app: &AppHandle) -> bool {
+    is_away(app) && current_config(app).pause_monitors
+}
+
+/// Time since the last keyboard or mouse input, if the OS can tell
+#[cfg(target_os = "macos")]
+pub fn system_idle_time() -> Option<Duration> {
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGEventSourceSecondsSinceLastEventType(source_state: i32, event_type: u32) -> f64;
+    }
+    // kCGEventSourceStateCombinedSessionState, kCGAnyInputEventType
+    let secs = unsafe { CGEventSourceSecondsSinceLastEventType(0, u32::MAX) };
+    (secs.is_finite() && secs >= 0.0).then(|| Duration::from_secs_f64(secs))
+}
+
+/// Time since the last keyboard or mouse input, if the OS can tell
+#[cfg(target_os = "windows")]
+pub fn system_idle_time() -> Option<Duration> {
+    use windows::Win32::System::SystemInformation::GetTickCount;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    unsafe {
+        if !GetLastInputInfo(&mut info).as_bool() {
+            return None;
+        }
+        // Both are milliseconds since boot and wrap together after 49 days
+        let idle_ms = GetTickCount().wrapping_sub(info.dwTime);
+        Some(Duration::from_millis(idle_ms as u64))
+    }
+}
+
+/// Time since the last keyboard or mouse input, if the OS can tell
+#[cfg(target_os = "linux")]
+pub fn system_idle_time() -> Option<Duration> {
+    use crate::process::audit::AuditedCommand;
+    use once_cell::sync::Lazy;
+    use std::process::Command;
+
+    type Probe = (&'static str, &'static [&'static str]);
+    const PROBES: &[Probe] = &[
+        ("xprintidle", &[]),
+        (
+            "gdbus",
+            &[
+                "call",
+                "--session",
+                "--dest",
+                "org.gnome.Mutter.IdleMonitor",
+                "--object-path",
+                "/org/gnome/Mutter/IdleMonitor/Core",
+                "--method",
+                "org.gnome.Mutter.IdleMonitor.GetIdletime",
+            ],
+        ),
+    ];
+    // The probe that worked last, so missing tools are not retried every poll
+    static WORKING: Lazy<Mutex<Option<usize>>> = Lazy::new(|| Mutex::new(None));
+
+    fn run(probe: &Probe) -> Option<Duration> {
+        let output = Command::new(probe.0)
+            .args(probe.1)
+            .audited_output("system")
+            .ok()
+            .filter(|output| output.status.success())?;
+        // xprintidle prints "12345", gdbus "(uint64 12345,)"; both in milliseconds
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let ms = stdout
+            .split(|c: char| !c.is_ascii_digit())
+            .rfind(|part| !part.is_empty())?
+            .parse::<u64>()
+            .ok()?;
+        Some(Duration::from_millis(ms))
+    }
+
+    let known = WORKING.lock().ok().and_then(|working| *working);
+    if let Some(index) = known {
+        if let Some(idle) = run(&PROBES[index]) {
+            return Some(idle);
+        }
+    }
+    for (index, probe) in PROBES.iter().enumerate() {
+        if Some(index) == known {
+            continue;
+        }
+        if let Some(idle) = run(probe) {
+            debug!("Reading idle time with {}", probe.0);
+            if let Ok(mut working) = WORKING.lock() {
+                *working = Some(index);
+            }
+            return Some(idle);
+        }
+    }
+    None
+}
+
+/// Time since the last keyboard or mouse input, if the OS can tell
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+pub fn system_idle_time() -> Option<Duration> {
+    None
+}
+
+async fn read_idle_time() -> Option<Duration> {
+    tokio::task::spawn_blocking(system_idle_time)
+        .await
+        .ok()
+        .flatten()
+}
+
+/// Projects with a tab open in the workspace, with their session IDs
+fn open_projects(app: &AppHandle) -> Vec<(String, Vec<String>)> {
+    let workspace = match super::workspace::load_workspace(app) {
+        Ok(workspace) => workspace,
+        Err(e) => {
+            warn!("Failed to read workspace for idle hooks: {}", e);
+            return Vec::new();
+        }
+    };
+    let mut projects: Vec<(String, Vec<String>)> = Vec::new();
+    for tab in workspace.tabs {
+        let project_path = match tab.project_path {
+            Some(path) => path,
+            None => continue,
+        };
+        let index = match projects.iter().position(|(path, _)| *path == project_path) {
+            Some(index) => index,
+            None => {
+                projects.push((project_path, Vec::new()));
+                projects.len() - 1
+            }
+        };
+        if let Some(session_id) = tab.session_id {
+            if !projects[index].1.contains(&session_id) {
+                projects[index].1.push(session_id);
+            }
+        }
+    }
+    projects
+}
+
+/// Run `event` hooks for every open project in the background
+fn fire_hooks(app: &AppHandle, event: &'static str, change: &IdleChange) {
+    for (project_path, sessions) in open_projects(app) {
+        let context = HookContext {
+            event: event.to_string(),
+            session_id: String::new(),
+            project_path,
+            data: serde_json::json!({
+                "idle_since": change.idle_since,
+                "idle_secs": change.idle_secs,
+                "sessions": sessions,
+            }),
+        };
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = trigger_hook_event(app, event.to_string(), context, None).await {
+                warn!("{} hooks failed: {}", event, e);
+            }
+        });
+    }
+}
+
+fn set_away(app: &AppHandle, since: Option<chrono::DateTime<chrono::Utc>>) {
+    if let Some(state) = app.try_state::<IdleState>() {
+        if let Ok(mut inner) = state.0.lock() {
+            inner.away_since = since;
+        }
+    }
+}
+
+fn went_idle(app: &AppHandle, idle: Duration) {
+    let since = chrono::Utc::now() - chrono::Duration::from_std(idle).unwrap_or_default();
+    set_away(app, Some(since));
+    let change = IdleChange {
+        idle_since: since.to_rfc3339(),
+        idle_secs: idle.as_secs(),
+    };
+    info!("User idle for {}s", change.idle_secs);
+    events::emit(app, AppEvent::UserIdle(change.clone()));
+    fire_hooks(app, "OnIdle", &change);
+}
+
+fn resumed(app: &AppHandle, since: chrono::DateTime<chrono::Utc>) {
+    set_away(app, None);
+    let change = IdleChange {
+        idle_since: since.to_rfc3339(),
+        idle_secs: (chrono::Utc::now() - since).num_seconds().max(0) as u64,
+    };
+    info!("User back after {}s", change.idle_secs);
+    events::emit(app, AppEvent::UserResumed(change.clone()));
+    fire_hooks(app, "OnResume", &change);
+    super::prompt_queue::release_held(app);
+}
+
+/// One poll; returns how long to wait before the next
+async fn poll(app: &AppHandle) -> Duration {
+    let config = current_config(app);
+    let away = away_since(app);
+    if !config.enabled {
+        // Don't leave anything held after detection is switched off
+        if let Some(since) = away {
+            resumed(app, since);
+        }
+        return INACTIVE_POLL;
+    }
+    let idle = match read_idle_time().await {
+        Some(idle) => idle,
+        None => {
+            debug!("Idle time unavailable");
+            return INACTIVE_POLL;
+        }
+    };
+    let threshold = config.threshold();
+    match away {
+        // Any input resets the idle time, so anything below the threshold is a return
+        Some(since) if idle < threshold => {
+            resumed(app, since);
+            threshold.max(MIN_ACTIVE_POLL)
+        }
+        Some(_) => AWAY_POLL,
+        None if idle >= threshold => {
+            went_idle(app, idle);
+            AWAY_POLL
+        }
+        // The threshold cannot be reached before the remaining time has passed
+        None => (threshold - idle).max(MIN_ACTIVE_POLL),
+    }
+}
+
+/// Start polling the idle time in the background
+pub fn start_idle_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let wait = poll(&app).await;
+            tokio::time::sleep(wait).await;
+        }
+    });
+}
+
+// ============ Tauri Commands ============
+
+/// Get idle detection settings
+#[tauri::command]
+pub async fn get_idle_config(app: AppHandle) -> Result<IdleConfig, String> {
+    Ok(current_config(&app))
+}
+
+/// Update idle detection settings; they apply from the next poll
+#[tauri::command]
+pub async fn update_idle_config(
+    db: State<'_, AgentDb>,
+    state: State<'_, IdleState>,
+    config: IdleConfig,
+) -> Result<(), String> {
+    if config.idle_minutes == 0 {
+        return Err("Idle time must be at least one minute".to_string());
+    }
+    let json = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+    {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+            params![SETTINGS_KEY, json],
+        )
+        .map_err(|e| format!("Failed to save idle detection settings: {}", e))?;
+    }
+    state.0.lock().map_err(|e| e.to_string())?.config = Some(config);
+    Ok(())
+}
+
+/// Whether the user is away and how long since the last input
+#[tauri::command]
+pub async fn get_idle_status(app: AppHandle) -> Result<IdleStatus, String> {
+    let idle = read_idle_time().await;
+    let away_since = away_since(&app);
+    Ok(IdleStatus {
+        supported: idle.is_some(),
+        away: away_since.is_some(),
+        idle_secs: idle.map(|idle| idle.as_secs()),
+        away_since: away_since.map(|since| since.to_rfc3339()),
+    })
+}
@@ -0,0 +1,419 @@
+use log::info;
+/// Knowledge base of answers captured from transcripts
+///
+/// An assistant answer, or one code block of it, can be saved with a title and
+/// tags so it is found again later instead of being buried in an old session.
+/// Each entry keeps the session and message it came from and the project it
+/// belongs to. Entries are indexed with SQLite FTS5 and `search_knowledge`
+/// matches every word of the query as a prefix, best matches first.
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tauri::State;
+
+use super::session_export::find_session_file;
+use super::storage::AgentDb;
+
+const SELECT_COLUMNS: &str = "e.id, e.title, e.content, e.kind, e.language, e.tags, e.project_path, e.session_id, e.message_index, e.created_at, e.updated_at";
+
+/// Longest title taken from the first line of the content
+const MAX_TITLE_CHARS: usize = 80;
+
+/// A saved answer or code block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnowledgeEntry {
+    pub id: i64,
+    pub title: String,
+    pub content: String,
+    /// "answer" or "code"
+    pub kind: String,
+    /// Language of a code block's fence
+    pub language: Option<String>,
+    pub tags: Vec<String>,
+    pub project_path: Option<String>,
+    pub session_id: Option<String>,
+    /// Index into `load_session_history` of the message it came from
+    pub message_index: Option<i64>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// What to capture: a message of a transcript, one of its code blocks, or text
+/// selected in it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnowledgeCapture {
+    pub session_id: String,
+    pub project_id: Option<String>,
+    /// Index into `load_session_history`
+    pub message_index: usize,
+    /// Zero-based code block of the message to capture instead of the whole answer
+    pub code_block: Option<usize>,
+    /// Text selected by the user, captured instead of the message
+    pub selection: Option<String>,
+    pub title: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// A search result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnowledgeHit {
+    pub entry: KnowledgeEntry,
+    /// Matching part of the content, with matches wrapped in `[` and `]`
+    pub snippet: String,
+}
+
+fn row_to_entry(row: &Row) -> rusqlite::Result<KnowledgeEntry> {
+    let tags: String = row.get(5)?;
+    Ok(KnowledgeEntry {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        content: row.get(2)?,
+        kind: row.get(3)?,
+        language: row.get(4)?,
+        tags: serde_json::from_str(&tags).unwrap_or_default(),
+        project_path: row.get(6)?,
+        session_id: row.get(7)?,
+        message_index: row.get(8)?,
+        created_at: row.get(9)?,
+        updated_at: row.get(10)?,
+    })
+}
+
+fn load_entry(conn: &Connection, id: i64) -> Result<KnowledgeEntry, String> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM knowledge_entries e WHERE e.id = ?1",
+            SELECT_COLUMNS
+        ),
+        [id],
+        row_to_entry,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Knowledge entry not found: {}", id))
+}
+
+fn clean_tags(tags: Vec<String>) -> Vec<String> {
+    let mut tags: Vec<String> = tags
+        .into_iter()
+        .map(|tag| tag.trim().to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+/// Text of an assistant message's text parts
+fn answer_text(line: &serde_json::Value) -> Option<String> {
+    if line["type"] != "assistant" {
+        return None;
+    }
+    let text = match &line["message"]["content"] {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Array(parts) => parts
+            .iter()
+            .filter(|part| part["type"] == "text")
+            .filter_map(|part| part["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        _ => return None,
+    };
+    (!text.trim().is_empty()).then_some(text)
+}
+
+/// Fenced code blocks of Markdown, with the language of their fence
+fn code_blocks(text: &str) -> Vec<(Option<String>, String)> {
+    let mut blocks = Vec::new();
+    let mut open: Option<(&str, Option<String>, Vec<&str>)> = None;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        match open.take() {
+            None => {
+                if let Some(fence) = ["```", "~~~"].into_iter().find(|f| trimmed.starts_with(f)) {
+                    let language = trimmed.trim_start_matches(fence).trim();
+                    let language = (!language.is_empty()).then(|| language.to_string());
+                    open = Some((fence, language, Vec::new()));
+                }
+            }
+            Some((fence, language, lines))
+                if trimmed.starts_with(fence)
+                    && trimmed.trim_start_matches(fence).trim().is_empty() =>
+            {
+                blocks.push((language, lines.join("\n")));
+            }
+            Some((fence, language, mut lines)) => {
+                lines.push(line);
+                open = Some((fence, language, lines));
+            }
+        }
+    }
+    blocks
+}
+
+fn default_title(content: &str) -> String {
+    let first = content
+        .lines()
+        .map(|line| line.trim().trim_start_matches('#').trim())
+        .find(|line| !line.is_empty())
+        .unwrap_or("Untitled");
+    if first.chars().count() > MAX_TITLE_CHARS {
+        let cut: String = first.chars().take(MAX_TITLE_CHARS - 1).collect();
+        format!("{}…", cut.trim_end())
+    } else {
+        first.to_string()
+    }
+}
+
+/// Text read from a transcript by `read_capture`
+struct Captured {
+    content: String,
+    kind: &'static str,
+    language: Option<String>,
+    project_path: Option<String>,
+}
+
+/// Read the captured text and its origin from the transcript
+fn read_capture(capture: &KnowledgeCapture) -> Result<Captured, String> {
+    let path = find_session_file(&capture.session_id, capture.project_id.as_deref())?;
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read session: {}", e))?;
+    // Indexed like `load_session_history`, which skips lines that don't parse
+    let lines: Vec<serde_json::Value> = content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    let line = lines.get(capture.message_index).ok_or_else(|| {
+        format!(
+            "Message {} is out of range; the session has {} messages",
+            capture.message_index,
+            lines.len()
+        )
+    })?;
+    let project_path = line["cwd"]
+        .as_str()
+        .or_else(|| lines.iter().find_map(|line| line["cwd"].as_str()))
+        .map(str::to_string);
+    if let Some(selection) = capture.selection.as_ref().filter(|s| !s.trim().is_empty()) {
+        return Ok(Captured {
+            content: selection.clone(),
+            kind: "answer",
+            language: None,
+            project_path,
+        });
+    }
+    let answer = answer_text(line).ok_or_else(|| {
+        format!(
+            "Message {} is not an assistant answer",
+            capture.message_index
+        )
+    })?;
+    match capture.code_block {
+        Some(index) => {
+            let mut blocks = code_blocks(&answer);
+            if index >= blocks.len() {
+                return Err(format!(
+                    "Code block {} not found; the answer has {}",
+                    index,
+                    blocks.len()
+                ));
+            }
+            let (language, code) = blocks.swap_remove(index);
+            Ok(Captured {
+                content: code,
+                kind: "code",
+                language,
+                project_path,
+            })
+        }
+        None => Ok(Captured {
+            content: answer,
+            kind: "answer",
+            language: None,
+            project_path,
+        }),
+    }
+}
+
+/// FTS5 query matching every word of the user's query as a prefix. Words are
+/// quoted, so operators and punctuation in the query are searched for literally.
+fn fts_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+// ============ Tauri Commands ============
+
+/// Save an assistant answer, one of its code blocks, or a selection of it
+#[tauri::command]
+pub async fn capture_knowledge(
+    db: State<'_, AgentDb>,
+    capture: KnowledgeCapture,
+) -> Result<KnowledgeEntry, String> {
+    let read = capture.clone();
+    let captured = tauri::async_runtime::spawn_blocking(move || read_capture(&read))
+        .await
+        .map_err(|e| e.to_string())??;
+    let title = capture
+        .title
+        .map(|title| title.trim().to_string())
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| default_title(&captured.content));
+    let tags = serde_json::to_string(&clean_tags(capture.tags)).map_err(|e| e.to_string())?;
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO knowledge_entries (title, content, kind, language, tags, project_path, session_id, message_index, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?9)",
+        params![
+            title,
+            captured.content,
+            captured.kind,
+            captured.language,
+            tags,
+            captured.project_path,
+            capture.session_id,
+            capture.message_index as i64,
+            now
+        ],
+    )
+    .map_err(|e| format!("Failed to save knowledge entry: {}", e))?;
+    let entry = load_entry(&conn, conn.last_insert_rowid())?;
+    info!(
+        "Captured {} from session {} as knowledge entry {}",
+        entry.kind, capture.session_id, entry.id
+    );
+    Ok(entry)
+}
+
+/// Entries matching every word of `query`, best first; with an empty query the
+/// newest entries. Optionally limited to a project or a tag.
+#[tauri::command]
+pub async fn search_knowledge(
+    db: State<'_, AgentDb>,
+    query: String,
+    project_path: Option<String>,
+    tag: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<KnowledgeHit>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let limit = limit.unwrap_or(50);
+    // Tags are stored as a JSON array, so a tag is matched with its quotes
+    let tag = tag.map(|tag| format!("%{}%", serde_json::Value::from(tag.trim().to_lowercase())));
+
+    let hits = match fts_query(&query) {
+        Some(fts) => {
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT {}, snippet(knowledge_fts, 1, '[', ']', '…', 24)
+                     FROM knowledge_fts JOIN knowledge_entries e ON e.id = knowledge_fts.rowid
+                     WHERE knowledge_fts MATCH ?1
+                       AND (?2 IS NULL OR e.project_path = ?2)
+                       AND (?3 IS NULL OR e.tags LIKE ?3)
+                     ORDER BY bm25(knowledge_fts, 5.0, 1.0, 3.0) LIMIT ?4",
+                    SELECT_COLUMNS
+                ))
+                .map_err(|e| e.to_string())?;
+            let hits = stmt
+                .query_map(params![fts, project_path, tag, limit], |row| {
+                    Ok(KnowledgeHit {
+                        entry: row_to_entry(row)?,
+                        snippet: row.get(11)?,
+                    })
+                })
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("Search failed: {}", e))?;
+            hits
+        }
+        None => {
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT {} FROM knowledge_entries e
+                     WHERE (?1 IS NULL OR e.project_path = ?1)
+                       AND (?2 IS NULL OR e.tags LIKE ?2)
+                     ORDER BY e.id DESC LIMIT ?3",
+                    SELECT_COLUMNS
+                ))
+                .map_err(|e| e.to_string())?;
+            let entries = stmt
+                .query_map(params![project_path, tag, limit], row_to_entry)
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            entries
+                .into_iter()
+                .map(|entry| KnowledgeHit {
+                    snippet: entry.content.chars().take(200).collect(),
+                    entry,
+                })
+                .collect()
+        }
+    };
+    Ok(hits)
+}
+
+#[tauri::command]
+pub async fn get_knowledge_entry(
+    db: State<'_, AgentDb>,
+    id: i64,
+) -> Result<KnowledgeEntry, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_entry(&conn, id)
+}
+
+/// Change the title, tags or content of an entry
+#[tauri::command]
+pub async fn update_knowledge_entry(
+    db: State<'_, AgentDb>,
+    id: i64,
+    title: Option<String>,
+    tags: Option<Vec<String>>,
+    content: Option<String>,
+) -> Result<KnowledgeEntry, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let mut entry = load_entry(&conn, id)?;
+    if let Some(title) = title
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+    {
+        entry.title = title;
+    }
+    if let Some(tags) = tags {
+        entry.tags = clean_tags(tags);
+    }
+    if let Some(content) = content.filter(|c| !c.trim().is_empty()) {
+        entry.content = content;
+    }
+    let tags = serde_json::to_string(&entry.tags).map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE knowledge_entries SET title = ?1, tags = ?2, content = ?3, updated_at = ?4 WHERE id = ?5",
+        params![
+            entry.title,
+            tags,
+            entry.content,
+            chrono::Utc::now().to_rfc3339(),
+            id
+        ],
+    )
+    .map_err(|e| format!("Failed to update knowledge entry: {}", e))?;
+    load_entry(&conn, id)
+}
+
+#[tauri::command]
+pub async fn delete_knowledge_entry(db: State<'_, AgentDb>, id: i64) -> Result<(), String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let deleted = conn
+        .execute("DELETE FROM knowledge_entries WHERE id = ?1", [id])
+        .map_err(|e| format!("Failed to delete knowledge entry: {}", e))?;
+    if deleted == 0 {
+        return Err(format!("Knowledge entry not found: {}", id));
+    }
+    info!("Deleted knowledge entry {}", id);
+    Ok(())
+}
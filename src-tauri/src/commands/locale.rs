@@ -0,0 +1,96 @@
+use log::{info, warn};
+/// Locale of backend-generated text
+///
+/// Notifications, hook and command-policy messages and doctor results come
+/// from the `i18n` catalog. The frontend passes the user's language with
+/// `set_locale`; without one the system locale is used. The choice is kept in
+/// `app_settings` and loaded at startup.
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use unic_langid::LanguageIdentifier;
+
+use super::storage::AgentDb;
+use crate::i18n;
+
+/// `app_settings` key holding the locale the user picked
+const SETTINGS_KEY: &str = "locale";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocaleInfo {
+    /// Locale messages are rendered in
+    pub locale: String,
+    /// Locale the user picked; None follows the system
+    pub requested: Option<String>,
+    pub system: Option<String>,
+    pub available: Vec<String>,
+}
+
+fn load_requested(conn: &Connection) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT value FROM app_settings WHERE key = ?1",
+        [SETTINGS_KEY],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+fn info(requested: Option<String>) -> LocaleInfo {
+    LocaleInfo {
+        locale: i18n::current_locale(),
+        requested,
+        system: i18n::system_locale(),
+        available: i18n::available_locales(),
+    }
+}
+
+/// Apply the stored locale; called once at startup
+pub fn load_locale(conn: &Connection) {
+    let requested = load_requested(conn).unwrap_or_else(|e| {
+        warn!("Failed to read locale: {}", e);
+        None
+    });
+    let locale = i18n::set_locale(requested.as_deref());
+    info!("Backend messages in {}", locale);
+}
+
+// ============ Tauri Commands ============
+
+#[tauri::command]
+pub async fn get_locale(db: State<'_, AgentDb>) -> Result<LocaleInfo, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    Ok(info(load_requested(&conn)?))
+}
+
+/// Render backend messages in a language tag such as `zh-CN`, or in the system
+/// locale with None; the closest available catalog is used
+#[tauri::command]
+pub async fn set_locale(
+    db: State<'_, AgentDb>,
+    locale: Option<String>,
+) -> Result<LocaleInfo, String> {
+    if let Some(tag) = &locale {
+        tag.parse::<LanguageIdentifier>()
+            .map_err(|e| format!("Invalid locale '{}': {}", tag, e))?;
+    }
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    match &locale {
+        Some(tag) => conn.execute(
+            "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+            params![SETTINGS_KEY, tag],
+        ),
+        None => conn.execute(
+            "DELETE FROM app_settings WHERE key = ?1",
+            params![SETTINGS_KEY],
+        ),
+    }
+    .map_err(|e| format!("Failed to save locale: {}", e))?;
+    let chosen = i18n::set_locale(locale.as_deref());
+    info!(
+        "Backend messages in {} (requested {})",
+        chosen,
+        locale.as_deref().unwrap_or("system")
+    );
+    Ok(info(locale))
+}
@@ -0,0 +1,125 @@
+use rusqlite::params_from_iter;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use super::storage::AgentDb;
+use crate::logging::LogRecord;
+
+/// Levels from most to least severe
+const LEVELS: &[&str] = &["ERROR", "WARN", "INFO", "DEBUG", "TRACE"];
+
+/// Default and maximum number of records returned by one query
+const DEFAULT_LIMIT: u32 = 200;
+const MAX_LIMIT: u32 = 5_000;
+
+/// Filters for `query_logs`; every field is optional
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogQuery {
+    /// Least severe level to include (e.g. "WARN" returns WARN and ERROR)
+    pub min_level: Option<String>,
+    /// Module path prefix, e.g. "claude_workbench::commands::enhanced_hooks"
+    pub module: Option<String>,
+    pub session_id: Option<String>,
+    /// Case-insensitive substring of the message or fields
+    pub search: Option<String>,
+    /// RFC 3339 lower bound (inclusive)
+    pub since: Option<String>,
+    /// RFC 3339 upper bound (exclusive)
+    pub until: Option<String>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+// ============ Tauri Commands ============
+
+/// Query stored log records, newest first
+#[tauri::command]
+pub async fn query_logs(
+    db: State<'_, AgentDb>,
+    filters: Option<LogQuery>,
+) -> Result<Vec<LogRecord>, String> {
+    let filters = filters.unwrap_or_default();
+    let mut clauses: Vec<String> = Vec::new();
+    let mut values: Vec<String> = Vec::new();
+
+    if let Some(min_level) = &filters.min_level {
+        let upper = min_level.to_uppercase();
+        let index = LEVELS
+            .iter()
+            .position(|l| *l == upper)
+            .ok_or_else(|| format!("Invalid log level: {}", min_level))?;
+        let placeholders: Vec<String> = LEVELS[..=index]
+            .iter()
+            .map(|level| {
+                values.push(level.to_string());
+                format!("?{}", values.len())
+            })
+            .collect();
+        clauses.push(format!("level IN ({})", placeholders.join(", ")));
+    }
+    if let Some(module) = &filters.module {
+        values.push(format!("{}%", module));
+        clauses.push(format!("module LIKE ?{}", values.len()));
+    }
+    if let Some(session_id) = &filters.session_id {
+        values.push(session_id.clone());
+        clauses.push(format!("session_id = ?{}", values.len()));
+    }
+    if let Some(search) = &filters.search {
+        values.push(format!("%{}%", search));
+        clauses.push(format!(
+            "(message LIKE ?{0} OR fields LIKE ?{0})",
+            values.len()
+        ));
+    }
+    if let Some(since) = &filters.since {
+        values.push(since.clone());
+        clauses.push(format!("timestamp >= ?{}", values.len()));
+    }
+    if let Some(until) = &filters.until {
+        values.push(until.clone());
+        clauses.push(format!("timestamp < ?{}", values.len()));
+    }
+
+    let where_clause = if clauses.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", clauses.join(" AND "))
+    };
+    let limit = filters.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    let offset = filters.offset.unwrap_or(0);
+    let sql = format!(
+        "SELECT id, timestamp, level, module, session_id, message, fields FROM app_logs
+         {} ORDER BY id DESC LIMIT {} OFFSET {}",
+        where_clause, limit, offset
+    );
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params_from_iter(values.iter()), |row| {
+            let fields: String = row.get(6)?;
+            Ok(LogRecord {
+                id: row.get(0)?,
+                timestamp: row.get(1)?,
+                level: row.get(2)?,
+                module: row.get(3)?,
+                session_id: row.get(4)?,
+                message: row.get(5)?,
+                fields: serde_json::from_str(&fields).unwrap_or_default(),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Delete all stored log records; returns how many were removed
+#[tauri::command]
+pub async fn clear_logs(db: State<'_, AgentDb>) -> Result<usize, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM app_logs", [])
+        .map_err(|e| format!("Failed to clear logs: {}", e))
+}
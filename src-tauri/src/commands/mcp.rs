@@ -4,9 +4,14 @@ use log::{error, info};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use tauri::AppHandle;
+use tauri::{AppHandle, Manager, State};
+
+use super::claude::get_claude_dir;
+use crate::paths::claude_json_path;
+use crate::process::audit::{AuditedAsyncCommand, AuditedCommand};
+use crate::process::{ProcessRegistry, ProcessRegistryState, ProcessType};
 
 /// Helper function to create a std::process::Command with proper environment variables
 /// This ensures commands like Claude can find Node.js and other dependencies
@@ -113,7 +118,9 @@ fn execute_claude_mcp_command(app_handle: &AppHandle, args: Vec<&str>) -> Result
         cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
     }
 
-    let output = cmd.output().context("Failed to execute claude command")?;
+    let output = cmd
+        .audited_output("mcp")
+        .context("Failed to execute claude command")?;
 
     if output.status.success() {
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
@@ -198,6 +205,7 @@ pub async fn mcp_add(
     match execute_claude_mcp_command(&app, cmd_args) {
         Ok(output) => {
             info!("Successfully added MCP server: {}", name);
+            super::telemetry::record(&app, "mcp.add");
             Ok(AddServerResult {
                 success: true,
                 message: output.trim().to_string(),
@@ -477,12 +485,12 @@ pub async fn mcp_add_from_claude_desktop(
 
     // ⚡ Correct fix: Claude Code CLI configuration is in the same location on all platforms
     // Windows, macOS, Linux all use ~/.claude/ directory
-    let home_dir = dirs::home_dir().ok_or_else(|| "Could not find home directory".to_string())?;
+    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
 
     let possible_paths = vec![
         // Claude Code CLI configuration files (unified across all platforms)
-        home_dir.join(".claude").join("settings.json"), // Main configuration file
-        home_dir.join(".claude.json"),                  // Legacy configuration file
+        claude_dir.join("settings.json"), // Main configuration file
+        claude_json_path()?,              // Legacy configuration file
     ];
 
     let config_path = possible_paths
@@ -624,8 +632,23 @@ pub async fn mcp_serve(app: AppHandle) -> Result<String, String> {
     let mut cmd = create_command_with_env(&claude_path);
     cmd.arg("mcp").arg("serve");
 
-    match cmd.spawn() {
-        Ok(_) => {
+    match cmd.audited_spawn("mcp") {
+        Ok((mut child, audit)) => {
+            // Track the server until it exits so it can be cleaned up after a crash
+            let registry = app.state::<ProcessRegistryState>().0.clone();
+            if let Ok(run_id) = registry.register_tracked_process(
+                ProcessType::McpServer {
+                    server_name: "claude mcp serve".to_string(),
+                },
+                child.id(),
+                String::new(),
+                "mcp serve".to_string(),
+            ) {
+                std::thread::spawn(move || {
+                    audit.finish(&child.wait());
+                    let _ = registry.unregister_process(run_id);
+                });
+            }
             info!("Successfully started Claude Code MCP server");
             Ok("Claude Code MCP server started".to_string())
         }
@@ -665,14 +688,15 @@ pub async fn mcp_reset_project_choices(app: AppHandle) -> Result<String, String>
     }
 }
 
-/// Gets the status of MCP servers
+/// Gets the status of MCP servers as last recorded by the health monitor
 #[tauri::command]
-pub async fn mcp_get_server_status() -> Result<HashMap<String, ServerStatus>, String> {
+pub async fn mcp_get_server_status(
+    state: tauri::State<'_, super::mcp_health::McpHealthState>,
+) -> Result<HashMap<String, ServerStatus>, String> {
     info!("Getting MCP server status");
 
-    // TODO: Implement actual status checking
-    // For now, return empty status
-    Ok(HashMap::new())
+    let records = state.0.snapshot()?;
+    Ok(super::mcp_health::health_to_status_map(&records))
 }
 
 /// Exports MCP server configuration from .claude.json
@@ -680,10 +704,7 @@ pub async fn mcp_get_server_status() -> Result<HashMap<String, ServerStatus>, St
 pub async fn mcp_export_config() -> Result<String, String> {
     info!("Exporting MCP server configuration from .claude.json");
 
-    // Get the .claude.json path from home directory
-    let home_dir = dirs::home_dir().ok_or_else(|| "Unable to get user home directory".to_string())?;
-
-    let claude_config_path = home_dir.join(".claude.json");
+    let claude_config_path = claude_json_path()?;
 
     if !claude_config_path.exists() {
         return Err(".claude.json configuration file not found".to_string());
@@ -753,11 +774,1042 @@ pub async fn mcp_save_project_config(
 
     let mcp_json_path = PathBuf::from(&project_path).join(".mcp.json");
 
-    let json_content = serde_json::to_string_pretty(&config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    let value =
+        serde_json::to_value(&config).map_err(|e| format!("Failed to serialize config: {}", e))?;
 
-    fs::write(&mcp_json_path, json_content)
+    super::config_io::write_json_atomic(&mcp_json_path, &value)
         .map_err(|e| format!("Failed to write .mcp.json: {}", e))?;
 
     Ok("Project MCP configuration saved".to_string())
 }
+
+// ============ Direct Config Management ============
+
+/// Key used to park disabled servers next to `mcpServers` in the same file
+const DISABLED_SERVERS_KEY: &str = "disabledMcpServers";
+
+/// Default timeout for the initialize handshake
+const DEFAULT_HANDSHAKE_TIMEOUT_SECS: u64 = 15;
+
+/// Server definition as stored in `~/.claude.json` or `.mcp.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MCPServerDefinition {
+    /// Server name/identifier (the key under `mcpServers`)
+    pub name: String,
+    /// Transport type: "stdio", "sse" or "http"
+    pub transport: String,
+    /// Command to execute (for stdio)
+    pub command: Option<String>,
+    /// Command arguments (for stdio)
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Environment variables (for stdio)
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// URL endpoint (for sse/http)
+    pub url: Option<String>,
+    /// Extra request headers (for sse/http)
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+/// A server found in one of the configuration files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MCPConfigEntry {
+    pub definition: MCPServerDefinition,
+    /// Configuration scope: "local", "project", or "user"
+    pub scope: String,
+    /// Whether the server is enabled (disabled servers are kept under `disabledMcpServers`)
+    pub enabled: bool,
+    /// File the entry was read from
+    pub config_path: String,
+}
+
+/// Result of validating a server definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MCPValidationResult {
+    pub valid: bool,
+    /// Absolute path of the resolved executable (stdio only)
+    pub resolved_command: Option<String>,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Result of a test connection (spawn + initialize handshake)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MCPConnectionTestResult {
+    pub success: bool,
+    pub message: String,
+    pub server_name: Option<String>,
+    pub server_version: Option<String>,
+    pub protocol_version: Option<String>,
+    pub latency_ms: u64,
+}
+
+/// Resolves the config file (and the `projects` key for local scope) for a scope
+pub(crate) fn mcp_config_location(
+    scope: &str,
+    project_path: Option<&str>,
+) -> Result<(PathBuf, Option<String>), String> {
+    match scope {
+        "user" => Ok((claude_json_path()?, None)),
+        "local" => {
+            let path = project_path.ok_or("Project path required for local scope")?;
+            Ok((claude_json_path()?, Some(path.to_string())))
+        }
+        "project" => {
+            let path = project_path.ok_or("Project path required for project scope")?;
+            Ok((PathBuf::from(path).join(".mcp.json"), None))
+        }
+        _ => Err(format!("Invalid scope: {}", scope)),
+    }
+}
+
+/// Reads a JSON config file, returning an empty object if it does not exist
+fn read_json_config(path: &Path) -> Result<serde_json::Value, String> {
+    if !path.exists() {
+        return Ok(serde_json::json!({}));
+    }
+
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    if content.trim().is_empty() {
+        return Ok(serde_json::json!({}));
+    }
+
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+/// Writes a JSON config file with pretty formatting, atomically and with a backup
+fn write_json_config(path: &Path, value: &serde_json::Value) -> Result<(), String> {
+    super::config_io::write_json_atomic(path, value).map(|_| ())
+}
+
+/// Returns the object that holds `mcpServers` (the root, or `projects[key]` for local scope)
+fn servers_container_mut<'a>(
+    root: &'a mut serde_json::Value,
+    project_key: Option<&str>,
+) -> Result<&'a mut serde_json::Map<String, serde_json::Value>, String> {
+    if !root.is_object() {
+        *root = serde_json::json!({});
+    }
+
+    let root_obj = root.as_object_mut().unwrap();
+
+    match project_key {
+        None => Ok(root_obj),
+        Some(key) => {
+            let projects = root_obj
+                .entry("projects")
+                .or_insert_with(|| serde_json::json!({}))
+                .as_object_mut()
+                .ok_or("projects is not an object")?;
+
+            projects
+                .entry(key.to_string())
+                .or_insert_with(|| serde_json::json!({}))
+                .as_object_mut()
+                .ok_or_else(|| format!("Project entry for {} is not an object", key))
+        }
+    }
+}
+
+/// Returns the named server map inside a container, creating it if needed
+fn server_map_mut<'a>(
+    container: &'a mut serde_json::Map<String, serde_json::Value>,
+    key: &str,
+) -> Result<&'a mut serde_json::Map<String, serde_json::Value>, String> {
+    container
+        .entry(key.to_string())
+        .or_insert_with(|| serde_json::json!({}))
+        .as_object_mut()
+        .ok_or_else(|| format!("{} is not an object", key))
+}
+
+/// Converts a definition into the JSON shape Claude Code expects
+fn definition_to_json(definition: &MCPServerDefinition) -> serde_json::Value {
+    match definition.transport.as_str() {
+        "sse" | "http" => {
+            let mut value = serde_json::json!({
+                "type": definition.transport,
+                "url": definition.url.clone().unwrap_or_default(),
+            });
+            if !definition.headers.is_empty() {
+                value["headers"] = serde_json::json!(definition.headers);
+            }
+            value
+        }
+        _ => serde_json::json!({
+            "type": "stdio",
+            "command": definition.command.clone().unwrap_or_default(),
+            "args": definition.args,
+            "env": definition.env,
+        }),
+    }
+}
+
+/// Parses a server entry from a config file
+fn definition_from_json(name: &str, value: &serde_json::Value) -> MCPServerDefinition {
+    let string_map = |key: &str| -> HashMap<String, String> {
+        value
+            .get(key)
+            .and_then(|v| v.as_object())
+            .map(|obj| {
+                obj.iter()
+                    .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let url = value
+        .get("url")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+    let transport = value
+        .get("type")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| if url.is_some() { "sse" } else { "stdio" }.to_string());
+
+    MCPServerDefinition {
+        name: name.to_string(),
+        transport,
+        command: value
+            .get("command")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
+        args: value
+            .get("args")
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        env: string_map("env"),
+        url,
+        headers: string_map("headers"),
+    }
+}
+
+/// Collects the enabled and disabled servers from one container object
+fn collect_config_entries(
+    container: &serde_json::Value,
+    scope: &str,
+    config_path: &Path,
+    entries: &mut Vec<MCPConfigEntry>,
+) {
+    for (key, enabled) in [("mcpServers", true), (DISABLED_SERVERS_KEY, false)] {
+        if let Some(servers) = container.get(key).and_then(|v| v.as_object()) {
+            for (name, value) in servers {
+                entries.push(MCPConfigEntry {
+                    definition: definition_from_json(name, value),
+                    scope: scope.to_string(),
+                    enabled,
+                    config_path: config_path.to_string_lossy().to_string(),
+                });
+            }
+        }
+    }
+}
+
+/// Reads every server configured for a scope
+pub fn read_scope_entries(
+    scope: &str,
+    project_path: Option<&str>,
+) -> Result<Vec<MCPConfigEntry>, String> {
+    let (config_path, project_key) = mcp_config_location(scope, project_path)?;
+    let root = read_json_config(&config_path)?;
+
+    let container = match &project_key {
+        Some(key) => root
+            .get("projects")
+            .and_then(|p| p.get(key))
+            .cloned()
+            .unwrap_or(serde_json::Value::Null),
+        None => root,
+    };
+
+    let mut entries = Vec::new();
+    collect_config_entries(&container, scope, &config_path, &mut entries);
+    Ok(entries)
+}
+
+/// Writes servers into the `mcpServers` map of a scope, returning how many replaced an entry
+///
+/// Re-added servers are always enabled, so any copy under `disabledMcpServers` is dropped.
+pub fn upsert_scope_servers(
+    scope: &str,
+    project_path: Option<&str>,
+    servers: &[MCPServerDefinition],
+) -> Result<usize, String> {
+    let (config_path, project_key) = mcp_config_location(scope, project_path)?;
+    let mut root = read_json_config(&config_path)?;
+    let container = servers_container_mut(&mut root, project_key.as_deref())?;
+
+    if let Some(disabled) = container
+        .get_mut(DISABLED_SERVERS_KEY)
+        .and_then(|v| v.as_object_mut())
+    {
+        for server in servers {
+            disabled.remove(&server.name);
+        }
+    }
+
+    let enabled = server_map_mut(container, "mcpServers")?;
+    let mut replaced = 0;
+    for server in servers {
+        if enabled
+            .insert(server.name.clone(), definition_to_json(server))
+            .is_some()
+        {
+            replaced += 1;
+        }
+    }
+
+    write_json_config(&config_path, &root)?;
+    Ok(replaced)
+}
+
+/// Resolves an executable name against PATH (honouring PATHEXT on Windows)
+pub fn resolve_executable(command: &str) -> Option<PathBuf> {
+    let candidate = PathBuf::from(command);
+    if candidate.components().count() > 1 || candidate.is_absolute() {
+        return if candidate.is_file() {
+            Some(candidate)
+        } else {
+            None
+        };
+    }
+
+    #[cfg(target_os = "windows")]
+    let extensions: Vec<String> = std::env::var("PATHEXT")
+        .unwrap_or_else(|_| ".EXE;.CMD;.BAT;.COM".to_string())
+        .split(';')
+        .map(|s| s.to_lowercase())
+        .collect();
+
+    let path_var = std::env::var_os("PATH")?;
+    for dir in std::env::split_paths(&path_var) {
+        let full_path = dir.join(command);
+        if full_path.is_file() {
+            return Some(full_path);
+        }
+
+        #[cfg(target_os = "windows")]
+        for ext in &extensions {
+            let with_ext = dir.join(format!("{}{}", command, ext));
+            if with_ext.is_file() {
+                return Some(with_ext);
+            }
+        }
+    }
+
+    None
+}
+
+/// Validates a server definition without starting it
+pub fn validate_server_definition(definition: &MCPServerDefinition) -> MCPValidationResult {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+    let mut resolved_command = None;
+
+    if definition.name.trim().is_empty() {
+        errors.push("Server name cannot be empty".to_string());
+    } else if !definition
+        .name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+    {
+        errors.push("Server name may only contain letters, digits, '-', '_' and '.'".to_string());
+    }
+
+    match definition.transport.as_str() {
+        "stdio" => match definition.command.as_deref().map(str::trim) {
+            Some(command) if !command.is_empty() => match resolve_executable(command) {
+                Some(path) => resolved_command = Some(path.to_string_lossy().to_string()),
+                None => errors.push(format!("Executable not found: {}", command)),
+            },
+            _ => errors.push("Command is required for stdio transport".to_string()),
+        },
+        "sse" | "http" => match definition.url.as_deref().map(str::trim) {
+            Some(url) if url.starts_with("http://") || url.starts_with("https://") => {
+                if url.starts_with("http://")
+                    && !url.contains("localhost")
+                    && !url.contains("127.0.0.1")
+                {
+                    warnings.push("URL uses plain HTTP for a remote host".to_string());
+                }
+            }
+            Some(url) if !url.is_empty() => {
+                errors.push(format!("URL must start with http:// or https://: {}", url))
+            }
+            _ => errors.push(format!(
+                "URL is required for {} transport",
+                definition.transport
+            )),
+        },
+        other => errors.push(format!("Unsupported transport: {}", other)),
+    }
+
+    if definition.transport == "stdio" && !definition.headers.is_empty() {
+        warnings.push("Headers are ignored for stdio transport".to_string());
+    }
+
+    MCPValidationResult {
+        valid: errors.is_empty(),
+        resolved_command,
+        errors,
+        warnings,
+    }
+}
+
+/// Builds the JSON-RPC initialize request sent during the handshake
+fn initialize_request() -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {
+            "protocolVersion": "2024-11-05",
+            "capabilities": {},
+            "clientInfo": {
+                "name": "claude-workbench",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+        },
+    })
+}
+
+/// Spawns a stdio server and waits for its initialize response
+async fn handshake_stdio(
+    definition: &MCPServerDefinition,
+    timeout: std::time::Duration,
+    registry: Option<&ProcessRegistry>,
+) -> Result<serde_json::Value, String> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let command = definition
+        .command
+        .as_deref()
+        .ok_or("Command is required for stdio transport")?;
+    let program = resolve_executable(command)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|| command.to_string());
+
+    let mut cmd = tokio::process::Command::from(create_command_with_env(&program));
+    cmd.args(&definition.args)
+        .envs(&definition.env)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true);
+
+    #[cfg(target_os = "windows")]
+    {
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+
+    let (mut child, audit) = cmd
+        .audited_spawn("mcp")
+        .map_err(|e| format!("Failed to start server: {}", e))?;
+    let run_id = match (registry, child.id()) {
+        (Some(registry), Some(pid)) => registry
+            .register_tracked_process(
+                ProcessType::McpServer {
+                    server_name: definition.name.clone(),
+                },
+                pid,
+                String::new(),
+                program.clone(),
+            )
+            .ok(),
+        _ => None,
+    };
+    let mut stdin = child.stdin.take().ok_or("Failed to open server stdin")?;
+    let stdout = child.stdout.take().ok_or("Failed to open server stdout")?;
+
+    let result = tokio::time::timeout(timeout, async {
+        let request = format!("{}\n", initialize_request());
+        stdin
+            .write_all(request.as_bytes())
+            .await
+            .map_err(|e| format!("Failed to send initialize request: {}", e))?;
+        stdin
+            .flush()
+            .await
+            .map_err(|e| format!("Failed to send initialize request: {}", e))?;
+
+        let mut lines = BufReader::new(stdout).lines();
+        while let Some(line) = lines
+            .next_line()
+            .await
+            .map_err(|e| format!("Failed to read server output: {}", e))?
+        {
+            // Servers may log to stdout before answering; skip anything that isn't our reply
+            let message = match serde_json::from_str::<serde_json::Value>(line.trim()) {
+                Ok(message) => message,
+                Err(_) => continue,
+            };
+
+            if message.get("id") == Some(&serde_json::json!(1)) {
+                if let Some(error) = message.get("error") {
+                    return Err(format!("Server rejected initialize: {}", error));
+                }
+                return Ok(message
+                    .get("result")
+                    .cloned()
+                    .unwrap_or(serde_json::Value::Null));
+            }
+        }
+
+        Err("Server exited before answering initialize".to_string())
+    })
+    .await
+    .unwrap_or_else(|_| Err("Timed out waiting for initialize response".to_string()));
+
+    let _ = child.kill().await;
+    audit.finish(&child.wait().await);
+    if let (Some(registry), Some(run_id)) = (registry, run_id) {
+        let _ = registry.unregister_process(run_id);
+    }
+    result
+}
+
+/// Connects to an sse/http server and performs the equivalent handshake
+async fn handshake_remote(
+    definition: &MCPServerDefinition,
+    timeout: std::time::Duration,
+) -> Result<serde_json::Value, String> {
+    let url = definition
+        .url
+        .as_deref()
+        .ok_or_else(|| format!("URL is required for {} transport", definition.transport))?;
+
+    let client = super::proxy::apply_to_client(reqwest::Client::builder())
+        .timeout(timeout)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let mut request = if definition.transport == "sse" {
+        client.get(url).header("Accept", "text/event-stream")
+    } else {
+        client
+            .post(url)
+            .header("Accept", "application/json, text/event-stream")
+            .json(&initialize_request())
+    };
+    for (key, value) in &definition.headers {
+        request = request.header(key.as_str(), value.as_str());
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Failed to connect: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Server responded with HTTP {}", response.status()));
+    }
+
+    let is_json = response
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.contains("application/json"))
+        .unwrap_or(false);
+
+    // SSE endpoints keep the stream open, so a successful status is the handshake
+    if definition.transport == "http" && is_json {
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| format!("Invalid initialize response: {}", e))?;
+        if let Some(error) = body.get("error") {
+            return Err(format!("Server rejected initialize: {}", error));
+        }
+        return Ok(body
+            .get("result")
+            .cloned()
+            .unwrap_or(serde_json::Value::Null));
+    }
+
+    Ok(serde_json::Value::Null)
+}
+
+/// Spawns (or connects to) a server and performs the MCP initialize handshake
+pub async fn handshake_server(
+    definition: &MCPServerDefinition,
+    timeout: std::time::Duration,
+    registry: Option<&ProcessRegistry>,
+) -> MCPConnectionTestResult {
+    let start_time = std::time::Instant::now();
+
+    let outcome = match definition.transport.as_str() {
+        "stdio" => handshake_stdio(definition, timeout, registry).await,
+        "sse" | "http" => handshake_remote(definition, timeout).await,
+        other => Err(format!("Unsupported transport: {}", other)),
+    };
+
+    let latency_ms = start_time.elapsed().as_millis() as u64;
+
+    match outcome {
+        Ok(result) => {
+            let server_info = result.get("serverInfo");
+            let server_name = server_info
+                .and_then(|i| i.get("name"))
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string());
+
+            MCPConnectionTestResult {
+                success: true,
+                message: format!(
+                    "Connected to {} in {} ms",
+                    server_name.as_deref().unwrap_or(&definition.name),
+                    latency_ms
+                ),
+                server_name,
+                server_version: server_info
+                    .and_then(|i| i.get("version"))
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string()),
+                protocol_version: result
+                    .get("protocolVersion")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string()),
+                latency_ms,
+            }
+        }
+        Err(e) => MCPConnectionTestResult {
+            success: false,
+            message: e,
+            server_name: None,
+            server_version: None,
+            protocol_version: None,
+            latency_ms,
+        },
+    }
+}
+
+/// Lists servers from `~/.claude.json` (user and local scope) and the project's `.mcp.json`
+#[tauri::command]
+pub async fn mcp_config_list(project_path: Option<String>) -> Result<Vec<MCPConfigEntry>, String> {
+    info!(
+        "Listing MCP servers from config files, project: {:?}",
+        project_path
+    );
+
+    let mut entries = read_scope_entries("user", None)?;
+    if let Some(path) = project_path.as_deref() {
+        entries.extend(read_scope_entries("local", Some(path))?);
+        entries.extend(read_scope_entries("project", Some(path))?);
+    }
+
+    Ok(entries)
+}
+
+/// Adds (or replaces) a server in the config file for the given scope
+///
+/// Unless `test_connection` is false the server is started and must complete the
+/// initialize handshake before anything is written.
+#[tauri::command]
+pub async fn mcp_config_add(
+    registry: State<'_, ProcessRegistryState>,
+    server: MCPServerDefinition,
+    scope: String,
+    project_path: Option<String>,
+    test_connection: Option<bool>,
+) -> Result<AddServerResult, String> {
+    info!("Adding MCP server {} to {} config", server.name, scope);
+
+    let validation = validate_server_definition(&server);
+    if !validation.valid {
+        return Ok(AddServerResult {
+            success: false,
+            message: validation.errors.join("; "),
+            server_name: None,
+        });
+    }
+
+    if test_connection.unwrap_or(true) {
+        let test = handshake_server(
+            &server,
+            std::time::Duration::from_secs(DEFAULT_HANDSHAKE_TIMEOUT_SECS),
+            Some(&registry.0),
+        )
+        .await;
+        if !test.success {
+            error!(
+                "MCP server {} failed test connection: {}",
+                server.name, test.message
+            );
+            return Ok(AddServerResult {
+                success: false,
+                message: format!("Test connection failed: {}", test.message),
+                server_name: None,
+            });
+        }
+    }
+
+    let existed = upsert_scope_servers(
+        &scope,
+        project_path.as_deref(),
+        std::slice::from_ref(&server),
+    )? > 0;
+
+    info!("Saved MCP server {} to {} config", server.name, scope);
+    Ok(AddServerResult {
+        success: true,
+        message: format!(
+            "{} MCP server {} in {} scope",
+            if existed { "Updated" } else { "Added" },
+            server.name,
+            scope
+        ),
+        server_name: Some(server.name),
+    })
+}
+
+/// Removes a server (enabled or disabled) from the config file for the given scope
+#[tauri::command]
+pub async fn mcp_config_remove(
+    name: String,
+    scope: String,
+    project_path: Option<String>,
+) -> Result<String, String> {
+    info!("Removing MCP server {} from {} config", name, scope);
+
+    let (config_path, project_key) = mcp_config_location(&scope, project_path.as_deref())?;
+    let mut root = read_json_config(&config_path)?;
+    let container = servers_container_mut(&mut root, project_key.as_deref())?;
+
+    let mut removed = false;
+    for key in ["mcpServers", DISABLED_SERVERS_KEY] {
+        if let Some(servers) = container.get_mut(key).and_then(|v| v.as_object_mut()) {
+            removed |= servers.remove(&name).is_some();
+        }
+    }
+
+    if !removed {
+        return Err(format!("MCP server {} not found in {} scope", name, scope));
+    }
+
+    write_json_config(&config_path, &root)?;
+    Ok(format!("Removed MCP server {}", name))
+}
+
+/// Enables or disables a server by moving it between `mcpServers` and `disabledMcpServers`
+#[tauri::command]
+pub async fn mcp_config_set_enabled(
+    name: String,
+    scope: String,
+    project_path: Option<String>,
+    enabled: bool,
+) -> Result<String, String> {
+    info!(
+        "Setting MCP server {} enabled={} in {} config",
+        name, enabled, scope
+    );
+
+    let (from_key, to_key) = if enabled {
+        (DISABLED_SERVERS_KEY, "mcpServers")
+    } else {
+        ("mcpServers", DISABLED_SERVERS_KEY)
+    };
+
+    let (config_path, project_key) = mcp_config_location(&scope, project_path.as_deref())?;
+    let mut root = read_json_config(&config_path)?;
+    let container = servers_container_mut(&mut root, project_key.as_deref())?;
+
+    let value = container
+        .get_mut(from_key)
+        .and_then(|v| v.as_object_mut())
+        .and_then(|servers| servers.remove(&name));
+
+    let value = match value {
+        Some(value) => value,
+        None => {
+            let already = container
+                .get(to_key)
+                .and_then(|v| v.as_object())
+                .map(|servers| servers.contains_key(&name))
+                .unwrap_or(false);
+            if already {
+                return Ok(format!(
+                    "MCP server {} is already {}",
+                    name,
+                    if enabled { "enabled" } else { "disabled" }
+                ));
+            }
+            return Err(format!("MCP server {} not found in {} scope", name, scope));
+        }
+    };
+
+    server_map_mut(container, to_key)?.insert(name.clone(), value);
+
+    // Drop an empty disabled map so untouched files stay clean
+    if container
+        .get(DISABLED_SERVERS_KEY)
+        .and_then(|v| v.as_object())
+        .map(|m| m.is_empty())
+        .unwrap_or(false)
+    {
+        container.remove(DISABLED_SERVERS_KEY);
+    }
+
+    write_json_config(&config_path, &root)?;
+    Ok(format!(
+        "MCP server {} {}",
+        name,
+        if enabled { "enabled" } else { "disabled" }
+    ))
+}
+
+/// Validates a server definition (name, transport, executable on PATH, URL format)
+#[tauri::command]
+pub async fn mcp_validate_server(
+    server: MCPServerDefinition,
+) -> Result<MCPValidationResult, String> {
+    Ok(validate_server_definition(&server))
+}
+
+/// Starts a server from its definition and performs the initialize handshake
+#[tauri::command]
+pub async fn mcp_test_server_config(
+    registry: State<'_, ProcessRegistryState>,
+    server: MCPServerDefinition,
+    timeout_secs: Option<u64>,
+) -> Result<MCPConnectionTestResult, String> {
+    info!("Test-connecting MCP server: {}", server.name);
+
+    let validation = validate_server_definition(&server);
+    if !validation.valid {
+        return Ok(MCPConnectionTestResult {
+            success: false,
+            message: validation.errors.join("; "),
+            server_name: None,
+            server_version: None,
+            protocol_version: None,
+            latency_ms: 0,
+        });
+    }
+
+    let timeout =
+        std::time::Duration::from_secs(timeout_secs.unwrap_or(DEFAULT_HANDSHAKE_TIMEOUT_SECS));
+    Ok(handshake_server(&server, timeout, Some(&registry.0)).await)
+}
+
+// ============ Import From Other Tools ============
+
+/// A server whose name already exists in the target scope with a different definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MCPImportConflict {
+    pub name: String,
+    pub existing: MCPServerDefinition,
+    pub incoming: MCPServerDefinition,
+}
+
+/// Outcome of importing servers from another tool's config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MCPImportReport {
+    /// Source tool: "claude-desktop" or "cursor"
+    pub source: String,
+    /// Config files that were read
+    pub config_paths: Vec<String>,
+    /// Servers written to the target scope
+    pub imported: Vec<String>,
+    /// Servers already present with an identical definition
+    pub duplicates: Vec<String>,
+    /// Servers present with a different definition (overwritten only when requested)
+    pub conflicts: Vec<MCPImportConflict>,
+    /// Entries that could not be mapped
+    pub failed: Vec<ImportServerResult>,
+}
+
+/// Candidate locations of the Claude Desktop config file
+fn claude_desktop_config_paths() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+
+    // ~/Library/Application Support on macOS, %APPDATA% on Windows, ~/.config on Linux
+    if let Some(config_dir) = dirs::config_dir() {
+        paths.push(config_dir.join("Claude").join("claude_desktop_config.json"));
+    }
+
+    paths
+}
+
+/// Candidate locations of Cursor MCP config files (global first, project last so it wins)
+fn cursor_config_paths(project_path: Option<&str>) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+
+    if let Some(home_dir) = dirs::home_dir() {
+        paths.push(home_dir.join(".cursor").join("mcp.json"));
+    }
+    if let Some(path) = project_path {
+        paths.push(PathBuf::from(path).join(".cursor").join("mcp.json"));
+    }
+
+    paths
+}
+
+/// Reads `mcpServers` from the given files, later files overriding earlier ones
+fn read_external_servers(
+    paths: &[PathBuf],
+    failed: &mut Vec<ImportServerResult>,
+) -> Result<(Vec<String>, Vec<MCPServerDefinition>), String> {
+    let mut read_paths = Vec::new();
+    let mut servers: Vec<MCPServerDefinition> = Vec::new();
+
+    for path in paths.iter().filter(|p| p.exists()) {
+        let config = read_json_config(path)?;
+        read_paths.push(path.to_string_lossy().to_string());
+
+        let entries = match config.get("mcpServers").and_then(|v| v.as_object()) {
+            Some(entries) => entries,
+            None => continue,
+        };
+
+        for (name, value) in entries {
+            if !value.is_object() {
+                failed.push(ImportServerResult {
+                    name: name.clone(),
+                    success: false,
+                    error: Some("Server entry is not an object".to_string()),
+                });
+                continue;
+            }
+
+            let definition = definition_from_json(name, value);
+            let validation = validate_server_definition(&definition);
+            if !validation.valid {
+                failed.push(ImportServerResult {
+                    name: name.clone(),
+                    success: false,
+                    error: Some(validation.errors.join("; ")),
+                });
+                continue;
+            }
+
+            servers.retain(|s| s.name != definition.name);
+            servers.push(definition);
+        }
+    }
+
+    Ok((read_paths, servers))
+}
+
+/// Compares incoming servers with the target scope and writes the new ones
+fn import_servers(
+    source: &str,
+    paths: Vec<PathBuf>,
+    scope: &str,
+    project_path: Option<&str>,
+    overwrite_conflicts: bool,
+) -> Result<MCPImportReport, String> {
+    let mut failed = Vec::new();
+    let (config_paths, incoming) = read_external_servers(&paths, &mut failed)?;
+
+    if config_paths.is_empty() {
+        return Err(format!(
+            "No {} MCP configuration found. Looked in: {}",
+            source,
+            paths
+                .iter()
+                .map(|p| p.to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    let existing: HashMap<String, MCPServerDefinition> = read_scope_entries(scope, project_path)?
+        .into_iter()
+        .map(|entry| (entry.definition.name.clone(), entry.definition))
+        .collect();
+
+    let mut to_write = Vec::new();
+    let mut duplicates = Vec::new();
+    let mut conflicts = Vec::new();
+
+    for server in incoming {
+        match existing.get(&server.name) {
+            Some(current) if definition_to_json(current) == definition_to_json(&server) => {
+                duplicates.push(server.name);
+            }
+            Some(current) => {
+                if overwrite_conflicts {
+                    to_write.push(server.clone());
+                }
+                conflicts.push(MCPImportConflict {
+                    name: server.name.clone(),
+                    existing: current.clone(),
+                    incoming: server,
+                });
+            }
+            None => to_write.push(server),
+        }
+    }
+
+    if !to_write.is_empty() {
+        upsert_scope_servers(scope, project_path, &to_write)?;
+    }
+
+    info!(
+        "Imported {} MCP servers from {} ({} duplicates, {} conflicts, {} failed)",
+        to_write.len(),
+        source,
+        duplicates.len(),
+        conflicts.len(),
+        failed.len()
+    );
+
+    Ok(MCPImportReport {
+        source: source.to_string(),
+        config_paths,
+        imported: to_write.into_iter().map(|s| s.name).collect(),
+        duplicates,
+        conflicts,
+        failed,
+    })
+}
+
+/// Imports servers from the Claude Desktop app's `claude_desktop_config.json`
+#[tauri::command]
+pub async fn import_mcp_from_claude_desktop(
+    scope: String,
+    project_path: Option<String>,
+    overwrite_conflicts: Option<bool>,
+) -> Result<MCPImportReport, String> {
+    info!(
+        "Importing MCP servers from Claude Desktop into {} scope",
+        scope
+    );
+
+    import_servers(
+        "claude-desktop",
+        claude_desktop_config_paths(),
+        &scope,
+        project_path.as_deref(),
+        overwrite_conflicts.unwrap_or(false),
+    )
+}
+
+/// Imports servers from Cursor's global `~/.cursor/mcp.json` and the project's `.cursor/mcp.json`
+#[tauri::command]
+pub async fn import_mcp_from_cursor(
+    scope: String,
+    project_path: Option<String>,
+    overwrite_conflicts: Option<bool>,
+) -> Result<MCPImportReport, String> {
+    info!("Importing MCP servers from Cursor into {} scope", scope);
+
+    import_servers(
+        "cursor",
+        cursor_config_paths(project_path.as_deref()),
+        &scope,
+        project_path.as_deref(),
+        overwrite_conflicts.unwrap_or(false),
+    )
+}
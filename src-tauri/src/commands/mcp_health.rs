@@ -0,0 +1,445 @@
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+/// MCP server health monitoring
+///
+/// Periodically performs the initialize handshake against every enabled MCP server,
+/// records latency and failures, and emits `mcp-server-down` / `mcp-server-recovered`
+/// events so the frontend can show a live status panel. The servers themselves are
+/// run by the Claude CLI, so a server that is down can only be re-probed with
+/// backoff until it answers again, not restarted from here.
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, State};
+
+use super::mcp::{handshake_server, read_scope_entries, MCPConfigEntry, ServerStatus};
+use super::retry::{Failure, RetryPolicy};
+use crate::events::{self, AppEvent};
+use crate::process::ProcessRegistryState;
+
+/// Configuration for the health monitor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpHealthConfig {
+    /// Enable periodic health checks (spawning servers is not free, so off by default)
+    pub enabled: bool,
+    /// Seconds between check rounds (default: 120)
+    pub interval_secs: u64,
+    /// Handshake timeout per server in seconds (default: 10)
+    pub timeout_secs: u64,
+    /// Consecutive failures before a server is reported down (default: 2)
+    pub failure_threshold: u32,
+    /// Re-probe a server with backoff as soon as it is reported down, instead of
+    /// waiting for the next round
+    pub auto_reprobe: bool,
+    /// Maximum re-probes per outage (default: 3)
+    pub max_reprobe_attempts: u32,
+    /// Re-probe policies by server name, for servers that need other limits or
+    /// backoff than `max_reprobe_attempts`
+    #[serde(default)]
+    pub reprobe_policies: HashMap<String, RetryPolicy>,
+}
+
+impl McpHealthConfig {
+    /// Re-probe policy of a server: its own, or exponential backoff from two
+    /// seconds up to `max_reprobe_attempts` attempts
+    pub fn reprobe_policy(&self, server_name: &str) -> RetryPolicy {
+        self.reprobe_policies
+            .get(server_name)
+            .cloned()
+            .unwrap_or_else(|| RetryPolicy {
+                max_attempts: self.max_reprobe_attempts,
+                initial_delay_ms: 2000,
+                max_delay_ms: 60_000,
+                ..RetryPolicy::default()
+            })
+    }
+}
+
+impl Default for McpHealthConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 120,
+            timeout_secs: 10,
+            failure_threshold: 2,
+            auto_reprobe: false,
+            max_reprobe_attempts: 3,
+            reprobe_policies: HashMap::new(),
+        }
+    }
+}
+
+/// Health record for a single configured server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpServerHealth {
+    pub name: String,
+    pub scope: String,
+    pub transport: String,
+    pub healthy: bool,
+    pub last_latency_ms: Option<u64>,
+    pub avg_latency_ms: Option<u64>,
+    pub consecutive_failures: u32,
+    pub total_checks: u64,
+    pub total_failures: u64,
+    /// Re-probes after the server was reported down
+    pub reprobe_count: u32,
+    pub last_error: Option<String>,
+    /// Unix timestamp (seconds) of the last check
+    pub last_checked: Option<u64>,
+}
+
+impl McpServerHealth {
+    fn new(entry: &MCPConfigEntry) -> Self {
+        Self {
+            name: entry.definition.name.clone(),
+            scope: entry.scope.clone(),
+            transport: entry.definition.transport.clone(),
+            healthy: true,
+            last_latency_ms: None,
+            avg_latency_ms: None,
+            consecutive_failures: 0,
+            total_checks: 0,
+            total_failures: 0,
+            reprobe_count: 0,
+            last_error: None,
+            last_checked: None,
+        }
+    }
+}
+
+/// Health monitor state
+pub struct McpHealthMonitor {
+    pub health: Arc<Mutex<HashMap<String, McpServerHealth>>>,
+    pub config: Arc<Mutex<McpHealthConfig>>,
+    pub project_paths: Arc<Mutex<Vec<String>>>,
+    pub is_monitoring: Arc<Mutex<bool>>,
+    /// Bumped by every start, so a loop left over from before a stop exits
+    /// instead of running next to the new one
+    generation: Arc<Mutex<u64>>,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn health_key(entry: &MCPConfigEntry) -> String {
+    format!("{}:{}", entry.scope, entry.definition.name)
+}
+
+impl McpHealthMonitor {
+    pub fn new() -> Self {
+        Self {
+            health: Arc::new(Mutex::new(HashMap::new())),
+            config: Arc::new(Mutex::new(McpHealthConfig::default())),
+            project_paths: Arc::new(Mutex::new(Vec::new())),
+            is_monitoring: Arc::new(Mutex::new(false)),
+            generation: Arc::new(Mutex::new(0)),
+        }
+    }
+
+    /// Collects every enabled server from user scope and the watched projects
+    fn enabled_servers(&self) -> Result<Vec<MCPConfigEntry>, String> {
+        let project_paths = self
+            .project_paths
+            .lock()
+            .map_err(|e| e.to_string())?
+            .clone();
+
+        let mut entries = read_scope_entries("user", None)?;
+        for path in &project_paths {
+            entries.extend(read_scope_entries("local", Some(path))?);
+            entries.extend(read_scope_entries("project", Some(path))?);
+        }
+
+        Ok(entries.into_iter().filter(|e| e.enabled).collect())
+    }
+
+    /// Runs one check round over all enabled servers
+    pub async fn check_all(&self, app: &AppHandle) -> Result<Vec<McpServerHealth>, String> {
+        let config = self.config.lock().map_err(|e| e.to_string())?.clone();
+        let entries = self.enabled_servers()?;
+        let timeout = Duration::from_secs(config.timeout_secs);
+
+        // Forget servers that were removed or disabled since the last round
+        {
+            let keys: Vec<String> = entries.iter().map(health_key).collect();
+            let mut health = self.health.lock().map_err(|e| e.to_string())?;
+            health.retain(|key, _| keys.contains(key));
+        }
+
+        for entry in &entries {
+            let key = health_key(entry);
+            let registry = app.state::<ProcessRegistryState>();
+            let result = handshake_server(&entry.definition, timeout, Some(&registry.0)).await;
+            debug!(
+                "MCP health check {}: success={} ({} ms)",
+                key, result.success, result.latency_ms
+            );
+
+            let (went_down, snapshot) = {
+                let mut health = self.health.lock().map_err(|e| e.to_string())?;
+                let record = health
+                    .entry(key.clone())
+                    .or_insert_with(|| McpServerHealth::new(entry));
+                let was_healthy = record.healthy;
+
+                record.total_checks += 1;
+                record.last_checked = Some(now_secs());
+
+                if result.success {
+                    record.last_latency_ms = Some(result.latency_ms);
+                    record.avg_latency_ms = Some(match record.avg_latency_ms {
+                        Some(avg) => (avg * 4 + result.latency_ms) / 5,
+                        None => result.latency_ms,
+                    });
+                    record.consecutive_failures = 0;
+                    record.last_error = None;
+                    record.healthy = true;
+                    if !was_healthy {
+                        info!("MCP server {} recovered", key);
+                        events::emit(app, AppEvent::McpServerRecovered(record.clone()));
+                    }
+                } else {
+                    record.total_failures += 1;
+                    record.consecutive_failures += 1;
+                    record.last_error = Some(result.message.clone());
+                    if record.consecutive_failures >= config.failure_threshold {
+                        record.healthy = false;
+                    }
+                }
+
+                (was_healthy && !record.healthy, record.clone())
+            };
+
+            if went_down {
+                warn!(
+                    "MCP server {} is down: {}",
+                    key,
+                    snapshot.last_error.as_deref().unwrap_or("unknown error")
+                );
+                events::emit(app, AppEvent::McpServerDown(snapshot));
+
+                if config.auto_reprobe {
+                    self.reprobe_server(app, entry, &config).await?;
+                }
+            }
+        }
+
+        self.snapshot()
+    }
+
+    /// Repeats the handshake with backoff until it succeeds or the attempts run out
+    async fn reprobe_server(
+        &self,
+        app: &AppHandle,
+        entry: &MCPConfigEntry,
+        config: &McpHealthConfig,
+    ) -> Result<(), String> {
+        let key = health_key(entry);
+        let timeout = Duration::from_secs(config.timeout_secs);
+        let policy = config.reprobe_policy(&entry.definition.name);
+
+        for attempt in 1..=policy.max_attempts {
+            info!(
+                "Re-probing MCP server {} (attempt {}/{})",
+                key, attempt, policy.max_attempts
+            );
+            tokio::time::sleep(policy.delay(attempt)).await;
+
+            let registry = app.state::<ProcessRegistryState>();
+            let result = handshake_server(&entry.definition, timeout, Some(&registry.0)).await;
+
+            let mut health = self.health.lock().map_err(|e| e.to_string())?;
+            if let Some(record) = health.get_mut(&key) {
+                record.reprobe_count += 1;
+                record.last_checked = Some(now_secs());
+                if result.success {
+                    record.healthy = true;
+                    record.consecutive_failures = 0;
+                    record.last_error = None;
+                    record.last_latency_ms = Some(result.latency_ms);
+                    info!("MCP server {} answered a re-probe", key);
+                    events::emit(app, AppEvent::McpServerRecovered(record.clone()));
+                    return Ok(());
+                }
+                record.last_error = Some(result.message.clone());
+            }
+            drop(health);
+
+            let failure = Failure::Exit {
+                code: None,
+                stdout: "",
+                stderr: &result.message,
+            };
+            if !policy.should_retry(attempt, &failure) {
+                break;
+            }
+        }
+
+        warn!("Giving up re-probing MCP server {}", key);
+        Ok(())
+    }
+
+    /// Returns the current health records sorted by name
+    pub fn snapshot(&self) -> Result<Vec<McpServerHealth>, String> {
+        let health = self.health.lock().map_err(|e| e.to_string())?;
+        let mut records: Vec<McpServerHealth> = health.values().cloned().collect();
+        records.sort_by(|a, b| a.name.cmp(&b.name).then(a.scope.cmp(&b.scope)));
+        Ok(records)
+    }
+
+    /// Start the background monitoring loop
+    pub async fn start_monitoring(self: &Arc<Self>, app: AppHandle) -> Result<(), String> {
+        let mut is_monitoring = self.is_monitoring.lock().map_err(|e| e.to_string())?;
+
+        if *is_monitoring {
+            return Ok(()); // Already monitoring
+        }
+
+        *is_monitoring = true;
+        drop(is_monitoring);
+
+        let generation = {
+            let mut current = self.generation.lock().map_err(|e| e.to_string())?;
+            *current += 1;
+            *current
+        };
+        let monitor = self.clone();
+
+        tokio::spawn(async move {
+            info!("Starting MCP health monitoring loop");
+
+            loop {
+                let still_monitoring = monitor.is_monitoring.lock().map(|f| *f).unwrap_or(false);
+                let current = monitor.generation.lock().map(|g| *g == generation);
+                if !still_monitoring || !current.unwrap_or(false) {
+                    break;
+                }
+
+                let (enabled, interval_secs) = match monitor.config.lock() {
+                    Ok(config) => (config.enabled, config.interval_secs),
+                    Err(_) => break,
+                };
+
+                // Checks spawn every server, so they can wait while the user is away
+                if enabled && !super::idle::pauses_monitors(&app) {
+                    if let Err(e) = monitor.check_all(&app).await {
+                        warn!("MCP health check round failed: {}", e);
+                    }
+                }
+
+                tokio::time::sleep(Duration::from_secs(interval_secs.max(10))).await;
+            }
+
+            info!("MCP health monitoring stopped");
+        });
+
+        Ok(())
+    }
+
+    /// Stop background monitoring
+    pub fn stop_monitoring(&self) -> Result<(), String> {
+        let mut is_monitoring = self.is_monitoring.lock().map_err(|e| e.to_string())?;
+        *is_monitoring = false;
+        Ok(())
+    }
+}
+
+impl Default for McpHealthMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// State wrapper for McpHealthMonitor
+#[derive(Clone)]
+pub struct McpHealthState(pub Arc<McpHealthMonitor>);
+
+/// Converts health records into the legacy per-name status map
+pub fn health_to_status_map(records: &[McpServerHealth]) -> HashMap<String, ServerStatus> {
+    records
+        .iter()
+        .map(|record| {
+            (
+                record.name.clone(),
+                ServerStatus {
+                    running: record.healthy && record.last_checked.is_some(),
+                    error: record.last_error.clone(),
+                    last_checked: record.last_checked,
+                },
+            )
+        })
+        .collect()
+}
+
+// ============ Tauri Commands ============
+
+/// Get the latest health records for the status panel
+#[tauri::command]
+pub async fn get_mcp_health(
+    state: State<'_, McpHealthState>,
+) -> Result<Vec<McpServerHealth>, String> {
+    state.0.snapshot()
+}
+
+/// Run a check round immediately, regardless of the schedule
+#[tauri::command]
+pub async fn check_mcp_health_now(
+    state: State<'_, McpHealthState>,
+    app: AppHandle,
+) -> Result<Vec<McpServerHealth>, String> {
+    info!("Running MCP health check on demand");
+    state.0.check_all(&app).await
+}
+
+/// Get health monitor configuration
+#[tauri::command]
+pub async fn get_mcp_health_config(
+    state: State<'_, McpHealthState>,
+) -> Result<McpHealthConfig, String> {
+    let config = state.0.config.lock().map_err(|e| e.to_string())?;
+    Ok(config.clone())
+}
+
+/// Update health monitor configuration
+#[tauri::command]
+pub async fn update_mcp_health_config(
+    state: State<'_, McpHealthState>,
+    config: McpHealthConfig,
+) -> Result<(), String> {
+    info!("Updating MCP health monitor configuration");
+    let mut current = state.0.config.lock().map_err(|e| e.to_string())?;
+    *current = config;
+    Ok(())
+}
+
+/// Set which projects' local and project-scoped servers are monitored
+#[tauri::command]
+pub async fn set_mcp_health_projects(
+    state: State<'_, McpHealthState>,
+    project_paths: Vec<String>,
+) -> Result<(), String> {
+    let mut paths = state.0.project_paths.lock().map_err(|e| e.to_string())?;
+    *paths = project_paths;
+    Ok(())
+}
+
+/// Start MCP health monitoring
+#[tauri::command]
+pub async fn start_mcp_health_monitoring(
+    state: State<'_, McpHealthState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    info!("Starting MCP health monitoring");
+    state.0.start_monitoring(app).await
+}
+
+/// Stop MCP health monitoring
+#[tauri::command]
+pub async fn stop_mcp_health_monitoring(state: State<'_, McpHealthState>) -> Result<(), String> {
+    info!("Stopping MCP health monitoring");
+    state.0.stop_monitoring()
+}
@@ -1,17 +1,93 @@
+pub mod activity_report;
+pub mod activity_summaries;
+pub mod attachments;
+pub mod audio;
+pub mod checkpoints;
 pub mod claude;
+pub mod claude_md;
+pub mod claude_stream;
 pub mod clipboard;
+pub mod command_policy;
+pub mod config_io;
+pub mod config_merge;
+pub mod config_watcher;
 pub mod context_commands;
 pub mod context_manager;
+pub mod context_monitor;
+pub mod control_api;
+pub mod cost_attribution;
+pub mod crash_reports;
+pub mod credentials;
+pub mod data_location;
+pub mod data_usage;
+pub mod deep_link;
 pub mod enhanced_hooks;
+pub mod env_profiles;
+pub mod environment_doctor;
 pub mod extensions;
 pub mod file_operations;
+pub mod git_backend;
 pub mod git_stats;
+pub mod headless;
+pub mod hook_approval;
+pub mod hook_graph;
+pub mod hook_metrics;
+pub mod hook_output;
+pub mod hook_presets;
+pub mod hook_sandbox;
+pub mod idle;
+pub mod knowledge_base;
+pub mod locale;
+pub mod logs;
 pub mod mcp;
+pub mod mcp_health;
+pub mod models;
+pub mod network;
+pub mod notifications;
+pub mod observer;
+pub mod operations;
+pub mod orchestration;
+pub mod output_mirror;
+pub mod performance;
 pub mod permission_config;
+pub mod permissions;
+pub mod process_audit;
+pub mod processes;
+pub mod profile_archive;
+pub mod project_insights;
+pub mod project_search;
+pub mod project_tree;
+pub mod project_windows;
+pub mod projects;
+pub mod prompt_queue;
+pub mod prompt_templates;
 pub mod prompt_tracker;
 pub mod provider;
+pub mod proxy;
+pub mod quick_prompt;
+pub mod redaction;
+pub mod retention;
+pub mod retry;
+pub mod run_scheduler;
+pub mod session_batch;
+pub mod session_bundle;
+pub mod session_export;
+pub mod session_handoff;
+pub mod session_import;
+pub mod session_rewind;
+pub mod session_state;
+pub mod session_status;
+pub mod session_templates;
+pub mod settings_manager;
+pub mod settings_sync;
 pub mod simple_git;
 pub mod slash_commands;
 pub mod storage;
+pub mod telemetry;
+pub mod terminal;
+pub mod thinking_presets;
 pub mod translator;
+pub mod tray;
+pub mod updater;
 pub mod usage;
+pub mod workspace;
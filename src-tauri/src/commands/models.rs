@@ -0,0 +1,390 @@
+use log::{info, warn};
+/// Model catalog and model choice
+///
+/// The catalog lists the Claude models the app knows, with context size and
+/// price; usage costs are computed from it. The ids the UI offers ("sonnet",
+/// "opus", ...) are CLI aliases for the newest model of a family.
+///
+/// Each project may have a default model, used where no model is chosen
+/// explicitly, such as API-started sessions. `switch_session_model` changes the
+/// model of an existing session: every later resume of it uses the new model,
+/// whatever model the caller passes.
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use super::storage::AgentDb;
+use super::usage::{self, UsageRange};
+use crate::events::{self, AppEvent};
+
+/// `app_settings` key holding the default model of each project
+const PROJECT_DEFAULTS_KEY: &str = "project_default_models";
+
+/// USD per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPricing {
+    pub input: f64,
+    pub output: f64,
+    pub cache_write: f64,
+    pub cache_read: f64,
+}
+
+impl ModelPricing {
+    const fn new(input: f64, output: f64, cache_write: f64, cache_read: f64) -> Self {
+        Self {
+            input,
+            output,
+            cache_write,
+            cache_read,
+        }
+    }
+}
+
+/// A model in the catalog
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelInfo {
+    /// Model id without the date suffix
+    pub id: &'static str,
+    pub name: &'static str,
+    /// "opus", "sonnet" or "haiku"
+    pub family: &'static str,
+    pub context_window: u64,
+    pub pricing: ModelPricing,
+    /// Substrings of a full model id that identify this model
+    #[serde(skip)]
+    matches: &'static [&'static str],
+}
+
+/// Known models, newest of each family first. A model id is priced by the first
+/// entry one of whose `matches` it contains, so more specific entries come first.
+const CATALOG: &[ModelInfo] = &[
+    ModelInfo {
+        id: "claude-opus-4-5",
+        name: "Claude Opus 4.5",
+        family: "opus",
+        context_window: 200_000,
+        pricing: ModelPricing::new(5.0, 25.0, 6.25, 0.50),
+        matches: &["opus-4-5"],
+    },
+    ModelInfo {
+        id: "claude-opus-4-1",
+        name: "Claude Opus 4.1",
+        family: "opus",
+        context_window: 200_000,
+        pricing: ModelPricing::new(15.0, 75.0, 18.75, 1.50),
+        matches: &["opus-4-1"],
+    },
+    ModelInfo {
+        id: "claude-opus-4",
+        name: "Claude Opus 4",
+        family: "opus",
+        context_window: 200_000,
+        pricing: ModelPricing::new(15.0, 75.0, 18.75, 1.50),
+        matches: &["opus-4"],
+    },
+    ModelInfo {
+        id: "claude-sonnet-4-5",
+        name: "Claude Sonnet 4.5",
+        family: "sonnet",
+        context_window: 200_000,
+        pricing: ModelPricing::new(3.0, 15.0, 3.75, 0.30),
+        matches: &["sonnet-4-5"],
+    },
+    ModelInfo {
+        id: "claude-sonnet-4",
+        name: "Claude Sonnet 4",
+        family: "sonnet",
+        context_window: 200_000,
+        pricing: ModelPricing::new(3.0, 15.0, 3.75, 0.30),
+        matches: &["sonnet-4"],
+    },
+    ModelInfo {
+        id: "claude-haiku-4-5",
+        name: "Claude Haiku 4.5",
+        family: "haiku",
+        context_window: 200_000,
+        pricing: ModelPricing::new(1.0, 5.0, 1.25, 0.10),
+        matches: &["haiku-4-5"],
+    },
+    ModelInfo {
+        id: "claude-3-7-sonnet",
+        name: "Claude Sonnet 3.7",
+        family: "sonnet",
+        context_window: 200_000,
+        pricing: ModelPricing::new(3.0, 15.0, 3.75, 0.30),
+        matches: &["3-7-sonnet"],
+    },
+    ModelInfo {
+        id: "claude-3-5-haiku",
+        name: "Claude Haiku 3.5",
+        family: "haiku",
+        context_window: 200_000,
+        pricing: ModelPricing::new(0.80, 4.0, 1.0, 0.08),
+        matches: &["3-5-haiku"],
+    },
+    // Also any other sonnet, as usage was always priced
+    ModelInfo {
+        id: "claude-3-5-sonnet",
+        name: "Claude Sonnet 3.5",
+        family: "sonnet",
+        context_window: 200_000,
+        pricing: ModelPricing::new(3.0, 15.0, 3.75, 0.30),
+        matches: &["3-5-sonnet", "3.5", "35", "sonnet"],
+    },
+];
+
+/// A model id the UI offers
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelChoice {
+    /// Passed as `model` to the session commands
+    pub id: &'static str,
+    pub name: &'static str,
+    pub family: &'static str,
+    pub context_window: u64,
+}
+
+const CHOICES: &[ModelChoice] = &[
+    ModelChoice {
+        id: "sonnet",
+        name: "Sonnet",
+        family: "sonnet",
+        context_window: 200_000,
+    },
+    ModelChoice {
+        id: "sonnet1m",
+        name: "Sonnet (1M context)",
+        family: "sonnet",
+        context_window: 1_000_000,
+    },
+    ModelChoice {
+        id: "opus",
+        name: "Opus",
+        family: "opus",
+        context_window: 200_000,
+    },
+    ModelChoice {
+        id: "haiku",
+        name: "Haiku",
+        family: "haiku",
+        context_window: 200_000,
+    },
+];
+
+/// Result of `get_model_catalog`
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelCatalog {
+    pub choices: Vec<ModelChoice>,
+    pub models: Vec<ModelInfo>,
+}
+
+/// Usage of one model, from `get_usage_by_model`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelUsageSummary {
+    pub model: String,
+    /// Catalog name, if the model is known
+    pub name: Option<String>,
+    pub sessions: usize,
+    pub messages: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+    /// Fraction of the range's total cost
+    pub cost_share: f64,
+}
+
+/// Models switched per session, applied on every later resume
+#[derive(Default)]
+pub struct ModelState(Mutex<HashMap<String, String>>);
+
+/// Catalog entry of a full model id such as `claude-sonnet-4-5-20250929`
+pub fn lookup(model: &str) -> Option<&'static ModelInfo> {
+    CATALOG
+        .iter()
+        .find(|info| info.matches.iter().any(|m| model.contains(m)))
+}
+
+/// Whether a model can be passed to the session commands: one of the offered
+/// ids, or a full model id
+pub(crate) fn is_valid_model(model: &str) -> bool {
+    CHOICES.iter().any(|c| c.id == model) || model.starts_with("claude-")
+}
+
+fn load_project_defaults(conn: &Connection) -> Result<BTreeMap<String, String>, String> {
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT value FROM app_settings WHERE key = ?1",
+            [PROJECT_DEFAULTS_KEY],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    match stored {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid project default models: {}", e)),
+        None => Ok(BTreeMap::new()),
+    }
+}
+
+/// Default model of a project, if one is set
+pub fn project_default(app: &AppHandle, project_path: &str) -> Option<String> {
+    let db = app.try_state::<AgentDb>()?;
+    let conn = db.0.lock().ok()?;
+    load_project_defaults(&conn)
+        .map_err(|e| warn!("{}", e))
+        .ok()?
+        .remove(project_path.trim_end_matches(['/', '\\']))
+}
+
+/// Model to resume a session with: the one it was switched to, if any
+pub fn resume_model(app: &AppHandle, session_id: &str, requested: String) -> String {
+    app.try_state::<ModelState>()
+        .and_then(|state| state.0.lock().ok()?.get(session_id).cloned())
+        .unwrap_or(requested)
+}
+
+// ============ Tauri Commands ============
+
+/// Models the UI offers, and every known model with its context size and price
+#[tauri::command]
+pub async fn get_model_catalog() -> Result<ModelCatalog, String> {
+    Ok(ModelCatalog {
+        choices: CHOICES.to_vec(),
+        models: CATALOG.to_vec(),
+    })
+}
+
+#[tauri::command]
+pub async fn get_project_default_model(
+    app: AppHandle,
+    project_path: String,
+) -> Result<Option<String>, String> {
+    Ok(project_default(&app, &project_path))
+}
+
+/// Set the default model of a project, or clear it with None
+#[tauri::command]
+pub async fn set_project_default_model(
+    db: State<'_, AgentDb>,
+    project_path: String,
+    model: Option<String>,
+) -> Result<(), String> {
+    if let Some(model) = &model {
+        if !is_valid_model(model) {
+            return Err(format!("Unknown model: {}", model));
+        }
+    }
+    let project_path = project_path.trim_end_matches(['/', '\\']).to_string();
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let mut defaults = load_project_defaults(&conn)?;
+    match &model {
+        Some(model) => defaults.insert(project_path.clone(), model.clone()),
+        None => defaults.remove(&project_path),
+    };
+    let json = serde_json::to_string(&defaults).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+        params![PROJECT_DEFAULTS_KEY, json],
+    )
+    .map_err(|e| format!("Failed to save default model: {}", e))?;
+    info!(
+        "Default model of {} set to {}",
+        project_path,
+        model.as_deref().unwrap_or("none")
+    );
+    Ok(())
+}
+
+/// Use another model for a session from its next resume on. A prompt already
+/// queued for the session is sent with the new model too.
+#[tauri::command]
+pub async fn switch_session_model(
+    app: AppHandle,
+    state: State<'_, ModelState>,
+    session_id: String,
+    model: String,
+) -> Result<(), String> {
+    if !is_valid_model(&model) {
+        return Err(format!("Unknown model: {}", model));
+    }
+    state
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(session_id.clone(), model.clone());
+    if let Some(queue) = app.try_state::<super::prompt_queue::PromptQueueState>() {
+        if let Ok(mut queues) = queue.0.lock() {
+            for prompt in queues.get_mut(&session_id).into_iter().flatten() {
+                prompt.model = model.clone();
+            }
+        }
+    }
+    info!("Session {} switched to {}", session_id, model);
+    let project_path = app
+        .try_state::<crate::process::ProcessRegistryState>()
+        .and_then(|registry| registry.0.get_claude_session_by_id(&session_id).ok())
+        .flatten()
+        .map(|info| info.project_path);
+    events::emit(
+        &app,
+        AppEvent::SessionModelChanged {
+            session_id,
+            project_path,
+            model,
+        },
+    );
+    Ok(())
+}
+
+/// Model a session was switched to, if any
+#[tauri::command]
+pub async fn get_session_model(
+    state: State<'_, ModelState>,
+    session_id: String,
+) -> Result<Option<String>, String> {
+    Ok(state
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .get(&session_id)
+        .cloned())
+}
+
+/// Usage per model within a range, most expensive first
+#[tauri::command]
+pub async fn get_usage_by_model(
+    range: Option<UsageRange>,
+) -> Result<Vec<ModelUsageSummary>, String> {
+    let range = range.unwrap_or_default();
+    let rows = tauri::async_runtime::spawn_blocking(move || usage::model_rows(&range))
+        .await
+        .map_err(|e| e.to_string())??;
+    let total_cost: f64 = rows.iter().map(|(row, _)| row.total_cost).sum();
+    Ok(rows
+        .into_iter()
+        .map(|(row, sessions)| {
+            let model = row.models.first().cloned().unwrap_or_default();
+            ModelUsageSummary {
+                name: lookup(&model).map(|info| info.name.to_string()),
+                model,
+                sessions,
+                messages: row.message_count,
+                input_tokens: row.input_tokens,
+                output_tokens: row.output_tokens,
+                cache_creation_tokens: row.cache_creation_tokens,
+                cache_read_tokens: row.cache_read_tokens,
+                total_tokens: row.total_tokens,
+                total_cost: row.total_cost,
+                cost_share: if total_cost > 0.0 {
+                    row.total_cost / total_cost
+                } else {
+                    0.0
+                },
+            }
+        })
+        .collect())
+}
@@ -0,0 +1,379 @@
+use log::{debug, info, warn};
+/// Network connectivity monitor
+///
+/// Periodically checks that the API of the active provider answers, through the
+/// configured proxy, and emits `network-status` whenever the result changes. When
+/// the API cannot be reached, a few well-known hosts tell apart "offline" from "the
+/// API is unreachable" (an outage, a blocked host, a broken proxy), so the UI can
+/// say which one it is.
+///
+/// While not online, scheduled runs stay queued and new sessions fail right away
+/// with the reason instead of an opaque CLI error a minute later. Before refusing a
+/// session the status is checked again, so a connection that just came back is not
+/// turned away.
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::Notify;
+
+use super::storage::AgentDb;
+use crate::events::{self, AppEvent};
+
+/// `app_settings` key holding the JSON config
+const SETTINGS_KEY: &str = "network_monitor";
+
+/// Time a single probe request may take
+const PROBE_TIMEOUT: Duration = Duration::from_secs(8);
+
+/// Hosts that answer when the internet is reachable at all
+const INTERNET_PROBES: &[&str] = &[
+    "https://www.google.com/generate_204",
+    "https://cloudflare.com/cdn-cgi/trace",
+];
+
+/// Monitor settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    pub enabled: bool,
+    /// Seconds between checks while online (default: 60)
+    pub interval_secs: u64,
+    /// Seconds between checks while not online, i.e. how quickly a return is noticed
+    /// (default: 10)
+    pub offline_interval_secs: u64,
+    /// Refuse to start sessions while not online
+    pub fail_fast: bool,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 60,
+            offline_interval_secs: 10,
+            fail_fast: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Connectivity {
+    /// Not checked yet, or monitoring is off
+    Unknown,
+    Online,
+    /// The internet is reachable but the API is not
+    ApiUnreachable,
+    Offline,
+}
+
+impl Connectivity {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unknown => "unknown",
+            Self::Online => "online",
+            Self::ApiUnreachable => "api_unreachable",
+            Self::Offline => "offline",
+        }
+    }
+
+    fn is_down(&self) -> bool {
+        matches!(self, Self::ApiUnreachable | Self::Offline)
+    }
+}
+
+/// Payload of `network-status` and result of `get_network_status`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkStatus {
+    pub connectivity: Connectivity,
+    /// API endpoint that was checked
+    pub endpoint: Option<String>,
+    /// Round trip of the last successful API check
+    pub latency_ms: Option<u64>,
+    /// Why the API could not be reached
+    pub error: Option<String>,
+    pub checked_at: Option<String>,
+    /// When the current connectivity began
+    pub since: String,
+}
+
+impl Default for NetworkStatus {
+    fn default() -> Self {
+        Self {
+            connectivity: Connectivity::Unknown,
+            endpoint: None,
+            latency_ms: None,
+            error: None,
+            checked_at: None,
+            since: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+impl NetworkStatus {
+    /// Why a session cannot start, if it cannot
+    fn reason(&self) -> Option<String> {
+        let endpoint = self.endpoint.as_deref().unwrap_or("the API");
+        let error = self.error.as_deref().unwrap_or("no response");
+        match self.connectivity {
+            Connectivity::Offline => Some(format!(
+                "Offline: no internet connection since {} ({})",
+                self.since, error
+            )),
+            Connectivity::ApiUnreachable => Some(format!(
+                "Cannot reach {} since {}: {}",
+                endpoint, self.since, error
+            )),
+            Connectivity::Unknown | Connectivity::Online => None,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct NetworkState {
+    status: Mutex<NetworkStatus>,
+    config: Mutex<Option<NetworkConfig>>,
+    /// Wakes the monitor for an immediate check
+    wake: Notify,
+    /// Held during a check so concurrent callers don't probe twice
+    checking: tokio::sync::Mutex<()>,
+}
+
+fn load_config(conn: &Connection) -> Result<NetworkConfig, String> {
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT value FROM app_settings WHERE key = ?1",
+            [SETTINGS_KEY],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    match stored {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid network monitor settings: {}", e)),
+        None => Ok(NetworkConfig::default()),
+    }
+}
+
+fn current_config(app: &AppHandle) -> NetworkConfig {
+    let state = match app.try_state::<NetworkState>() {
+        Some(state) => state,
+        None => return NetworkConfig::default(),
+    };
+    if let Some(config) = state.config.lock().ok().and_then(|c| c.clone()) {
+        return config;
+    }
+    let loaded = match app.try_state::<AgentDb>() {
+        Some(db) => {
+            db.0.lock()
+                .map_err(|e| e.to_string())
+                .and_then(|conn| load_config(&conn))
+        }
+        None => Ok(NetworkConfig::default()),
+    };
+    let config = loaded.unwrap_or_else(|e| {
+        warn!("{}", e);
+        NetworkConfig::default()
+    });
+    if let Ok(mut cached) = state.config.lock() {
+        *cached = Some(config.clone());
+    }
+    config
+}
+
+/// Last known status
+pub fn status(app: &AppHandle) -> NetworkStatus {
+    app.try_state::<NetworkState>()
+        .and_then(|state| state.status.lock().ok().map(|s| s.clone()))
+        .unwrap_or_default()
+}
+
+/// Whether the last check found the API unreachable
+pub fn is_down(app: &AppHandle) -> bool {
+    current_config(app).enabled && status(app).connectivity.is_down()
+}
+
+/// Fail with the reason if sessions cannot reach the API. A known outage is
+/// checked again first, so the answer is never older than this call.
+pub async fn ensure_online(app: &AppHandle) -> Result<(), String> {
+    let config = current_config(app);
+    if !config.enabled || !config.fail_fast || !status(app).connectivity.is_down() {
+        return Ok(());
+    }
+    match check(app).await.reason() {
+        Some(reason) => {
+            warn!("Refusing to start session: {}", reason);
+            Err(reason)
+        }
+        None => Ok(()),
+    }
+}
+
+/// Probe the API, and the internet if the API does not answer
+pub(crate) async fn probe(endpoint: &str) -> (Connectivity, Option<u64>, Option<String>) {
+    let client = match super::proxy::apply_to_client(reqwest::Client::builder())
+        .timeout(PROBE_TIMEOUT)
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            return (
+                Connectivity::Unknown,
+                None,
+                Some(format!("Failed to create HTTP client: {}", e)),
+            )
+        }
+    };
+
+    // Any HTTP response means the host is reachable, even 4xx without credentials
+    let start = Instant::now();
+    let error = match client.head(endpoint).send().await {
+        Ok(_) => {
+            let latency_ms = start.elapsed().as_millis() as u64;
+            return (Connectivity::Online, Some(latency_ms), None);
+        }
+        Err(e) if e.is_timeout() => "timed out".to_string(),
+        Err(e) if e.is_connect() => format!("connection failed: {}", e),
+        Err(e) => e.to_string(),
+    };
+
+    for url in INTERNET_PROBES {
+        if client.head(*url).send().await.is_ok() {
+            return (Connectivity::ApiUnreachable, None, Some(error));
+        }
+    }
+    (Connectivity::Offline, None, Some(error))
+}
+
+/// Check now, record the result and announce a change
+async fn check(app: &AppHandle) -> NetworkStatus {
+    let state = match app.try_state::<NetworkState>() {
+        Some(state) => state,
+        None => return NetworkStatus::default(),
+    };
+    let _checking = state.checking.lock().await;
+
+    let endpoint = super::provider::api_endpoint();
+    let (connectivity, latency_ms, error) = probe(&endpoint).await;
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let (status, previous) = {
+        let mut current = match state.status.lock() {
+            Ok(current) => current,
+            Err(_) => return NetworkStatus::default(),
+        };
+        let previous = current.connectivity;
+        let since = if previous == connectivity {
+            current.since.clone()
+        } else {
+            now.clone()
+        };
+        *current = NetworkStatus {
+            connectivity,
+            endpoint: Some(endpoint),
+            latency_ms,
+            error,
+            checked_at: Some(now),
+            since,
+        };
+        (current.clone(), previous)
+    };
+
+    if previous != connectivity {
+        match &status.error {
+            Some(error) => info!("Network is now {} ({})", connectivity.as_str(), error),
+            None => info!("Network is now {}", connectivity.as_str()),
+        }
+        events::emit(app, AppEvent::NetworkStatus(status.clone()));
+        if previous.is_down() && !connectivity.is_down() {
+            super::run_scheduler::wake(app);
+        }
+    } else {
+        debug!("Network still {}", connectivity.as_str());
+    }
+    status
+}
+
+/// Forget the last result, e.g. after monitoring is switched off
+fn reset(app: &AppHandle) {
+    let state = match app.try_state::<NetworkState>() {
+        Some(state) => state,
+        None => return,
+    };
+    let reset = match state.status.lock() {
+        Ok(mut current) if current.connectivity != Connectivity::Unknown => {
+            *current = NetworkStatus::default();
+            Some(current.clone())
+        }
+        _ => None,
+    };
+    if let Some(status) = reset {
+        events::emit(app, AppEvent::NetworkStatus(status));
+        super::run_scheduler::wake(app);
+    }
+}
+
+/// Start checking connectivity in the background
+pub fn start_network_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let config = current_config(&app);
+            let wait = if !config.enabled {
+                reset(&app);
+                config.interval_secs
+            } else if super::idle::pauses_monitors(&app) {
+                config.interval_secs
+            } else if check(&app).await.connectivity.is_down() {
+                config.offline_interval_secs
+            } else {
+                config.interval_secs
+            };
+            let state = app.state::<NetworkState>();
+            let wait = Duration::from_secs(wait.max(5));
+            let _ = tokio::time::timeout(wait, state.wake.notified()).await;
+        }
+    });
+}
+
+// ============ Tauri Commands ============
+
+/// Current connectivity; with `refresh` it is checked first
+#[tauri::command]
+pub async fn get_network_status(
+    app: AppHandle,
+    refresh: Option<bool>,
+) -> Result<NetworkStatus, String> {
+    if refresh.unwrap_or(false) && current_config(&app).enabled {
+        return Ok(check(&app).await);
+    }
+    Ok(status(&app))
+}
+
+/// Get network monitor settings
+#[tauri::command]
+pub async fn get_network_config(app: AppHandle) -> Result<NetworkConfig, String> {
+    Ok(current_config(&app))
+}
+
+/// Update network monitor settings; the monitor picks them up right away
+#[tauri::command]
+pub async fn update_network_config(
+    db: State<'_, AgentDb>,
+    state: State<'_, NetworkState>,
+    config: NetworkConfig,
+) -> Result<(), String> {
+    let json = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+    {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+            params![SETTINGS_KEY, json],
+        )
+        .map_err(|e| format!("Failed to save network monitor settings: {}", e))?;
+    }
+    *state.config.lock().map_err(|e| e.to_string())? = Some(config);
+    state.wake.notify_one();
+    Ok(())
+}
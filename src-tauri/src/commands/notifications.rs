@@ -0,0 +1,225 @@
+use log::{debug, info, warn};
+/// Native desktop notifications
+///
+/// Wraps the Tauri notification plugin for on-demand notifications, the `notify`
+/// hook action, and automatic pings when a Claude session finishes or hooks fail
+/// in the background.
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, State, UserAttentionType};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::events::AppEvent;
+use crate::i18n;
+
+/// Notification preferences
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationConfig {
+    /// Master switch for all notifications
+    pub enabled: bool,
+    /// Notify when a Claude session finishes
+    pub notify_on_session_complete: bool,
+    /// Skip session notifications while the window has focus
+    pub only_when_unfocused: bool,
+    /// Notify when a hook in a chain fails
+    #[serde(default = "default_true")]
+    pub notify_on_hook_failure: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            notify_on_session_complete: true,
+            only_when_unfocused: true,
+            notify_on_hook_failure: true,
+        }
+    }
+}
+
+/// State wrapper for notification preferences
+#[derive(Default)]
+pub struct NotificationState(pub Arc<Mutex<NotificationConfig>>);
+
+/// Notification urgency
+///
+/// Desktop notifications have no native urgency, so "low" is only shown while the
+/// window is unfocused and "critical" also requests the user's attention.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationUrgency {
+    Low,
+    #[default]
+    Normal,
+    Critical,
+}
+
+/// Whether the main window currently has focus
+fn main_window_focused(app: &AppHandle) -> bool {
+    app.get_webview_window("main")
+        .and_then(|w| w.is_focused().ok())
+        .unwrap_or(false)
+}
+
+fn current_config(app: &AppHandle) -> NotificationConfig {
+    app.try_state::<NotificationState>()
+        .and_then(|state| state.0.lock().ok().map(|c| c.clone()))
+        .unwrap_or_default()
+}
+
+/// Show a desktop notification; returns false if it was suppressed
+pub fn send_notification(
+    app: &AppHandle,
+    title: &str,
+    body: &str,
+    urgency: NotificationUrgency,
+) -> Result<bool, String> {
+    if !current_config(app).enabled {
+        debug!("Notifications disabled, dropping: {}", title);
+        return Ok(false);
+    }
+
+    if urgency == NotificationUrgency::Low && main_window_focused(app) {
+        debug!(
+            "Window focused, skipping low-urgency notification: {}",
+            title
+        );
+        return Ok(false);
+    }
+
+    app.notification()
+        .builder()
+        .title(title)
+        .body(body)
+        .show()
+        .map_err(|e| format!("Failed to show notification: {}", e))?;
+
+    if urgency == NotificationUrgency::Critical {
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.request_user_attention(Some(UserAttentionType::Critical));
+        }
+    }
+
+    Ok(true)
+}
+
+/// Called when a Claude session exits; pings the user if they are elsewhere
+pub fn notify_session_finished(
+    app: &AppHandle,
+    session_id: &str,
+    project_path: &str,
+    success: bool,
+) {
+    let config = current_config(app);
+    if !config.enabled || !config.notify_on_session_complete {
+        return;
+    }
+    if config.only_when_unfocused && main_window_focused(app) {
+        return;
+    }
+
+    let project_name = std::path::Path::new(project_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| project_path.to_string());
+
+    let (title, urgency) = if success {
+        (
+            i18n::t("notification-session-finished"),
+            NotificationUrgency::Normal,
+        )
+    } else {
+        (
+            i18n::t("notification-session-failed"),
+            NotificationUrgency::Critical,
+        )
+    };
+    let short_id: String = session_id.chars().take(8).collect();
+    let body = format!("{} ({})", project_name, short_id);
+
+    if let Err(e) = send_notification(app, &title, &body, urgency) {
+        warn!("Failed to send session notification: {}", e);
+    }
+}
+
+/// Event bus subscriber; pings the user when hooks fail while they are elsewhere
+pub fn on_app_event(app: &AppHandle, event: &AppEvent) {
+    let AppEvent::HookChainComplete {
+        project_path,
+        event,
+        results,
+        ..
+    } = event
+    else {
+        return;
+    };
+    let failed = results.iter().filter(|r| !r.success).count();
+    if failed == 0 {
+        return;
+    }
+    let config = current_config(app);
+    if !config.enabled || !config.notify_on_hook_failure {
+        return;
+    }
+    if config.only_when_unfocused && main_window_focused(app) {
+        return;
+    }
+
+    let project_name = std::path::Path::new(project_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| project_path.to_string());
+    let body = i18n::t_args(
+        "notification-hooks-failed-body",
+        &[
+            ("failed", failed.into()),
+            ("total", results.len().into()),
+            ("event", event.as_str().into()),
+            ("project", project_name.into()),
+        ],
+    );
+
+    let title = i18n::t("notification-hook-failed");
+    if let Err(e) = send_notification(app, &title, &body, NotificationUrgency::Normal) {
+        warn!("Failed to send hook notification: {}", e);
+    }
+}
+
+// ============ Tauri Commands ============
+
+/// Show a desktop notification
+#[tauri::command]
+pub async fn notify(
+    app: AppHandle,
+    title: String,
+    body: String,
+    urgency: Option<NotificationUrgency>,
+) -> Result<bool, String> {
+    info!("Sending notification: {}", title);
+    send_notification(&app, &title, &body, urgency.unwrap_or_default())
+}
+
+/// Get notification preferences
+#[tauri::command]
+pub async fn get_notification_config(
+    state: State<'_, NotificationState>,
+) -> Result<NotificationConfig, String> {
+    let config = state.0.lock().map_err(|e| e.to_string())?;
+    Ok(config.clone())
+}
+
+/// Update notification preferences
+#[tauri::command]
+pub async fn update_notification_config(
+    state: State<'_, NotificationState>,
+    config: NotificationConfig,
+) -> Result<(), String> {
+    info!("Updating notification configuration");
+    let mut current = state.0.lock().map_err(|e| e.to_string())?;
+    *current = config;
+    Ok(())
+}
@@ -0,0 +1,209 @@
+use log::{info, warn};
+/// Read-only observer windows
+///
+/// An observer window follows one session's transcript and hook activity, for
+/// screen sharing while the session is driven from another window. It loads
+/// the transcript with `load_session_history` and `get_claude_session_output`,
+/// then listens for `claude-output:{session_id}` and the project's hook events
+/// (`events::emit` and `events::emit_stream` send those to observers of the
+/// project as well).
+///
+/// Read-only is enforced by `read_only_guard`, which wraps the invoke handler:
+/// observer windows can only call the commands in `OBSERVER_COMMANDS`, so they
+/// cannot send prompts, cancel runs or change anything whatever their UI does.
+/// Their capability (`capabilities/observer.json`) grants only `core:default`.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::ipc::Invoke;
+use tauri::{
+    AppHandle, Manager, Runtime, State, WebviewUrl, WebviewWindow, WebviewWindowBuilder,
+    WindowEvent,
+};
+
+use super::project_windows::same_project;
+
+/// Prefix of the labels of observer windows
+const LABEL_PREFIX: &str = "observer-";
+
+/// Commands observer windows may call; all of them only read
+const OBSERVER_COMMANDS: &[&str] = &[
+    "get_observer_context",
+    "get_window_context",
+    "load_session_history",
+    "get_claude_session_output",
+    "list_running_claude_sessions",
+    "get_session_state",
+    "get_session_statuses",
+    "get_session_code_changes",
+    "get_hook_output",
+    "get_hook_metrics",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObserverContext {
+    pub label: String,
+    pub session_id: String,
+    pub project_id: String,
+    pub project_path: String,
+    pub opened_at: String,
+}
+
+/// Session each observer window follows, by label
+#[derive(Default)]
+pub struct ObserverRegistry(Mutex<HashMap<String, ObserverContext>>);
+
+pub fn is_observer(label: &str) -> bool {
+    label.starts_with(LABEL_PREFIX)
+}
+
+/// Whether the window `label` may call `command`
+pub fn allows(label: &str, command: &str) -> bool {
+    !is_observer(label) || OBSERVER_COMMANDS.contains(&command)
+}
+
+/// Wrap the app's invoke handler so observer windows can only call read-only
+/// commands. Plugin commands do not pass through here; the observer capability
+/// covers those.
+pub fn read_only_guard<R: Runtime>(
+    handler: impl Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        let label = invoke.message.webview_ref().label().to_string();
+        let command = invoke.message.command().to_string();
+        if allows(&label, &command) {
+            return handler(invoke);
+        }
+        warn!("Blocked {} from observer window {}", command, label);
+        invoke.resolver.reject(format!(
+            "{} is not available in an observer window",
+            command
+        ));
+        true
+    }
+}
+
+/// Labels of the observer windows following sessions of `project_path`
+pub fn windows_watching(app: &AppHandle, project_path: &str) -> Vec<String> {
+    let Some(registry) = app.try_state::<ObserverRegistry>() else {
+        return Vec::new();
+    };
+    let Ok(observers) = registry.0.lock() else {
+        return Vec::new();
+    };
+    observers
+        .values()
+        .filter(|o| same_project(&o.project_path, project_path))
+        .map(|o| o.label.clone())
+        .collect()
+}
+
+fn focus(window: &WebviewWindow) -> Result<(), String> {
+    if window.is_minimized().unwrap_or(false) {
+        window.unminimize().map_err(|e| e.to_string())?;
+    }
+    window.show().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())
+}
+
+// ============ Tauri Commands ============
+
+/// Open a read-only window following a session, focusing the one already
+/// following it if there is one
+#[tauri::command]
+pub async fn open_observer_window(
+    app: AppHandle,
+    registry: State<'_, ObserverRegistry>,
+    session_id: String,
+    project_id: String,
+    project_path: String,
+) -> Result<ObserverContext, String> {
+    let context = {
+        let mut observers = registry.0.lock().map_err(|e| e.to_string())?;
+        let existing = observers
+            .values()
+            .find(|o| o.session_id == session_id)
+            .map(|o| o.label.clone());
+        if let Some(label) = existing {
+            if let Some(window) = app.get_webview_window(&label) {
+                focus(&window)?;
+                return Ok(observers[&label].clone());
+            }
+            observers.remove(&label);
+        }
+        let label = format!(
+            "{}{}",
+            LABEL_PREFIX,
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        );
+        let context = ObserverContext {
+            label: label.clone(),
+            session_id,
+            project_id,
+            project_path,
+            opened_at: chrono::Utc::now().to_rfc3339(),
+        };
+        // Registered before the window loads, so its first get_observer_context finds it
+        observers.insert(label, context.clone());
+        context
+    };
+
+    let short_id: String = context.session_id.chars().take(8).collect();
+    let window = WebviewWindowBuilder::new(&app, &context.label, WebviewUrl::default())
+        .title(format!("Observing {} - Claude Workbench", short_id))
+        .inner_size(900.0, 700.0)
+        .min_inner_size(500.0, 400.0)
+        .focused(true)
+        .build();
+    let window = match window {
+        Ok(window) => window,
+        Err(e) => {
+            if let Ok(mut observers) = registry.0.lock() {
+                observers.remove(&context.label);
+            }
+            return Err(format!("Failed to open observer window: {}", e));
+        }
+    };
+
+    let handle = app.clone();
+    let closed_label = context.label.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Destroyed = event {
+            if let Some(registry) = handle.try_state::<ObserverRegistry>() {
+                match registry.0.lock() {
+                    Ok(mut observers) => {
+                        observers.remove(&closed_label);
+                    }
+                    Err(e) => warn!("Failed to unregister observer {}: {}", closed_label, e),
+                }
+            }
+        }
+    });
+
+    info!(
+        "Opened observer window {} for session {}",
+        context.label, context.session_id
+    );
+    Ok(context)
+}
+
+/// Session the calling observer window follows, None for other windows
+#[tauri::command]
+pub async fn get_observer_context(
+    window: WebviewWindow,
+    registry: State<'_, ObserverRegistry>,
+) -> Result<Option<ObserverContext>, String> {
+    let observers = registry.0.lock().map_err(|e| e.to_string())?;
+    Ok(observers.get(window.label()).cloned())
+}
+
+/// Open observer windows
+#[tauri::command]
+pub async fn list_observer_windows(
+    registry: State<'_, ObserverRegistry>,
+) -> Result<Vec<ObserverContext>, String> {
+    let observers = registry.0.lock().map_err(|e| e.to_string())?;
+    let mut windows: Vec<ObserverContext> = observers.values().cloned().collect();
+    windows.sort_by(|a, b| a.opened_at.cmp(&b.opened_at));
+    Ok(windows)
+}
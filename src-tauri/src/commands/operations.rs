@@ -0,0 +1,202 @@
+use log::{info, warn};
+/// Cancellation of long-running commands
+///
+/// Commands that can take a while (git diff stats on large repositories, hook
+/// chains, usage indexing) accept an optional `request_id` chosen by the caller.
+/// While such a command runs, its cancel token is registered under that ID and
+/// `cancel_operation(request_id)` trips it. Operations check the token at points
+/// where they can stop cleanly (between hooks, while waiting for a child process,
+/// between files) and then fail with `WorkbenchError::Cancelled`.
+///
+/// Inside `run`, the token is also available to everything the operation awaits
+/// through `is_cancelled` and `cancelled`, so shared code such as the hook
+/// executor needs no extra parameters. Without a request ID nothing is
+/// registered and those never report a cancellation.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+use tokio_util::sync::CancellationToken;
+
+use crate::error::{WorkbenchError, WorkbenchResult};
+
+tokio::task_local! {
+    static CURRENT: CancellationToken;
+}
+
+/// Distinguishes registrations that reuse a request ID
+static NEXT_SEQ: AtomicU64 = AtomicU64::new(1);
+
+struct Operation {
+    seq: u64,
+    kind: &'static str,
+    started_at: String,
+    token: CancellationToken,
+}
+
+/// A running operation, as listed by `list_operations`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationInfo {
+    pub request_id: String,
+    pub kind: String,
+    pub started_at: String,
+    pub cancelled: bool,
+}
+
+/// Cancel tokens of running operations by request ID
+#[derive(Default)]
+pub struct OperationRegistry(Mutex<HashMap<String, Operation>>);
+
+/// Registration of one operation; unregisters it when dropped
+pub struct OperationGuard {
+    app: AppHandle,
+    request_id: String,
+    seq: u64,
+    token: CancellationToken,
+}
+
+impl OperationGuard {
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+}
+
+impl Drop for OperationGuard {
+    fn drop(&mut self) {
+        if let Some(registry) = self.app.try_state::<OperationRegistry>() {
+            if let Ok(mut operations) = registry.0.lock() {
+                if operations.get(&self.request_id).map(|op| op.seq) == Some(self.seq) {
+                    operations.remove(&self.request_id);
+                }
+            }
+        }
+    }
+}
+
+/// Register an operation under `request_id`. Fails if one with that ID is still
+/// running.
+pub fn register(
+    app: &AppHandle,
+    request_id: &str,
+    kind: &'static str,
+) -> WorkbenchResult<OperationGuard> {
+    let registry = app
+        .try_state::<OperationRegistry>()
+        .ok_or("Operation registry not initialized")?;
+    let mut operations = registry.0.lock().map_err(|e| e.to_string())?;
+    if operations.contains_key(request_id) {
+        return Err(WorkbenchError::InvalidInput(format!(
+            "Operation {} is already running",
+            request_id
+        )));
+    }
+    let seq = NEXT_SEQ.fetch_add(1, Ordering::Relaxed);
+    let token = CancellationToken::new();
+    operations.insert(
+        request_id.to_string(),
+        Operation {
+            seq,
+            kind,
+            started_at: chrono::Utc::now().to_rfc3339(),
+            token: token.clone(),
+        },
+    );
+    Ok(OperationGuard {
+        app: app.clone(),
+        request_id: request_id.to_string(),
+        seq,
+        token,
+    })
+}
+
+/// Run `operation` as a cancellable operation. With a request ID it runs as its
+/// own task, so it stays cancellable even if the caller goes away, and a task
+/// that dies before finishing is reported as `Dropped`. Without one it simply
+/// runs inline.
+pub async fn run<T, F>(
+    app: &AppHandle,
+    request_id: Option<String>,
+    kind: &'static str,
+    operation: F,
+) -> WorkbenchResult<T>
+where
+    T: Send + 'static,
+    F: Future<Output = WorkbenchResult<T>> + Send + 'static,
+{
+    let request_id = match request_id {
+        Some(request_id) => request_id,
+        None => return operation.await,
+    };
+    let guard = register(app, &request_id, kind)?;
+    let token = guard.token();
+    tauri::async_runtime::spawn(async move {
+        let _guard = guard;
+        CURRENT.scope(token, operation).await
+    })
+    .await
+    .unwrap_or_else(|e| {
+        warn!("Operation {} ({}) did not finish: {}", request_id, kind, e);
+        Err(WorkbenchError::Dropped(kind.to_string()))
+    })
+}
+
+/// Whether the operation this task runs for has been cancelled
+pub fn is_cancelled() -> bool {
+    CURRENT
+        .try_with(|token| token.is_cancelled())
+        .unwrap_or(false)
+}
+
+/// Cancel token of the operation this task runs for, for work handed to a
+/// blocking thread where `is_cancelled` cannot see it
+pub fn current_token() -> Option<CancellationToken> {
+    CURRENT.try_with(|token| token.clone()).ok()
+}
+
+/// Resolves once the operation this task runs for is cancelled; never resolves
+/// outside an operation
+pub async fn cancelled() {
+    match CURRENT.try_with(|token| token.clone()) {
+        Ok(token) => token.cancelled_owned().await,
+        Err(_) => std::future::pending().await,
+    }
+}
+
+// ============ Tauri Commands ============
+
+/// Cancel a running operation; returns false if none has that request ID
+#[tauri::command]
+pub async fn cancel_operation(
+    registry: State<'_, OperationRegistry>,
+    request_id: String,
+) -> Result<bool, WorkbenchError> {
+    let operations = registry.0.lock().map_err(|e| e.to_string())?;
+    match operations.get(&request_id) {
+        Some(operation) => {
+            info!("Cancelling {} operation {}", operation.kind, request_id);
+            operation.token.cancel();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+#[tauri::command]
+pub async fn list_operations(
+    registry: State<'_, OperationRegistry>,
+) -> Result<Vec<OperationInfo>, WorkbenchError> {
+    let operations = registry.0.lock().map_err(|e| e.to_string())?;
+    let mut list: Vec<OperationInfo> = operations
+        .iter()
+        .map(|(request_id, operation)| OperationInfo {
+            request_id: request_id.clone(),
+            kind: operation.kind.to_string(),
+            started_at: operation.started_at.clone(),
+            cancelled: operation.token.is_cancelled(),
+        })
+        .collect();
+    list.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    Ok(list)
+}
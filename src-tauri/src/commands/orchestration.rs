@@ -0,0 +1,390 @@
+use log::{info, warn};
+/// Multi-session orchestration
+///
+/// Runs the same prompt in several projects (or worktrees) at once, e.g. to apply
+/// one refactor across a multi-repo workspace. Each run is a headless session
+/// (see `headless`), independent of the interactive one. Progress is pushed as
+/// `orchestration-updated` / `orchestration-output:{id}` events, every finished run
+/// fires OnSessionEnd hooks, and `orchestration-complete` carries the summary.
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::Notify;
+
+use super::enhanced_hooks::{trigger_hook_event, HookContext};
+use super::headless::{self, ProgressFn, SessionExit, SessionProgress, SessionSpec};
+use crate::events::{self, AppEvent};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl RunStatus {
+    fn is_finished(self) -> bool {
+        matches!(self, Self::Completed | Self::Failed | Self::Cancelled)
+    }
+}
+
+/// One project's run within an orchestration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrchestrationRun {
+    pub index: usize,
+    pub project_path: String,
+    pub status: RunStatus,
+    pub session_id: Option<String>,
+    pub pid: Option<u32>,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    /// Final text of the `result` message
+    pub result: Option<String>,
+    pub cost_usd: Option<f64>,
+    pub num_turns: Option<u64>,
+    pub error: Option<String>,
+    #[serde(skip)]
+    cancel: Arc<Notify>,
+    #[serde(skip)]
+    cancel_requested: bool,
+}
+
+/// The same prompt launched across several projects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Orchestration {
+    pub id: String,
+    pub prompt: String,
+    pub model: String,
+    pub plan_mode: bool,
+    pub created_at: String,
+    pub finished_at: Option<String>,
+    pub runs: Vec<OrchestrationRun>,
+}
+
+/// Aggregated outcome, sent with `orchestration-complete`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrchestrationSummary {
+    pub id: String,
+    pub total: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub cancelled: usize,
+    pub running: usize,
+    pub total_cost_usd: f64,
+    pub finished: bool,
+}
+
+impl Orchestration {
+    fn summary(&self) -> OrchestrationSummary {
+        let count = |status: RunStatus| self.runs.iter().filter(|r| r.status == status).count();
+        OrchestrationSummary {
+            id: self.id.clone(),
+            total: self.runs.len(),
+            completed: count(RunStatus::Completed),
+            failed: count(RunStatus::Failed),
+            cancelled: count(RunStatus::Cancelled),
+            running: count(RunStatus::Running) + count(RunStatus::Pending),
+            total_cost_usd: self.runs.iter().filter_map(|r| r.cost_usd).sum(),
+            finished: self.finished_at.is_some(),
+        }
+    }
+}
+
+/// All orchestrations started since launch
+#[derive(Default)]
+pub struct OrchestrationState(pub Mutex<HashMap<String, Orchestration>>);
+
+/// Apply a change to one run and emit the updated orchestration. Returns the
+/// summary when this change finished the last run.
+fn update_run(
+    app: &AppHandle,
+    orchestration_id: &str,
+    index: usize,
+    change: impl FnOnce(&mut OrchestrationRun),
+) -> Option<OrchestrationSummary> {
+    let state = app.try_state::<OrchestrationState>()?;
+    let mut orchestrations = state.0.lock().ok()?;
+    let orchestration = orchestrations.get_mut(orchestration_id)?;
+    change(orchestration.runs.get_mut(index)?);
+
+    let all_done = orchestration.runs.iter().all(|r| r.status.is_finished());
+    let just_finished = all_done && orchestration.finished_at.is_none();
+    if just_finished {
+        orchestration.finished_at = Some(chrono::Utc::now().to_rfc3339());
+    }
+    events::emit(app, AppEvent::OrchestrationUpdated(orchestration.clone()));
+    just_finished.then(|| orchestration.summary())
+}
+
+/// Launch one project's run and wait for it to end
+async fn run_project(
+    app: AppHandle,
+    orchestration_id: String,
+    index: usize,
+    project_path: String,
+    prompt: String,
+    model: String,
+    plan_mode: bool,
+) {
+    let cancel = {
+        let state = app.state::<OrchestrationState>();
+        let orchestrations = match state.0.lock() {
+            Ok(orchestrations) => orchestrations,
+            Err(_) => return,
+        };
+        match orchestrations
+            .get(&orchestration_id)
+            .and_then(|o| o.runs.get(index))
+        {
+            Some(run) if !run.cancel_requested => Some(run.cancel.clone()),
+            Some(_) => None,
+            None => return,
+        }
+    };
+    // Cancelled before it started
+    let cancel = match cancel {
+        Some(cancel) => cancel,
+        None => {
+            finish_run(&app, &orchestration_id, index, |run| {
+                run.status = RunStatus::Cancelled;
+            })
+            .await;
+            return;
+        }
+    };
+
+    let spec = SessionSpec {
+        project_path,
+        prompt,
+        model,
+        plan_mode,
+        timeout: None,
+    };
+    let progress_app = app.clone();
+    let progress_id = orchestration_id.clone();
+    let progress: ProgressFn = Arc::new(move |progress| match progress {
+        SessionProgress::Started { pid } => {
+            update_run(&progress_app, &progress_id, index, |run| {
+                run.status = RunStatus::Running;
+                run.pid = Some(pid);
+                run.started_at = Some(chrono::Utc::now().to_rfc3339());
+            });
+        }
+        SessionProgress::SessionId(session_id) => {
+            update_run(&progress_app, &progress_id, index, |run| {
+                run.session_id = Some(session_id.to_string());
+            });
+        }
+        SessionProgress::Line(line) => {
+            events::emit(
+                &progress_app,
+                AppEvent::OrchestrationOutput {
+                    orchestration_id: progress_id.clone(),
+                    index,
+                    line: line.to_string(),
+                },
+            );
+        }
+    });
+    let session = headless::run_session(&app, &spec, cancel, progress).await;
+
+    finish_run(&app, &orchestration_id, index, move |run| {
+        run.status = match session.exit {
+            _ if session.succeeded() => RunStatus::Completed,
+            SessionExit::Cancelled => RunStatus::Cancelled,
+            _ => RunStatus::Failed,
+        };
+        run.error = session.error().filter(|_| run.status == RunStatus::Failed);
+        run.result = session.result;
+        run.cost_usd = session.cost_usd;
+        run.num_turns = session.num_turns;
+    })
+    .await;
+}
+
+/// Record a run's final state and fire OnSessionEnd hooks; announces the summary
+/// once the last run is done
+async fn finish_run(
+    app: &AppHandle,
+    orchestration_id: &str,
+    index: usize,
+    change: impl FnOnce(&mut OrchestrationRun),
+) {
+    let mut finished: Option<OrchestrationRun> = None;
+    let summary = update_run(app, orchestration_id, index, |run| {
+        change(run);
+        run.finished_at = Some(chrono::Utc::now().to_rfc3339());
+        finished = Some(run.clone());
+    });
+    let run = match finished {
+        Some(run) => run,
+        None => return,
+    };
+    info!(
+        "Orchestration {} run {} finished: {:?}",
+        orchestration_id, index, run.status
+    );
+
+    let context = HookContext {
+        event: "OnSessionEnd".to_string(),
+        session_id: run.session_id.clone().unwrap_or_default(),
+        project_path: run.project_path.clone(),
+        data: serde_json::json!({
+            "orchestration_id": orchestration_id,
+            "run_index": index,
+            "status": run.status,
+            "result": run.result,
+            "cost_usd": run.cost_usd,
+            "error": run.error,
+        }),
+    };
+    if let Err(e) = trigger_hook_event(app.clone(), "OnSessionEnd".to_string(), context, None).await
+    {
+        warn!("OnSessionEnd hooks failed for orchestrated run: {}", e);
+    }
+
+    if let Some(summary) = summary {
+        info!(
+            "Orchestration {} complete: {} completed, {} failed, {} cancelled",
+            summary.id, summary.completed, summary.failed, summary.cancelled
+        );
+        events::emit(app, AppEvent::OrchestrationComplete(summary));
+    }
+}
+
+// ============ Tauri Commands ============
+
+/// Run the same prompt in each project concurrently, one new session per project
+#[tauri::command]
+pub async fn start_orchestration(
+    app: AppHandle,
+    state: State<'_, OrchestrationState>,
+    project_paths: Vec<String>,
+    prompt: String,
+    model: String,
+    plan_mode: Option<bool>,
+) -> Result<Orchestration, String> {
+    if prompt.trim().is_empty() {
+        return Err("Prompt cannot be empty".to_string());
+    }
+    let mut seen = HashSet::new();
+    let project_paths: Vec<String> = project_paths
+        .into_iter()
+        .filter(|p| seen.insert(p.clone()))
+        .collect();
+    if project_paths.is_empty() {
+        return Err("Select at least one project".to_string());
+    }
+    if let Some(missing) = project_paths.iter().find(|p| !Path::new(p).is_dir()) {
+        return Err(format!("Project directory not found: {}", missing));
+    }
+
+    let plan_mode = plan_mode.unwrap_or(false);
+    let orchestration = Orchestration {
+        id: uuid::Uuid::new_v4().to_string(),
+        prompt: prompt.clone(),
+        model: model.clone(),
+        plan_mode,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        finished_at: None,
+        runs: project_paths
+            .iter()
+            .enumerate()
+            .map(|(index, project_path)| OrchestrationRun {
+                index,
+                project_path: project_path.clone(),
+                status: RunStatus::Pending,
+                session_id: None,
+                pid: None,
+                started_at: None,
+                finished_at: None,
+                result: None,
+                cost_usd: None,
+                num_turns: None,
+                error: None,
+                cancel: Arc::new(Notify::new()),
+                cancel_requested: false,
+            })
+            .collect(),
+    };
+    state
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(orchestration.id.clone(), orchestration.clone());
+    info!(
+        "Starting orchestration {} across {} projects",
+        orchestration.id,
+        project_paths.len()
+    );
+
+    for (index, project_path) in project_paths.into_iter().enumerate() {
+        tauri::async_runtime::spawn(run_project(
+            app.clone(),
+            orchestration.id.clone(),
+            index,
+            project_path,
+            prompt.clone(),
+            model.clone(),
+            plan_mode,
+        ));
+    }
+    Ok(orchestration)
+}
+
+/// Current state of an orchestration and its runs
+#[tauri::command]
+pub async fn get_orchestration(
+    state: State<'_, OrchestrationState>,
+    orchestration_id: String,
+) -> Result<Orchestration, String> {
+    let orchestrations = state.0.lock().map_err(|e| e.to_string())?;
+    orchestrations
+        .get(&orchestration_id)
+        .cloned()
+        .ok_or_else(|| format!("Orchestration not found: {}", orchestration_id))
+}
+
+/// Summaries of all orchestrations, newest first
+#[tauri::command]
+pub async fn list_orchestrations(
+    state: State<'_, OrchestrationState>,
+) -> Result<Vec<OrchestrationSummary>, String> {
+    let orchestrations = state.0.lock().map_err(|e| e.to_string())?;
+    let mut list: Vec<&Orchestration> = orchestrations.values().collect();
+    list.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(list.into_iter().map(Orchestration::summary).collect())
+}
+
+/// Stop every run of an orchestration that has not finished yet
+#[tauri::command]
+pub async fn cancel_orchestration(
+    state: State<'_, OrchestrationState>,
+    orchestration_id: String,
+) -> Result<usize, String> {
+    let mut orchestrations = state.0.lock().map_err(|e| e.to_string())?;
+    let orchestration = orchestrations
+        .get_mut(&orchestration_id)
+        .ok_or_else(|| format!("Orchestration not found: {}", orchestration_id))?;
+
+    let mut cancelled = 0;
+    for run in orchestration
+        .runs
+        .iter_mut()
+        .filter(|r| !r.status.is_finished() && !r.cancel_requested)
+    {
+        run.cancel_requested = true;
+        run.cancel.notify_one();
+        cancelled += 1;
+    }
+    info!(
+        "Cancelling {} runs of orchestration {}",
+        cancelled, orchestration_id
+    );
+    Ok(cancelled)
+}
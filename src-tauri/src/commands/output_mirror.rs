@@ -0,0 +1,221 @@
+use log::{info, warn};
+/// Live mirror of a session's stream-json output
+///
+/// Copies every output line of a session to a file or named pipe as it arrives, so
+/// other tools can `tail -f` or read Claude's activity. Each mirror has its own
+/// writer thread fed through a channel: opening a FIFO blocks until a reader
+/// attaches, and a slow reader must not stall the session's stream. A mirror stops
+/// by itself (emitting `output-mirror-stopped`) when a write fails, e.g. because
+/// the pipe's reader went away.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, State};
+
+use crate::events::{self, AppEvent};
+
+/// A session's active mirror
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputMirror {
+    pub session_id: String,
+    pub path: String,
+    pub started_at: String,
+    pub lines_written: u64,
+}
+
+struct MirrorHandle {
+    path: PathBuf,
+    started_at: String,
+    sender: Sender<String>,
+    lines_written: Arc<AtomicU64>,
+    closed: Arc<AtomicBool>,
+}
+
+impl MirrorHandle {
+    fn info(&self, session_id: &str) -> OutputMirror {
+        OutputMirror {
+            session_id: session_id.to_string(),
+            path: self.path.to_string_lossy().to_string(),
+            started_at: self.started_at.clone(),
+            lines_written: self.lines_written.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Active mirrors keyed by session id
+#[derive(Default)]
+pub struct OutputMirrorState(Mutex<HashMap<String, MirrorHandle>>);
+
+#[cfg(unix)]
+fn is_fifo(path: &Path) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    std::fs::metadata(path).is_ok_and(|m| m.file_type().is_fifo())
+}
+
+#[cfg(not(unix))]
+fn is_fifo(_path: &Path) -> bool {
+    false
+}
+
+/// Copy one output line to the session's mirror, if it has one
+pub fn mirror_line(app: &AppHandle, session_id: &str, line: &str) {
+    let state = match app.try_state::<OutputMirrorState>() {
+        Some(state) => state,
+        None => return,
+    };
+    let mut mirrors = match state.0.lock() {
+        Ok(mirrors) => mirrors,
+        Err(_) => return,
+    };
+    let closed = match mirrors.get(session_id) {
+        Some(mirror) => {
+            mirror.closed.load(Ordering::Relaxed) || mirror.sender.send(line.to_string()).is_err()
+        }
+        None => return,
+    };
+    if closed {
+        mirrors.remove(session_id);
+    }
+}
+
+/// Writer thread: open the target, then append lines until the channel closes or
+/// a write fails
+fn spawn_writer(
+    app: AppHandle,
+    session_id: String,
+    path: PathBuf,
+    append: bool,
+    receiver: mpsc::Receiver<String>,
+    lines_written: Arc<AtomicU64>,
+    closed: Arc<AtomicBool>,
+) {
+    std::thread::spawn(move || {
+        let fifo = is_fifo(&path);
+        let mut options = OpenOptions::new();
+        options.write(true);
+        if !fifo {
+            options.create(true);
+            if append {
+                options.append(true);
+            } else {
+                options.truncate(true);
+            }
+        }
+
+        let error = match options.open(&path) {
+            Ok(mut file) => loop {
+                let line = match receiver.recv() {
+                    Ok(line) => line,
+                    // Stopped, nothing went wrong
+                    Err(_) => break None,
+                };
+                let written = writeln!(file, "{}", line).and_then(|_| file.flush());
+                if let Err(e) = written {
+                    break Some(format!("Failed to write to {:?}: {}", path, e));
+                }
+                lines_written.fetch_add(1, Ordering::Relaxed);
+            },
+            Err(e) => Some(format!("Failed to open {:?}: {}", path, e)),
+        };
+        closed.store(true, Ordering::Relaxed);
+
+        if let Some(error) = &error {
+            warn!(
+                "Output mirror for session {} stopped: {}",
+                session_id, error
+            );
+        }
+        let stopped = events::OutputMirrorStopped {
+            session_id,
+            path: path.to_string_lossy().into_owned(),
+            lines_written: lines_written.load(Ordering::Relaxed),
+            error,
+        };
+        events::emit(&app, AppEvent::OutputMirrorStopped(stopped));
+    });
+}
+
+// ============ Tauri Commands ============
+
+/// Mirror a session's streaming output to a file (created if needed) or named
+/// pipe; `append` keeps an existing file's contents. Replaces an earlier mirror
+/// of the same session.
+#[tauri::command]
+pub async fn start_output_mirror(
+    app: AppHandle,
+    state: State<'_, OutputMirrorState>,
+    session_id: String,
+    path: String,
+    append: Option<bool>,
+) -> Result<OutputMirror, String> {
+    let target = PathBuf::from(&path);
+    if target.is_dir() {
+        return Err(format!("{} is a directory", path));
+    }
+    match target.parent() {
+        Some(parent) if parent.as_os_str().is_empty() || parent.is_dir() => {}
+        _ => return Err(format!("Directory of {} does not exist", path)),
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    let lines_written = Arc::new(AtomicU64::new(0));
+    let closed = Arc::new(AtomicBool::new(false));
+    spawn_writer(
+        app,
+        session_id.clone(),
+        target.clone(),
+        append.unwrap_or(true),
+        receiver,
+        lines_written.clone(),
+        closed.clone(),
+    );
+
+    let handle = MirrorHandle {
+        path: target,
+        started_at: chrono::Utc::now().to_rfc3339(),
+        sender,
+        lines_written,
+        closed,
+    };
+    let info = handle.info(&session_id);
+    // Dropping a replaced handle closes its channel and ends its writer
+    state
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(session_id.clone(), handle);
+    info!("Mirroring output of session {} to {}", session_id, path);
+    Ok(info)
+}
+
+/// Stop mirroring a session's output
+#[tauri::command]
+pub async fn stop_output_mirror(
+    state: State<'_, OutputMirrorState>,
+    session_id: String,
+) -> Result<bool, String> {
+    let removed = state
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&session_id);
+    Ok(removed.is_some())
+}
+
+/// Active mirrors with the number of lines written so far
+#[tauri::command]
+pub async fn list_output_mirrors(
+    state: State<'_, OutputMirrorState>,
+) -> Result<Vec<OutputMirror>, String> {
+    let mut mirrors = state.0.lock().map_err(|e| e.to_string())?;
+    mirrors.retain(|_, mirror| !mirror.closed.load(Ordering::Relaxed));
+    Ok(mirrors
+        .iter()
+        .map(|(session_id, mirror)| mirror.info(session_id))
+        .collect())
+}
@@ -0,0 +1,31 @@
+/// Span timings for performance triage
+///
+/// Hot paths run inside `tracing` spans timed by `crate::profiling`: `hook_chain`
+/// and `hook` around hook execution, `git` around git commands, `git_query`
+/// around the library-backed git queries, `usage_index` around usage indexing,
+/// and `claude_stream` / `stream_record` around the Claude CLI output stream.
+use crate::profiling::{self, PerformanceSnapshot};
+
+/// Recent timings listed per span name when the caller does not say
+const DEFAULT_RECENT: usize = 20;
+
+// ============ Tauri Commands ============
+
+/// Timings per span name, most total time first; `name` limits it to one span
+/// name and `recent` sets how many recent timings each lists
+#[tauri::command]
+pub async fn get_performance_snapshot(
+    name: Option<String>,
+    recent: Option<usize>,
+) -> Result<PerformanceSnapshot, String> {
+    Ok(profiling::snapshot(
+        name.as_deref(),
+        recent.unwrap_or(DEFAULT_RECENT),
+    ))
+}
+
+#[tauri::command]
+pub async fn reset_performance_snapshot() -> Result<(), String> {
+    profiling::reset();
+    Ok(())
+}
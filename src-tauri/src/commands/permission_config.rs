@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use super::retry::RetryPolicy;
+
 /// Claude权限管理配置结构
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClaudePermissionConfig {
@@ -68,6 +70,9 @@ pub struct ClaudeExecutionConfig {
     pub max_thinking_tokens: Option<u32>,
     pub verbose: bool,
     pub permissions: ClaudePermissionConfig,
+    /// Retry starting the Claude process if spawning fails
+    #[serde(default)]
+    pub spawn_retry: Option<RetryPolicy>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -86,6 +91,7 @@ impl Default for ClaudeExecutionConfig {
             max_thinking_tokens: None,
             verbose: true,
             permissions: ClaudePermissionConfig::default(),
+            spawn_retry: None,
         }
     }
 }
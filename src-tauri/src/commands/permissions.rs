@@ -0,0 +1,836 @@
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::settings_manager::{read_settings_file, settings_path, write_settings_atomically};
+use super::usage::ToolOutcome;
+
+/// Rule lists in evaluation order: deny wins over ask, ask over allow
+const RULE_LISTS: &[&str] = &["deny", "ask", "allow"];
+
+/// Settings scopes that can carry permission rules
+const RULE_SCOPES: &[&str] = &["user", "project", "local", "managed"];
+
+/// Tools that accept a specifier in parentheses
+const SPECIFIER_TOOLS: &[&str] = &[
+    "Bash",
+    "Read",
+    "Edit",
+    "Write",
+    "MultiEdit",
+    "NotebookEdit",
+    "Glob",
+    "Grep",
+    "WebFetch",
+];
+
+/// Tools that only make sense as bare rules
+const BARE_TOOLS: &[&str] = &[
+    "BashOutput",
+    "ExitPlanMode",
+    "KillShell",
+    "SlashCommand",
+    "Task",
+    "TodoWrite",
+    "WebSearch",
+];
+
+/// Tools whose specifier is a path pattern
+const PATH_TOOLS: &[&str] = &[
+    "Read",
+    "Edit",
+    "Write",
+    "MultiEdit",
+    "NotebookEdit",
+    "Glob",
+    "Grep",
+];
+
+/// Tools Claude Code runs without asking, so approvals of them say nothing
+const PROMPTLESS_TOOLS: &[&str] = &[
+    "Read",
+    "Glob",
+    "Grep",
+    "LS",
+    "NotebookRead",
+    "TodoWrite",
+    "Task",
+    "BashOutput",
+    "ExitPlanMode",
+];
+
+/// Tools whose `Edit(...)` rules cover them
+const EDIT_RULE_TOOLS: &[&str] = &["Edit", "MultiEdit", "Write", "NotebookEdit"];
+
+/// Commands whose first word also needs the second to say what they do
+const SUBCOMMAND_TOOLS: &[&str] = &[
+    "npm", "pnpm", "yarn", "bun", "npx", "cargo", "git", "go", "docker", "kubectl", "make", "pip",
+    "poetry", "uv", "dotnet", "gradle", "mvn",
+];
+
+/// Commands never worth blanket-allowing, however often they were approved
+const RISKY_COMMANDS: &[&str] = &[
+    "rm", "sudo", "su", "chmod", "chown", "dd", "mkfs", "kill", "pkill", "killall", "shutdown",
+    "reboot", "curl", "wget", "ssh", "scp", "eval",
+];
+
+/// Approvals needed before a rule is suggested
+const DEFAULT_MIN_EVIDENCE: u32 = 3;
+
+/// Examples kept per suggestion
+const SUGGESTION_EXAMPLES: usize = 3;
+
+/// A single permission rule and where it lives
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PermissionRule {
+    /// The rule as written, e.g. "Bash(npm run test:*)"
+    pub rule: String,
+    pub tool: String,
+    pub specifier: Option<String>,
+    /// "allow", "ask" or "deny"
+    pub list: String,
+    /// Settings scope the rule comes from
+    pub scope: String,
+}
+
+/// Result of validating a rule pattern
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionRuleValidation {
+    pub valid: bool,
+    pub tool: Option<String>,
+    pub specifier: Option<String>,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Two rules that contradict or shadow each other
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionConflict {
+    /// "contradiction" (same rule allowed and denied), "shadowed" (rule never takes
+    /// effect because a stronger rule covers it) or "duplicate"
+    pub kind: String,
+    pub rule: PermissionRule,
+    pub other: PermissionRule,
+    pub message: String,
+}
+
+/// Which rule decides a tool invocation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionTestResult {
+    /// "deny", "ask", "allow" or "none" (Claude falls back to prompting)
+    pub decision: String,
+    pub matched_rule: Option<PermissionRule>,
+    /// Every rule that matches, strongest first
+    pub matching_rules: Vec<PermissionRule>,
+}
+
+/// An allow rule covering tool calls the user kept approving
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionSuggestion {
+    pub rule: String,
+    pub tool: String,
+    /// Approved calls the rule would have allowed
+    pub evidence_count: u32,
+    /// A few of those calls' inputs, e.g. Bash commands
+    pub examples: Vec<String>,
+    pub last_seen: String,
+}
+
+/// Split a rule into tool and optional specifier
+fn parse_rule(rule: &str) -> Result<(String, Option<String>), String> {
+    let rule = rule.trim();
+    if rule.is_empty() {
+        return Err("Rule cannot be empty".to_string());
+    }
+
+    match rule.find('(') {
+        Some(open) => {
+            if !rule.ends_with(')') {
+                return Err("Rule has an opening '(' but does not end with ')'".to_string());
+            }
+            let tool = rule[..open].trim().to_string();
+            let specifier = rule[open + 1..rule.len() - 1].trim().to_string();
+            if specifier.is_empty() {
+                return Err(format!(
+                    "Empty specifier; use '{}' to match every call",
+                    tool
+                ));
+            }
+            Ok((tool, Some(specifier)))
+        }
+        None => {
+            if rule.contains(')') {
+                return Err("Unbalanced ')' in rule".to_string());
+            }
+            Ok((rule.to_string(), None))
+        }
+    }
+}
+
+/// Validate a rule's syntax and tool name
+fn check_rule(rule: &str) -> PermissionRuleValidation {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    let (tool, specifier) = match parse_rule(rule) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            return PermissionRuleValidation {
+                valid: false,
+                tool: None,
+                specifier: None,
+                errors: vec![e],
+                warnings,
+            }
+        }
+    };
+
+    if tool.starts_with("mcp__") {
+        if specifier.is_some() {
+            errors.push("MCP rules do not take a specifier; use mcp__server__tool".to_string());
+        }
+        if tool.trim_start_matches("mcp__").is_empty() {
+            errors.push("MCP rule is missing the server name".to_string());
+        }
+    } else if SPECIFIER_TOOLS.contains(&tool.as_str()) {
+        if let Some(spec) = &specifier {
+            if tool == "Bash" {
+                if let Some(pos) = spec.find(":*") {
+                    if pos + 2 != spec.len() {
+                        errors.push("':*' is only allowed at the end of a Bash rule".to_string());
+                    }
+                }
+            } else if tool == "WebFetch" {
+                if !spec.starts_with("domain:") {
+                    errors.push(
+                        "WebFetch rules must look like WebFetch(domain:example.com)".to_string(),
+                    );
+                }
+            } else if let Err(e) = glob::Pattern::new(&normalize_path_pattern(spec, None)) {
+                errors.push(format!("Invalid path pattern: {}", e));
+            }
+        }
+    } else if BARE_TOOLS.contains(&tool.as_str()) {
+        if specifier.is_some() {
+            errors.push(format!("{} does not accept a specifier", tool));
+        }
+    } else {
+        warnings.push(format!("Unknown tool '{}'", tool));
+    }
+
+    PermissionRuleValidation {
+        valid: errors.is_empty(),
+        tool: Some(tool),
+        specifier,
+        errors,
+        warnings,
+    }
+}
+
+/// Expand `~/` and `//` prefixes and anchor relative patterns to the project
+fn normalize_path_pattern(pattern: &str, project_path: Option<&str>) -> String {
+    if let Some(rest) = pattern.strip_prefix("//") {
+        format!("/{}", rest)
+    } else if let Some(rest) = pattern.strip_prefix("~/") {
+        dirs::home_dir()
+            .map(|home| home.join(rest).to_string_lossy().to_string())
+            .unwrap_or_else(|| pattern.to_string())
+    } else if let Some(root) = project_path {
+        let relative = pattern.trim_start_matches("./").trim_start_matches('/');
+        let relative = if relative.contains('/') || relative.starts_with("**") {
+            relative.to_string()
+        } else {
+            // Bare patterns like "*.env" match at any depth
+            format!("**/{}", relative)
+        };
+        format!("{}/{}", root.trim_end_matches('/'), relative)
+    } else {
+        pattern.to_string()
+    }
+}
+
+/// Match with `*` as "any characters"
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+
+    let mut rest = text;
+    for (i, part) in parts.iter().enumerate() {
+        if i == 0 {
+            match rest.strip_prefix(part) {
+                Some(r) => rest = r,
+                None => return false,
+            }
+        } else if i == parts.len() - 1 {
+            return rest.ends_with(part);
+        } else {
+            match rest.find(part) {
+                Some(pos) => rest = &rest[pos + part.len()..],
+                None => return false,
+            }
+        }
+    }
+    true
+}
+
+/// Host portion of a URL
+fn url_host(url: &str) -> &str {
+    let without_scheme = url.split("://").nth(1).unwrap_or(url);
+    without_scheme
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or("")
+        .split(':')
+        .next()
+        .unwrap_or("")
+}
+
+/// Whether a rule applies to a tool call
+fn rule_matches(
+    rule: &PermissionRule,
+    tool: &str,
+    input: Option<&str>,
+    project_path: Option<&str>,
+) -> bool {
+    if rule.tool.starts_with("mcp__") {
+        // mcp__server matches every tool of that server
+        return tool == rule.tool || tool.starts_with(&format!("{}__", rule.tool));
+    }
+
+    if rule.tool != tool {
+        return false;
+    }
+
+    let (spec, input) = match (&rule.specifier, input) {
+        (None, _) => return true,
+        (Some(spec), Some(input)) => (spec.as_str(), input.trim()),
+        (Some(_), None) => return false,
+    };
+
+    if tool == "Bash" {
+        match spec.strip_suffix(":*") {
+            Some(prefix) => input == prefix || input.starts_with(&format!("{} ", prefix)),
+            None => wildcard_match(spec, input),
+        }
+    } else if tool == "WebFetch" {
+        spec.strip_prefix("domain:")
+            .map(|domain| url_host(input).eq_ignore_ascii_case(domain))
+            .unwrap_or(false)
+    } else if PATH_TOOLS.contains(&tool) {
+        path_glob_matches(spec, input, project_path)
+    } else {
+        wildcard_match(spec, input)
+    }
+}
+
+/// Whether a path matches a glob, anchored the same way as path specifiers
+/// (bare patterns like `*.rs` match at any depth below the project)
+pub fn path_glob_matches(pattern: &str, path: &str, project_path: Option<&str>) -> bool {
+    let pattern = normalize_path_pattern(pattern, project_path);
+    let path = match project_path {
+        Some(root) if !path.starts_with('/') && !path.starts_with('~') => {
+            format!("{}/{}", root.trim_end_matches('/'), path)
+        }
+        _ => path.to_string(),
+    };
+    let options = glob::MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+    glob::Pattern::new(&pattern)
+        .map(|p| p.matches_with(&path, options))
+        .unwrap_or(false)
+}
+
+/// The part of a tool call's input that specifiers match against
+pub fn tool_input_subject(tool: &str, input: &Value) -> Option<String> {
+    let key = match tool {
+        "Bash" => "command",
+        "WebFetch" => "url",
+        "NotebookEdit" => "notebook_path",
+        "Glob" | "Grep" => "path",
+        _ if PATH_TOOLS.contains(&tool) => "file_path",
+        _ => return None,
+    };
+    input.get(key).and_then(|v| v.as_str()).map(String::from)
+}
+
+/// Check the syntax of a rule-style tool pattern such as `Bash(git *)`
+pub fn check_tool_pattern(pattern: &str) -> Result<(), String> {
+    parse_rule(pattern).map(|_| ())
+}
+
+/// Whether a rule-style tool pattern applies to a tool call
+pub fn tool_pattern_matches(
+    pattern: &str,
+    tool: &str,
+    input: Option<&str>,
+    project_path: Option<&str>,
+) -> Result<bool, String> {
+    let (rule_tool, specifier) = parse_rule(pattern)?;
+    let rule = PermissionRule {
+        rule: pattern.to_string(),
+        tool: rule_tool,
+        specifier,
+        list: String::new(),
+        scope: String::new(),
+    };
+    Ok(rule_matches(&rule, tool, input, project_path))
+}
+
+/// Read the rules of one list from a settings value
+fn rules_from_settings(settings: &Value, list: &str, scope: &str) -> Vec<PermissionRule> {
+    settings
+        .get("permissions")
+        .and_then(|p| p.get(list))
+        .and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|v| v.as_str())
+                .map(|rule| {
+                    let (tool, specifier) =
+                        parse_rule(rule).unwrap_or_else(|_| (rule.to_string(), None));
+                    PermissionRule {
+                        rule: rule.to_string(),
+                        tool,
+                        specifier,
+                        list: list.to_string(),
+                        scope: scope.to_string(),
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Collect rules from the given scopes (all scopes when None)
+fn collect_rules(
+    scope: Option<&str>,
+    project_path: Option<&str>,
+) -> Result<Vec<PermissionRule>, String> {
+    let scopes: Vec<&str> = match scope {
+        Some(scope) => vec![scope],
+        None => RULE_SCOPES
+            .iter()
+            .copied()
+            .filter(|s| project_path.is_some() || !matches!(*s, "project" | "local"))
+            .collect(),
+    };
+
+    let mut rules = Vec::new();
+    for scope in scopes {
+        let file = read_settings_file(scope, project_path)?;
+        for list in RULE_LISTS {
+            rules.extend(rules_from_settings(&file.settings, list, scope));
+        }
+    }
+    Ok(rules)
+}
+
+/// Apply an edit to one rule list of a scope's settings file
+fn edit_rule_list<F>(
+    scope: &str,
+    project_path: Option<&str>,
+    list: &str,
+    edit: F,
+) -> Result<Vec<String>, String>
+where
+    F: FnOnce(&mut Vec<String>) -> Result<(), String>,
+{
+    if !RULE_LISTS.contains(&list) {
+        return Err(format!("Invalid rule list: {}", list));
+    }
+    if scope == "managed" {
+        return Err("Managed settings are read-only".to_string());
+    }
+
+    let file = read_settings_file(scope, project_path)?;
+    if let Some(e) = file.parse_error {
+        return Err(format!("Settings file is not valid JSON: {}", e));
+    }
+
+    let mut settings = file.settings;
+    if !settings.is_object() {
+        settings = serde_json::json!({});
+    }
+
+    let mut rules: Vec<String> = settings
+        .get("permissions")
+        .and_then(|p| p.get(list))
+        .and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    edit(&mut rules)?;
+
+    if !settings
+        .get("permissions")
+        .map(|p| p.is_object())
+        .unwrap_or(false)
+    {
+        settings["permissions"] = serde_json::json!({});
+    }
+    settings["permissions"][list] = serde_json::json!(rules);
+
+    let path = settings_path(scope, project_path)?;
+    write_settings_atomically(&path, &settings)?;
+    Ok(rules)
+}
+
+/// Allow rule that would cover a call, or None if the call should not be generalized
+fn suggested_rule(tool: &str, subject: Option<&str>, project_path: &str) -> Option<String> {
+    if tool.starts_with("mcp__") || BARE_TOOLS.contains(&tool) {
+        return Some(tool.to_string());
+    }
+    let subject = subject?.trim();
+
+    match tool {
+        "Bash" => {
+            // Chained or redirected commands are too varied to generalize safely
+            if subject.is_empty() || subject.contains(['|', '&', ';', '>', '<', '`', '$', '\n']) {
+                return None;
+            }
+            let words: Vec<&str> = subject.split_whitespace().collect();
+            let first = *words.first()?;
+            if RISKY_COMMANDS.contains(&first) {
+                return None;
+            }
+            let prefix = if SUBCOMMAND_TOOLS.contains(&first) && words.len() > 1 {
+                words[..2].join(" ")
+            } else {
+                first.to_string()
+            };
+            if prefix == subject {
+                Some(format!("Bash({})", subject))
+            } else {
+                Some(format!("Bash({}:*)", prefix))
+            }
+        }
+        "WebFetch" => {
+            let host = url_host(subject);
+            Some(format!("WebFetch(domain:{})", host)).filter(|_| !host.is_empty())
+        }
+        _ if EDIT_RULE_TOOLS.contains(&tool) => {
+            // Only files inside the project, by top-level directory
+            let relative = subject
+                .strip_prefix(project_path.trim_end_matches(['/', '\\']))?
+                .trim_start_matches(['/', '\\']);
+            let (dir, _) = relative.split_once(['/', '\\'])?;
+            Some(format!("Edit({}/**)", dir))
+        }
+        _ => None,
+    }
+}
+
+// ============ Tauri Commands ============
+
+/// List permission rules for one scope, or every scope when scope is None
+#[tauri::command]
+pub async fn list_permission_rules(
+    scope: Option<String>,
+    project_path: Option<String>,
+) -> Result<Vec<PermissionRule>, String> {
+    collect_rules(scope.as_deref(), project_path.as_deref())
+}
+
+/// Validate a rule pattern such as `Bash(npm run *)`
+#[tauri::command]
+pub async fn validate_permission_rule(rule: String) -> Result<PermissionRuleValidation, String> {
+    Ok(check_rule(&rule))
+}
+
+/// Add a rule to the allow, ask or deny list of a scope
+#[tauri::command]
+pub async fn add_permission_rule(
+    scope: String,
+    list: String,
+    rule: String,
+    project_path: Option<String>,
+) -> Result<Vec<String>, String> {
+    info!("Adding {} rule '{}' to {} settings", list, rule, scope);
+
+    let validation = check_rule(&rule);
+    if !validation.valid {
+        return Err(validation.errors.join("; "));
+    }
+
+    let rule = rule.trim().to_string();
+    edit_rule_list(&scope, project_path.as_deref(), &list, |rules| {
+        if rules.contains(&rule) {
+            return Err(format!("Rule already exists in {}: {}", list, rule));
+        }
+        rules.push(rule);
+        Ok(())
+    })
+}
+
+/// Replace an existing rule in place
+#[tauri::command]
+pub async fn update_permission_rule(
+    scope: String,
+    list: String,
+    old_rule: String,
+    new_rule: String,
+    project_path: Option<String>,
+) -> Result<Vec<String>, String> {
+    info!(
+        "Updating {} rule '{}' -> '{}' in {} settings",
+        list, old_rule, new_rule, scope
+    );
+
+    let validation = check_rule(&new_rule);
+    if !validation.valid {
+        return Err(validation.errors.join("; "));
+    }
+
+    let new_rule = new_rule.trim().to_string();
+    edit_rule_list(&scope, project_path.as_deref(), &list, |rules| {
+        if old_rule != new_rule && rules.contains(&new_rule) {
+            return Err(format!("Rule already exists in {}: {}", list, new_rule));
+        }
+        let pos = rules
+            .iter()
+            .position(|r| *r == old_rule)
+            .ok_or_else(|| format!("Rule not found: {}", old_rule))?;
+        rules[pos] = new_rule;
+        Ok(())
+    })
+}
+
+/// Remove a rule from a list
+#[tauri::command]
+pub async fn remove_permission_rule(
+    scope: String,
+    list: String,
+    rule: String,
+    project_path: Option<String>,
+) -> Result<Vec<String>, String> {
+    info!("Removing {} rule '{}' from {} settings", list, rule, scope);
+
+    edit_rule_list(&scope, project_path.as_deref(), &list, |rules| {
+        let before = rules.len();
+        rules.retain(|r| *r != rule);
+        if rules.len() == before {
+            return Err(format!("Rule not found: {}", rule));
+        }
+        Ok(())
+    })
+}
+
+/// Find rules that contradict, duplicate or are shadowed by other rules across all scopes
+#[tauri::command]
+pub async fn detect_permission_conflicts(
+    project_path: Option<String>,
+) -> Result<Vec<PermissionConflict>, String> {
+    let rules = collect_rules(None, project_path.as_deref())?;
+    let strength = |list: &str| RULE_LISTS.iter().position(|l| *l == list).unwrap_or(0);
+    let mut conflicts = Vec::new();
+
+    for (i, rule) in rules.iter().enumerate() {
+        for (j, other) in rules.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+
+            if rule.rule == other.rule {
+                if rule.list == other.list {
+                    if i < j {
+                        conflicts.push(PermissionConflict {
+                            kind: "duplicate".to_string(),
+                            rule: rule.clone(),
+                            other: other.clone(),
+                            message: format!(
+                                "{} is listed in {} by both {} and {} settings",
+                                rule.rule, rule.list, rule.scope, other.scope
+                            ),
+                        });
+                    }
+                } else if strength(&other.list) < strength(&rule.list) {
+                    conflicts.push(PermissionConflict {
+                        kind: "contradiction".to_string(),
+                        rule: rule.clone(),
+                        other: other.clone(),
+                        message: format!(
+                            "{} is in both {} ({}) and {} ({}); {} wins",
+                            rule.rule, rule.list, rule.scope, other.list, other.scope, other.list
+                        ),
+                    });
+                }
+                continue;
+            }
+
+            // A stronger rule that matches everything this rule matches makes it dead
+            let covers = other.tool == rule.tool
+                && strength(&other.list) < strength(&rule.list)
+                && match (&other.specifier, &rule.specifier) {
+                    (None, _) => true,
+                    (Some(_), Some(spec)) => {
+                        rule_matches(other, &rule.tool, Some(spec), project_path.as_deref())
+                    }
+                    (Some(_), None) => false,
+                };
+            if covers {
+                conflicts.push(PermissionConflict {
+                    kind: "shadowed".to_string(),
+                    rule: rule.clone(),
+                    other: other.clone(),
+                    message: format!(
+                        "{} rule {} never applies because {} rule {} takes precedence",
+                        rule.list, rule.rule, other.list, other.rule
+                    ),
+                });
+            }
+        }
+    }
+
+    Ok(conflicts)
+}
+
+/// Report which rule would decide a tool call, e.g. tool "Bash" with input "npm run test"
+#[tauri::command]
+pub async fn test_permission(
+    tool: String,
+    input: Option<String>,
+    project_path: Option<String>,
+) -> Result<PermissionTestResult, String> {
+    let rules = collect_rules(None, project_path.as_deref())?;
+
+    let mut matching_rules: Vec<PermissionRule> = rules
+        .into_iter()
+        .filter(|rule| rule_matches(rule, &tool, input.as_deref(), project_path.as_deref()))
+        .collect();
+    let strength = |list: &str| RULE_LISTS.iter().position(|l| *l == list).unwrap_or(0);
+    matching_rules.sort_by_key(|rule| strength(&rule.list));
+
+    let matched_rule = matching_rules.first().cloned();
+    Ok(PermissionTestResult {
+        decision: matched_rule
+            .as_ref()
+            .map(|r| r.list.clone())
+            .unwrap_or_else(|| "none".to_string()),
+        matched_rule,
+        matching_rules,
+    })
+}
+
+/// Suggest allow rules for tool calls the user approved at least `min_count`
+/// times (default 3) in this project without any rule deciding them. Calls
+/// declined at least once are never generalized.
+#[tauri::command]
+pub async fn suggest_permission_rules(
+    project_path: String,
+    min_count: Option<u32>,
+) -> Result<Vec<PermissionSuggestion>, String> {
+    let min_count = min_count.unwrap_or(DEFAULT_MIN_EVIDENCE).max(1);
+    let rules = collect_rules(None, Some(&project_path))?;
+    let lookup_path = project_path.clone();
+    let calls = tauri::async_runtime::spawn_blocking(move || {
+        super::usage::tool_calls_in_range(Some(&lookup_path), &Default::default())
+    })
+    .await
+    .map_err(|e| format!("Transcript scan failed: {}", e))??;
+
+    let mut suggestions: Vec<PermissionSuggestion> = Vec::new();
+    let mut rejected: Vec<String> = Vec::new();
+    for call in &calls {
+        if PROMPTLESS_TOOLS.contains(&call.tool.as_str()) {
+            continue;
+        }
+        let rule_tool = if EDIT_RULE_TOOLS.contains(&call.tool.as_str()) {
+            "Edit"
+        } else {
+            call.tool.as_str()
+        };
+        // A call some rule already decides never reached the prompt
+        let decided = rules.iter().any(|r| {
+            rule_matches(r, &call.tool, call.subject.as_deref(), Some(&project_path))
+                || rule_matches(r, rule_tool, call.subject.as_deref(), Some(&project_path))
+        });
+        if decided {
+            continue;
+        }
+        let rule = match suggested_rule(&call.tool, call.subject.as_deref(), &project_path) {
+            Some(rule) if check_rule(&rule).valid => rule,
+            _ => continue,
+        };
+
+        match call.outcome {
+            ToolOutcome::Rejected => rejected.push(rule),
+            ToolOutcome::Success | ToolOutcome::Error => {
+                let index = match suggestions.iter().position(|s| s.rule == rule) {
+                    Some(index) => index,
+                    None => {
+                        suggestions.push(PermissionSuggestion {
+                            rule,
+                            tool: rule_tool.to_string(),
+                            evidence_count: 0,
+                            examples: Vec::new(),
+                            last_seen: String::new(),
+                        });
+                        suggestions.len() - 1
+                    }
+                };
+                let suggestion = &mut suggestions[index];
+                suggestion.evidence_count += 1;
+                if call.timestamp > suggestion.last_seen {
+                    suggestion.last_seen = call.timestamp.clone();
+                }
+                if let Some(subject) = &call.subject {
+                    if suggestion.examples.len() < SUGGESTION_EXAMPLES
+                        && !suggestion.examples.contains(subject)
+                    {
+                        suggestion.examples.push(subject.clone());
+                    }
+                }
+            }
+            ToolOutcome::Unknown => {}
+        }
+    }
+
+    suggestions.retain(|s| s.evidence_count >= min_count && !rejected.contains(&s.rule));
+    suggestions.sort_by(|a, b| {
+        b.evidence_count
+            .cmp(&a.evidence_count)
+            .then_with(|| a.rule.cmp(&b.rule))
+    });
+    Ok(suggestions)
+}
+
+/// Add suggested rules to the allow list of a scope (default `local`, the
+/// personal settings file); rules already present are skipped
+#[tauri::command]
+pub async fn apply_suggested_rules(
+    project_path: String,
+    rules: Vec<String>,
+    scope: Option<String>,
+) -> Result<Vec<String>, String> {
+    let scope = scope.unwrap_or_else(|| "local".to_string());
+    for rule in &rules {
+        let validation = check_rule(rule);
+        if !validation.valid {
+            return Err(format!("{}: {}", rule, validation.errors.join("; ")));
+        }
+    }
+
+    info!(
+        "Applying {} suggested rule(s) to {} settings",
+        rules.len(),
+        scope
+    );
+    edit_rule_list(&scope, Some(&project_path), "allow", |existing| {
+        for rule in rules {
+            let rule = rule.trim().to_string();
+            if !existing.contains(&rule) {
+                existing.push(rule);
+            }
+        }
+        Ok(())
+    })
+}
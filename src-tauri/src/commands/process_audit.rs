@@ -0,0 +1,143 @@
+use rusqlite::{params, ToSql};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use super::storage::AgentDb;
+use crate::error::WorkbenchError;
+use crate::process::audit;
+
+/// One recorded process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessAuditEntry {
+    pub id: i64,
+    /// "git", "claude", "gemini", "hook", "mcp", "terminal" or "system"
+    pub source: String,
+    pub program: String,
+    pub args: Vec<String>,
+    pub cwd: Option<String>,
+    pub pid: Option<u32>,
+    pub started_at: String,
+    pub duration_ms: Option<i64>,
+    pub exit_code: Option<i32>,
+    /// "exited", "signaled", "failed_to_start" or "detached"
+    pub outcome: String,
+    pub error: Option<String>,
+}
+
+/// Filters for `get_process_audit`; unset fields match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessAuditFilter {
+    pub source: Option<String>,
+    /// Substring of the program path
+    pub program: Option<String>,
+    /// RFC 3339 timestamps bounding `startedAt`
+    pub since: Option<String>,
+    pub until: Option<String>,
+    /// Only processes that failed to start or exited non-zero
+    #[serde(default)]
+    pub failed_only: bool,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// Recorded processes, most recent first
+#[tauri::command]
+pub async fn get_process_audit(
+    app: AppHandle,
+    filters: Option<ProcessAuditFilter>,
+) -> Result<Vec<ProcessAuditEntry>, WorkbenchError> {
+    let filters = filters.unwrap_or_default();
+    let db = app
+        .try_state::<AgentDb>()
+        .ok_or("Database not initialized")?;
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+
+    let mut sql = String::from(
+        "SELECT id, source, program, args, cwd, pid, started_at, duration_ms, exit_code, outcome, error
+         FROM process_audit WHERE 1 = 1",
+    );
+    let program = filters.program.map(|program| format!("%{}%", program));
+    let mut values: Vec<&dyn ToSql> = Vec::new();
+    if let Some(source) = &filters.source {
+        sql.push_str(" AND source = ?");
+        values.push(source);
+    }
+    if let Some(program) = &program {
+        sql.push_str(" AND program LIKE ?");
+        values.push(program);
+    }
+    if let Some(since) = &filters.since {
+        sql.push_str(" AND started_at >= ?");
+        values.push(since);
+    }
+    if let Some(until) = &filters.until {
+        sql.push_str(" AND started_at <= ?");
+        values.push(until);
+    }
+    if filters.failed_only {
+        sql.push_str(
+            " AND (outcome = 'failed_to_start' OR outcome = 'signaled' OR exit_code != 0)",
+        );
+    }
+    let limit = filters.limit.unwrap_or(200).min(5000);
+    let offset = filters.offset.unwrap_or(0);
+    sql.push_str(" ORDER BY id DESC LIMIT ? OFFSET ?");
+    values.push(&limit);
+    values.push(&offset);
+
+    let mut stmt = conn.prepare(&sql)?;
+    let entries = stmt
+        .query_map(values.as_slice(), |row| {
+            let args: String = row.get(3)?;
+            Ok(ProcessAuditEntry {
+                id: row.get(0)?,
+                source: row.get(1)?,
+                program: row.get(2)?,
+                args: serde_json::from_str(&args).unwrap_or_default(),
+                cwd: row.get(4)?,
+                pid: row.get(5)?,
+                started_at: row.get(6)?,
+                duration_ms: row.get(7)?,
+                exit_code: row.get(8)?,
+                outcome: row.get(9)?,
+                error: row.get(10)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(entries)
+}
+
+/// Delete all recorded processes
+#[tauri::command]
+pub async fn clear_process_audit(app: AppHandle) -> Result<usize, WorkbenchError> {
+    let db = app
+        .try_state::<AgentDb>()
+        .ok_or("Database not initialized")?;
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    Ok(conn.execute("DELETE FROM process_audit", [])?)
+}
+
+#[tauri::command]
+pub async fn get_process_audit_enabled() -> Result<bool, WorkbenchError> {
+    Ok(audit::is_enabled())
+}
+
+/// Switch process auditing on or off; the choice is kept across restarts
+#[tauri::command]
+pub async fn set_process_audit_enabled(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), WorkbenchError> {
+    let db = app
+        .try_state::<AgentDb>()
+        .ok_or("Database not initialized")?;
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+        params![audit::SETTINGS_KEY, enabled.to_string()],
+    )?;
+    audit::set_enabled(enabled);
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use log::{info, warn};
+use tauri::{AppHandle, Manager, State};
+
+use crate::events::{self, AppEvent};
+use crate::process::{OrphanedProcess, ProcessInfo, ProcessRegistryState};
+
+/// Look for processes left behind by a previous (crashed) run and tell the frontend
+pub fn check_orphans_on_startup(app: &AppHandle) {
+    let registry = app.state::<ProcessRegistryState>();
+    match registry.0.find_orphans() {
+        Ok(orphans) if !orphans.is_empty() => {
+            warn!(
+                "Found {} orphaned processes from a previous run",
+                orphans.len()
+            );
+            events::emit(app, AppEvent::OrphanedProcessesDetected(orphans));
+        }
+        Ok(_) => {}
+        Err(e) => warn!("Failed to check for orphaned processes: {}", e),
+    }
+}
+
+// ============ Tauri Commands ============
+
+/// List every child process the workbench is currently tracking
+#[tauri::command]
+pub async fn list_running_processes(
+    registry: State<'_, ProcessRegistryState>,
+) -> Result<Vec<ProcessInfo>, String> {
+    let mut processes = registry.0.get_running_processes()?;
+    processes.sort_by_key(|p| p.started_at);
+    Ok(processes)
+}
+
+/// Kill a tracked process by its run id
+#[tauri::command]
+pub async fn kill_process(
+    registry: State<'_, ProcessRegistryState>,
+    run_id: i64,
+) -> Result<bool, String> {
+    info!("Killing tracked process {}", run_id);
+    registry.0.kill_process(run_id).await
+}
+
+/// List processes from previous runs that are still alive
+#[tauri::command]
+pub async fn list_orphaned_processes(
+    registry: State<'_, ProcessRegistryState>,
+) -> Result<Vec<OrphanedProcess>, String> {
+    registry.0.find_orphans()
+}
+
+/// Kill the given orphans (all of them when `ids` is omitted); returns the ids killed
+#[tauri::command]
+pub async fn kill_orphaned_processes(
+    registry: State<'_, ProcessRegistryState>,
+    ids: Option<Vec<i64>>,
+) -> Result<Vec<i64>, String> {
+    let ids = match ids {
+        Some(ids) => ids,
+        None => registry.0.find_orphans()?.iter().map(|o| o.id).collect(),
+    };
+    info!("Killing {} orphaned processes", ids.len());
+
+    let mut killed = Vec::new();
+    for id in ids {
+        match registry.0.kill_orphan(id) {
+            Ok(true) => killed.push(id),
+            Ok(false) => warn!("Orphan {} was already gone", id),
+            Err(e) => warn!("Failed to kill orphan {}: {}", id, e),
+        }
+    }
+    Ok(killed)
+}
+
+/// Leave an orphan running but stop reporting it
+#[tauri::command]
+pub async fn dismiss_orphaned_process(
+    registry: State<'_, ProcessRegistryState>,
+    id: i64,
+) -> Result<(), String> {
+    registry.0.dismiss_orphan(id)
+}
@@ -0,0 +1,392 @@
+use aes_gcm::aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng};
+/// Encrypted export and import of the workbench profile
+///
+/// A profile archive bundles the user settings (including hooks), user-scope MCP
+/// servers, project metadata (pins, nicknames, tags), app settings and env
+/// profiles, for backup or moving to another machine. The JSON bundle is
+/// zstd-compressed and sealed with AES-256-GCM under a key derived from the
+/// passphrase with Argon2id:
+///
+/// `MAGIC | salt (16) | nonce (12) | ciphertext`
+///
+/// Credentials are only included on request. Without them, keychain secrets are
+/// left out and secret-looking values (`*KEY*`, `*TOKEN*`, `*SECRET*`,
+/// `*PASSWORD*`, `Authorization`) are stripped from settings, MCP servers and env
+/// profiles; importing such an archive keeps the values already on the machine.
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use tauri::{AppHandle, State};
+
+use super::claude::get_claude_dir;
+use super::config_io::write_json_atomic;
+use super::credentials::{self, ExportedCredential};
+use super::storage::AgentDb;
+use crate::paths::claude_json_path;
+
+/// File signature and format version
+const MAGIC: &[u8] = b"CWPROFILE1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// Shortest accepted passphrase
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+/// App settings that only make sense on the machine they were set on
+const MACHINE_SETTINGS: &[&str] = &["claude_binary_path"];
+
+/// App settings that hold secrets, exported only with credentials
+const SECRET_SETTINGS: &[&str] = &["control_api"];
+
+/// Key fragments marking a value as secret
+const SECRET_MARKERS: &[&str] = &["KEY", "TOKEN", "SECRET", "PASSWORD", "AUTHORIZATION"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ProjectRecord {
+    project_path: String,
+    project_id: String,
+    pinned: bool,
+    nickname: Option<String>,
+    tags: String,
+}
+
+/// Decrypted archive contents
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ProfileBundle {
+    created_at: String,
+    app_version: String,
+    /// `~/.claude/settings.json`
+    settings: Option<Value>,
+    /// `mcpServers` of `~/.claude.json`
+    mcp_servers: Option<Value>,
+    projects: Vec<ProjectRecord>,
+    app_settings: BTreeMap<String, String>,
+    env_profiles: Option<Value>,
+    /// None when exported without credentials
+    credentials: Option<Vec<ExportedCredential>>,
+}
+
+/// What an archive holds, returned by export and import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileArchiveSummary {
+    pub path: String,
+    pub created_at: String,
+    pub settings: bool,
+    pub mcp_servers: usize,
+    pub projects: usize,
+    pub app_settings: usize,
+    pub env_profiles: bool,
+    /// None when the archive was made without credentials
+    pub credentials: Option<usize>,
+}
+
+impl ProfileBundle {
+    fn summary(&self, path: &str) -> ProfileArchiveSummary {
+        ProfileArchiveSummary {
+            path: path.to_string(),
+            created_at: self.created_at.clone(),
+            settings: self.settings.is_some(),
+            mcp_servers: self
+                .mcp_servers
+                .as_ref()
+                .and_then(|servers| servers.as_object())
+                .map_or(0, |servers| servers.len()),
+            projects: self.projects.len(),
+            app_settings: self.app_settings.len(),
+            env_profiles: self.env_profiles.is_some(),
+            credentials: self.credentials.as_ref().map(|c| c.len()),
+        }
+    }
+}
+
+fn read_json(path: &Path) -> Result<Option<Value>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse {:?}: {}", path, e))
+}
+
+pub(super) fn is_secret_key(key: &str) -> bool {
+    let key = key.to_ascii_uppercase();
+    SECRET_MARKERS.iter().any(|marker| key.contains(marker))
+}
+
+/// Drop string values under secret-looking keys, at any depth
+fn redact_secrets(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|key, value| !(value.is_string() && is_secret_key(key)));
+            map.values_mut().for_each(redact_secrets);
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_secrets),
+        _ => {}
+    }
+}
+
+/// Overlay `incoming` on `base`: objects merge key by key, anything else is
+/// replaced. Keys missing from `incoming`, such as stripped secrets, are kept.
+fn merge_json(base: &mut Value, incoming: Value) {
+    match (base, incoming) {
+        (Value::Object(base), Value::Object(incoming)) => {
+            for (key, value) in incoming {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, incoming) => *base = incoming,
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key<Aes256Gcm>, String> {
+    let mut key = Key::<Aes256Gcm>::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Failed to derive key: {}", e))?;
+    Ok(key)
+}
+
+fn seal(bundle: &ProfileBundle, passphrase: &str) -> Result<Vec<u8>, String> {
+    let json = serde_json::to_vec(bundle).map_err(|e| e.to_string())?;
+    let compressed =
+        zstd::encode_all(json.as_slice(), 0).map_err(|e| format!("Failed to compress: {}", e))?;
+
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let cipher = Aes256Gcm::new(&derive_key(passphrase, &salt)?);
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, compressed.as_slice())
+        .map_err(|_| "Failed to encrypt profile".to_string())?;
+
+    let mut archive = Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+    archive.extend_from_slice(MAGIC);
+    archive.extend_from_slice(&salt);
+    archive.extend_from_slice(&nonce);
+    archive.extend_from_slice(&ciphertext);
+    Ok(archive)
+}
+
+fn open(archive: &[u8], passphrase: &str) -> Result<ProfileBundle, String> {
+    let body = archive
+        .strip_prefix(MAGIC)
+        .filter(|body| body.len() > SALT_LEN + NONCE_LEN)
+        .ok_or("Not a Claude Workbench profile archive")?;
+    let (salt, rest) = body.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let cipher = Aes256Gcm::new(&derive_key(passphrase, salt)?);
+    let compressed = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Wrong passphrase or damaged archive".to_string())?;
+    let json = zstd::decode_all(compressed.as_slice())
+        .map_err(|e| format!("Failed to decompress profile: {}", e))?;
+    serde_json::from_slice(&json).map_err(|e| format!("Invalid profile archive: {}", e))
+}
+
+fn collect_bundle(
+    app: &AppHandle,
+    db: &AgentDb,
+    include_credentials: bool,
+) -> Result<ProfileBundle, String> {
+    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    let mut bundle = ProfileBundle {
+        created_at: chrono::Utc::now().to_rfc3339(),
+        app_version: app.package_info().version.to_string(),
+        settings: read_json(&claude_dir.join("settings.json"))?,
+        mcp_servers: read_json(&claude_json_path()?)?
+            .and_then(|config| config.get("mcpServers").cloned()),
+        env_profiles: read_json(&super::env_profiles::store_file(app)?)?,
+        ..Default::default()
+    };
+
+    {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT project_path, project_id, pinned, nickname, tags FROM project_metadata",
+            )
+            .map_err(|e| e.to_string())?;
+        bundle.projects = stmt
+            .query_map([], |row| {
+                Ok(ProjectRecord {
+                    project_path: row.get(0)?,
+                    project_id: row.get(1)?,
+                    pinned: row.get::<_, i64>(2)? != 0,
+                    nickname: row.get(3)?,
+                    tags: row.get(4)?,
+                })
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        let mut stmt = conn
+            .prepare("SELECT key, value FROM app_settings")
+            .map_err(|e| e.to_string())?;
+        bundle.app_settings = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<BTreeMap<String, String>, _>>()
+            .map_err(|e| e.to_string())?;
+    }
+    bundle.app_settings.retain(|key, _| {
+        !MACHINE_SETTINGS.contains(&key.as_str())
+            && (include_credentials || !SECRET_SETTINGS.contains(&key.as_str()))
+    });
+
+    if !include_credentials {
+        for value in [
+            &mut bundle.settings,
+            &mut bundle.mcp_servers,
+            &mut bundle.env_profiles,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            redact_secrets(value);
+        }
+    }
+    Ok(bundle)
+}
+
+fn apply_bundle(app: &AppHandle, db: &AgentDb, bundle: &mut ProfileBundle) -> Result<(), String> {
+    if let Some(settings) = bundle.settings.take() {
+        let path = get_claude_dir()
+            .map_err(|e| e.to_string())?
+            .join("settings.json");
+        let mut current = read_json(&path)?.unwrap_or_else(|| Value::Object(Map::new()));
+        merge_json(&mut current, settings);
+        write_json_atomic(&path, &current)?;
+    }
+
+    if let Some(servers) = bundle.mcp_servers.take() {
+        let path = claude_json_path()?;
+        let mut config = read_json(&path)?.unwrap_or_else(|| Value::Object(Map::new()));
+        merge_json(&mut config, serde_json::json!({ "mcpServers": servers }));
+        write_json_atomic(&path, &config)?;
+    }
+
+    if let Some(profiles) = bundle.env_profiles.take() {
+        let path = super::env_profiles::store_file(app)?;
+        let mut current = read_json(&path)?.unwrap_or_else(|| Value::Object(Map::new()));
+        merge_json(&mut current, profiles);
+        write_json_atomic(&path, &current)?;
+    }
+
+    let mut conn = db.0.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for project in &bundle.projects {
+        tx.execute(
+            "INSERT INTO project_metadata (project_path, project_id, pinned, nickname, tags, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, CURRENT_TIMESTAMP)
+             ON CONFLICT(project_path) DO UPDATE SET
+                project_id = excluded.project_id,
+                pinned = excluded.pinned,
+                nickname = excluded.nickname,
+                tags = excluded.tags,
+                updated_at = CURRENT_TIMESTAMP",
+            rusqlite::params![
+                project.project_path,
+                project.project_id,
+                project.pinned as i64,
+                project.nickname,
+                project.tags
+            ],
+        )
+        .map_err(|e| format!("Failed to import project metadata: {}", e))?;
+    }
+    for (key, value) in &bundle.app_settings {
+        if MACHINE_SETTINGS.contains(&key.as_str()) {
+            continue;
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+            rusqlite::params![key, value],
+        )
+        .map_err(|e| format!("Failed to import app settings: {}", e))?;
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+// ============ Tauri Commands ============
+
+/// Write an encrypted profile archive to `path`
+#[tauri::command]
+pub async fn export_profile(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    path: String,
+    passphrase: String,
+    include_credentials: bool,
+) -> Result<ProfileArchiveSummary, String> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(format!(
+            "Passphrase must be at least {} characters",
+            MIN_PASSPHRASE_LEN
+        ));
+    }
+
+    let mut bundle = collect_bundle(&app, &db, include_credentials)?;
+    if include_credentials {
+        bundle.credentials = Some(credentials::export_credentials(&app).await?);
+    }
+    let summary = bundle.summary(&path);
+
+    let archive = tauri::async_runtime::spawn_blocking(move || seal(&bundle, &passphrase))
+        .await
+        .map_err(|e| e.to_string())??;
+    fs::write(&path, archive).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+
+    info!(
+        "Exported profile to {} ({} projects, credentials {})",
+        path,
+        summary.projects,
+        if include_credentials {
+            "included"
+        } else {
+            "excluded"
+        }
+    );
+    Ok(summary)
+}
+
+/// Restore a profile archive over the current one. Settings are merged, so
+/// anything the archive does not carry is kept. App settings take effect after a
+/// restart.
+#[tauri::command]
+pub async fn import_profile(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    path: String,
+    passphrase: String,
+) -> Result<ProfileArchiveSummary, String> {
+    let archive = fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let mut bundle = tauri::async_runtime::spawn_blocking(move || open(&archive, &passphrase))
+        .await
+        .map_err(|e| e.to_string())??;
+    let summary = bundle.summary(&path);
+
+    apply_bundle(&app, &db, &mut bundle)?;
+    if let Some(credentials) = bundle.credentials.take() {
+        credentials::import_credentials(&app, credentials).await?;
+    }
+
+    info!(
+        "Imported profile from {} (created {} by version {})",
+        path, bundle.created_at, bundle.app_version
+    );
+    Ok(summary)
+}
@@ -0,0 +1,324 @@
+use log::{debug, warn};
+/// Per-project statistics for the insights dashboard
+///
+/// Combines transcript scans (sessions, session length), the usage index (tokens,
+/// cost and tool calls), git (lines changed) and the hook run history. Results are
+/// cached per project and range, and reused until a transcript changes or the
+/// entry is older than `CACHE_TTL`, so reopening the dashboard is instant.
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::State;
+
+use super::claude::{encode_project_path, get_claude_dir};
+use super::git_stats::{self, GitDiffStats};
+use super::storage::AgentDb;
+use super::usage::{self, UsageRange};
+
+/// Cached insights are recomputed after this long even if no transcript changed,
+/// since commits and hook runs do not touch the transcripts
+const CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Tools listed in `top_tools`
+const TOP_TOOLS: usize = 10;
+
+/// How often a tool was called
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolUsageCount {
+    pub name: String,
+    pub count: u64,
+}
+
+/// Aggregated statistics for one project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectInsights {
+    pub project_path: String,
+    pub range: UsageRange,
+    pub generated_at: String,
+    pub session_count: usize,
+    pub message_count: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+    pub models: Vec<String>,
+    /// None when the project is not a git repository
+    pub lines_changed: Option<GitDiffStats>,
+    /// Most-called tools, most frequent first
+    pub top_tools: Vec<ToolUsageCount>,
+    pub hook_runs: u64,
+    pub hook_failures: u64,
+    /// Failed runs / all runs, 0 when no hook ran
+    pub hook_failure_rate: f64,
+    /// Mean time between the first and last message of a session
+    pub average_session_secs: f64,
+}
+
+/// Transcript file count and newest modification time, to detect new activity
+type Fingerprint = (usize, Option<SystemTime>);
+
+struct CachedInsights {
+    computed_at: Instant,
+    fingerprint: Fingerprint,
+    insights: ProjectInsights,
+}
+
+static INSIGHTS_CACHE: Lazy<Mutex<HashMap<String, CachedInsights>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Session transcripts of a project
+fn transcript_files(project_path: &str) -> Vec<PathBuf> {
+    let dir = match get_claude_dir() {
+        Ok(dir) => dir.join("projects").join(encode_project_path(project_path)),
+        Err(_) => return Vec::new(),
+    };
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|ext| ext == "jsonl"))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn fingerprint(files: &[PathBuf]) -> Fingerprint {
+    let newest = files
+        .iter()
+        .filter_map(|f| fs::metadata(f).and_then(|m| m.modified()).ok())
+        .max();
+    (files.len(), newest)
+}
+
+/// Sessions and session lengths from the transcripts
+struct TranscriptStats {
+    session_count: usize,
+    total_session_secs: f64,
+}
+
+fn scan_transcripts(
+    files: &[PathBuf],
+    start: Option<chrono::NaiveDate>,
+    end: Option<chrono::NaiveDate>,
+) -> TranscriptStats {
+    let mut stats = TranscriptStats {
+        session_count: 0,
+        total_session_secs: 0.0,
+    };
+
+    for file in files {
+        let content = match fs::read_to_string(file) {
+            Ok(content) => content,
+            Err(e) => {
+                warn!("Failed to read transcript {:?}: {}", file, e);
+                continue;
+            }
+        };
+
+        let mut first: Option<chrono::DateTime<chrono::FixedOffset>> = None;
+        let mut last: Option<chrono::DateTime<chrono::FixedOffset>> = None;
+        for line in content.lines().filter(|l| !l.trim().is_empty()) {
+            let value: serde_json::Value = match serde_json::from_str(line) {
+                Ok(value) => value,
+                Err(_) => continue,
+            };
+            if !matches!(
+                value.get("type").and_then(|t| t.as_str()),
+                Some("user" | "assistant")
+            ) {
+                continue;
+            }
+            let timestamp = value
+                .get("timestamp")
+                .and_then(|t| t.as_str())
+                .unwrap_or("");
+            if !usage::in_date_range(timestamp, start, end) {
+                continue;
+            }
+
+            if let Ok(at) = chrono::DateTime::parse_from_rfc3339(timestamp) {
+                first = Some(first.map_or(at, |f| f.min(at)));
+                last = Some(last.map_or(at, |l| l.max(at)));
+            }
+        }
+
+        if let (Some(first), Some(last)) = (first, last) {
+            stats.session_count += 1;
+            stats.total_session_secs += (last - first).num_milliseconds() as f64 / 1000.0;
+        }
+    }
+    stats
+}
+
+/// Lines changed by commits made within the range
+pub(super) async fn lines_changed(
+    project_path: &str,
+    start: Option<chrono::NaiveDate>,
+    end: Option<chrono::NaiveDate>,
+) -> Option<GitDiffStats> {
+    if !Path::new(project_path).join(".git").exists() {
+        return None;
+    }
+    let from = match start {
+        Some(start) => git_stats::commit_before(project_path, &format!("{} 00:00", start)).ok()?,
+        None => None,
+    }
+    .unwrap_or_else(|| git_stats::EMPTY_TREE.to_string());
+    let to = match end {
+        Some(end) => {
+            let next_day = end.succ_opt()?;
+            git_stats::commit_before(project_path, &format!("{} 00:00", next_day)).ok()?
+        }
+        None => Some("HEAD".to_string()),
+    };
+
+    match to {
+        Some(to) => {
+            match git_stats::diff_stats(project_path.to_string(), from, Some(to), false).await {
+                Ok(stats) => Some(stats),
+                Err(e) => {
+                    debug!("No git stats for {}: {}", project_path, e);
+                    None
+                }
+            }
+        }
+        // No commits yet at the end of the range
+        None => Some(GitDiffStats {
+            lines_added: 0,
+            lines_removed: 0,
+            files_changed: 0,
+        }),
+    }
+}
+
+/// Hook runs and failures recorded for the project within the range
+fn hook_run_counts(
+    db: &AgentDb,
+    project_path: &str,
+    start: Option<chrono::NaiveDate>,
+    end: Option<chrono::NaiveDate>,
+) -> Result<(u64, u64), String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare("SELECT timestamp, success FROM hook_runs WHERE project_path = ?1")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([project_path], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, bool>(1)?))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let (mut runs, mut failures) = (0, 0);
+    for row in rows {
+        let (timestamp, success) = row.map_err(|e| e.to_string())?;
+        if usage::in_date_range(&timestamp, start, end) {
+            runs += 1;
+            if !success {
+                failures += 1;
+            }
+        }
+    }
+    Ok((runs, failures))
+}
+
+// ============ Tauri Commands ============
+
+/// Statistics for a project's dashboard, served from cache while the project's
+/// transcripts are unchanged; `refresh` forces a recompute
+#[tauri::command]
+pub async fn get_project_insights(
+    db: State<'_, AgentDb>,
+    project_path: String,
+    range: Option<UsageRange>,
+    refresh: Option<bool>,
+) -> Result<ProjectInsights, String> {
+    let range = range.unwrap_or_default();
+    let (start, end) = range.bounds()?;
+    let cache_key = format!("{}|{}", project_path, range.cache_key());
+
+    let files = transcript_files(&project_path);
+    let current = fingerprint(&files);
+    if !refresh.unwrap_or(false) {
+        let cache = INSIGHTS_CACHE.lock().map_err(|e| e.to_string())?;
+        if let Some(cached) = cache.get(&cache_key) {
+            if cached.fingerprint == current && cached.computed_at.elapsed() < CACHE_TTL {
+                return Ok(cached.insights.clone());
+            }
+        }
+    }
+
+    let scan_files = files.clone();
+    let transcripts =
+        tauri::async_runtime::spawn_blocking(move || scan_transcripts(&scan_files, start, end))
+            .await
+            .map_err(|e| format!("Transcript scan failed: {}", e))?;
+    let usage_path = project_path.clone();
+    let usage_range = range.clone();
+    let (usage, tool_calls) = tauri::async_runtime::spawn_blocking(move || {
+        Ok::<_, String>((
+            usage::project_usage(&usage_path, &usage_range)?,
+            usage::tool_calls_in_range(Some(&usage_path), &usage_range)?,
+        ))
+    })
+    .await
+    .map_err(|e| format!("Usage scan failed: {}", e))??;
+    let lines_changed = lines_changed(&project_path, start, end).await;
+    let (hook_runs, hook_failures) = hook_run_counts(&db, &project_path, start, end)?;
+
+    let mut tool_counts: HashMap<String, u64> = HashMap::new();
+    for call in tool_calls {
+        *tool_counts.entry(call.tool).or_default() += 1;
+    }
+    let mut top_tools: Vec<ToolUsageCount> = tool_counts
+        .into_iter()
+        .map(|(name, count)| ToolUsageCount { name, count })
+        .collect();
+    top_tools.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    top_tools.truncate(TOP_TOOLS);
+
+    let insights = ProjectInsights {
+        project_path: project_path.clone(),
+        range,
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        session_count: transcripts.session_count,
+        message_count: usage.as_ref().map_or(0, |u| u.message_count),
+        input_tokens: usage.as_ref().map_or(0, |u| u.input_tokens),
+        output_tokens: usage.as_ref().map_or(0, |u| u.output_tokens),
+        cache_creation_tokens: usage.as_ref().map_or(0, |u| u.cache_creation_tokens),
+        cache_read_tokens: usage.as_ref().map_or(0, |u| u.cache_read_tokens),
+        total_tokens: usage.as_ref().map_or(0, |u| u.total_tokens),
+        total_cost: usage.as_ref().map_or(0.0, |u| u.total_cost),
+        models: usage.map(|u| u.models).unwrap_or_default(),
+        lines_changed,
+        top_tools,
+        hook_runs,
+        hook_failures,
+        hook_failure_rate: if hook_runs > 0 {
+            hook_failures as f64 / hook_runs as f64
+        } else {
+            0.0
+        },
+        average_session_secs: if transcripts.session_count > 0 {
+            transcripts.total_session_secs / transcripts.session_count as f64
+        } else {
+            0.0
+        },
+    };
+
+    INSIGHTS_CACHE.lock().map_err(|e| e.to_string())?.insert(
+        cache_key,
+        CachedInsights {
+            computed_at: Instant::now(),
+            fingerprint: current,
+            insights: insights.clone(),
+        },
+    );
+    Ok(insights)
+}
@@ -0,0 +1,261 @@
+/// Project-wide text search
+///
+/// `search_in_project` searches a directory the way ripgrep does, using the
+/// same `ignore` and `grep` crates: `.gitignore` and hidden-file rules apply,
+/// binary files are skipped and the pattern is smart-case (case-insensitive
+/// unless it contains an uppercase letter). Matches are emitted per file as
+/// `project-search-results` while the walk runs, and the command returns a
+/// summary once it is done. With a `request_id` the search can be cancelled
+/// through `cancel_operation`.
+///
+/// Hooks use `project_contains` for `project contains '<pattern>'` conditions.
+use grep_matcher::Matcher;
+use grep_regex::{RegexMatcher, RegexMatcherBuilder};
+use grep_searcher::sinks::Lossy;
+use grep_searcher::{BinaryDetection, SearcherBuilder};
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter};
+use tokio_util::sync::CancellationToken;
+
+use super::operations;
+use crate::error::{WorkbenchError, WorkbenchResult};
+
+/// Matches returned unless the caller asks otherwise, and the upper bound
+const DEFAULT_MAX_RESULTS: usize = 2000;
+const MAX_RESULTS_LIMIT: usize = 20_000;
+/// Longer lines are cut (minified files would otherwise flood the UI)
+const MAX_LINE_BYTES: usize = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchMatch {
+    /// 1-based
+    pub line_number: u64,
+    /// The matching line without its line ending, cut at `MAX_LINE_BYTES`
+    pub line: String,
+    /// Byte ranges of the matches within `line`
+    pub ranges: Vec<[usize; 2]>,
+}
+
+/// Payload of `project-search-results`: the matches in one file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchFileMatches {
+    pub search_id: String,
+    /// Relative to the searched directory
+    pub path: String,
+    pub matches: Vec<SearchMatch>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchSummary {
+    pub search_id: String,
+    pub files_searched: usize,
+    pub files_matched: usize,
+    pub match_count: usize,
+    /// The search stopped at `max_results`
+    pub truncated: bool,
+    pub duration_ms: u64,
+}
+
+pub struct SearchOptions {
+    pub pattern: String,
+    /// Treat `pattern` as a regular expression rather than literal text
+    pub regex: bool,
+    /// Include/exclude globs in gitignore syntax, e.g. `*.rs` or `!target/**`
+    pub globs: Vec<String>,
+    pub max_results: usize,
+}
+
+fn build_matcher(options: &SearchOptions) -> WorkbenchResult<RegexMatcher> {
+    RegexMatcherBuilder::new()
+        .case_smart(true)
+        .fixed_strings(!options.regex)
+        .build(&options.pattern)
+        .map_err(|e| WorkbenchError::InvalidInput(format!("Invalid search pattern: {}", e)))
+}
+
+/// Cut `line` to `MAX_LINE_BYTES` on a character boundary
+fn clip_line(line: &str) -> &str {
+    if line.len() <= MAX_LINE_BYTES {
+        return line;
+    }
+    let mut end = MAX_LINE_BYTES;
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    &line[..end]
+}
+
+/// Search `root`, calling `on_file` with the matches of every matching file.
+/// Stops at `max_results` matches or when `cancel` is tripped.
+pub fn search(
+    root: &Path,
+    options: &SearchOptions,
+    cancel: Option<&CancellationToken>,
+    mut on_file: impl FnMut(String, Vec<SearchMatch>),
+) -> WorkbenchResult<SearchSummary> {
+    let started = Instant::now();
+    let matcher = build_matcher(options)?;
+
+    let mut overrides = OverrideBuilder::new(root);
+    for glob in &options.globs {
+        overrides
+            .add(glob)
+            .map_err(|e| WorkbenchError::InvalidInput(format!("Invalid glob {}: {}", glob, e)))?;
+    }
+    let overrides = overrides
+        .build()
+        .map_err(|e| WorkbenchError::InvalidInput(format!("Invalid globs: {}", e)))?;
+
+    let walker = WalkBuilder::new(root)
+        .overrides(overrides)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+    let mut searcher = SearcherBuilder::new()
+        .binary_detection(BinaryDetection::quit(b'\x00'))
+        .line_number(true)
+        .build();
+
+    let mut summary = SearchSummary::default();
+    for entry in walker {
+        if cancel.is_some_and(|token| token.is_cancelled()) {
+            return Err(WorkbenchError::Cancelled("Project search".to_string()));
+        }
+        if summary.match_count >= options.max_results {
+            summary.truncated = true;
+            break;
+        }
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                debug!("Skipping unreadable entry: {}", e);
+                continue;
+            }
+        };
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        summary.files_searched += 1;
+
+        let remaining = options.max_results - summary.match_count;
+        let mut matches = Vec::new();
+        let result = searcher.search_path(
+            &matcher,
+            entry.path(),
+            Lossy(|line_number, line| {
+                let line = clip_line(line.trim_end_matches(['\r', '\n']));
+                let mut ranges = Vec::new();
+                let _ = matcher.find_iter(line.as_bytes(), |m| {
+                    ranges.push([m.start(), m.end()]);
+                    true
+                });
+                matches.push(SearchMatch {
+                    line_number,
+                    line: line.to_string(),
+                    ranges,
+                });
+                Ok(matches.len() < remaining)
+            }),
+        );
+        if let Err(e) = result {
+            debug!("Failed to search {}: {}", entry.path().display(), e);
+        }
+        if matches.is_empty() {
+            continue;
+        }
+
+        summary.files_matched += 1;
+        summary.match_count += matches.len();
+        let path = entry
+            .path()
+            .strip_prefix(root)
+            .unwrap_or(entry.path())
+            .to_string_lossy()
+            .to_string();
+        on_file(path, matches);
+    }
+
+    summary.duration_ms = started.elapsed().as_millis() as u64;
+    Ok(summary)
+}
+
+/// Whether any file in the project matches the regex `pattern`
+pub fn project_contains(project_path: &str, pattern: &str) -> WorkbenchResult<bool> {
+    let options = SearchOptions {
+        pattern: pattern.to_string(),
+        regex: true,
+        globs: Vec::new(),
+        max_results: 1,
+    };
+    let summary = search(Path::new(project_path), &options, None, |_, _| {})?;
+    Ok(summary.match_count > 0)
+}
+
+// ============ Tauri Commands ============
+
+/// Search the files under `path`. Matches are streamed as
+/// `project-search-results` events tagged with the returned `searchId` (the
+/// request ID when one is given).
+#[tauri::command]
+pub async fn search_in_project(
+    app: AppHandle,
+    path: String,
+    pattern: String,
+    globs: Option<Vec<String>>,
+    regex: Option<bool>,
+    max_results: Option<usize>,
+    request_id: Option<String>,
+) -> Result<SearchSummary, WorkbenchError> {
+    if pattern.is_empty() {
+        return Err(WorkbenchError::InvalidInput(
+            "Search pattern is empty".to_string(),
+        ));
+    }
+    let root = Path::new(&path).to_path_buf();
+    if !root.is_dir() {
+        return Err(WorkbenchError::InvalidInput(format!(
+            "{} is not a directory",
+            path
+        )));
+    }
+    let options = SearchOptions {
+        pattern,
+        regex: regex.unwrap_or(false),
+        globs: globs.unwrap_or_default(),
+        max_results: max_results
+            .unwrap_or(DEFAULT_MAX_RESULTS)
+            .clamp(1, MAX_RESULTS_LIMIT),
+    };
+    let search_id = request_id
+        .clone()
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    let emitter = app.clone();
+    operations::run(&app, request_id, "project-search", async move {
+        let cancel = operations::current_token();
+        tauri::async_runtime::spawn_blocking(move || {
+            let mut summary = search(&root, &options, cancel.as_ref(), |path, matches| {
+                let payload = SearchFileMatches {
+                    search_id: search_id.clone(),
+                    path,
+                    matches,
+                };
+                if let Err(e) = emitter.emit("project-search-results", &payload) {
+                    debug!("Failed to emit search results: {}", e);
+                }
+            })?;
+            summary.search_id = search_id;
+            Ok(summary)
+        })
+        .await
+        .map_err(|_| WorkbenchError::Dropped("Project search".to_string()))?
+    })
+    .await
+}
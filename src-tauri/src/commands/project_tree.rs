@@ -0,0 +1,237 @@
+use log::{debug, warn};
+/// Project file tree
+///
+/// `list_project_tree` lists a directory down to a given depth for the file
+/// explorer and the review hook's file picker. Entries ignored by git are left
+/// out (or flagged, with `include_ignored`), `.git` itself is never listed, and
+/// files carry their git status. Directories below the requested depth come back
+/// without children but with `has_children`, so the frontend expands them by
+/// listing that directory again.
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::git_backend;
+use crate::error::{WorkbenchError, WorkbenchResult};
+
+/// Default and maximum number of directory levels per call
+const DEFAULT_TREE_DEPTH: usize = 1;
+const MAX_TREE_DEPTH: usize = 16;
+/// Entries returned by one call before the listing is cut short
+const MAX_TREE_ENTRIES: usize = 5000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TreeNode {
+    pub name: String,
+    /// Absolute path, to pass back to `list_project_tree` for expansion
+    pub path: String,
+    pub is_dir: bool,
+    pub is_symlink: bool,
+    /// Size of files in bytes
+    pub size: Option<u64>,
+    /// Git status of a file ("modified", "added", "untracked", ...); directories
+    /// are "modified" if anything below them changed
+    pub git_status: Option<String>,
+    /// Matched by a gitignore rule; only listed with `include_ignored`
+    pub ignored: bool,
+    /// None for directories that were not expanded
+    pub children: Option<Vec<TreeNode>>,
+    pub has_children: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectTree {
+    pub root: String,
+    pub is_git_repo: bool,
+    pub nodes: Vec<TreeNode>,
+    /// The listing stopped at the entry limit
+    pub truncated: bool,
+}
+
+struct TreeWalk {
+    repo: Option<git2::Repository>,
+    workdir: Option<PathBuf>,
+    /// Git status by path relative to the repository root
+    statuses: HashMap<String, String>,
+    /// Directories with a changed path somewhere below them
+    dirty_dirs: HashSet<String>,
+    include_ignored: bool,
+    entries: usize,
+    truncated: bool,
+}
+
+impl TreeWalk {
+    /// `path` relative to the repository root, with `/` separators
+    fn repo_relative(&self, path: &Path) -> Option<String> {
+        let relative = path.strip_prefix(self.workdir.as_ref()?).ok()?;
+        Some(
+            relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/"),
+        )
+    }
+
+    fn is_ignored(&self, relative: &str, is_dir: bool) -> bool {
+        let repo = match &self.repo {
+            Some(repo) => repo,
+            None => return false,
+        };
+        // Directory-only rules such as `build/` need the trailing slash
+        let relative = if is_dir {
+            format!("{}/", relative)
+        } else {
+            relative.to_string()
+        };
+        repo.is_path_ignored(Path::new(&relative)).unwrap_or(false)
+    }
+
+    fn list(&mut self, dir: &Path, depth: usize) -> WorkbenchResult<Vec<TreeNode>> {
+        let mut entries: Vec<_> = std::fs::read_dir(dir)
+            .map_err(|e| WorkbenchError::io(format!("Failed to list {}", dir.display()), e))?
+            .filter_map(Result::ok)
+            .collect();
+        entries.sort_by_key(|entry| entry.file_name().to_string_lossy().to_lowercase());
+
+        let mut nodes = Vec::new();
+        for entry in entries {
+            if self.entries >= MAX_TREE_ENTRIES {
+                self.truncated = true;
+                break;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name == ".git" {
+                continue;
+            }
+            let path = entry.path();
+            let metadata = match std::fs::symlink_metadata(&path) {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    debug!("Skipping {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            // Symlinked directories are not followed
+            let is_dir = metadata.is_dir();
+            let relative = self.repo_relative(&path);
+            let ignored = relative
+                .as_deref()
+                .map(|relative| self.is_ignored(relative, is_dir))
+                .unwrap_or(false);
+            if ignored && !self.include_ignored {
+                continue;
+            }
+            self.entries += 1;
+
+            let git_status = relative.as_deref().and_then(|relative| {
+                if is_dir {
+                    self.dirty_dirs
+                        .contains(relative)
+                        .then(|| "modified".to_string())
+                } else {
+                    self.statuses.get(relative).cloned()
+                }
+            });
+
+            // Ignored directories (node_modules, target) are only listed on request
+            let children = if is_dir && depth > 1 && !ignored {
+                Some(self.list(&path, depth - 1)?)
+            } else {
+                None
+            };
+            let has_children = match &children {
+                Some(children) => !children.is_empty(),
+                None => {
+                    is_dir
+                        && std::fs::read_dir(&path)
+                            .map(|mut entries| entries.next().is_some())
+                            .unwrap_or(false)
+                }
+            };
+            nodes.push(TreeNode {
+                name,
+                path: path.to_string_lossy().to_string(),
+                is_dir,
+                is_symlink: metadata.file_type().is_symlink(),
+                size: (!is_dir).then_some(metadata.len()),
+                git_status,
+                ignored,
+                children,
+                has_children,
+            });
+        }
+
+        // Directories first
+        nodes.sort_by_key(|node| !node.is_dir);
+        Ok(nodes)
+    }
+}
+
+/// List `path` down to `depth` levels (1 by default), honoring `.gitignore`
+#[tauri::command]
+pub async fn list_project_tree(
+    path: String,
+    depth: Option<usize>,
+    include_ignored: Option<bool>,
+) -> Result<ProjectTree, WorkbenchError> {
+    let root = PathBuf::from(&path)
+        .canonicalize()
+        .map_err(|e| WorkbenchError::io(format!("Failed to open {}", path), e))?;
+    if !root.is_dir() {
+        return Err(WorkbenchError::InvalidInput(format!(
+            "{} is not a directory",
+            path
+        )));
+    }
+    let depth = depth.unwrap_or(DEFAULT_TREE_DEPTH).clamp(1, MAX_TREE_DEPTH);
+
+    let repo = git2::Repository::discover(&root).ok();
+    let workdir = repo
+        .as_ref()
+        .and_then(|repo| repo.workdir())
+        .and_then(|workdir| workdir.canonicalize().ok());
+    let mut statuses = HashMap::new();
+    if let Some(workdir) = &workdir {
+        match git_backend::status(&workdir.to_string_lossy()).await {
+            Ok(files) => {
+                for file in files {
+                    if let Some(status) = file.unstaged.or(file.staged) {
+                        statuses.insert(file.path, status);
+                    }
+                }
+            }
+            Err(e) => warn!("Failed to read git status of {}: {}", workdir.display(), e),
+        }
+    }
+    let dirty_dirs = statuses
+        .keys()
+        .flat_map(|path| {
+            path.match_indices('/')
+                .map(move |(end, _)| path[..end].to_string())
+        })
+        .collect();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut walk = TreeWalk {
+            repo,
+            workdir,
+            statuses,
+            dirty_dirs,
+            include_ignored: include_ignored.unwrap_or(false),
+            entries: 0,
+            truncated: false,
+        };
+        let nodes = walk.list(&root, depth)?;
+        Ok(ProjectTree {
+            root: root.to_string_lossy().to_string(),
+            is_git_repo: walk.repo.is_some(),
+            nodes,
+            truncated: walk.truncated,
+        })
+    })
+    .await
+    .map_err(|_| WorkbenchError::Dropped("Project tree listing".to_string()))?
+}
@@ -0,0 +1,226 @@
+use log::{info, warn};
+/// Windows bound to projects
+///
+/// Besides `main`, the app can show each project in a window of its own.
+/// `open_project_in_new_window` opens one (or focuses the window that already
+/// has the project) and the registry maps window labels to projects. A window
+/// asks for its project with `get_window_context` when it loads; `main` can
+/// switch projects with `set_window_project`.
+///
+/// `events::emit` uses the registry to send events about a project only to the
+/// windows showing it, so `hook-chain-complete` reaches the window whose session
+/// ran the hooks. Events about a project no window is bound to go to the windows
+/// without a project. Windows must listen with their own listener
+/// (`getCurrentWebviewWindow().listen`); listeners registered for any target
+/// still receive every event. Observer windows (see `observer`) also get the
+/// events about the project of the session they follow.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{
+    AppHandle, Manager, State, WebviewUrl, WebviewWindow, WebviewWindowBuilder, WindowEvent,
+};
+
+use super::observer;
+
+/// Prefix of the labels of project windows
+const LABEL_PREFIX: &str = "project-";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowContext {
+    pub label: String,
+    /// Project the window shows, None if it is not bound to one
+    pub project_path: Option<String>,
+    pub bound_at: Option<String>,
+}
+
+impl WindowContext {
+    fn new(label: &str, project_path: Option<String>) -> Self {
+        Self {
+            label: label.to_string(),
+            bound_at: project_path
+                .as_ref()
+                .map(|_| chrono::Utc::now().to_rfc3339()),
+            project_path,
+        }
+    }
+}
+
+/// Project of each window, by label
+#[derive(Default)]
+pub struct WindowRegistry(Mutex<HashMap<String, WindowContext>>);
+
+/// Compare project paths without trailing separators
+pub(crate) fn same_project(a: &str, b: &str) -> bool {
+    let trim = |path: &str| path.trim_end_matches(['/', '\\']).to_string();
+    trim(a) == trim(b)
+}
+
+fn bound_window(contexts: &HashMap<String, WindowContext>, project_path: &str) -> Option<String> {
+    contexts
+        .values()
+        .find(|c| {
+            c.project_path
+                .as_deref()
+                .is_some_and(|p| same_project(p, project_path))
+        })
+        .map(|c| c.label.clone())
+}
+
+/// Labels of the windows an event about `project_path` should go to. None when
+/// no window is bound to a project, so the event can go to every window.
+pub fn windows_for_project(app: &AppHandle, project_path: &str) -> Option<Vec<String>> {
+    let registry = app.try_state::<WindowRegistry>()?;
+    let contexts = registry.0.lock().ok()?;
+    if contexts.values().all(|c| c.project_path.is_none()) {
+        return None;
+    }
+
+    let mut labels = match bound_window(&contexts, project_path) {
+        Some(label) => vec![label],
+        None => app
+            .webview_windows()
+            .into_keys()
+            .filter(|label| !observer::is_observer(label))
+            .filter(|label| contexts.get(label).is_none_or(|c| c.project_path.is_none()))
+            .collect(),
+    };
+    labels.extend(observer::windows_watching(app, project_path));
+    Some(labels)
+}
+
+fn focus(window: &WebviewWindow) -> Result<(), String> {
+    if window.is_minimized().unwrap_or(false) {
+        window.unminimize().map_err(|e| e.to_string())?;
+    }
+    window.show().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())
+}
+
+fn window_title(project_path: &str) -> String {
+    let name = Path::new(project_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| project_path.to_string());
+    format!("{} - Claude Workbench", name)
+}
+
+// ============ Tauri Commands ============
+
+/// Show a project in a window of its own, focusing the window that already
+/// shows it if there is one
+#[tauri::command]
+pub async fn open_project_in_new_window(
+    app: AppHandle,
+    registry: State<'_, WindowRegistry>,
+    project_path: String,
+) -> Result<WindowContext, String> {
+    if !Path::new(&project_path).is_dir() {
+        return Err(format!("Project directory not found: {}", project_path));
+    }
+
+    let label = {
+        let mut contexts = registry.0.lock().map_err(|e| e.to_string())?;
+        if let Some(label) = bound_window(&contexts, &project_path) {
+            if let Some(window) = app.get_webview_window(&label) {
+                focus(&window)?;
+                return Ok(contexts[&label].clone());
+            }
+            // Closed without the registry hearing about it
+            contexts.remove(&label);
+        }
+        let label = format!(
+            "{}{}",
+            LABEL_PREFIX,
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        );
+        // Registered before the window loads, so its first get_window_context finds it
+        contexts.insert(
+            label.clone(),
+            WindowContext::new(&label, Some(project_path.clone())),
+        );
+        label
+    };
+
+    let window = WebviewWindowBuilder::new(&app, &label, WebviewUrl::default())
+        .title(window_title(&project_path))
+        .inner_size(1200.0, 800.0)
+        .min_inner_size(800.0, 600.0)
+        .focused(true)
+        .build();
+    let window = match window {
+        Ok(window) => window,
+        Err(e) => {
+            if let Ok(mut contexts) = registry.0.lock() {
+                contexts.remove(&label);
+            }
+            return Err(format!("Failed to open window: {}", e));
+        }
+    };
+
+    let handle = app.clone();
+    let closed_label = label.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Destroyed = event {
+            if let Some(registry) = handle.try_state::<WindowRegistry>() {
+                match registry.0.lock() {
+                    Ok(mut contexts) => {
+                        contexts.remove(&closed_label);
+                    }
+                    Err(e) => warn!("Failed to unregister window {}: {}", closed_label, e),
+                }
+            }
+        }
+    });
+
+    info!("Opened window {} for {}", label, project_path);
+    Ok(WindowContext::new(&label, Some(project_path)))
+}
+
+/// Project of the calling window
+#[tauri::command]
+pub async fn get_window_context(
+    window: WebviewWindow,
+    registry: State<'_, WindowRegistry>,
+) -> Result<WindowContext, String> {
+    let contexts = registry.0.lock().map_err(|e| e.to_string())?;
+    Ok(contexts
+        .get(window.label())
+        .cloned()
+        .unwrap_or_else(|| WindowContext::new(window.label(), None)))
+}
+
+/// Bind the calling window to a project, or unbind it with None. A project
+/// shows in one window at a time.
+#[tauri::command]
+pub async fn set_window_project(
+    window: WebviewWindow,
+    registry: State<'_, WindowRegistry>,
+    project_path: Option<String>,
+) -> Result<WindowContext, String> {
+    let mut contexts = registry.0.lock().map_err(|e| e.to_string())?;
+    if let Some(project) = &project_path {
+        if let Some(other) = bound_window(&contexts, project).filter(|l| l != window.label()) {
+            return Err(format!("{} is already open in window {}", project, other));
+        }
+    }
+    let context = WindowContext::new(window.label(), project_path);
+    contexts.insert(context.label.clone(), context.clone());
+    Ok(context)
+}
+
+/// Windows bound to a project
+#[tauri::command]
+pub async fn list_project_windows(
+    registry: State<'_, WindowRegistry>,
+) -> Result<Vec<WindowContext>, String> {
+    let contexts = registry.0.lock().map_err(|e| e.to_string())?;
+    let mut windows: Vec<WindowContext> = contexts
+        .values()
+        .filter(|c| c.project_path.is_some())
+        .cloned()
+        .collect();
+    windows.sort_by(|a, b| a.label.cmp(&b.label));
+    Ok(windows)
+}
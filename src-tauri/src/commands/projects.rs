@@ -0,0 +1,288 @@
+use log::{debug, info};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use tauri::State;
+
+use super::claude::{delete_project, get_claude_dir, list_projects, restore_project};
+use super::storage::AgentDb;
+
+/// Lines read from the top of each session file when looking for a `cwd`
+const CWD_SCAN_LINES: usize = 20;
+
+/// A project as shown in the project picker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisteredProject {
+    /// Directory name under ~/.claude/projects
+    pub id: String,
+    /// Real project directory
+    pub path: String,
+    /// "ok", "missing" (directory gone) or "moved" (sessions point somewhere that exists)
+    pub status: String,
+    /// New location when status is "moved"
+    pub moved_to: Option<String>,
+    pub session_count: usize,
+    /// Unix timestamp of the latest session activity
+    pub last_activity: u64,
+    pub pinned: bool,
+    pub nickname: Option<String>,
+    pub tags: Vec<String>,
+}
+
+/// User-managed metadata stored per project path
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectMetadata {
+    pub pinned: bool,
+    pub nickname: Option<String>,
+    pub tags: Vec<String>,
+}
+
+/// Load all stored metadata keyed by project path
+fn load_all_metadata(conn: &Connection) -> Result<HashMap<String, ProjectMetadata>, String> {
+    let mut stmt = conn
+        .prepare("SELECT project_path, pinned, nickname, tags FROM project_metadata")
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            let tags: String = row.get(3)?;
+            Ok((
+                row.get::<_, String>(0)?,
+                ProjectMetadata {
+                    pinned: row.get::<_, i64>(1)? != 0,
+                    nickname: row.get(2)?,
+                    tags: serde_json::from_str(&tags).unwrap_or_default(),
+                },
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+
+    rows.collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Load metadata for one project, or defaults if none is stored
+fn load_metadata(conn: &Connection, project_path: &str) -> Result<ProjectMetadata, String> {
+    conn.query_row(
+        "SELECT pinned, nickname, tags FROM project_metadata WHERE project_path = ?1",
+        params![project_path],
+        |row| {
+            let tags: String = row.get(2)?;
+            Ok(ProjectMetadata {
+                pinned: row.get::<_, i64>(0)? != 0,
+                nickname: row.get(1)?,
+                tags: serde_json::from_str(&tags).unwrap_or_default(),
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+    .map(|m| m.unwrap_or_default())
+}
+
+/// Insert or replace the metadata for a project
+fn store_metadata(
+    conn: &Connection,
+    project_path: &str,
+    project_id: &str,
+    metadata: &ProjectMetadata,
+) -> Result<(), String> {
+    let tags = serde_json::to_string(&metadata.tags).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO project_metadata (project_path, project_id, pinned, nickname, tags, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, CURRENT_TIMESTAMP)
+         ON CONFLICT(project_path) DO UPDATE SET
+            project_id = excluded.project_id,
+            pinned = excluded.pinned,
+            nickname = excluded.nickname,
+            tags = excluded.tags,
+            updated_at = CURRENT_TIMESTAMP",
+        params![
+            project_path,
+            project_id,
+            metadata.pinned as i64,
+            metadata.nickname,
+            tags
+        ],
+    )
+    .map_err(|e| format!("Failed to save project metadata: {}", e))?;
+    Ok(())
+}
+
+/// Find a `cwd` recorded in the project's sessions that still exists on disk
+fn find_moved_location(project_id: &str, original_path: &str) -> Option<String> {
+    let project_dir = get_claude_dir().ok()?.join("projects").join(project_id);
+    let entries = fs::read_dir(&project_dir).ok()?;
+
+    let mut candidates: Vec<(std::time::SystemTime, String)> = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("jsonl") {
+            continue;
+        }
+        let modified = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .unwrap_or(std::time::SystemTime::UNIX_EPOCH);
+
+        let file = match fs::File::open(&path) {
+            Ok(file) => file,
+            Err(_) => continue,
+        };
+        let cwd = BufReader::new(file)
+            .lines()
+            .take(CWD_SCAN_LINES)
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str::<serde_json::Value>(&line).ok())
+            .find_map(|json| json.get("cwd").and_then(|v| v.as_str()).map(String::from));
+
+        if let Some(cwd) = cwd {
+            candidates.push((modified, cwd));
+        }
+    }
+
+    // Most recently used location wins
+    candidates.sort_by_key(|c| std::cmp::Reverse(c.0));
+    candidates
+        .into_iter()
+        .map(|(_, cwd)| cwd)
+        .find(|cwd| cwd != original_path && Path::new(cwd).is_dir())
+}
+
+// ============ Tauri Commands ============
+
+/// List visible projects with existence status and user metadata, pinned first
+#[tauri::command]
+pub async fn list_project_registry(
+    db: State<'_, AgentDb>,
+) -> Result<Vec<RegisteredProject>, String> {
+    info!("Listing project registry");
+
+    let projects = list_projects().await?;
+    let metadata = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        load_all_metadata(&conn)?
+    };
+
+    let mut registry: Vec<RegisteredProject> = projects
+        .into_iter()
+        .map(|project| {
+            let (status, moved_to) = if Path::new(&project.path).is_dir() {
+                ("ok", None)
+            } else {
+                match find_moved_location(&project.id, &project.path) {
+                    Some(new_path) => {
+                        debug!(
+                            "Project {} appears to have moved to {}",
+                            project.path, new_path
+                        );
+                        ("moved", Some(new_path))
+                    }
+                    None => ("missing", None),
+                }
+            };
+            let meta = metadata.get(&project.path).cloned().unwrap_or_default();
+
+            RegisteredProject {
+                id: project.id,
+                path: project.path,
+                status: status.to_string(),
+                moved_to,
+                session_count: project.sessions.len(),
+                last_activity: project.created_at,
+                pinned: meta.pinned,
+                nickname: meta.nickname,
+                tags: meta.tags,
+            }
+        })
+        .collect();
+
+    registry.sort_by(|a, b| {
+        b.pinned
+            .cmp(&a.pinned)
+            .then(b.last_activity.cmp(&a.last_activity))
+    });
+
+    Ok(registry)
+}
+
+/// Pin or unpin a project in the picker
+#[tauri::command]
+pub async fn pin_project(
+    db: State<'_, AgentDb>,
+    project_id: String,
+    project_path: String,
+    pinned: bool,
+) -> Result<ProjectMetadata, String> {
+    info!("Setting pinned={} for project {}", pinned, project_path);
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let mut metadata = load_metadata(&conn, &project_path)?;
+    metadata.pinned = pinned;
+    store_metadata(&conn, &project_path, &project_id, &metadata)?;
+    Ok(metadata)
+}
+
+/// Set a project's nickname and tags
+#[tauri::command]
+pub async fn update_project_metadata(
+    db: State<'_, AgentDb>,
+    project_id: String,
+    project_path: String,
+    nickname: Option<String>,
+    tags: Vec<String>,
+) -> Result<ProjectMetadata, String> {
+    info!("Updating metadata for project {}", project_path);
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let mut metadata = load_metadata(&conn, &project_path)?;
+    metadata.nickname = nickname
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty());
+
+    let mut cleaned: Vec<String> = tags
+        .into_iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+    cleaned.sort();
+    cleaned.dedup();
+    metadata.tags = cleaned;
+
+    store_metadata(&conn, &project_path, &project_id, &metadata)?;
+    Ok(metadata)
+}
+
+/// Hide or unhide a project (shares the hidden list with delete/restore)
+#[tauri::command]
+pub async fn hide_project(project_id: String, hidden: bool) -> Result<String, String> {
+    if hidden {
+        delete_project(project_id).await
+    } else {
+        restore_project(project_id).await
+    }
+}
+
+/// Re-point a moved project's metadata at its new location
+#[tauri::command]
+pub async fn relocate_project_metadata(
+    db: State<'_, AgentDb>,
+    project_id: String,
+    old_path: String,
+    new_path: String,
+) -> Result<(), String> {
+    info!("Relocating project metadata {} -> {}", old_path, new_path);
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let metadata = load_metadata(&conn, &old_path)?;
+    store_metadata(&conn, &new_path, &project_id, &metadata)?;
+    conn.execute(
+        "DELETE FROM project_metadata WHERE project_path = ?1",
+        params![old_path],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
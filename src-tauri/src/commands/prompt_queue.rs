@@ -0,0 +1,265 @@
+use log::{info, warn};
+/// Prompt queue for sessions that are still responding
+///
+/// Starting a Claude process replaces the one currently running, so a prompt sent
+/// mid-response would abort the turn. Instead `resume_claude_code` parks it here
+/// while the session is busy, and the next queued prompt is sent once the session's
+/// process exits cleanly (its Stop). A cancelled or failed run leaves the queue
+/// paused until the user sends something again, and idle detection can hold it
+/// while the user is away.
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::events::{self, AppEvent};
+
+/// A prompt waiting for its session to finish
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedPrompt {
+    pub id: String,
+    pub session_id: String,
+    pub project_path: String,
+    pub prompt: String,
+    pub model: String,
+    pub plan_mode: Option<bool>,
+    pub max_thinking_tokens: Option<u32>,
+    pub queued_at: String,
+}
+
+/// Queued prompts per session, in dispatch order, and the sessions whose next
+/// prompt waits for the user to come back
+#[derive(Default)]
+pub struct PromptQueueState(
+    pub Mutex<HashMap<String, Vec<QueuedPrompt>>>,
+    Mutex<HashSet<String>>,
+);
+
+fn emit_queue(app: &AppHandle, session_id: &str, project_path: &str, queue: &[QueuedPrompt]) {
+    events::emit(
+        app,
+        AppEvent::PromptQueueChanged {
+            session_id: session_id.to_string(),
+            project_path: project_path.to_string(),
+            queue: queue.to_vec(),
+        },
+    );
+}
+
+/// Whether a Claude process is currently running for the session
+fn session_busy(app: &AppHandle, session_id: &str) -> bool {
+    app.try_state::<crate::process::ProcessRegistryState>()
+        .and_then(|registry| registry.0.get_claude_session_by_id(session_id).ok())
+        .flatten()
+        .is_some()
+}
+
+/// Queue the prompt if the session is still responding; returns true when queued
+pub fn enqueue_if_busy(
+    app: &AppHandle,
+    project_path: &str,
+    session_id: &str,
+    prompt: &str,
+    model: &str,
+    plan_mode: Option<bool>,
+    max_thinking_tokens: Option<u32>,
+) -> Result<bool, String> {
+    if !session_busy(app, session_id) {
+        return Ok(false);
+    }
+    let state = match app.try_state::<PromptQueueState>() {
+        Some(state) => state,
+        None => return Ok(false),
+    };
+
+    let mut queues = state.0.lock().map_err(|e| e.to_string())?;
+    let queue = queues.entry(session_id.to_string()).or_default();
+    queue.push(QueuedPrompt {
+        id: uuid::Uuid::new_v4().to_string(),
+        session_id: session_id.to_string(),
+        project_path: project_path.to_string(),
+        prompt: prompt.to_string(),
+        model: model.to_string(),
+        plan_mode,
+        max_thinking_tokens,
+        queued_at: chrono::Utc::now().to_rfc3339(),
+    });
+    info!(
+        "Session {} is busy, queued prompt ({} waiting)",
+        session_id,
+        queue.len()
+    );
+    emit_queue(app, session_id, project_path, queue);
+    Ok(true)
+}
+
+/// Called when a session's process has exited; sends the next queued prompt
+/// after a clean finish
+pub fn on_session_stopped(app: &AppHandle, session_id: &str, success: bool) {
+    if !success {
+        return;
+    }
+    let state = match app.try_state::<PromptQueueState>() {
+        Some(state) => state,
+        None => return,
+    };
+    if super::idle::holds_prompt_queue(app) {
+        let queued = state
+            .0
+            .lock()
+            .map(|queues| queues.contains_key(session_id))
+            .unwrap_or(false);
+        if queued {
+            info!(
+                "User is away, holding queued prompts for session {}",
+                session_id
+            );
+            if let Ok(mut held) = state.1.lock() {
+                held.insert(session_id.to_string());
+            }
+        }
+        return;
+    }
+    let next = {
+        let mut queues = match state.0.lock() {
+            Ok(queues) => queues,
+            Err(_) => return,
+        };
+        let queue = match queues.get_mut(session_id) {
+            Some(queue) if !queue.is_empty() => queue,
+            _ => return,
+        };
+        let next = queue.remove(0);
+        emit_queue(app, session_id, &next.project_path, queue);
+        if queue.is_empty() {
+            queues.remove(session_id);
+        }
+        next
+    };
+
+    info!(
+        "Dispatching queued prompt {} for session {}",
+        next.id, session_id
+    );
+    events::emit(app, AppEvent::PromptDispatched(next.clone()));
+    // Boxed because resuming spawns the process whose exit leads back here
+    let app = app.clone();
+    let project_path = next.project_path.clone();
+    let dispatch: Pin<Box<dyn Future<Output = Result<(), String>> + Send>> =
+        Box::pin(super::claude::resume_claude_code(
+            app.clone(),
+            next.project_path,
+            next.session_id,
+            next.prompt,
+            next.model,
+            next.plan_mode,
+            next.max_thinking_tokens,
+        ));
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = dispatch.await {
+            warn!("Failed to dispatch queued prompt: {}", e);
+            let error = AppEvent::ClaudeError {
+                session_id: None,
+                project_path: Some(project_path),
+                message: e,
+            };
+            events::emit(&app, error);
+        }
+    });
+}
+
+/// Called when the user is back; sends the next prompt of every queue held
+/// while they were away
+pub fn release_held(app: &AppHandle) {
+    let sessions: Vec<String> = match app.try_state::<PromptQueueState>() {
+        Some(state) => match state.1.lock() {
+            Ok(mut held) => held.drain().collect(),
+            Err(_) => return,
+        },
+        None => return,
+    };
+    for session_id in sessions {
+        if !session_busy(app, &session_id) {
+            on_session_stopped(app, &session_id, true);
+        }
+    }
+}
+
+// ============ Tauri Commands ============
+
+/// Prompts waiting for the session to finish, in dispatch order
+#[tauri::command]
+pub async fn get_prompt_queue(
+    state: State<'_, PromptQueueState>,
+    session_id: String,
+) -> Result<Vec<QueuedPrompt>, String> {
+    let queues = state.0.lock().map_err(|e| e.to_string())?;
+    Ok(queues.get(&session_id).cloned().unwrap_or_default())
+}
+
+/// Reorder a session's queue; `prompt_ids` must list every queued prompt
+#[tauri::command]
+pub async fn reorder_prompt_queue(
+    app: AppHandle,
+    state: State<'_, PromptQueueState>,
+    session_id: String,
+    prompt_ids: Vec<String>,
+) -> Result<Vec<QueuedPrompt>, String> {
+    let mut queues = state.0.lock().map_err(|e| e.to_string())?;
+    let queue = queues
+        .get_mut(&session_id)
+        .ok_or_else(|| format!("No queued prompts for session {}", session_id))?;
+    if prompt_ids.len() != queue.len() {
+        return Err(format!(
+            "Expected {} prompt ids, got {}",
+            queue.len(),
+            prompt_ids.len()
+        ));
+    }
+
+    // Resolve every id before touching the queue so a bad request changes nothing
+    let mut order = Vec::with_capacity(queue.len());
+    for id in &prompt_ids {
+        let index = queue
+            .iter()
+            .position(|p| &p.id == id)
+            .ok_or_else(|| format!("Queued prompt not found: {}", id))?;
+        if order.contains(&index) {
+            return Err(format!("Duplicate prompt id: {}", id));
+        }
+        order.push(index);
+    }
+    *queue = order.into_iter().map(|i| queue[i].clone()).collect();
+    if let Some(project_path) = queue.first().map(|p| p.project_path.clone()) {
+        emit_queue(&app, &session_id, &project_path, queue);
+    }
+    Ok(queue.clone())
+}
+
+/// Remove a prompt from the queue before it is sent
+#[tauri::command]
+pub async fn cancel_queued_prompt(
+    app: AppHandle,
+    state: State<'_, PromptQueueState>,
+    session_id: String,
+    prompt_id: String,
+) -> Result<Vec<QueuedPrompt>, String> {
+    let mut queues = state.0.lock().map_err(|e| e.to_string())?;
+    let queue = queues
+        .get_mut(&session_id)
+        .ok_or_else(|| format!("No queued prompts for session {}", session_id))?;
+    let index = queue
+        .iter()
+        .position(|p| p.id == prompt_id)
+        .ok_or_else(|| format!("Queued prompt not found: {}", prompt_id))?;
+    let cancelled = queue.remove(index);
+    emit_queue(&app, &session_id, &cancelled.project_path, queue);
+
+    let remaining = queue.clone();
+    if remaining.is_empty() {
+        queues.remove(&session_id);
+    }
+    Ok(remaining)
+}
@@ -0,0 +1,444 @@
+use log::info;
+/// Prompt template library
+///
+/// Reusable prompts the user fills in and sends, such as "review {{file}} for
+/// {{concern}}". Unlike slash commands, which are files Claude expands itself,
+/// templates are rendered here and arrive in the session as an ordinary prompt.
+///
+/// Placeholders are written `{{name}}`. Each variable has a type, checked when
+/// rendering, and may have a default; placeholders not declared in `variables`
+/// are treated as required text. Templates can be imported from and exported to
+/// Markdown with YAML frontmatter holding the title, tags and variables.
+use regex::{Captures, Regex};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::{AppHandle, State};
+
+use super::storage::AgentDb;
+
+const SELECT_COLUMNS: &str = "id, title, tags, variables, body, created_at, updated_at";
+
+/// Matches `{{name}}`, capturing the name
+fn placeholder() -> Regex {
+    Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_-]*)\s*\}\}").expect("valid regex")
+}
+
+fn valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VariableKind {
+    #[default]
+    Text,
+    /// Text entered in a larger field
+    Multiline,
+    Number,
+    Boolean,
+    /// One of `options`
+    Choice,
+}
+
+/// A variable a template is filled in with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateVariable {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub kind: VariableKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<serde_json::Value>,
+    /// Allowed values of a `choice`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<String>,
+    /// Rendering fails when no value or default is given; otherwise the
+    /// placeholder becomes empty
+    #[serde(default = "default_required")]
+    pub required: bool,
+}
+
+fn default_required() -> bool {
+    true
+}
+
+/// A stored template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    pub id: i64,
+    pub title: String,
+    pub tags: Vec<String>,
+    pub variables: Vec<TemplateVariable>,
+    pub body: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// A template to create, or to update when `id` is set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplateInput {
+    pub id: Option<i64>,
+    pub title: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub variables: Vec<TemplateVariable>,
+    pub body: String,
+}
+
+/// Frontmatter of an imported or exported template
+#[derive(Debug, Serialize, Deserialize)]
+struct TemplateFrontmatter {
+    title: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    variables: Vec<TemplateVariable>,
+}
+
+/// Column holding a JSON array
+fn json_column<T: serde::de::DeserializeOwned>(row: &Row, index: usize) -> rusqlite::Result<T> {
+    let value: String = row.get(index)?;
+    serde_json::from_str(&value).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+fn row_to_template(row: &Row) -> rusqlite::Result<PromptTemplate> {
+    Ok(PromptTemplate {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        tags: json_column(row, 2)?,
+        variables: json_column(row, 3)?,
+        body: row.get(4)?,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+    })
+}
+
+fn load_template(conn: &Connection, id: i64) -> Result<PromptTemplate, String> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM prompt_templates WHERE id = ?1",
+            SELECT_COLUMNS
+        ),
+        [id],
+        row_to_template,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Prompt template not found: {}", id))
+}
+
+/// Check the variables and declare placeholders that are missing from them
+fn normalize(mut input: PromptTemplateInput) -> Result<PromptTemplateInput, String> {
+    input.title = input.title.trim().to_string();
+    if input.title.is_empty() {
+        return Err("Template title is empty".to_string());
+    }
+    if input.body.trim().is_empty() {
+        return Err("Template body is empty".to_string());
+    }
+    input.tags = input
+        .tags
+        .iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    input.tags.sort();
+    input.tags.dedup();
+
+    for (i, variable) in input.variables.iter().enumerate() {
+        if !valid_name(&variable.name) {
+            return Err(format!("Invalid variable name: '{}'", variable.name));
+        }
+        if input.variables[..i].iter().any(|v| v.name == variable.name) {
+            return Err(format!("Variable '{}' is declared twice", variable.name));
+        }
+        if variable.kind == VariableKind::Choice && variable.options.is_empty() {
+            return Err(format!(
+                "Choice variable '{}' has no options",
+                variable.name
+            ));
+        }
+        if let Some(default) = &variable.default {
+            check_value(variable, default)?;
+        }
+    }
+    for capture in placeholder().captures_iter(&input.body) {
+        let name = &capture[1];
+        if !input.variables.iter().any(|v| v.name == name) {
+            input.variables.push(TemplateVariable {
+                name: name.to_string(),
+                kind: VariableKind::Text,
+                description: None,
+                default: None,
+                options: Vec::new(),
+                required: true,
+            });
+        }
+    }
+    Ok(input)
+}
+
+/// The text a value stands for, if it suits the variable's type
+fn check_value(variable: &TemplateVariable, value: &serde_json::Value) -> Result<String, String> {
+    let text = match value {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Number(number) => number.to_string(),
+        serde_json::Value::Bool(flag) => flag.to_string(),
+        _ => {
+            return Err(format!(
+                "Variable '{}' must be a string, number or boolean",
+                variable.name
+            ))
+        }
+    };
+    let valid = match variable.kind {
+        VariableKind::Text | VariableKind::Multiline => true,
+        VariableKind::Number => text.trim().parse::<f64>().is_ok(),
+        VariableKind::Boolean => matches!(text.as_str(), "true" | "false"),
+        VariableKind::Choice => variable.options.contains(&text),
+    };
+    if !valid {
+        let expected = match variable.kind {
+            VariableKind::Number => "a number".to_string(),
+            VariableKind::Boolean => "true or false".to_string(),
+            _ => format!("one of: {}", variable.options.join(", ")),
+        };
+        return Err(format!(
+            "Variable '{}' must be {}, got '{}'",
+            variable.name, expected, text
+        ));
+    }
+    Ok(text)
+}
+
+/// Fill in the placeholders of a template
+fn render(
+    template: &PromptTemplate,
+    values: &HashMap<String, serde_json::Value>,
+) -> Result<String, String> {
+    let mut filled = HashMap::new();
+    for variable in &template.variables {
+        let value = values
+            .get(&variable.name)
+            .filter(|value| !value.is_null() && value.as_str() != Some(""))
+            .or(variable.default.as_ref());
+        let text = match value {
+            Some(value) => check_value(variable, value)?,
+            None if variable.required => {
+                return Err(format!("Missing value for '{}'", variable.name))
+            }
+            None => String::new(),
+        };
+        filled.insert(variable.name.as_str(), text);
+    }
+    Ok(placeholder()
+        .replace_all(&template.body, |capture: &Captures| {
+            // Every placeholder is declared by `normalize`
+            filled.get(&capture[1]).cloned().unwrap_or_default()
+        })
+        .into_owned())
+}
+
+fn save(conn: &Connection, input: PromptTemplateInput) -> Result<PromptTemplate, String> {
+    let input = normalize(input)?;
+    let tags = serde_json::to_string(&input.tags).map_err(|e| e.to_string())?;
+    let variables = serde_json::to_string(&input.variables).map_err(|e| e.to_string())?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let id = match input.id {
+        Some(id) => {
+            let updated = conn
+                .execute(
+                    "UPDATE prompt_templates SET title = ?1, tags = ?2, variables = ?3, body = ?4, updated_at = ?5
+                     WHERE id = ?6",
+                    params![input.title, tags, variables, input.body, now, id],
+                )
+                .map_err(|e| format!("Failed to save prompt template: {}", e))?;
+            if updated == 0 {
+                return Err(format!("Prompt template not found: {}", id));
+            }
+            id
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO prompt_templates (title, tags, variables, body, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+                params![input.title, tags, variables, input.body, now],
+            )
+            .map_err(|e| format!("Failed to save prompt template: {}", e))?;
+            conn.last_insert_rowid()
+        }
+    };
+    load_template(conn, id)
+}
+
+/// Split Markdown into its YAML frontmatter and body
+fn parse_markdown(content: &str) -> Result<PromptTemplateInput, String> {
+    let lines: Vec<&str> = content.trim_start_matches('\u{feff}').lines().collect();
+    if lines.first().map(|line| line.trim_end()) != Some("---") {
+        return Err("Template has no frontmatter with a title".to_string());
+    }
+    let end = lines
+        .iter()
+        .skip(1)
+        .position(|line| line.trim_end() == "---")
+        .map(|i| i + 1)
+        .ok_or_else(|| "Frontmatter is not closed with '---'".to_string())?;
+    let frontmatter: TemplateFrontmatter = serde_yaml::from_str(&lines[1..end].join("\n"))
+        .map_err(|e| format!("Invalid frontmatter: {}", e))?;
+    let body = lines[end + 1..].join("\n");
+    Ok(PromptTemplateInput {
+        id: None,
+        title: frontmatter
+            .title
+            .ok_or_else(|| "Frontmatter has no title".to_string())?,
+        tags: frontmatter.tags,
+        variables: frontmatter.variables,
+        body: body.trim_start_matches('\n').to_string(),
+    })
+}
+
+fn to_markdown(template: &PromptTemplate) -> Result<String, String> {
+    let frontmatter = TemplateFrontmatter {
+        title: Some(template.title.clone()),
+        tags: template.tags.clone(),
+        variables: template.variables.clone(),
+    };
+    let yaml = serde_yaml::to_string(&frontmatter).map_err(|e| e.to_string())?;
+    Ok(format!("---\n{}---\n\n{}", yaml, template.body))
+}
+
+// ============ Tauri Commands ============
+
+/// Templates ordered by title, optionally only those with the given tag
+#[tauri::command]
+pub async fn list_prompt_templates(
+    db: State<'_, AgentDb>,
+    tag: Option<String>,
+) -> Result<Vec<PromptTemplate>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM prompt_templates ORDER BY title COLLATE NOCASE, id",
+            SELECT_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let templates = stmt
+        .query_map([], row_to_template)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(templates
+        .into_iter()
+        .filter(|t| tag.as_ref().is_none_or(|tag| t.tags.contains(tag)))
+        .collect())
+}
+
+#[tauri::command]
+pub async fn get_prompt_template(
+    db: State<'_, AgentDb>,
+    id: i64,
+) -> Result<PromptTemplate, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_template(&conn, id)
+}
+
+/// Create a template, or update it when `id` is set
+#[tauri::command]
+pub async fn save_prompt_template(
+    db: State<'_, AgentDb>,
+    template: PromptTemplateInput,
+) -> Result<PromptTemplate, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let template = save(&conn, template)?;
+    info!("Saved prompt template {} '{}'", template.id, template.title);
+    Ok(template)
+}
+
+#[tauri::command]
+pub async fn delete_prompt_template(db: State<'_, AgentDb>, id: i64) -> Result<(), String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let deleted = conn
+        .execute("DELETE FROM prompt_templates WHERE id = ?1", [id])
+        .map_err(|e| format!("Failed to delete prompt template: {}", e))?;
+    if deleted == 0 {
+        return Err(format!("Prompt template not found: {}", id));
+    }
+    info!("Deleted prompt template {}", id);
+    Ok(())
+}
+
+/// The prompt a template gives with the given variable values
+#[tauri::command]
+pub async fn render_prompt_template(
+    db: State<'_, AgentDb>,
+    id: i64,
+    values: HashMap<String, serde_json::Value>,
+) -> Result<String, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    render(&load_template(&conn, id)?, &values)
+}
+
+/// Render a template and send it to a session as a prompt, queued if the session
+/// is still responding; returns the prompt sent
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn send_prompt_template(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    id: i64,
+    values: HashMap<String, serde_json::Value>,
+    session_id: String,
+    project_path: String,
+    model: String,
+    plan_mode: Option<bool>,
+) -> Result<String, String> {
+    let prompt = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        render(&load_template(&conn, id)?, &values)?
+    };
+    super::claude::resume_claude_code(
+        app,
+        project_path,
+        session_id.clone(),
+        prompt.clone(),
+        model,
+        plan_mode,
+        None,
+    )
+    .await?;
+    info!("Sent prompt template {} to session {}", id, session_id);
+    Ok(prompt)
+}
+
+/// Create a template from Markdown with `title`, `tags` and `variables` in its
+/// YAML frontmatter
+#[tauri::command]
+pub async fn import_prompt_template(
+    db: State<'_, AgentDb>,
+    content: String,
+) -> Result<PromptTemplate, String> {
+    let input = parse_markdown(&content)?;
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let template = save(&conn, input)?;
+    info!(
+        "Imported prompt template {} '{}'",
+        template.id, template.title
+    );
+    Ok(template)
+}
+
+/// A template as Markdown with YAML frontmatter, as `import_prompt_template` reads it
+#[tauri::command]
+pub async fn export_prompt_template(db: State<'_, AgentDb>, id: i64) -> Result<String, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    to_markdown(&load_template(&conn, id)?)
+}
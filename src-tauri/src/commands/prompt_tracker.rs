@@ -475,6 +475,7 @@ pub async fn mark_prompt_completed(
 /// Revert to a specific prompt with support for different rewind modes
 #[tauri::command]
 pub async fn revert_to_prompt(
+    app: tauri::AppHandle,
     session_id: String,
     project_id: String,
     project_path: String,
@@ -484,6 +485,8 @@ pub async fn revert_to_prompt(
     log::info!("Reverting to prompt #{} in session: {} with mode: {:?}",
         prompt_index, session_id, mode);
 
+    super::session_state::ensure_not_live(&app, &session_id, "rewind")?;
+
     // Get prompts from JSONL (single source of truth)
     let prompts = extract_prompts_from_jsonl(&session_id, &project_id)
         .map_err(|e| format!("Failed to extract prompts: {}", e))?;
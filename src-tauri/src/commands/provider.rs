@@ -4,17 +4,68 @@ use std::fs;
 use std::path::PathBuf;
 use tauri::{command, AppHandle};
 
+use super::enhanced_hooks::{trigger_hook_event, HookContext};
+use crate::events::{self, AppEvent};
+
+/// 切换代理商时需要清理的环境变量（覆盖所有代理商类型）
+const PROVIDER_ENV_KEYS: &[&str] = &[
+    "ANTHROPIC_API_KEY",
+    "ANTHROPIC_AUTH_TOKEN",
+    "ANTHROPIC_BASE_URL",
+    "ANTHROPIC_MODEL",
+    "ANTHROPIC_SMALL_FAST_MODEL",
+    "API_TIMEOUT_MS",
+    "CLAUDE_CODE_DISABLE_NONESSENTIAL_TRAFFIC",
+    "CLAUDE_CODE_USE_BEDROCK",
+    "CLAUDE_CODE_USE_VERTEX",
+    "AWS_REGION",
+    "AWS_PROFILE",
+    "CLOUD_ML_REGION",
+    "ANTHROPIC_VERTEX_PROJECT_ID",
+];
+
+/// 代理商类型
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderType {
+    /// Anthropic 官方 API
+    Anthropic,
+    /// AWS Bedrock
+    Bedrock,
+    /// Google Vertex AI
+    Vertex,
+    /// 兼容 Anthropic API 的第三方中转
+    #[default]
+    Custom,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ProviderConfig {
     pub id: String,
     pub name: String,
     pub description: String,
+    #[serde(default)]
+    pub provider_type: ProviderType,
+    /// Bedrock / Vertex 不需要 base_url
+    #[serde(default)]
     pub base_url: String,
     pub auth_token: Option<String>,
     pub api_key: Option<String>,
     pub api_key_helper: Option<String>,
     pub model: Option<String>,
     pub enable_auto_api_key_helper: Option<bool>,
+    /// Bedrock 的 AWS 区域或 Vertex 的 GCP 区域
+    #[serde(default)]
+    pub region: Option<String>,
+    /// Bedrock 使用的 AWS profile（为空时使用默认凭证链）
+    #[serde(default)]
+    pub aws_profile: Option<String>,
+    /// Vertex 的 GCP 项目 ID
+    #[serde(default)]
+    pub vertex_project_id: Option<String>,
+    /// 钥匙串中的凭证名称；设置后密钥在启动 Claude CLI 时注入，不写入 settings.json
+    #[serde(default)]
+    pub credential: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -32,9 +83,7 @@ pub struct CurrentConfig {
 
 // 获取Claude设置文件路径
 fn get_settings_path() -> Result<PathBuf, String> {
-    let home_dir = dirs::home_dir().ok_or_else(|| "无法获取用户主目录".to_string())?;
-
-    let config_dir = home_dir.join(".claude");
+    let config_dir = crate::paths::claude_dir()?;
 
     // 确保配置目录存在
     if !config_dir.exists() {
@@ -46,8 +95,8 @@ fn get_settings_path() -> Result<PathBuf, String> {
 
 // 获取遗留的providers.json路径（用于迁移）
 fn get_legacy_providers_path() -> Result<PathBuf, String> {
-    let home_dir = dirs::home_dir().ok_or_else(|| "无法获取用户主目录".to_string())?;
-    Ok(home_dir.join(".claude").join("providers.json"))
+    let config_dir = crate::paths::claude_dir()?;
+    Ok(config_dir.join("providers.json"))
 }
 
 // 读取settings.json文件
@@ -81,10 +130,8 @@ fn load_settings() -> Result<Value, String> {
 fn save_settings(settings: &Value) -> Result<(), String> {
     let settings_path = get_settings_path()?;
 
-    let content =
-        serde_json::to_string_pretty(settings).map_err(|e| format!("序列化设置失败: {}", e))?;
-
-    fs::write(&settings_path, content).map_err(|e| format!("写入设置文件失败: {}", e))?;
+    super::config_io::write_json_atomic(&settings_path, settings)
+        .map_err(|e| format!("写入设置文件失败: {}", e))?;
 
     Ok(())
 }
@@ -244,10 +291,43 @@ pub fn get_current_provider_config() -> Result<CurrentConfig, String> {
     })
 }
 
+// 当前代理商的 API 地址（settings.json 的 env 优先，其次是进程环境变量），供网络监测探测
+pub(crate) fn api_endpoint() -> String {
+    let settings = get_settings_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .unwrap_or_default();
+    let env = |key: &str| {
+        settings
+            .get("env")
+            .and_then(|env| env.get(key))
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string())
+            .or_else(|| std::env::var(key).ok())
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    if env("CLAUDE_CODE_USE_BEDROCK").is_some() {
+        let region = env("AWS_REGION").unwrap_or_else(|| "us-east-1".to_string());
+        return format!("https://bedrock-runtime.{}.amazonaws.com", region);
+    }
+    if env("CLAUDE_CODE_USE_VERTEX").is_some() {
+        return match env("CLOUD_ML_REGION") {
+            Some(region) if region != "global" => {
+                format!("https://{}-aiplatform.googleapis.com", region)
+            }
+            _ => "https://aiplatform.googleapis.com".to_string(),
+        };
+    }
+    env("ANTHROPIC_BASE_URL").unwrap_or_else(|| "https://api.anthropic.com".to_string())
+}
+
 // 切换代理商配置（写入settings.json的env字段）
 #[command]
 pub async fn switch_provider_config(
-    _app: AppHandle,
+    app: AppHandle,
     config: ProviderConfig,
 ) -> Result<String, String> {
     log::info!(
@@ -277,16 +357,38 @@ pub async fn switch_provider_config(
         .as_object_mut()
         .ok_or("env字段格式错误")?;
 
-    // 清理之前的ANTHROPIC环境变量
-    env_obj.remove("ANTHROPIC_API_KEY");
-    env_obj.remove("ANTHROPIC_AUTH_TOKEN");
-    env_obj.remove("ANTHROPIC_BASE_URL");
-    env_obj.remove("ANTHROPIC_MODEL");
+    // 清理之前代理商写入的环境变量
+    for key in PROVIDER_ENV_KEYS {
+        env_obj.remove(*key);
+    }
+    // 解除之前代理商绑定的钥匙串凭证，避免旧密钥随新代理商一起注入
+    crate::commands::credentials::unbind_env_vars(&app, PROVIDER_ENV_KEYS)?;
+
+    // Bedrock / Vertex 通过 Claude CLI 的云厂商开关接入，不使用 base_url 和令牌
+    if matches!(
+        config.provider_type,
+        ProviderType::Bedrock | ProviderType::Vertex
+    ) {
+        insert_cloud_provider_env(env_obj, &config);
+        settings_obj.remove("apiKeyHelper");
+        save_settings(&settings)?;
+
+        log::info!("代理商配置切换完成: {}", config.name);
+        return Ok(format!(
+            "✅ 已成功切换到 {} ({})\n\n配置已写入 ~/.claude/settings.json，即时生效！",
+            config.name, config.description
+        ));
+    }
 
-    // 清理Claude Code 2025的新环境变量
-    env_obj.remove("ANTHROPIC_SMALL_FAST_MODEL");
-    env_obj.remove("API_TIMEOUT_MS");
-    env_obj.remove("CLAUDE_CODE_DISABLE_NONESSENTIAL_TRAFFIC");
+    // 使用钥匙串凭证时，把它绑定到对应的环境变量，由进程启动时注入
+    if let Some(credential) = config.credential.as_deref().filter(|c| !c.is_empty()) {
+        let env_var = match config.provider_type {
+            ProviderType::Anthropic => "ANTHROPIC_API_KEY",
+            _ => "ANTHROPIC_AUTH_TOKEN",
+        };
+        crate::commands::credentials::bind_env_var(&app, credential, env_var)?;
+        log::info!("代理商使用钥匙串凭证 {} ({})", credential, env_var);
+    }
 
     // 设置新的环境变量
     env_obj.insert(
@@ -382,12 +484,64 @@ pub async fn switch_provider_config(
     ))
 }
 
+// 写入 Bedrock / Vertex 所需的环境变量（已通过校验）
+fn insert_cloud_provider_env(
+    env_obj: &mut serde_json::Map<String, Value>,
+    config: &ProviderConfig,
+) {
+    let mut set = |key: &str, value: &str| {
+        env_obj.insert(key.to_string(), Value::String(value.to_string()));
+    };
+    let non_empty = |value: &Option<String>| value.clone().filter(|v| !v.trim().is_empty());
+
+    match config.provider_type {
+        ProviderType::Bedrock => {
+            set("CLAUDE_CODE_USE_BEDROCK", "1");
+            if let Some(region) = non_empty(&config.region) {
+                set("AWS_REGION", &region);
+            }
+            if let Some(profile) = non_empty(&config.aws_profile) {
+                set("AWS_PROFILE", &profile);
+            }
+        }
+        ProviderType::Vertex => {
+            set("CLAUDE_CODE_USE_VERTEX", "1");
+            if let Some(region) = non_empty(&config.region) {
+                set("CLOUD_ML_REGION", &region);
+            }
+            if let Some(project_id) = non_empty(&config.vertex_project_id) {
+                set("ANTHROPIC_VERTEX_PROJECT_ID", &project_id);
+            }
+        }
+        ProviderType::Anthropic | ProviderType::Custom => {}
+    }
+
+    if let Some(model) = non_empty(&config.model) {
+        set("ANTHROPIC_MODEL", &model);
+    }
+}
+
 // 验证第三方API配置的兼容性（Claude Code 2025标准）
 fn validate_third_party_config(config: &ProviderConfig) -> Result<(), String> {
+    let missing = |value: &Option<String>| value.as_deref().is_none_or(|v| v.trim().is_empty());
+    match config.provider_type {
+        ProviderType::Bedrock if missing(&config.region) => {
+            return Err("Bedrock 需要设置 AWS 区域".to_string());
+        }
+        ProviderType::Vertex if missing(&config.region) => {
+            return Err("Vertex 需要设置 GCP 区域".to_string());
+        }
+        ProviderType::Vertex if missing(&config.vertex_project_id) => {
+            return Err("Vertex 需要设置 GCP 项目 ID".to_string());
+        }
+        ProviderType::Bedrock | ProviderType::Vertex => return Ok(()),
+        ProviderType::Anthropic | ProviderType::Custom => {}
+    }
+
     // 检查是否为第三方API
     if config.base_url != "https://api.anthropic.com" {
         // 确保有认证信息
-        if config.auth_token.is_none() && config.api_key.is_none() {
+        if config.auth_token.is_none() && config.api_key.is_none() && missing(&config.credential) {
             return Err("第三方API需要设置认证令牌或API密钥".to_string());
         }
 
@@ -421,17 +575,19 @@ fn validate_third_party_config(config: &ProviderConfig) -> Result<(), String> {
 
 // 清理代理商配置（清理settings.json的env字段中的ANTHROPIC变量和apiKeyHelper字段）
 #[command]
-pub async fn clear_provider_config(_app: AppHandle) -> Result<String, String> {
+pub async fn clear_provider_config(app: AppHandle) -> Result<String, String> {
     log::info!("开始清理代理商配置");
 
+    // 解除绑定到代理商环境变量的钥匙串凭证
+    crate::commands::credentials::unbind_env_vars(&app, PROVIDER_ENV_KEYS)?;
+
     let mut settings = load_settings()?;
 
-    // 如果有env字段，清理ANTHROPIC相关变量
+    // 如果有env字段，清理代理商相关变量
     if let Some(env_obj) = settings.get_mut("env").and_then(|v| v.as_object_mut()) {
-        env_obj.remove("ANTHROPIC_API_KEY");
-        env_obj.remove("ANTHROPIC_AUTH_TOKEN");
-        env_obj.remove("ANTHROPIC_BASE_URL");
-        env_obj.remove("ANTHROPIC_MODEL");
+        for key in PROVIDER_ENV_KEYS {
+            env_obj.remove(*key);
+        }
 
         log::info!("已清理ANTHROPIC环境变量");
     }
@@ -450,6 +606,54 @@ pub async fn clear_provider_config(_app: AppHandle) -> Result<String, String> {
     Ok("✅ 已清理所有ANTHROPIC环境变量和apiKeyHelper配置\n\n配置已从 ~/.claude/settings.json 中移除！".to_string())
 }
 
+// 按ID切换到已保存的代理商配置，并通知前端和 OnProviderSwitch 钩子
+#[command]
+pub async fn switch_provider(
+    app: AppHandle,
+    profile_id: String,
+    project_path: Option<String>,
+) -> Result<String, String> {
+    let config = get_provider_config(profile_id)?;
+    let previous = get_current_provider_config().ok();
+    let message = switch_provider_config(app.clone(), config.clone()).await?;
+
+    // 事件中不包含任何凭证
+    let data = serde_json::json!({
+        "provider": {
+            "id": config.id,
+            "name": config.name,
+            "provider_type": config.provider_type,
+            "base_url": config.base_url,
+            "model": config.model,
+            "region": config.region,
+        },
+        "previous": previous.map(|p| serde_json::json!({
+            "base_url": p.anthropic_base_url,
+            "model": p.anthropic_model,
+        })),
+    });
+    events::emit(&app, AppEvent::ProviderSwitched(data.clone()));
+
+    // 钩子从项目设置中加载，因此只有指定项目时才触发
+    if let Some(project_path) = project_path {
+        let context = HookContext {
+            event: "OnProviderSwitch".to_string(),
+            session_id: String::new(),
+            project_path,
+            data,
+        };
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) =
+                trigger_hook_event(app, "OnProviderSwitch".to_string(), context, None).await
+            {
+                log::warn!("OnProviderSwitch hooks failed: {}", e);
+            }
+        });
+    }
+
+    Ok(message)
+}
+
 // 测试代理商连接
 #[command]
 pub fn test_provider_connection(base_url: String) -> Result<String, String> {
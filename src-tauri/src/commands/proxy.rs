@@ -0,0 +1,230 @@
+use log::{info, warn};
+/// Outbound proxy configuration
+///
+/// The proxy is stored in the `app_settings` table and cached in memory so that
+/// code without an `AppHandle` (HTTP clients, command builders) can read it. When
+/// enabled it is injected as `HTTP_PROXY` / `HTTPS_PROXY` / `NO_PROXY` into the
+/// Claude CLI, MCP servers and hook commands, and configured on reqwest clients.
+use once_cell::sync::Lazy;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+use tauri::State;
+
+use super::storage::AgentDb;
+
+/// `app_settings` key holding the JSON config
+const SETTINGS_KEY: &str = "proxy_config";
+
+/// Checked by `test_proxy_connectivity` when no URL is given
+const DEFAULT_TEST_URL: &str = "https://api.anthropic.com";
+
+/// Proxy settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ProxyConfig {
+    pub enabled: bool,
+    /// Proxy for plain HTTP, e.g. `http://proxy.corp:8080`
+    #[serde(default)]
+    pub http_proxy: Option<String>,
+    /// Proxy for HTTPS; falls back to `http_proxy` when unset
+    #[serde(default)]
+    pub https_proxy: Option<String>,
+    /// Comma-separated hosts that bypass the proxy
+    #[serde(default)]
+    pub no_proxy: Option<String>,
+}
+
+impl ProxyConfig {
+    fn https(&self) -> Option<&str> {
+        self.https_proxy
+            .as_deref()
+            .or(self.http_proxy.as_deref())
+            .filter(|s| !s.trim().is_empty())
+    }
+
+    fn http(&self) -> Option<&str> {
+        self.http_proxy.as_deref().filter(|s| !s.trim().is_empty())
+    }
+}
+
+/// Result of `test_proxy_connectivity`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyTestResult {
+    pub success: bool,
+    pub url: String,
+    pub status: Option<u16>,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+static PROXY_CONFIG: Lazy<RwLock<ProxyConfig>> = Lazy::new(|| RwLock::new(ProxyConfig::default()));
+
+/// The proxy currently in effect
+pub fn current_proxy() -> ProxyConfig {
+    PROXY_CONFIG
+        .read()
+        .map(|config| config.clone())
+        .unwrap_or_default()
+}
+
+/// Load the stored proxy into memory; called once at startup
+pub fn load_proxy_config(conn: &Connection) {
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT value FROM app_settings WHERE key = ?1",
+            [SETTINGS_KEY],
+            |row| row.get(0),
+        )
+        .optional()
+        .unwrap_or_else(|e| {
+            warn!("Failed to read proxy config: {}", e);
+            None
+        });
+
+    let config = match stored.map(|json| serde_json::from_str::<ProxyConfig>(&json)) {
+        Some(Ok(config)) => config,
+        Some(Err(e)) => {
+            warn!("Stored proxy config is invalid, ignoring: {}", e);
+            ProxyConfig::default()
+        }
+        None => ProxyConfig::default(),
+    };
+    if config.enabled {
+        info!("Using outbound proxy");
+    }
+    if let Ok(mut current) = PROXY_CONFIG.write() {
+        *current = config;
+    }
+}
+
+/// Environment variables for child processes; empty when the proxy is off.
+/// Both cases are set because tools disagree on which one they read.
+pub fn proxy_env_vars() -> Vec<(&'static str, String)> {
+    let config = current_proxy();
+    if !config.enabled {
+        return Vec::new();
+    }
+
+    let mut vars = Vec::new();
+    if let Some(http) = config.http() {
+        vars.push(("HTTP_PROXY", http.to_string()));
+        vars.push(("http_proxy", http.to_string()));
+    }
+    if let Some(https) = config.https() {
+        vars.push(("HTTPS_PROXY", https.to_string()));
+        vars.push(("https_proxy", https.to_string()));
+    }
+    if let Some(no_proxy) = config.no_proxy.as_deref().filter(|s| !s.trim().is_empty()) {
+        vars.push(("NO_PROXY", no_proxy.to_string()));
+        vars.push(("no_proxy", no_proxy.to_string()));
+    }
+    vars
+}
+
+/// reqwest proxies for a config; empty when the proxy is off
+fn client_proxies(config: &ProxyConfig) -> Result<Vec<reqwest::Proxy>, String> {
+    if !config.enabled {
+        return Ok(Vec::new());
+    }
+
+    let no_proxy = config
+        .no_proxy
+        .as_deref()
+        .and_then(reqwest::NoProxy::from_string);
+    let mut proxies = Vec::new();
+    if let Some(http) = config.http() {
+        let proxy = reqwest::Proxy::http(http).map_err(|e| format!("Invalid HTTP proxy: {}", e))?;
+        proxies.push(proxy.no_proxy(no_proxy.clone()));
+    }
+    if let Some(https) = config.https() {
+        let proxy =
+            reqwest::Proxy::https(https).map_err(|e| format!("Invalid HTTPS proxy: {}", e))?;
+        proxies.push(proxy.no_proxy(no_proxy));
+    }
+    Ok(proxies)
+}
+
+/// Route a reqwest client through the configured proxy
+pub fn apply_to_client(builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+    match client_proxies(&current_proxy()) {
+        Ok(proxies) => proxies.into_iter().fold(builder, |b, proxy| b.proxy(proxy)),
+        Err(e) => {
+            // Validated on save, so only a hand-edited database gets here
+            warn!("Ignoring proxy config: {}", e);
+            builder
+        }
+    }
+}
+
+// ============ Tauri Commands ============
+
+/// Get the proxy settings
+#[tauri::command]
+pub async fn get_proxy_config() -> Result<ProxyConfig, String> {
+    Ok(current_proxy())
+}
+
+/// Save the proxy settings; they apply to processes and clients created afterwards
+#[tauri::command]
+pub async fn update_proxy_config(
+    db: State<'_, AgentDb>,
+    config: ProxyConfig,
+) -> Result<(), String> {
+    client_proxies(&config)?;
+    info!("Updating proxy configuration (enabled: {})", config.enabled);
+
+    let json = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+    {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+            params![SETTINGS_KEY, json],
+        )
+        .map_err(|e| format!("Failed to save proxy config: {}", e))?;
+    }
+
+    let mut current = PROXY_CONFIG.write().map_err(|e| e.to_string())?;
+    *current = config;
+    Ok(())
+}
+
+/// Make a request through a proxy (the given config, or the saved one)
+#[tauri::command]
+pub async fn test_proxy_connectivity(
+    config: Option<ProxyConfig>,
+    url: Option<String>,
+) -> Result<ProxyTestResult, String> {
+    let config = config.unwrap_or_else(current_proxy);
+    let url = url.unwrap_or_else(|| DEFAULT_TEST_URL.to_string());
+    info!("Testing proxy connectivity to {}", url);
+
+    let client = client_proxies(&config)?
+        .into_iter()
+        .fold(reqwest::Client::builder(), |b, proxy| b.proxy(proxy))
+        .timeout(Duration::from_secs(15))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let start = Instant::now();
+    let result = client.head(&url).send().await;
+    let latency_ms = start.elapsed().as_millis() as u64;
+
+    // Any HTTP response means the proxy let us through, even 4xx from the target
+    Ok(match result {
+        Ok(response) => ProxyTestResult {
+            success: true,
+            url,
+            status: Some(response.status().as_u16()),
+            latency_ms,
+            error: None,
+        },
+        Err(e) => ProxyTestResult {
+            success: false,
+            url,
+            status: None,
+            latency_ms,
+            error: Some(e.to_string()),
+        },
+    })
+}
@@ -0,0 +1,310 @@
+use log::{info, warn};
+/// Quick prompt palette behind a global shortcut
+///
+/// A configurable system-wide hotkey toggles a small always-on-top window (label
+/// `quick-prompt`; the frontend renders the palette when it runs in that window).
+/// A submitted prompt goes to the chosen session, or the most recent one: the
+/// newest running session, else the active workspace tab. Busy sessions queue it
+/// like any other prompt. The shortcut is off until the user sets one.
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use super::storage::AgentDb;
+use crate::events::{self, AppEvent};
+use crate::process::{ProcessRegistryState, ProcessType};
+
+/// `app_settings` key holding the JSON config
+const SETTINGS_KEY: &str = "quick_prompt";
+
+pub const WINDOW_LABEL: &str = "quick-prompt";
+
+/// Persisted palette settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickPromptConfig {
+    /// e.g. `CommandOrControl+Shift+Space`; None disables the shortcut
+    pub shortcut: Option<String>,
+    /// Model for sessions that are not running, unless their project has a default
+    pub default_model: String,
+}
+
+impl Default for QuickPromptConfig {
+    fn default() -> Self {
+        Self {
+            shortcut: None,
+            default_model: "sonnet".to_string(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct QuickPromptState(Mutex<QuickPromptConfig>);
+
+/// A session the palette can send to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickPromptTarget {
+    pub session_id: String,
+    pub project_path: String,
+    pub title: String,
+    /// Model of the running process, if any
+    pub model: Option<String>,
+    pub running: bool,
+}
+
+fn load_config(app: &AppHandle) -> Result<QuickPromptConfig, String> {
+    let db = app
+        .try_state::<AgentDb>()
+        .ok_or("Database not initialized")?;
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT value FROM app_settings WHERE key = ?1",
+            [SETTINGS_KEY],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    match stored {
+        Some(json) => {
+            serde_json::from_str(&json).map_err(|e| format!("Invalid quick prompt settings: {}", e))
+        }
+        None => Ok(QuickPromptConfig::default()),
+    }
+}
+
+fn save_config(db: &AgentDb, config: &QuickPromptConfig) -> Result<(), String> {
+    let json = serde_json::to_string(config).map_err(|e| e.to_string())?;
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+        params![SETTINGS_KEY, json],
+    )
+    .map_err(|e| format!("Failed to save quick prompt settings: {}", e))?;
+    Ok(())
+}
+
+fn parse_shortcut(shortcut: &str) -> Result<Shortcut, String> {
+    Shortcut::from_str(shortcut.trim()).map_err(|e| format!("Invalid shortcut {}: {}", shortcut, e))
+}
+
+fn register_shortcut(app: &AppHandle, shortcut: Shortcut) -> Result<(), String> {
+    app.global_shortcut()
+        .on_shortcut(shortcut, |app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                if let Err(e) = toggle_window(app) {
+                    warn!("Failed to toggle quick prompt: {}", e);
+                }
+            }
+        })
+        .map_err(|e| e.to_string())
+}
+
+/// Show the palette, creating its window on first use, or hide it if it is showing
+fn toggle_window(app: &AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+        if window.is_visible().unwrap_or(false) {
+            return window.hide().map_err(|e| e.to_string());
+        }
+        window.show().map_err(|e| e.to_string())?;
+        return window.set_focus().map_err(|e| e.to_string());
+    }
+    WebviewWindowBuilder::new(app, WINDOW_LABEL, WebviewUrl::default())
+        .title("Quick Prompt")
+        .inner_size(640.0, 160.0)
+        .resizable(false)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .center()
+        .focused(true)
+        .build()
+        .map(|_| ())
+        .map_err(|e| format!("Failed to open quick prompt window: {}", e))
+}
+
+/// Load the settings and register the saved shortcut
+pub fn init_quick_prompt(app: &AppHandle) {
+    let config = load_config(app).unwrap_or_else(|e| {
+        warn!("{}", e);
+        QuickPromptConfig::default()
+    });
+    if let Some(shortcut) = &config.shortcut {
+        match parse_shortcut(shortcut).and_then(|parsed| register_shortcut(app, parsed)) {
+            Ok(()) => info!("Quick prompt shortcut {} registered", shortcut),
+            Err(e) => warn!("Quick prompt shortcut {} unavailable: {}", shortcut, e),
+        }
+    }
+    if let Some(state) = app.try_state::<QuickPromptState>() {
+        if let Ok(mut current) = state.0.lock() {
+            *current = config;
+        }
+    }
+}
+
+/// Sessions to offer, most recent first: running sessions newest first, then the
+/// active workspace tab, then the other open tabs
+fn collect_targets(app: &AppHandle) -> Result<Vec<QuickPromptTarget>, String> {
+    let mut running = app
+        .state::<ProcessRegistryState>()
+        .0
+        .get_running_claude_sessions()?;
+    running.sort_by_key(|process| std::cmp::Reverse(process.started_at));
+
+    let mut targets: Vec<QuickPromptTarget> = running
+        .into_iter()
+        .filter_map(|process| match process.process_type {
+            ProcessType::ClaudeSession { session_id } => Some(QuickPromptTarget {
+                session_id,
+                title: process.task.chars().take(80).collect(),
+                project_path: process.project_path,
+                model: Some(process.model),
+                running: true,
+            }),
+            _ => None,
+        })
+        .collect();
+
+    let workspace = super::workspace::load_workspace(app)?;
+    let active = workspace.active_tab_id.as_deref();
+    let mut tabs: Vec<_> = workspace.tabs.iter().collect();
+    tabs.sort_by_key(|tab| Some(tab.id.as_str()) != active);
+    for tab in tabs {
+        let (session_id, project_path) = match (&tab.session_id, &tab.project_path) {
+            (Some(session_id), Some(project_path)) => (session_id, project_path),
+            _ => continue,
+        };
+        if targets.iter().any(|t| &t.session_id == session_id) {
+            continue;
+        }
+        targets.push(QuickPromptTarget {
+            session_id: session_id.clone(),
+            project_path: project_path.clone(),
+            title: tab.title.clone(),
+            model: None,
+            running: false,
+        });
+    }
+    Ok(targets)
+}
+
+// ============ Tauri Commands ============
+
+#[tauri::command]
+pub async fn get_quick_prompt_config(
+    state: State<'_, QuickPromptState>,
+) -> Result<QuickPromptConfig, String> {
+    Ok(state.0.lock().map_err(|e| e.to_string())?.clone())
+}
+
+/// Change (or with None, remove) the palette's global shortcut. Fails without
+/// changing anything if the shortcut is invalid or already taken, by this app or
+/// another one.
+#[tauri::command]
+pub async fn set_global_shortcut(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    state: State<'_, QuickPromptState>,
+    shortcut: Option<String>,
+) -> Result<QuickPromptConfig, String> {
+    let shortcut = shortcut
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty());
+    let new = shortcut.as_deref().map(parse_shortcut).transpose()?;
+    let mut config = state.0.lock().map_err(|e| e.to_string())?.clone();
+    let current = config
+        .shortcut
+        .as_deref()
+        .and_then(|s| parse_shortcut(s).ok());
+
+    if new != current {
+        let global_shortcut = app.global_shortcut();
+        if let (Some(new), Some(name)) = (new, &shortcut) {
+            if global_shortcut.is_registered(new) {
+                return Err(format!("{} is already used by Claude Workbench", name));
+            }
+        }
+        if let Some(current) = current {
+            let _ = global_shortcut.unregister(current);
+        }
+        if let (Some(new), Some(name)) = (new, &shortcut) {
+            if let Err(e) = register_shortcut(&app, new) {
+                if let Some(current) = current {
+                    let _ = register_shortcut(&app, current);
+                }
+                return Err(format!(
+                    "{} could not be registered, it may be in use by another application: {}",
+                    name, e
+                ));
+            }
+        }
+    }
+
+    config.shortcut = shortcut;
+    save_config(&db, &config)?;
+    *state.0.lock().map_err(|e| e.to_string())? = config.clone();
+    info!("Quick prompt shortcut set to {:?}", config.shortcut);
+    Ok(config)
+}
+
+/// Sessions the palette can send to, most recent first
+#[tauri::command]
+pub async fn list_quick_prompt_targets(app: AppHandle) -> Result<Vec<QuickPromptTarget>, String> {
+    collect_targets(&app)
+}
+
+/// Send a prompt from the palette to `session_id`, or the most recent session,
+/// then hide the palette
+#[tauri::command]
+pub async fn submit_quick_prompt(
+    app: AppHandle,
+    state: State<'_, QuickPromptState>,
+    prompt: String,
+    session_id: Option<String>,
+) -> Result<QuickPromptTarget, String> {
+    let prompt = prompt.trim().to_string();
+    if prompt.is_empty() {
+        return Err("Prompt is empty".to_string());
+    }
+    let targets = collect_targets(&app)?;
+    let target = match &session_id {
+        Some(id) => targets.into_iter().find(|t| &t.session_id == id),
+        None => targets.into_iter().next(),
+    }
+    .ok_or_else(|| "No session to send the prompt to".to_string())?;
+
+    let project_default = super::models::project_default(&app, &target.project_path);
+    let model = match target.model.clone().or(project_default) {
+        Some(model) => model,
+        None => state
+            .0
+            .lock()
+            .map_err(|e| e.to_string())?
+            .default_model
+            .clone(),
+    };
+    super::claude::resume_claude_code(
+        app.clone(),
+        target.project_path.clone(),
+        target.session_id.clone(),
+        prompt,
+        model,
+        None,
+        None,
+    )
+    .await?;
+
+    events::emit(&app, AppEvent::QuickPromptSubmitted(target.clone()));
+    if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+        let _ = window.hide();
+    }
+    Ok(target)
+}
+
+/// Show or hide the palette, as the shortcut does
+#[tauri::command]
+pub async fn toggle_quick_prompt(app: AppHandle) -> Result<(), String> {
+    toggle_window(&app)
+}
@@ -0,0 +1,525 @@
+use log::warn;
+/// Redaction of secrets and personal data
+///
+/// Text that leaves the app or is kept for later inspection passes through a
+/// `Redactor` built from the persisted `RedactionConfig`: session exports, shared
+/// bundles, the hook run and blocked command logs, and the telemetry payload.
+/// Matches are found by
+///
+/// - built-in patterns for API keys and tokens with a known shape, private key
+///   blocks, bearer tokens, credentials in URLs and values assigned to names
+///   containing KEY, TOKEN, SECRET or PASSWORD,
+/// - an entropy check for long random-looking strings,
+/// - email addresses,
+/// - the home directory (shown as `~`) and user names in other home paths,
+/// - user-defined patterns.
+///
+/// Overlapping matches are resolved in favour of the one that starts first.
+/// Strings on the allowlist are never masked. `preview_redaction` shows what a
+/// session export would mask.
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, State};
+
+use super::session_export::{find_session_file, load_transcript};
+use super::storage::AgentDb;
+
+/// `app_settings` key holding the JSON config
+const SETTINGS_KEY: &str = "redaction";
+
+/// Matches listed by `preview_redaction`; the counts cover all of them
+const MAX_PREVIEW_MATCHES: usize = 500;
+
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Names of fields and variables whose values are secrets
+const SECRET_NAME_MARKERS: &[&str] = &["KEY", "TOKEN", "SECRET", "PASSWORD", "AUTHORIZATION"];
+
+/// Persisted redaction settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedactionConfig {
+    /// Built-in patterns for keys, tokens and credentials
+    pub secrets: bool,
+    /// Mask long strings that look random
+    pub entropy: bool,
+    /// Shannon entropy in bits per character from which a string counts as random
+    pub min_entropy: f64,
+    /// Shortest string the entropy check looks at
+    pub min_entropy_length: usize,
+    pub emails: bool,
+    /// Replace the home directory with `~` and other users' names with `[USER]`
+    pub paths: bool,
+    pub custom_patterns: Vec<RedactionPattern>,
+    /// Exact strings that are never masked
+    pub allowlist: Vec<String>,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            secrets: true,
+            entropy: true,
+            min_entropy: 4.2,
+            min_entropy_length: 24,
+            emails: true,
+            paths: true,
+            custom_patterns: Vec::new(),
+            allowlist: Vec::new(),
+        }
+    }
+}
+
+/// A user-defined pattern; the first capture group is masked if there is one,
+/// the whole match otherwise
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RedactionPattern {
+    pub name: String,
+    pub pattern: String,
+}
+
+/// One masked piece of text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionFinding {
+    /// Name of the rule that matched
+    pub rule: String,
+    pub original: String,
+    pub replacement: String,
+}
+
+struct Rule {
+    name: String,
+    regex: Regex,
+    /// Capture group to mask
+    group: usize,
+    replacement: &'static str,
+    /// Extra test on the masked text
+    check: Option<fn(&str) -> bool>,
+}
+
+impl Rule {
+    fn new(name: &str, pattern: &str, group: usize, replacement: &'static str) -> Self {
+        Self {
+            name: name.to_string(),
+            regex: Regex::new(pattern).expect("valid redaction pattern"),
+            group,
+            replacement,
+            check: None,
+        }
+    }
+
+    fn checked(mut self, check: fn(&str) -> bool) -> Self {
+        self.check = Some(check);
+        self
+    }
+}
+
+/// Values assigned to secret-looking names are masked only if they mix letters
+/// and digits; plain numbers and words are settings
+fn mixes_letters_and_digits(value: &str) -> bool {
+    value.chars().any(|c| c.is_ascii_digit()) && value.chars().any(|c| c.is_ascii_alphabetic())
+}
+
+/// `git@github.com` and the like are remotes, not addresses
+fn is_personal_email(email: &str) -> bool {
+    !email.starts_with("git@") && !email.contains("noreply")
+}
+
+/// Built-in secret patterns: rule name, regex, capture group to mask
+const SECRET_RULES: &[(&str, &str, usize)] = &[
+    ("anthropic_key", r"sk-ant-[A-Za-z0-9_\-]{16,}", 0),
+    ("openai_key", r"\bsk-(?:proj-)?[A-Za-z0-9_\-]{20,}", 0),
+    (
+        "github_token",
+        r"\b(?:gh[pousr]_[A-Za-z0-9]{20,}|github_pat_[A-Za-z0-9_]{20,})",
+        0,
+    ),
+    ("aws_access_key", r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b", 0),
+    ("slack_token", r"\bxox[abprs]-[A-Za-z0-9\-]{10,}", 0),
+    ("google_api_key", r"\bAIza[0-9A-Za-z_\-]{35}", 0),
+    (
+        "jwt",
+        r"\beyJ[A-Za-z0-9_\-]{8,}\.[A-Za-z0-9_\-]{8,}\.[A-Za-z0-9_\-]{8,}",
+        0,
+    ),
+    (
+        "private_key",
+        r"(?s)-----BEGIN [A-Z ]*PRIVATE KEY-----.*?-----END [A-Z ]*PRIVATE KEY-----",
+        0,
+    ),
+    (
+        "bearer_token",
+        r"(?i)\bbearer\s+([A-Za-z0-9._~+/\-]{16,}=*)",
+        1,
+    ),
+    (
+        "url_credentials",
+        r"(?i)\b[a-z][a-z0-9+.\-]*://[^\s:/@]+:([^\s@/]+)@",
+        1,
+    ),
+];
+
+const ASSIGNMENT_PATTERN: &str = r#"(?i)[A-Za-z0-9_.\-]*(?:key|token|secret|password|passwd)[A-Za-z0-9_]*["']?\s*[=:]\s*["']?([^\s"',;]{8,})"#;
+const EMAIL_PATTERN: &str = r"\b[A-Za-z0-9._%+\-]+@[A-Za-z0-9.\-]+\.[A-Za-z]{2,}\b";
+const USER_PATH_PATTERN: &str = r#"(?:/home/|/Users/|[A-Za-z]:\\Users\\)([^/\\\s"']+)"#;
+
+/// Average bits of information per character
+fn shannon_entropy(text: &str) -> f64 {
+    let mut counts: BTreeMap<char, usize> = BTreeMap::new();
+    for c in text.chars() {
+        *counts.entry(c).or_default() += 1;
+    }
+    let len = text.chars().count() as f64;
+    counts
+        .values()
+        .map(|&n| {
+            let p = n as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+fn is_secret_name(name: &str) -> bool {
+    let name = name.to_uppercase();
+    SECRET_NAME_MARKERS
+        .iter()
+        .any(|marker| name.contains(marker))
+}
+
+struct Span {
+    start: usize,
+    end: usize,
+    rule: String,
+    replacement: &'static str,
+}
+
+/// Compiled `RedactionConfig`
+pub struct Redactor {
+    rules: Vec<Rule>,
+    secrets: bool,
+    entropy: Option<(Regex, f64)>,
+    allowlist: HashSet<String>,
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::new(&RedactionConfig::default()).expect("default redaction config compiles")
+    }
+}
+
+impl Redactor {
+    /// Fails if a custom pattern is not a valid regex
+    pub fn new(config: &RedactionConfig) -> Result<Self, String> {
+        let mut rules = Vec::new();
+        if config.paths {
+            // Starts before the user name inside it, so it wins over `user_path`
+            if let Some(home) = dirs::home_dir()
+                .map(|home| home.to_string_lossy().to_string())
+                .filter(|home| home.len() > 1)
+            {
+                rules.push(Rule::new(
+                    "home_path",
+                    &format!(r"{}\b", regex::escape(&home)),
+                    0,
+                    "~",
+                ));
+            }
+            rules.push(Rule::new("user_path", USER_PATH_PATTERN, 1, "[USER]"));
+        }
+        if config.secrets {
+            for (name, pattern, group) in SECRET_RULES.iter() {
+                rules.push(Rule::new(name, pattern, *group, REDACTED));
+            }
+            rules.push(
+                Rule::new("secret_assignment", ASSIGNMENT_PATTERN, 1, REDACTED)
+                    .checked(mixes_letters_and_digits),
+            );
+        }
+        if config.emails {
+            rules.push(Rule::new("email", EMAIL_PATTERN, 0, "[EMAIL]").checked(is_personal_email));
+        }
+        for custom in &config.custom_patterns {
+            let regex = Regex::new(&custom.pattern)
+                .map_err(|e| format!("Invalid redaction pattern '{}': {}", custom.name, e))?;
+            rules.push(Rule {
+                name: custom.name.clone(),
+                group: if regex.captures_len() > 1 { 1 } else { 0 },
+                regex,
+                replacement: REDACTED,
+                check: None,
+            });
+        }
+        let entropy = if config.entropy {
+            let pattern = format!(
+                r"[A-Za-z0-9+/_\-]{{{},}}={{0,2}}",
+                config.min_entropy_length.max(8)
+            );
+            Some((
+                Regex::new(&pattern).map_err(|e| e.to_string())?,
+                config.min_entropy,
+            ))
+        } else {
+            None
+        };
+        Ok(Self {
+            rules,
+            secrets: config.secrets,
+            entropy,
+            allowlist: config.allowlist.iter().cloned().collect(),
+        })
+    }
+
+    fn spans(&self, text: &str) -> Vec<Span> {
+        let mut spans = Vec::new();
+        for rule in &self.rules {
+            for caps in rule.regex.captures_iter(text) {
+                let m = match caps.get(rule.group).or_else(|| caps.get(0)) {
+                    Some(m) => m,
+                    None => continue,
+                };
+                if rule.check.is_some_and(|check| !check(m.as_str()))
+                    || self.allowlist.contains(m.as_str())
+                {
+                    continue;
+                }
+                spans.push(Span {
+                    start: m.start(),
+                    end: m.end(),
+                    rule: rule.name.clone(),
+                    replacement: rule.replacement,
+                });
+            }
+        }
+        if let Some((regex, min_entropy)) = &self.entropy {
+            for m in regex.find_iter(text) {
+                let candidate = m.as_str();
+                if mixes_letters_and_digits(candidate)
+                    && shannon_entropy(candidate) >= *min_entropy
+                    && !self.allowlist.contains(candidate)
+                {
+                    spans.push(Span {
+                        start: m.start(),
+                        end: m.end(),
+                        rule: "high_entropy".to_string(),
+                        replacement: REDACTED,
+                    });
+                }
+            }
+        }
+        spans.sort_by_key(|span| (span.start, std::cmp::Reverse(span.end)));
+        spans
+    }
+
+    /// Mask `text`, recording every replacement in `findings`
+    pub fn redact_with(&self, text: &str, findings: &mut Vec<RedactionFinding>) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut pos = 0;
+        for span in self.spans(text) {
+            if span.start < pos {
+                continue;
+            }
+            out.push_str(&text[pos..span.start]);
+            out.push_str(span.replacement);
+            findings.push(RedactionFinding {
+                rule: span.rule,
+                original: text[span.start..span.end].to_string(),
+                replacement: span.replacement.to_string(),
+            });
+            pos = span.end;
+        }
+        out.push_str(&text[pos..]);
+        out
+    }
+
+    pub fn redact(&self, text: &str) -> String {
+        self.redact_with(text, &mut Vec::new())
+    }
+
+    /// Whether `text` holds a secret; paths and emails alone do not count
+    pub fn contains_secret(&self, text: &str) -> bool {
+        let mut findings = Vec::new();
+        self.redact_with(text, &mut findings);
+        findings.iter().any(|f| f.replacement == REDACTED)
+    }
+
+    /// Mask every string in `value`; with secret patterns on, string fields with
+    /// secret-looking names are masked whole
+    pub fn redact_json_with(
+        &self,
+        value: &mut serde_json::Value,
+        findings: &mut Vec<RedactionFinding>,
+    ) {
+        match value {
+            serde_json::Value::String(text) => *text = self.redact_with(text, findings),
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    self.redact_json_with(item, findings);
+                }
+            }
+            serde_json::Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    match value {
+                        serde_json::Value::String(text)
+                            if self.secrets
+                                && is_secret_name(key)
+                                && !text.is_empty()
+                                && !self.allowlist.contains(text.as_str()) =>
+                        {
+                            findings.push(RedactionFinding {
+                                rule: "secret_field".to_string(),
+                                original: std::mem::replace(text, REDACTED.to_string()),
+                                replacement: REDACTED.to_string(),
+                            });
+                        }
+                        _ => self.redact_json_with(value, findings),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Cached redactor, rebuilt when the config changes
+#[derive(Default)]
+pub struct RedactionState(Mutex<Option<Arc<Redactor>>>);
+
+fn load_config(conn: &Connection) -> Result<RedactionConfig, String> {
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT value FROM app_settings WHERE key = ?1",
+            [SETTINGS_KEY],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    match stored {
+        Some(json) => {
+            serde_json::from_str(&json).map_err(|e| format!("Invalid redaction settings: {}", e))
+        }
+        None => Ok(RedactionConfig::default()),
+    }
+}
+
+/// The configured redactor; falls back to the defaults if the config cannot be
+/// loaded. Must not be called while holding the database lock.
+pub fn redactor(app: &AppHandle) -> Arc<Redactor> {
+    let state = match app.try_state::<RedactionState>() {
+        Some(state) => state,
+        None => return Arc::new(Redactor::default()),
+    };
+    let mut cached = match state.0.lock() {
+        Ok(cached) => cached,
+        Err(_) => return Arc::new(Redactor::default()),
+    };
+    if let Some(redactor) = cached.as_ref() {
+        return redactor.clone();
+    }
+    let config = match app.try_state::<AgentDb>() {
+        Some(db) => {
+            db.0.lock()
+                .map_err(|e| e.to_string())
+                .and_then(|conn| load_config(&conn))
+        }
+        None => Ok(RedactionConfig::default()),
+    };
+    let redactor = config.and_then(|config| Redactor::new(&config));
+    match redactor {
+        Ok(redactor) => cached.insert(Arc::new(redactor)).clone(),
+        Err(e) => {
+            warn!("{}, using default redaction", e);
+            Arc::new(Redactor::default())
+        }
+    }
+}
+
+/// A masked piece of a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionPreviewMatch {
+    pub message_index: usize,
+    pub role: String,
+    #[serde(flatten)]
+    pub finding: RedactionFinding,
+}
+
+/// Result of `preview_redaction`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionPreview {
+    pub session_id: String,
+    pub total: usize,
+    /// Matches per rule
+    pub by_rule: BTreeMap<String, usize>,
+    /// The first `MAX_PREVIEW_MATCHES` matches in transcript order
+    pub matches: Vec<RedactionPreviewMatch>,
+}
+
+// ============ Tauri Commands ============
+
+#[tauri::command]
+pub async fn get_redaction_config(db: State<'_, AgentDb>) -> Result<RedactionConfig, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_config(&conn)
+}
+
+/// Save the redaction settings; custom patterns are checked first
+#[tauri::command]
+pub async fn update_redaction_config(
+    db: State<'_, AgentDb>,
+    state: State<'_, RedactionState>,
+    config: RedactionConfig,
+) -> Result<(), String> {
+    let redactor = Redactor::new(&config)?;
+    let json = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+    {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+            params![SETTINGS_KEY, json],
+        )
+        .map_err(|e| format!("Failed to save redaction settings: {}", e))?;
+    }
+    *state.0.lock().map_err(|e| e.to_string())? = Some(Arc::new(redactor));
+    Ok(())
+}
+
+/// What exporting or sharing a session would mask
+#[tauri::command]
+pub async fn preview_redaction(
+    app: AppHandle,
+    session_id: String,
+    project_id: Option<String>,
+) -> Result<RedactionPreview, String> {
+    let redactor = redactor(&app);
+    tauri::async_runtime::spawn_blocking(move || {
+        let session_file = find_session_file(&session_id, project_id.as_deref())?;
+        let mut transcript = load_transcript(&session_id, &session_file)?;
+
+        let mut preview = RedactionPreview {
+            session_id,
+            total: 0,
+            by_rule: BTreeMap::new(),
+            matches: Vec::new(),
+        };
+        for (index, message) in transcript.messages.iter_mut().enumerate() {
+            let mut findings = Vec::new();
+            message.redact(&redactor, &mut findings);
+            for finding in findings {
+                preview.total += 1;
+                *preview.by_rule.entry(finding.rule.clone()).or_default() += 1;
+                if preview.matches.len() < MAX_PREVIEW_MATCHES {
+                    preview.matches.push(RedactionPreviewMatch {
+                        message_index: index,
+                        role: message.role.clone(),
+                        finding,
+                    });
+                }
+            }
+        }
+        Ok(preview)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
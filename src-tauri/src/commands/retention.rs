@@ -0,0 +1,703 @@
+use log::{info, warn};
+/// Retention of old sessions
+///
+/// Rules pick sessions by project, age and transcript size; a transcript
+/// written to within the last day is never picked, since a CLI session running
+/// outside the app may still be appending to it. Matching sessions are
+/// compressed into the archive directory (`~/.claude/archive/sessions` by
+/// default) and removed from `~/.claude/projects`, so they no longer show up in
+/// the session lists, and `restore_archived_session` puts them back. Usage and
+/// cost history are kept.
+///
+/// Rules run on demand with `run_retention`, or in the background every
+/// `interval_hours` when the policy is enabled. `get_disk_usage_report` shows
+/// what transcripts and archives take up per project.
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager, State};
+use walkdir::WalkDir;
+
+use super::claude::get_claude_dir;
+use super::session_batch::compress_file;
+use super::session_state;
+use super::storage::AgentDb;
+use crate::events::{self, AppEvent};
+
+/// `app_settings` key holding the JSON policy
+const SETTINGS_KEY: &str = "retention_policy";
+/// `app_settings` key holding when the policy last ran in the background
+const LAST_RUN_KEY: &str = "retention_last_run";
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Transcripts modified more recently than this are kept whatever the rules say
+const MIN_IDLE: Duration = Duration::from_secs(24 * 3600);
+
+const SELECT_COLUMNS: &str =
+    "session_id, project_id, original_path, archive_path, original_bytes, \
+     archived_bytes, last_activity, archived_at, rule";
+
+/// Sessions a rule archives; every criterion given must hold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionRule {
+    pub name: String,
+    /// Directory name under `~/.claude/projects`; None for every project
+    #[serde(default)]
+    pub project_id: Option<String>,
+    /// No activity for this many days
+    #[serde(default)]
+    pub older_than_days: Option<u32>,
+    /// Transcript at least this large
+    #[serde(default)]
+    pub larger_than_mb: Option<u64>,
+}
+
+impl RetentionRule {
+    fn matches(&self, session: &SessionFile, now: SystemTime) -> bool {
+        if self.older_than_days.is_none() && self.larger_than_mb.is_none() {
+            return false;
+        }
+        if self
+            .project_id
+            .as_ref()
+            .is_some_and(|p| p != &session.project_id)
+        {
+            return false;
+        }
+        let age = now.duration_since(session.modified).unwrap_or_default();
+        if age < MIN_IDLE {
+            return false;
+        }
+        self.older_than_days
+            .is_none_or(|days| age >= Duration::from_secs(u64::from(days) * 86_400))
+            && self
+                .larger_than_mb
+                .is_none_or(|mb| session.bytes >= mb * 1024 * 1024)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// Apply the rules in the background
+    pub enabled: bool,
+    pub interval_hours: u32,
+    /// Where archives go; defaults to `~/.claude/archive/sessions`
+    pub archive_dir: Option<String>,
+    pub rules: Vec<RetentionRule>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_hours: 24,
+            archive_dir: None,
+            rules: Vec::new(),
+        }
+    }
+}
+
+/// A session moved into the archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedSession {
+    pub session_id: String,
+    pub project_id: String,
+    /// Transcript path it is restored to
+    pub original_path: String,
+    /// The compressed transcript
+    pub archive_path: String,
+    pub original_bytes: u64,
+    pub archived_bytes: u64,
+    pub last_activity: String,
+    pub archived_at: String,
+    /// Name of the rule that archived it
+    pub rule: String,
+}
+
+/// A session a rule selects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionCandidate {
+    pub session_id: String,
+    pub project_id: String,
+    pub bytes: u64,
+    pub last_activity: String,
+    pub rule: String,
+}
+
+/// Result of applying the rules
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionRun {
+    pub dry_run: bool,
+    /// Sessions selected; all of them were archived unless listed in `errors`
+    pub candidates: Vec<RetentionCandidate>,
+    pub archived: usize,
+    /// Bytes removed from `~/.claude/projects`
+    pub freed_bytes: u64,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectDiskUsage {
+    pub project_id: String,
+    pub sessions: usize,
+    /// Transcripts and the files stored next to them
+    pub transcript_bytes: u64,
+    pub archived_sessions: usize,
+    /// Size of the archived sessions before compression
+    pub archived_original_bytes: u64,
+    pub archived_bytes: u64,
+}
+
+/// Result of `get_disk_usage_report`, largest projects first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskUsageReport {
+    pub projects: Vec<ProjectDiskUsage>,
+    pub transcript_bytes: u64,
+    pub archived_bytes: u64,
+    pub archive_dir: String,
+}
+
+/// A transcript in `~/.claude/projects`
+struct SessionFile {
+    session_id: String,
+    project_id: String,
+    path: PathBuf,
+    bytes: u64,
+    modified: SystemTime,
+}
+
+impl SessionFile {
+    /// Files Claude keeps next to the transcript, such as subagent transcripts
+    fn side_dir(&self) -> PathBuf {
+        self.path.with_extension("")
+    }
+}
+
+fn row_to_archived(row: &Row) -> rusqlite::Result<ArchivedSession> {
+    Ok(ArchivedSession {
+        session_id: row.get(0)?,
+        project_id: row.get(1)?,
+        original_path: row.get(2)?,
+        archive_path: row.get(3)?,
+        original_bytes: row.get::<_, i64>(4)? as u64,
+        archived_bytes: row.get::<_, i64>(5)? as u64,
+        last_activity: row.get(6)?,
+        archived_at: row.get(7)?,
+        rule: row.get(8)?,
+    })
+}
+
+fn load_setting(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT value FROM app_settings WHERE key = ?1",
+        [key],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+fn load_policy(conn: &Connection) -> Result<RetentionPolicy, String> {
+    match load_setting(conn, SETTINGS_KEY)? {
+        Some(json) => {
+            serde_json::from_str(&json).map_err(|e| format!("Invalid retention policy: {}", e))
+        }
+        None => Ok(RetentionPolicy::default()),
+    }
+}
+
+fn load_archived(
+    conn: &Connection,
+    project_id: Option<&str>,
+) -> Result<Vec<ArchivedSession>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM archived_sessions
+             WHERE ?1 IS NULL OR project_id = ?1
+             ORDER BY archived_at DESC",
+            SELECT_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let sessions = stmt
+        .query_map([project_id], row_to_archived)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(sessions)
+}
+
+fn claude_dir() -> Result<PathBuf, String> {
+    get_claude_dir().map_err(|e| e.to_string())
+}
+
+fn archive_root(policy: &RetentionPolicy) -> Result<PathBuf, String> {
+    match &policy.archive_dir {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => Ok(claude_dir()?.join("archive").join("sessions")),
+    }
+}
+
+/// Total size of the files under a directory
+fn dir_bytes(dir: &Path) -> u64 {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Every transcript in `~/.claude/projects`
+fn session_files() -> Result<Vec<SessionFile>, String> {
+    let projects_dir = claude_dir()?.join("projects");
+    let mut sessions = Vec::new();
+    let projects = match fs::read_dir(&projects_dir) {
+        Ok(projects) => projects,
+        Err(_) => return Ok(sessions),
+    };
+    for project in projects.flatten().filter(|e| e.path().is_dir()) {
+        let project_id = project.file_name().to_string_lossy().to_string();
+        let entries = match fs::read_dir(project.path()) {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!("Failed to read {}: {}", project.path().display(), e);
+                continue;
+            }
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                continue;
+            }
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+            let session_id = match path.file_stem() {
+                Some(stem) => stem.to_string_lossy().to_string(),
+                None => continue,
+            };
+            sessions.push(SessionFile {
+                session_id,
+                project_id: project_id.clone(),
+                bytes: metadata.len(),
+                modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                path,
+            });
+        }
+    }
+    Ok(sessions)
+}
+
+fn rfc3339(time: SystemTime) -> String {
+    chrono::DateTime::<chrono::Utc>::from(time).to_rfc3339()
+}
+
+/// Sessions the rules select, each with the first rule that matched; running
+/// sessions are left alone
+fn candidates(
+    app: &AppHandle,
+    policy: &RetentionPolicy,
+) -> Result<Vec<(SessionFile, String)>, String> {
+    let now = SystemTime::now();
+    Ok(session_files()?
+        .into_iter()
+        .filter(|s| !session_state::phase(app, &s.session_id).is_some_and(|p| p.is_live()))
+        .filter_map(|session| {
+            let rule = policy.rules.iter().find(|r| r.matches(&session, now))?;
+            Some((session, rule.name.clone()))
+        })
+        .collect())
+}
+
+/// Compress a session into the archive and record it; the transcript is
+/// removed only once its archive is complete
+fn archive_session(
+    db: &AgentDb,
+    root: &Path,
+    session: &SessionFile,
+    rule: &str,
+) -> Result<ArchivedSession, String> {
+    let project_dir = root.join(&session.project_id);
+    let archive_path = project_dir.join(format!("{}.jsonl.zst", session.session_id));
+    let archive_side_dir = project_dir.join(&session.session_id);
+    if archive_path.exists() {
+        return Err(format!("{} already exists", archive_path.display()));
+    }
+
+    let side_dir = session.side_dir();
+    let mut original_bytes = session.bytes;
+    if side_dir.is_dir() {
+        original_bytes += dir_bytes(&side_dir);
+    }
+    let compress = || -> Result<u64, String> {
+        let mut archived_bytes = compress_file(&session.path, &archive_path)?;
+        if side_dir.is_dir() {
+            for entry in WalkDir::new(&side_dir).into_iter().filter_map(|e| e.ok()) {
+                if !entry.file_type().is_file() {
+                    continue;
+                }
+                let relative = entry.path().strip_prefix(&side_dir).unwrap_or(entry.path());
+                let target = archive_side_dir.join(format!("{}.zst", relative.display()));
+                archived_bytes += compress_file(entry.path(), &target)?;
+            }
+        }
+        Ok(archived_bytes)
+    };
+    let record = |archived_bytes: u64| -> Result<ArchivedSession, String> {
+        let archived = ArchivedSession {
+            session_id: session.session_id.clone(),
+            project_id: session.project_id.clone(),
+            original_path: session.path.to_string_lossy().to_string(),
+            archive_path: archive_path.to_string_lossy().to_string(),
+            original_bytes,
+            archived_bytes,
+            last_activity: rfc3339(session.modified),
+            archived_at: chrono::Utc::now().to_rfc3339(),
+            rule: rule.to_string(),
+        };
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR REPLACE INTO archived_sessions (session_id, project_id, original_path, archive_path,
+             original_bytes, archived_bytes, last_activity, archived_at, rule)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                archived.session_id,
+                archived.project_id,
+                archived.original_path,
+                archived.archive_path,
+                archived.original_bytes as i64,
+                archived.archived_bytes as i64,
+                archived.last_activity,
+                archived.archived_at,
+                archived.rule
+            ],
+        )
+        .map_err(|e| format!("Failed to record archived session: {}", e))?;
+        Ok(archived)
+    };
+    let archived = match compress().and_then(record) {
+        Ok(archived) => archived,
+        Err(e) => {
+            let _ = fs::remove_file(&archive_path);
+            let _ = fs::remove_dir_all(&archive_side_dir);
+            return Err(e);
+        }
+    };
+
+    fs::remove_file(&session.path).map_err(|e| {
+        format!(
+            "Archived but failed to remove {}: {}",
+            session.path.display(),
+            e
+        )
+    })?;
+    if side_dir.is_dir() {
+        if let Err(e) = fs::remove_dir_all(&side_dir) {
+            warn!("Failed to remove {}: {}", side_dir.display(), e);
+        }
+    }
+    Ok(archived)
+}
+
+/// Apply the rules, or only list what they select when `dry_run` is set
+fn apply_policy(app: &AppHandle, dry_run: bool) -> Result<RetentionRun, String> {
+    let db = app.state::<AgentDb>();
+    let policy = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        load_policy(&conn)?
+    };
+    let root = archive_root(&policy)?;
+    let selected = candidates(app, &policy)?;
+
+    let mut run = RetentionRun {
+        dry_run,
+        candidates: selected
+            .iter()
+            .map(|(session, rule)| RetentionCandidate {
+                session_id: session.session_id.clone(),
+                project_id: session.project_id.clone(),
+                bytes: session.bytes,
+                last_activity: rfc3339(session.modified),
+                rule: rule.clone(),
+            })
+            .collect(),
+        archived: 0,
+        freed_bytes: 0,
+        errors: Vec::new(),
+    };
+    if dry_run {
+        return Ok(run);
+    }
+
+    for (session, rule) in &selected {
+        let result = archive_session(&db, &root, session, rule);
+        match result {
+            Ok(archived) => {
+                run.archived += 1;
+                run.freed_bytes += archived.original_bytes;
+            }
+            Err(e) => {
+                warn!("Failed to archive session {}: {}", session.session_id, e);
+                run.errors.push(format!("{}: {}", session.session_id, e));
+            }
+        }
+    }
+    info!(
+        "Retention archived {} sessions, freeing {} bytes",
+        run.archived, run.freed_bytes
+    );
+    Ok(run)
+}
+
+/// Apply the rules if the policy is enabled and its interval has passed
+async fn run_schedule(app: &AppHandle) -> Result<(), String> {
+    let (policy, last_run) = {
+        let db = app.state::<AgentDb>();
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        (load_policy(&conn)?, load_setting(&conn, LAST_RUN_KEY)?)
+    };
+    if !policy.enabled || policy.rules.is_empty() {
+        return Ok(());
+    }
+    let now = chrono::Utc::now();
+    let due = last_run
+        .and_then(|last| chrono::DateTime::parse_from_rfc3339(&last).ok())
+        .is_none_or(|last| {
+            now.signed_duration_since(last) >= chrono::Duration::hours(policy.interval_hours.into())
+        });
+    if !due {
+        return Ok(());
+    }
+
+    let worker = app.clone();
+    let run = tauri::async_runtime::spawn_blocking(move || apply_policy(&worker, false))
+        .await
+        .map_err(|e| e.to_string())??;
+    {
+        let db = app.state::<AgentDb>();
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+            params![LAST_RUN_KEY, now.to_rfc3339()],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    if run.archived > 0 || !run.errors.is_empty() {
+        events::emit(app, AppEvent::RetentionCompleted(run));
+    }
+    Ok(())
+}
+
+/// Apply the retention policy in the background
+pub fn start_retention_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = run_schedule(&app).await {
+                warn!("Scheduled retention failed: {}", e);
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// Decompress an archived session back to where it was and forget the archive
+fn restore(conn: &Connection, archived: &ArchivedSession) -> Result<(), String> {
+    let original = PathBuf::from(&archived.original_path);
+    if original.exists() {
+        return Err(format!("{} already exists", original.display()));
+    }
+    let archive_path = PathBuf::from(&archived.archive_path);
+    let archive_side_dir = archive_path.with_file_name(&archived.session_id);
+
+    let mut files = vec![(archive_path.clone(), original.clone())];
+    if archive_side_dir.is_dir() {
+        let side_dir = original.with_extension("");
+        for entry in WalkDir::new(&archive_side_dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let relative = entry
+                .path()
+                .strip_prefix(&archive_side_dir)
+                .unwrap_or(entry.path());
+            files.push((
+                entry.path().to_path_buf(),
+                side_dir.join(relative).with_extension(""),
+            ));
+        }
+    }
+
+    let mut written = Vec::new();
+    for (from, to) in &files {
+        let result = fs::read(from)
+            .map_err(|e| format!("Failed to read {}: {}", from.display(), e))
+            .and_then(|compressed| {
+                zstd::decode_all(compressed.as_slice())
+                    .map_err(|e| format!("Failed to decompress {}: {}", from.display(), e))
+            })
+            .and_then(|content| {
+                if let Some(parent) = to.parent() {
+                    fs::create_dir_all(parent)
+                        .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+                }
+                fs::write(to, content)
+                    .map_err(|e| format!("Failed to write {}: {}", to.display(), e))
+            });
+        if let Err(e) = result {
+            for path in &written {
+                let _ = fs::remove_file(path);
+            }
+            return Err(e);
+        }
+        written.push(to.clone());
+    }
+
+    conn.execute(
+        "DELETE FROM archived_sessions WHERE session_id = ?1",
+        [&archived.session_id],
+    )
+    .map_err(|e| e.to_string())?;
+    let _ = fs::remove_file(&archive_path);
+    if archive_side_dir.is_dir() {
+        let _ = fs::remove_dir_all(&archive_side_dir);
+    }
+    Ok(())
+}
+
+// ============ Tauri Commands ============
+
+#[tauri::command]
+pub async fn get_retention_policy(db: State<'_, AgentDb>) -> Result<RetentionPolicy, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_policy(&conn)
+}
+
+/// Replace the retention policy; the background run picks it up within the hour
+#[tauri::command]
+pub async fn update_retention_policy(
+    db: State<'_, AgentDb>,
+    policy: RetentionPolicy,
+) -> Result<(), String> {
+    if policy.interval_hours == 0 {
+        return Err("Interval must be at least one hour".to_string());
+    }
+    if let Some(rule) = policy.rules.iter().find(|r| r.name.trim().is_empty()) {
+        return Err(format!("Retention rule without a name: {:?}", rule));
+    }
+    let json = serde_json::to_string(&policy).map_err(|e| e.to_string())?;
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+        params![SETTINGS_KEY, json],
+    )
+    .map_err(|e| format!("Failed to save retention policy: {}", e))?;
+    info!("Retention policy updated ({} rules)", policy.rules.len());
+    Ok(())
+}
+
+/// Apply the retention rules now; with `dry_run` only list the sessions they
+/// select
+#[tauri::command]
+pub async fn run_retention(app: AppHandle, dry_run: Option<bool>) -> Result<RetentionRun, String> {
+    let dry_run = dry_run.unwrap_or(false);
+    tauri::async_runtime::spawn_blocking(move || apply_policy(&app, dry_run))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Archived sessions, most recently archived first
+#[tauri::command]
+pub async fn list_archived_sessions(
+    db: State<'_, AgentDb>,
+    project_id: Option<String>,
+) -> Result<Vec<ArchivedSession>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_archived(&conn, project_id.as_deref())
+}
+
+/// Put an archived session back where it was
+#[tauri::command]
+pub async fn restore_archived_session(
+    app: AppHandle,
+    session_id: String,
+) -> Result<ArchivedSession, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = app.state::<AgentDb>();
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        let archived = conn
+            .query_row(
+                &format!(
+                    "SELECT {} FROM archived_sessions WHERE session_id = ?1",
+                    SELECT_COLUMNS
+                ),
+                [&session_id],
+                row_to_archived,
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Archived session not found: {}", session_id))?;
+        restore(&conn, &archived)?;
+        info!("Restored archived session {}", session_id);
+        Ok(archived)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Disk space taken by transcripts and archives, per project
+#[tauri::command]
+pub async fn get_disk_usage_report(app: AppHandle) -> Result<DiskUsageReport, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = app.state::<AgentDb>();
+        let (policy, archived) = {
+            let conn = db.0.lock().map_err(|e| e.to_string())?;
+            (load_policy(&conn)?, load_archived(&conn, None)?)
+        };
+
+        let mut projects: BTreeMap<String, ProjectDiskUsage> = BTreeMap::new();
+        let empty = |id: &str| ProjectDiskUsage {
+            project_id: id.to_string(),
+            ..Default::default()
+        };
+        for session in session_files()? {
+            let side_dir = session.side_dir();
+            let usage = projects
+                .entry(session.project_id.clone())
+                .or_insert_with(|| empty(&session.project_id));
+            usage.sessions += 1;
+            usage.transcript_bytes += session.bytes;
+            if side_dir.is_dir() {
+                usage.transcript_bytes += dir_bytes(&side_dir);
+            }
+        }
+        for session in &archived {
+            let usage = projects
+                .entry(session.project_id.clone())
+                .or_insert_with(|| empty(&session.project_id));
+            usage.archived_sessions += 1;
+            usage.archived_original_bytes += session.original_bytes;
+            usage.archived_bytes += session.archived_bytes;
+        }
+
+        let mut projects: Vec<ProjectDiskUsage> = projects.into_values().collect();
+        projects.sort_by_key(|p| std::cmp::Reverse(p.transcript_bytes + p.archived_bytes));
+        Ok(DiskUsageReport {
+            transcript_bytes: projects.iter().map(|p| p.transcript_bytes).sum(),
+            archived_bytes: projects.iter().map(|p| p.archived_bytes).sum(),
+            archive_dir: archive_root(&policy)?.to_string_lossy().to_string(),
+            projects,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,178 @@
+use log::warn;
+/// Retry policy shared by hooks, MCP server re-probes and Claude spawns
+///
+/// A `RetryPolicy` says how often a failed attempt is repeated, how long to wait
+/// in between (exponential backoff with optional jitter) and which failures are
+/// worth repeating at all. Subsystems keep their own loops where they need the
+/// attempt's result (hooks look at exit codes and output) and use `run` where a
+/// plain `WorkbenchResult` is enough.
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::Duration;
+
+use super::operations;
+use crate::error::{WorkbenchError, WorkbenchResult};
+
+/// How a failed operation is retried
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Total attempts including the first; 1 disables retrying
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub initial_delay_ms: u64,
+    /// Factor applied to the delay for each further retry; 1.0 keeps it fixed
+    pub multiplier: f64,
+    /// Upper bound for a single delay
+    pub max_delay_ms: u64,
+    /// Fraction of each delay that is randomized, from 0.0 to 1.0
+    pub jitter: f64,
+    /// Failures worth retrying; empty retries failed exits and transient errors
+    pub retry_on: Vec<RetryOn>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay_ms: 1000,
+            multiplier: 2.0,
+            max_delay_ms: 30_000,
+            jitter: 0.2,
+            retry_on: Vec::new(),
+        }
+    }
+}
+
+/// A kind of failure a policy retries
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RetryOn {
+    /// The process exited with one of these codes
+    ExitCode { codes: Vec<i32> },
+    /// The output or error message contains this text
+    OutputContains { text: String },
+    /// The attempt ran into its timeout
+    Timeout,
+    /// The process could not be started
+    SpawnError,
+    /// Errors `WorkbenchError::retryable` considers transient
+    Transient,
+}
+
+/// What went wrong in one attempt
+pub enum Failure<'a> {
+    /// The process ran and exited unsuccessfully
+    Exit {
+        code: Option<i32>,
+        stdout: &'a str,
+        stderr: &'a str,
+    },
+    /// The attempt failed before producing an exit status
+    Error(&'a WorkbenchError),
+}
+
+impl RetryOn {
+    fn matches(&self, failure: &Failure) -> bool {
+        match (self, failure) {
+            (Self::ExitCode { codes }, Failure::Exit { code, .. }) => {
+                code.is_some_and(|code| codes.contains(&code))
+            }
+            (Self::OutputContains { text }, Failure::Exit { stdout, stderr, .. }) => {
+                stdout.contains(text.as_str()) || stderr.contains(text.as_str())
+            }
+            (Self::OutputContains { text }, Failure::Error(e)) => {
+                e.to_string().contains(text.as_str())
+            }
+            (Self::Timeout, Failure::Error(e)) => matches!(e, WorkbenchError::Timeout { .. }),
+            (Self::SpawnError, Failure::Error(e)) => matches!(e, WorkbenchError::Io { .. }),
+            (Self::Transient, Failure::Error(e)) => e.retryable(),
+            _ => false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// `max_attempts` attempts with a constant delay and no jitter
+    pub fn fixed(max_attempts: u32, delay: Duration) -> Self {
+        Self {
+            max_attempts,
+            initial_delay_ms: delay.as_millis() as u64,
+            multiplier: 1.0,
+            max_delay_ms: delay.as_millis() as u64,
+            jitter: 0.0,
+            retry_on: Vec::new(),
+        }
+    }
+
+    /// Whether to try again after `attempt` (1-based) failed like this
+    pub fn should_retry(&self, attempt: u32, failure: &Failure) -> bool {
+        if attempt >= self.max_attempts {
+            return false;
+        }
+        if self.retry_on.is_empty() {
+            return match failure {
+                Failure::Exit { .. } => true,
+                Failure::Error(e) => e.retryable(),
+            };
+        }
+        self.retry_on.iter().any(|matcher| matcher.matches(failure))
+    }
+
+    /// Delay before retry number `retry` (1-based)
+    pub fn delay(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(32) as i32;
+        let base = (self.initial_delay_ms as f64 * self.multiplier.max(1.0).powi(exponent))
+            .min(self.max_delay_ms.max(self.initial_delay_ms) as f64);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        // Spread the delay over [base * (1 - jitter), base]
+        let random = (uuid::Uuid::new_v4().as_u128() % 10_000) as f64 / 10_000.0;
+        Duration::from_millis((base * (1.0 - jitter * random)) as u64)
+    }
+
+    /// Wait before retry number `retry`; fails if the surrounding operation is
+    /// cancelled meanwhile
+    pub async fn wait(&self, retry: u32) -> WorkbenchResult<()> {
+        tokio::select! {
+            _ = tokio::time::sleep(self.delay(retry)) => Ok(()),
+            _ = operations::cancelled() => {
+                Err(WorkbenchError::Cancelled("Waiting to retry".to_string()))
+            }
+        }
+    }
+
+    /// Run `attempt` until it succeeds, the policy gives up or the failure is not
+    /// one it retries. The closure gets the 1-based attempt number.
+    pub async fn run<T, F, Fut>(&self, what: &str, mut attempt: F) -> WorkbenchResult<T>
+    where
+        F: FnMut(u32) -> Fut,
+        Fut: Future<Output = WorkbenchResult<T>>,
+    {
+        let mut number = 1;
+        loop {
+            match attempt(number).await {
+                Ok(value) => return Ok(value),
+                Err(e) if self.should_retry(number, &Failure::Error(&e)) => {
+                    warn!(
+                        "{} failed ({}), retrying ({}/{})",
+                        what,
+                        e,
+                        number,
+                        self.max_attempts - 1
+                    );
+                    self.wait(number).await?;
+                    number += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
@@ -0,0 +1,527 @@
+use log::{error, info, warn};
+/// Scheduler for background runs
+///
+/// Automation such as a nightly "update deps and run tests" session is queued here
+/// rather than started directly. Runs are persisted in `scheduled_runs`, started as
+/// headless sessions once due and while fewer than `max_concurrency` are running,
+/// killed after their timeout, and retried with exponential backoff until they run
+/// out of attempts. Every state change is emitted as `scheduled-run-updated`.
+/// Nothing starts while the network monitor finds the API unreachable.
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::Notify;
+
+use super::headless::{self, ProgressFn, SessionExit, SessionSpec};
+use super::storage::AgentDb;
+use crate::events::{self, AppEvent};
+
+/// `app_settings` key holding the JSON config
+const SETTINGS_KEY: &str = "run_scheduler";
+
+/// How often due runs are checked for when nothing wakes the scheduler
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Longest wait between two attempts
+const MAX_BACKOFF_SECS: u64 = 6 * 60 * 60;
+
+const SELECT_COLUMNS: &str = "id, name, project_path, prompt, model, plan_mode, status, attempts, max_retries, timeout_secs, next_attempt_at, created_at, started_at, finished_at, session_id, result, cost_usd, error";
+
+/// Scheduler limits and run defaults
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulerConfig {
+    /// Runs executing at the same time
+    pub max_concurrency: usize,
+    pub default_timeout_secs: u64,
+    pub default_max_retries: u32,
+    /// Delay before the first retry; doubled for each further one
+    pub retry_backoff_secs: u64,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrency: 2,
+            default_timeout_secs: 60 * 60,
+            default_max_retries: 2,
+            retry_backoff_secs: 60,
+        }
+    }
+}
+
+/// A persisted background run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledRun {
+    pub id: i64,
+    pub name: Option<String>,
+    pub project_path: String,
+    pub prompt: String,
+    pub model: String,
+    pub plan_mode: bool,
+    /// "queued", "running", "retrying", "completed", "failed" or "cancelled"
+    pub status: String,
+    /// Attempts started so far
+    pub attempts: u32,
+    pub max_retries: u32,
+    pub timeout_secs: u64,
+    /// Earliest time the next attempt may start
+    pub next_attempt_at: String,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    /// Session of the latest attempt
+    pub session_id: Option<String>,
+    pub result: Option<String>,
+    pub cost_usd: Option<f64>,
+    pub error: Option<String>,
+}
+
+/// Cancellation handles of running attempts, and the cached config
+#[derive(Default)]
+pub struct RunSchedulerState {
+    active: Mutex<HashMap<i64, Arc<Notify>>>,
+    wake: Notify,
+    config: Mutex<Option<SchedulerConfig>>,
+}
+
+impl RunSchedulerState {
+    fn config(&self, conn: &Connection) -> SchedulerConfig {
+        let mut cached = match self.config.lock() {
+            Ok(cached) => cached,
+            Err(_) => return SchedulerConfig::default(),
+        };
+        cached
+            .get_or_insert_with(|| {
+                load_config(conn).unwrap_or_else(|e| {
+                    warn!("Using default run scheduler settings: {}", e);
+                    SchedulerConfig::default()
+                })
+            })
+            .clone()
+    }
+}
+
+fn load_config(conn: &Connection) -> Result<SchedulerConfig, String> {
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT value FROM app_settings WHERE key = ?1",
+            [SETTINGS_KEY],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    match stored {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid run scheduler settings: {}", e)),
+        None => Ok(SchedulerConfig::default()),
+    }
+}
+
+fn row_to_run(row: &Row) -> rusqlite::Result<ScheduledRun> {
+    Ok(ScheduledRun {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        project_path: row.get(2)?,
+        prompt: row.get(3)?,
+        model: row.get(4)?,
+        plan_mode: row.get::<_, i64>(5)? != 0,
+        status: row.get(6)?,
+        attempts: row.get(7)?,
+        max_retries: row.get(8)?,
+        timeout_secs: row.get::<_, i64>(9)? as u64,
+        next_attempt_at: row.get(10)?,
+        created_at: row.get(11)?,
+        started_at: row.get(12)?,
+        finished_at: row.get(13)?,
+        session_id: row.get(14)?,
+        result: row.get(15)?,
+        cost_usd: row.get(16)?,
+        error: row.get(17)?,
+    })
+}
+
+fn load_run(conn: &Connection, id: i64) -> Result<ScheduledRun, String> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM scheduled_runs WHERE id = ?1",
+            SELECT_COLUMNS
+        ),
+        [id],
+        row_to_run,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Run not found: {}", id))
+}
+
+fn emit_run(app: &AppHandle, run: &ScheduledRun) {
+    events::emit(app, AppEvent::ScheduledRunUpdated(run.clone()));
+}
+
+/// Delay before retrying after the given number of attempts
+fn backoff(config: &SchedulerConfig, attempts: u32) -> Duration {
+    let factor = 2u64.saturating_pow(attempts.saturating_sub(1));
+    Duration::from_secs(
+        config
+            .retry_backoff_secs
+            .saturating_mul(factor)
+            .min(MAX_BACKOFF_SECS),
+    )
+}
+
+/// Claim due runs up to the concurrency cap and mark them running
+fn claim_due_runs(app: &AppHandle) -> Result<Vec<ScheduledRun>, String> {
+    // Attempts would only fail and use up their retries
+    if super::network::is_down(app) {
+        return Ok(Vec::new());
+    }
+    let state = app.state::<RunSchedulerState>();
+    let db = app.state::<AgentDb>();
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let config = state.config(&conn);
+
+    let mut active = state.active.lock().map_err(|e| e.to_string())?;
+    let free = config.max_concurrency.max(1).saturating_sub(active.len());
+    if free == 0 {
+        return Ok(Vec::new());
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM scheduled_runs
+             WHERE status IN ('queued', 'retrying') AND next_attempt_at <= ?1
+             ORDER BY next_attempt_at, id LIMIT ?2",
+            SELECT_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let due = stmt
+        .query_map(params![now, free as i64], row_to_run)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut claimed = Vec::with_capacity(due.len());
+    for mut run in due {
+        run.status = "running".to_string();
+        run.attempts += 1;
+        run.started_at = Some(now.clone());
+        run.error = None;
+        conn.execute(
+            "UPDATE scheduled_runs SET status = ?1, attempts = ?2, started_at = ?3, error = NULL WHERE id = ?4",
+            params![run.status, run.attempts, run.started_at, run.id],
+        )
+        .map_err(|e| e.to_string())?;
+        active.insert(run.id, Arc::new(Notify::new()));
+        claimed.push(run);
+    }
+    Ok(claimed)
+}
+
+/// Run one attempt and record its outcome
+async fn execute_run(app: AppHandle, run: ScheduledRun) {
+    let cancel = {
+        let state = app.state::<RunSchedulerState>();
+        let active = state.active.lock().ok();
+        active.and_then(|active| active.get(&run.id).cloned())
+    };
+    let cancel = match cancel {
+        Some(cancel) => cancel,
+        None => return,
+    };
+    emit_run(&app, &run);
+    info!(
+        "Starting scheduled run {} (attempt {} of {})",
+        run.id,
+        run.attempts,
+        run.max_retries + 1
+    );
+
+    let spec = SessionSpec {
+        project_path: run.project_path.clone(),
+        prompt: run.prompt.clone(),
+        model: run.model.clone(),
+        plan_mode: run.plan_mode,
+        timeout: Some(Duration::from_secs(run.timeout_secs)),
+    };
+    let progress_app = app.clone();
+    let run_id = run.id;
+    let project_path = run.project_path.clone();
+    let progress: ProgressFn = Arc::new(move |progress| {
+        if let headless::SessionProgress::Line(line) = progress {
+            events::emit(
+                &progress_app,
+                AppEvent::ScheduledRunOutput {
+                    run_id,
+                    project_path: project_path.clone(),
+                    line: line.to_string(),
+                },
+            );
+        }
+    });
+    let session = headless::run_session(&app, &spec, cancel, progress).await;
+
+    let state = app.state::<RunSchedulerState>();
+    if let Ok(mut active) = state.active.lock() {
+        active.remove(&run.id);
+    }
+
+    let updated = {
+        let db = app.state::<AgentDb>();
+        let conn = match db.0.lock() {
+            Ok(conn) => conn,
+            Err(_) => return,
+        };
+        let config = state.config(&conn);
+        let now = chrono::Utc::now();
+        let mut next_attempt_at = run.next_attempt_at.clone();
+        let status = match session.exit {
+            _ if session.succeeded() => "completed",
+            SessionExit::Cancelled => "cancelled",
+            _ if run.attempts <= run.max_retries => {
+                let delay = backoff(&config, run.attempts);
+                next_attempt_at = (now
+                    + chrono::Duration::from_std(delay)
+                        .unwrap_or_else(|_| chrono::Duration::zero()))
+                .to_rfc3339();
+                "retrying"
+            }
+            _ => "failed",
+        };
+        let finished_at = (status != "retrying").then(|| now.to_rfc3339());
+        let result = conn.execute(
+            "UPDATE scheduled_runs SET status = ?1, next_attempt_at = ?2, finished_at = ?3,
+             session_id = ?4, result = ?5, cost_usd = ?6, error = ?7 WHERE id = ?8",
+            params![
+                status,
+                next_attempt_at,
+                finished_at,
+                session.session_id,
+                session.result,
+                session.cost_usd,
+                session.error(),
+                run.id
+            ],
+        );
+        if let Err(e) = result {
+            error!("Failed to record scheduled run {}: {}", run.id, e);
+        }
+        load_run(&conn, run.id)
+    };
+
+    match updated {
+        Ok(updated) => {
+            info!("Scheduled run {} is now {}", updated.id, updated.status);
+            emit_run(&app, &updated);
+        }
+        Err(e) => warn!("Failed to reload scheduled run {}: {}", run.id, e),
+    }
+    state.wake.notify_one();
+}
+
+/// Look for due runs now instead of at the next poll
+pub fn wake(app: &AppHandle) {
+    if let Some(state) = app.try_state::<RunSchedulerState>() {
+        state.wake.notify_one();
+    }
+}
+
+/// Requeue runs that were running when the app last exited, then start the loop
+/// that launches due runs
+pub fn start_run_scheduler(app: AppHandle) {
+    if let Some(db) = app.try_state::<AgentDb>() {
+        if let Ok(conn) = db.0.lock() {
+            match conn.execute(
+                "UPDATE scheduled_runs SET status = 'queued', error = 'Interrupted by app exit'
+                 WHERE status = 'running'",
+                [],
+            ) {
+                Ok(0) => {}
+                Ok(count) => info!("Requeued {} interrupted scheduled runs", count),
+                Err(e) => warn!("Failed to requeue interrupted runs: {}", e),
+            }
+        }
+    }
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match claim_due_runs(&app) {
+                Ok(runs) => {
+                    for run in runs {
+                        tauri::async_runtime::spawn(execute_run(app.clone(), run));
+                    }
+                }
+                Err(e) => warn!("Run scheduler tick failed: {}", e),
+            }
+            let state = app.state::<RunSchedulerState>();
+            let _ = tokio::time::timeout(POLL_INTERVAL, state.wake.notified()).await;
+        }
+    });
+}
+
+// ============ Tauri Commands ============
+
+/// Queue a background run; it starts at `run_at` (RFC 3339) or as soon as a slot
+/// is free
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn schedule_run(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    state: State<'_, RunSchedulerState>,
+    project_path: String,
+    prompt: String,
+    model: String,
+    name: Option<String>,
+    plan_mode: Option<bool>,
+    run_at: Option<String>,
+    timeout_secs: Option<u64>,
+    max_retries: Option<u32>,
+) -> Result<ScheduledRun, String> {
+    if prompt.trim().is_empty() {
+        return Err("Prompt cannot be empty".to_string());
+    }
+    if !std::path::Path::new(&project_path).is_dir() {
+        return Err(format!("Project directory not found: {}", project_path));
+    }
+    let now = chrono::Utc::now().to_rfc3339();
+    let next_attempt_at = match run_at {
+        Some(run_at) => chrono::DateTime::parse_from_rfc3339(&run_at)
+            .map_err(|e| format!("Invalid run time {}: {}", run_at, e))?
+            .with_timezone(&chrono::Utc)
+            .to_rfc3339(),
+        None => now.clone(),
+    };
+
+    let run = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        let config = state.config(&conn);
+        let timeout_secs = timeout_secs.unwrap_or(config.default_timeout_secs).max(1);
+        conn.execute(
+            "INSERT INTO scheduled_runs (name, project_path, prompt, model, plan_mode, status,
+             max_retries, timeout_secs, next_attempt_at, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, 'queued', ?6, ?7, ?8, ?9)",
+            params![
+                name,
+                project_path,
+                prompt,
+                model,
+                plan_mode.unwrap_or(false),
+                max_retries.unwrap_or(config.default_max_retries),
+                timeout_secs as i64,
+                next_attempt_at,
+                now
+            ],
+        )
+        .map_err(|e| format!("Failed to schedule run: {}", e))?;
+        load_run(&conn, conn.last_insert_rowid())?
+    };
+
+    info!("Scheduled run {} in {}", run.id, run.project_path);
+    emit_run(&app, &run);
+    state.wake.notify_one();
+    Ok(run)
+}
+
+/// Runs, newest first, optionally only those with the given status
+#[tauri::command]
+pub async fn list_runs(
+    db: State<'_, AgentDb>,
+    status: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<ScheduledRun>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM scheduled_runs WHERE ?1 IS NULL OR status = ?1
+             ORDER BY id DESC LIMIT ?2",
+            SELECT_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let runs = stmt
+        .query_map(params![status, limit.unwrap_or(100)], row_to_run)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(runs)
+}
+
+/// Cancel a run: queued runs never start, a running attempt is killed, and no
+/// retry follows
+#[tauri::command]
+pub async fn cancel_run(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    state: State<'_, RunSchedulerState>,
+    run_id: i64,
+) -> Result<ScheduledRun, String> {
+    let running = state
+        .active
+        .lock()
+        .map_err(|e| e.to_string())?
+        .get(&run_id)
+        .cloned();
+    if let Some(cancel) = running {
+        // The attempt records the cancellation when it exits
+        cancel.notify_one();
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        return load_run(&conn, run_id);
+    }
+
+    let run = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        let updated = conn
+            .execute(
+                "UPDATE scheduled_runs SET status = 'cancelled', finished_at = ?1
+                 WHERE id = ?2 AND status IN ('queued', 'retrying')",
+                params![chrono::Utc::now().to_rfc3339(), run_id],
+            )
+            .map_err(|e| e.to_string())?;
+        let run = load_run(&conn, run_id)?;
+        if updated == 0 {
+            return Err(format!("Run {} is already {}", run_id, run.status));
+        }
+        run
+    };
+    emit_run(&app, &run);
+    Ok(run)
+}
+
+/// Get the scheduler limits and run defaults
+#[tauri::command]
+pub async fn get_scheduler_config(
+    db: State<'_, AgentDb>,
+    state: State<'_, RunSchedulerState>,
+) -> Result<SchedulerConfig, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    Ok(state.config(&conn))
+}
+
+/// Replace the scheduler limits and run defaults
+#[tauri::command]
+pub async fn update_scheduler_config(
+    db: State<'_, AgentDb>,
+    state: State<'_, RunSchedulerState>,
+    config: SchedulerConfig,
+) -> Result<(), String> {
+    if config.max_concurrency == 0 {
+        return Err("max_concurrency must be at least 1".to_string());
+    }
+    let json = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+    {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+            params![SETTINGS_KEY, json],
+        )
+        .map_err(|e| format!("Failed to save run scheduler settings: {}", e))?;
+    }
+    *state.config.lock().map_err(|e| e.to_string())? = Some(config);
+    // A higher cap may free slots right away
+    state.wake.notify_one();
+    info!("Run scheduler settings updated");
+    Ok(())
+}
@@ -0,0 +1,500 @@
+use log::{info, warn};
+/// Batch operations on sessions and projects
+///
+/// For users with hundreds of sessions: delete, export or tag many at once, and
+/// archive a whole project. Each batch is all or nothing. Everything is checked
+/// before the first change; if an item then fails, or the batch is cancelled
+/// through `cancel_operation(request_id)`, the items already done are undone.
+/// Deleted sessions are moved aside and only removed once the whole batch has
+/// succeeded, and an export or archive removes the files it already wrote.
+///
+/// Progress is emitted as `batch-progress` after each item.
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, State};
+use walkdir::WalkDir;
+
+use super::claude::get_claude_dir;
+use super::operations;
+use super::session_export::{self, find_session_file, SessionExportOptions};
+use super::session_state;
+use super::storage::AgentDb;
+use crate::error::{WorkbenchError, WorkbenchResult};
+use crate::events::{self, AppEvent};
+
+/// Sent as `batch-progress` after each item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchProgress {
+    pub batch_id: String,
+    /// "delete", "export", "retag" or "archive"
+    pub operation: String,
+    pub done: usize,
+    pub total: usize,
+    /// Session ID, or the file archived
+    pub item: String,
+}
+
+/// Outcome of a finished batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchResult {
+    /// The request ID, or a generated one when none was given
+    pub batch_id: String,
+    pub operation: String,
+    pub processed: usize,
+    /// Files written: exports, or the archive directory
+    pub outputs: Vec<String>,
+}
+
+/// Progress of a running batch
+struct Batch {
+    app: AppHandle,
+    id: String,
+    operation: &'static str,
+    total: usize,
+    done: usize,
+}
+
+impl Batch {
+    fn new(
+        app: &AppHandle,
+        request_id: Option<&str>,
+        operation: &'static str,
+        total: usize,
+    ) -> Self {
+        Self {
+            app: app.clone(),
+            id: request_id
+                .map(str::to_string)
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            operation,
+            total,
+            done: 0,
+        }
+    }
+
+    /// Fail if the batch was cancelled; called before each item
+    fn check(&self) -> WorkbenchResult<()> {
+        if operations::is_cancelled() {
+            return Err(WorkbenchError::Cancelled(format!(
+                "Batch {}",
+                self.operation
+            )));
+        }
+        Ok(())
+    }
+
+    fn advance(&mut self, item: &str) {
+        self.done += 1;
+        let progress = BatchProgress {
+            batch_id: self.id.clone(),
+            operation: self.operation.to_string(),
+            done: self.done,
+            total: self.total,
+            item: item.to_string(),
+        };
+        events::emit(&self.app, AppEvent::BatchProgress(progress));
+    }
+
+    fn finish(self, outputs: Vec<String>) -> BatchResult {
+        info!(
+            "Batch {} {} finished: {} of {}",
+            self.operation, self.id, self.done, self.total
+        );
+        BatchResult {
+            batch_id: self.id,
+            operation: self.operation.to_string(),
+            processed: self.done,
+            outputs,
+        }
+    }
+}
+
+fn claude_dir() -> WorkbenchResult<PathBuf> {
+    get_claude_dir().map_err(|e| WorkbenchError::Other(e.to_string()))
+}
+
+fn rename(from: &Path, to: &Path) -> WorkbenchResult<()> {
+    fs::rename(from, to)
+        .map_err(|e| WorkbenchError::io(format!("Failed to move {}", from.display()), e))
+}
+
+/// Move session files aside, then drop their tags and attributions; on failure
+/// the files are moved back
+fn delete_all(
+    app: &AppHandle,
+    batch: &mut Batch,
+    files: &[(String, PathBuf)],
+) -> WorkbenchResult<()> {
+    let trash = claude_dir()?.join("batch-trash").join(&batch.id);
+    fs::create_dir_all(&trash)
+        .map_err(|e| WorkbenchError::io("Failed to create batch directory", e))?;
+
+    let mut moved: Vec<(PathBuf, PathBuf)> = Vec::new();
+    let mut delete = || -> WorkbenchResult<()> {
+        for (session_id, file) in files {
+            batch.check()?;
+            let aside = trash.join(format!("{}.jsonl", session_id));
+            rename(file, &aside)?;
+            moved.push((file.clone(), aside));
+            // Subagent transcripts and tool results live next to the session
+            let dir = file.with_extension("");
+            if dir.is_dir() {
+                let aside = trash.join(session_id);
+                rename(&dir, &aside)?;
+                moved.push((dir, aside));
+            }
+            batch.advance(session_id);
+        }
+
+        let db = app.state::<AgentDb>();
+        let mut conn = db.0.lock().map_err(|e| e.to_string())?;
+        let tx = conn.transaction()?;
+        for (session_id, _) in files {
+            tx.execute(
+                "DELETE FROM session_tags WHERE session_id = ?1",
+                [session_id],
+            )?;
+            tx.execute(
+                "DELETE FROM session_attributions WHERE session_id = ?1",
+                [session_id],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    };
+    let result = delete();
+
+    let mut clean_up = true;
+    if result.is_err() {
+        for (original, aside) in moved.iter().rev() {
+            if let Err(e) = fs::rename(aside, original) {
+                warn!("Failed to restore {}: {}", original.display(), e);
+                // Keep what could not be put back
+                clean_up = false;
+            }
+        }
+    }
+    if !clean_up {
+        warn!("Sessions not restored are kept in {}", trash.display());
+    } else if let Err(e) = fs::remove_dir_all(&trash) {
+        warn!("Failed to clean up {}: {}", trash.display(), e);
+    }
+    result
+}
+
+/// Write a zstd-compressed copy of a file, creating its directory; returns the
+/// compressed size
+pub(super) fn compress_file(from: &Path, to: &Path) -> WorkbenchResult<u64> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| WorkbenchError::io("Failed to create archive directory", e))?;
+    }
+    let content = fs::read(from)
+        .map_err(|e| WorkbenchError::io(format!("Failed to read {}", from.display()), e))?;
+    let compressed = zstd::encode_all(content.as_slice(), 0)
+        .map_err(|e| WorkbenchError::io("Failed to compress", e))?;
+    fs::write(to, &compressed)
+        .map_err(|e| WorkbenchError::io(format!("Failed to write {}", to.display()), e))?;
+    Ok(compressed.len() as u64)
+}
+
+/// Compress every file of a project directory into `out_dir`, keeping the layout
+fn archive_files(
+    batch: &mut Batch,
+    project_dir: &Path,
+    files: &[PathBuf],
+    out_dir: &Path,
+) -> WorkbenchResult<()> {
+    for file in files {
+        batch.check()?;
+        let relative = file.strip_prefix(project_dir).unwrap_or(file);
+        compress_file(file, &out_dir.join(format!("{}.zst", relative.display())))?;
+        batch.advance(&relative.to_string_lossy());
+    }
+    Ok(())
+}
+
+// ============ Tauri Commands ============
+
+/// Delete sessions and their transcripts. No session may be running.
+#[tauri::command]
+pub async fn delete_sessions(
+    app: AppHandle,
+    session_ids: Vec<String>,
+    project_id: Option<String>,
+    request_id: Option<String>,
+) -> WorkbenchResult<BatchResult> {
+    let mut batch = Batch::new(&app, request_id.as_deref(), "delete", session_ids.len());
+    operations::run(&app, request_id, "batch-delete", async move {
+        let mut files = Vec::new();
+        for session_id in session_ids {
+            session_state::ensure_not_live(&batch.app, &session_id, "delete")?;
+            let file = find_session_file(&session_id, project_id.as_deref())
+                .map_err(WorkbenchError::NotFound)?;
+            files.push((session_id, file));
+        }
+        let app = batch.app.clone();
+        delete_all(&app, &mut batch, &files)?;
+        Ok(batch.finish(Vec::new()))
+    })
+    .await
+}
+
+/// Export sessions into a directory as `claude-session-<id>.<ext>`, in any
+/// format `export_session` supports. Existing files are never overwritten.
+#[tauri::command]
+pub async fn export_sessions(
+    app: AppHandle,
+    session_ids: Vec<String>,
+    format: String,
+    output_dir: String,
+    project_id: Option<String>,
+    options: Option<SessionExportOptions>,
+    request_id: Option<String>,
+) -> WorkbenchResult<BatchResult> {
+    let extension = session_export::export_extension(&format)?;
+    let mut targets = Vec::new();
+    for session_id in session_ids {
+        let target =
+            Path::new(&output_dir).join(format!("claude-session-{}.{}", session_id, extension));
+        if target.exists() {
+            return Err(WorkbenchError::InvalidInput(format!(
+                "{} already exists",
+                target.display()
+            )));
+        }
+        targets.push((session_id, target));
+    }
+    fs::create_dir_all(&output_dir)
+        .map_err(|e| WorkbenchError::io("Failed to create export directory", e))?;
+
+    let mut batch = Batch::new(&app, request_id.as_deref(), "export", targets.len());
+    let options = options.unwrap_or_default();
+    operations::run(&app, request_id, "batch-export", async move {
+        let mut written: Vec<String> = Vec::new();
+        let mut outcome = Ok(());
+        for (session_id, target) in targets {
+            if let Err(e) = batch.check() {
+                outcome = Err(e);
+                break;
+            }
+            let mut options = options.clone();
+            options.output_path = Some(target.to_string_lossy().to_string());
+            match session_export::export_session(
+                batch.app.clone(),
+                session_id.clone(),
+                format.clone(),
+                project_id.clone(),
+                Some(options),
+            )
+            .await
+            {
+                Ok(result) => {
+                    written.push(result.path);
+                    batch.advance(&session_id);
+                }
+                Err(e) => {
+                    outcome = Err(WorkbenchError::Other(format!(
+                        "Failed to export session {}: {}",
+                        session_id, e
+                    )));
+                    break;
+                }
+            }
+        }
+
+        if let Err(e) = outcome {
+            for path in &written {
+                if let Err(e) = fs::remove_file(path) {
+                    warn!("Failed to remove partial export {}: {}", path, e);
+                }
+            }
+            return Err(e);
+        }
+        Ok(batch.finish(written))
+    })
+    .await
+}
+
+/// Set the tag of sessions, or clear it with None
+#[tauri::command]
+pub async fn retag_sessions(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    session_ids: Vec<String>,
+    tag: Option<String>,
+) -> WorkbenchResult<BatchResult> {
+    let tag = tag.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    let mut batch = Batch::new(&app, None, "retag", session_ids.len());
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let mut conn = db.0.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction()?;
+    for session_id in &session_ids {
+        match &tag {
+            Some(tag) => tx.execute(
+                "INSERT OR REPLACE INTO session_tags (session_id, tag, updated_at) VALUES (?1, ?2, ?3)",
+                params![session_id, tag, now],
+            )?,
+            None => tx.execute("DELETE FROM session_tags WHERE session_id = ?1", [session_id])?,
+        };
+    }
+    tx.commit()?;
+
+    for session_id in &session_ids {
+        batch.advance(session_id);
+    }
+    Ok(batch.finish(Vec::new()))
+}
+
+/// Tags of the given sessions, or of every tagged session; untagged sessions
+/// are left out
+#[tauri::command]
+pub async fn get_session_tags(
+    db: State<'_, AgentDb>,
+    session_ids: Option<Vec<String>>,
+) -> WorkbenchResult<HashMap<String, String>> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare("SELECT session_id, tag FROM session_tags")?;
+    let tags = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<Result<HashMap<_, _>, _>>()?;
+    Ok(match session_ids {
+        Some(ids) => tags
+            .into_iter()
+            .filter(|(id, _)| ids.contains(id))
+            .collect(),
+        None => tags,
+    })
+}
+
+/// Directory of `project_id`, which must name a direct child of the projects
+/// directory; archiving removes it afterwards
+fn project_dir(claude_dir: &Path, project_id: &str) -> WorkbenchResult<PathBuf> {
+    if project_id.is_empty()
+        || project_id == "."
+        || project_id.contains("..")
+        || project_id.contains(['/', '\\'])
+    {
+        return Err(WorkbenchError::InvalidInput(format!(
+            "Invalid project id: {}",
+            project_id
+        )));
+    }
+    let projects_dir = claude_dir.join("projects");
+    let not_found = || WorkbenchError::NotFound(format!("Project not found: {}", project_id));
+    let project_dir = projects_dir
+        .join(project_id)
+        .canonicalize()
+        .map_err(|_| not_found())?;
+    let inside = projects_dir
+        .canonicalize()
+        .map(|projects_dir| project_dir.parent() == Some(projects_dir.as_path()))
+        .unwrap_or(false);
+    if !inside {
+        return Err(WorkbenchError::InvalidInput(format!(
+            "{} is not inside {}",
+            project_dir.display(),
+            projects_dir.display()
+        )));
+    }
+    if !project_dir.is_dir() {
+        return Err(not_found());
+    }
+    Ok(project_dir)
+}
+
+/// Move a project's sessions into a compressed archive and remove them from
+/// the project list. Every file is kept as `<name>.zst` (readable with
+/// `zstd -d`) under `<output_dir>/<project id>-<time>`; `output_dir` defaults to
+/// `~/.claude/archive`. No session of the project may be running.
+#[tauri::command]
+pub async fn archive_project(
+    app: AppHandle,
+    project_id: String,
+    output_dir: Option<String>,
+    request_id: Option<String>,
+) -> WorkbenchResult<BatchResult> {
+    let claude_dir = claude_dir()?;
+    let project_dir = project_dir(&claude_dir, &project_id)?;
+
+    let files: Vec<PathBuf> = WalkDir::new(&project_dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+        .collect();
+    let sessions: Vec<String> = files
+        .iter()
+        .filter(|file| file.parent() == Some(project_dir.as_path()))
+        .filter(|file| file.extension().and_then(|e| e.to_str()) == Some("jsonl"))
+        .filter_map(|file| file.file_stem().map(|s| s.to_string_lossy().to_string()))
+        .collect();
+    for session_id in &sessions {
+        session_state::ensure_not_live(&app, session_id, "archive")?;
+    }
+
+    let archive_dir = output_dir
+        .map(PathBuf::from)
+        .unwrap_or_else(|| claude_dir.join("archive"))
+        .join(format!(
+            "{}-{}",
+            project_id,
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        ));
+    if archive_dir.exists() {
+        return Err(WorkbenchError::InvalidInput(format!(
+            "{} already exists",
+            archive_dir.display()
+        )));
+    }
+    let partial_dir = PathBuf::from(format!("{}.partial", archive_dir.display()));
+
+    let mut batch = Batch::new(&app, request_id.as_deref(), "archive", files.len());
+    operations::run(&app, request_id, "batch-archive", async move {
+        let manifest = serde_json::json!({
+            "project_id": project_id,
+            "archived_at": chrono::Utc::now().to_rfc3339(),
+            "sessions": sessions,
+        });
+        let written = archive_files(&mut batch, &project_dir, &files, &partial_dir)
+            .and_then(|_| {
+                fs::write(
+                    partial_dir.join("manifest.json"),
+                    serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?,
+                )
+                .map_err(|e| WorkbenchError::io("Failed to write archive manifest", e))
+            })
+            .and_then(|_| rename(&partial_dir, &archive_dir));
+        if let Err(e) = written {
+            if let Err(e) = fs::remove_dir_all(&partial_dir) {
+                warn!(
+                    "Failed to remove partial archive {}: {}",
+                    partial_dir.display(),
+                    e
+                );
+            }
+            return Err(e);
+        }
+
+        // The archive is complete, so the originals can go
+        if let Err(e) = fs::remove_dir_all(&project_dir) {
+            warn!(
+                "Archived {} but failed to remove its sessions: {}",
+                project_id, e
+            );
+        }
+        info!(
+            "Archived project {} to {}",
+            project_id,
+            archive_dir.display()
+        );
+        Ok(batch.finish(vec![archive_dir.to_string_lossy().to_string()]))
+    })
+    .await
+}
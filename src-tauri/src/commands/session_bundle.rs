@@ -0,0 +1,424 @@
+use log::{info, warn};
+/// Shareable static site of a session
+///
+/// `publish_session_bundle` writes a session transcript to a directory as
+/// `index.html`, `style.css` and `app.js`, which any static host can serve as is.
+/// Images are inlined as data URIs and nothing is loaded from elsewhere. The
+/// transcript is rendered like an HTML export (see `session_export`) with a
+/// search box and a switch to open every tool call. Optionally the page also
+/// lists the project's changes since the session started and the checkpoints
+/// created while it ran.
+///
+/// The transcript and diffs are masked by the configured `Redactor` before
+/// anything is rendered. Redaction is pattern based, so the bundle should still
+/// be read before it is published.
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use super::checkpoints::{list_git_checkpoints, snapshot_tree, Checkpoint};
+use super::git_stats::{commit_before, git_file_diffs, summarize_file_diffs, FileDiff, EMPTY_TREE};
+use super::redaction::{self, Redactor};
+use super::session_export::{
+    escape_html, find_session_file, load_transcript, render_html_messages, summary_lines,
+    SessionExportOptions, Transcript, HTML_STYLE,
+};
+
+/// Diff lines rendered per file; the file list still shows the full counts
+const MAX_DIFF_LINES_PER_FILE: usize = 400;
+
+/// Options for `publish_session_bundle`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionBundleOptions {
+    /// Show tool inputs and results instead of a one-line summary
+    #[serde(default)]
+    pub expand_tool_calls: bool,
+    #[serde(default = "default_true")]
+    pub include_timestamps: bool,
+    #[serde(default = "default_true")]
+    pub include_thinking: bool,
+    /// Mask secrets and personal data with the configured redaction rules
+    #[serde(default = "default_true")]
+    pub redact_secrets: bool,
+    /// List what changed in the project since the session started
+    #[serde(default)]
+    pub include_diff_stats: bool,
+    /// Show the checkpoints created while the session ran
+    #[serde(default)]
+    pub include_checkpoints: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for SessionBundleOptions {
+    fn default() -> Self {
+        Self {
+            expand_tool_calls: false,
+            include_timestamps: true,
+            include_thinking: true,
+            redact_secrets: true,
+            include_diff_stats: false,
+            include_checkpoints: false,
+        }
+    }
+}
+
+/// Result of `publish_session_bundle`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionBundleResult {
+    pub out_dir: String,
+    /// The page to open or link to
+    pub index_path: String,
+    pub files: Vec<String>,
+    pub message_count: usize,
+    pub attachment_count: usize,
+    /// How many values were redacted
+    pub redaction_count: usize,
+}
+
+/// Changes shown in the bundle
+struct BundleChanges {
+    from: String,
+    files: Vec<FileDiff>,
+}
+
+/// A checkpoint and the index of the first message at or after it
+struct TimelineEntry {
+    checkpoint: Checkpoint,
+    message_index: Option<usize>,
+}
+
+fn parse_time(timestamp: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|t| t.timestamp())
+}
+
+/// Changes from the last commit before the session to the current working tree
+fn collect_changes(project_path: &str, started: &str) -> Result<BundleChanges, String> {
+    let from = commit_before(project_path, started)
+        .map_err(|e| e.to_string())?
+        .unwrap_or_else(|| EMPTY_TREE.to_string());
+    let to = snapshot_tree(project_path)?;
+    let files = git_file_diffs(project_path, &from, &to).map_err(|e| e.to_string())?;
+    Ok(BundleChanges { from, files })
+}
+
+/// Checkpoints created between the first and last message, oldest first
+fn collect_timeline(
+    transcript: &Transcript,
+    project_path: &str,
+) -> Result<Vec<TimelineEntry>, String> {
+    let (start, end) = match (
+        transcript.started().and_then(parse_time),
+        transcript.ended().and_then(parse_time),
+    ) {
+        (Some(start), Some(end)) => (start, end),
+        _ => return Ok(Vec::new()),
+    };
+    let message_times: Vec<Option<i64>> = transcript
+        .messages
+        .iter()
+        .map(|m| m.timestamp.as_deref().and_then(parse_time))
+        .collect();
+
+    let mut entries: Vec<TimelineEntry> = list_git_checkpoints(project_path)?
+        .into_iter()
+        .filter(|c| c.created_at >= start && c.created_at <= end)
+        .map(|checkpoint| TimelineEntry {
+            message_index: message_times
+                .iter()
+                .position(|t| t.is_some_and(|t| t >= checkpoint.created_at)),
+            checkpoint,
+        })
+        .collect();
+    entries.reverse();
+    Ok(entries)
+}
+
+/// Diff text is masked when a redactor is given; replacements are added to
+/// `redactions`
+fn render_changes(
+    changes: &BundleChanges,
+    redactor: Option<&Redactor>,
+    redactions: &mut usize,
+) -> String {
+    let stats = summarize_file_diffs(&changes.files);
+    let short_from: String = changes.from.chars().take(10).collect();
+    let mut out = format!(
+        "<section id=\"changes\"><h2>Changes</h2>\n<p>{} files changed, +{} / -{} since {} \
+         (working tree when the bundle was made)</p>\n",
+        stats.files_changed,
+        stats.lines_added,
+        stats.lines_removed,
+        escape_html(&short_from)
+    );
+    for file in &changes.files {
+        out.push_str(&format!(
+            "<details><summary class=\"tool\">{} {} <span class=\"add\">+{}</span> \
+             <span class=\"del\">-{}</span></summary>",
+            escape_html(&file.status),
+            escape_html(&file.path),
+            file.additions,
+            file.deletions
+        ));
+        if file.binary {
+            out.push_str("<p>Binary file</p></details>\n");
+            continue;
+        }
+        let mut diff = String::new();
+        let mut lines = 0;
+        'hunks: for hunk in &file.hunks {
+            diff.push_str(&format!(
+                "@@ -{},{} +{},{} @@ {}\n",
+                hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines, hunk.header
+            ));
+            for line in &hunk.lines {
+                if lines == MAX_DIFF_LINES_PER_FILE {
+                    diff.push_str("… diff truncated\n");
+                    break 'hunks;
+                }
+                let prefix = match line.kind.as_str() {
+                    "added" => '+',
+                    "removed" => '-',
+                    _ => ' ',
+                };
+                diff.push(prefix);
+                diff.push_str(&line.content);
+                diff.push('\n');
+                lines += 1;
+            }
+        }
+        if let Some(redactor) = redactor {
+            let mut findings = Vec::new();
+            diff = redactor.redact_with(&diff, &mut findings);
+            *redactions += findings.len();
+        }
+        out.push_str(&format!(
+            "<pre class=\"diff\">{}</pre></details>\n",
+            escape_html(&diff)
+        ));
+    }
+    out.push_str("</section>\n");
+    out
+}
+
+fn render_timeline(entries: &[TimelineEntry]) -> String {
+    let mut out = String::from("<section id=\"checkpoints\"><h2>Checkpoints</h2>\n<ol>\n");
+    if entries.is_empty() {
+        out.push_str("<li>No checkpoints were created during this session</li>\n");
+    }
+    for entry in entries {
+        let when = chrono::DateTime::from_timestamp(entry.checkpoint.created_at, 0)
+            .map(|t| t.to_rfc3339())
+            .unwrap_or_default();
+        let label = format!(
+            "{} · {}",
+            escape_html(&when),
+            escape_html(&entry.checkpoint.message)
+        );
+        match entry.message_index {
+            Some(index) => out.push_str(&format!(
+                "<li><a href=\"#msg-{}\" data-msg=\"{}\">{}</a></li>\n",
+                index, index, label
+            )),
+            None => out.push_str(&format!("<li>{}</li>\n", label)),
+        }
+    }
+    out.push_str("</ol></section>\n");
+    out
+}
+
+fn render_index(
+    transcript: &Transcript,
+    options: &SessionBundleOptions,
+    changes: Option<&str>,
+    timeline: Option<&str>,
+) -> String {
+    let export_options = SessionExportOptions {
+        expand_tool_calls: options.expand_tool_calls,
+        include_timestamps: options.include_timestamps,
+        include_thinking: options.include_thinking,
+        redact_secrets: options.redact_secrets,
+        output_path: None,
+    };
+    let title = format!("Claude session {}", transcript.session_id);
+
+    let mut out = String::from("<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\">\n");
+    out.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    out.push_str(&format!("<title>{}</title>\n", escape_html(&title)));
+    out.push_str("<link rel=\"stylesheet\" href=\"style.css\">\n</head><body>\n");
+    out.push_str(&format!("<h1>{}</h1>\n<ul>\n", escape_html(&title)));
+    for line in summary_lines(transcript) {
+        out.push_str(&format!("<li>{}</li>\n", escape_html(&line)));
+    }
+    out.push_str("</ul>\n");
+    if options.redact_secrets {
+        out.push_str(
+            "<p class=\"note\">Secrets and personal data were masked before publishing.</p>\n",
+        );
+    }
+    out.extend(changes);
+    out.extend(timeline);
+
+    out.push_str(
+        "<nav class=\"toolbar\"><input id=\"search\" type=\"search\" placeholder=\"Search messages\">\
+         <button id=\"toggle-tools\" type=\"button\">Expand tool calls</button>\
+         <span id=\"match-count\"></span></nav>\n",
+    );
+    out.push_str("<main id=\"transcript\">\n");
+    out.push_str(&render_html_messages(transcript, &export_options));
+    out.push_str("</main>\n<script src=\"app.js\"></script>\n</body></html>\n");
+    out
+}
+
+const BUNDLE_STYLE: &str = "\
+    .toolbar{position:sticky;top:0;background:#fff;padding:.5em 0;display:flex;gap:.5em;\
+    align-items:center;border-bottom:1px solid #d0d7de;z-index:1}\
+    .toolbar input{flex:1;padding:.35em .5em;border:1px solid #d0d7de;border-radius:6px}\
+    .toolbar button{padding:.35em .75em;border:1px solid #d0d7de;border-radius:6px;background:#f6f8fa;\
+    cursor:pointer}\
+    #match-count{font-size:.85em;color:#57606a}\
+    .hidden{display:none}.highlight{outline:2px solid #bf8700}\
+    .note{font-size:.85em;color:#57606a}\
+    .add{color:#1a7f37}.del{color:#cf222e}\
+    .diff{font-size:.85em}";
+
+const BUNDLE_SCRIPT: &str = r#"(function () {
+  var messages = Array.prototype.slice.call(document.querySelectorAll('#transcript .msg'));
+  messages.forEach(function (msg, i) { msg.id = 'msg-' + i; });
+
+  var search = document.getElementById('search');
+  var count = document.getElementById('match-count');
+  search.addEventListener('input', function () {
+    var query = search.value.trim().toLowerCase();
+    var shown = 0;
+    messages.forEach(function (msg) {
+      var match = !query || msg.textContent.toLowerCase().indexOf(query) !== -1;
+      msg.classList.toggle('hidden', !match);
+      if (match) shown++;
+    });
+    count.textContent = query ? shown + ' of ' + messages.length : '';
+  });
+
+  var toggle = document.getElementById('toggle-tools');
+  var expanded = false;
+  toggle.addEventListener('click', function () {
+    expanded = !expanded;
+    document.querySelectorAll('#transcript details').forEach(function (d) { d.open = expanded; });
+    toggle.textContent = expanded ? 'Collapse tool calls' : 'Expand tool calls';
+  });
+
+  document.querySelectorAll('a[data-msg]').forEach(function (link) {
+    link.addEventListener('click', function () {
+      var target = document.getElementById('msg-' + link.getAttribute('data-msg'));
+      if (!target) return;
+      search.value = '';
+      search.dispatchEvent(new Event('input'));
+      messages.forEach(function (msg) { msg.classList.remove('highlight'); });
+      target.classList.add('highlight');
+    });
+  });
+})();
+"#;
+
+fn write_bundle(
+    session_id: &str,
+    project_id: Option<&str>,
+    out_dir: &Path,
+    options: &SessionBundleOptions,
+    redactor: Option<&Redactor>,
+) -> Result<SessionBundleResult, String> {
+    let session_file = find_session_file(session_id, project_id)?;
+    let mut transcript = load_transcript(session_id, &session_file)?;
+    // Taken before redaction, which may mask the path
+    let project_path = transcript.project_path.clone();
+    let mut redaction_count = match redactor {
+        Some(redactor) => transcript.redact(redactor),
+        None => 0,
+    };
+
+    let changes = match (&project_path, transcript.started()) {
+        (Some(project), Some(started)) if options.include_diff_stats => {
+            match collect_changes(project, started) {
+                Ok(changes) => Some(render_changes(&changes, redactor, &mut redaction_count)),
+                Err(e) => {
+                    warn!("Bundle for {} without changes: {}", session_id, e);
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
+    let timeline = match &project_path {
+        Some(project) if options.include_checkpoints => {
+            match collect_timeline(&transcript, project) {
+                Ok(entries) => Some(render_timeline(&entries)),
+                Err(e) => {
+                    warn!("Bundle for {} without checkpoints: {}", session_id, e);
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
+
+    let index = render_index(
+        &transcript,
+        options,
+        changes.as_deref(),
+        timeline.as_deref(),
+    );
+    fs::create_dir_all(out_dir).map_err(|e| format!("Failed to create bundle directory: {}", e))?;
+    let files = [
+        ("index.html", index),
+        ("style.css", format!("{}{}", HTML_STYLE, BUNDLE_STYLE)),
+        ("app.js", BUNDLE_SCRIPT.to_string()),
+    ];
+    let mut written = Vec::new();
+    for (name, content) in &files {
+        let path = out_dir.join(name);
+        fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", name, e))?;
+        written.push(path.to_string_lossy().to_string());
+    }
+
+    Ok(SessionBundleResult {
+        out_dir: out_dir.to_string_lossy().to_string(),
+        index_path: out_dir.join("index.html").to_string_lossy().to_string(),
+        files: written,
+        message_count: transcript.messages.len(),
+        attachment_count: transcript.attachment_count(),
+        redaction_count,
+    })
+}
+
+// ============ Tauri Commands ============
+
+/// Write a session as a static site to `out_dir`
+#[tauri::command]
+pub async fn publish_session_bundle(
+    app: AppHandle,
+    session_id: String,
+    out_dir: String,
+    project_id: Option<String>,
+    options: Option<SessionBundleOptions>,
+) -> Result<SessionBundleResult, String> {
+    let options = options.unwrap_or_default();
+    let out_dir = PathBuf::from(out_dir);
+    let redactor = options.redact_secrets.then(|| redaction::redactor(&app));
+    info!("Publishing session {} to {:?}", session_id, out_dir);
+    tauri::async_runtime::spawn_blocking(move || {
+        write_bundle(
+            &session_id,
+            project_id.as_deref(),
+            &out_dir,
+            &options,
+            redactor.as_deref(),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
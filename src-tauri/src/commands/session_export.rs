@@ -0,0 +1,806 @@
+use base64::Engine;
+use log::info;
+/// Session transcript export
+///
+/// Renders a session's JSONL history to Markdown, standalone HTML or PDF so it can
+/// be shared outside the app. Secrets and personal data are masked by the
+/// configured `Redactor` unless `redact_secrets` is turned off. Tool calls are
+/// collapsed to a one-line summary unless `expand_tool_calls` is set. Images are written next to Markdown exports, inlined
+/// as data URIs in HTML, and replaced by a placeholder in PDF. The PDF writer is a
+/// minimal text-only one (Courier, ASCII), which keeps it dependency-free.
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use super::claude::get_claude_dir;
+use super::redaction::{self, RedactionFinding, Redactor};
+use super::usage::message_cost;
+
+/// Options for `export_session`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionExportOptions {
+    /// Show tool inputs and results instead of a one-line summary
+    #[serde(default)]
+    pub expand_tool_calls: bool,
+    #[serde(default = "default_true")]
+    pub include_timestamps: bool,
+    #[serde(default = "default_true")]
+    pub include_thinking: bool,
+    /// Mask secrets and personal data with the configured redaction rules
+    #[serde(default = "default_true")]
+    pub redact_secrets: bool,
+    /// Where to write the file; defaults to the downloads directory
+    #[serde(default)]
+    pub output_path: Option<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for SessionExportOptions {
+    fn default() -> Self {
+        Self {
+            expand_tool_calls: false,
+            include_timestamps: true,
+            include_thinking: true,
+            redact_secrets: true,
+            output_path: None,
+        }
+    }
+}
+
+/// Result of `export_session`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionExportResult {
+    pub path: String,
+    pub format: String,
+    pub message_count: usize,
+    pub attachment_count: usize,
+    /// How many values were masked
+    #[serde(default)]
+    pub redaction_count: usize,
+}
+
+pub(super) enum Block {
+    Text(String),
+    Thinking(String),
+    ToolUse {
+        name: String,
+        input: serde_json::Value,
+    },
+    ToolResult {
+        content: String,
+        is_error: bool,
+    },
+    Image {
+        media_type: String,
+        data: String,
+    },
+}
+
+pub(super) struct Message {
+    pub(super) role: String,
+    pub(super) timestamp: Option<String>,
+    pub(super) blocks: Vec<Block>,
+}
+
+#[derive(Default)]
+struct CostSummary {
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_tokens: u64,
+    cost: f64,
+    models: Vec<String>,
+}
+
+pub(super) struct Transcript {
+    pub(super) session_id: String,
+    pub(super) project_path: Option<String>,
+    pub(super) messages: Vec<Message>,
+    summary: CostSummary,
+}
+
+impl Transcript {
+    pub(super) fn started(&self) -> Option<&str> {
+        self.messages.iter().find_map(|m| m.timestamp.as_deref())
+    }
+
+    pub(super) fn ended(&self) -> Option<&str> {
+        self.messages
+            .iter()
+            .rev()
+            .find_map(|m| m.timestamp.as_deref())
+    }
+
+    pub(super) fn attachment_count(&self) -> usize {
+        self.messages
+            .iter()
+            .flat_map(|m| &m.blocks)
+            .filter(|b| matches!(b, Block::Image { .. }))
+            .count()
+    }
+}
+
+impl Message {
+    /// Mask every block in place
+    pub(super) fn redact(&mut self, redactor: &Redactor, findings: &mut Vec<RedactionFinding>) {
+        for block in &mut self.blocks {
+            match block {
+                Block::Text(text) | Block::Thinking(text) => {
+                    *text = redactor.redact_with(text, findings)
+                }
+                Block::ToolUse { input, .. } => redactor.redact_json_with(input, findings),
+                Block::ToolResult { content, .. } => {
+                    *content = redactor.redact_with(content, findings)
+                }
+                Block::Image { .. } => {}
+            }
+        }
+    }
+}
+
+impl Transcript {
+    /// Mask every message in place; returns the number of replacements
+    pub(super) fn redact(&mut self, redactor: &Redactor) -> usize {
+        let mut findings = Vec::new();
+        for message in &mut self.messages {
+            message.redact(redactor, &mut findings);
+        }
+        if let Some(project) = &self.project_path {
+            self.project_path = Some(redactor.redact_with(project, &mut findings));
+        }
+        findings.len()
+    }
+}
+
+/// Locate a session file, searching every project when none is given
+pub(super) fn find_session_file(
+    session_id: &str,
+    project_id: Option<&str>,
+) -> Result<PathBuf, String> {
+    let projects_dir = get_claude_dir()
+        .map_err(|e| e.to_string())?
+        .join("projects");
+    let file_name = format!("{}.jsonl", session_id);
+
+    if let Some(project_id) = project_id {
+        let path = projects_dir.join(project_id).join(&file_name);
+        return if path.exists() {
+            Ok(path)
+        } else {
+            Err(format!("Session file not found: {}", session_id))
+        };
+    }
+
+    fs::read_dir(&projects_dir)
+        .map_err(|e| format!("Failed to read projects directory: {}", e))?
+        .flatten()
+        .map(|entry| entry.path().join(&file_name))
+        .find(|path| path.exists())
+        .ok_or_else(|| format!("Session file not found: {}", session_id))
+}
+
+fn tool_result_text(content: Option<&serde_json::Value>) -> String {
+    match content {
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(serde_json::Value::Array(items)) => items
+            .iter()
+            .filter_map(|item| item.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Some(other) => other.to_string(),
+        None => String::new(),
+    }
+}
+
+fn parse_block(block: &serde_json::Value) -> Option<Block> {
+    let text = |key: &str| block.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    match block.get("type").and_then(|t| t.as_str())? {
+        "text" => text("text").map(Block::Text),
+        "thinking" => text("thinking").map(Block::Thinking),
+        "tool_use" => Some(Block::ToolUse {
+            name: text("name").unwrap_or_else(|| "tool".to_string()),
+            input: block.get("input").cloned().unwrap_or_default(),
+        }),
+        "tool_result" => Some(Block::ToolResult {
+            content: tool_result_text(block.get("content")),
+            is_error: block
+                .get("is_error")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+        }),
+        "image" => {
+            let source = block.get("source")?;
+            Some(Block::Image {
+                media_type: source
+                    .get("media_type")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("image/png")
+                    .to_string(),
+                data: source.get("data")?.as_str()?.to_string(),
+            })
+        }
+        _ => None,
+    }
+}
+
+pub(super) fn load_transcript(session_id: &str, path: &Path) -> Result<Transcript, String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read session file: {}", e))?;
+
+    let mut transcript = Transcript {
+        session_id: session_id.to_string(),
+        project_path: None,
+        messages: Vec::new(),
+        summary: CostSummary::default(),
+    };
+    // Streamed assistant messages repeat their usage on every line
+    let mut counted = HashSet::new();
+
+    for line in content.lines() {
+        let entry = match serde_json::from_str::<serde_json::Value>(line) {
+            Ok(entry) => entry,
+            Err(_) => continue,
+        };
+        if transcript.project_path.is_none() {
+            transcript.project_path = entry.get("cwd").and_then(|v| v.as_str()).map(String::from);
+        }
+        let role = match entry.get("type").and_then(|t| t.as_str()) {
+            Some(role @ ("user" | "assistant")) => role.to_string(),
+            _ => continue,
+        };
+        if entry.get("isMeta").and_then(|v| v.as_bool()) == Some(true) {
+            continue;
+        }
+        let message = match entry.get("message") {
+            Some(message) => message,
+            None => continue,
+        };
+
+        if let (Some(usage), Some(model)) = (
+            message.get("usage"),
+            message.get("model").and_then(|m| m.as_str()),
+        ) {
+            let id = message.get("id").and_then(|v| v.as_str()).unwrap_or("");
+            if id.is_empty() || counted.insert(id.to_string()) {
+                let tokens = |key: &str| usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+                let summary = &mut transcript.summary;
+                summary.input_tokens += tokens("input_tokens");
+                summary.output_tokens += tokens("output_tokens");
+                summary.cache_tokens +=
+                    tokens("cache_creation_input_tokens") + tokens("cache_read_input_tokens");
+                summary.cost += entry
+                    .get("costUSD")
+                    .and_then(|v| v.as_f64())
+                    .unwrap_or_else(|| message_cost(model, usage));
+                if !summary.models.iter().any(|m| m == model) {
+                    summary.models.push(model.to_string());
+                }
+            }
+        }
+
+        let blocks: Vec<Block> = match message.get("content") {
+            Some(serde_json::Value::String(text)) => vec![Block::Text(text.clone())],
+            Some(serde_json::Value::Array(blocks)) => {
+                blocks.iter().filter_map(parse_block).collect()
+            }
+            _ => Vec::new(),
+        };
+        if blocks.is_empty() {
+            continue;
+        }
+        transcript.messages.push(Message {
+            role,
+            timestamp: entry
+                .get("timestamp")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            blocks,
+        });
+    }
+    Ok(transcript)
+}
+
+fn tool_summary(name: &str, input: &serde_json::Value) -> String {
+    let detail = [
+        "command",
+        "file_path",
+        "path",
+        "pattern",
+        "url",
+        "description",
+    ]
+    .iter()
+    .find_map(|key| input.get(*key).and_then(|v| v.as_str()));
+    match detail {
+        Some(detail) => {
+            let detail: String = detail
+                .lines()
+                .next()
+                .unwrap_or("")
+                .chars()
+                .take(80)
+                .collect();
+            format!("{}: {}", name, detail)
+        }
+        None => name.to_string(),
+    }
+}
+
+fn image_extension(media_type: &str) -> &str {
+    match media_type {
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        _ => "png",
+    }
+}
+
+/// Whether an image can go into a data URI as it is: a known image type and
+/// data that is strictly base64, so neither can break out of the attribute
+fn is_inline_image(media_type: &str, data: &str) -> bool {
+    matches!(
+        media_type,
+        "image/png" | "image/jpeg" | "image/gif" | "image/webp"
+    ) && base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .is_ok()
+}
+
+pub(super) fn summary_lines(transcript: &Transcript) -> Vec<String> {
+    let summary = &transcript.summary;
+    let mut lines = vec![format!("Session: {}", transcript.session_id)];
+    if let Some(project) = &transcript.project_path {
+        lines.push(format!("Project: {}", project));
+    }
+    if let (Some(start), Some(end)) = (transcript.started(), transcript.ended()) {
+        lines.push(format!("Time: {} to {}", start, end));
+    }
+    if !summary.models.is_empty() {
+        lines.push(format!("Models: {}", summary.models.join(", ")));
+    }
+    lines.push(format!(
+        "Tokens: {} input, {} output, {} cache",
+        summary.input_tokens, summary.output_tokens, summary.cache_tokens
+    ));
+    lines.push(format!("Cost: ${:.4}", summary.cost));
+    lines
+}
+
+fn role_heading(message: &Message, options: &SessionExportOptions) -> String {
+    let role = if message.role == "user" {
+        "User"
+    } else {
+        "Assistant"
+    };
+    match (&message.timestamp, options.include_timestamps) {
+        (Some(timestamp), true) => format!("{} · {}", role, timestamp),
+        _ => role.to_string(),
+    }
+}
+
+/// Markdown; images are written to `<name>_attachments/` beside the output file
+fn render_markdown(
+    transcript: &Transcript,
+    options: &SessionExportOptions,
+    output: &Path,
+) -> Result<String, String> {
+    let stem = output
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "session".to_string());
+    let attachment_dir_name = format!("{}_attachments", stem);
+    let attachment_dir = output.with_file_name(&attachment_dir_name);
+    let mut attachment_index = 0;
+
+    let mut out = format!("# Claude session {}\n\n", transcript.session_id);
+    for line in summary_lines(transcript) {
+        out.push_str(&format!("- {}\n", line));
+    }
+
+    for message in &transcript.messages {
+        out.push_str(&format!("\n## {}\n\n", role_heading(message, options)));
+        for block in &message.blocks {
+            match block {
+                Block::Text(text) => out.push_str(&format!("{}\n\n", text.trim_end())),
+                Block::Thinking(text) if options.include_thinking => {
+                    out.push_str("<details><summary>Thinking</summary>\n\n");
+                    out.push_str(&format!("{}\n\n</details>\n\n", text.trim_end()));
+                }
+                Block::Thinking(_) => {}
+                Block::ToolUse { name, input } if options.expand_tool_calls => {
+                    let input = serde_json::to_string_pretty(input).unwrap_or_default();
+                    out.push_str(&format!(
+                        "**Tool: {}**\n\n```json\n{}\n```\n\n",
+                        name, input
+                    ));
+                }
+                Block::ToolUse { name, input } => {
+                    out.push_str(&format!("> 🔧 `{}`\n\n", tool_summary(name, input)));
+                }
+                Block::ToolResult { content, is_error } if options.expand_tool_calls => {
+                    let label = if *is_error {
+                        "Tool error"
+                    } else {
+                        "Tool result"
+                    };
+                    out.push_str(&format!(
+                        "<details><summary>{}</summary>\n\n```\n{}\n```\n\n</details>\n\n",
+                        label,
+                        content.trim_end()
+                    ));
+                }
+                Block::ToolResult { .. } => {}
+                Block::Image { media_type, data } => {
+                    attachment_index += 1;
+                    let file_name = format!(
+                        "attachment-{}.{}",
+                        attachment_index,
+                        image_extension(media_type)
+                    );
+                    let bytes = base64::engine::general_purpose::STANDARD
+                        .decode(data)
+                        .map_err(|e| format!("Invalid image attachment: {}", e))?;
+                    fs::create_dir_all(&attachment_dir)
+                        .map_err(|e| format!("Failed to create attachments directory: {}", e))?;
+                    fs::write(attachment_dir.join(&file_name), bytes)
+                        .map_err(|e| format!("Failed to write attachment: {}", e))?;
+                    out.push_str(&format!(
+                        "![{}]({}/{})\n\n",
+                        file_name, attachment_dir_name, file_name
+                    ));
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+pub(super) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Styles for the rendered transcript
+pub(super) const HTML_STYLE: &str = "\
+    body{font-family:-apple-system,'Segoe UI',sans-serif;max-width:860px;margin:2em auto;\
+    padding:0 1em;color:#1f2328;line-height:1.5}\
+    .msg{border-radius:8px;padding:.75em 1em;margin:1em 0}\
+    .user{background:#eef4ff}.assistant{background:#f6f8fa}\
+    .role{font-weight:600;font-size:.85em;color:#57606a;margin-bottom:.5em}\
+    pre{background:#fff;border:1px solid #d0d7de;border-radius:6px;padding:.6em;\
+    overflow-x:auto;white-space:pre-wrap}\
+    .text{white-space:pre-wrap}.tool{font-family:monospace;font-size:.9em;color:#57606a}\
+    .error{color:#cf222e}img{max-width:100%}";
+
+/// Standalone HTML with inline styles and images
+fn render_html(transcript: &Transcript, options: &SessionExportOptions) -> String {
+    let mut out = String::from("<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\">\n");
+    out.push_str(&format!(
+        "<title>Claude session {}</title>\n",
+        escape_html(&transcript.session_id)
+    ));
+    out.push_str(&format!("<style>{}</style></head><body>\n", HTML_STYLE));
+
+    out.push_str(&format!(
+        "<h1>Claude session {}</h1>\n<ul>\n",
+        escape_html(&transcript.session_id)
+    ));
+    for line in summary_lines(transcript) {
+        out.push_str(&format!("<li>{}</li>\n", escape_html(&line)));
+    }
+    out.push_str("</ul>\n");
+
+    out.push_str(&render_html_messages(transcript, options));
+    out.push_str("</body></html>\n");
+    out
+}
+
+/// One `<div class="msg">` per message
+pub(super) fn render_html_messages(
+    transcript: &Transcript,
+    options: &SessionExportOptions,
+) -> String {
+    let mut out = String::new();
+    for message in &transcript.messages {
+        out.push_str(&format!(
+            "<div class=\"msg {}\"><div class=\"role\">{}</div>\n",
+            message.role,
+            escape_html(&role_heading(message, options))
+        ));
+        for block in &message.blocks {
+            match block {
+                Block::Text(text) => {
+                    out.push_str(&format!(
+                        "<div class=\"text\">{}</div>\n",
+                        escape_html(text)
+                    ));
+                }
+                Block::Thinking(text) if options.include_thinking => {
+                    out.push_str(&format!(
+                        "<details><summary>Thinking</summary><div class=\"text\">{}</div></details>\n",
+                        escape_html(text)
+                    ));
+                }
+                Block::Thinking(_) => {}
+                Block::ToolUse { name, input } => {
+                    let open = if options.expand_tool_calls {
+                        " open"
+                    } else {
+                        ""
+                    };
+                    let pretty = serde_json::to_string_pretty(input).unwrap_or_default();
+                    out.push_str(&format!(
+                        "<details{}><summary class=\"tool\">🔧 {}</summary><pre>{}</pre></details>\n",
+                        open,
+                        escape_html(&tool_summary(name, input)),
+                        escape_html(&pretty)
+                    ));
+                }
+                Block::ToolResult { content, is_error } => {
+                    let open = if options.expand_tool_calls {
+                        " open"
+                    } else {
+                        ""
+                    };
+                    let (class, label) = if *is_error {
+                        ("tool error", "Tool error")
+                    } else {
+                        ("tool", "Tool result")
+                    };
+                    out.push_str(&format!(
+                        "<details{}><summary class=\"{}\">{}</summary><pre>{}</pre></details>\n",
+                        open,
+                        class,
+                        label,
+                        escape_html(content)
+                    ));
+                }
+                Block::Image { media_type, data } => {
+                    if is_inline_image(media_type, data) {
+                        out.push_str(&format!(
+                            "<img src=\"data:{};base64,{}\" alt=\"attachment\">\n",
+                            media_type, data
+                        ));
+                    } else {
+                        out.push_str(&format!(
+                            "<p>[image attachment ({})]</p>\n",
+                            escape_html(media_type)
+                        ));
+                    }
+                }
+            }
+        }
+        out.push_str("</div>\n");
+    }
+    out
+}
+
+/// Plain text lines used for the PDF
+fn render_text(transcript: &Transcript, options: &SessionExportOptions) -> Vec<String> {
+    let mut lines = vec![
+        format!("Claude session {}", transcript.session_id),
+        String::new(),
+    ];
+    lines.extend(summary_lines(transcript));
+    let mut attachment_index = 0;
+
+    for message in &transcript.messages {
+        lines.push(String::new());
+        lines.push(format!("== {} ==", role_heading(message, options)));
+        for block in &message.blocks {
+            match block {
+                Block::Text(text) => lines.extend(text.lines().map(String::from)),
+                Block::Thinking(text) if options.include_thinking => {
+                    lines.push("[thinking]".to_string());
+                    lines.extend(text.lines().map(|l| format!("  {}", l)));
+                }
+                Block::Thinking(_) => {}
+                Block::ToolUse { name, input } => {
+                    lines.push(format!("> {}", tool_summary(name, input)));
+                    if options.expand_tool_calls {
+                        let input = serde_json::to_string_pretty(input).unwrap_or_default();
+                        lines.extend(input.lines().map(|l| format!("    {}", l)));
+                    }
+                }
+                Block::ToolResult { content, is_error } if options.expand_tool_calls => {
+                    lines.push(if *is_error { "< error:" } else { "< result:" }.to_string());
+                    lines.extend(content.lines().map(|l| format!("    {}", l)));
+                }
+                Block::ToolResult { .. } => {}
+                Block::Image { media_type, .. } => {
+                    attachment_index += 1;
+                    lines.push(format!(
+                        "[image attachment {} ({})]",
+                        attachment_index, media_type
+                    ));
+                }
+            }
+        }
+    }
+    lines
+}
+
+// Letter page, 9pt Courier: every glyph is 5.4pt wide, so wrapping is exact
+const PDF_PAGE_WIDTH: usize = 612;
+const PDF_PAGE_HEIGHT: usize = 792;
+const PDF_MARGIN: usize = 50;
+const PDF_FONT_SIZE: usize = 9;
+const PDF_LINE_HEIGHT: usize = 12;
+const PDF_LINE_CHARS: usize = 94;
+
+/// Wrap to the page width and reduce to printable ASCII, which the base fonts cover
+fn pdf_lines(lines: &[String]) -> Vec<String> {
+    let mut wrapped = Vec::new();
+    for line in lines {
+        let ascii: Vec<char> = line
+            .chars()
+            .map(|c| match c {
+                '\t' => ' ',
+                ' '..='~' => c,
+                _ => '?',
+            })
+            .collect();
+        if ascii.is_empty() {
+            wrapped.push(String::new());
+        }
+        for chunk in ascii.chunks(PDF_LINE_CHARS) {
+            wrapped.push(chunk.iter().collect());
+        }
+    }
+    wrapped
+}
+
+fn escape_pdf(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('(', "\\(")
+        .replace(')', "\\)")
+}
+
+/// Minimal multi-page PDF 1.4 with one Courier font
+fn render_pdf(lines: &[String]) -> Vec<u8> {
+    let lines = pdf_lines(lines);
+    let per_page = (PDF_PAGE_HEIGHT - 2 * PDF_MARGIN) / PDF_LINE_HEIGHT;
+    let pages: Vec<&[String]> = if lines.is_empty() {
+        vec![&[]]
+    } else {
+        lines.chunks(per_page).collect()
+    };
+
+    // Objects: 1 catalog, 2 page tree, 3 font, then a page and its content per page
+    let mut objects: Vec<String> = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        String::new(),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>"
+            .to_string(),
+    ];
+    let mut kids = Vec::new();
+    for page in &pages {
+        let page_id = objects.len() + 1;
+        kids.push(format!("{} 0 R", page_id));
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+             /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            PDF_PAGE_WIDTH,
+            PDF_PAGE_HEIGHT,
+            page_id + 1
+        ));
+
+        let mut stream = format!(
+            "BT /F1 {} Tf {} TL {} {} Td\n",
+            PDF_FONT_SIZE,
+            PDF_LINE_HEIGHT,
+            PDF_MARGIN,
+            PDF_PAGE_HEIGHT - PDF_MARGIN
+        );
+        for line in page.iter() {
+            stream.push_str(&format!("({}) Tj T*\n", escape_pdf(line)));
+        }
+        stream.push_str("ET");
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}\nendstream",
+            stream.len(),
+            stream
+        ));
+    }
+    objects[1] = format!(
+        "<< /Type /Pages /Kids [{}] /Count {} >>",
+        kids.join(" "),
+        pages.len()
+    );
+
+    let mut out = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        out.push_str(&format!("{} 0 obj\n{}\nendobj\n", i + 1, object));
+    }
+    let xref_offset = out.len();
+    out.push_str(&format!(
+        "xref\n0 {}\n0000000000 65535 f \n",
+        objects.len() + 1
+    ));
+    for offset in offsets {
+        out.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    out.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref_offset
+    ));
+    out.into_bytes()
+}
+
+/// File extension of an export format
+pub(super) fn export_extension(format: &str) -> Result<&'static str, String> {
+    match format.to_lowercase().as_str() {
+        "markdown" | "md" => Ok("md"),
+        "html" => Ok("html"),
+        "pdf" => Ok("pdf"),
+        _ => Err(format!("Unsupported export format: {}", format)),
+    }
+}
+
+fn default_output_path(session_id: &str, extension: &str) -> Result<PathBuf, String> {
+    let dir = dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .ok_or("Failed to find a directory to export to")?;
+    let short_id: String = session_id.chars().take(8).collect();
+    Ok(dir.join(format!("claude-session-{}.{}", short_id, extension)))
+}
+
+// ============ Tauri Commands ============
+
+/// Export a session transcript as `markdown`, `html` or `pdf`
+#[tauri::command]
+pub async fn export_session(
+    app: AppHandle,
+    session_id: String,
+    format: String,
+    project_id: Option<String>,
+    options: Option<SessionExportOptions>,
+) -> Result<SessionExportResult, String> {
+    let extension = export_extension(&format)?;
+    let options = options.unwrap_or_default();
+
+    let session_file = find_session_file(&session_id, project_id.as_deref())?;
+    let mut transcript = load_transcript(&session_id, &session_file)?;
+    let redaction_count = if options.redact_secrets {
+        transcript.redact(&redaction::redactor(&app))
+    } else {
+        0
+    };
+
+    let output = match &options.output_path {
+        Some(path) => PathBuf::from(path),
+        None => default_output_path(&session_id, extension)?,
+    };
+    if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create export directory: {}", e))?;
+    }
+    info!(
+        "Exporting session {} as {} to {:?}",
+        session_id, extension, output
+    );
+
+    let content = match extension {
+        "md" => render_markdown(&transcript, &options, &output)?.into_bytes(),
+        "html" => render_html(&transcript, &options).into_bytes(),
+        _ => render_pdf(&render_text(&transcript, &options)),
+    };
+    fs::write(&output, content).map_err(|e| format!("Failed to write export: {}", e))?;
+
+    Ok(SessionExportResult {
+        path: output.to_string_lossy().to_string(),
+        format: extension.to_string(),
+        message_count: transcript.messages.len(),
+        attachment_count: transcript.attachment_count(),
+        redaction_count,
+    })
+}
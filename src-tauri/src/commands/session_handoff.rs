@@ -0,0 +1,351 @@
+use log::{info, warn};
+/// Moving a session to another machine
+///
+/// `export_session_handoff` writes one JSON file holding what is needed to carry
+/// on with a session elsewhere: the raw transcript, the project's CLAUDE.md files,
+/// the env profile the session used without variables whose name or value looks
+/// like a secret, the project's git branch and commit, and the checkpoints
+/// created while it ran.
+///
+/// `import_session_handoff` writes the transcript under the same repo path (or
+/// the path given) so `resume_claude_code` can pick it up, restores CLAUDE.md
+/// files the project does not have, and adds the env profile unless one with its
+/// name exists. The profile is added inactive, since the file may come from
+/// anyone; the user switches to it explicitly. Checkpoints are listed for
+/// reference only: their commits live in the exporting machine's repository.
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
+
+use super::checkpoints::{list_git_checkpoints, Checkpoint};
+use super::env_profiles::{self, is_secret_var, EnvProfile, SessionEnvProfiles};
+use super::redaction::redactor;
+use super::session_export::{find_session_file, load_transcript};
+use super::session_import::import_claude_jsonl;
+use super::simple_git::{git_current_branch, git_current_commit, is_git_repo};
+
+const HANDOFF_FORMAT: &str = "claude-workbench-handoff";
+const HANDOFF_VERSION: u32 = 1;
+
+/// Memory files carried in a handoff, relative to the project root
+const CLAUDE_MD_FILES: &[&str] = &["CLAUDE.md", ".claude/CLAUDE.md", "CLAUDE.local.md"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ClaudeMdSnapshot {
+    relative_path: String,
+    content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GitState {
+    branch: Option<String>,
+    commit: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HandoffBundle {
+    format: String,
+    version: u32,
+    exported_at: String,
+    session_id: String,
+    project_path: String,
+    /// The session's JSONL, unchanged
+    transcript: String,
+    claude_md: Vec<ClaudeMdSnapshot>,
+    env_profile: Option<EnvProfile>,
+    /// Variables left out of `env_profile` because they look like secrets
+    removed_vars: Vec<String>,
+    git: Option<GitState>,
+    checkpoints: Vec<Checkpoint>,
+}
+
+/// Result of `export_session_handoff`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandoffExportResult {
+    pub path: String,
+    pub session_id: String,
+    pub project_path: String,
+    pub message_count: usize,
+    pub claude_md_files: Vec<String>,
+    pub env_profile: Option<String>,
+    /// Variables to set again on the other machine
+    pub removed_vars: Vec<String>,
+    pub checkpoint_count: usize,
+}
+
+/// Result of `import_session_handoff`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandoffImportResult {
+    /// Id to resume; differs from the exported one if that was taken here
+    pub session_id: String,
+    pub project_id: String,
+    pub project_path: String,
+    pub message_count: usize,
+    /// CLAUDE.md files written because the project did not have them
+    pub restored_claude_md: Vec<String>,
+    /// CLAUDE.md files left alone because they differ from the snapshot
+    pub differing_claude_md: Vec<String>,
+    /// Env profile added to the project, inactive; None if there was none or
+    /// it existed
+    pub env_profile: Option<String>,
+    /// Variables removed at export that need to be set again
+    pub removed_vars: Vec<String>,
+    pub exported_branch: Option<String>,
+    pub exported_commit: Option<String>,
+    /// Whether the local repository is at the exported commit
+    pub same_commit: Option<bool>,
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+fn snapshot_claude_md(project_path: &str) -> Vec<ClaudeMdSnapshot> {
+    CLAUDE_MD_FILES
+        .iter()
+        .filter_map(|relative| {
+            let content = fs::read_to_string(Path::new(project_path).join(relative)).ok()?;
+            Some(ClaudeMdSnapshot {
+                relative_path: relative.to_string(),
+                content,
+            })
+        })
+        .collect()
+}
+
+/// The profile the session ran with, or else the project's active one, with
+/// variables removed whose name or value looks like a secret
+fn session_profile(
+    app: &AppHandle,
+    sessions: &SessionEnvProfiles,
+    session_id: &str,
+    project_path: &str,
+) -> (Option<EnvProfile>, Vec<String>) {
+    let used = sessions
+        .0
+        .lock()
+        .ok()
+        .and_then(|s| s.get(session_id).cloned());
+    let profile = match used {
+        Some(name) => env_profiles::find_profile(app, project_path, &name),
+        None => env_profiles::active_profile(app, project_path),
+    };
+    let Some(mut profile) = profile else {
+        return (None, Vec::new());
+    };
+    let redactor = redactor(app);
+    let removed: Vec<String> = profile
+        .vars
+        .iter()
+        .filter(|(key, value)| is_secret_var(&redactor, key, value))
+        .map(|(key, _)| key.clone())
+        .collect();
+    profile.vars.retain(|key, _| !removed.contains(key));
+    (Some(profile), removed)
+}
+
+fn checkpoints_during(
+    project_path: &str,
+    started: Option<&str>,
+    ended: Option<&str>,
+) -> Vec<Checkpoint> {
+    let time = |t: Option<&str>| {
+        t.and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.timestamp())
+    };
+    let (Some(start), Some(end)) = (time(started), time(ended)) else {
+        return Vec::new();
+    };
+    match list_git_checkpoints(project_path) {
+        Ok(checkpoints) => checkpoints
+            .into_iter()
+            .filter(|c| c.created_at >= start && c.created_at <= end)
+            .collect(),
+        Err(e) => {
+            warn!("Handoff without checkpoints: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn git_state(project_path: &str) -> Option<GitState> {
+    if !is_git_repo(project_path) {
+        return None;
+    }
+    let commit = git_current_commit(project_path).ok()?;
+    let branch = git_current_branch(project_path).ok().flatten();
+    Some(GitState { branch, commit })
+}
+
+fn default_output_path(session_id: &str) -> Result<PathBuf, String> {
+    let dir = dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .ok_or("Failed to find a directory to export to")?;
+    let short_id: String = session_id.chars().take(8).collect();
+    Ok(dir.join(format!("claude-handoff-{}.json", short_id)))
+}
+
+/// Write CLAUDE.md snapshots the project lacks; returns the restored and the
+/// differing files
+fn restore_claude_md(
+    project_path: &str,
+    snapshots: &[ClaudeMdSnapshot],
+) -> Result<(Vec<String>, Vec<String>), String> {
+    let mut restored = Vec::new();
+    let mut differing = Vec::new();
+    for snapshot in snapshots {
+        if !CLAUDE_MD_FILES.contains(&snapshot.relative_path.as_str()) {
+            warn!(
+                "Skipping unexpected file in handoff: {}",
+                snapshot.relative_path
+            );
+            continue;
+        }
+        let path = Path::new(project_path).join(&snapshot.relative_path);
+        match fs::read_to_string(&path) {
+            Ok(existing) if existing == snapshot.content => {}
+            Ok(_) => differing.push(snapshot.relative_path.clone()),
+            Err(_) if path.exists() => differing.push(snapshot.relative_path.clone()),
+            Err(_) => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)
+                        .map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+                }
+                fs::write(&path, &snapshot.content)
+                    .map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+                restored.push(snapshot.relative_path.clone());
+            }
+        }
+    }
+    Ok((restored, differing))
+}
+
+// ============ Tauri Commands ============
+
+/// Write a session and its project context to a handoff file, by default in
+/// the downloads directory
+#[tauri::command]
+pub async fn export_session_handoff(
+    app: AppHandle,
+    sessions: State<'_, SessionEnvProfiles>,
+    session_id: String,
+    project_id: Option<String>,
+    output_path: Option<String>,
+) -> Result<HandoffExportResult, String> {
+    let session_file = find_session_file(&session_id, project_id.as_deref())?;
+    let transcript = load_transcript(&session_id, &session_file)?;
+    let project_path = transcript
+        .project_path
+        .clone()
+        .ok_or("Cannot tell which project the session belongs to")?;
+    let raw = fs::read_to_string(&session_file)
+        .map_err(|e| format!("Failed to read session file: {}", e))?;
+
+    let (env_profile, removed_vars) = session_profile(&app, &sessions, &session_id, &project_path);
+    let bundle = HandoffBundle {
+        format: HANDOFF_FORMAT.to_string(),
+        version: HANDOFF_VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        session_id: session_id.clone(),
+        project_path: project_path.clone(),
+        transcript: raw,
+        claude_md: snapshot_claude_md(&project_path),
+        env_profile,
+        removed_vars,
+        git: git_state(&project_path),
+        checkpoints: checkpoints_during(&project_path, transcript.started(), transcript.ended()),
+    };
+
+    let output = match output_path {
+        Some(path) => PathBuf::from(path),
+        None => default_output_path(&session_id)?,
+    };
+    if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create export directory: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(&bundle)
+        .map_err(|e| format!("Failed to serialize handoff: {}", e))?;
+    fs::write(&output, json).map_err(|e| format!("Failed to write handoff: {}", e))?;
+    info!("Exported handoff of session {} to {:?}", session_id, output);
+
+    Ok(HandoffExportResult {
+        path: output.to_string_lossy().to_string(),
+        session_id,
+        project_path,
+        message_count: transcript.messages.len(),
+        claude_md_files: bundle
+            .claude_md
+            .iter()
+            .map(|s| s.relative_path.clone())
+            .collect(),
+        env_profile: bundle.env_profile.map(|p| p.name),
+        removed_vars: bundle.removed_vars,
+        checkpoint_count: bundle.checkpoints.len(),
+    })
+}
+
+/// Recreate a session from a handoff file. The project must already be at
+/// `project_path`, or at the exported path when none is given.
+#[tauri::command]
+pub async fn import_session_handoff(
+    app: AppHandle,
+    file: String,
+    project_path: Option<String>,
+) -> Result<HandoffImportResult, String> {
+    let content = fs::read_to_string(&file).map_err(|e| format!("Failed to read file: {}", e))?;
+    let bundle: HandoffBundle =
+        serde_json::from_str(&content).map_err(|e| format!("Not a session handoff: {}", e))?;
+    if bundle.format != HANDOFF_FORMAT {
+        return Err("Not a session handoff".to_string());
+    }
+    if bundle.version > HANDOFF_VERSION {
+        return Err(format!(
+            "Handoff version {} is newer than this app supports",
+            bundle.version
+        ));
+    }
+
+    let project_path = project_path
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or_else(|| bundle.project_path.clone());
+    if !Path::new(&project_path).is_dir() {
+        return Err(format!(
+            "Project directory not found: {}; check out the repository there or pass its path",
+            project_path
+        ));
+    }
+    info!(
+        "Importing handoff of session {} into {}",
+        bundle.session_id, project_path
+    );
+
+    let imported = import_claude_jsonl(&bundle.transcript, Some(project_path.clone()))?;
+    let (restored_claude_md, differing_claude_md) =
+        restore_claude_md(&project_path, &bundle.claude_md)?;
+    let env_profile = match bundle.env_profile {
+        Some(profile) => {
+            let name = profile.name.clone();
+            env_profiles::add_profile(&app, &project_path, profile)?.then_some(name)
+        }
+        None => None,
+    };
+    let same_commit = bundle.git.as_ref().and_then(|git| {
+        git_current_commit(&project_path)
+            .ok()
+            .map(|head| head == git.commit)
+    });
+
+    Ok(HandoffImportResult {
+        session_id: imported.session_id,
+        project_id: imported.project_id,
+        project_path,
+        message_count: imported.message_count,
+        restored_claude_md,
+        differing_claude_md,
+        env_profile,
+        removed_vars: bundle.removed_vars,
+        exported_branch: bundle.git.as_ref().and_then(|git| git.branch.clone()),
+        exported_commit: bundle.git.map(|git| git.commit),
+        same_commit,
+        checkpoints: bundle.checkpoints,
+    })
+}
@@ -0,0 +1,359 @@
+use log::{info, warn};
+/// Import of conversation logs from elsewhere
+///
+/// Accepts Claude Code JSONL copied from another machine and OpenAI-style JSON
+/// exports (a `messages` array, or ChatGPT's `conversations.json` with its message
+/// `mapping`). Everything is normalized into Claude Code's JSONL layout under
+/// `~/.claude/projects/<encoded path>/`, where session listing, usage analytics and
+/// `--resume` already look for transcripts.
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use super::claude::{encode_project_path, get_claude_dir};
+
+/// A session written by `import_session`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedSession {
+    pub session_id: String,
+    pub project_id: String,
+    pub project_path: String,
+    pub title: Option<String>,
+    /// `claude-jsonl`, `openai-messages` or `chatgpt-export`
+    pub source_format: String,
+    pub message_count: usize,
+}
+
+/// A message from a foreign export, before normalization
+struct ForeignMessage {
+    role: String,
+    text: String,
+    timestamp: Option<String>,
+    model: Option<String>,
+}
+
+struct ForeignConversation {
+    title: Option<String>,
+    messages: Vec<ForeignMessage>,
+}
+
+fn unix_to_rfc3339(value: Option<&serde_json::Value>) -> Option<String> {
+    let secs = value?.as_f64()?;
+    chrono::DateTime::from_timestamp_millis((secs * 1000.0) as i64).map(|dt| dt.to_rfc3339())
+}
+
+/// Text of an OpenAI message content: a string or an array of parts
+fn content_text(content: Option<&serde_json::Value>) -> String {
+    match content {
+        Some(serde_json::Value::String(text)) => text.clone(),
+        Some(serde_json::Value::Array(parts)) => parts
+            .iter()
+            .filter_map(|part| part.as_str().or_else(|| part.get("text")?.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+fn parse_openai_messages(messages: &[serde_json::Value]) -> Vec<ForeignMessage> {
+    messages
+        .iter()
+        .map(|message| ForeignMessage {
+            role: message
+                .get("role")
+                .and_then(|r| r.as_str())
+                .unwrap_or("user")
+                .to_string(),
+            text: content_text(message.get("content")),
+            timestamp: unix_to_rfc3339(message.get("created_at").or(message.get("timestamp"))),
+            model: message
+                .get("model")
+                .and_then(|m| m.as_str())
+                .map(String::from),
+        })
+        .collect()
+}
+
+/// Follow a ChatGPT conversation from its current node back to the root
+fn parse_chatgpt_conversation(conversation: &serde_json::Value) -> Option<ForeignConversation> {
+    let mapping = conversation.get("mapping")?.as_object()?;
+    let mut node_id = conversation
+        .get("current_node")
+        .and_then(|n| n.as_str())
+        .map(String::from);
+
+    let mut messages = Vec::new();
+    // A malformed export can link parents in a cycle
+    let mut visited = HashSet::new();
+    while let Some(id) = node_id {
+        if !visited.insert(id.clone()) {
+            warn!("Parent cycle at node {} in ChatGPT export", id);
+            break;
+        }
+        let node = match mapping.get(&id) {
+            Some(node) => node,
+            None => break,
+        };
+        if let Some(message) = node.get("message").filter(|m| !m.is_null()) {
+            let content = message.get("content");
+            messages.push(ForeignMessage {
+                role: message
+                    .pointer("/author/role")
+                    .and_then(|r| r.as_str())
+                    .unwrap_or("user")
+                    .to_string(),
+                text: content_text(content.and_then(|c| c.get("parts"))),
+                timestamp: unix_to_rfc3339(message.get("create_time")),
+                model: message
+                    .pointer("/metadata/model_slug")
+                    .and_then(|m| m.as_str())
+                    .map(String::from),
+            });
+        }
+        node_id = node
+            .get("parent")
+            .and_then(|p| p.as_str())
+            .map(String::from);
+    }
+    messages.reverse();
+
+    Some(ForeignConversation {
+        title: conversation
+            .get("title")
+            .and_then(|t| t.as_str())
+            .map(String::from),
+        messages,
+    })
+}
+
+/// Recognize an OpenAI-style export; returns the format name and its conversations
+fn parse_foreign_json(
+    value: &serde_json::Value,
+) -> Option<(&'static str, Vec<ForeignConversation>)> {
+    if let Some(conversations) = value.as_array() {
+        // conversations.json holds many conversations; a bare array is a message list
+        if conversations.iter().all(|c| c.get("mapping").is_some()) && !conversations.is_empty() {
+            let parsed = conversations
+                .iter()
+                .filter_map(parse_chatgpt_conversation)
+                .collect();
+            return Some(("chatgpt-export", parsed));
+        }
+        if conversations.iter().all(|m| m.get("role").is_some()) {
+            let messages = parse_openai_messages(conversations);
+            return Some((
+                "openai-messages",
+                vec![ForeignConversation {
+                    title: None,
+                    messages,
+                }],
+            ));
+        }
+        return None;
+    }
+
+    if value.get("mapping").is_some() {
+        return parse_chatgpt_conversation(value).map(|c| ("chatgpt-export", vec![c]));
+    }
+    let messages = value.get("messages")?.as_array()?;
+    Some((
+        "openai-messages",
+        vec![ForeignConversation {
+            title: value
+                .get("title")
+                .and_then(|t| t.as_str())
+                .map(String::from),
+            messages: parse_openai_messages(messages),
+        }],
+    ))
+}
+
+fn project_dir(project_path: &str) -> Result<(String, PathBuf), String> {
+    let project_id = encode_project_path(project_path);
+    let dir = get_claude_dir()
+        .map_err(|e| e.to_string())?
+        .join("projects")
+        .join(&project_id);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create project directory: {}", e))?;
+    Ok((project_id, dir))
+}
+
+/// Write JSONL lines as a session file, never overwriting an existing session
+pub(super) fn write_session(
+    dir: &Path,
+    session_id: &str,
+    lines: &[serde_json::Value],
+) -> Result<(), String> {
+    let path = dir.join(format!("{}.jsonl", session_id));
+    if path.exists() {
+        return Err(format!("Session {} already exists", session_id));
+    }
+    let mut content = String::new();
+    for line in lines {
+        content.push_str(&line.to_string());
+        content.push('\n');
+    }
+    fs::write(&path, content).map_err(|e| format!("Failed to write session: {}", e))
+}
+
+/// Re-home a Claude Code transcript: point `cwd` at the local project and pick a
+/// fresh session id if the original is already taken here
+pub(super) fn import_claude_jsonl(
+    content: &str,
+    project_path: Option<String>,
+) -> Result<ImportedSession, String> {
+    let mut lines: Vec<serde_json::Value> = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Invalid JSONL: {}", e))?;
+
+    let original_cwd = lines
+        .iter()
+        .find_map(|l| l.get("cwd").and_then(|c| c.as_str()))
+        .map(String::from);
+    let project_path = project_path
+        .or(original_cwd)
+        .ok_or("Cannot tell which project the session belongs to; pass a project path")?;
+    let (project_id, dir) = project_dir(&project_path)?;
+
+    let original_id = lines
+        .iter()
+        .find_map(|l| l.get("sessionId").and_then(|s| s.as_str()))
+        .map(String::from);
+    let session_id = match original_id {
+        Some(id) if !dir.join(format!("{}.jsonl", id)).exists() => id,
+        _ => Uuid::new_v4().to_string(),
+    };
+
+    for line in lines.iter_mut().filter_map(|l| l.as_object_mut()) {
+        if line.contains_key("cwd") {
+            line.insert("cwd".to_string(), project_path.clone().into());
+        }
+        if line.contains_key("sessionId") {
+            line.insert("sessionId".to_string(), session_id.clone().into());
+        }
+    }
+    write_session(&dir, &session_id, &lines)?;
+
+    let message_count = lines
+        .iter()
+        .filter(|l| {
+            matches!(
+                l.get("type").and_then(|t| t.as_str()),
+                Some("user" | "assistant")
+            )
+        })
+        .count();
+    Ok(ImportedSession {
+        session_id,
+        project_id,
+        project_path,
+        title: None,
+        source_format: "claude-jsonl".to_string(),
+        message_count,
+    })
+}
+
+/// Convert a foreign conversation into Claude Code JSONL lines. System and tool
+/// messages have no equivalent there and are dropped.
+fn normalize_conversation(
+    conversation: &ForeignConversation,
+    session_id: &str,
+    project_path: &str,
+) -> Vec<serde_json::Value> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut parent: Option<String> = None;
+    let mut lines = Vec::new();
+
+    for (index, message) in conversation.messages.iter().enumerate() {
+        if !matches!(message.role.as_str(), "user" | "assistant") || message.text.trim().is_empty()
+        {
+            continue;
+        }
+        let uuid = Uuid::new_v4().to_string();
+        let body = if message.role == "user" {
+            serde_json::json!({ "role": "user", "content": message.text })
+        } else {
+            serde_json::json!({
+                "id": format!("msg_imported_{}_{}", session_id, index),
+                "type": "message",
+                "role": "assistant",
+                "model": message.model.as_deref().unwrap_or("imported"),
+                "content": [{ "type": "text", "text": message.text }],
+            })
+        };
+        lines.push(serde_json::json!({
+            "type": message.role,
+            "uuid": uuid,
+            "parentUuid": parent,
+            "sessionId": session_id,
+            "cwd": project_path,
+            "timestamp": message.timestamp.as_deref().unwrap_or(&now),
+            "isSidechain": false,
+            "userType": "external",
+            "message": body,
+        }));
+        parent = Some(uuid);
+    }
+    lines
+}
+
+// ============ Tauri Commands ============
+
+/// Import a conversation log into the transcript index. Claude Code JSONL keeps
+/// its project unless `project_path` is given; OpenAI-style exports need one.
+/// Exports holding several conversations produce one session each.
+#[tauri::command]
+pub async fn import_session(
+    file: String,
+    project_path: Option<String>,
+) -> Result<Vec<ImportedSession>, String> {
+    info!("Importing conversation log from {}", file);
+    let content = fs::read_to_string(&file).map_err(|e| format!("Failed to read file: {}", e))?;
+    let project_path = project_path.filter(|p| !p.trim().is_empty());
+
+    // A whole-file JSON document is an OpenAI-style export, unless it is a
+    // one-line Claude transcript; anything else is treated as JSONL
+    let (format, conversations) = match serde_json::from_str::<serde_json::Value>(&content) {
+        Ok(value) if value.get("sessionId").is_none() => {
+            parse_foreign_json(&value).ok_or("Unrecognized conversation export format")?
+        }
+        _ => return import_claude_jsonl(&content, project_path).map(|s| vec![s]),
+    };
+
+    let project_path =
+        project_path.ok_or("A project path is required to import this conversation")?;
+    let (project_id, dir) = project_dir(&project_path)?;
+
+    let mut imported = Vec::new();
+    for conversation in &conversations {
+        let session_id = Uuid::new_v4().to_string();
+        let lines = normalize_conversation(conversation, &session_id, &project_path);
+        if lines.is_empty() {
+            continue;
+        }
+        write_session(&dir, &session_id, &lines)?;
+        imported.push(ImportedSession {
+            session_id,
+            project_id: project_id.clone(),
+            project_path: project_path.clone(),
+            title: conversation.title.clone(),
+            source_format: format.to_string(),
+            message_count: lines.len(),
+        });
+    }
+
+    if imported.is_empty() {
+        return Err("No user or assistant messages found to import".to_string());
+    }
+    info!(
+        "Imported {} session(s) into {}",
+        imported.len(),
+        project_path
+    );
+    Ok(imported)
+}
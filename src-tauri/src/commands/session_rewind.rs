@@ -0,0 +1,222 @@
+use log::info;
+/// Rewinding a conversation to an earlier turn
+///
+/// `rewind_session` copies a transcript up to the turn containing a chosen message
+/// into a new session, which is listed and resumed like any other; the original is
+/// left untouched. The cut is always made before a prompt of the user, so no tool
+/// call loses its result, and the removed prompt is returned for editing and
+/// sending again.
+///
+/// Optionally the project files are put back to how they were when that turn
+/// started, using the newest checkpoint taken since the turn before it. The state
+/// being replaced is saved as a checkpoint first, so this can be undone.
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tauri::AppHandle;
+use uuid::Uuid;
+
+use super::checkpoints::{list_git_checkpoints, restore_git_checkpoint, Checkpoint};
+use super::session_export::find_session_file;
+use super::session_import::write_session;
+use super::session_state::{self, SessionPhase};
+
+/// Result of `rewind_session`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RewoundSession {
+    /// The new session holding the shortened transcript
+    pub session_id: String,
+    pub project_id: String,
+    pub project_path: Option<String>,
+    pub source_session_id: String,
+    /// Transcript lines copied over
+    pub kept_lines: usize,
+    /// The prompt of the first removed turn
+    pub prompt: Option<String>,
+    /// Checkpoint the project files were restored to
+    pub restored_checkpoint: Option<Checkpoint>,
+    /// Checkpoint of the files as they were before the restore
+    pub undo_checkpoint: Option<Checkpoint>,
+    /// Why files were not restored although asked to
+    pub checkpoint_note: Option<String>,
+}
+
+/// Whether a transcript line is a prompt typed by the user, as opposed to tool
+/// results, sub-agent traffic and injected context, which are also `user` lines
+fn is_prompt(line: &serde_json::Value) -> bool {
+    if line["type"] != "user"
+        || line["isSidechain"].as_bool() == Some(true)
+        || line["isMeta"].as_bool() == Some(true)
+    {
+        return false;
+    }
+    match &line["message"]["content"] {
+        serde_json::Value::String(_) => true,
+        serde_json::Value::Array(parts) => parts.iter().all(|part| part["type"] != "tool_result"),
+        _ => false,
+    }
+}
+
+fn prompt_text(line: &serde_json::Value) -> Option<String> {
+    match &line["message"]["content"] {
+        serde_json::Value::String(text) => Some(text.clone()),
+        serde_json::Value::Array(parts) => {
+            let text = parts
+                .iter()
+                .filter_map(|part| part["text"].as_str())
+                .collect::<Vec<_>>()
+                .join("\n");
+            (!text.is_empty()).then_some(text)
+        }
+        _ => None,
+    }
+}
+
+fn timestamp_secs(line: &serde_json::Value) -> Option<i64> {
+    let timestamp = line["timestamp"].as_str()?;
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|dt| dt.timestamp())
+}
+
+/// Newest checkpoint taken after the previous prompt and no later than the cut one
+fn matching_checkpoint(
+    project_path: &str,
+    after: Option<i64>,
+    until: i64,
+) -> Result<Option<Checkpoint>, String> {
+    Ok(list_git_checkpoints(project_path)?
+        .into_iter()
+        .find(|c| c.created_at <= until && after.is_none_or(|after| c.created_at > after)))
+}
+
+fn rewind(
+    app: &AppHandle,
+    session_id: &str,
+    message_index: usize,
+    project_id: Option<&str>,
+    restore_files: bool,
+) -> Result<RewoundSession, String> {
+    let path = find_session_file(session_id, project_id)?;
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read session: {}", e))?;
+    // Indexed like `load_session_history`, which skips lines that don't parse
+    let lines: Vec<serde_json::Value> = content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    if message_index >= lines.len() {
+        return Err(format!(
+            "Message {} is out of range; the session has {} messages",
+            message_index,
+            lines.len()
+        ));
+    }
+
+    let cut = (0..=message_index)
+        .rev()
+        .find(|&i| is_prompt(&lines[i]))
+        .ok_or_else(|| format!("No prompt at or before message {}", message_index))?;
+    let previous = (0..cut).rev().find(|&i| is_prompt(&lines[i]));
+    if previous.is_none() {
+        return Err(
+            "Nothing would be left before the first prompt; start a new session instead"
+                .to_string(),
+        );
+    }
+
+    let project_path = lines
+        .iter()
+        .find_map(|line| line["cwd"].as_str())
+        .map(str::to_string);
+    let new_session_id = Uuid::new_v4().to_string();
+    let mut kept: Vec<serde_json::Value> = lines[..cut].to_vec();
+    for line in kept.iter_mut().filter_map(|line| line.as_object_mut()) {
+        if line.contains_key("sessionId") {
+            line.insert("sessionId".to_string(), new_session_id.clone().into());
+        }
+    }
+
+    let mut rewound = RewoundSession {
+        session_id: new_session_id.clone(),
+        project_id: path
+            .parent()
+            .and_then(|dir| dir.file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        project_path: project_path.clone(),
+        source_session_id: session_id.to_string(),
+        kept_lines: kept.len(),
+        prompt: prompt_text(&lines[cut]),
+        restored_checkpoint: None,
+        undo_checkpoint: None,
+        checkpoint_note: None,
+    };
+
+    if restore_files {
+        // Files are restored before the copy is written, so a failure leaves nothing behind
+        session_state::ensure_not_live(app, session_id, "rewind the files of")
+            .map_err(|e| e.to_string())?;
+        let turn_start = timestamp_secs(&lines[cut]);
+        let previous_start = previous.and_then(|i| timestamp_secs(&lines[i]));
+        let checkpoint = match (&project_path, turn_start) {
+            (Some(project_path), Some(turn_start)) => {
+                matching_checkpoint(project_path, previous_start, turn_start)?
+            }
+            _ => None,
+        };
+        match (&project_path, checkpoint) {
+            (Some(project_path), Some(checkpoint)) => {
+                rewound.undo_checkpoint =
+                    Some(restore_git_checkpoint(project_path, &checkpoint.id)?);
+                rewound.restored_checkpoint = Some(checkpoint);
+            }
+            _ => {
+                rewound.checkpoint_note =
+                    Some("No checkpoint was taken at the start of that turn".to_string())
+            }
+        }
+    }
+
+    let dir = path
+        .parent()
+        .ok_or_else(|| format!("Session file has no directory: {}", path.display()))?;
+    write_session(dir, &new_session_id, &kept)?;
+    session_state::observe(
+        app,
+        &new_session_id,
+        SessionPhase::Idle,
+        project_path.as_deref(),
+    );
+
+    info!(
+        "Rewound session {} to message {} as {} ({} lines kept)",
+        session_id, cut, new_session_id, rewound.kept_lines
+    );
+    Ok(rewound)
+}
+
+// ============ Tauri Commands ============
+
+/// Copy a session up to the turn containing `message_index` (an index into
+/// `load_session_history`) into a new session, optionally restoring the project
+/// files to the checkpoint taken when that turn started
+#[tauri::command]
+pub async fn rewind_session(
+    app: AppHandle,
+    session_id: String,
+    message_index: usize,
+    project_id: Option<String>,
+    restore_files: Option<bool>,
+) -> Result<RewoundSession, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        rewind(
+            &app,
+            &session_id,
+            message_index,
+            project_id.as_deref(),
+            restore_files.unwrap_or(false),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
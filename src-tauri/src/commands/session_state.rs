@@ -0,0 +1,321 @@
+use log::{debug, info, warn};
+/// Session lifecycle state machine
+///
+/// Every session the workbench runs has a phase: `starting` while its process is
+/// launched, `streaming` once Claude reports the session, `paused` while
+/// suspended, `idle` between turns, and `ended` once its OnSessionEnd hooks ran.
+/// Transitions are checked against `SessionPhase::can_become`, persisted in
+/// `session_states` and announced as `session-state-changed`. Other modules use
+/// the phase to refuse operations that would race a running session, such as
+/// rewinding it or running its end hooks twice.
+///
+/// Phases are recorded as they are observed; a session the workbench never ran
+/// has no phase. After a restart no process survives, so sessions left in a live
+/// phase come back as `idle`.
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use super::storage::AgentDb;
+use crate::error::{WorkbenchError, WorkbenchResult};
+use crate::events::{self, AppEvent};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionPhase {
+    Starting,
+    Streaming,
+    Paused,
+    Idle,
+    Ended,
+}
+
+impl SessionPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Starting => "starting",
+            Self::Streaming => "streaming",
+            Self::Paused => "paused",
+            Self::Idle => "idle",
+            Self::Ended => "ended",
+        }
+    }
+
+    fn parse(phase: &str) -> Option<Self> {
+        match phase {
+            "starting" => Some(Self::Starting),
+            "streaming" => Some(Self::Streaming),
+            "paused" => Some(Self::Paused),
+            "idle" => Some(Self::Idle),
+            "ended" => Some(Self::Ended),
+            _ => None,
+        }
+    }
+
+    /// Whether the session's process is running
+    pub fn is_live(&self) -> bool {
+        matches!(self, Self::Starting | Self::Streaming | Self::Paused)
+    }
+
+    /// Whether a session in this phase (None if unknown) may move to `to`
+    pub fn can_become(from: Option<Self>, to: Self) -> bool {
+        use SessionPhase::*;
+        match (from, to) {
+            (None, _) => true,
+            // Streaming and idle sessions can be resumed, ended ones reopened
+            (Some(Idle | Ended), Starting) => true,
+            (Some(Starting | Idle | Paused | Ended), Streaming) => true,
+            (Some(Streaming), Paused) => true,
+            (Some(Starting | Streaming | Paused), Idle) => true,
+            (Some(from), Ended) => from != Ended,
+            // Repeated observations of the same phase are harmless
+            (Some(from), to) => from == to && matches!(to, Streaming | Paused | Idle),
+        }
+    }
+}
+
+/// Current phase of one session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub session_id: String,
+    pub phase: SessionPhase,
+    pub project_path: Option<String>,
+    pub updated_at: String,
+}
+
+/// Payload of `session-state-changed`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionStateChange {
+    pub session_id: String,
+    pub from: Option<SessionPhase>,
+    pub to: SessionPhase,
+    pub project_path: Option<String>,
+    pub at: String,
+}
+
+/// Phases of all known sessions, mirrored in `session_states`
+#[derive(Default)]
+pub struct SessionStates(Mutex<HashMap<String, SessionRecord>>);
+
+fn load_records(conn: &Connection) -> WorkbenchResult<Vec<SessionRecord>> {
+    let mut stmt =
+        conn.prepare("SELECT session_id, phase, project_path, updated_at FROM session_states")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, Option<String>>(2)?,
+            row.get::<_, String>(3)?,
+        ))
+    })?;
+    let mut records = Vec::new();
+    for row in rows {
+        let (session_id, phase, project_path, updated_at) = row?;
+        match SessionPhase::parse(&phase) {
+            Some(phase) => records.push(SessionRecord {
+                session_id,
+                phase,
+                project_path,
+                updated_at,
+            }),
+            None => warn!("Unknown phase {} for session {}", phase, session_id),
+        }
+    }
+    Ok(records)
+}
+
+fn save_record(conn: &Connection, record: &SessionRecord) -> WorkbenchResult<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO session_states (session_id, phase, project_path, updated_at)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![
+            record.session_id,
+            record.phase.as_str(),
+            record.project_path,
+            record.updated_at
+        ],
+    )?;
+    Ok(())
+}
+
+/// Persisted phases, with sessions that were live at shutdown moved to idle
+fn restore_records(conn: &Connection) -> WorkbenchResult<Vec<SessionRecord>> {
+    let mut records = load_records(conn)?;
+    for record in records.iter_mut().filter(|r| r.phase.is_live()) {
+        debug!(
+            "Session {} was {} at shutdown, marking idle",
+            record.session_id,
+            record.phase.as_str()
+        );
+        record.phase = SessionPhase::Idle;
+        record.updated_at = chrono::Utc::now().to_rfc3339();
+        save_record(conn, record)?;
+    }
+    Ok(records)
+}
+
+/// Load persisted phases and manage `SessionStates`
+pub fn init_session_states(app: &AppHandle) {
+    let mut records = HashMap::new();
+    if let Some(db) = app.try_state::<AgentDb>() {
+        let restored = match db.0.lock() {
+            Ok(conn) => restore_records(&conn),
+            Err(e) => Err(WorkbenchError::Other(e.to_string())),
+        };
+        match restored {
+            Ok(restored) => {
+                records.extend(restored.into_iter().map(|r| (r.session_id.clone(), r)));
+            }
+            Err(e) => warn!("Failed to load session states: {}", e),
+        }
+    }
+    app.manage(SessionStates(Mutex::new(records)));
+}
+
+/// Current phase of a session, None if it was never recorded
+pub fn phase(app: &AppHandle, session_id: &str) -> Option<SessionPhase> {
+    let states = app.try_state::<SessionStates>()?;
+    let records = states.0.lock().ok()?;
+    records.get(session_id).map(|r| r.phase)
+}
+
+/// Move a session to `to`, persist it and announce the change. Fails without
+/// changing anything if the transition is not allowed.
+pub fn transition(
+    app: &AppHandle,
+    session_id: &str,
+    to: SessionPhase,
+    project_path: Option<&str>,
+) -> WorkbenchResult<()> {
+    if session_id.is_empty() {
+        return Err(WorkbenchError::InvalidInput(
+            "Session state needs a session ID".to_string(),
+        ));
+    }
+    let states = app
+        .try_state::<SessionStates>()
+        .ok_or("Session states not initialized")?;
+    let change = {
+        let mut records = states.0.lock().map_err(|e| e.to_string())?;
+        let from = records.get(session_id).map(|r| r.phase);
+        if !SessionPhase::can_become(from, to) {
+            return Err(WorkbenchError::InvalidState(format!(
+                "Session {} is {}, it cannot become {}",
+                session_id,
+                from.map(|p| p.as_str()).unwrap_or("unknown"),
+                to.as_str()
+            )));
+        }
+        if from == Some(to) {
+            return Ok(());
+        }
+
+        let record = SessionRecord {
+            session_id: session_id.to_string(),
+            phase: to,
+            project_path: project_path
+                .map(str::to_string)
+                .or_else(|| records.get(session_id).and_then(|r| r.project_path.clone())),
+            updated_at: chrono::Utc::now().to_rfc3339(),
+        };
+        if let Some(db) = app.try_state::<AgentDb>() {
+            let conn = db.0.lock().map_err(|e| e.to_string())?;
+            save_record(&conn, &record)?;
+        }
+        let change = SessionStateChange {
+            session_id: session_id.to_string(),
+            from,
+            to,
+            project_path: record.project_path.clone(),
+            at: record.updated_at.clone(),
+        };
+        records.insert(session_id.to_string(), record);
+        change
+    };
+
+    debug!(
+        "Session {}: {} -> {}",
+        session_id,
+        change.from.map(|p| p.as_str()).unwrap_or("unknown"),
+        to.as_str()
+    );
+    events::emit(app, AppEvent::SessionStateChanged(change));
+    Ok(())
+}
+
+/// `transition` for phases observed from the process itself; a refused
+/// transition is only logged
+pub fn observe(app: &AppHandle, session_id: &str, to: SessionPhase, project_path: Option<&str>) {
+    if let Err(e) = transition(app, session_id, to, project_path) {
+        warn!("Unexpected session state change: {}", e);
+    }
+}
+
+/// Fail if the session's process is running; `action` names what was refused
+pub fn ensure_not_live(app: &AppHandle, session_id: &str, action: &str) -> WorkbenchResult<()> {
+    match phase(app, session_id) {
+        Some(phase) if phase.is_live() => Err(WorkbenchError::InvalidState(format!(
+            "Cannot {} session {} while it is {}",
+            action,
+            session_id,
+            phase.as_str()
+        ))),
+        _ => Ok(()),
+    }
+}
+
+// ============ Tauri Commands ============
+
+#[tauri::command]
+pub async fn get_session_state(
+    states: State<'_, SessionStates>,
+    session_id: String,
+) -> Result<Option<SessionRecord>, WorkbenchError> {
+    let records = states.0.lock().map_err(|e| e.to_string())?;
+    Ok(records.get(&session_id).cloned())
+}
+
+/// Sessions with a recorded phase, most recently changed first
+#[tauri::command]
+pub async fn list_session_states(
+    states: State<'_, SessionStates>,
+    project_path: Option<String>,
+) -> Result<Vec<SessionRecord>, WorkbenchError> {
+    let records = states.0.lock().map_err(|e| e.to_string())?;
+    let mut list: Vec<SessionRecord> = records
+        .values()
+        .filter(|r| project_path.is_none() || r.project_path == project_path)
+        .cloned()
+        .collect();
+    list.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    Ok(list)
+}
+
+/// Forget a session's phase, e.g. after its transcript was deleted
+#[tauri::command]
+pub async fn clear_session_state(
+    app: AppHandle,
+    states: State<'_, SessionStates>,
+    session_id: String,
+) -> Result<bool, WorkbenchError> {
+    ensure_not_live(&app, &session_id, "clear the state of")?;
+    let removed = states
+        .0
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&session_id)
+        .is_some();
+    if let Some(db) = app.try_state::<AgentDb>() {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "DELETE FROM session_states WHERE session_id = ?1",
+            [&session_id],
+        )?;
+    }
+    if removed {
+        info!("Cleared state of session {}", session_id);
+    }
+    Ok(removed)
+}
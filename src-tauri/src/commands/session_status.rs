@@ -0,0 +1,230 @@
+use log::{info, warn};
+/// Aggregated status of running sessions
+///
+/// Joins the process registry with the context monitor's token estimates into one
+/// list, polls it, and emits `session-status-changed` (and updates the tray) only
+/// when something changed. Also pauses, resumes and stops sessions; pausing
+/// suspends the session's process group, so it is only available on Unix.
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use super::context_monitor::ContextMonitorState;
+use super::session_state::{self, SessionPhase};
+use crate::events::{self, AppEvent};
+use crate::process::audit::AuditedCommand;
+use crate::process::{ProcessRegistryState, ProcessType};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A running session as shown in the tray and status views
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionStatus {
+    pub session_id: String,
+    pub project_path: String,
+    pub model: String,
+    pub pid: u32,
+    pub started_at: String,
+    /// Context size estimate, None until the session has streamed
+    pub estimated_tokens: Option<usize>,
+    pub context_percent: Option<f64>,
+    pub paused: bool,
+}
+
+/// Last published snapshot and the PIDs of paused sessions
+#[derive(Default)]
+pub struct SessionStatusState {
+    snapshot: Mutex<Vec<SessionStatus>>,
+    paused: Mutex<HashSet<u32>>,
+}
+
+fn collect(app: &AppHandle, paused: &HashSet<u32>) -> Result<Vec<SessionStatus>, String> {
+    let mut running = app
+        .state::<ProcessRegistryState>()
+        .0
+        .get_running_claude_sessions()?;
+    running.sort_by_key(|process| process.started_at);
+    let monitor = app.try_state::<ContextMonitorState>();
+
+    Ok(running
+        .into_iter()
+        .filter_map(|process| {
+            let session_id = match process.process_type {
+                ProcessType::ClaudeSession { session_id } => session_id,
+                _ => return None,
+            };
+            let context = monitor
+                .as_ref()
+                .and_then(|monitor| monitor.status(&session_id));
+            Some(SessionStatus {
+                estimated_tokens: context.as_ref().map(|c| c.estimated_tokens),
+                context_percent: context.as_ref().map(|c| c.percent),
+                paused: paused.contains(&process.pid),
+                session_id,
+                project_path: process.project_path,
+                model: process.model,
+                pid: process.pid,
+                started_at: process.started_at.to_rfc3339(),
+            })
+        })
+        .collect())
+}
+
+/// Recompute the status list; publishes it if it changed
+pub fn refresh(app: &AppHandle) {
+    let state = match app.try_state::<SessionStatusState>() {
+        Some(state) => state,
+        None => return,
+    };
+    let sessions = {
+        let mut paused = match state.paused.lock() {
+            Ok(paused) => paused,
+            Err(_) => return,
+        };
+        let sessions = match collect(app, &paused) {
+            Ok(sessions) => sessions,
+            Err(e) => {
+                warn!("Failed to collect session status: {}", e);
+                return;
+            }
+        };
+        // Forget sessions that ended while paused
+        paused.retain(|pid| sessions.iter().any(|s| s.pid == *pid));
+        sessions
+    };
+
+    {
+        let mut snapshot = match state.snapshot.lock() {
+            Ok(snapshot) => snapshot,
+            Err(_) => return,
+        };
+        if *snapshot == sessions {
+            return;
+        }
+        *snapshot = sessions.clone();
+    }
+    events::emit(app, AppEvent::SessionStatusChanged(sessions.clone()));
+    super::tray::update_sessions(app, sessions);
+}
+
+/// Last published snapshot, without waiting on its lock; for the crash handler
+pub(crate) fn try_snapshot(app: &AppHandle) -> Option<Vec<SessionStatus>> {
+    let state = app.try_state::<SessionStatusState>()?;
+    let snapshot = state.snapshot.try_lock().ok()?;
+    Some(snapshot.clone())
+}
+
+/// Poll the session status in the background
+pub fn start_session_status_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            refresh(&app);
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(unix)]
+fn signal_process_group(pid: u32, signal: &str) -> Result<(), String> {
+    // Sessions lead their own process group, so this reaches tools they spawned too
+    let output = std::process::Command::new("kill")
+        .args([signal, &format!("-{}", pid)])
+        .audited_output("system")
+        .map_err(|e| format!("Failed to run kill: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+#[cfg(not(unix))]
+fn signal_process_group(_pid: u32, _signal: &str) -> Result<(), String> {
+    Err("Pausing sessions is not supported on this platform".to_string())
+}
+
+/// Suspend or continue a running session
+pub(crate) fn set_paused(app: &AppHandle, session_id: &str, pause: bool) -> Result<(), String> {
+    let process = app
+        .state::<ProcessRegistryState>()
+        .0
+        .get_claude_session_by_id(session_id)?
+        .ok_or_else(|| format!("Session {} is not running", session_id))?;
+    let state = app.state::<SessionStatusState>();
+    let mut paused = state.paused.lock().map_err(|e| e.to_string())?;
+    if paused.contains(&process.pid) == pause {
+        return Ok(());
+    }
+
+    let phase = if pause {
+        SessionPhase::Paused
+    } else {
+        SessionPhase::Streaming
+    };
+    let current = session_state::phase(app, session_id);
+    if !SessionPhase::can_become(current, phase) {
+        return Err(format!(
+            "Session {} is {} and cannot be {}",
+            session_id,
+            current.map(|p| p.as_str()).unwrap_or("unknown"),
+            if pause { "paused" } else { "resumed" }
+        ));
+    }
+
+    signal_process_group(process.pid, if pause { "-STOP" } else { "-CONT" })?;
+    if pause {
+        paused.insert(process.pid);
+    } else {
+        paused.remove(&process.pid);
+    }
+    drop(paused);
+    session_state::observe(app, session_id, phase, Some(&process.project_path));
+    info!(
+        "{} session {} (PID {})",
+        if pause { "Paused" } else { "Resumed" },
+        session_id,
+        process.pid
+    );
+    refresh(app);
+    Ok(())
+}
+
+/// Stop a running session; a paused one is continued first so it can exit
+pub(crate) async fn stop(app: &AppHandle, session_id: &str) -> Result<(), String> {
+    if let Err(e) = set_paused(app, session_id, false) {
+        warn!(
+            "Could not continue session {} before stopping it: {}",
+            session_id, e
+        );
+    }
+    super::claude::cancel_claude_execution(app.clone(), Some(session_id.to_string())).await?;
+    refresh(app);
+    Ok(())
+}
+
+// ============ Tauri Commands ============
+
+/// Running sessions with their token usage, oldest first
+#[tauri::command]
+pub async fn get_session_statuses(
+    state: State<'_, SessionStatusState>,
+) -> Result<Vec<SessionStatus>, String> {
+    Ok(state.snapshot.lock().map_err(|e| e.to_string())?.clone())
+}
+
+#[tauri::command]
+pub async fn pause_session(app: AppHandle, session_id: String) -> Result<(), String> {
+    set_paused(&app, &session_id, true)
+}
+
+#[tauri::command]
+pub async fn resume_session(app: AppHandle, session_id: String) -> Result<(), String> {
+    set_paused(&app, &session_id, false)
+}
+
+#[tauri::command]
+pub async fn stop_session(app: AppHandle, session_id: String) -> Result<(), String> {
+    stop(&app, &session_id).await
+}
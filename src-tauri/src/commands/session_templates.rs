@@ -0,0 +1,407 @@
+use log::{info, warn};
+/// Session templates
+///
+/// A template is a recipe for starting a session: the first prompt, the model,
+/// and the project setup the session should run with, namely a thinking preset,
+/// an env profile, hook presets to install and MCP servers to enable.
+///
+/// `create_session_from_template` checks every part before changing anything,
+/// then applies them all; if one fails, the config files already written are
+/// put back as they were, so a project never ends up half set up. The session
+/// is launched only once the whole setup is in place.
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, State};
+
+use super::storage::AgentDb;
+use super::{env_profiles, hook_presets, mcp, models, settings_manager, thinking_presets};
+
+const SELECT_COLUMNS: &str = "id, name, description, prompt, model, thinking_preset, env_profile, \
+     hook_presets, mcp_servers, created_at, updated_at";
+
+/// Scopes searched for a template's MCP servers, most specific first
+const MCP_SCOPES: [&str; 3] = ["local", "project", "user"];
+
+/// A stored template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionTemplate {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    /// First prompt of the session
+    pub prompt: String,
+    /// Falls back to the project's default model, then Sonnet
+    pub model: Option<String>,
+    pub thinking_preset: Option<String>,
+    /// Name of an env profile of the target project, made active
+    pub env_profile: Option<String>,
+    /// Built-in hook presets installed into the project's settings
+    pub hook_presets: Vec<String>,
+    /// Configured MCP servers enabled before launch
+    pub mcp_servers: Vec<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// A template to create, or to update when `id` is set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionTemplateInput {
+    pub id: Option<i64>,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub prompt: String,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub thinking_preset: Option<String>,
+    #[serde(default)]
+    pub env_profile: Option<String>,
+    #[serde(default)]
+    pub hook_presets: Vec<String>,
+    #[serde(default)]
+    pub mcp_servers: Vec<String>,
+}
+
+/// Changes a template makes to a project, worked out before any is made
+struct Setup {
+    /// Disabled servers to enable, with their scope
+    mcp_enable: Vec<(String, String)>,
+    /// Config files the changes write
+    files: Vec<PathBuf>,
+}
+
+/// Contents of config files before a template was applied; None for files
+/// that did not exist
+struct Snapshot(Vec<(PathBuf, Option<Vec<u8>>)>);
+
+impl Snapshot {
+    fn take(files: &[PathBuf]) -> Result<Self, String> {
+        let mut saved = Vec::new();
+        for path in files {
+            let content = if path.exists() {
+                Some(
+                    fs::read(path)
+                        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?,
+                )
+            } else {
+                None
+            };
+            saved.push((path.clone(), content));
+        }
+        Ok(Self(saved))
+    }
+
+    fn restore(self) {
+        for (path, content) in self.0 {
+            let result = match content {
+                Some(content) => fs::write(&path, content),
+                None if path.exists() => fs::remove_file(&path),
+                None => Ok(()),
+            };
+            if let Err(e) = result {
+                warn!("Failed to restore {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+/// Column holding a JSON array
+fn json_column(row: &Row, index: usize) -> rusqlite::Result<Vec<String>> {
+    let value: String = row.get(index)?;
+    serde_json::from_str(&value).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+fn row_to_template(row: &Row) -> rusqlite::Result<SessionTemplate> {
+    Ok(SessionTemplate {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        prompt: row.get(3)?,
+        model: row.get(4)?,
+        thinking_preset: row.get(5)?,
+        env_profile: row.get(6)?,
+        hook_presets: json_column(row, 7)?,
+        mcp_servers: json_column(row, 8)?,
+        created_at: row.get(9)?,
+        updated_at: row.get(10)?,
+    })
+}
+
+fn load_template(conn: &Connection, id: i64) -> Result<SessionTemplate, String> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM session_templates WHERE id = ?1",
+            SELECT_COLUMNS
+        ),
+        [id],
+        row_to_template,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("Session template not found: {}", id))
+}
+
+/// Check the parts that do not depend on the project
+fn check_input(input: &SessionTemplateInput) -> Result<(), String> {
+    if input.name.trim().is_empty() {
+        return Err("Template name cannot be empty".to_string());
+    }
+    if input.prompt.trim().is_empty() {
+        return Err("Template prompt cannot be empty".to_string());
+    }
+    if let Some(model) = &input.model {
+        if !models::is_valid_model(model) {
+            return Err(format!("Unknown model: {}", model));
+        }
+    }
+    if let Some(preset) = &input.thinking_preset {
+        if thinking_presets::find_preset(preset).is_none() {
+            return Err(format!("Thinking preset not found: {}", preset));
+        }
+    }
+    if let Some(preset) = input
+        .hook_presets
+        .iter()
+        .find(|p| !hook_presets::preset_exists(p))
+    {
+        return Err(format!("Unknown hook preset: {}", preset));
+    }
+    Ok(())
+}
+
+fn save(conn: &Connection, input: SessionTemplateInput) -> Result<SessionTemplate, String> {
+    check_input(&input)?;
+    let hooks = serde_json::to_string(&input.hook_presets).map_err(|e| e.to_string())?;
+    let servers = serde_json::to_string(&input.mcp_servers).map_err(|e| e.to_string())?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let id = match input.id {
+        Some(id) => {
+            let updated = conn
+                .execute(
+                    "UPDATE session_templates SET name = ?1, description = ?2, prompt = ?3, model = ?4,
+                     thinking_preset = ?5, env_profile = ?6, hook_presets = ?7, mcp_servers = ?8, updated_at = ?9
+                     WHERE id = ?10",
+                    params![
+                        input.name,
+                        input.description,
+                        input.prompt,
+                        input.model,
+                        input.thinking_preset,
+                        input.env_profile,
+                        hooks,
+                        servers,
+                        now,
+                        id
+                    ],
+                )
+                .map_err(|e| format!("Failed to save session template: {}", e))?;
+            if updated == 0 {
+                return Err(format!("Session template not found: {}", id));
+            }
+            id
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO session_templates (name, description, prompt, model, thinking_preset, env_profile,
+                 hook_presets, mcp_servers, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?9)",
+                params![
+                    input.name,
+                    input.description,
+                    input.prompt,
+                    input.model,
+                    input.thinking_preset,
+                    input.env_profile,
+                    hooks,
+                    servers,
+                    now
+                ],
+            )
+            .map_err(|e| format!("Failed to save session template: {}", e))?;
+            conn.last_insert_rowid()
+        }
+    };
+    load_template(conn, id)
+}
+
+/// Work out what applying a template to a project changes, failing if any part
+/// cannot be applied there
+async fn plan(
+    app: &AppHandle,
+    template: &SessionTemplate,
+    project_path: &str,
+) -> Result<Setup, String> {
+    let mut files = Vec::new();
+
+    if let Some(name) = &template.env_profile {
+        let profiles =
+            env_profiles::list_env_profiles(app.clone(), project_path.to_string()).await?;
+        if !profiles.profiles.iter().any(|p| &p.name == name) {
+            return Err(format!("Profile {} not found", name));
+        }
+        files.push(env_profiles::store_file(app)?);
+    }
+
+    if !template.hook_presets.is_empty() {
+        files.push(settings_manager::settings_path(
+            "project",
+            Some(project_path),
+        )?);
+    }
+
+    let mut mcp_enable = Vec::new();
+    for name in &template.mcp_servers {
+        let mut found = None;
+        for scope in MCP_SCOPES {
+            let entries = mcp::read_scope_entries(scope, Some(project_path))?;
+            if let Some(entry) = entries.into_iter().find(|e| &e.definition.name == name) {
+                found = Some(entry);
+                break;
+            }
+        }
+        let entry = found.ok_or_else(|| format!("MCP server {} not found", name))?;
+        if !entry.enabled {
+            let (config_path, _) = mcp::mcp_config_location(&entry.scope, Some(project_path))?;
+            if !files.contains(&config_path) {
+                files.push(config_path);
+            }
+            mcp_enable.push((name.clone(), entry.scope));
+        }
+    }
+
+    Ok(Setup { mcp_enable, files })
+}
+
+/// Make the changes; the thinking preset goes last, as it is the only one not
+/// held in a file the snapshot covers
+async fn apply(
+    app: &AppHandle,
+    db: &AgentDb,
+    template: &SessionTemplate,
+    project_path: &str,
+    setup: &Setup,
+) -> Result<(), String> {
+    for (name, scope) in &setup.mcp_enable {
+        mcp::mcp_config_set_enabled(
+            name.clone(),
+            scope.clone(),
+            Some(project_path.to_string()),
+            true,
+        )
+        .await?;
+    }
+    for id in &template.hook_presets {
+        hook_presets::install_preset(db, id, "project", Some(project_path))?;
+    }
+    if let Some(name) = &template.env_profile {
+        env_profiles::activate_env_profile(
+            app.clone(),
+            project_path.to_string(),
+            Some(name.clone()),
+        )
+        .await?;
+    }
+    if let Some(preset) = &template.thinking_preset {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        thinking_presets::select(&conn, project_path, Some(preset))?;
+    }
+    Ok(())
+}
+
+// ============ Tauri Commands ============
+
+/// Templates ordered by name
+#[tauri::command]
+pub async fn list_session_templates(
+    db: State<'_, AgentDb>,
+) -> Result<Vec<SessionTemplate>, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM session_templates ORDER BY name COLLATE NOCASE, id",
+            SELECT_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let templates = stmt
+        .query_map([], row_to_template)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(templates)
+}
+
+#[tauri::command]
+pub async fn get_session_template(
+    db: State<'_, AgentDb>,
+    id: i64,
+) -> Result<SessionTemplate, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    load_template(&conn, id)
+}
+
+/// Create a template, or update it when `id` is set
+#[tauri::command]
+pub async fn save_session_template(
+    db: State<'_, AgentDb>,
+    template: SessionTemplateInput,
+) -> Result<SessionTemplate, String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let template = save(&conn, template)?;
+    info!("Saved session template {} '{}'", template.id, template.name);
+    Ok(template)
+}
+
+#[tauri::command]
+pub async fn delete_session_template(db: State<'_, AgentDb>, id: i64) -> Result<(), String> {
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let deleted = conn
+        .execute("DELETE FROM session_templates WHERE id = ?1", [id])
+        .map_err(|e| format!("Failed to delete session template: {}", e))?;
+    if deleted == 0 {
+        return Err(format!("Session template not found: {}", id));
+    }
+    info!("Deleted session template {}", id);
+    Ok(())
+}
+
+/// Set up a project as a template describes, then start a session there with
+/// the template's prompt. Nothing is changed if any part of the setup fails.
+#[tauri::command]
+pub async fn create_session_from_template(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    template_id: i64,
+    project_path: String,
+) -> Result<(), String> {
+    let template = {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        load_template(&conn, template_id)?
+    };
+    let setup = plan(&app, &template, &project_path).await?;
+    let model = template
+        .model
+        .clone()
+        .or_else(|| models::project_default(&app, &project_path))
+        .unwrap_or_else(|| "sonnet".to_string());
+
+    let snapshot = Snapshot::take(&setup.files)?;
+    if let Err(e) = apply(&app, &db, &template, &project_path, &setup).await {
+        warn!(
+            "Session template {} failed to apply, restoring config: {}",
+            template.id, e
+        );
+        snapshot.restore();
+        return Err(format!("Failed to apply session template: {}", e));
+    }
+    info!(
+        "Applied session template {} '{}' to {}",
+        template.id, template.name, project_path
+    );
+
+    super::claude::execute_claude_code(app, project_path, template.prompt, model, None, None).await
+}
@@ -0,0 +1,318 @@
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::claude::get_claude_dir;
+use super::config_io::write_json_atomic;
+
+/// JSON schema for the settings keys Claude Code understands
+static SETTINGS_SCHEMA: Lazy<Value> = Lazy::new(|| {
+    serde_json::from_str(include_str!("settings_schema.json"))
+        .expect("settings_schema.json must be valid JSON")
+});
+
+/// Scopes from lowest to highest precedence
+const SCOPE_ORDER: &[&str] = &["user", "project", "local", "managed"];
+
+/// Arrays that Claude concatenates across scopes instead of replacing
+const MERGED_ARRAYS: &[&str] = &[
+    "permissions.allow",
+    "permissions.ask",
+    "permissions.deny",
+    "permissions.additionalDirectories",
+    "enabledMcpjsonServers",
+    "disabledMcpjsonServers",
+];
+
+/// A settings file at one scope
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsFile {
+    /// "user", "project", "local" or "managed"
+    pub scope: String,
+    pub path: String,
+    pub exists: bool,
+    /// Managed (enterprise) settings cannot be written from the app
+    pub read_only: bool,
+    /// Parsed content ({} when the file does not exist)
+    pub settings: Value,
+    /// Parse error if the file exists but is not valid JSON
+    pub parse_error: Option<String>,
+}
+
+/// A single schema violation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsIssue {
+    /// JSON pointer to the offending value (e.g. "/permissions/allow/0")
+    pub pointer: String,
+    pub message: String,
+}
+
+/// Result of validating settings against the schema
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsValidation {
+    pub valid: bool,
+    pub errors: Vec<SettingsIssue>,
+    /// Keys the schema does not know about (kept, but possibly typos)
+    pub warnings: Vec<SettingsIssue>,
+}
+
+/// Effective settings after resolving the scope hierarchy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergedSettingsPreview {
+    pub effective: Value,
+    /// Dotted key path -> scopes that contributed the effective value
+    pub sources: BTreeMap<String, Vec<String>>,
+    /// The individual layers in precedence order (lowest first)
+    pub layers: Vec<SettingsFile>,
+}
+
+/// Result of saving a settings file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsSaveResult {
+    pub path: String,
+    pub backup_path: Option<String>,
+    pub validation: SettingsValidation,
+}
+
+/// Path of the enterprise managed settings file for this platform
+fn managed_settings_path() -> PathBuf {
+    #[cfg(target_os = "macos")]
+    {
+        PathBuf::from("/Library/Application Support/ClaudeCode/managed-settings.json")
+    }
+    #[cfg(target_os = "windows")]
+    {
+        PathBuf::from(r"C:\ProgramData\ClaudeCode\managed-settings.json")
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        PathBuf::from("/etc/claude-code/managed-settings.json")
+    }
+}
+
+/// Resolve the settings file for a scope
+pub fn settings_path(scope: &str, project_path: Option<&str>) -> Result<PathBuf, String> {
+    match scope {
+        "user" => Ok(get_claude_dir()
+            .map_err(|e| e.to_string())?
+            .join("settings.json")),
+        "project" => {
+            let path = project_path.ok_or("Project path required for project scope")?;
+            Ok(PathBuf::from(path).join(".claude").join("settings.json"))
+        }
+        "local" => {
+            let path = project_path.ok_or("Project path required for local scope")?;
+            Ok(PathBuf::from(path)
+                .join(".claude")
+                .join("settings.local.json"))
+        }
+        "managed" => Ok(managed_settings_path()),
+        _ => Err(format!("Invalid scope: {}", scope)),
+    }
+}
+
+/// Read one settings layer, tolerating missing and malformed files
+pub fn read_settings_file(scope: &str, project_path: Option<&str>) -> Result<SettingsFile, String> {
+    let path = settings_path(scope, project_path)?;
+    let mut file = SettingsFile {
+        scope: scope.to_string(),
+        path: path.to_string_lossy().to_string(),
+        exists: path.is_file(),
+        read_only: scope == "managed",
+        settings: serde_json::json!({}),
+        parse_error: None,
+    };
+
+    if file.exists {
+        let content =
+            fs::read_to_string(&path).map_err(|e| format!("Failed to read settings: {}", e))?;
+        match serde_json::from_str::<Value>(&content) {
+            Ok(settings) => file.settings = settings,
+            Err(e) => file.parse_error = Some(e.to_string()),
+        }
+    }
+
+    Ok(file)
+}
+
+/// Validate settings against the schema and flag unknown top-level keys
+pub fn validate_settings_value(settings: &Value) -> SettingsValidation {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    match jsonschema::validator_for(&SETTINGS_SCHEMA) {
+        Ok(validator) => {
+            for error in validator.iter_errors(settings) {
+                errors.push(SettingsIssue {
+                    pointer: error.instance_path.to_string(),
+                    message: error.to_string(),
+                });
+            }
+        }
+        Err(e) => warn!("Settings schema failed to compile: {}", e),
+    }
+
+    if let (Some(obj), Some(known)) = (
+        settings.as_object(),
+        SETTINGS_SCHEMA
+            .get("properties")
+            .and_then(|p| p.as_object()),
+    ) {
+        for key in obj.keys().filter(|k| !known.contains_key(*k)) {
+            warnings.push(SettingsIssue {
+                pointer: format!("/{}", key),
+                message: format!("Unknown setting '{}'", key),
+            });
+        }
+    }
+
+    SettingsValidation {
+        valid: errors.is_empty(),
+        errors,
+        warnings,
+    }
+}
+
+/// Write settings atomically through the shared config writer, which keeps
+/// timestamped backups of the previous file
+pub fn write_settings_atomically(path: &Path, settings: &Value) -> Result<Option<PathBuf>, String> {
+    write_json_atomic(path, settings)
+}
+
+/// Merge `overlay` into `base`, recording which scope set each key
+fn merge_layer(
+    base: &mut Value,
+    overlay: &Value,
+    scope: &str,
+    prefix: &str,
+    sources: &mut BTreeMap<String, Vec<String>>,
+) {
+    let (base_obj, overlay_obj) = match (base.as_object_mut(), overlay.as_object()) {
+        (Some(base_obj), Some(overlay_obj)) => (base_obj, overlay_obj),
+        _ => return,
+    };
+
+    for (key, value) in overlay_obj {
+        let key_path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+
+        match (base_obj.get_mut(key), value) {
+            (Some(existing @ Value::Object(_)), Value::Object(_)) => {
+                merge_layer(existing, value, scope, &key_path, sources);
+            }
+            (Some(Value::Array(existing)), Value::Array(items))
+                if MERGED_ARRAYS.contains(&key_path.as_str()) =>
+            {
+                for item in items {
+                    if !existing.contains(item) {
+                        existing.push(item.clone());
+                    }
+                }
+                sources.entry(key_path).or_default().push(scope.to_string());
+            }
+            _ => {
+                base_obj.insert(key.clone(), value.clone());
+                if value.is_object() {
+                    // Record the leaves of a freshly inserted object
+                    let mut scratch = serde_json::json!({});
+                    merge_layer(&mut scratch, value, scope, &key_path, sources);
+                } else {
+                    sources.insert(key_path, vec![scope.to_string()]);
+                }
+            }
+        }
+    }
+}
+
+// ============ Tauri Commands ============
+
+/// Read the settings file for a scope
+#[tauri::command]
+pub async fn get_settings_file(
+    scope: String,
+    project_path: Option<String>,
+) -> Result<SettingsFile, String> {
+    info!("Reading {} settings", scope);
+    read_settings_file(&scope, project_path.as_deref())
+}
+
+/// Validate settings without saving them
+#[tauri::command]
+pub async fn validate_settings(settings: Value) -> Result<SettingsValidation, String> {
+    Ok(validate_settings_value(&settings))
+}
+
+/// Replace the settings file for a scope after validating it
+#[tauri::command]
+pub async fn save_settings_file(
+    scope: String,
+    project_path: Option<String>,
+    settings: Value,
+) -> Result<SettingsSaveResult, String> {
+    info!("Saving {} settings", scope);
+
+    if scope == "managed" {
+        return Err("Managed settings are read-only".to_string());
+    }
+
+    let validation = validate_settings_value(&settings);
+    if !validation.valid {
+        let summary: Vec<String> = validation
+            .errors
+            .iter()
+            .map(|e| format!("{}: {}", e.pointer, e.message))
+            .collect();
+        return Err(format!("Invalid settings: {}", summary.join("; ")));
+    }
+
+    let path = settings_path(&scope, project_path.as_deref())?;
+    let backup_path = write_settings_atomically(&path, &settings)?;
+
+    Ok(SettingsSaveResult {
+        path: path.to_string_lossy().to_string(),
+        backup_path: backup_path.map(|p| p.to_string_lossy().to_string()),
+        validation,
+    })
+}
+
+/// Preview the effective settings, optionally with unsaved edits substituted for one scope
+#[tauri::command]
+pub async fn preview_merged_settings(
+    project_path: Option<String>,
+    pending_scope: Option<String>,
+    pending_settings: Option<Value>,
+) -> Result<MergedSettingsPreview, String> {
+    let mut effective = serde_json::json!({});
+    let mut sources = BTreeMap::new();
+    let mut layers = Vec::new();
+
+    for scope in SCOPE_ORDER {
+        if project_path.is_none() && matches!(*scope, "project" | "local") {
+            continue;
+        }
+
+        let mut layer = read_settings_file(scope, project_path.as_deref())?;
+        if pending_scope.as_deref() == Some(*scope) {
+            if let Some(pending) = &pending_settings {
+                layer.settings = pending.clone();
+                layer.parse_error = None;
+            }
+        }
+
+        merge_layer(&mut effective, &layer.settings, scope, "", &mut sources);
+        layers.push(layer);
+    }
+
+    Ok(MergedSettingsPreview {
+        effective,
+        sources,
+        layers,
+    })
+}
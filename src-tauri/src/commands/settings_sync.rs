@@ -0,0 +1,648 @@
+use log::{info, warn};
+/// Settings sync through a git repository
+///
+/// Opt-in. Portable settings are mirrored into a clone of a user-provided repo in
+/// the app data dir, one file per item:
+///
+/// - `hooks.json` – the `hooks` of the user settings, including installed presets
+/// - `env-profiles.json` – the env profile store without secret-looking
+///   variables, which stay on each machine; off by default
+/// - `commands/**` – user slash commands
+/// - `agents/**` – user agent definitions
+///
+/// `sync_now` fetches, then merges per file against the last synced commit: a file
+/// changed only on this machine is committed and pushed, one changed only in the
+/// repo is applied here. A file changed on both sides is a conflict; nothing is
+/// written until the caller retries with `prefer` set to "local" or "remote".
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+use walkdir::WalkDir;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use super::claude::get_claude_dir;
+use super::config_io::write_atomic;
+use super::storage::AgentDb;
+use crate::events::{self, AppEvent};
+use crate::process::audit::AuditedCommand;
+
+/// `app_settings` key holding the JSON config
+const SETTINGS_KEY: &str = "settings_sync";
+
+/// Clone of the sync repo, inside the app data dir
+const REPO_DIR: &str = "settings-sync";
+
+const HOOKS_FILE: &str = "hooks.json";
+const ENV_PROFILES_FILE: &str = "env-profiles.json";
+const COMMANDS_DIR: &str = "commands";
+const AGENTS_DIR: &str = "agents";
+
+/// Which settings are synced
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncItems {
+    pub hooks: bool,
+    pub env_profiles: bool,
+    pub slash_commands: bool,
+    pub agents: bool,
+}
+
+impl Default for SyncItems {
+    fn default() -> Self {
+        Self {
+            hooks: true,
+            // Profiles often hold credentials; opt in explicitly
+            env_profiles: false,
+            slash_commands: true,
+            agents: true,
+        }
+    }
+}
+
+/// Persisted sync settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConfig {
+    pub enabled: bool,
+    /// Git URL or path of the repository
+    pub remote: Option<String>,
+    pub branch: String,
+    #[serde(default)]
+    pub items: SyncItems,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            remote: None,
+            branch: "main".to_string(),
+            items: SyncItems::default(),
+        }
+    }
+}
+
+/// Outcome of the last sync plus what changed locally since
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncStatus {
+    pub enabled: bool,
+    pub remote: Option<String>,
+    /// RFC 3339 time of the last successful sync
+    pub last_sync: Option<String>,
+    /// Commit the settings were last synced at
+    pub last_commit: Option<String>,
+    /// Files changed on this machine since the last sync
+    pub pending_local: Vec<String>,
+    /// Files pulled from the repo by the last sync
+    pub applied: Vec<String>,
+    /// Files pushed by the last sync
+    pub pushed: Vec<String>,
+    /// Files changed on both sides; sync again with `prefer` to resolve
+    pub conflicts: Vec<String>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Default)]
+pub struct SettingsSyncState {
+    config: Mutex<Option<SyncConfig>>,
+    status: Mutex<SyncStatus>,
+    /// Serializes syncs
+    running: tokio::sync::Mutex<()>,
+}
+
+/// File contents keyed by repo-relative path; `/` separated
+type Snapshot = BTreeMap<String, Vec<u8>>;
+
+fn load_config(app: &AppHandle) -> Result<SyncConfig, String> {
+    let db = app
+        .try_state::<AgentDb>()
+        .ok_or("Database not initialized")?;
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT value FROM app_settings WHERE key = ?1",
+            [SETTINGS_KEY],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    match stored {
+        Some(json) => {
+            serde_json::from_str(&json).map_err(|e| format!("Invalid settings sync config: {}", e))
+        }
+        None => Ok(SyncConfig::default()),
+    }
+}
+
+impl SettingsSyncState {
+    fn config(&self, app: &AppHandle) -> SyncConfig {
+        let mut cached = match self.config.lock() {
+            Ok(cached) => cached,
+            Err(_) => return SyncConfig::default(),
+        };
+        cached
+            .get_or_insert_with(|| {
+                load_config(app).unwrap_or_else(|e| {
+                    warn!("{}", e);
+                    SyncConfig::default()
+                })
+            })
+            .clone()
+    }
+}
+
+fn repo_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    crate::paths::app_data_dir(app)
+        .map(|dir| dir.join(REPO_DIR))
+        .map_err(|e| format!("Failed to get app data dir: {}", e))
+}
+
+fn git_output(repo: &Path, args: &[&str]) -> Result<std::process::Output, String> {
+    let mut cmd = Command::new("git");
+    cmd.args(args).current_dir(repo);
+
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+    cmd.audited_output("git")
+        .map_err(|e| format!("Failed to run git {}: {}", args[0], e))
+}
+
+fn git(repo: &Path, args: &[&str]) -> Result<String, String> {
+    let output = git_output(repo, args)?;
+    if !output.status.success() {
+        return Err(format!(
+            "Git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Whether `rev` names a commit in the repo
+fn has_rev(repo: &Path, rev: &str) -> bool {
+    git_output(repo, &["rev-parse", "--verify", "--quiet", rev])
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Clone-equivalent that also works for an empty remote
+fn ensure_repo(repo: &Path, remote: &str) -> Result<(), String> {
+    if !repo.join(".git").is_dir() {
+        fs::create_dir_all(repo).map_err(|e| format!("Failed to create sync dir: {}", e))?;
+        git(repo, &["init", "--quiet"])?;
+        git(repo, &["remote", "add", "origin", remote])?;
+    } else if git(repo, &["remote", "get-url", "origin"])? != remote {
+        git(repo, &["remote", "set-url", "origin", remote])?;
+    }
+    Ok(())
+}
+
+/// All files of a commit
+fn read_tree(repo: &Path, rev: &str) -> Result<Snapshot, String> {
+    let mut snapshot = Snapshot::new();
+    if !has_rev(repo, rev) {
+        return Ok(snapshot);
+    }
+    for path in git(repo, &["ls-tree", "-r", "--name-only", rev])?.lines() {
+        let output = git_output(repo, &["show", &format!("{}:{}", rev, path)])?;
+        if output.status.success() {
+            snapshot.insert(path.to_string(), output.stdout);
+        }
+    }
+    Ok(snapshot)
+}
+
+/// Files under `dir`, keyed by `prefix/relative/path`
+fn read_dir_files(dir: &Path, prefix: &str, snapshot: &mut Snapshot) {
+    for entry in WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+    {
+        let relative = match entry.path().strip_prefix(dir) {
+            Ok(relative) => relative,
+            Err(_) => continue,
+        };
+        let key = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        if let Ok(content) = fs::read(entry.path()) {
+            snapshot.insert(format!("{}/{}", prefix, key), content);
+        }
+    }
+}
+
+/// The synced settings as they are on this machine
+fn local_snapshot(app: &AppHandle, items: &SyncItems) -> Result<Snapshot, String> {
+    let mut snapshot = Snapshot::new();
+    if items.hooks {
+        let file = super::settings_manager::read_settings_file("user", None)?;
+        if let Some(e) = file.parse_error {
+            return Err(format!("User settings are not valid JSON: {}", e));
+        }
+        if let Some(hooks) = file.settings.get("hooks") {
+            let json = serde_json::to_string_pretty(hooks).map_err(|e| e.to_string())?;
+            snapshot.insert(HOOKS_FILE.to_string(), json.into_bytes());
+        }
+    }
+    if items.env_profiles {
+        if let Some(content) = super::env_profiles::synced_store(app)? {
+            snapshot.insert(ENV_PROFILES_FILE.to_string(), content);
+        }
+    }
+    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    if items.slash_commands {
+        read_dir_files(&claude_dir.join(COMMANDS_DIR), COMMANDS_DIR, &mut snapshot);
+    }
+    if items.agents {
+        read_dir_files(&claude_dir.join(AGENTS_DIR), AGENTS_DIR, &mut snapshot);
+    }
+    Ok(snapshot)
+}
+
+/// Path on this machine of a synced directory file, refusing paths that escape it
+fn local_dir_path(root: &Path, relative: &str) -> Result<PathBuf, String> {
+    let mut path = root.to_path_buf();
+    for part in relative.split('/') {
+        if part.is_empty() || part == "." || part == ".." {
+            return Err(format!("Refusing to sync suspicious path: {}", relative));
+        }
+        path.push(part);
+    }
+    Ok(path)
+}
+
+/// Write a file pulled from the repo into this machine's settings; None deletes it
+fn apply_local(app: &AppHandle, path: &str, content: Option<&[u8]>) -> Result<(), String> {
+    if path == HOOKS_FILE {
+        let file = super::settings_manager::read_settings_file("user", None)?;
+        let mut settings = file.settings;
+        if !settings.is_object() {
+            settings = serde_json::json!({});
+        }
+        let obj = settings
+            .as_object_mut()
+            .ok_or("User settings are not a JSON object")?;
+        match content {
+            Some(content) => {
+                let hooks: serde_json::Value = serde_json::from_slice(content)
+                    .map_err(|e| format!("Synced hooks are not valid JSON: {}", e))?;
+                obj.insert("hooks".to_string(), hooks);
+            }
+            None => {
+                obj.remove("hooks");
+            }
+        }
+        super::settings_manager::write_settings_atomically(Path::new(&file.path), &settings)?;
+        return Ok(());
+    }
+
+    if path == ENV_PROFILES_FILE {
+        return super::env_profiles::apply_synced_store(app, content);
+    }
+
+    let claude_dir = get_claude_dir().map_err(|e| e.to_string())?;
+    let target = match path.split_once('/') {
+        Some((dir @ (COMMANDS_DIR | AGENTS_DIR), rest)) => {
+            local_dir_path(&claude_dir.join(dir), rest)?
+        }
+        _ => return Err(format!("Unknown synced file: {}", path)),
+    };
+    match content {
+        Some(content) => write_atomic(&target, content),
+        None if target.exists() => {
+            fs::remove_file(&target).map_err(|e| format!("Failed to remove {:?}: {}", target, e))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Whether a repo path belongs to an item that is synced
+fn item_enabled(items: &SyncItems, path: &str) -> bool {
+    match path.split('/').next() {
+        Some(HOOKS_FILE) => items.hooks,
+        Some(ENV_PROFILES_FILE) => items.env_profiles,
+        Some(COMMANDS_DIR) => items.slash_commands,
+        Some(AGENTS_DIR) => items.agents,
+        _ => false,
+    }
+}
+
+/// Replace the repo's working tree with `tree`, leaving `.git` alone
+fn write_tree(repo: &Path, tree: &Snapshot) -> Result<(), String> {
+    for entry in fs::read_dir(repo).map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        if entry.file_name() == ".git" {
+            continue;
+        }
+        let path = entry.path();
+        let removed = if path.is_dir() {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        };
+        removed.map_err(|e| format!("Failed to clear {:?}: {}", path, e))?;
+    }
+    for (relative, content) in tree {
+        let path = local_dir_path(repo, relative)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::write(&path, content).map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+    }
+    Ok(())
+}
+
+/// One sync round; see the module docs
+fn run_sync(app: &AppHandle, config: &SyncConfig, prefer: Option<&str>) -> SyncStatus {
+    let mut status = SyncStatus {
+        enabled: config.enabled,
+        remote: config.remote.clone(),
+        ..Default::default()
+    };
+    if let Err(e) = sync_into(app, config, prefer, &mut status) {
+        status.last_error = Some(e);
+    }
+    status
+}
+
+fn sync_into(
+    app: &AppHandle,
+    config: &SyncConfig,
+    prefer: Option<&str>,
+    status: &mut SyncStatus,
+) -> Result<(), String> {
+    let remote = config
+        .remote
+        .as_deref()
+        .filter(|r| !r.trim().is_empty())
+        .ok_or("No sync repository configured")?;
+    let repo = repo_dir(app)?;
+    ensure_repo(&repo, remote)?;
+
+    let branch = config.branch.as_str();
+    let remote_ref = format!("refs/remotes/origin/{}", branch);
+    // The branch does not exist yet on a fresh remote
+    let fetch = git_output(&repo, &["fetch", "--quiet", "origin", branch])?;
+    if !fetch.status.success() && has_rev(&repo, &remote_ref) {
+        return Err(format!(
+            "Git fetch failed: {}",
+            String::from_utf8_lossy(&fetch.stderr).trim()
+        ));
+    }
+    if fetch.status.success() {
+        git(&repo, &["update-ref", &remote_ref, "FETCH_HEAD"])?;
+    }
+
+    let base = read_tree(&repo, "HEAD")?;
+    let theirs = read_tree(&repo, &remote_ref)?;
+    let ours = local_snapshot(app, &config.items)?;
+
+    let paths: BTreeSet<&String> = base
+        .keys()
+        .chain(theirs.keys())
+        .chain(ours.keys())
+        .filter(|path| item_enabled(&config.items, path))
+        .collect();
+    let mut merged = Snapshot::new();
+    let mut apply: Vec<(String, Option<Vec<u8>>)> = Vec::new();
+    for path in paths {
+        let (b, t, o) = (base.get(path), theirs.get(path), ours.get(path));
+        let take_theirs = if o == t {
+            false
+        } else if o == b {
+            true
+        } else if t == b {
+            false
+        } else {
+            match prefer {
+                Some("local") => false,
+                Some("remote") => true,
+                _ => {
+                    status.conflicts.push(path.clone());
+                    continue;
+                }
+            }
+        };
+        let winner = if take_theirs { t } else { o };
+        if take_theirs {
+            apply.push((path.clone(), t.cloned()));
+        } else if o != t {
+            status.pushed.push(path.clone());
+        }
+        if let Some(content) = winner {
+            merged.insert(path.clone(), content.clone());
+        }
+    }
+    // Items that are not synced keep what the repo has
+    for (path, content) in theirs
+        .iter()
+        .filter(|(p, _)| !item_enabled(&config.items, p))
+    {
+        merged.insert(path.clone(), content.clone());
+    }
+
+    if !status.conflicts.is_empty() {
+        warn!(
+            "Settings sync stopped on {} conflicts",
+            status.conflicts.len()
+        );
+        status.pushed.clear();
+        return Ok(());
+    }
+
+    // Build the new commit on top of the remote branch
+    let previous_head = git(&repo, &["rev-parse", "--verify", "--quiet", "HEAD"]).ok();
+    if has_rev(&repo, &remote_ref) {
+        git(&repo, &["reset", "--quiet", "--hard", &remote_ref])?;
+    }
+    write_tree(&repo, &merged)?;
+    git(&repo, &["add", "-A"])?;
+    if !git(&repo, &["status", "--porcelain"])?.is_empty() {
+        let host = std::env::var("COMPUTERNAME")
+            .or_else(|_| std::env::var("HOSTNAME"))
+            .unwrap_or_else(|_| "workbench".to_string());
+        let message = format!("Sync settings from {}", host);
+        git(
+            &repo,
+            &[
+                "-c",
+                "user.name=Claude Workbench",
+                "-c",
+                "user.email=workbench@localhost",
+                "commit",
+                "--quiet",
+                "-m",
+                &message,
+            ],
+        )?;
+        if let Err(e) = git(
+            &repo,
+            &["push", "--quiet", "origin", &format!("HEAD:{}", branch)],
+        ) {
+            // Keep HEAD at the last synced state so the next round merges correctly
+            match &previous_head {
+                Some(head) => git(&repo, &["reset", "--quiet", "--hard", head])?,
+                None => git(&repo, &["update-ref", "-d", "HEAD"])?,
+            };
+            return Err(e);
+        }
+    }
+
+    for (path, content) in &apply {
+        apply_local(app, path, content.as_deref())?;
+        status.applied.push(path.clone());
+    }
+    status.last_commit = git(&repo, &["rev-parse", "--verify", "--quiet", "HEAD"]).ok();
+    status.last_sync = Some(chrono::Utc::now().to_rfc3339());
+    info!(
+        "Settings synced: {} pushed, {} applied",
+        status.pushed.len(),
+        status.applied.len()
+    );
+    Ok(())
+}
+
+/// Files that differ from the last synced commit
+fn pending_local(app: &AppHandle, config: &SyncConfig) -> Result<Vec<String>, String> {
+    let repo = repo_dir(app)?;
+    if !repo.join(".git").is_dir() {
+        return Ok(Vec::new());
+    }
+    let base = read_tree(&repo, "HEAD")?;
+    let ours = local_snapshot(app, &config.items)?;
+    let paths: BTreeSet<&String> = base
+        .keys()
+        .chain(ours.keys())
+        .filter(|path| item_enabled(&config.items, path))
+        .collect();
+    Ok(paths
+        .into_iter()
+        .filter(|path| base.get(*path) != ours.get(*path))
+        .cloned()
+        .collect())
+}
+
+// ============ Tauri Commands ============
+
+#[tauri::command]
+pub async fn get_sync_config(
+    app: AppHandle,
+    state: State<'_, SettingsSyncState>,
+) -> Result<SyncConfig, String> {
+    Ok(state.config(&app))
+}
+
+/// Save the sync settings; pointing at another repository starts over with a
+/// fresh clone
+#[tauri::command]
+pub async fn update_sync_config(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    state: State<'_, SettingsSyncState>,
+    config: SyncConfig,
+) -> Result<(), String> {
+    let mut config = config;
+    config.remote = config
+        .remote
+        .map(|r| r.trim().to_string())
+        .filter(|r| !r.is_empty());
+    if config.enabled && config.remote.is_none() {
+        return Err("Set a repository before enabling sync".to_string());
+    }
+    if config.branch.trim().is_empty() {
+        return Err("Branch cannot be empty".to_string());
+    }
+
+    let previous = state.config(&app);
+    let json = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+    {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+            params![SETTINGS_KEY, json],
+        )
+        .map_err(|e| format!("Failed to save settings sync config: {}", e))?;
+    }
+    if previous.remote != config.remote || previous.branch != config.branch {
+        let _guard = state.running.lock().await;
+        let repo = repo_dir(&app)?;
+        if repo.exists() {
+            fs::remove_dir_all(&repo).map_err(|e| format!("Failed to reset sync dir: {}", e))?;
+        }
+        *state.status.lock().map_err(|e| e.to_string())? = SyncStatus::default();
+    }
+    *state.config.lock().map_err(|e| e.to_string())? = Some(config);
+    Ok(())
+}
+
+/// Sync now. `prefer` ("local" or "remote") settles files changed on both sides.
+#[tauri::command]
+pub async fn sync_now(
+    app: AppHandle,
+    state: State<'_, SettingsSyncState>,
+    prefer: Option<String>,
+) -> Result<SyncStatus, String> {
+    let config = state.config(&app);
+    if !config.enabled {
+        return Err("Settings sync is not enabled".to_string());
+    }
+    if let Some(prefer) = prefer.as_deref() {
+        if prefer != "local" && prefer != "remote" {
+            return Err(format!("prefer must be local or remote, not {}", prefer));
+        }
+    }
+
+    let _guard = state.running.lock().await;
+    let sync_app = app.clone();
+    let mut status = tauri::async_runtime::spawn_blocking(move || {
+        run_sync(&sync_app, &config, prefer.as_deref())
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    {
+        let mut current = state.status.lock().map_err(|e| e.to_string())?;
+        // A failed round keeps the time and commit of the last good one
+        if status.last_sync.is_none() {
+            status.last_sync = current.last_sync.clone();
+            status.last_commit = current.last_commit.clone();
+        }
+        *current = status.clone();
+    }
+    events::emit(&app, AppEvent::SettingsSyncFinished(status.clone()));
+    match &status.last_error {
+        Some(e) => Err(e.clone()),
+        None => Ok(status),
+    }
+}
+
+/// Result of the last sync, with the files changed locally since
+#[tauri::command]
+pub async fn sync_status(
+    app: AppHandle,
+    state: State<'_, SettingsSyncState>,
+) -> Result<SyncStatus, String> {
+    let config = state.config(&app);
+    let mut status = state.status.lock().map_err(|e| e.to_string())?.clone();
+    status.enabled = config.enabled;
+    status.remote = config.remote.clone();
+    if config.enabled {
+        let _guard = state.running.lock().await;
+        let pending_app = app.clone();
+        status.pending_local =
+            tauri::async_runtime::spawn_blocking(move || pending_local(&pending_app, &config))
+                .await
+                .map_err(|e| e.to_string())??;
+    }
+    Ok(status)
+}
@@ -5,6 +5,8 @@ use std::process::Command;
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
 
+use crate::process::audit::AuditedCommand;
+
 /// Check if a directory is a Git repository
 pub fn is_git_repo(project_path: &str) -> bool {
     Path::new(project_path).join(".git").exists()
@@ -35,7 +37,7 @@ pub fn ensure_git_repo(project_path: &str) -> Result<(), String> {
         cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
 
         let init_output = cmd
-            .output()
+            .audited_output("git")
             .map_err(|e| format!("Failed to init git: {}", e))?;
 
         if !init_output.status.success() {
@@ -54,14 +56,14 @@ pub fn ensure_git_repo(project_path: &str) -> Result<(), String> {
     config_name.current_dir(project_path);
     #[cfg(target_os = "windows")]
     config_name.creation_flags(0x08000000);
-    let _ = config_name.output();
+    let _ = config_name.audited_output("git");
 
     let mut config_email = Command::new("git");
     config_email.args(["config", "user.email", "ai@claude.workbench"]);
     config_email.current_dir(project_path);
     #[cfg(target_os = "windows")]
     config_email.creation_flags(0x08000000);
-    let _ = config_email.output();
+    let _ = config_email.audited_output("git");
 
     // CRITICAL: Add all existing files first to preserve user code!
     log::info!("Adding all existing files to git staging area...");
@@ -72,7 +74,7 @@ pub fn ensure_git_repo(project_path: &str) -> Result<(), String> {
     add_cmd.creation_flags(0x08000000);
 
     let add_output = add_cmd
-        .output()
+        .audited_output("git")
         .map_err(|e| format!("Failed to add files: {}", e))?;
 
     if !add_output.status.success() {
@@ -96,7 +98,7 @@ pub fn ensure_git_repo(project_path: &str) -> Result<(), String> {
     commit_cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
 
     let commit_output = commit_cmd
-        .output()
+        .audited_output("git")
         .map_err(|e| format!("Failed to create initial commit: {}", e))?;
 
     if !commit_output.status.success() {
@@ -119,7 +121,7 @@ pub fn git_current_commit(project_path: &str) -> Result<String, String> {
     cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
 
     let output = cmd
-        .output()
+        .audited_output("git")
         .map_err(|e| format!("Failed to get current commit: {}", e))?;
 
     if !output.status.success() {
@@ -137,6 +139,30 @@ pub fn git_current_commit(project_path: &str) -> Result<String, String> {
     Ok(commit)
 }
 
+/// Get the checked out branch, or None when HEAD is detached
+pub fn git_current_branch(project_path: &str) -> Result<Option<String>, String> {
+    let mut cmd = Command::new("git");
+    cmd.args(["branch", "--show-current"]);
+    cmd.current_dir(project_path);
+
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+    let output = cmd
+        .audited_output("git")
+        .map_err(|e| format!("Failed to get current branch: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Git branch failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok((!branch.is_empty()).then_some(branch))
+}
+
 /// Commit all changes with a message
 /// Returns: Ok(true) if committed, Ok(false) if no changes, Err if failed
 pub fn git_commit_changes(project_path: &str, message: &str) -> Result<bool, String> {
@@ -149,7 +175,7 @@ pub fn git_commit_changes(project_path: &str, message: &str) -> Result<bool, Str
     status_cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
 
     let status_output = status_cmd
-        .output()
+        .audited_output("git")
         .map_err(|e| format!("Failed to check git status: {}", e))?;
 
     if !status_output.status.success() {
@@ -174,7 +200,7 @@ pub fn git_commit_changes(project_path: &str, message: &str) -> Result<bool, Str
     add_cmd.creation_flags(0x08000000);
 
     let add_output = add_cmd
-        .output()
+        .audited_output("git")
         .map_err(|e| format!("Failed to git add: {}", e))?;
 
     if !add_output.status.success() {
@@ -193,7 +219,7 @@ pub fn git_commit_changes(project_path: &str, message: &str) -> Result<bool, Str
     commit_cmd.creation_flags(0x08000000);
 
     let commit_output = commit_cmd
-        .output()
+        .audited_output("git")
         .map_err(|e| format!("Failed to git commit: {}", e))?;
 
     if !commit_output.status.success() {
@@ -219,7 +245,7 @@ pub fn git_reset_hard(project_path: &str, commit: &str) -> Result<(), String> {
     cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
 
     let output = cmd
-        .output()
+        .audited_output("git")
         .map_err(|e| format!("Failed to reset: {}", e))?;
 
     if !output.status.success() {
@@ -244,7 +270,7 @@ pub fn git_stash_save(project_path: &str, message: &str) -> Result<(), String> {
     status_cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
 
     let status_output = status_cmd
-        .output()
+        .audited_output("git")
         .map_err(|e| format!("Failed to check status: {}", e))?;
 
     if status_output.stdout.is_empty() {
@@ -262,7 +288,7 @@ pub fn git_stash_save(project_path: &str, message: &str) -> Result<(), String> {
     stash_cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
 
     let output = stash_cmd
-        .output()
+        .audited_output("git")
         .map_err(|e| format!("Failed to stash: {}", e))?;
 
     if !output.status.success() {
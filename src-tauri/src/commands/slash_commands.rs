@@ -1,9 +1,11 @@
 use anyhow::{Context, Result};
-use dirs;
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use super::enhanced_hooks::{trigger_hook_event, HookContext};
 
 /// Represents a custom slash command
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +28,10 @@ pub struct SlashCommand {
     pub description: Option<String>,
     /// Allowed tools from frontmatter
     pub allowed_tools: Vec<String>,
+    /// Argument hint from frontmatter (e.g., "[pr-number] [priority]")
+    pub argument_hint: Option<String>,
+    /// Model override from frontmatter
+    pub model: Option<String>,
     /// Whether the command has bash commands (!)
     pub has_bash_commands: bool,
     /// Whether the command has file references (@)
@@ -34,12 +40,58 @@ pub struct SlashCommand {
     pub accepts_arguments: bool,
 }
 
+/// A single slash command name conflict
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlashCommandConflict {
+    /// "invalid", "builtin", "duplicate" or "shadow"
+    pub kind: String,
+    pub message: String,
+    /// File of the conflicting command, if any
+    pub file_path: Option<String>,
+}
+
+/// Result of validating a command name before saving
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlashCommandValidation {
+    /// False if the command cannot be saved under this name
+    pub valid: bool,
+    pub full_command: String,
+    /// Blocking problems (invalid name, built-in or same-scope duplicate)
+    pub errors: Vec<SlashCommandConflict>,
+    /// Non-blocking problems (shadowing a command in the other scope)
+    pub warnings: Vec<SlashCommandConflict>,
+}
+
+/// `allowed-tools` may be a YAML list or a comma-separated string
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum AllowedTools {
+    List(Vec<String>),
+    Csv(String),
+}
+
+impl AllowedTools {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            AllowedTools::List(tools) => tools,
+            AllowedTools::Csv(tools) => tools
+                .split(',')
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect(),
+        }
+    }
+}
+
 /// YAML frontmatter structure
 #[derive(Debug, Deserialize)]
 struct CommandFrontmatter {
     #[serde(rename = "allowed-tools")]
-    allowed_tools: Option<Vec<String>>,
+    allowed_tools: Option<AllowedTools>,
+    #[serde(rename = "argument-hint")]
+    argument_hint: Option<String>,
     description: Option<String>,
+    model: Option<String>,
 }
 
 /// Parse a markdown file with optional YAML frontmatter
@@ -143,10 +195,17 @@ fn load_command_from_file(file_path: &Path, base_path: &Path, scope: &str) -> Re
     let accepts_arguments = body.contains("$ARGUMENTS");
 
     // Extract metadata from frontmatter
-    let (description, allowed_tools) = if let Some(fm) = frontmatter {
-        (fm.description, fm.allowed_tools.unwrap_or_default())
+    let (description, allowed_tools, argument_hint, model) = if let Some(fm) = frontmatter {
+        (
+            fm.description,
+            fm.allowed_tools
+                .map(AllowedTools::into_vec)
+                .unwrap_or_default(),
+            fm.argument_hint,
+            fm.model,
+        )
     } else {
-        (None, Vec::new())
+        (None, Vec::new(), None, None)
     };
 
     Ok(SlashCommand {
@@ -159,6 +218,8 @@ fn load_command_from_file(file_path: &Path, base_path: &Path, scope: &str) -> Re
         content: body,
         description,
         allowed_tools,
+        argument_hint,
+        model,
         has_bash_commands,
         has_file_references,
         accepts_arguments,
@@ -196,6 +257,164 @@ fn find_markdown_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
     Ok(())
 }
 
+/// Resolve the commands directory for a scope
+fn commands_dir(scope: &str, project_path: Option<&str>) -> Result<PathBuf, String> {
+    match scope {
+        "project" => project_path
+            .map(|p| PathBuf::from(p).join(".claude").join("commands"))
+            .ok_or_else(|| "Project path required for project scope".to_string()),
+        "user" => Ok(crate::paths::claude_dir()?.join("commands")),
+        _ => Err("Invalid scope. Must be 'project' or 'user'".to_string()),
+    }
+}
+
+/// The command file an edit started from, canonicalized, with the commands
+/// directory it is in. It is removed after a rename or scope move, so anything
+/// but a `.md` file inside the user or project commands directory is refused.
+/// None when the file no longer exists.
+fn original_command_file(
+    path: &str,
+    project_path: Option<&str>,
+) -> Result<Option<(PathBuf, PathBuf)>, String> {
+    let file = match Path::new(path).canonicalize() {
+        Ok(file) => file,
+        Err(_) => return Ok(None),
+    };
+    if file.is_file() && file.extension().is_some_and(|ext| ext == "md") {
+        for scope in ["project", "user"] {
+            let dir = commands_dir(scope, project_path).and_then(|dir| {
+                dir.canonicalize()
+                    .map_err(|e| format!("Failed to resolve {:?}: {}", dir, e))
+            });
+            if let Ok(dir) = dir {
+                if file.starts_with(&dir) {
+                    return Ok(Some((file, dir)));
+                }
+            }
+        }
+    }
+    Err(format!("Not a command file: {}", path))
+}
+
+/// Load every command of one scope, skipping files that fail to parse
+fn load_scope_commands(scope: &str, project_path: Option<&str>) -> Vec<SlashCommand> {
+    let dir = match commands_dir(scope, project_path) {
+        Ok(dir) => dir,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut md_files = Vec::new();
+    if let Err(e) = find_markdown_files(&dir, &mut md_files) {
+        error!("Failed to find {} command files: {}", scope, e);
+        return Vec::new();
+    }
+
+    md_files
+        .iter()
+        .filter_map(
+            |file_path| match load_command_from_file(file_path, &dir, scope) {
+                Ok(cmd) => Some(cmd),
+                Err(e) => {
+                    error!("Failed to load command from {:?}: {}", file_path, e);
+                    None
+                }
+            },
+        )
+        .collect()
+}
+
+/// Build the invocation string for a name and optional namespace
+fn build_full_command(name: &str, namespace: Option<&str>) -> String {
+    match namespace {
+        Some(ns) if !ns.is_empty() => format!("/{ns}:{name}"),
+        _ => format!("/{name}"),
+    }
+}
+
+/// Command names and namespace segments may only contain letters, digits, '-' and '_'
+fn is_valid_command_segment(segment: &str) -> bool {
+    !segment.is_empty()
+        && segment
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Check a command name against built-ins and existing commands in both scopes
+///
+/// `original_file_path` is the file being edited, so it never conflicts with itself.
+fn check_command_conflicts(
+    scope: &str,
+    name: &str,
+    namespace: Option<&str>,
+    project_path: Option<&str>,
+    original_file_path: Option<&str>,
+) -> SlashCommandValidation {
+    let full_command = build_full_command(name, namespace);
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    let segments_valid = is_valid_command_segment(name)
+        && namespace
+            .map(|ns| ns.split(':').all(is_valid_command_segment))
+            .unwrap_or(true);
+    if !segments_valid {
+        errors.push(SlashCommandConflict {
+            kind: "invalid".to_string(),
+            message: "Command names may only contain letters, digits, '-' and '_'".to_string(),
+            file_path: None,
+        });
+    }
+
+    if create_default_commands()
+        .iter()
+        .any(|cmd| cmd.full_command == full_command)
+    {
+        errors.push(SlashCommandConflict {
+            kind: "builtin".to_string(),
+            message: format!("{} is a built-in command", full_command),
+            file_path: None,
+        });
+    }
+
+    let is_original = |path: &str| {
+        original_file_path
+            .map(|orig| Path::new(orig) == Path::new(path))
+            .unwrap_or(false)
+    };
+
+    for other_scope in ["project", "user"] {
+        for cmd in load_scope_commands(other_scope, project_path) {
+            if cmd.full_command != full_command || is_original(&cmd.file_path) {
+                continue;
+            }
+
+            if other_scope == scope {
+                errors.push(SlashCommandConflict {
+                    kind: "duplicate".to_string(),
+                    message: format!("{} already exists in {} scope", full_command, scope),
+                    file_path: Some(cmd.file_path),
+                });
+            } else {
+                warnings.push(SlashCommandConflict {
+                    kind: "shadow".to_string(),
+                    message: format!(
+                        "{} also exists in {} scope; both will be listed",
+                        full_command, other_scope
+                    ),
+                    file_path: Some(cmd.file_path),
+                });
+            }
+        }
+    }
+
+    SlashCommandValidation {
+        valid: errors.is_empty(),
+        full_command,
+        errors,
+        warnings,
+    }
+}
+
 /// Create default/built-in slash commands
 fn create_default_commands() -> Vec<SlashCommand> {
     vec![
@@ -210,6 +429,8 @@ fn create_default_commands() -> Vec<SlashCommand> {
             content: "Add additional working directories".to_string(),
             description: Some("添加额外的工作目录".to_string()),
             allowed_tools: vec![],
+            argument_hint: None,
+            model: None,
             has_bash_commands: false,
             has_file_references: false,
             accepts_arguments: false,
@@ -225,6 +446,8 @@ fn create_default_commands() -> Vec<SlashCommand> {
             content: "Manage custom AI subagents for specialized tasks".to_string(),
             description: Some("管理专门任务的自定义AI子代理".to_string()),
             allowed_tools: vec![],
+            argument_hint: None,
+            model: None,
             has_bash_commands: false,
             has_file_references: false,
             accepts_arguments: false,
@@ -240,6 +463,8 @@ fn create_default_commands() -> Vec<SlashCommand> {
             content: "Report bugs (sends conversation to Anthropic)".to_string(),
             description: Some("报告错误（发送对话给Anthropic）".to_string()),
             allowed_tools: vec![],
+            argument_hint: None,
+            model: None,
             has_bash_commands: false,
             has_file_references: false,
             accepts_arguments: false,
@@ -255,6 +480,8 @@ fn create_default_commands() -> Vec<SlashCommand> {
             content: "Clear conversation history".to_string(),
             description: Some("清除对话历史".to_string()),
             allowed_tools: vec![],
+            argument_hint: None,
+            model: None,
             has_bash_commands: false,
             has_file_references: false,
             accepts_arguments: false,
@@ -270,6 +497,8 @@ fn create_default_commands() -> Vec<SlashCommand> {
             content: "Compact conversation with optional focus instructions".to_string(),
             description: Some("压缩对话内容以节省令牌".to_string()),
             allowed_tools: vec![],
+            argument_hint: None,
+            model: None,
             has_bash_commands: false,
             has_file_references: false,
             accepts_arguments: false,
@@ -285,6 +514,8 @@ fn create_default_commands() -> Vec<SlashCommand> {
             content: "View/modify configuration".to_string(),
             description: Some("查看/修改配置".to_string()),
             allowed_tools: vec![],
+            argument_hint: None,
+            model: None,
             has_bash_commands: false,
             has_file_references: false,
             accepts_arguments: false,
@@ -300,6 +531,8 @@ fn create_default_commands() -> Vec<SlashCommand> {
             content: "Show token usage statistics".to_string(),
             description: Some("显示令牌使用统计".to_string()),
             allowed_tools: vec![],
+            argument_hint: None,
+            model: None,
             has_bash_commands: false,
             has_file_references: false,
             accepts_arguments: false,
@@ -315,6 +548,8 @@ fn create_default_commands() -> Vec<SlashCommand> {
             content: "Checks the health of your Claude Code installation".to_string(),
             description: Some("检查Claude Code安装的健康状态".to_string()),
             allowed_tools: vec![],
+            argument_hint: None,
+            model: None,
             has_bash_commands: false,
             has_file_references: false,
             accepts_arguments: false,
@@ -330,6 +565,8 @@ fn create_default_commands() -> Vec<SlashCommand> {
             content: "Get usage help".to_string(),
             description: Some("获取使用帮助".to_string()),
             allowed_tools: vec![],
+            argument_hint: None,
+            model: None,
             has_bash_commands: false,
             has_file_references: false,
             accepts_arguments: false,
@@ -345,6 +582,8 @@ fn create_default_commands() -> Vec<SlashCommand> {
             content: "Initialize project with CLAUDE.md guide".to_string(),
             description: Some("使用CLAUDE.md指南初始化项目".to_string()),
             allowed_tools: vec![],
+            argument_hint: None,
+            model: None,
             has_bash_commands: false,
             has_file_references: false,
             accepts_arguments: false,
@@ -360,6 +599,8 @@ fn create_default_commands() -> Vec<SlashCommand> {
             content: "Switch Anthropic accounts".to_string(),
             description: Some("切换Anthropic账户".to_string()),
             allowed_tools: vec![],
+            argument_hint: None,
+            model: None,
             has_bash_commands: false,
             has_file_references: false,
             accepts_arguments: false,
@@ -375,6 +616,8 @@ fn create_default_commands() -> Vec<SlashCommand> {
             content: "Sign out from your Anthropic account".to_string(),
             description: Some("退出Anthropic账户".to_string()),
             allowed_tools: vec![],
+            argument_hint: None,
+            model: None,
             has_bash_commands: false,
             has_file_references: false,
             accepts_arguments: false,
@@ -390,6 +633,8 @@ fn create_default_commands() -> Vec<SlashCommand> {
             content: "Manage MCP server connections and OAuth authentication".to_string(),
             description: Some("管理MCP服务器连接和OAuth认证".to_string()),
             allowed_tools: vec![],
+            argument_hint: None,
+            model: None,
             has_bash_commands: false,
             has_file_references: false,
             accepts_arguments: false,
@@ -405,6 +650,8 @@ fn create_default_commands() -> Vec<SlashCommand> {
             content: "Edit CLAUDE.md memory files".to_string(),
             description: Some("编辑CLAUDE.md记忆文件".to_string()),
             allowed_tools: vec![],
+            argument_hint: None,
+            model: None,
             has_bash_commands: false,
             has_file_references: false,
             accepts_arguments: false,
@@ -420,6 +667,8 @@ fn create_default_commands() -> Vec<SlashCommand> {
             content: "Select or change the AI model".to_string(),
             description: Some("选择或更改AI模型".to_string()),
             allowed_tools: vec![],
+            argument_hint: None,
+            model: None,
             has_bash_commands: false,
             has_file_references: false,
             accepts_arguments: false,
@@ -435,6 +684,8 @@ fn create_default_commands() -> Vec<SlashCommand> {
             content: "View or update permissions".to_string(),
             description: Some("查看或更新权限".to_string()),
             allowed_tools: vec![],
+            argument_hint: None,
+            model: None,
             has_bash_commands: false,
             has_file_references: false,
             accepts_arguments: false,
@@ -450,6 +701,8 @@ fn create_default_commands() -> Vec<SlashCommand> {
             content: "View pull request comments".to_string(),
             description: Some("查看拉取请求评论".to_string()),
             allowed_tools: vec![],
+            argument_hint: None,
+            model: None,
             has_bash_commands: false,
             has_file_references: false,
             accepts_arguments: false,
@@ -465,6 +718,8 @@ fn create_default_commands() -> Vec<SlashCommand> {
             content: "Request code review".to_string(),
             description: Some("请求代码审查".to_string()),
             allowed_tools: vec![],
+            argument_hint: None,
+            model: None,
             has_bash_commands: false,
             has_file_references: false,
             accepts_arguments: false,
@@ -480,6 +735,8 @@ fn create_default_commands() -> Vec<SlashCommand> {
             content: "View account and system statuses".to_string(),
             description: Some("查看账户和系统状态".to_string()),
             allowed_tools: vec![],
+            argument_hint: None,
+            model: None,
             has_bash_commands: false,
             has_file_references: false,
             accepts_arguments: false,
@@ -495,6 +752,8 @@ fn create_default_commands() -> Vec<SlashCommand> {
             content: "Install Shift+Enter key binding for newlines".to_string(),
             description: Some("安装Shift+Enter键绑定用于换行".to_string()),
             allowed_tools: vec![],
+            argument_hint: None,
+            model: None,
             has_bash_commands: false,
             has_file_references: false,
             accepts_arguments: false,
@@ -510,6 +769,8 @@ fn create_default_commands() -> Vec<SlashCommand> {
             content: "Enter vim mode for alternating insert and command modes".to_string(),
             description: Some("进入vim模式，交替使用插入和命令模式".to_string()),
             allowed_tools: vec![],
+            argument_hint: None,
+            model: None,
             has_bash_commands: false,
             has_file_references: false,
             accepts_arguments: false,
@@ -517,6 +778,81 @@ fn create_default_commands() -> Vec<SlashCommand> {
     ]
 }
 
+/// Find the custom command a prompt starts with, plus its arguments.
+/// Project commands shadow user commands of the same name.
+fn match_user_command(prompt: &str, project_path: &str) -> Option<(SlashCommand, String)> {
+    let prompt = prompt.trim_start();
+    if !prompt.starts_with('/') {
+        return None;
+    }
+
+    let (invocation, args) = match prompt.split_once(char::is_whitespace) {
+        Some((invocation, args)) => (invocation, args.trim()),
+        None => (prompt, ""),
+    };
+
+    ["project", "user"]
+        .iter()
+        .flat_map(|scope| load_scope_commands(scope, Some(project_path)))
+        .find(|command| command.full_command == invocation)
+        .map(|command| (command, args.to_string()))
+}
+
+/// Run OnUserCommand hooks when a prompt invokes a custom slash command.
+///
+/// A failing hook blocks the prompt so hooks can validate arguments; errors
+/// loading or running the hooks themselves only log a warning.
+pub async fn run_user_command_hooks(
+    app: &AppHandle,
+    project_path: &str,
+    session_id: &str,
+    prompt: &str,
+) -> Result<(), String> {
+    let (command, args) = match match_user_command(prompt, project_path) {
+        Some(found) => found,
+        None => return Ok(()),
+    };
+    debug!("Prompt runs custom command {}", command.full_command);
+
+    let context = HookContext {
+        event: "OnUserCommand".to_string(),
+        session_id: session_id.to_string(),
+        project_path: project_path.to_string(),
+        data: serde_json::json!({
+            "command": command.full_command,
+            "name": command.name,
+            "namespace": command.namespace,
+            "scope": command.scope,
+            "file_path": command.file_path,
+            "args": args,
+        }),
+    };
+
+    let result =
+        match trigger_hook_event(app.clone(), "OnUserCommand".to_string(), context, None).await {
+            Ok(result) => result,
+            Err(e) => {
+                warn!("OnUserCommand hooks failed: {}", e);
+                return Ok(());
+            }
+        };
+    if result.should_continue {
+        return Ok(());
+    }
+
+    let reason = result
+        .results
+        .iter()
+        .find(|r| !r.success)
+        .and_then(|r| r.error.clone())
+        .unwrap_or_default();
+    Err(format!(
+        "{} was blocked by an OnUserCommand hook: {}",
+        command.full_command,
+        reason.trim()
+    ))
+}
+
 /// Discover all custom slash commands
 #[tauri::command]
 pub async fn slash_commands_list(
@@ -529,54 +865,12 @@ pub async fn slash_commands_list(
     commands.extend(create_default_commands());
 
     // Load project commands if project path is provided
-    if let Some(proj_path) = project_path {
-        let project_commands_dir = PathBuf::from(&proj_path).join(".claude").join("commands");
-        if project_commands_dir.exists() {
-            debug!("Scanning project commands at: {:?}", project_commands_dir);
-
-            let mut md_files = Vec::new();
-            if let Err(e) = find_markdown_files(&project_commands_dir, &mut md_files) {
-                error!("Failed to find project command files: {}", e);
-            } else {
-                for file_path in md_files {
-                    match load_command_from_file(&file_path, &project_commands_dir, "project") {
-                        Ok(cmd) => {
-                            debug!("Loaded project command: {}", cmd.full_command);
-                            commands.push(cmd);
-                        }
-                        Err(e) => {
-                            error!("Failed to load command from {:?}: {}", file_path, e);
-                        }
-                    }
-                }
-            }
-        }
+    if project_path.is_some() {
+        commands.extend(load_scope_commands("project", project_path.as_deref()));
     }
 
     // Load user commands
-    if let Some(home_dir) = dirs::home_dir() {
-        let user_commands_dir = home_dir.join(".claude").join("commands");
-        if user_commands_dir.exists() {
-            debug!("Scanning user commands at: {:?}", user_commands_dir);
-
-            let mut md_files = Vec::new();
-            if let Err(e) = find_markdown_files(&user_commands_dir, &mut md_files) {
-                error!("Failed to find user command files: {}", e);
-            } else {
-                for file_path in md_files {
-                    match load_command_from_file(&file_path, &user_commands_dir, "user") {
-                        Ok(cmd) => {
-                            debug!("Loaded user command: {}", cmd.full_command);
-                            commands.push(cmd);
-                        }
-                        Err(e) => {
-                            error!("Failed to load command from {:?}: {}", file_path, e);
-                        }
-                    }
-                }
-            }
-        }
-    }
+    commands.extend(load_scope_commands("user", None));
 
     info!("Found {} slash commands", commands.len());
     Ok(commands)
@@ -584,7 +878,10 @@ pub async fn slash_commands_list(
 
 /// Get a single slash command by ID
 #[tauri::command]
-pub async fn slash_command_get(command_id: String) -> Result<SlashCommand, String> {
+pub async fn slash_command_get(
+    command_id: String,
+    project_path: Option<String>,
+) -> Result<SlashCommand, String> {
     debug!("Getting slash command: {}", command_id);
 
     // Parse the ID to determine scope and reconstruct file path
@@ -595,7 +892,7 @@ pub async fn slash_command_get(command_id: String) -> Result<SlashCommand, Strin
 
     // The actual implementation would need to reconstruct the path and reload the command
     // For now, we'll list all commands and find the matching one
-    let commands = slash_commands_list(None).await?;
+    let commands = slash_commands_list(project_path).await?;
 
     commands
         .into_iter()
@@ -603,8 +900,34 @@ pub async fn slash_command_get(command_id: String) -> Result<SlashCommand, Strin
         .ok_or_else(|| format!("Command not found: {}", command_id))
 }
 
+/// Check whether a command can be saved under a name without colliding
+#[tauri::command]
+pub async fn slash_command_validate(
+    scope: String,
+    name: String,
+    namespace: Option<String>,
+    project_path: Option<String>,
+    original_file_path: Option<String>,
+) -> Result<SlashCommandValidation, String> {
+    if !["project", "user"].contains(&scope.as_str()) {
+        return Err("Invalid scope. Must be 'project' or 'user'".to_string());
+    }
+
+    Ok(check_command_conflicts(
+        &scope,
+        &name,
+        namespace.as_deref(),
+        project_path.as_deref(),
+        original_file_path.as_deref(),
+    ))
+}
+
 /// Create or update a slash command
+///
+/// When editing, pass `original_file_path` so the command does not collide with
+/// itself; if the name, namespace or scope changed the old file is removed.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn slash_command_save(
     scope: String,
     name: String,
@@ -613,6 +936,9 @@ pub async fn slash_command_save(
     description: Option<String>,
     allowed_tools: Vec<String>,
     project_path: Option<String>,
+    argument_hint: Option<String>,
+    model: Option<String>,
+    original_file_path: Option<String>,
 ) -> Result<SlashCommand, String> {
     info!("Saving slash command: {} in scope: {}", name, scope);
 
@@ -621,56 +947,64 @@ pub async fn slash_command_save(
         return Err("Command name cannot be empty".to_string());
     }
 
-    if !["project", "user"].contains(&scope.as_str()) {
-        return Err("Invalid scope. Must be 'project' or 'user'".to_string());
-    }
-
     // Determine base directory
-    let base_dir = if scope == "project" {
-        if let Some(proj_path) = project_path {
-            PathBuf::from(proj_path).join(".claude").join("commands")
-        } else {
-            return Err("Project path required for project scope".to_string());
-        }
-    } else {
-        dirs::home_dir()
-            .ok_or_else(|| "Could not find home directory".to_string())?
-            .join(".claude")
-            .join("commands")
+    let base_dir = commands_dir(&scope, project_path.as_deref())?;
+    let original = match original_file_path.as_deref() {
+        Some(path) => original_command_file(path, project_path.as_deref())?,
+        None => None,
     };
 
-    // Build file path
-    let mut file_path = base_dir.clone();
-    if let Some(ns) = &namespace {
-        for component in ns.split(':') {
-            file_path = file_path.join(component);
+    let validation = check_command_conflicts(
+        &scope,
+        &name,
+        namespace.as_deref(),
+        project_path.as_deref(),
+        original_file_path.as_deref(),
+    );
+    if let Some(conflict) = validation.errors.first() {
+        // Saving over the same file without original_file_path keeps the old overwrite behaviour
+        let target = build_command_path(&base_dir, &name, namespace.as_deref());
+        let is_self = conflict.kind == "duplicate"
+            && original_file_path.is_none()
+            && conflict.file_path.as_deref().map(Path::new) == Some(target.as_path());
+        if !is_self {
+            return Err(conflict.message.clone());
         }
     }
 
-    // Create directories if needed
-    fs::create_dir_all(&file_path).map_err(|e| format!("Failed to create directories: {}", e))?;
+    // Build file path
+    let file_path = build_command_path(&base_dir, &name, namespace.as_deref());
 
-    // Add filename
-    file_path = file_path.join(format!("{}.md", name));
+    // Create directories if needed
+    if let Some(parent) = file_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directories: {}", e))?;
+    }
 
     // Build content with frontmatter
     let mut full_content = String::new();
 
     // Add frontmatter if we have metadata
-    if description.is_some() || !allowed_tools.is_empty() {
-        full_content.push_str("---\n");
-
-        if let Some(desc) = &description {
-            full_content.push_str(&format!("description: {}\n", desc));
-        }
-
-        if !allowed_tools.is_empty() {
-            full_content.push_str("allowed-tools:\n");
-            for tool in &allowed_tools {
-                full_content.push_str(&format!("  - {}\n", tool));
-            }
-        }
+    let mut frontmatter = serde_yaml::Mapping::new();
+    if let Some(desc) = &description {
+        frontmatter.insert("description".into(), desc.as_str().into());
+    }
+    if let Some(hint) = &argument_hint {
+        frontmatter.insert("argument-hint".into(), hint.as_str().into());
+    }
+    if let Some(model) = &model {
+        frontmatter.insert("model".into(), model.as_str().into());
+    }
+    if !allowed_tools.is_empty() {
+        let tools: Vec<serde_yaml::Value> =
+            allowed_tools.iter().map(|t| t.as_str().into()).collect();
+        frontmatter.insert("allowed-tools".into(), tools.into());
+    }
 
+    if !frontmatter.is_empty() {
+        let yaml = serde_yaml::to_string(&frontmatter)
+            .map_err(|e| format!("Failed to serialize frontmatter: {}", e))?;
+        full_content.push_str("---\n");
+        full_content.push_str(&yaml);
         full_content.push_str("---\n\n");
     }
 
@@ -680,6 +1014,17 @@ pub async fn slash_command_save(
     fs::write(&file_path, &full_content)
         .map_err(|e| format!("Failed to write command file: {}", e))?;
 
+    // Remove the old file after a rename or scope move
+    if let Some((original, original_dir)) = original {
+        if file_path.canonicalize().ok().as_ref() != Some(&original) {
+            fs::remove_file(&original)
+                .map_err(|e| format!("Failed to remove previous command file: {}", e))?;
+            if let Some(parent) = original.parent() {
+                let _ = remove_empty_dirs(parent, &original_dir);
+            }
+        }
+    }
+
     // Load and return the saved command
     load_command_from_file(&file_path, &base_dir, &scope)
         .map_err(|e| format!("Failed to load saved command: {}", e))
@@ -702,7 +1047,7 @@ pub async fn slash_command_delete(
     }
 
     // List all commands (including project commands if applicable)
-    let commands = slash_commands_list(project_path).await?;
+    let commands = slash_commands_list(project_path.clone()).await?;
 
     // Find the command by ID
     let command = commands
@@ -715,16 +1060,30 @@ pub async fn slash_command_delete(
         .map_err(|e| format!("Failed to delete command file: {}", e))?;
 
     // Clean up empty directories
-    if let Some(parent) = Path::new(&command.file_path).parent() {
-        let _ = remove_empty_dirs(parent);
+    if let (Some(parent), Ok(base_dir)) = (
+        Path::new(&command.file_path).parent(),
+        commands_dir(&command.scope, project_path.as_deref()),
+    ) {
+        let _ = remove_empty_dirs(parent, &base_dir);
     }
 
     Ok(format!("Deleted command: {}", command.full_command))
 }
 
-/// Remove empty directories recursively
-fn remove_empty_dirs(dir: &Path) -> Result<()> {
-    if !dir.exists() {
+/// Build the markdown file path for a command inside a commands directory
+fn build_command_path(base_dir: &Path, name: &str, namespace: Option<&str>) -> PathBuf {
+    let mut file_path = base_dir.to_path_buf();
+    if let Some(ns) = namespace {
+        for component in ns.split(':').filter(|c| !c.is_empty()) {
+            file_path = file_path.join(component);
+        }
+    }
+    file_path.join(format!("{}.md", name))
+}
+
+/// Remove empty directories recursively, up to but not including `base_dir`
+fn remove_empty_dirs(dir: &Path, base_dir: &Path) -> Result<()> {
+    if !dir.exists() || dir == base_dir || !dir.starts_with(base_dir) {
         return Ok(());
     }
 
@@ -736,7 +1095,7 @@ fn remove_empty_dirs(dir: &Path) -> Result<()> {
 
         // Try to remove parent if it's also empty
         if let Some(parent) = dir.parent() {
-            let _ = remove_empty_dirs(parent);
+            let _ = remove_empty_dirs(parent, base_dir);
         }
     }
 
@@ -6,39 +6,324 @@ use std::collections::HashMap;
 use std::sync::Mutex;
 use tauri::{AppHandle, Manager, State};
 
+use crate::error::{WorkbenchError, WorkbenchResult};
+
 // Database wrapper for storage operations
 pub struct AgentDb(pub Mutex<Connection>);
 
+/// Schema migrations, applied in order inside a transaction. `PRAGMA user_version`
+/// records how many have run, so append new migrations and never edit one that has
+/// shipped. The first one uses `IF NOT EXISTS` because it adopts databases created
+/// before versioning existed.
+const MIGRATIONS: &[&str] = &[
+    // 1: baseline schema
+    "
+    -- usage_entries table for token usage tracking
+    CREATE TABLE IF NOT EXISTS usage_entries (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_id TEXT NOT NULL,
+        timestamp TEXT NOT NULL,
+        model TEXT NOT NULL,
+        input_tokens INTEGER DEFAULT 0,
+        output_tokens INTEGER DEFAULT 0,
+        cache_creation_tokens INTEGER DEFAULT 0,
+        cache_read_tokens INTEGER DEFAULT 0,
+        total_tokens INTEGER DEFAULT 0,
+        cost REAL DEFAULT 0.0,
+        project_path TEXT,
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    -- project_metadata table for user-managed project registry data
+    CREATE TABLE IF NOT EXISTS project_metadata (
+        project_path TEXT PRIMARY KEY,
+        project_id TEXT NOT NULL,
+        pinned INTEGER NOT NULL DEFAULT 0,
+        nickname TEXT,
+        tags TEXT NOT NULL DEFAULT '[]',
+        updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    -- process_journal table so child processes can be found after a crash
+    CREATE TABLE IF NOT EXISTS process_journal (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        instance_id TEXT NOT NULL,
+        run_id INTEGER NOT NULL,
+        process_type TEXT NOT NULL,
+        pid INTEGER NOT NULL,
+        process_name TEXT,
+        project_path TEXT,
+        started_at TEXT NOT NULL
+    );
+
+    -- app_logs table for the structured log store
+    CREATE TABLE IF NOT EXISTS app_logs (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        timestamp TEXT NOT NULL,
+        level TEXT NOT NULL,
+        module TEXT NOT NULL,
+        session_id TEXT,
+        message TEXT NOT NULL,
+        fields TEXT NOT NULL DEFAULT '{}'
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_app_logs_session ON app_logs(session_id);
+
+    -- app_settings table for key-value app settings
+    CREATE TABLE IF NOT EXISTS app_settings (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+
+    -- hook_approvals table for the project hook approval gate
+    CREATE TABLE IF NOT EXISTS hook_approvals (
+        project_path TEXT NOT NULL,
+        hash TEXT NOT NULL,
+        approved_at TEXT NOT NULL,
+        PRIMARY KEY (project_path, hash)
+    );
+
+    -- credentials table; secrets live in the OS keychain, not here
+    CREATE TABLE IF NOT EXISTS credentials (
+        name TEXT PRIMARY KEY,
+        env_var TEXT,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+    ",
+    // 2: hook run history for project insights
+    "
+    CREATE TABLE IF NOT EXISTS hook_runs (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        timestamp TEXT NOT NULL,
+        project_path TEXT NOT NULL,
+        session_id TEXT NOT NULL,
+        event TEXT NOT NULL,
+        command TEXT NOT NULL,
+        success INTEGER NOT NULL,
+        duration_ms INTEGER NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_hook_runs_project ON hook_runs(project_path, timestamp);
+    ",
+    // 3: background runs queued in the run scheduler
+    "
+    CREATE TABLE IF NOT EXISTS scheduled_runs (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT,
+        project_path TEXT NOT NULL,
+        prompt TEXT NOT NULL,
+        model TEXT NOT NULL,
+        plan_mode INTEGER NOT NULL DEFAULT 0,
+        status TEXT NOT NULL,
+        attempts INTEGER NOT NULL DEFAULT 0,
+        max_retries INTEGER NOT NULL,
+        timeout_secs INTEGER NOT NULL,
+        next_attempt_at TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        started_at TEXT,
+        finished_at TEXT,
+        session_id TEXT,
+        result TEXT,
+        cost_usd REAL,
+        error TEXT
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_scheduled_runs_status ON scheduled_runs(status, next_attempt_at);
+    ",
+    // 4: opt-in telemetry counters
+    "
+    CREATE TABLE IF NOT EXISTS telemetry_events (
+        day TEXT NOT NULL,
+        event TEXT NOT NULL,
+        count INTEGER NOT NULL,
+        sent_count INTEGER NOT NULL DEFAULT 0,
+        PRIMARY KEY (day, event)
+    );
+    ",
+    // 5: session lifecycle states
+    "
+    CREATE TABLE IF NOT EXISTS session_states (
+        session_id TEXT PRIMARY KEY,
+        phase TEXT NOT NULL,
+        project_path TEXT,
+        updated_at TEXT NOT NULL
+    );
+    ",
+    // 6: commands refused by the command policy
+    "
+    CREATE TABLE IF NOT EXISTS command_audit (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        timestamp TEXT NOT NULL,
+        project_path TEXT NOT NULL,
+        source TEXT NOT NULL,
+        command TEXT NOT NULL,
+        rule TEXT NOT NULL,
+        reason TEXT NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_command_audit_project ON command_audit(project_path, id);
+    ",
+    // 7: external processes started by the app
+    "
+    CREATE TABLE IF NOT EXISTS process_audit (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        source TEXT NOT NULL,
+        program TEXT NOT NULL,
+        args TEXT NOT NULL,
+        cwd TEXT,
+        pid INTEGER,
+        started_at TEXT NOT NULL,
+        duration_ms INTEGER,
+        exit_code INTEGER,
+        outcome TEXT NOT NULL,
+        error TEXT
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_process_audit_source ON process_audit(source, id);
+    ",
+    // 8: reusable prompt templates
+    "
+    CREATE TABLE IF NOT EXISTS prompt_templates (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        title TEXT NOT NULL,
+        tags TEXT NOT NULL,
+        variables TEXT NOT NULL,
+        body TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+    ",
+    // 9: knowledge base captured from transcripts, with its full-text index
+    "
+    CREATE TABLE IF NOT EXISTS knowledge_entries (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        title TEXT NOT NULL,
+        content TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        language TEXT,
+        tags TEXT NOT NULL,
+        project_path TEXT,
+        session_id TEXT,
+        message_index INTEGER,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_knowledge_entries_project ON knowledge_entries(project_path, id);
+
+    CREATE VIRTUAL TABLE IF NOT EXISTS knowledge_fts USING fts5(
+        title, content, tags, content='knowledge_entries', content_rowid='id'
+    );
+
+    CREATE TRIGGER IF NOT EXISTS knowledge_entries_ai AFTER INSERT ON knowledge_entries BEGIN
+        INSERT INTO knowledge_fts(rowid, title, content, tags)
+        VALUES (new.id, new.title, new.content, new.tags);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS knowledge_entries_ad AFTER DELETE ON knowledge_entries BEGIN
+        INSERT INTO knowledge_fts(knowledge_fts, rowid, title, content, tags)
+        VALUES ('delete', old.id, old.title, old.content, old.tags);
+    END;
+
+    CREATE TRIGGER IF NOT EXISTS knowledge_entries_au AFTER UPDATE ON knowledge_entries BEGIN
+        INSERT INTO knowledge_fts(knowledge_fts, rowid, title, content, tags)
+        VALUES ('delete', old.id, old.title, old.content, old.tags);
+        INSERT INTO knowledge_fts(rowid, title, content, tags)
+        VALUES (new.id, new.title, new.content, new.tags);
+    END;
+    ",
+    // 10: branch and ticket each session's cost is attributed to
+    "
+    CREATE TABLE IF NOT EXISTS session_attributions (
+        session_id TEXT PRIMARY KEY,
+        project_path TEXT,
+        branch TEXT,
+        ticket TEXT,
+        source TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+    ",
+    // 11: recipes for starting a configured session
+    "
+    CREATE TABLE IF NOT EXISTS session_templates (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT NOT NULL,
+        description TEXT,
+        prompt TEXT NOT NULL,
+        model TEXT,
+        thinking_preset TEXT,
+        env_profile TEXT,
+        hook_presets TEXT NOT NULL,
+        mcp_servers TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+    ",
+    // 12: user tag of each session
+    "
+    CREATE TABLE IF NOT EXISTS session_tags (
+        session_id TEXT PRIMARY KEY,
+        tag TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_session_tags_tag ON session_tags(tag);
+    ",
+    // 13: transcripts moved out by retention rules
+    "
+    CREATE TABLE IF NOT EXISTS archived_sessions (
+        session_id TEXT PRIMARY KEY,
+        project_id TEXT NOT NULL,
+        original_path TEXT NOT NULL,
+        archive_path TEXT NOT NULL,
+        original_bytes INTEGER NOT NULL,
+        archived_bytes INTEGER NOT NULL,
+        last_activity TEXT NOT NULL,
+        archived_at TEXT NOT NULL,
+        rule TEXT NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_archived_sessions_project ON archived_sessions(project_id);
+    ",
+];
+
+/// Bring the schema up to the latest migration
+fn run_migrations(conn: &mut Connection) -> SqliteResult<()> {
+    let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration)?;
+        tx.pragma_update(None, "user_version", index + 1)?;
+        tx.commit()?;
+        log::info!("Applied database migration {}", index + 1);
+    }
+    Ok(())
+}
+
 /// Initialize the database
 pub fn init_database(app: &AppHandle) -> SqliteResult<Connection> {
-    let app_dir = app
-        .path()
-        .app_data_dir()
-        .expect("Failed to get app data dir");
+    let app_dir = crate::paths::app_data_dir(app).expect("Failed to get app data dir");
     std::fs::create_dir_all(&app_dir).expect("Failed to create app data dir");
 
     let db_path = app_dir.join("agents.db");
-    let conn = Connection::open(db_path)?;
+    let mut conn = Connection::open(db_path)?;
+    // The log writer holds its own connection, so wait for locks instead of failing
+    conn.busy_timeout(std::time::Duration::from_secs(5))?;
 
-    // Create usage_entries table for token usage tracking
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS usage_entries (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            session_id TEXT NOT NULL,
-            timestamp TEXT NOT NULL,
-            model TEXT NOT NULL,
-            input_tokens INTEGER DEFAULT 0,
-            output_tokens INTEGER DEFAULT 0,
-            cache_creation_tokens INTEGER DEFAULT 0,
-            cache_read_tokens INTEGER DEFAULT 0,
-            total_tokens INTEGER DEFAULT 0,
-            cost REAL DEFAULT 0.0,
-            project_path TEXT,
-            created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
-        )",
-        [],
-    )?;
+    run_migrations(&mut conn)?;
+
+    Ok(conn)
+}
 
+/// Open a second connection to the app database for components that
+/// write independently of the shared `AgentDb` lock
+pub fn open_database_connection(app: &AppHandle) -> WorkbenchResult<Connection> {
+    let app_dir = crate::paths::app_data_dir(app)
+        .map_err(|e| WorkbenchError::Other(format!("Failed to get app data dir: {}", e)))?;
+    let conn = Connection::open(app_dir.join("agents.db"))?;
+    // `AgentDb` holds the lock while it writes, so wait for it instead of failing
+    conn.busy_timeout(std::time::Duration::from_secs(5))?;
     Ok(conn)
 }
 
@@ -503,6 +788,10 @@ pub async fn storage_reset_database(app: AppHandle) -> Result<(), String> {
         conn.execute("DROP TABLE IF EXISTS app_settings", [])
             .map_err(|e| format!("Failed to drop app_settings table: {}", e))?;
 
+        // Let the migrations recreate what was dropped
+        conn.pragma_update(None, "user_version", 0)
+            .map_err(|e| format!("Failed to reset schema version: {}", e))?;
+
         // Re-enable foreign key constraints
         conn.execute("PRAGMA foreign_keys = ON", [])
             .map_err(|e| format!("Failed to re-enable foreign keys: {}", e))?;
@@ -530,6 +819,91 @@ pub async fn storage_reset_database(app: AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// Database size, schema version and per-table row counts
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DbStats {
+    pub path: String,
+    pub size_bytes: u64,
+    pub schema_version: usize,
+    pub latest_schema_version: usize,
+    pub page_size: i64,
+    pub page_count: i64,
+    /// Unused pages that `vacuum_db` would reclaim
+    pub freelist_count: i64,
+    pub tables: Vec<TableRowCount>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TableRowCount {
+    pub name: String,
+    pub row_count: i64,
+}
+
+fn database_path(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    crate::paths::app_data_dir(app)
+        .map(|dir| dir.join("agents.db"))
+        .map_err(|e| format!("Failed to get app data dir: {}", e))
+}
+
+/// Report database size, schema version and row counts
+#[tauri::command]
+pub async fn get_db_stats(app: AppHandle, db: State<'_, AgentDb>) -> Result<DbStats, String> {
+    let path = database_path(&app)?;
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let pragma = |name: &str| -> Result<i64, String> {
+        conn.query_row(&format!("PRAGMA {}", name), [], |row| row.get(0))
+            .map_err(|e| e.to_string())
+    };
+
+    let mut stmt = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%' ORDER BY name")
+        .map_err(|e| e.to_string())?;
+    let names: Vec<String> = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<SqliteResult<Vec<_>>>()
+        .map_err(|e| e.to_string())?;
+    let tables = names
+        .into_iter()
+        .map(|name| {
+            let row_count = conn
+                .query_row(&format!("SELECT COUNT(*) FROM \"{}\"", name), [], |row| {
+                    row.get(0)
+                })
+                .unwrap_or(0);
+            TableRowCount { name, row_count }
+        })
+        .collect();
+
+    Ok(DbStats {
+        size_bytes: std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+        path: path.to_string_lossy().to_string(),
+        schema_version: pragma("user_version")? as usize,
+        latest_schema_version: MIGRATIONS.len(),
+        page_size: pragma("page_size")?,
+        page_count: pragma("page_count")?,
+        freelist_count: pragma("freelist_count")?,
+        tables,
+    })
+}
+
+/// Rebuild the database file to reclaim free pages; returns the bytes saved
+#[tauri::command]
+pub async fn vacuum_db(app: AppHandle, db: State<'_, AgentDb>) -> Result<u64, String> {
+    let path = database_path(&app)?;
+    let size = || std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    let before = size();
+
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    conn.execute("VACUUM", [])
+        .map_err(|e| format!("Failed to vacuum database: {}", e))?;
+    drop(conn);
+
+    let saved = before.saturating_sub(size());
+    log::info!("Vacuumed database, reclaimed {} bytes", saved);
+    Ok(saved)
+}
+
 /// Helper function to validate table name exists
 fn is_valid_table_name(conn: &Connection, table_name: &str) -> Result<bool, String> {
     let count: i64 = conn
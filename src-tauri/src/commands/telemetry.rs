@@ -0,0 +1,356 @@
+use log::{debug, info, warn};
+/// Opt-in usage telemetry
+///
+/// Off by default. When enabled, subsystems record which features are used as
+/// daily counters in the local `telemetry_events` table; event names are static
+/// strings, so no paths, prompts or settings ever reach the store. Nothing leaves
+/// the machine unless the user also sets an endpoint (there is no built-in one):
+/// pending counts are then posted there periodically, as exactly the JSON that
+/// `preview_telemetry_payload` shows, tagged with a random install ID. As a last
+/// guard, counters whose event name the redaction rules would mask are never sent.
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use super::redaction;
+use super::storage::AgentDb;
+
+/// `app_settings` key holding the JSON config
+const SETTINGS_KEY: &str = "telemetry";
+
+/// How often pending counts are sent to the endpoint
+const SEND_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Sent counts are kept this many days for inspection
+const RETENTION_DAYS: i64 = 90;
+
+/// Every event a subsystem may record
+pub const EVENTS: &[&str] = &[
+    "session.start",
+    "session.continue",
+    "session.resume",
+    "hooks.trigger",
+    "hooks.preset_install",
+    "checkpoint.policy",
+    "mcp.add",
+];
+
+/// Persisted telemetry settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryConfig {
+    pub enabled: bool,
+    /// Self-hosted collector the payload is POSTed to; None keeps everything local
+    pub endpoint: Option<String>,
+    /// Random ID with no link to the user or machine; reset by `reset_telemetry`
+    pub install_id: String,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: None,
+            install_id: uuid::Uuid::new_v4().to_string(),
+        }
+    }
+}
+
+/// One counter in the payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryCount {
+    pub day: String,
+    pub event: String,
+    pub count: u64,
+}
+
+/// Exactly what is sent to the endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryPayload {
+    pub install_id: String,
+    pub app_version: String,
+    pub os: String,
+    pub events: Vec<TelemetryCount>,
+}
+
+#[derive(Default)]
+pub struct TelemetryState(Mutex<Option<TelemetryConfig>>);
+
+fn load_config(conn: &Connection) -> Result<TelemetryConfig, String> {
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT value FROM app_settings WHERE key = ?1",
+            [SETTINGS_KEY],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    match stored {
+        Some(json) => {
+            serde_json::from_str(&json).map_err(|e| format!("Invalid telemetry settings: {}", e))
+        }
+        None => Ok(TelemetryConfig::default()),
+    }
+}
+
+fn save_config(conn: &Connection, config: &TelemetryConfig) -> Result<(), String> {
+    let json = serde_json::to_string(config).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+        params![SETTINGS_KEY, json],
+    )
+    .map_err(|e| format!("Failed to save telemetry settings: {}", e))?;
+    Ok(())
+}
+
+impl TelemetryState {
+    fn config(&self, db: &AgentDb) -> TelemetryConfig {
+        let mut cached = match self.0.lock() {
+            Ok(cached) => cached,
+            Err(_) => return TelemetryConfig::default(),
+        };
+        if let Some(config) = cached.as_ref() {
+            return config.clone();
+        }
+        let loaded = db.0.lock().map_err(|e| e.to_string()).and_then(|conn| {
+            let config = load_config(&conn)?;
+            // Keep the install ID stable from the first load on
+            save_config(&conn, &config)?;
+            Ok(config)
+        });
+        match loaded {
+            Ok(config) => cached.insert(config).clone(),
+            Err(e) => {
+                warn!("{}", e);
+                TelemetryConfig::default()
+            }
+        }
+    }
+}
+
+/// Count a use of a feature; does nothing unless telemetry is enabled
+pub fn record(app: &AppHandle, event: &'static str) {
+    debug_assert!(EVENTS.contains(&event), "unknown telemetry event {}", event);
+    let (state, db) = match (
+        app.try_state::<TelemetryState>(),
+        app.try_state::<AgentDb>(),
+    ) {
+        (Some(state), Some(db)) => (state, db),
+        _ => return,
+    };
+    if !state.config(&db).enabled {
+        return;
+    }
+    let day = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let result = db.0.lock().map_err(|e| e.to_string()).and_then(|conn| {
+        conn.execute(
+            "INSERT INTO telemetry_events (day, event, count, sent_count) VALUES (?1, ?2, 1, 0)
+             ON CONFLICT(day, event) DO UPDATE SET count = count + 1",
+            params![day, event],
+        )
+        .map_err(|e| e.to_string())
+    });
+    if let Err(e) = result {
+        debug!("Failed to record telemetry event {}: {}", event, e);
+    }
+}
+
+/// Counts not sent yet
+fn pending_counts(conn: &Connection) -> Result<Vec<TelemetryCount>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT day, event, count - sent_count FROM telemetry_events
+             WHERE count > sent_count ORDER BY day, event",
+        )
+        .map_err(|e| e.to_string())?;
+    let counts = stmt
+        .query_map([], |row| {
+            Ok(TelemetryCount {
+                day: row.get(0)?,
+                event: row.get(1)?,
+                count: row.get::<_, i64>(2)? as u64,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string());
+    counts
+}
+
+fn build_payload(
+    app: &AppHandle,
+    db: &AgentDb,
+    config: &TelemetryConfig,
+) -> Result<TelemetryPayload, String> {
+    let redactor = redaction::redactor(app);
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let events = pending_counts(&conn)?
+        .into_iter()
+        .filter(|count| redactor.redact(&count.event) == count.event)
+        .collect();
+    Ok(TelemetryPayload {
+        install_id: config.install_id.clone(),
+        app_version: app.package_info().version.to_string(),
+        os: std::env::consts::OS.to_string(),
+        events,
+    })
+}
+
+/// Post pending counts to the endpoint and mark them sent. Returns how many
+/// counters were sent.
+async fn send_pending(app: &AppHandle) -> Result<usize, String> {
+    let db = app.state::<AgentDb>();
+    let config = app.state::<TelemetryState>().config(&db);
+    if !config.enabled {
+        return Err("Telemetry is not enabled".to_string());
+    }
+    let endpoint = config
+        .endpoint
+        .clone()
+        .ok_or("No telemetry endpoint configured")?;
+    let payload = build_payload(app, &db, &config)?;
+    if payload.events.is_empty() {
+        return Ok(0);
+    }
+
+    let client = super::proxy::apply_to_client(reqwest::Client::builder())
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let response = client
+        .post(&endpoint)
+        .json(&payload)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send telemetry: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Telemetry endpoint returned {}", response.status()));
+    }
+
+    // Counts recorded while the request was in flight stay pending
+    let mut conn = db.0.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for count in &payload.events {
+        tx.execute(
+            "UPDATE telemetry_events SET sent_count = sent_count + ?1 WHERE day = ?2 AND event = ?3",
+            params![count.count as i64, count.day, count.event],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    let cutoff = (chrono::Utc::now() - chrono::Duration::days(RETENTION_DAYS))
+        .format("%Y-%m-%d")
+        .to_string();
+    tx.execute(
+        "DELETE FROM telemetry_events WHERE day < ?1 AND count = sent_count",
+        [cutoff],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    info!(
+        "Sent {} telemetry counters to {}",
+        payload.events.len(),
+        endpoint
+    );
+    Ok(payload.events.len())
+}
+
+/// Send pending counts in the background while an endpoint is configured
+pub fn start_telemetry_sender(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(SEND_INTERVAL).await;
+            let config = app
+                .state::<TelemetryState>()
+                .config(&app.state::<AgentDb>());
+            if !config.enabled || config.endpoint.is_none() {
+                continue;
+            }
+            if let Err(e) = send_pending(&app).await {
+                warn!("{}", e);
+            }
+        }
+    });
+}
+
+// ============ Tauri Commands ============
+
+#[tauri::command]
+pub async fn get_telemetry_config(
+    db: State<'_, AgentDb>,
+    state: State<'_, TelemetryState>,
+) -> Result<TelemetryConfig, String> {
+    Ok(state.config(&db))
+}
+
+/// Turn telemetry on or off and set the endpoint. Turning it off also deletes
+/// everything recorded so far.
+#[tauri::command]
+pub async fn update_telemetry_config(
+    db: State<'_, AgentDb>,
+    state: State<'_, TelemetryState>,
+    enabled: bool,
+    endpoint: Option<String>,
+) -> Result<TelemetryConfig, String> {
+    let endpoint = endpoint
+        .map(|e| e.trim().to_string())
+        .filter(|e| !e.is_empty());
+    if let Some(endpoint) = &endpoint {
+        let url = reqwest::Url::parse(endpoint)
+            .map_err(|e| format!("Invalid telemetry endpoint: {}", e))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err("Telemetry endpoint must be an http(s) URL".to_string());
+        }
+    }
+
+    let mut config = state.config(&db);
+    config.enabled = enabled;
+    config.endpoint = endpoint;
+    {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        save_config(&conn, &config)?;
+        if !enabled {
+            conn.execute("DELETE FROM telemetry_events", [])
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    *state.0.lock().map_err(|e| e.to_string())? = Some(config.clone());
+    info!("Telemetry {}", if enabled { "enabled" } else { "disabled" });
+    Ok(config)
+}
+
+/// The payload the next send would post, byte for byte
+#[tauri::command]
+pub async fn preview_telemetry_payload(
+    app: AppHandle,
+    db: State<'_, AgentDb>,
+    state: State<'_, TelemetryState>,
+) -> Result<TelemetryPayload, String> {
+    let config = state.config(&db);
+    build_payload(&app, &db, &config)
+}
+
+/// Send pending counts now
+#[tauri::command]
+pub async fn send_telemetry(app: AppHandle) -> Result<usize, String> {
+    send_pending(&app).await
+}
+
+/// Delete all recorded events and start over with a new install ID
+#[tauri::command]
+pub async fn reset_telemetry(
+    db: State<'_, AgentDb>,
+    state: State<'_, TelemetryState>,
+) -> Result<TelemetryConfig, String> {
+    let mut config = state.config(&db);
+    config.install_id = uuid::Uuid::new_v4().to_string();
+    {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM telemetry_events", [])
+            .map_err(|e| e.to_string())?;
+        save_config(&conn, &config)?;
+    }
+    *state.0.lock().map_err(|e| e.to_string())? = Some(config.clone());
+    Ok(config)
+}
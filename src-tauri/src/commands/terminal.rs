@@ -0,0 +1,480 @@
+use log::{debug, info, warn};
+/// Embedded terminals
+///
+/// Each terminal is a shell (or a single command) running on a pseudo-terminal
+/// from `portable-pty`, so programs see a real TTY with colors, line editing and
+/// a window size. Output is streamed as `terminal-output:{id}` events and
+/// `terminal-exit:{id}` fires once the process ended; `terminal-created` lets
+/// the UI pick up terminals it did not open itself, such as those of hooks
+/// running with `terminal: true`.
+///
+/// Every terminal has a reader thread that forwards output and a waiter thread
+/// that owns the child; the waiter removes the terminal once the process exits.
+use portable_pty::{native_pty_system, ChildKiller, CommandBuilder, MasterPty, PtySize};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::oneshot;
+
+use super::command_policy;
+use super::env_profiles;
+use crate::error::{WorkbenchError, WorkbenchResult};
+use crate::events::{self, AppEvent};
+use crate::process::audit::ProcessAudit;
+
+pub const DEFAULT_COLS: u16 = 120;
+pub const DEFAULT_ROWS: u16 = 32;
+/// Output kept for terminals that capture it (hooks)
+const MAX_CAPTURED_BYTES: usize = 1024 * 1024;
+
+/// A running terminal, as listed by `list_terminals`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalInfo {
+    pub id: String,
+    pub project_path: String,
+    pub title: String,
+    /// Command line the terminal runs
+    pub command: String,
+    pub pid: Option<u32>,
+    pub cols: u16,
+    pub rows: u16,
+    pub created_at: String,
+}
+
+/// Payload of `terminal-output:{id}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalOutput {
+    pub terminal_id: String,
+    pub data: String,
+}
+
+/// Payload of `terminal-exit:{id}`, also delivered to whoever spawned it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalExit {
+    pub terminal_id: String,
+    pub exit_code: u32,
+    pub success: bool,
+    /// Everything the process printed, for terminals spawned with `capture`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+}
+
+/// What to run in a new terminal
+pub struct TerminalSpec {
+    pub project_path: String,
+    pub title: String,
+    /// Script run with `bash -c`; None starts the user's login shell
+    pub script: Option<String>,
+    pub env: Vec<(String, String)>,
+    pub cols: u16,
+    pub rows: u16,
+    /// Keep the output for `TerminalExit::output`
+    pub capture: bool,
+}
+
+struct Terminal {
+    info: TerminalInfo,
+    master: Box<dyn MasterPty + Send>,
+    writer: Box<dyn Write + Send>,
+    killer: Box<dyn ChildKiller + Send + Sync>,
+    /// The line being typed, for the command policy
+    input: InputLine,
+}
+
+/// Tracks what the user types between two Enters. Cursor keys, history recall
+/// and tab completion change the line in ways only the shell knows, so after
+/// any of those the line is unknown until the next Enter.
+#[derive(Default)]
+struct InputLine {
+    text: String,
+    unknown: bool,
+}
+
+impl InputLine {
+    /// Feed one input character; returns the finished line on Enter (None if it
+    /// could not be followed)
+    fn feed(&mut self, c: char) -> Option<Option<String>> {
+        match c {
+            '\r' | '\n' => {
+                let line = std::mem::take(&mut self.text);
+                let known = !std::mem::take(&mut self.unknown);
+                return Some(known.then_some(line));
+            }
+            // Backspace
+            '\x7f' | '\x08' => {
+                self.text.pop();
+            }
+            // Ctrl-C and Ctrl-U discard the line
+            '\x03' | '\x15' => {
+                self.text.clear();
+                self.unknown = false;
+            }
+            '\x1b' | '\t' => self.unknown = true,
+            c if c.is_control() => {}
+            c => self.text.push(c),
+        }
+        None
+    }
+}
+
+/// Running terminals by ID
+#[derive(Default)]
+pub struct TerminalState(Mutex<HashMap<String, Terminal>>);
+
+fn pty_error(what: &str, e: impl std::fmt::Display) -> WorkbenchError {
+    WorkbenchError::Other(format!("{}: {}", what, e))
+}
+
+/// Decode as much of `pending` as forms complete UTF-8, keeping a trailing
+/// partial character for the next read
+fn take_utf8(pending: &mut Vec<u8>) -> String {
+    let valid = match std::str::from_utf8(pending) {
+        Ok(_) => pending.len(),
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        Err(_) => pending.len(),
+    };
+    let text = String::from_utf8_lossy(&pending[..valid]).to_string();
+    pending.drain(..valid);
+    text
+}
+
+/// Forward output until the pty closes; returns the captured output
+fn read_output(
+    app: AppHandle,
+    id: String,
+    mut reader: Box<dyn Read + Send>,
+    capture: bool,
+) -> Option<String> {
+    let event = format!("terminal-output:{}", id);
+    let mut captured = capture.then(String::new);
+    let mut pending = Vec::new();
+    let mut buffer = [0u8; 8192];
+    loop {
+        match reader.read(&mut buffer) {
+            // Linux reports EIO once the child side is closed
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                pending.extend_from_slice(&buffer[..n]);
+                let data = take_utf8(&mut pending);
+                if data.is_empty() {
+                    continue;
+                }
+                if let Some(captured) = captured.as_mut() {
+                    if captured.len() < MAX_CAPTURED_BYTES {
+                        captured.push_str(&data);
+                    }
+                }
+                let payload = TerminalOutput {
+                    terminal_id: id.clone(),
+                    data,
+                };
+                if let Err(e) = app.emit(&event, &payload) {
+                    debug!("Failed to emit terminal output: {}", e);
+                }
+            }
+        }
+    }
+    captured
+}
+
+/// Start a terminal. The receiver resolves once its process has exited and all
+/// output was forwarded.
+pub fn spawn_terminal(
+    app: &AppHandle,
+    spec: TerminalSpec,
+) -> WorkbenchResult<(TerminalInfo, oneshot::Receiver<TerminalExit>)> {
+    let state = app
+        .try_state::<TerminalState>()
+        .ok_or("Terminal state not initialized")?;
+    let size = PtySize {
+        rows: spec.rows,
+        cols: spec.cols,
+        pixel_width: 0,
+        pixel_height: 0,
+    };
+    let pair = native_pty_system()
+        .openpty(size)
+        .map_err(|e| pty_error("Failed to open terminal", e))?;
+
+    let mut cmd = match &spec.script {
+        Some(script) => {
+            let mut cmd = CommandBuilder::new("bash");
+            cmd.args(["-c", script]);
+            cmd
+        }
+        None => CommandBuilder::new_default_prog(),
+    };
+    cmd.cwd(&spec.project_path);
+    cmd.env("TERM", "xterm-256color");
+    cmd.env("COLORTERM", "truecolor");
+    for (key, value) in &spec.env {
+        cmd.env(key, value);
+    }
+    let command = match &spec.script {
+        Some(script) => script.clone(),
+        None => cmd.get_shell(),
+    };
+
+    let mut audit = match &spec.script {
+        Some(script) => ProcessAudit::start_program(
+            "terminal",
+            "bash",
+            &["-c", script],
+            Some(&spec.project_path),
+        ),
+        None => ProcessAudit::start_program("terminal", &command, &[], Some(&spec.project_path)),
+    };
+    let mut child = match pair.slave.spawn_command(cmd) {
+        Ok(child) => child,
+        Err(e) => {
+            audit.finish(&Err(std::io::Error::other(e.to_string())));
+            return Err(pty_error("Failed to start terminal process", e));
+        }
+    };
+    audit.set_pid(child.process_id());
+    // Only the child keeps the slave side open, so reads end when it exits
+    drop(pair.slave);
+
+    let reader = pair
+        .master
+        .try_clone_reader()
+        .map_err(|e| pty_error("Failed to read terminal", e))?;
+    let writer = pair
+        .master
+        .take_writer()
+        .map_err(|e| pty_error("Failed to write terminal", e))?;
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let info = TerminalInfo {
+        id: id.clone(),
+        project_path: spec.project_path.clone(),
+        title: spec.title,
+        command,
+        pid: child.process_id(),
+        cols: spec.cols,
+        rows: spec.rows,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    state.0.lock().map_err(|e| e.to_string())?.insert(
+        id.clone(),
+        Terminal {
+            info: info.clone(),
+            master: pair.master,
+            writer,
+            input: InputLine::default(),
+            killer: child.clone_killer(),
+        },
+    );
+
+    let reader_thread = {
+        let app = app.clone();
+        let id = id.clone();
+        std::thread::spawn(move || read_output(app, id, reader, spec.capture))
+    };
+
+    let (exit_tx, exit_rx) = oneshot::channel();
+    let waiter_app = app.clone();
+    std::thread::spawn(move || {
+        let status = child.wait();
+        // Dropping the master ends the reader on platforms where the child's exit
+        // alone does not
+        if let Some(state) = waiter_app.try_state::<TerminalState>() {
+            if let Ok(mut terminals) = state.0.lock() {
+                terminals.remove(&id);
+            }
+        }
+        let output = reader_thread.join().unwrap_or_default();
+        let (exit_code, success) = match status {
+            Ok(status) => {
+                audit.finish_code(Some(status.exit_code() as i32));
+                (status.exit_code(), status.success())
+            }
+            Err(e) => {
+                warn!("Failed to wait for terminal {}: {}", id, e);
+                (1, false)
+            }
+        };
+        debug!("Terminal {} exited with {}", id, exit_code);
+        let exit = TerminalExit {
+            terminal_id: id.clone(),
+            exit_code,
+            success,
+            output,
+        };
+        events::emit(
+            &waiter_app,
+            AppEvent::TerminalExit(TerminalExit {
+                output: None,
+                ..exit.clone()
+            }),
+        );
+        let _ = exit_tx.send(exit);
+    });
+
+    info!(
+        "Started terminal {} in {} ({})",
+        info.id, info.project_path, info.command
+    );
+    events::emit(app, AppEvent::TerminalCreated(info.clone()));
+    Ok((info, exit_rx))
+}
+
+/// Kill a terminal's process; returns false if no terminal has that ID
+pub fn kill_terminal(app: &AppHandle, id: &str) -> WorkbenchResult<bool> {
+    let state = app
+        .try_state::<TerminalState>()
+        .ok_or("Terminal state not initialized")?;
+    let mut terminals = state.0.lock().map_err(|e| e.to_string())?;
+    match terminals.get_mut(id) {
+        Some(terminal) => {
+            if let Err(e) = terminal.killer.kill() {
+                warn!("Failed to kill terminal {}: {}", id, e);
+            }
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Kill all terminals, on shutdown
+pub fn close_all(app: &AppHandle) {
+    if let Some(state) = app.try_state::<TerminalState>() {
+        if let Ok(mut terminals) = state.0.lock() {
+            for terminal in terminals.values_mut() {
+                let _ = terminal.killer.kill();
+            }
+        }
+    }
+}
+
+// ============ Tauri Commands ============
+
+/// Open an interactive shell in the project
+#[tauri::command]
+pub async fn create_terminal(
+    app: AppHandle,
+    project_path: String,
+    title: Option<String>,
+    cols: Option<u16>,
+    rows: Option<u16>,
+) -> Result<TerminalInfo, WorkbenchError> {
+    if !std::path::Path::new(&project_path).is_dir() {
+        return Err(WorkbenchError::NotFound(format!(
+            "Project directory not found: {}",
+            project_path
+        )));
+    }
+    let env = env_profiles::active_profile(&app, &project_path)
+        .map(|profile| profile.vars.into_iter().collect())
+        .unwrap_or_default();
+    let spec = TerminalSpec {
+        title: title.unwrap_or_else(|| "Terminal".to_string()),
+        project_path,
+        script: None,
+        env,
+        cols: cols.unwrap_or(DEFAULT_COLS),
+        rows: rows.unwrap_or(DEFAULT_ROWS),
+        capture: false,
+    };
+    let (info, _exit) = spawn_terminal(&app, spec)?;
+    Ok(info)
+}
+
+fn write_input(terminal: &mut Terminal, data: &str) -> WorkbenchResult<()> {
+    terminal
+        .writer
+        .write_all(data.as_bytes())
+        .and_then(|_| terminal.writer.flush())
+        .map_err(|e| WorkbenchError::io("Failed to write to terminal", e))
+}
+
+/// Send input (keystrokes or pasted text) to a terminal. Each entered line is
+/// checked against the project's command policy first; a refused line, or one
+/// that could not be followed while a policy is enabled, is erased (Ctrl-U)
+/// instead of being run.
+#[tauri::command]
+pub async fn write_to_terminal(
+    app: AppHandle,
+    state: State<'_, TerminalState>,
+    terminal_id: String,
+    data: String,
+) -> Result<(), WorkbenchError> {
+    let mut terminals = state.0.lock().map_err(|e| e.to_string())?;
+    let terminal = terminals
+        .get_mut(&terminal_id)
+        .ok_or_else(|| WorkbenchError::NotFound(format!("Terminal {} not found", terminal_id)))?;
+
+    for (offset, c) in data.char_indices() {
+        let line = match terminal.input.feed(c) {
+            Some(line) => line,
+            None => continue,
+        };
+        let project_path = terminal.info.project_path.clone();
+        let checked = match line {
+            Some(line) => command_policy::enforce(&app, &project_path, &line, "terminal"),
+            None => command_policy::enforce_untracked(&app, &project_path, "terminal"),
+        };
+        if let Err(e) = checked {
+            write_input(terminal, &data[..offset])?;
+            write_input(terminal, "\x15")?;
+            return Err(e);
+        }
+    }
+    write_input(terminal, &data)
+}
+
+#[tauri::command]
+pub async fn resize_terminal(
+    state: State<'_, TerminalState>,
+    terminal_id: String,
+    cols: u16,
+    rows: u16,
+) -> Result<(), WorkbenchError> {
+    if cols == 0 || rows == 0 {
+        return Err(WorkbenchError::InvalidInput(
+            "Terminal size must be at least 1x1".to_string(),
+        ));
+    }
+    let mut terminals = state.0.lock().map_err(|e| e.to_string())?;
+    let terminal = terminals
+        .get_mut(&terminal_id)
+        .ok_or_else(|| WorkbenchError::NotFound(format!("Terminal {} not found", terminal_id)))?;
+    terminal
+        .master
+        .resize(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(|e| pty_error("Failed to resize terminal", e))?;
+    terminal.info.cols = cols;
+    terminal.info.rows = rows;
+    Ok(())
+}
+
+/// Kill a terminal's process; `terminal-exit:{id}` follows
+#[tauri::command]
+pub async fn close_terminal(app: AppHandle, terminal_id: String) -> Result<bool, WorkbenchError> {
+    kill_terminal(&app, &terminal_id)
+}
+
+#[tauri::command]
+pub async fn list_terminals(
+    state: State<'_, TerminalState>,
+    project_path: Option<String>,
+) -> Result<Vec<TerminalInfo>, WorkbenchError> {
+    let terminals = state.0.lock().map_err(|e| e.to_string())?;
+    let mut list: Vec<TerminalInfo> = terminals
+        .values()
+        .map(|t| t.info.clone())
+        .filter(|info| project_path.is_none() || Some(&info.project_path) == project_path.as_ref())
+        .collect();
+    list.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    Ok(list)
+}
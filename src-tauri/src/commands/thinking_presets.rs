@@ -0,0 +1,193 @@
+use log::{info, warn};
+/// Thinking-mode and effort presets per project
+///
+/// A preset bundles an extended-thinking budget and an effort level, such as
+/// "deep work" for hard problems or "quick edits" for small changes. Each
+/// project may select one; sessions started in the project then get the
+/// preset's `--max-thinking-tokens` flag, unless the caller passes a budget of
+/// its own, and its effort level as `CLAUDE_CODE_EFFORT_LEVEL`. Hooks of the
+/// project see the active preset as `thinking_preset` in their data.
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::{AppHandle, Manager, State};
+use tokio::process::Command;
+
+use super::permission_config::ClaudeExecutionConfig;
+use super::storage::AgentDb;
+
+/// `app_settings` key holding the preset of each project
+const SETTINGS_KEY: &str = "project_thinking_presets";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EffortLevel {
+    Low,
+    Medium,
+    High,
+}
+
+impl EffortLevel {
+    fn as_str(self) -> &'static str {
+        match self {
+            EffortLevel::Low => "low",
+            EffortLevel::Medium => "medium",
+            EffortLevel::High => "high",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ThinkingPreset {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Extended-thinking budget; 0 turns thinking off
+    pub max_thinking_tokens: u32,
+    pub effort: EffortLevel,
+}
+
+const PRESETS: &[ThinkingPreset] = &[
+    ThinkingPreset {
+        id: "deep_work",
+        name: "Deep work",
+        description: "Extended thinking and high effort, for design and hard bugs",
+        max_thinking_tokens: 31_999,
+        effort: EffortLevel::High,
+    },
+    ThinkingPreset {
+        id: "balanced",
+        name: "Balanced",
+        description: "Moderate thinking budget for everyday tasks",
+        max_thinking_tokens: 10_000,
+        effort: EffortLevel::Medium,
+    },
+    ThinkingPreset {
+        id: "quick_edits",
+        name: "Quick edits",
+        description: "No extended thinking and low effort, for small, well-defined changes",
+        max_thinking_tokens: 0,
+        effort: EffortLevel::Low,
+    },
+];
+
+pub(crate) fn find_preset(id: &str) -> Option<&'static ThinkingPreset> {
+    PRESETS.iter().find(|p| p.id == id)
+}
+
+fn project_key(project_path: &str) -> String {
+    project_path.trim_end_matches(['/', '\\']).to_string()
+}
+
+fn load_selections(conn: &Connection) -> Result<BTreeMap<String, String>, String> {
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT value FROM app_settings WHERE key = ?1",
+            [SETTINGS_KEY],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    match stored {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid project thinking presets: {}", e)),
+        None => Ok(BTreeMap::new()),
+    }
+}
+
+/// Preset selected for a project, if any
+pub fn active_preset(app: &AppHandle, project_path: &str) -> Option<&'static ThinkingPreset> {
+    let db = app.try_state::<AgentDb>()?;
+    let conn = db.0.lock().ok()?;
+    let selections = load_selections(&conn).map_err(|e| warn!("{}", e)).ok()?;
+    let id = selections.get(&project_key(project_path))?;
+    let preset = find_preset(id);
+    if preset.is_none() {
+        warn!("Unknown thinking preset '{}' for {}", id, project_path);
+    }
+    preset
+}
+
+/// Select the preset of a project, or clear it with None; returns the one
+/// selected before
+pub(crate) fn select(
+    conn: &Connection,
+    project_path: &str,
+    preset: Option<&str>,
+) -> Result<Option<String>, String> {
+    let project_path = project_key(project_path);
+    let mut selections = load_selections(conn)?;
+    let previous = match preset {
+        Some(id) => selections.insert(project_path, id.to_string()),
+        None => selections.remove(&project_path),
+    };
+    let json = serde_json::to_string(&selections).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+        params![SETTINGS_KEY, json],
+    )
+    .map_err(|e| format!("Failed to save thinking preset: {}", e))?;
+    Ok(previous)
+}
+
+/// Give a session's execution config the thinking budget of the project's
+/// preset; returns the preset, whose effort goes on the command with `set_env`
+pub(crate) fn apply(
+    app: &AppHandle,
+    project_path: &str,
+    config: &mut ClaudeExecutionConfig,
+) -> Option<&'static ThinkingPreset> {
+    let preset = active_preset(app, project_path)?;
+    config.max_thinking_tokens = Some(preset.max_thinking_tokens);
+    info!(
+        "Applying thinking preset '{}' to session in {}",
+        preset.id, project_path
+    );
+    Some(preset)
+}
+
+/// Pass a preset's effort level to the CLI
+pub(crate) fn set_env(cmd: &mut Command, preset: Option<&ThinkingPreset>) {
+    if let Some(preset) = preset {
+        cmd.env("CLAUDE_CODE_EFFORT_LEVEL", preset.effort.as_str());
+    }
+}
+
+// ============ Tauri Commands ============
+
+#[tauri::command]
+pub async fn list_thinking_presets() -> Result<Vec<ThinkingPreset>, String> {
+    Ok(PRESETS.to_vec())
+}
+
+/// Preset selected for a project, if any
+#[tauri::command]
+pub async fn get_thinking_preset(
+    app: AppHandle,
+    project_path: String,
+) -> Result<Option<ThinkingPreset>, String> {
+    Ok(active_preset(&app, &project_path).cloned())
+}
+
+/// Select the preset of a project, or clear it with None; applies from the
+/// next session start or resume
+#[tauri::command]
+pub async fn set_thinking_preset(
+    db: State<'_, AgentDb>,
+    project_path: String,
+    preset: Option<String>,
+) -> Result<(), String> {
+    if let Some(id) = &preset {
+        if find_preset(id).is_none() {
+            return Err(format!("Thinking preset not found: {}", id));
+        }
+    }
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    select(&conn, &project_path, preset.as_deref())?;
+    info!(
+        "Thinking preset of {} set to {}",
+        project_key(&project_path),
+        preset.as_deref().unwrap_or("none")
+    );
+    Ok(())
+}
@@ -71,7 +71,7 @@ pub struct TranslationService {
 impl TranslationService {
     /// 创建新的翻译服务实例
     pub fn new(config: TranslationConfig) -> Self {
-        let client = Client::builder()
+        let client = crate::commands::proxy::apply_to_client(Client::builder())
             .timeout(Duration::from_secs(config.timeout_seconds))
             .build()
             .expect("Failed to create HTTP client");
@@ -536,8 +536,7 @@ fn get_translation_config_path() -> Result<PathBuf, String> {
 
 /// 获取Claude目录路径
 fn get_claude_dir() -> Result<PathBuf, String> {
-    let home_dir = dirs::home_dir().ok_or_else(|| "Could not find home directory".to_string())?;
-    let claude_dir = home_dir.join(".claude");
+    let claude_dir = crate::paths::claude_dir()?;
 
     // 确保目录存在
     if !claude_dir.exists() {
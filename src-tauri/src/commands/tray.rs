@@ -0,0 +1,220 @@
+use log::{info, warn};
+/// System tray icon
+///
+/// The tray menu lists running sessions with their context usage, each with a
+/// submenu to pause, resume or stop it, and check items that switch installed
+/// hook presets (user scope) on and off. The session list comes from the
+/// session status aggregator; the menu is rebuilt whenever it or the user
+/// settings change.
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Manager};
+
+use super::hook_presets::installed_presets;
+use super::session_status::{self, SessionStatus};
+use crate::events::{self, AppEvent};
+
+const TRAY_ID: &str = "main";
+
+/// Settings scope whose presets the tray toggles
+const PRESET_SCOPE: &str = "user";
+
+/// Sessions currently shown in the menu
+#[derive(Default)]
+pub struct TrayState(Mutex<Vec<SessionStatus>>);
+
+fn session_label(session: &SessionStatus) -> String {
+    let project = Path::new(&session.project_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| session.project_path.clone());
+    let usage = match (session.estimated_tokens, session.context_percent) {
+        (Some(tokens), Some(percent)) => {
+            format!(" · {}k tokens ({:.0}%)", tokens.div_ceil(1000), percent)
+        }
+        _ => String::new(),
+    };
+    let paused = if session.paused { " · paused" } else { "" };
+    format!("{}{}{}", project, usage, paused)
+}
+
+fn build_menu(app: &AppHandle, sessions: &[SessionStatus]) -> tauri::Result<Menu<tauri::Wry>> {
+    let menu = Menu::new(app)?;
+    menu.append(&MenuItem::with_id(
+        app,
+        "show",
+        "Show Claude Workbench",
+        true,
+        None::<&str>,
+    )?)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+
+    if sessions.is_empty() {
+        menu.append(&MenuItem::new(
+            app,
+            "No running sessions",
+            false,
+            None::<&str>,
+        )?)?;
+    }
+    for session in sessions {
+        let id = &session.session_id;
+        let toggle = if session.paused {
+            MenuItem::with_id(app, format!("resume:{}", id), "Resume", true, None::<&str>)?
+        } else {
+            MenuItem::with_id(app, format!("pause:{}", id), "Pause", true, None::<&str>)?
+        };
+        let stop = MenuItem::with_id(app, format!("stop:{}", id), "Stop", true, None::<&str>)?;
+        let submenu = Submenu::with_items(app, session_label(session), true, &[&toggle, &stop])?;
+        menu.append(&submenu)?;
+    }
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+
+    let presets_menu = Submenu::new(app, "Hook presets", true)?;
+    match installed_presets(PRESET_SCOPE, None) {
+        Ok(presets) if !presets.is_empty() => {
+            for preset in presets {
+                presets_menu.append(&CheckMenuItem::with_id(
+                    app,
+                    format!("preset:{}", preset.id),
+                    &preset.name,
+                    true,
+                    preset.enabled,
+                    None::<&str>,
+                )?)?;
+            }
+        }
+        Ok(_) => presets_menu.append(&MenuItem::new(
+            app,
+            "No presets installed",
+            false,
+            None::<&str>,
+        )?)?,
+        Err(e) => {
+            warn!("Failed to read installed hook presets: {}", e);
+            presets_menu.append(&MenuItem::new(
+                app,
+                "Hook settings unavailable",
+                false,
+                None::<&str>,
+            )?)?
+        }
+    }
+    menu.append(&presets_menu)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?)?;
+    Ok(menu)
+}
+
+fn tooltip(sessions: &[SessionStatus]) -> String {
+    match sessions.len() {
+        0 => "Claude Workbench".to_string(),
+        1 => "Claude Workbench · 1 running session".to_string(),
+        n => format!("Claude Workbench · {} running sessions", n),
+    }
+}
+
+/// Rebuild the menu from the current sessions and presets
+pub fn refresh_tray(app: &AppHandle) {
+    let tray = match app.tray_by_id(TRAY_ID) {
+        Some(tray) => tray,
+        None => return,
+    };
+    let sessions = app
+        .try_state::<TrayState>()
+        .and_then(|state| state.0.lock().ok().map(|s| s.clone()))
+        .unwrap_or_default();
+    match build_menu(app, &sessions) {
+        Ok(menu) => {
+            let _ = tray.set_menu(Some(menu));
+            let _ = tray.set_tooltip(Some(tooltip(&sessions)));
+        }
+        Err(e) => warn!("Failed to build tray menu: {}", e),
+    }
+}
+
+/// Show a new session list; called by the session status aggregator
+pub fn update_sessions(app: &AppHandle, sessions: Vec<SessionStatus>) {
+    if let Some(state) = app.try_state::<TrayState>() {
+        if let Ok(mut current) = state.0.lock() {
+            *current = sessions;
+        }
+    }
+    refresh_tray(app);
+}
+
+fn handle_menu_event(app: &AppHandle, id: &str) {
+    match id {
+        "show" => super::deep_link::focus_main_window(app),
+        "quit" => app.exit(0),
+        _ => {
+            let (action, target) = match id.split_once(':') {
+                Some(parts) => parts,
+                None => return,
+            };
+            let app = app.clone();
+            let action = action.to_string();
+            let target = target.to_string();
+            tauri::async_runtime::spawn(async move {
+                let result = match action.as_str() {
+                    "pause" => session_status::set_paused(&app, &target, true),
+                    "resume" => session_status::set_paused(&app, &target, false),
+                    "stop" => session_status::stop(&app, &target).await,
+                    "preset" => toggle_preset(&app, &target),
+                    _ => Ok(()),
+                };
+                if let Err(e) = result {
+                    warn!("Tray action {} on {} failed: {}", action, target, e);
+                }
+            });
+        }
+    }
+}
+
+fn toggle_preset(app: &AppHandle, preset: &str) -> Result<(), String> {
+    let enabled = installed_presets(PRESET_SCOPE, None)?
+        .into_iter()
+        .find(|p| p.id == preset)
+        .map(|p| p.enabled)
+        .ok_or_else(|| format!("Hook preset {} is not installed", preset))?;
+    super::enhanced_hooks::set_preset_hooks_enabled(PRESET_SCOPE, None, preset, !enabled)?;
+    refresh_tray(app);
+    Ok(())
+}
+
+/// Create the tray icon and keep its menu current
+pub fn init_tray(app: &AppHandle) -> tauri::Result<()> {
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip(tooltip(&[]))
+        .menu(&build_menu(app, &[])?)
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app, event| handle_menu_event(app, event.id().as_ref()))
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                super::deep_link::focus_main_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+
+    // Presets installed or toggled outside the tray
+    events::subscribe(app, |app, event| {
+        if let AppEvent::ConfigChanged(change) = event {
+            if change.scope == PRESET_SCOPE {
+                refresh_tray(app);
+            }
+        }
+    });
+
+    info!("Tray icon ready");
+    Ok(())
+}
@@ -0,0 +1,387 @@
+use log::{info, warn};
+/// App updates with release channels
+///
+/// Checks the stable or beta release feed, downloads the signed bundle with
+/// progress events, and installs it. With `defer_restart` on, a downloaded update
+/// waits until no Claude session is running (per the process registry and the
+/// interactive process) before installing and restarting, so a restart never cuts
+/// off a session; `restart_to_update` installs it right away.
+///
+/// Events: `update-download-progress` `{downloaded, total}`, `update-downloaded`,
+/// `update-restart-deferred` and `update-status-changed`.
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State, Url};
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+use super::claude::ClaudeProcessState;
+use super::storage::AgentDb;
+use crate::events::{self, AppEvent};
+use crate::process::ProcessRegistryState;
+
+/// `app_settings` key holding the JSON config
+const SETTINGS_KEY: &str = "updater";
+
+const STABLE_FEED: &str =
+    "https://github.com/anyme123/claude-workbench/releases/latest/download/latest.json";
+/// Published by prereleases; it also carries stable releases newer than the last beta
+const BETA_FEED: &str =
+    "https://github.com/anyme123/claude-workbench/releases/download/beta/latest.json";
+
+/// How often a deferred update checks whether sessions have finished
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    Beta,
+}
+
+/// Persisted update preferences
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdaterConfig {
+    pub channel: UpdateChannel,
+    /// Hold a downloaded update until no Claude session is running
+    pub defer_restart: bool,
+}
+
+impl Default for UpdaterConfig {
+    fn default() -> Self {
+        Self {
+            channel: UpdateChannel::Stable,
+            defer_restart: true,
+        }
+    }
+}
+
+/// An update offered by the feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvailableUpdate {
+    pub version: String,
+    pub current_version: String,
+    pub channel: UpdateChannel,
+    pub notes: Option<String>,
+    pub date: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "lowercase")]
+pub enum UpdateStatus {
+    Idle,
+    Available {
+        version: String,
+    },
+    Downloading {
+        version: String,
+    },
+    /// Downloaded, waiting for running sessions to finish
+    Deferred {
+        version: String,
+    },
+    Installing {
+        version: String,
+    },
+}
+
+/// A downloaded, verified bundle
+struct DownloadedUpdate {
+    update: Update,
+    bytes: Vec<u8>,
+}
+
+pub struct UpdaterState {
+    config: Mutex<Option<UpdaterConfig>>,
+    status: Mutex<UpdateStatus>,
+    available: tokio::sync::Mutex<Option<Update>>,
+    downloaded: tokio::sync::Mutex<Option<DownloadedUpdate>>,
+}
+
+impl Default for UpdaterState {
+    fn default() -> Self {
+        Self {
+            config: Mutex::new(None),
+            status: Mutex::new(UpdateStatus::Idle),
+            available: tokio::sync::Mutex::new(None),
+            downloaded: tokio::sync::Mutex::new(None),
+        }
+    }
+}
+
+fn load_config(app: &AppHandle) -> Result<UpdaterConfig, String> {
+    let db = app
+        .try_state::<AgentDb>()
+        .ok_or("Database not initialized")?;
+    let conn = db.0.lock().map_err(|e| e.to_string())?;
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT value FROM app_settings WHERE key = ?1",
+            [SETTINGS_KEY],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    match stored {
+        Some(json) => {
+            serde_json::from_str(&json).map_err(|e| format!("Invalid updater settings: {}", e))
+        }
+        None => Ok(UpdaterConfig::default()),
+    }
+}
+
+impl UpdaterState {
+    fn config(&self, app: &AppHandle) -> UpdaterConfig {
+        let mut cached = match self.config.lock() {
+            Ok(cached) => cached,
+            Err(_) => return UpdaterConfig::default(),
+        };
+        cached
+            .get_or_insert_with(|| {
+                load_config(app).unwrap_or_else(|e| {
+                    warn!("{}", e);
+                    UpdaterConfig::default()
+                })
+            })
+            .clone()
+    }
+
+    fn set_status(&self, app: &AppHandle, status: UpdateStatus) {
+        if let Ok(mut current) = self.status.lock() {
+            *current = status.clone();
+        }
+        events::emit(app, AppEvent::UpdateStatusChanged(status));
+    }
+}
+
+/// Whether any Claude session, interactive or background, is running
+async fn sessions_running(app: &AppHandle) -> bool {
+    let registered = app
+        .state::<ProcessRegistryState>()
+        .0
+        .get_running_claude_sessions()
+        .map(|sessions| !sessions.is_empty())
+        .unwrap_or(false);
+    registered
+        || app
+            .state::<ClaudeProcessState>()
+            .current_process
+            .lock()
+            .await
+            .is_some()
+}
+
+fn feeds(channel: UpdateChannel) -> Result<Vec<Url>, String> {
+    let feeds: &[&str] = match channel {
+        UpdateChannel::Stable => &[STABLE_FEED],
+        // Fall back to the stable feed while no beta has been published
+        UpdateChannel::Beta => &[BETA_FEED, STABLE_FEED],
+    };
+    feeds
+        .iter()
+        .map(|feed| Url::parse(feed).map_err(|e| e.to_string()))
+        .collect()
+}
+
+/// Install a downloaded bundle and restart into it
+fn install_and_restart(app: &AppHandle, state: &UpdaterState, downloaded: DownloadedUpdate) {
+    let version = downloaded.update.version.clone();
+    state.set_status(
+        app,
+        UpdateStatus::Installing {
+            version: version.clone(),
+        },
+    );
+    info!("Installing update {}", version);
+    match downloaded.update.install(&downloaded.bytes) {
+        Ok(()) => app.restart(),
+        Err(e) => {
+            warn!("Failed to install update {}: {}", version, e);
+            state.set_status(app, UpdateStatus::Idle);
+        }
+    }
+}
+
+/// Wait in the background until no session runs, then install the held update
+fn wait_for_idle_sessions(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(IDLE_POLL_INTERVAL).await;
+            if sessions_running(&app).await {
+                continue;
+            }
+            let state = app.state::<UpdaterState>();
+            // Already installed through restart_to_update
+            let downloaded = match state.downloaded.lock().await.take() {
+                Some(downloaded) => downloaded,
+                None => return,
+            };
+            info!("No Claude sessions running, applying deferred update");
+            install_and_restart(&app, &state, downloaded);
+            return;
+        }
+    });
+}
+
+// ============ Tauri Commands ============
+
+#[tauri::command]
+pub async fn get_updater_config(
+    app: AppHandle,
+    state: State<'_, UpdaterState>,
+) -> Result<UpdaterConfig, String> {
+    Ok(state.config(&app))
+}
+
+#[tauri::command]
+pub async fn update_updater_config(
+    db: State<'_, AgentDb>,
+    state: State<'_, UpdaterState>,
+    config: UpdaterConfig,
+) -> Result<(), String> {
+    let json = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+    {
+        let conn = db.0.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR REPLACE INTO app_settings (key, value) VALUES (?1, ?2)",
+            params![SETTINGS_KEY, json],
+        )
+        .map_err(|e| format!("Failed to save updater settings: {}", e))?;
+    }
+    *state.config.lock().map_err(|e| e.to_string())? = Some(config);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_update_status(state: State<'_, UpdaterState>) -> Result<UpdateStatus, String> {
+    Ok(state.status.lock().map_err(|e| e.to_string())?.clone())
+}
+
+/// Ask the configured channel's feed for a newer version
+#[tauri::command]
+pub async fn check_for_updates(
+    app: AppHandle,
+    state: State<'_, UpdaterState>,
+) -> Result<Option<AvailableUpdate>, String> {
+    let channel = state.config(&app).channel;
+    let updater = app
+        .updater_builder()
+        .endpoints(feeds(channel)?)
+        .map_err(|e| e.to_string())?
+        .build()
+        .map_err(|e| e.to_string())?;
+    let update = updater
+        .check()
+        .await
+        .map_err(|e| format!("Failed to check for updates: {}", e))?;
+
+    let mut available = state.available.lock().await;
+    let info = update.as_ref().map(|update| AvailableUpdate {
+        version: update.version.clone(),
+        current_version: update.current_version.clone(),
+        channel,
+        notes: update.body.clone(),
+        date: update.date.map(|date| date.to_string()),
+    });
+    match &info {
+        Some(info) => {
+            info!(
+                "Update {} available on the {:?} channel",
+                info.version, channel
+            );
+            if !matches!(
+                *state.status.lock().map_err(|e| e.to_string())?,
+                UpdateStatus::Downloading { .. } | UpdateStatus::Deferred { .. }
+            ) {
+                state.set_status(
+                    &app,
+                    UpdateStatus::Available {
+                        version: info.version.clone(),
+                    },
+                );
+            }
+        }
+        None => info!("No update available on the {:?} channel", channel),
+    }
+    *available = update;
+    Ok(info)
+}
+
+/// Download the update found by `check_for_updates` and install it, now or, with
+/// `defer_restart`, once no session is running. Returns true if it was deferred.
+#[tauri::command]
+pub async fn install_update(
+    app: AppHandle,
+    state: State<'_, UpdaterState>,
+) -> Result<bool, String> {
+    let update = state
+        .available
+        .lock()
+        .await
+        .take()
+        .ok_or("No update available; check for updates first")?;
+    let version = update.version.clone();
+    state.set_status(
+        &app,
+        UpdateStatus::Downloading {
+            version: version.clone(),
+        },
+    );
+
+    let progress_app = app.clone();
+    let mut downloaded = 0u64;
+    let bytes = match update
+        .download(
+            |chunk, total| {
+                downloaded += chunk as u64;
+                let payload = serde_json::json!({ "downloaded": downloaded, "total": total });
+                let _ = progress_app.emit("update-download-progress", payload);
+            },
+            || {},
+        )
+        .await
+    {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            state.set_status(&app, UpdateStatus::Idle);
+            return Err(format!("Failed to download update {}: {}", version, e));
+        }
+    };
+    events::emit(&app, AppEvent::UpdateDownloaded(version.clone()));
+
+    let downloaded = DownloadedUpdate { update, bytes };
+    if state.config(&app).defer_restart && sessions_running(&app).await {
+        info!("Deferring update {} until Claude sessions finish", version);
+        *state.downloaded.lock().await = Some(downloaded);
+        state.set_status(
+            &app,
+            UpdateStatus::Deferred {
+                version: version.clone(),
+            },
+        );
+        events::emit(&app, AppEvent::UpdateRestartDeferred(version.clone()));
+        wait_for_idle_sessions(app.clone());
+        return Ok(true);
+    }
+
+    install_and_restart(&app, &state, downloaded);
+    Ok(false)
+}
+
+/// Install a deferred update now, even if sessions are running
+#[tauri::command]
+pub async fn restart_to_update(
+    app: AppHandle,
+    state: State<'_, UpdaterState>,
+) -> Result<(), String> {
+    let downloaded = state
+        .downloaded
+        .lock()
+        .await
+        .take()
+        .ok_or("No downloaded update is waiting")?;
+    install_and_restart(&app, &state, downloaded);
+    Ok(())
+}
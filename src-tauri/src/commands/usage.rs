@@ -2,12 +2,19 @@
 // Source: https://github.com/meistrari/opcode
 
 use chrono::{DateTime, Local, NaiveDate};
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use serde_json;
 use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
-use tauri::command;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{command, AppHandle, Emitter};
+use tokio_util::sync::CancellationToken;
+
+use super::claude::get_claude_dir;
+use super::operations;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UsageEntry {
@@ -20,6 +27,12 @@ pub struct UsageEntry {
     cost: f64,
     session_id: String,
     project_path: String,
+    /// `message_id:request_id`, used to drop the same response logged in several files
+    #[serde(skip)]
+    dedup_key: Option<String>,
+    /// Branch checked out when the response was logged
+    #[serde(skip)]
+    git_branch: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -66,23 +79,6 @@ pub struct ProjectUsage {
     last_used: String,
 }
 
-// Claude 4 pricing constants (per million tokens)
-const OPUS_4_INPUT_PRICE: f64 = 15.0;
-const OPUS_4_OUTPUT_PRICE: f64 = 75.0;
-const OPUS_4_CACHE_WRITE_PRICE: f64 = 18.75;
-const OPUS_4_CACHE_READ_PRICE: f64 = 1.50;
-
-const SONNET_4_INPUT_PRICE: f64 = 3.0;
-const SONNET_4_OUTPUT_PRICE: f64 = 15.0;
-const SONNET_4_CACHE_WRITE_PRICE: f64 = 3.75;
-const SONNET_4_CACHE_READ_PRICE: f64 = 0.30;
-
-// Claude 3.5 Sonnet pricing (for backward compatibility)
-const SONNET_35_INPUT_PRICE: f64 = 3.0;
-const SONNET_35_OUTPUT_PRICE: f64 = 15.0;
-const SONNET_35_CACHE_WRITE_PRICE: f64 = 3.75;
-const SONNET_35_CACHE_READ_PRICE: f64 = 0.30;
-
 #[derive(Debug, Deserialize)]
 struct JsonlEntry {
     timestamp: String,
@@ -93,6 +89,8 @@ struct JsonlEntry {
     request_id: Option<String>,
     #[serde(rename = "costUSD")]
     cost_usd: Option<f64>,
+    #[serde(rename = "gitBranch")]
+    git_branch: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -116,44 +114,28 @@ fn calculate_cost(model: &str, usage: &UsageData) -> f64 {
     let cache_creation_tokens = usage.cache_creation_input_tokens.unwrap_or(0) as f64;
     let cache_read_tokens = usage.cache_read_input_tokens.unwrap_or(0) as f64;
 
-    // Calculate cost based on model
-    let (input_price, output_price, cache_write_price, cache_read_price) =
-        if model.contains("opus-4") || model.contains("claude-opus-4") {
-            (
-                OPUS_4_INPUT_PRICE,
-                OPUS_4_OUTPUT_PRICE,
-                OPUS_4_CACHE_WRITE_PRICE,
-                OPUS_4_CACHE_READ_PRICE,
-            )
-        } else if model.contains("sonnet-4") || model.contains("claude-sonnet-4") {
-            (
-                SONNET_4_INPUT_PRICE,
-                SONNET_4_OUTPUT_PRICE,
-                SONNET_4_CACHE_WRITE_PRICE,
-                SONNET_4_CACHE_READ_PRICE,
-            )
-        } else if model.contains("3.5") || model.contains("35") || model.contains("sonnet") {
-            // Default to Sonnet 3.5 pricing for any sonnet variant
-            (
-                SONNET_35_INPUT_PRICE,
-                SONNET_35_OUTPUT_PRICE,
-                SONNET_35_CACHE_WRITE_PRICE,
-                SONNET_35_CACHE_READ_PRICE,
-            )
-        } else {
-            // Return 0 for unknown models to avoid incorrect cost estimations
-            (0.0, 0.0, 0.0, 0.0)
-        };
+    // Unknown models cost 0 to avoid incorrect estimates
+    let pricing = match super::models::lookup(model) {
+        Some(info) => info.pricing,
+        None => return 0.0,
+    };
 
     // Calculate cost (prices are per million tokens)
-    let cost = (input_tokens * input_price / 1_000_000.0)
-        + (output_tokens * output_price / 1_000_000.0)
-        + (cache_creation_tokens * cache_write_price / 1_000_000.0)
-        + (cache_read_tokens * cache_read_price / 1_000_000.0);
+    let cost = (input_tokens * pricing.input / 1_000_000.0)
+        + (output_tokens * pricing.output / 1_000_000.0)
+        + (cache_creation_tokens * pricing.cache_write / 1_000_000.0)
+        + (cache_read_tokens * pricing.cache_read / 1_000_000.0);
 
     cost
 }
 
+/// Cost of one message from its raw `usage` object, for callers outside this module
+pub(crate) fn message_cost(model: &str, usage: &serde_json::Value) -> f64 {
+    serde_json::from_value::<UsageData>(usage.clone())
+        .map(|usage| calculate_cost(model, &usage))
+        .unwrap_or(0.0)
+}
+
 fn parse_jsonl_file(
     path: &PathBuf,
     encoded_project_name: &str,
@@ -188,12 +170,15 @@ fn parse_jsonl_file(
                 if let Ok(entry) = serde_json::from_value::<JsonlEntry>(json_value) {
                     if let Some(message) = &entry.message {
                         // Deduplication based on message ID and request ID
-                        if let (Some(msg_id), Some(req_id)) = (&message.id, &entry.request_id) {
-                            let unique_hash = format!("{}:{}", msg_id, req_id);
-                            if processed_hashes.contains(&unique_hash) {
+                        let dedup_key = match (&message.id, &entry.request_id) {
+                            (Some(msg_id), Some(req_id)) => Some(format!("{}:{}", msg_id, req_id)),
+                            _ => None,
+                        };
+                        if let Some(unique_hash) = &dedup_key {
+                            if processed_hashes.contains(unique_hash) {
                                 continue; // Skip duplicate entry
                             }
-                            processed_hashes.insert(unique_hash);
+                            processed_hashes.insert(unique_hash.clone());
                         }
 
                         if let Some(usage) = &message.usage {
@@ -234,6 +219,8 @@ fn parse_jsonl_file(
                                 cost,
                                 session_id: entry.session_id.unwrap_or_else(|| session_id.clone()),
                                 project_path,
+                                dedup_key,
+                                git_branch: entry.git_branch.filter(|b| !b.is_empty()),
                             });
                         }
                     }
@@ -266,50 +253,409 @@ fn get_earliest_timestamp(path: &PathBuf) -> Option<String> {
     None
 }
 
+/// Usage entries of every session, served from the usage index after
+/// re-parsing only the files that changed since the last scan
 fn get_all_usage_entries(claude_path: &PathBuf) -> Vec<UsageEntry> {
-    let mut all_entries = Vec::new();
+    refresh_usage_index(claude_path, &CancellationToken::new(), |_| {});
+
+    let index = match USAGE_INDEX.lock() {
+        Ok(index) => index,
+        Err(_) => return Vec::new(),
+    };
+
+    // Process files by their earliest timestamp to ensure deterministic deduplication
+    let mut files: Vec<&IndexedFile> = index.values().collect();
+    files.sort_by(|a, b| a.earliest.cmp(&b.earliest));
+
     let mut processed_hashes = HashSet::new();
-    let projects_dir = claude_path.join("projects");
-
-    let mut files_to_process: Vec<(PathBuf, String)> = Vec::new();
-
-    if let Ok(projects) = fs::read_dir(&projects_dir) {
-        for project in projects.flatten() {
-            if project.file_type().map(|t| t.is_dir()).unwrap_or(false) {
-                let project_name = project.file_name().to_string_lossy().to_string();
-                let project_path = project.path();
-
-                walkdir::WalkDir::new(&project_path)
-                    .into_iter()
-                    .filter_map(Result::ok)
-                    .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("jsonl"))
-                    .for_each(|entry| {
-                        files_to_process.push((entry.path().to_path_buf(), project_name.clone()));
+    let mut all_entries: Vec<UsageEntry> = files
+        .into_iter()
+        .flat_map(|file| file.entries.iter())
+        .filter(|entry| match &entry.dedup_key {
+            Some(key) => processed_hashes.insert(key.clone()),
+            None => true,
+        })
+        .cloned()
+        .collect();
+
+    // Sort by timestamp
+    all_entries.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+
+    all_entries
+}
+
+// ============ Tool calls ============
+
+/// Tools that modify the file named by their input
+const EDIT_TOOLS: &[&str] = &["Edit", "MultiEdit", "Write", "NotebookEdit"];
+
+/// Start of the tool result Claude Code records when the user declines a tool call
+const REJECTION_MARKERS: &[&str] = &[
+    "The user doesn't want to proceed with this tool use",
+    "The user doesn't want to take this action",
+];
+
+/// How a tool call ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ToolOutcome {
+    Success,
+    Error,
+    /// Declined at the permission prompt
+    Rejected,
+    /// No result in the transcript (interrupted session)
+    Unknown,
+}
+
+/// One `tool_use` block and its result
+#[derive(Debug, Clone)]
+pub(crate) struct ToolCall {
+    id: String,
+    pub(crate) tool: String,
+    pub(crate) timestamp: String,
+    pub(crate) project_path: String,
+    /// What permission specifiers match against, e.g. the Bash command
+    pub(crate) subject: Option<String>,
+    pub(crate) outcome: ToolOutcome,
+    pub(crate) duration_ms: Option<u64>,
+}
+
+/// Text of a `tool_result` content: a string or an array of text blocks
+fn tool_result_text(content: Option<&serde_json::Value>) -> String {
+    match content {
+        Some(serde_json::Value::String(text)) => text.clone(),
+        Some(serde_json::Value::Array(blocks)) => blocks
+            .iter()
+            .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+fn content_blocks(value: &serde_json::Value) -> impl Iterator<Item = &serde_json::Value> {
+    value
+        .pointer("/message/content")
+        .and_then(|c| c.as_array())
+        .into_iter()
+        .flatten()
+}
+
+/// Pair each `tool_use` of a transcript with its `tool_result`
+fn parse_tool_calls(path: &Path, encoded_project_name: &str) -> Vec<ToolCall> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut calls: Vec<ToolCall> = Vec::new();
+    let mut pending: HashMap<String, usize> = HashMap::new();
+    let mut project_path: Option<String> = None;
+
+    for line in content.lines().filter(|l| !l.trim().is_empty()) {
+        let value: serde_json::Value = match serde_json::from_str(line) {
+            Ok(value) => value,
+            Err(_) => continue,
+        };
+        if project_path.is_none() {
+            project_path = value.get("cwd").and_then(|c| c.as_str()).map(String::from);
+        }
+        let timestamp = value
+            .get("timestamp")
+            .and_then(|t| t.as_str())
+            .unwrap_or_default();
+
+        for block in content_blocks(&value) {
+            match block.get("type").and_then(|t| t.as_str()) {
+                Some("tool_use") => {
+                    let (id, tool) = match (
+                        block.get("id").and_then(|i| i.as_str()),
+                        block.get("name").and_then(|n| n.as_str()),
+                    ) {
+                        (Some(id), Some(tool)) => (id, tool),
+                        _ => continue,
+                    };
+                    let subject = block
+                        .get("input")
+                        .and_then(|input| super::permissions::tool_input_subject(tool, input));
+                    pending.insert(id.to_string(), calls.len());
+                    calls.push(ToolCall {
+                        id: id.to_string(),
+                        tool: tool.to_string(),
+                        timestamp: timestamp.to_string(),
+                        project_path: String::new(),
+                        subject,
+                        outcome: ToolOutcome::Unknown,
+                        duration_ms: None,
                     });
+                }
+                Some("tool_result") => {
+                    let call = match block
+                        .get("tool_use_id")
+                        .and_then(|i| i.as_str())
+                        .and_then(|id| pending.remove(id))
+                    {
+                        Some(index) => &mut calls[index],
+                        None => continue,
+                    };
+                    let is_error = block
+                        .get("is_error")
+                        .and_then(|e| e.as_bool())
+                        .unwrap_or(false);
+                    let text = tool_result_text(block.get("content"));
+                    call.outcome = if REJECTION_MARKERS.iter().any(|m| text.starts_with(m)) {
+                        ToolOutcome::Rejected
+                    } else if is_error {
+                        ToolOutcome::Error
+                    } else {
+                        ToolOutcome::Success
+                    };
+                    if let (Ok(start), Ok(end)) = (
+                        DateTime::parse_from_rfc3339(&call.timestamp),
+                        DateTime::parse_from_rfc3339(timestamp),
+                    ) {
+                        call.duration_ms = u64::try_from((end - start).num_milliseconds()).ok();
+                    }
+                }
+                _ => {}
             }
         }
     }
 
-    // Sort files by their earliest timestamp to ensure chronological processing
-    // and deterministic deduplication
-    files_to_process.sort_by_cached_key(|(path, _)| get_earliest_timestamp(path));
+    let project_path = project_path.unwrap_or_else(|| encoded_project_name.to_string());
+    for call in &mut calls {
+        call.project_path = project_path.clone();
+    }
+    calls
+}
+
+/// Tool calls of every session, optionally limited to one project and a range.
+/// Calls repeated across resumed transcripts are counted once.
+pub(crate) fn tool_calls_in_range(
+    project_path: Option<&str>,
+    range: &UsageRange,
+) -> Result<Vec<ToolCall>, String> {
+    let (start, end) = range.bounds()?;
+    let claude_path = get_claude_dir().map_err(|e| e.to_string())?;
+    refresh_usage_index(&claude_path, &CancellationToken::new(), |_| {});
+
+    let project_path = project_path.map(|p| p.trim_end_matches(['/', '\\']));
+    let index = USAGE_INDEX.lock().map_err(|e| e.to_string())?;
+    let mut seen = HashSet::new();
+    Ok(index
+        .values()
+        .flat_map(|file| file.tool_calls.iter())
+        .filter(|call| {
+            project_path.is_none_or(|p| call.project_path.trim_end_matches(['/', '\\']) == p)
+                && in_date_range(&call.timestamp, start, end)
+                && seen.insert(call.id.clone())
+        })
+        .cloned()
+        .collect())
+}
+
+// ============ Usage index ============
+//
+// Parsing every transcript under ~/.claude/projects on each request gets slow on
+// large histories, so parsed entries are cached per file and only files whose
+// mtime or size changed are parsed again. `start_usage_indexing` runs a scan in the
+// background with `index-progress` events and can be cancelled.
+
+/// Parsed usage of one transcript file
+struct IndexedFile {
+    modified: Option<SystemTime>,
+    len: u64,
+    earliest: Option<String>,
+    entries: Vec<UsageEntry>,
+    tool_calls: Vec<ToolCall>,
+}
+
+static USAGE_INDEX: Lazy<Mutex<HashMap<PathBuf, IndexedFile>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Cancel token of the running background scan, if any
+static INDEX_JOB: Lazy<Mutex<Option<CancellationToken>>> = Lazy::new(|| Mutex::new(None));
+
+/// Minimum time between two `index-progress` events
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Payload of `index-progress` events
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IndexProgress {
+    files_total: usize,
+    files_scanned: usize,
+    /// Files parsed because they were new or changed
+    files_updated: usize,
+    sessions_found: usize,
+    elapsed_ms: u64,
+    eta_ms: Option<u64>,
+    done: bool,
+    cancelled: bool,
+}
+
+/// All transcript files with the encoded project directory they belong to
+fn list_transcript_files(claude_path: &Path) -> Vec<(PathBuf, String)> {
+    let mut files = Vec::new();
+    let projects = match fs::read_dir(claude_path.join("projects")) {
+        Ok(projects) => projects,
+        Err(_) => return files,
+    };
+    for project in projects.flatten() {
+        if project.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+            let project_name = project.file_name().to_string_lossy().to_string();
+            walkdir::WalkDir::new(project.path())
+                .into_iter()
+                .filter_map(Result::ok)
+                .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("jsonl"))
+                .for_each(|entry| files.push((entry.path().to_path_buf(), project_name.clone())));
+        }
+    }
+    files
+}
+
+/// Bring the index up to date, calling `on_progress` at most every
+/// `PROGRESS_INTERVAL`. Entries of deleted files are dropped unless cancelled.
+#[tracing::instrument(name = "usage_index", skip_all, fields(files = tracing::field::Empty))]
+fn refresh_usage_index(
+    claude_path: &Path,
+    cancel: &CancellationToken,
+    mut on_progress: impl FnMut(&IndexProgress),
+) -> IndexProgress {
+    let start = Instant::now();
+    let files = list_transcript_files(claude_path);
+    tracing::Span::current().record("files", files.len());
+    let mut progress = IndexProgress {
+        files_total: files.len(),
+        ..Default::default()
+    };
+    let mut sessions = HashSet::new();
+    let mut last_report = Instant::now();
+
+    for (path, project_name) in &files {
+        if cancel.is_cancelled() {
+            progress.cancelled = true;
+            break;
+        }
 
-    for (path, project_name) in files_to_process {
-        let entries = parse_jsonl_file(&path, &project_name, &mut processed_hashes);
-        all_entries.extend(entries);
+        let metadata = fs::metadata(path).ok();
+        let modified = metadata.as_ref().and_then(|m| m.modified().ok());
+        let len = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
+        let cached = USAGE_INDEX.lock().ok().and_then(|index| {
+            index
+                .get(path)
+                .filter(|f| f.modified == modified && f.len == len)
+                .map(|f| {
+                    f.entries
+                        .iter()
+                        .map(|e| e.session_id.clone())
+                        .collect::<Vec<_>>()
+                })
+        });
+
+        match cached {
+            Some(session_ids) => sessions.extend(session_ids),
+            None => {
+                // Parse outside the lock; dedup across files happens when reading
+                let entries = parse_jsonl_file(path, project_name, &mut HashSet::new());
+                sessions.extend(entries.iter().map(|e| e.session_id.clone()));
+                let file = IndexedFile {
+                    modified,
+                    len,
+                    earliest: get_earliest_timestamp(path),
+                    entries,
+                    tool_calls: parse_tool_calls(path, project_name),
+                };
+                if let Ok(mut index) = USAGE_INDEX.lock() {
+                    index.insert(path.clone(), file);
+                }
+                progress.files_updated += 1;
+            }
+        }
+
+        progress.files_scanned += 1;
+        progress.sessions_found = sessions.len();
+        if last_report.elapsed() >= PROGRESS_INTERVAL {
+            let elapsed = start.elapsed();
+            progress.elapsed_ms = elapsed.as_millis() as u64;
+            let remaining = (progress.files_total - progress.files_scanned) as u128;
+            progress.eta_ms =
+                Some((elapsed.as_millis() * remaining / progress.files_scanned as u128) as u64);
+            on_progress(&progress);
+            last_report = Instant::now();
+        }
     }
 
-    // Sort by timestamp
-    all_entries.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    if !progress.cancelled {
+        let listed: HashSet<&PathBuf> = files.iter().map(|(path, _)| path).collect();
+        if let Ok(mut index) = USAGE_INDEX.lock() {
+            index.retain(|path, _| listed.contains(path));
+        }
+    }
+    progress.elapsed_ms = start.elapsed().as_millis() as u64;
+    progress.eta_ms = Some(0).filter(|_| !progress.cancelled);
+    progress.done = true;
+    on_progress(&progress);
+    progress
+}
 
-    all_entries
+/// Start a background scan of ~/.claude; progress arrives as `index-progress`
+/// events. Returns false if a scan is already running. With a `request_id` the
+/// scan can also be cancelled through `cancel_operation`.
+#[command]
+pub fn start_usage_indexing(app: AppHandle, request_id: Option<String>) -> Result<bool, String> {
+    let claude_path = get_claude_dir().map_err(|e| e.to_string())?;
+
+    let (cancel, operation) = {
+        let mut job = INDEX_JOB.lock().map_err(|e| e.to_string())?;
+        if job.is_some() {
+            return Ok(false);
+        }
+        let operation = match &request_id {
+            Some(request_id) => Some(operations::register(&app, request_id, "usage-index")?),
+            None => None,
+        };
+        let cancel = operation
+            .as_ref()
+            .map(|operation| operation.token())
+            .unwrap_or_default();
+        *job = Some(cancel.clone());
+        (cancel, operation)
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let _operation = operation;
+        let result = refresh_usage_index(&claude_path, &cancel, |progress| {
+            let _ = app.emit("index-progress", progress);
+        });
+        log::info!(
+            "Usage indexing finished: {} files, {} updated, {} sessions{}",
+            result.files_scanned,
+            result.files_updated,
+            result.sessions_found,
+            if result.cancelled { " (cancelled)" } else { "" }
+        );
+        if let Ok(mut job) = INDEX_JOB.lock() {
+            *job = None;
+        }
+    });
+    Ok(true)
+}
+
+/// Cancel the running background scan; returns false if none was running
+#[command]
+pub fn cancel_usage_indexing() -> Result<bool, String> {
+    let job = INDEX_JOB.lock().map_err(|e| e.to_string())?;
+    match job.as_ref() {
+        Some(cancel) => {
+            cancel.cancel();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
 }
 
 #[command]
 pub fn get_usage_stats(days: Option<u32>) -> Result<UsageStats, String> {
-    let claude_path = dirs::home_dir()
-        .ok_or("Failed to get home directory")?
-        .join(".claude");
+    let claude_path = get_claude_dir().map_err(|e| e.to_string())?;
 
     let all_entries = get_all_usage_entries(&claude_path);
 
@@ -467,9 +813,7 @@ pub fn get_usage_stats(days: Option<u32>) -> Result<UsageStats, String> {
 
 #[command]
 pub fn get_usage_by_date_range(start_date: String, end_date: String) -> Result<UsageStats, String> {
-    let claude_path = dirs::home_dir()
-        .ok_or("Failed to get home directory")?
-        .join(".claude");
+    let claude_path = get_claude_dir().map_err(|e| e.to_string())?;
 
     let all_entries = get_all_usage_entries(&claude_path);
 
@@ -628,9 +972,7 @@ pub fn get_session_stats(
     until: Option<String>,
     order: Option<String>,
 ) -> Result<Vec<ProjectUsage>, String> {
-    let claude_path = dirs::home_dir()
-        .ok_or("Failed to get home directory")?
-        .join(".claude");
+    let claude_path = get_claude_dir().map_err(|e| e.to_string())?;
 
     let all_entries = get_all_usage_entries(&claude_path);
 
@@ -695,3 +1037,465 @@ pub fn get_session_stats(
 
     Ok(by_session)
 }
+
+/// Date range for usage exports and insights; either bound may be omitted
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageRange {
+    /// First day included, `YYYY-MM-DD` or RFC 3339
+    pub start_date: Option<String>,
+    /// Last day included, `YYYY-MM-DD` or RFC 3339
+    pub end_date: Option<String>,
+}
+
+impl UsageRange {
+    /// Parsed first and last day
+    pub(crate) fn bounds(&self) -> Result<(Option<NaiveDate>, Option<NaiveDate>), String> {
+        let start = self
+            .start_date
+            .as_deref()
+            .map(|d| parse_report_date(d, "start"))
+            .transpose()?;
+        let end = self
+            .end_date
+            .as_deref()
+            .map(|d| parse_report_date(d, "end"))
+            .transpose()?;
+        Ok((start, end))
+    }
+
+    /// Stable key for caches keyed by range
+    pub(crate) fn cache_key(&self) -> String {
+        format!(
+            "{}..{}",
+            self.start_date.as_deref().unwrap_or(""),
+            self.end_date.as_deref().unwrap_or("")
+        )
+    }
+}
+
+/// Whether an RFC 3339 timestamp falls within the given days; unparseable
+/// timestamps only pass an unbounded range
+pub(crate) fn in_date_range(
+    timestamp: &str,
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+) -> bool {
+    match DateTime::parse_from_rfc3339(timestamp) {
+        Ok(dt) => {
+            let date = dt.naive_local().date();
+            start.is_none_or(|s| date >= s) && end.is_none_or(|end| date <= end)
+        }
+        Err(_) => start.is_none() && end.is_none(),
+    }
+}
+
+/// Usage entries within a range
+fn entries_in_range(range: &UsageRange) -> Result<Vec<UsageEntry>, String> {
+    let (start, end) = range.bounds()?;
+    let claude_path = get_claude_dir().map_err(|e| e.to_string())?;
+    Ok(get_all_usage_entries(&claude_path)
+        .into_iter()
+        .filter(|e| in_date_range(&e.timestamp, start, end))
+        .collect())
+}
+
+/// Token and cost totals for one project within a range, or None without usage
+pub(crate) fn project_usage(
+    project_path: &str,
+    range: &UsageRange,
+) -> Result<Option<UsageReportRow>, String> {
+    let project_path = project_path.trim_end_matches(['/', '\\']);
+    let entries: Vec<UsageEntry> = entries_in_range(range)?
+        .into_iter()
+        .filter(|e| e.project_path.trim_end_matches(['/', '\\']) == project_path)
+        .collect();
+    let (_, projects) = build_report_rows(&entries);
+    Ok(projects.into_iter().next())
+}
+
+/// Per-session and per-project usage within a range, most expensive first
+pub(crate) fn usage_rows(
+    range: &UsageRange,
+) -> Result<(Vec<UsageReportRow>, Vec<UsageReportRow>), String> {
+    Ok(build_report_rows(&entries_in_range(range)?))
+}
+
+/// Per-model usage within a range, most expensive first, with the number of
+/// sessions that used each model
+pub(crate) fn model_rows(range: &UsageRange) -> Result<Vec<(UsageReportRow, usize)>, String> {
+    let mut models: HashMap<String, (UsageReportRow, HashSet<String>)> = HashMap::new();
+    for entry in entries_in_range(range)? {
+        let (row, sessions) = models
+            .entry(entry.model.clone())
+            .or_insert_with(|| (UsageReportRow::new("model", &entry), HashSet::new()));
+        row.add(&entry);
+        sessions.insert(entry.session_id.clone());
+    }
+    let mut rows: Vec<_> = models
+        .into_values()
+        .map(|(row, sessions)| (row, sessions.len()))
+        .collect();
+    rows.sort_by(|a, b| b.0.total_cost.total_cmp(&a.0.total_cost));
+    Ok(rows)
+}
+
+/// One row of a usage report, aggregated per session, project or model
+#[derive(Debug, Serialize)]
+pub(crate) struct UsageReportRow {
+    scope: &'static str,
+    pub(crate) project_path: String,
+    pub(crate) project_name: String,
+    pub(crate) session_id: String,
+    pub(crate) first_used: String,
+    pub(crate) last_used: String,
+    pub(crate) message_count: u64,
+    pub(crate) input_tokens: u64,
+    pub(crate) output_tokens: u64,
+    pub(crate) cache_creation_tokens: u64,
+    pub(crate) cache_read_tokens: u64,
+    pub(crate) total_tokens: u64,
+    pub(crate) total_cost: f64,
+    pub(crate) models: Vec<String>,
+    /// First branch recorded in the transcript
+    #[serde(skip)]
+    pub(crate) git_branch: Option<String>,
+}
+
+impl UsageReportRow {
+    fn new(scope: &'static str, entry: &UsageEntry) -> Self {
+        UsageReportRow {
+            scope,
+            project_path: entry.project_path.clone(),
+            project_name: entry
+                .project_path
+                .split(['/', '\\'])
+                .next_back()
+                .unwrap_or(&entry.project_path)
+                .to_string(),
+            session_id: if scope == "session" {
+                entry.session_id.clone()
+            } else {
+                String::new()
+            },
+            first_used: entry.timestamp.clone(),
+            last_used: entry.timestamp.clone(),
+            message_count: 0,
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
+            total_tokens: 0,
+            total_cost: 0.0,
+            models: Vec::new(),
+            git_branch: None,
+        }
+    }
+
+    fn add(&mut self, entry: &UsageEntry) {
+        self.message_count += 1;
+        self.input_tokens += entry.input_tokens;
+        self.output_tokens += entry.output_tokens;
+        self.cache_creation_tokens += entry.cache_creation_tokens;
+        self.cache_read_tokens += entry.cache_read_tokens;
+        self.total_tokens += entry.input_tokens
+            + entry.output_tokens
+            + entry.cache_creation_tokens
+            + entry.cache_read_tokens;
+        self.total_cost += entry.cost;
+        if entry.timestamp < self.first_used {
+            self.first_used = entry.timestamp.clone();
+        }
+        if entry.timestamp > self.last_used {
+            self.last_used = entry.timestamp.clone();
+        }
+        if !self.models.contains(&entry.model) {
+            self.models.push(entry.model.clone());
+        }
+        if self.git_branch.is_none() {
+            self.git_branch = entry.git_branch.clone();
+        }
+    }
+}
+
+/// Columns available in usage reports, in default order
+const REPORT_COLUMNS: &[&str] = &[
+    "scope",
+    "project_path",
+    "project_name",
+    "session_id",
+    "first_used",
+    "last_used",
+    "message_count",
+    "input_tokens",
+    "output_tokens",
+    "cache_creation_tokens",
+    "cache_read_tokens",
+    "total_tokens",
+    "total_cost",
+    "models",
+];
+
+/// Summary returned by `export_usage_report`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UsageExportResult {
+    path: String,
+    format: String,
+    session_count: usize,
+    project_count: usize,
+    total_cost: f64,
+}
+
+fn parse_report_date(value: &str, label: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").or_else(|_| {
+        DateTime::parse_from_rfc3339(value)
+            .map(|dt| dt.naive_local().date())
+            .map_err(|e| format!("Invalid {} date: {}", label, e))
+    })
+}
+
+/// Aggregate entries into per-session and per-project rows, most expensive first
+fn build_report_rows(entries: &[UsageEntry]) -> (Vec<UsageReportRow>, Vec<UsageReportRow>) {
+    let mut sessions: HashMap<(String, String), UsageReportRow> = HashMap::new();
+    let mut projects: HashMap<String, UsageReportRow> = HashMap::new();
+    for entry in entries {
+        sessions
+            .entry((entry.project_path.clone(), entry.session_id.clone()))
+            .or_insert_with(|| UsageReportRow::new("session", entry))
+            .add(entry);
+        projects
+            .entry(entry.project_path.clone())
+            .or_insert_with(|| UsageReportRow::new("project", entry))
+            .add(entry);
+    }
+
+    let by_cost = |a: &UsageReportRow, b: &UsageReportRow| b.total_cost.total_cmp(&a.total_cost);
+    let mut sessions: Vec<_> = sessions.into_values().collect();
+    let mut projects: Vec<_> = projects.into_values().collect();
+    sessions.sort_by(by_cost);
+    projects.sort_by(by_cost);
+    (sessions, projects)
+}
+
+/// Keep only the requested columns of a row, in the requested order. A list
+/// rather than a map: serde_json maps sort their keys.
+fn select_columns(
+    row: &UsageReportRow,
+    columns: &[String],
+) -> Result<Vec<(String, serde_json::Value)>, String> {
+    let value = serde_json::to_value(row).map_err(|e| e.to_string())?;
+    Ok(columns
+        .iter()
+        .map(|c| (c.clone(), value.get(c).cloned().unwrap_or_default()))
+        .collect())
+}
+
+fn to_object(fields: Vec<(String, serde_json::Value)>) -> serde_json::Value {
+    serde_json::Value::Object(fields.into_iter().collect())
+}
+
+fn csv_field(value: &serde_json::Value) -> String {
+    let text = match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .filter_map(|v| v.as_str())
+            .collect::<Vec<_>>()
+            .join(";"),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+/// Export per-session and per-project usage to a CSV or JSON file.
+/// `columns` defaults to every column in `REPORT_COLUMNS`.
+#[command]
+pub fn export_usage_report(
+    range: Option<UsageRange>,
+    format: String,
+    path: String,
+    columns: Option<Vec<String>>,
+) -> Result<UsageExportResult, String> {
+    let format = format.to_lowercase();
+    if format != "csv" && format != "json" {
+        return Err(format!("Unsupported export format: {}", format));
+    }
+
+    let columns = match columns.filter(|c| !c.is_empty()) {
+        Some(columns) => {
+            if let Some(unknown) = columns
+                .iter()
+                .find(|c| !REPORT_COLUMNS.contains(&c.as_str()))
+            {
+                return Err(format!("Unknown usage report column: {}", unknown));
+            }
+            columns
+        }
+        None => REPORT_COLUMNS.iter().map(|c| c.to_string()).collect(),
+    };
+
+    let range = range.unwrap_or_default();
+    let entries = entries_in_range(&range)?;
+
+    let (sessions, projects) = build_report_rows(&entries);
+    let total_cost = projects.iter().map(|p| p.total_cost).sum();
+
+    let content = if format == "csv" {
+        // One table; the `scope` column tells session rows from project rows
+        let mut out = columns.join(",");
+        out.push('\n');
+        for row in sessions.iter().chain(projects.iter()) {
+            let fields = select_columns(row, &columns)?;
+            let line: Vec<String> = fields.iter().map(|(_, value)| csv_field(value)).collect();
+            out.push_str(&line.join(","));
+            out.push('\n');
+        }
+        out
+    } else {
+        let sessions = sessions
+            .iter()
+            .map(|row| select_columns(row, &columns).map(to_object))
+            .collect::<Result<Vec<_>, _>>()?;
+        let projects = projects
+            .iter()
+            .map(|row| select_columns(row, &columns).map(to_object))
+            .collect::<Result<Vec<_>, _>>()?;
+        let report = serde_json::json!({
+            "generated_at": Local::now().to_rfc3339(),
+            "range": range,
+            "total_cost": total_cost,
+            "sessions": sessions,
+            "projects": projects,
+        });
+        serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?
+    };
+
+    let target = PathBuf::from(&path);
+    if let Some(parent) = target.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create export directory: {}", e))?;
+    }
+    fs::write(&target, content).map_err(|e| format!("Failed to write usage report: {}", e))?;
+
+    Ok(UsageExportResult {
+        path,
+        format,
+        session_count: sessions.len(),
+        project_count: projects.len(),
+        total_cost,
+    })
+}
+
+/// Invocation statistics of one tool
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ToolStats {
+    tool: String,
+    invocations: u64,
+    failures: u64,
+    /// Calls declined at the permission prompt; not counted as failures
+    rejections: u64,
+    /// Failures over calls that completed or failed
+    failure_rate: f64,
+    average_duration_ms: Option<f64>,
+}
+
+/// A file and how many successful edits it received
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EditedFile {
+    path: String,
+    edits: u64,
+}
+
+/// Result of `get_tool_usage_stats`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ToolUsageStats {
+    total_invocations: u64,
+    /// Most used first
+    tools: Vec<ToolStats>,
+    most_edited_files: Vec<EditedFile>,
+}
+
+/// Files listed in `most_edited_files`
+const MOST_EDITED_LIMIT: usize = 20;
+
+/// Per-tool invocation counts, failure rates and durations from the transcript
+/// index, plus the files edited most often
+#[command]
+pub fn get_tool_usage_stats(
+    project: Option<String>,
+    range: Option<UsageRange>,
+) -> Result<ToolUsageStats, String> {
+    let calls = tool_calls_in_range(project.as_deref(), &range.unwrap_or_default())?;
+
+    // (invocations, failures, rejections, finished, total duration, timed calls)
+    let mut by_tool: HashMap<&str, (u64, u64, u64, u64, u64, u64)> = HashMap::new();
+    let mut edits: HashMap<&str, u64> = HashMap::new();
+    for call in &calls {
+        let stats = by_tool.entry(call.tool.as_str()).or_default();
+        stats.0 += 1;
+        match call.outcome {
+            ToolOutcome::Success => stats.3 += 1,
+            ToolOutcome::Error => {
+                stats.1 += 1;
+                stats.3 += 1;
+            }
+            ToolOutcome::Rejected => stats.2 += 1,
+            ToolOutcome::Unknown => {}
+        }
+        if let Some(duration) = call
+            .duration_ms
+            .filter(|_| call.outcome != ToolOutcome::Rejected)
+        {
+            stats.4 += duration;
+            stats.5 += 1;
+        }
+        if call.outcome == ToolOutcome::Success && EDIT_TOOLS.contains(&call.tool.as_str()) {
+            if let Some(path) = &call.subject {
+                *edits.entry(path.as_str()).or_default() += 1;
+            }
+        }
+    }
+
+    let mut tools: Vec<ToolStats> = by_tool
+        .into_iter()
+        .map(
+            |(tool, (invocations, failures, rejections, finished, duration, timed))| ToolStats {
+                tool: tool.to_string(),
+                invocations,
+                failures,
+                rejections,
+                failure_rate: if finished > 0 {
+                    failures as f64 / finished as f64
+                } else {
+                    0.0
+                },
+                average_duration_ms: Some(duration as f64 / timed as f64).filter(|_| timed > 0),
+            },
+        )
+        .collect();
+    tools.sort_by(|a, b| {
+        b.invocations
+            .cmp(&a.invocations)
+            .then_with(|| a.tool.cmp(&b.tool))
+    });
+
+    let mut most_edited_files: Vec<EditedFile> = edits
+        .into_iter()
+        .map(|(path, edits)| EditedFile {
+            path: path.to_string(),
+            edits,
+        })
+        .collect();
+    most_edited_files.sort_by(|a, b| b.edits.cmp(&a.edits).then_with(|| a.path.cmp(&b.path)));
+    most_edited_files.truncate(MOST_EDITED_LIMIT);
+
+    Ok(ToolUsageStats {
+        total_invocations: calls.len() as u64,
+        tools,
+        most_edited_files,
+    })
+}
@@ -0,0 +1,179 @@
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+use super::config_io::write_atomic;
+use super::enhanced_hooks::{trigger_hook_event, HookContext};
+
+/// Bumped when the on-disk layout changes incompatibly
+const WORKSPACE_VERSION: u32 = 1;
+
+/// A single open tab
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceTab {
+    pub id: String,
+    pub title: String,
+    /// Tab type as understood by the frontend (e.g. "session", "settings")
+    #[serde(default)]
+    pub kind: String,
+    pub project_path: Option<String>,
+    pub session_id: Option<String>,
+    /// Vertical scroll offset of the message list
+    #[serde(default)]
+    pub scroll_position: f64,
+    /// Frontend-owned extra state, persisted as-is
+    #[serde(default)]
+    pub extra: serde_json::Value,
+}
+
+/// All persisted workspace state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceState {
+    #[serde(default)]
+    pub version: u32,
+    pub tabs: Vec<WorkspaceTab>,
+    pub active_tab_id: Option<String>,
+    /// Unix timestamp of the last save
+    #[serde(default)]
+    pub updated_at: u64,
+}
+
+impl Default for WorkspaceState {
+    fn default() -> Self {
+        Self {
+            version: WORKSPACE_VERSION,
+            tabs: Vec::new(),
+            active_tab_id: None,
+            updated_at: 0,
+        }
+    }
+}
+
+/// Location of the workspace file inside the app data dir
+fn workspace_file(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = crate::paths::app_data_dir(app)
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    Ok(dir.join("workspace.json"))
+}
+
+/// Read the saved workspace, falling back to an empty one if missing or unreadable
+pub(crate) fn load_workspace(app: &AppHandle) -> Result<WorkspaceState, String> {
+    let path = workspace_file(app)?;
+    if !path.exists() {
+        return Ok(WorkspaceState::default());
+    }
+
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read workspace: {}", e))?;
+    match serde_json::from_str::<WorkspaceState>(&content) {
+        Ok(state) if state.version <= WORKSPACE_VERSION => Ok(state),
+        Ok(state) => {
+            warn!(
+                "Workspace file version {} is newer than supported {}, starting fresh",
+                state.version, WORKSPACE_VERSION
+            );
+            Ok(WorkspaceState::default())
+        }
+        Err(e) => {
+            warn!("Workspace file is corrupt, starting fresh: {}", e);
+            Ok(WorkspaceState::default())
+        }
+    }
+}
+
+fn store_workspace(app: &AppHandle, state: &WorkspaceState) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(state)
+        .map_err(|e| format!("Failed to serialize workspace: {}", e))?;
+    write_atomic(&workspace_file(app)?, json.as_bytes())
+}
+
+/// Fire OnTabSwitch hooks for the newly focused tab in the background
+fn emit_tab_switch(app: &AppHandle, previous: Option<&WorkspaceTab>, current: &WorkspaceTab) {
+    let project_path = match &current.project_path {
+        Some(path) => path.clone(),
+        // Hooks are loaded from the project's settings, so there is nothing to run
+        None => return,
+    };
+
+    let context = HookContext {
+        event: "OnTabSwitch".to_string(),
+        session_id: current.session_id.clone().unwrap_or_default(),
+        project_path,
+        data: serde_json::json!({
+            "from_tab": previous,
+            "to_tab": current,
+        }),
+    };
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = trigger_hook_event(app, "OnTabSwitch".to_string(), context, None).await {
+            warn!("OnTabSwitch hooks failed: {}", e);
+        }
+    });
+}
+
+// ============ Tauri Commands ============
+
+/// Get the persisted workspace so the frontend can restore tabs on launch
+#[tauri::command]
+pub async fn get_workspace_state(app: AppHandle) -> Result<WorkspaceState, String> {
+    let state = load_workspace(&app)?;
+    info!("Restoring workspace with {} tabs", state.tabs.len());
+    Ok(state)
+}
+
+/// Persist the workspace; called by the frontend whenever tabs change
+#[tauri::command]
+pub async fn save_workspace_state(app: AppHandle, state: WorkspaceState) -> Result<(), String> {
+    let previous = load_workspace(&app)?;
+
+    let mut state = state;
+    state.version = WORKSPACE_VERSION;
+    state.updated_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    // Drop a dangling active tab rather than restoring into nothing
+    if let Some(active) = &state.active_tab_id {
+        if !state.tabs.iter().any(|t| &t.id == active) {
+            state.active_tab_id = state.tabs.first().map(|t| t.id.clone());
+        }
+    }
+
+    store_workspace(&app, &state)?;
+    debug!("Saved workspace with {} tabs", state.tabs.len());
+
+    if state.active_tab_id != previous.active_tab_id {
+        let current = state
+            .active_tab_id
+            .as_ref()
+            .and_then(|id| state.tabs.iter().find(|t| &t.id == id));
+        let before = previous
+            .active_tab_id
+            .as_ref()
+            .and_then(|id| previous.tabs.iter().find(|t| &t.id == id));
+        if let Some(current) = current {
+            emit_tab_switch(&app, before, current);
+        }
+    }
+
+    Ok(())
+}
+
+/// Forget all saved tabs
+#[tauri::command]
+pub async fn clear_workspace_state(app: AppHandle) -> Result<(), String> {
+    info!("Clearing workspace state");
+    let path = workspace_file(&app)?;
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to remove workspace: {}", e))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,209 @@
+//! Crate-wide error type
+//!
+//! Commands have historically returned `Result<_, String>`. `WorkbenchError`
+//! keeps the kind and source of a failure and serializes for the frontend as
+//!
+//! ```json
+//! { "code": "git", "message": "...", "detail": "...", "hint": "...", "retryable": false }
+//! ```
+//!
+//! so it can show structured errors and decide whether to offer a retry. It
+//! converts to and from `String`, so migrated modules interoperate with the
+//! ones still using string errors through `?`.
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum WorkbenchError {
+    /// The caller passed something unusable
+    #[error("{0}")]
+    InvalidInput(String),
+
+    #[error("{0}")]
+    NotFound(String),
+
+    #[error("{context}: {source}")]
+    Io {
+        context: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("{context}: {source}")]
+    Json {
+        context: String,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+
+    /// A git command exited unsuccessfully
+    #[error("Git {command} failed: {stderr}")]
+    Git { command: String, stderr: String },
+
+    #[error("{what} timed out after {secs}s")]
+    Timeout { what: String, secs: u64 },
+
+    /// Not allowed in the current state of the target, e.g. a running session
+    #[error("{0}")]
+    InvalidState(String),
+
+    /// A file changed since it was read; `id` names the held conflict
+    #[error("{path} changed on disk since it was loaded")]
+    Conflict { path: String, id: String },
+
+    /// Refused by a policy, approval or hook
+    #[error("{0}")]
+    Blocked(String),
+
+    /// Aborted through `cancel_operation` or a dedicated cancel command
+    #[error("{0} was cancelled")]
+    Cancelled(String),
+
+    /// The task running the operation went away before it finished
+    #[error("{0} stopped before finishing")]
+    Dropped(String),
+
+    /// Errors from modules that still use strings
+    #[error("{0}")]
+    Other(String),
+}
+
+pub type WorkbenchResult<T> = Result<T, WorkbenchError>;
+
+impl WorkbenchError {
+    pub fn io(context: impl Into<String>, source: std::io::Error) -> Self {
+        Self::Io {
+            context: context.into(),
+            source,
+        }
+    }
+
+    pub fn json(context: impl Into<String>, source: serde_json::Error) -> Self {
+        Self::Json {
+            context: context.into(),
+            source,
+        }
+    }
+
+    /// A failed git invocation, from its name (e.g. `diff`) and stderr
+    pub fn git(command: impl Into<String>, stderr: &[u8]) -> Self {
+        Self::Git {
+            command: command.into(),
+            stderr: String::from_utf8_lossy(stderr).trim().to_string(),
+        }
+    }
+
+    /// Stable identifier the frontend can switch on
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidInput(_) => "invalid_input",
+            Self::NotFound(_) => "not_found",
+            Self::Io { .. } => "io",
+            Self::Json { .. } => "json",
+            Self::Database(_) => "database",
+            Self::Git { .. } => "git",
+            Self::Timeout { .. } => "timeout",
+            Self::InvalidState(_) => "invalid_state",
+            Self::Conflict { .. } => "conflict",
+            Self::Blocked(_) => "blocked",
+            Self::Cancelled(_) => "cancelled",
+            Self::Dropped(_) => "dropped",
+            Self::Other(_) => "error",
+        }
+    }
+
+    /// Technical detail beyond the message, if any
+    pub fn detail(&self) -> Option<String> {
+        match self {
+            Self::Io { source, .. } => Some(format!("{:?}", source.kind())),
+            Self::Json { source, .. } => Some(format!(
+                "line {}, column {}",
+                source.line(),
+                source.column()
+            )),
+            Self::Database(source) => source.sqlite_error_code().map(|code| format!("{:?}", code)),
+            Self::Git { stderr, .. } if !stderr.is_empty() => Some(stderr.clone()),
+            Self::Conflict { id, .. } => Some(id.clone()),
+            _ => None,
+        }
+    }
+
+    /// What the user can do about it
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Self::Git { stderr, .. } if stderr.contains("not a git repository") => {
+                Some("Initialize a git repository in the project first")
+            }
+            Self::Git { stderr, .. }
+                if stderr.contains("unknown revision") || stderr.contains("bad revision") =>
+            {
+                Some("The commit no longer exists; refresh and try again")
+            }
+            Self::Git { stderr, .. } if stderr.contains("index.lock") => {
+                Some("Another git process is running in this repository")
+            }
+            Self::Conflict { .. } => Some("Review both versions and choose which changes to keep"),
+            Self::Timeout { .. } => Some("Raise the timeout or check what the command waits for"),
+            Self::Dropped(_) => Some("The operation stopped unexpectedly; try again"),
+            Self::Json { .. } => Some("Fix the JSON syntax in the file"),
+            Self::Io { source, .. } if source.kind() == std::io::ErrorKind::PermissionDenied => {
+                Some("Check the file permissions")
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the same call may succeed if simply repeated
+    pub fn retryable(&self) -> bool {
+        match self {
+            Self::Timeout { .. } | Self::Dropped(_) => true,
+            Self::Io { source, .. } => matches!(
+                source.kind(),
+                std::io::ErrorKind::Interrupted
+                    | std::io::ErrorKind::WouldBlock
+                    | std::io::ErrorKind::TimedOut
+            ),
+            Self::Database(source) => matches!(
+                source.sqlite_error_code(),
+                Some(rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked)
+            ),
+            Self::Git { stderr, .. } => stderr.contains("index.lock"),
+            _ => false,
+        }
+    }
+}
+
+impl Serialize for WorkbenchError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("WorkbenchError", 5)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("detail", &self.detail())?;
+        state.serialize_field("hint", &self.hint())?;
+        state.serialize_field("retryable", &self.retryable())?;
+        state.end()
+    }
+}
+
+impl From<String> for WorkbenchError {
+    fn from(message: String) -> Self {
+        Self::Other(message)
+    }
+}
+
+impl From<&str> for WorkbenchError {
+    fn from(message: &str) -> Self {
+        Self::Other(message.to_string())
+    }
+}
+
+impl From<WorkbenchError> for String {
+    fn from(error: WorkbenchError) -> Self {
+        error.to_string()
+    }
+}
@@ -0,0 +1,540 @@
+use log::warn;
+/// Typed application events
+///
+/// Backend code announces what happened by passing an `AppEvent` to `emit`
+/// rather than calling `Emitter::emit` with a channel name it spells itself.
+/// Each variant owns its channel, named `<area>-<what>` with a `:<id>` suffix
+/// for events scoped to one session, run or terminal (`hook-chain-complete:{session_id}`),
+/// and its payload type, so the names and shapes the frontend listens for are
+/// defined in one place.
+///
+/// Events about a project go only to the windows showing it when projects are
+/// open in windows of their own (see `commands::project_windows`); the rest go
+/// to every window.
+///
+/// Besides going to the frontend, every event is handed to the backend
+/// subscribers registered with `subscribe`, so one module can react to another's
+/// events (notifications for failed hooks) without a round-trip through the UI.
+/// Subscribers run on the emitting thread and must return quickly; ones that only
+/// matter for a while (a WebSocket waiting for its session to end) remove
+/// themselves with `unsubscribe`.
+///
+/// High-volume streams that only the frontend reads skip the variants and the
+/// subscribers: Claude output goes through `emit_stream`, which scopes it to the
+/// project's windows like any other event, while log records, search results,
+/// usage indexing and update download progress still use plain channels.
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use tauri::{AppHandle, Emitter, EventTarget, Manager};
+
+use crate::commands::activity_report::RenderedReport;
+use crate::commands::checkpoints::Checkpoint;
+use crate::commands::config_watcher::ConfigChange;
+use crate::commands::deep_link::DeepLink;
+use crate::commands::enhanced_hooks::{HookExecutionResult, HookOutputLine};
+use crate::commands::hook_approval::PendingHook;
+use crate::commands::idle::IdleChange;
+use crate::commands::mcp_health::McpServerHealth;
+use crate::commands::network::NetworkStatus;
+use crate::commands::orchestration::{Orchestration, OrchestrationSummary};
+use crate::commands::project_windows;
+use crate::commands::prompt_queue::QueuedPrompt;
+use crate::commands::quick_prompt::QuickPromptTarget;
+use crate::commands::retention::RetentionRun;
+use crate::commands::run_scheduler::ScheduledRun;
+use crate::commands::session_batch::BatchProgress;
+use crate::commands::session_state::SessionStateChange;
+use crate::commands::session_status::SessionStatus;
+use crate::commands::settings_sync::SyncStatus;
+use crate::commands::terminal::{TerminalExit, TerminalInfo};
+use crate::commands::updater::UpdateStatus;
+use crate::process::OrphanedProcess;
+
+/// Payload of `hook-run-started`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookRunStarted {
+    pub run_id: i64,
+    pub event: String,
+    pub session_id: String,
+    pub command: String,
+}
+
+/// Payload of `hooks-pending-approval`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HooksPendingApproval {
+    pub project_path: String,
+    pub hooks: Vec<PendingHook>,
+}
+
+/// Payload of `command-blocked`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandBlocked {
+    pub project_path: String,
+    /// "hook" or "terminal"
+    pub source: String,
+    pub command: String,
+    pub rule: String,
+    pub reason: String,
+    pub timestamp: String,
+}
+
+/// Payload of `checkpoint-created`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointCreated {
+    pub project_path: String,
+    /// Name of the checkpoint policy that fired
+    pub policy: String,
+    pub checkpoint: Checkpoint,
+}
+
+/// Payload of `activity-summary`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivitySummary {
+    /// Short text for a screen reader
+    pub text: String,
+    /// Whether it reports a failure and should interrupt (`aria-live="assertive"`)
+    pub urgent: bool,
+    pub session_ids: Vec<String>,
+    pub at: String,
+}
+
+/// Payload of `claude-session-state`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaudeSessionState {
+    pub session_id: String,
+    pub project_path: String,
+    /// "started" or "stopped"
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env_profile: Option<String>,
+    /// Set once the process has stopped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub success: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Payload of `deep-link-error`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeepLinkError {
+    pub url: String,
+    pub error: String,
+}
+
+/// Payload of `output-mirror-stopped`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputMirrorStopped {
+    pub session_id: String,
+    pub path: String,
+    pub lines_written: u64,
+    /// Why the mirror stopped, None when the session ended
+    pub error: Option<String>,
+}
+
+/// Everything the backend announces through `emit`
+#[derive(Debug, Clone)]
+pub enum AppEvent {
+    /// `hook-run-started`: a hook's process was spawned
+    HookRunStarted(HookRunStarted),
+    /// `hook-output:{run_id}`: one line printed by a running hook
+    HookOutput(HookOutputLine),
+    /// `hook-chain-complete:{session_id}`: all hooks for an event have run; the
+    /// frontend receives the list of results
+    HookChainComplete {
+        session_id: String,
+        project_path: String,
+        event: String,
+        results: Vec<HookExecutionResult>,
+    },
+    /// `hooks-pending-approval`: project hooks skipped until the user approves them
+    HooksPendingApproval(HooksPendingApproval),
+    /// `session-state-changed`: a session moved to another lifecycle phase
+    SessionStateChanged(SessionStateChange),
+    /// `context-threshold`: a session's context crossed a warning threshold; the
+    /// payload is `data` plus `session_id` and `project_path`
+    ContextThreshold {
+        session_id: String,
+        project_path: String,
+        data: serde_json::Value,
+    },
+    /// `context-compacted:{session_id}`: a session's context was compacted
+    ContextCompacted {
+        session_id: String,
+        data: serde_json::Value,
+    },
+    /// `mcp-server-down`: a server failed enough health checks in a row
+    McpServerDown(McpServerHealth),
+    /// `mcp-server-recovered`: a server that was down answers again
+    McpServerRecovered(McpServerHealth),
+    /// `command-blocked`: the command policy refused a command
+    CommandBlocked(CommandBlocked),
+    /// `checkpoint-created`: a checkpoint policy created a checkpoint
+    CheckpointCreated(CheckpointCreated),
+    /// `terminal-created`: a terminal was opened, possibly by a hook
+    TerminalCreated(TerminalInfo),
+    /// `terminal-exit:{terminal_id}`: a terminal's process ended
+    TerminalExit(TerminalExit),
+    /// `user-idle`: no input for the configured idle time
+    UserIdle(IdleChange),
+    /// `user-resumed`: input again after `user-idle`
+    UserResumed(IdleChange),
+    /// `network-status`: the API became reachable or unreachable
+    NetworkStatus(NetworkStatus),
+    /// `activity-summary`: what happened since the last summary, condensed for
+    /// screen readers
+    ActivitySummary(ActivitySummary),
+    /// `claude-complete:{session_id}`, or `claude-complete` for listeners that
+    /// predate per-session channels: a Claude process ended; the frontend
+    /// receives whether it succeeded
+    ClaudeComplete {
+        session_id: Option<String>,
+        project_path: Option<String>,
+        success: bool,
+    },
+    /// `prompt-queue-changed:{session_id}`: prompts were queued, reordered,
+    /// cancelled or sent; the frontend receives the remaining queue
+    PromptQueueChanged {
+        session_id: String,
+        project_path: String,
+        queue: Vec<QueuedPrompt>,
+    },
+    /// `prompt-dispatched:{session_id}`: a queued prompt is being sent
+    PromptDispatched(QueuedPrompt),
+    /// `scheduled-run-updated`: a background run was queued, started or finished
+    ScheduledRunUpdated(ScheduledRun),
+    /// `scheduled-run-output:{run_id}`: one line of a background run's output
+    ScheduledRunOutput {
+        run_id: i64,
+        project_path: String,
+        line: String,
+    },
+    /// `orchestration-updated`: a run of an orchestration changed
+    OrchestrationUpdated(Orchestration),
+    /// `orchestration-output:{orchestration_id}`: one line of output from the
+    /// run at `index`
+    OrchestrationOutput {
+        orchestration_id: String,
+        index: usize,
+        line: String,
+    },
+    /// `orchestration-complete`: every run of an orchestration has finished
+    OrchestrationComplete(OrchestrationSummary),
+    /// `session-model-changed:{session_id}`: the session resumes with another
+    /// model from now on; the frontend receives the model
+    SessionModelChanged {
+        session_id: String,
+        project_path: Option<String>,
+        model: String,
+    },
+    /// `batch-progress`: one more item of a batch session operation is done
+    BatchProgress(BatchProgress),
+    /// `retention-completed`: a retention run archived sessions or hit errors
+    RetentionCompleted(RetentionRun),
+    /// `activity-report-generated`: the scheduled activity report was written
+    ActivityReportGenerated(RenderedReport),
+    /// `config-changed`: a settings file was edited outside the app
+    ConfigChanged(ConfigChange),
+    /// `claude-session-state`: a Claude session started or stopped
+    ClaudeSessionState(ClaudeSessionState),
+    /// `provider-switched`: another API provider is in use; the payload names
+    /// the new provider and the previous base URL and model, never credentials
+    ProviderSwitched(serde_json::Value),
+    /// `deep-link`: a link was received and queued for the UI
+    DeepLink(DeepLink),
+    /// `deep-link-error`: a received link could not be parsed
+    DeepLinkError(DeepLinkError),
+    /// `orphaned-processes-detected`: processes left running by a previous run
+    OrphanedProcessesDetected(Vec<OrphanedProcess>),
+    /// `session-status-changed`: the running sessions shown in the tray changed;
+    /// the frontend receives all of them
+    SessionStatusChanged(Vec<SessionStatus>),
+    /// `quick-prompt-submitted`: the quick prompt palette sent a prompt
+    QuickPromptSubmitted(QuickPromptTarget),
+    /// `update-status-changed`: the updater moved to another state
+    UpdateStatusChanged(UpdateStatus),
+    /// `update-downloaded`: an update was downloaded; the payload is its version
+    UpdateDownloaded(String),
+    /// `update-restart-deferred`: installing the downloaded update waits for
+    /// running sessions; the payload is its version
+    UpdateRestartDeferred(String),
+    /// `output-mirror-stopped`: a session's output mirror stopped writing
+    OutputMirrorStopped(OutputMirrorStopped),
+    /// `crash-reports-found`: crash reports from earlier runs are on disk; the
+    /// payload is how many
+    CrashReportsFound(usize),
+    /// `settings-sync-finished`: a settings sync round ended
+    SettingsSyncFinished(SyncStatus),
+    /// `claude-error:{session_id}`, or `claude-error` before the session id is
+    /// known: starting or running Claude failed; the frontend receives the message
+    ClaudeError {
+        session_id: Option<String>,
+        project_path: Option<String>,
+        message: String,
+    },
+    /// `claude-cancelled:{session_id}`, or `claude-cancelled` for listeners that
+    /// predate per-session channels: a Claude run was cancelled
+    ClaudeCancelled {
+        session_id: Option<String>,
+        project_path: Option<String>,
+    },
+}
+
+impl AppEvent {
+    /// Channel the frontend listens on
+    pub fn channel(&self) -> String {
+        match self {
+            Self::HookRunStarted(_) => "hook-run-started".to_string(),
+            Self::HookOutput(line) => format!("hook-output:{}", line.run_id),
+            Self::HookChainComplete { session_id, .. } => {
+                format!("hook-chain-complete:{}", session_id)
+            }
+            Self::HooksPendingApproval(_) => "hooks-pending-approval".to_string(),
+            Self::SessionStateChanged(_) => "session-state-changed".to_string(),
+            Self::ContextThreshold { .. } => "context-threshold".to_string(),
+            Self::ContextCompacted { session_id, .. } => {
+                format!("context-compacted:{}", session_id)
+            }
+            Self::McpServerDown(_) => "mcp-server-down".to_string(),
+            Self::McpServerRecovered(_) => "mcp-server-recovered".to_string(),
+            Self::CommandBlocked(_) => "command-blocked".to_string(),
+            Self::CheckpointCreated(_) => "checkpoint-created".to_string(),
+            Self::TerminalCreated(_) => "terminal-created".to_string(),
+            Self::TerminalExit(exit) => format!("terminal-exit:{}", exit.terminal_id),
+            Self::UserIdle(_) => "user-idle".to_string(),
+            Self::UserResumed(_) => "user-resumed".to_string(),
+            Self::NetworkStatus(_) => "network-status".to_string(),
+            Self::ActivitySummary(_) => "activity-summary".to_string(),
+            Self::ClaudeComplete { session_id, .. } => match session_id {
+                Some(session_id) => format!("claude-complete:{}", session_id),
+                None => "claude-complete".to_string(),
+            },
+            Self::PromptQueueChanged { session_id, .. } => {
+                format!("prompt-queue-changed:{}", session_id)
+            }
+            Self::PromptDispatched(prompt) => format!("prompt-dispatched:{}", prompt.session_id),
+            Self::ScheduledRunUpdated(_) => "scheduled-run-updated".to_string(),
+            Self::ScheduledRunOutput { run_id, .. } => format!("scheduled-run-output:{}", run_id),
+            Self::OrchestrationUpdated(_) => "orchestration-updated".to_string(),
+            Self::OrchestrationOutput {
+                orchestration_id, ..
+            } => format!("orchestration-output:{}", orchestration_id),
+            Self::OrchestrationComplete(_) => "orchestration-complete".to_string(),
+            Self::SessionModelChanged { session_id, .. } => {
+                format!("session-model-changed:{}", session_id)
+            }
+            Self::BatchProgress(_) => "batch-progress".to_string(),
+            Self::RetentionCompleted(_) => "retention-completed".to_string(),
+            Self::ActivityReportGenerated(_) => "activity-report-generated".to_string(),
+            Self::ConfigChanged(_) => "config-changed".to_string(),
+            Self::ClaudeSessionState(_) => "claude-session-state".to_string(),
+            Self::ProviderSwitched(_) => "provider-switched".to_string(),
+            Self::DeepLink(_) => "deep-link".to_string(),
+            Self::DeepLinkError(_) => "deep-link-error".to_string(),
+            Self::OrphanedProcessesDetected(_) => "orphaned-processes-detected".to_string(),
+            Self::SessionStatusChanged(_) => "session-status-changed".to_string(),
+            Self::QuickPromptSubmitted(_) => "quick-prompt-submitted".to_string(),
+            Self::UpdateStatusChanged(_) => "update-status-changed".to_string(),
+            Self::UpdateDownloaded(_) => "update-downloaded".to_string(),
+            Self::UpdateRestartDeferred(_) => "update-restart-deferred".to_string(),
+            Self::OutputMirrorStopped(_) => "output-mirror-stopped".to_string(),
+            Self::CrashReportsFound(_) => "crash-reports-found".to_string(),
+            Self::SettingsSyncFinished(_) => "settings-sync-finished".to_string(),
+            Self::ClaudeError { session_id, .. } => match session_id {
+                Some(session_id) => format!("claude-error:{}", session_id),
+                None => "claude-error".to_string(),
+            },
+            Self::ClaudeCancelled { session_id, .. } => match session_id {
+                Some(session_id) => format!("claude-cancelled:{}", session_id),
+                None => "claude-cancelled".to_string(),
+            },
+        }
+    }
+
+    /// Project the event is about, which decides the windows it goes to
+    pub fn project_path(&self) -> Option<&str> {
+        match self {
+            Self::HookChainComplete { project_path, .. }
+            | Self::ContextThreshold { project_path, .. } => Some(project_path),
+            Self::HooksPendingApproval(pending) => Some(&pending.project_path),
+            Self::SessionStateChanged(change) => change.project_path.as_deref(),
+            Self::CommandBlocked(blocked) => Some(&blocked.project_path),
+            Self::CheckpointCreated(created) => Some(&created.project_path),
+            Self::TerminalCreated(info) => Some(&info.project_path),
+            Self::PromptQueueChanged { project_path, .. }
+            | Self::ScheduledRunOutput { project_path, .. } => Some(project_path),
+            Self::PromptDispatched(prompt) => Some(&prompt.project_path),
+            Self::ScheduledRunUpdated(run) => Some(&run.project_path),
+            Self::SessionModelChanged { project_path, .. }
+            | Self::ClaudeComplete { project_path, .. }
+            | Self::ClaudeError { project_path, .. }
+            | Self::ClaudeCancelled { project_path, .. } => project_path.as_deref(),
+            Self::QuickPromptSubmitted(target) => Some(&target.project_path),
+            Self::ConfigChanged(change) => change.project_path.as_deref(),
+            Self::ClaudeSessionState(state) => Some(&state.project_path),
+            _ => None,
+        }
+    }
+
+    /// What the frontend receives on `channel`
+    pub fn payload(&self) -> serde_json::Result<serde_json::Value> {
+        match self {
+            Self::HookRunStarted(started) => serde_json::to_value(started),
+            Self::HookOutput(line) => serde_json::to_value(line),
+            Self::HookChainComplete { results, .. } => serde_json::to_value(results),
+            Self::HooksPendingApproval(pending) => serde_json::to_value(pending),
+            Self::SessionStateChanged(change) => serde_json::to_value(change),
+            Self::ContextThreshold {
+                session_id,
+                project_path,
+                data,
+            } => {
+                let mut payload = data.clone();
+                payload["session_id"] = session_id.as_str().into();
+                payload["project_path"] = project_path.as_str().into();
+                Ok(payload)
+            }
+            Self::ContextCompacted { data, .. } => Ok(data.clone()),
+            Self::McpServerDown(health) | Self::McpServerRecovered(health) => {
+                serde_json::to_value(health)
+            }
+            Self::CommandBlocked(blocked) => serde_json::to_value(blocked),
+            Self::CheckpointCreated(created) => serde_json::to_value(created),
+            Self::TerminalCreated(info) => serde_json::to_value(info),
+            Self::TerminalExit(exit) => serde_json::to_value(exit),
+            Self::UserIdle(change) | Self::UserResumed(change) => serde_json::to_value(change),
+            Self::NetworkStatus(status) => serde_json::to_value(status),
+            Self::ActivitySummary(summary) => serde_json::to_value(summary),
+            Self::ClaudeComplete { success, .. } => serde_json::to_value(success),
+            Self::PromptQueueChanged { queue, .. } => serde_json::to_value(queue),
+            Self::PromptDispatched(prompt) => serde_json::to_value(prompt),
+            Self::ScheduledRunUpdated(run) => serde_json::to_value(run),
+            Self::ScheduledRunOutput { line, .. } => serde_json::to_value(line),
+            Self::OrchestrationUpdated(orchestration) => serde_json::to_value(orchestration),
+            Self::OrchestrationOutput { index, line, .. } => Ok(serde_json::json!({
+                "index": index,
+                "line": line,
+            })),
+            Self::OrchestrationComplete(summary) => serde_json::to_value(summary),
+            Self::SessionModelChanged { model, .. } => serde_json::to_value(model),
+            Self::BatchProgress(progress) => serde_json::to_value(progress),
+            Self::RetentionCompleted(run) => serde_json::to_value(run),
+            Self::ActivityReportGenerated(rendered) => serde_json::to_value(rendered),
+            Self::ConfigChanged(change) => serde_json::to_value(change),
+            Self::ClaudeSessionState(state) => serde_json::to_value(state),
+            Self::ProviderSwitched(data) => Ok(data.clone()),
+            Self::DeepLink(link) => serde_json::to_value(link),
+            Self::DeepLinkError(error) => serde_json::to_value(error),
+            Self::OrphanedProcessesDetected(orphans) => serde_json::to_value(orphans),
+            Self::SessionStatusChanged(sessions) => serde_json::to_value(sessions),
+            Self::QuickPromptSubmitted(target) => serde_json::to_value(target),
+            Self::UpdateStatusChanged(status) => serde_json::to_value(status),
+            Self::UpdateDownloaded(version) | Self::UpdateRestartDeferred(version) => {
+                serde_json::to_value(version)
+            }
+            Self::OutputMirrorStopped(stopped) => serde_json::to_value(stopped),
+            Self::CrashReportsFound(count) => serde_json::to_value(count),
+            Self::SettingsSyncFinished(status) => serde_json::to_value(status),
+            Self::ClaudeError { message, .. } => serde_json::to_value(message),
+            Self::ClaudeCancelled { .. } => Ok(true.into()),
+        }
+    }
+}
+
+type Subscriber = Arc<dyn Fn(&AppHandle, &AppEvent) + Send + Sync>;
+
+/// Backend subscribers of `AppEvent`s, with the ids `subscribe` handed out
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: RwLock<Vec<(u64, Subscriber)>>,
+    next_id: AtomicU64,
+}
+
+/// Call `subscriber` for every event emitted from now on. Returns the id to
+/// pass to `unsubscribe`, or None when the subscriber was dropped.
+pub fn subscribe<F>(app: &AppHandle, subscriber: F) -> Option<u64>
+where
+    F: Fn(&AppHandle, &AppEvent) + Send + Sync + 'static,
+{
+    let bus = match app.try_state::<EventBus>() {
+        Some(bus) => bus,
+        None => {
+            warn!("Event bus not initialized, subscriber dropped");
+            return None;
+        }
+    };
+    let mut subscribers = match bus.subscribers.write() {
+        Ok(subscribers) => subscribers,
+        Err(e) => {
+            warn!("Failed to add event subscriber: {}", e);
+            return None;
+        }
+    };
+    let id = bus.next_id.fetch_add(1, Ordering::Relaxed);
+    subscribers.push((id, Arc::new(subscriber)));
+    Some(id)
+}
+
+/// Stop calling the subscriber `subscribe` returned `id` for
+pub fn unsubscribe(app: &AppHandle, id: u64) {
+    if let Some(bus) = app.try_state::<EventBus>() {
+        if let Ok(mut subscribers) = bus.subscribers.write() {
+            subscribers.retain(|(subscriber_id, _)| *subscriber_id != id);
+        }
+    }
+}
+
+/// Send `event` to the frontend and to every backend subscriber
+pub fn emit(app: &AppHandle, event: AppEvent) {
+    let channel = event.channel();
+    match event.payload() {
+        Ok(payload) => emit_stream(app, event.project_path(), &channel, payload),
+        Err(e) => warn!("Failed to serialize {} payload: {}", channel, e),
+    }
+
+    // Clone the list so subscribers can emit events themselves
+    let subscribers = match app.try_state::<EventBus>() {
+        Some(bus) => match bus.subscribers.read() {
+            Ok(subscribers) => subscribers.clone(),
+            Err(_) => return,
+        },
+        None => return,
+    };
+    for (_, subscriber) in subscribers {
+        subscriber(app, &event);
+    }
+}
+
+/// Send `payload` on a plain channel, only to the windows showing `project_path`
+/// when it is open in a window of its own. Backend `listen` handlers always
+/// receive it; subscribers never do.
+pub fn emit_stream<S: Serialize + Clone>(
+    app: &AppHandle,
+    project_path: Option<&str>,
+    channel: &str,
+    payload: S,
+) {
+    let windows =
+        project_path.and_then(|project| project_windows::windows_for_project(app, project));
+    let sent = match windows {
+        Some(labels) => app.emit_filter(channel, payload, |target| match target {
+            EventTarget::App => true,
+            EventTarget::WebviewWindow { label }
+            | EventTarget::Webview { label }
+            | EventTarget::Window { label }
+            | EventTarget::AnyLabel { label } => labels.contains(label),
+            _ => false,
+        }),
+        None => app.emit(channel, payload),
+    };
+    if let Err(e) = sent {
+        warn!("Failed to emit {}: {}", channel, e);
+    }
+}
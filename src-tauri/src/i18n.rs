@@ -0,0 +1,151 @@
+//! Message catalog for user-facing text produced in the backend
+//!
+//! Messages are Fluent files under `locales/<locale>/workbench.ftl`, compiled
+//! into the binary. The locale is negotiated from the one the user picked (see
+//! `commands::locale`) or the system locale against the catalogs available, and
+//! `en-US` stands in for unknown locales and for messages a catalog lacks.
+//!
+//! ```text
+//! i18n::t("notification-session-finished");
+//! i18n::t_args("policy-blocked-binary", &[("binary", binary.as_str().into())]);
+//! ```
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use fluent_langneg::{negotiate_languages, NegotiationStrategy};
+use log::warn;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use unic_langid::LanguageIdentifier;
+
+pub use fluent_bundle::FluentValue;
+
+const DEFAULT_LOCALE: &str = "en-US";
+
+/// Catalogs compiled into the binary, by locale
+const CATALOGS: &[(&str, &str)] = &[
+    ("en-US", include_str!("../locales/en-US/workbench.ftl")),
+    ("zh-CN", include_str!("../locales/zh-CN/workbench.ftl")),
+];
+
+static BUNDLES: Lazy<HashMap<&'static str, FluentBundle<FluentResource>>> = Lazy::new(|| {
+    CATALOGS
+        .iter()
+        .filter_map(|(locale, source)| {
+            let langid: LanguageIdentifier = locale.parse().ok()?;
+            let resource =
+                FluentResource::try_new(source.to_string()).unwrap_or_else(|(resource, errors)| {
+                    warn!("Errors in the {} catalog: {:?}", locale, errors);
+                    resource
+                });
+            let mut bundle = FluentBundle::new_concurrent(vec![langid]);
+            // Isolation marks end up in logs and notifications as stray characters
+            bundle.set_use_isolating(false);
+            if let Err(errors) = bundle.add_resource(resource) {
+                warn!("Duplicate messages in the {} catalog: {:?}", locale, errors);
+            }
+            Some((*locale, bundle))
+        })
+        .collect()
+});
+
+static LOCALE: Lazy<RwLock<String>> = Lazy::new(|| RwLock::new(negotiate(None)));
+
+/// Locales with a catalog
+pub fn available_locales() -> Vec<String> {
+    CATALOGS
+        .iter()
+        .map(|(locale, _)| locale.to_string())
+        .collect()
+}
+
+/// The OS locale as a language tag, e.g. `de-DE`
+pub fn system_locale() -> Option<String> {
+    // POSIX locales look like `de_DE.UTF-8`
+    sys_locale::get_locale().map(|locale| {
+        let tag = locale.split(['.', '@']).next().unwrap_or_default();
+        tag.replace('_', "-")
+    })
+}
+
+/// Best available locale for `requested`, or for the system locale if None
+pub fn negotiate(requested: Option<&str>) -> String {
+    let available: Vec<LanguageIdentifier> = CATALOGS
+        .iter()
+        .filter_map(|(locale, _)| locale.parse().ok())
+        .collect();
+    let default: LanguageIdentifier = DEFAULT_LOCALE.parse().expect("valid default locale");
+    let requested: Vec<LanguageIdentifier> = requested
+        .map(str::to_string)
+        .or_else(system_locale)
+        .and_then(|locale| locale.parse().ok())
+        .into_iter()
+        .collect();
+    negotiate_languages(
+        &requested,
+        &available,
+        Some(&default),
+        NegotiationStrategy::Filtering,
+    )
+    .first()
+    .map(|locale| locale.to_string())
+    .unwrap_or_else(|| DEFAULT_LOCALE.to_string())
+}
+
+/// Locale messages are currently rendered in
+pub fn current_locale() -> String {
+    LOCALE
+        .read()
+        .map(|locale| locale.clone())
+        .unwrap_or_else(|_| DEFAULT_LOCALE.to_string())
+}
+
+/// Render messages in the best match for `requested` (the system locale if
+/// None) from now on; returns the locale chosen
+pub fn set_locale(requested: Option<&str>) -> String {
+    let locale = negotiate(requested);
+    if let Ok(mut current) = LOCALE.write() {
+        *current = locale.clone();
+    }
+    locale
+}
+
+/// A message without arguments
+pub fn t(id: &str) -> String {
+    t_args(id, &[])
+}
+
+/// A message with named arguments; falls back to `en-US`, then to the id
+pub fn t_args(id: &str, args: &[(&str, FluentValue)]) -> String {
+    let fluent_args = (!args.is_empty()).then(|| {
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args {
+            fluent_args.set(*name, value.clone());
+        }
+        fluent_args
+    });
+
+    let locale = current_locale();
+    for locale in [locale.as_str(), DEFAULT_LOCALE] {
+        let bundle = match BUNDLES.get(locale) {
+            Some(bundle) => bundle,
+            None => continue,
+        };
+        let pattern = match bundle.get_message(id).and_then(|m| m.value()) {
+            Some(pattern) => pattern,
+            None => continue,
+        };
+        let mut errors = Vec::new();
+        let text = bundle.format_pattern(pattern, fluent_args.as_ref(), &mut errors);
+        if !errors.is_empty() {
+            warn!(
+                "Errors formatting message {} ({}): {:?}",
+                id, locale, errors
+            );
+        }
+        return text.into_owned();
+    }
+    warn!("Missing message {}", id);
+    id.to_string()
+}
@@ -0,0 +1,709 @@
+//! Claude Workbench core: the Tauri app and the modules shared with `workbench-cli`
+
+pub mod claude_binary;
+pub mod commands;
+pub mod error;
+pub mod events;
+pub mod i18n;
+pub mod logging;
+pub mod paths;
+pub mod process;
+pub mod profiling;
+
+use std::sync::{Arc, Mutex};
+
+use commands::claude::{
+    cancel_claude_execution, check_claude_version, clear_custom_claude_path, continue_claude_code,
+    delete_project, delete_project_permanently, enhance_prompt, enhance_prompt_with_gemini,
+    execute_claude_code, find_claude_md_files, get_available_tools, get_claude_execution_config,
+    get_claude_path, get_claude_permission_config, get_claude_session_output, get_claude_settings,
+    get_hooks_config, get_permission_presets, get_project_sessions, get_system_prompt,
+    list_directory_contents, list_hidden_projects, list_projects, list_running_claude_sessions,
+    load_session_history, open_new_session, read_claude_md_file, reset_claude_execution_config,
+    restore_project, resume_claude_code, save_claude_md_file, save_claude_settings,
+    save_system_prompt, search_files, set_custom_claude_path, update_claude_execution_config,
+    update_claude_permission_config, update_hooks_config, update_thinking_mode,
+    validate_hook_command, validate_permission_config, ClaudeProcessState,
+};
+use commands::mcp::{
+    import_mcp_from_claude_desktop, import_mcp_from_cursor, mcp_add, mcp_add_from_claude_desktop,
+    mcp_add_json, mcp_config_add, mcp_config_list, mcp_config_remove, mcp_config_set_enabled,
+    mcp_export_config, mcp_get, mcp_get_server_status, mcp_list, mcp_read_project_config,
+    mcp_remove, mcp_reset_project_choices, mcp_save_project_config, mcp_serve,
+    mcp_test_connection, mcp_test_server_config, mcp_validate_server,
+};
+use commands::storage::{init_database, AgentDb};
+
+use commands::clipboard::{read_from_clipboard, save_clipboard_image, write_to_clipboard};
+use commands::prompt_tracker::{
+    check_rewind_capabilities, get_prompt_list, get_unified_prompt_list, mark_prompt_completed,
+    record_prompt_sent, revert_to_prompt,
+};
+use commands::provider::{
+    add_provider_config, clear_provider_config, delete_provider_config,
+    get_current_provider_config, get_provider_config, get_provider_presets, switch_provider,
+    switch_provider_config, test_provider_connection, update_provider_config,
+};
+use commands::simple_git::check_and_init_git;
+use commands::storage::{
+    storage_delete_row, storage_execute_sql, storage_insert_row, storage_list_tables,
+    storage_read_table, storage_reset_database, storage_update_row,
+};
+use commands::translator::{
+    clear_translation_cache, detect_text_language, get_translation_cache_stats,
+    get_translation_config, init_translation_service_command, translate, translate_batch,
+    update_translation_config,
+};
+use commands::usage::{
+    cancel_usage_indexing, export_usage_report, get_session_stats, get_tool_usage_stats,
+    get_usage_by_date_range, get_usage_stats, start_usage_indexing,
+};
+
+use commands::enhanced_hooks::{
+    execute_pre_commit_review, test_hook_condition, trigger_hook_event,
+};
+use commands::extensions::{
+    list_agent_skills, list_plugins, list_subagents, open_agents_directory, open_plugins_directory,
+    open_skills_directory, read_skill, read_subagent,
+};
+use commands::file_operations::{open_directory_in_explorer, open_file_with_default_app};
+use commands::git_stats::{get_git_diff_stats, get_session_code_changes};
+use process::ProcessRegistryState;
+use tauri::Manager;
+use tauri_plugin_window_state::Builder as WindowStatePlugin;
+
+/// Build and run the desktop app
+pub fn run() {
+    // Initialize logger (console plus structured store, drained once setup runs)
+    let log_receiver = logging::init();
+    // Time hot-path spans, for get_performance_snapshot
+    profiling::init();
+
+    tauri::Builder::default()
+        // Must come first: a second launch (e.g. from a deep link) hands its
+        // arguments to the running instance and exits
+        .plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+            commands::deep_link::focus_main_window(app);
+        }))
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_shell::init())
+        .plugin(
+            tauri_plugin_http::init()
+        )
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(
+            WindowStatePlugin::default()
+                .with_state_flags(tauri_plugin_window_state::StateFlags::all())
+                .build(),
+        )
+        .setup(|app| {
+            // Typed events; backend subscribers are added below
+            app.manage(events::EventBus::default());
+
+            // Data root first: it decides where the database and ~/.claude live
+            commands::data_location::init(app.handle());
+
+            // Initialize database for storage operations
+            let conn = init_database(&app.handle()).expect("Failed to initialize database");
+            commands::proxy::load_proxy_config(&conn);
+            commands::locale::load_locale(&conn);
+            app.manage(AgentDb(Mutex::new(conn)));
+            commands::session_state::init_session_states(app.handle());
+
+            // Record external process spawns, including those queued before now
+            process::audit::init(app.handle());
+
+            // Check how the last run ended before this run's logs reach the file
+            commands::crash_reports::init_crash_reports(app.handle());
+
+            // Start persisting structured log records
+            logging::start_writer(app.handle().clone(), log_receiver);
+
+            // Initialize process registry, journaling spawned processes to the database
+            let process_registry = ProcessRegistryState::default();
+            match commands::storage::open_database_connection(app.handle()) {
+                Ok(journal) => {
+                    if let Err(e) = process_registry.0.attach_journal(journal) {
+                        log::warn!("Failed to attach process journal: {}", e);
+                    }
+                }
+                Err(e) => log::warn!("Failed to open process journal: {}", e),
+            }
+            app.manage(process_registry);
+
+            // Report processes orphaned by a previous crash
+            let app_handle_for_orphans = app.handle().clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                commands::processes::check_orphans_on_startup(&app_handle_for_orphans);
+            });
+
+            // Initialize Claude process state
+            app.manage(ClaudeProcessState::default());
+
+            // Initialize cancellation handles for running hooks
+            app.manage(commands::enhanced_hooks::HookRunState::default());
+
+            // Initialize the record of which env profile each session used
+            app.manage(commands::env_profiles::SessionEnvProfiles::default());
+            app.manage(commands::checkpoints::CheckpointCounters::default());
+
+            // Projects shown in windows of their own, which scopes project events
+            app.manage(commands::project_windows::WindowRegistry::default());
+            app.manage(commands::observer::ObserverRegistry::default());
+
+            // Initialize the hook manager, which holds debounce / rate-limit state
+            app.manage(commands::enhanced_hooks::HookManager::new(
+                app.handle().clone(),
+            ));
+
+            // Reload settings and hooks edited outside the app
+            let config_watcher = match commands::config_watcher::start_config_watcher(app.handle())
+            {
+                Ok(watcher) => Some(watcher),
+                Err(e) => {
+                    log::warn!("Config file watching disabled: {}", e);
+                    None
+                }
+            };
+            app.manage(commands::config_watcher::ConfigWatcherState(Mutex::new(
+                config_watcher,
+            )));
+
+            // Initialize notification preferences
+            app.manage(commands::notifications::NotificationState::default());
+            events::subscribe(app.handle(), commands::notifications::on_app_event);
+            app.manage(commands::activity_summaries::SummaryState::default());
+            events::subscribe(app.handle(), commands::activity_summaries::on_app_event);
+            commands::activity_summaries::start_summary_channel(app.handle().clone());
+            app.manage(commands::prompt_queue::PromptQueueState::default());
+            app.manage(commands::models::ModelState::default());
+            app.manage(commands::output_mirror::OutputMirrorState::default());
+            app.manage(commands::orchestration::OrchestrationState::default());
+            app.manage(commands::run_scheduler::RunSchedulerState::default());
+            commands::run_scheduler::start_run_scheduler(app.handle().clone());
+            app.manage(commands::control_api::ControlApiState::default());
+            commands::control_api::start_control_api(app.handle().clone());
+
+            // Handle claude-workbench:// links, including one that launched the app
+            app.manage(commands::deep_link::DeepLinkState::default());
+            commands::deep_link::init_deep_links(app.handle());
+
+            // Register the quick prompt palette's global shortcut
+            app.manage(commands::quick_prompt::QuickPromptState::default());
+            commands::quick_prompt::init_quick_prompt(app.handle());
+
+            // Tray icon with running sessions, kept current by the status aggregator
+            app.manage(commands::session_status::SessionStatusState::default());
+            app.manage(commands::tray::TrayState::default());
+            if let Err(e) = commands::tray::init_tray(app.handle()) {
+                log::warn!("Failed to create tray icon: {}", e);
+            }
+            commands::session_status::start_session_status_monitor(app.handle().clone());
+            app.manage(commands::updater::UpdaterState::default());
+            app.manage(commands::settings_sync::SettingsSyncState::default());
+            app.manage(commands::telemetry::TelemetryState::default());
+            app.manage(commands::redaction::RedactionState::default());
+            app.manage(commands::attachments::AttachmentState::default());
+            app.manage(commands::hook_metrics::HookMetricsState::default());
+            app.manage(commands::operations::OperationRegistry::default());
+            app.manage(commands::terminal::TerminalState::default());
+            commands::telemetry::start_telemetry_sender(app.handle().clone());
+            app.manage(commands::idle::IdleState::default());
+            commands::idle::start_idle_monitor(app.handle().clone());
+            app.manage(commands::network::NetworkState::default());
+            app.manage(commands::claude_stream::StreamDiagnosticsState::default());
+            commands::network::start_network_monitor(app.handle().clone());
+            commands::activity_report::start_report_scheduler(app.handle().clone());
+            commands::retention::start_retention_scheduler(app.handle().clone());
+
+            // Initialize auto-compact manager for context management
+            let auto_compact_manager =
+                Arc::new(commands::context_manager::AutoCompactManager::new());
+            let app_handle_for_monitor = app.handle().clone();
+            let manager_for_monitor = auto_compact_manager.clone();
+
+            // Start monitoring in background
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = manager_for_monitor
+                    .start_monitoring(app_handle_for_monitor)
+                    .await
+                {
+                    log::error!("Failed to start auto-compact monitoring: {}", e);
+                }
+            });
+
+            app.manage(commands::context_manager::AutoCompactState(
+                auto_compact_manager,
+            ));
+            app.manage(commands::context_monitor::ContextMonitorState::default());
+
+            // Initialize MCP health monitor (idle until enabled in its config)
+            let mcp_health_monitor = Arc::new(commands::mcp_health::McpHealthMonitor::new());
+            let app_handle_for_mcp = app.handle().clone();
+            let monitor_for_mcp = mcp_health_monitor.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = monitor_for_mcp.start_monitoring(app_handle_for_mcp).await {
+                    log::error!("Failed to start MCP health monitoring: {}", e);
+                }
+            });
+            app.manage(commands::mcp_health::McpHealthState(mcp_health_monitor));
+
+            // Initialize translation service with saved configuration
+            tauri::async_runtime::spawn(async move {
+                commands::translator::init_translation_service_with_saved_config().await;
+            });
+
+            Ok(())
+        })
+        .invoke_handler(commands::observer::read_only_guard(tauri::generate_handler![
+            // Claude & Project Management
+            list_projects,
+            get_project_sessions,
+            delete_project,
+            restore_project,
+            list_hidden_projects,
+            delete_project_permanently,
+            commands::projects::list_project_registry,
+            commands::projects::pin_project,
+            commands::projects::update_project_metadata,
+            commands::projects::hide_project,
+            commands::projects::relocate_project_metadata,
+            get_claude_settings,
+            open_new_session,
+            get_system_prompt,
+            check_claude_version,
+            save_system_prompt,
+            save_claude_settings,
+            update_thinking_mode,
+            commands::settings_manager::get_settings_file,
+            commands::settings_manager::validate_settings,
+            commands::settings_manager::save_settings_file,
+            commands::settings_manager::preview_merged_settings,
+            commands::config_io::list_config_backups,
+            commands::config_io::restore_config_backup,
+            commands::config_merge::list_config_conflicts,
+            commands::config_merge::resolve_config_conflict,
+            commands::config_watcher::watch_project_config,
+            commands::config_watcher::unwatch_project_config,
+            find_claude_md_files,
+            read_claude_md_file,
+            save_claude_md_file,
+            commands::claude_md::list_claude_md_hierarchy,
+            commands::claude_md::get_claude_md,
+            commands::claude_md::save_claude_md,
+            load_session_history,
+            execute_claude_code,
+            continue_claude_code,
+            resume_claude_code,
+            cancel_claude_execution,
+            list_running_claude_sessions,
+            commands::processes::list_running_processes,
+            commands::processes::kill_process,
+            commands::processes::list_orphaned_processes,
+            commands::processes::kill_orphaned_processes,
+            commands::processes::dismiss_orphaned_process,
+            commands::logs::query_logs,
+            commands::logs::clear_logs,
+            get_claude_session_output,
+            commands::claude_stream::get_stream_diagnostics,
+            list_directory_contents,
+            search_files,
+            get_hooks_config,
+            update_hooks_config,
+            validate_hook_command,
+            commands::enhanced_hooks::list_enhanced_hooks,
+            commands::enhanced_hooks::reorder_hooks,
+            commands::enhanced_hooks::add_enhanced_hook,
+            commands::enhanced_hooks::update_enhanced_hook,
+            commands::enhanced_hooks::delete_enhanced_hook,
+            commands::enhanced_hooks::toggle_enhanced_hook,
+            commands::hook_graph::get_hook_chain_graph,
+            commands::enhanced_hooks::cancel_hook_run,
+            commands::hook_output::get_hook_output,
+            commands::enhanced_hooks::get_hook_manager_status,
+            commands::hook_sandbox::get_hook_sandbox_support,
+            commands::hook_approval::list_pending_hooks,
+            commands::hook_approval::approve_hooks,
+            commands::hook_presets::list_hook_presets,
+            commands::hook_presets::install_hook_preset,
+            commands::hook_presets::list_installed_hook_presets,
+            commands::hook_presets::set_hook_preset_enabled,
+            commands::env_profiles::list_env_profiles,
+            commands::env_profiles::save_env_profile,
+            commands::env_profiles::update_env_profile,
+            commands::env_profiles::delete_env_profile,
+            commands::env_profiles::activate_env_profile,
+            commands::env_profiles::get_session_env_profile,
+            commands::proxy::get_proxy_config,
+            commands::proxy::update_proxy_config,
+            commands::proxy::test_proxy_connectivity,
+            commands::credentials::set_credential,
+            commands::credentials::list_credentials,
+            commands::credentials::delete_credential,
+            commands::checkpoints::get_checkpoint_settings,
+            commands::checkpoints::update_checkpoint_settings,
+            commands::checkpoints::list_checkpoints,
+            commands::checkpoints::create_checkpoint,
+            commands::checkpoints::delete_checkpoint,
+            commands::checkpoints::restore_checkpoint,
+            commands::checkpoints::diff_checkpoints,
+            commands::checkpoints::prune_checkpoints,
+            // 权限管理命令
+            get_claude_execution_config,
+            update_claude_execution_config,
+            reset_claude_execution_config,
+            get_claude_permission_config,
+            update_claude_permission_config,
+            get_permission_presets,
+            get_available_tools,
+            validate_permission_config,
+            commands::permissions::list_permission_rules,
+            commands::permissions::validate_permission_rule,
+            commands::permissions::add_permission_rule,
+            commands::permissions::update_permission_rule,
+            commands::permissions::remove_permission_rule,
+            commands::permissions::detect_permission_conflicts,
+            commands::permissions::test_permission,
+            commands::permissions::suggest_permission_rules,
+            commands::permissions::apply_suggested_rules,
+            set_custom_claude_path,
+            get_claude_path,
+            clear_custom_claude_path,
+            enhance_prompt,
+            enhance_prompt_with_gemini,
+            // Enhanced Hooks Automation
+            trigger_hook_event,
+            test_hook_condition,
+            execute_pre_commit_review,
+            // Usage & Analytics (Simplified from opcode)
+            get_usage_stats,
+            get_usage_by_date_range,
+            get_session_stats,
+            export_usage_report,
+            commands::project_insights::get_project_insights,
+            commands::models::get_model_catalog,
+            commands::models::get_project_default_model,
+            commands::models::set_project_default_model,
+            commands::models::switch_session_model,
+            commands::models::get_session_model,
+            commands::models::get_usage_by_model,
+            commands::thinking_presets::list_thinking_presets,
+            commands::thinking_presets::get_thinking_preset,
+            commands::thinking_presets::set_thinking_preset,
+            commands::cost_attribution::get_session_attribution,
+            commands::cost_attribution::set_session_attribution,
+            commands::cost_attribution::get_cost_by_branch,
+            commands::cost_attribution::get_cost_by_ticket,
+            commands::cost_attribution::get_attribution_config,
+            commands::cost_attribution::update_attribution_config,
+            commands::activity_report::generate_activity_report,
+            commands::activity_report::get_report_schedule,
+            commands::activity_report::update_report_schedule,
+            start_usage_indexing,
+            cancel_usage_indexing,
+            get_tool_usage_stats,
+            commands::session_export::export_session,
+            commands::session_bundle::publish_session_bundle,
+            commands::session_import::import_session,
+            commands::session_handoff::export_session_handoff,
+            commands::session_handoff::import_session_handoff,
+            commands::session_rewind::rewind_session,
+            commands::session_batch::delete_sessions,
+            commands::session_batch::export_sessions,
+            commands::session_batch::retag_sessions,
+            commands::session_batch::get_session_tags,
+            commands::session_batch::archive_project,
+            commands::retention::get_retention_policy,
+            commands::retention::update_retention_policy,
+            commands::retention::run_retention,
+            commands::retention::list_archived_sessions,
+            commands::retention::restore_archived_session,
+            commands::retention::get_disk_usage_report,
+            commands::data_usage::analyze_data_usage,
+            commands::data_usage::clean_category,
+            commands::data_location::get_data_location,
+            commands::data_location::move_data_directory,
+            commands::project_windows::open_project_in_new_window,
+            commands::project_windows::get_window_context,
+            commands::project_windows::set_window_project,
+            commands::project_windows::list_project_windows,
+            commands::observer::open_observer_window,
+            commands::observer::get_observer_context,
+            commands::observer::list_observer_windows,
+            commands::environment_doctor::run_environment_doctor,
+            commands::locale::get_locale,
+            commands::locale::set_locale,
+            commands::activity_summaries::get_activity_summary_config,
+            commands::activity_summaries::update_activity_summary_config,
+            commands::performance::get_performance_snapshot,
+            commands::performance::reset_performance_snapshot,
+            // MCP (Model Context Protocol)
+            mcp_add,
+            mcp_list,
+            mcp_get,
+            mcp_remove,
+            mcp_add_json,
+            mcp_add_from_claude_desktop,
+            mcp_serve,
+            mcp_test_connection,
+            mcp_reset_project_choices,
+            mcp_get_server_status,
+            mcp_export_config,
+            mcp_read_project_config,
+            mcp_save_project_config,
+            mcp_config_list,
+            mcp_config_add,
+            mcp_config_remove,
+            mcp_config_set_enabled,
+            mcp_validate_server,
+            mcp_test_server_config,
+            import_mcp_from_claude_desktop,
+            import_mcp_from_cursor,
+            commands::mcp_health::get_mcp_health,
+            commands::mcp_health::check_mcp_health_now,
+            commands::mcp_health::get_mcp_health_config,
+            commands::mcp_health::update_mcp_health_config,
+            commands::mcp_health::set_mcp_health_projects,
+            commands::mcp_health::start_mcp_health_monitoring,
+            commands::mcp_health::stop_mcp_health_monitoring,
+            // Storage Management
+            storage_list_tables,
+            storage_read_table,
+            storage_update_row,
+            storage_delete_row,
+            storage_insert_row,
+            storage_execute_sql,
+            storage_reset_database,
+            commands::storage::get_db_stats,
+            commands::storage::vacuum_db,
+            // Slash Commands
+            commands::slash_commands::slash_commands_list,
+            commands::slash_commands::slash_command_get,
+            commands::slash_commands::slash_command_save,
+            commands::slash_commands::slash_command_delete,
+            commands::slash_commands::slash_command_validate,
+            // Clipboard
+            save_clipboard_image,
+            write_to_clipboard,
+            read_from_clipboard,
+            // Attachments
+            commands::attachments::add_attachment,
+            commands::attachments::add_attachment_file,
+            commands::attachments::paste_clipboard_attachment,
+            commands::attachments::list_attachments,
+            commands::attachments::delete_attachment,
+            // Provider Management
+            get_provider_presets,
+            get_current_provider_config,
+            switch_provider_config,
+            switch_provider,
+            clear_provider_config,
+            test_provider_connection,
+            add_provider_config,
+            update_provider_config,
+            delete_provider_config,
+            get_provider_config,
+            // Translation
+            translate,
+            translate_batch,
+            get_translation_config,
+            update_translation_config,
+            clear_translation_cache,
+            get_translation_cache_stats,
+            detect_text_language,
+            init_translation_service_command,
+            // Auto-Compact Context Management
+            commands::context_commands::init_auto_compact_manager,
+            commands::context_commands::register_auto_compact_session,
+            commands::context_commands::update_session_context,
+            commands::context_commands::trigger_manual_compaction,
+            commands::context_commands::get_auto_compact_config,
+            commands::context_commands::update_auto_compact_config,
+            commands::context_commands::get_session_context_stats,
+            commands::context_commands::get_all_monitored_sessions,
+            commands::context_commands::unregister_auto_compact_session,
+            commands::context_commands::stop_auto_compact_monitoring,
+            commands::context_commands::start_auto_compact_monitoring,
+            commands::context_commands::get_auto_compact_status,
+            commands::context_monitor::get_context_status,
+            commands::context_monitor::get_context_monitor_config,
+            commands::context_monitor::update_context_monitor_config,
+            // Prompt Revert System
+            check_and_init_git,
+            record_prompt_sent,
+            mark_prompt_completed,
+            revert_to_prompt,
+            get_prompt_list,
+            get_unified_prompt_list,
+            check_rewind_capabilities,
+            // Prompt Queue
+            commands::prompt_queue::get_prompt_queue,
+            commands::prompt_queue::reorder_prompt_queue,
+            commands::prompt_queue::cancel_queued_prompt,
+            // Prompt Templates
+            commands::prompt_templates::list_prompt_templates,
+            commands::prompt_templates::get_prompt_template,
+            commands::prompt_templates::save_prompt_template,
+            commands::prompt_templates::delete_prompt_template,
+            commands::prompt_templates::render_prompt_template,
+            commands::prompt_templates::send_prompt_template,
+            commands::prompt_templates::import_prompt_template,
+            commands::prompt_templates::export_prompt_template,
+            // Session Templates
+            commands::session_templates::list_session_templates,
+            commands::session_templates::get_session_template,
+            commands::session_templates::save_session_template,
+            commands::session_templates::delete_session_template,
+            commands::session_templates::create_session_from_template,
+            // Knowledge Base
+            commands::knowledge_base::capture_knowledge,
+            commands::knowledge_base::search_knowledge,
+            commands::knowledge_base::get_knowledge_entry,
+            commands::knowledge_base::update_knowledge_entry,
+            commands::knowledge_base::delete_knowledge_entry,
+            // Session Output Mirror
+            commands::output_mirror::start_output_mirror,
+            commands::output_mirror::stop_output_mirror,
+            commands::output_mirror::list_output_mirrors,
+            // Multi-session Orchestration
+            commands::orchestration::start_orchestration,
+            commands::orchestration::get_orchestration,
+            commands::orchestration::list_orchestrations,
+            commands::orchestration::cancel_orchestration,
+            // Background Run Scheduler
+            commands::run_scheduler::schedule_run,
+            commands::run_scheduler::list_runs,
+            commands::run_scheduler::cancel_run,
+            commands::run_scheduler::get_scheduler_config,
+            commands::run_scheduler::update_scheduler_config,
+            // Local Control API
+            commands::control_api::get_control_api_status,
+            commands::control_api::update_control_api_config,
+            commands::control_api::regenerate_control_api_token,
+            // Deep Links
+            commands::deep_link::list_pending_deep_links,
+            commands::deep_link::accept_deep_link,
+            commands::deep_link::dismiss_deep_link,
+            // Quick Prompt
+            commands::quick_prompt::get_quick_prompt_config,
+            commands::quick_prompt::set_global_shortcut,
+            commands::quick_prompt::list_quick_prompt_targets,
+            commands::quick_prompt::submit_quick_prompt,
+            commands::quick_prompt::toggle_quick_prompt,
+            // Session Status & Tray
+            commands::session_status::get_session_statuses,
+            commands::session_status::pause_session,
+            commands::session_status::resume_session,
+            commands::session_status::stop_session,
+            commands::session_state::get_session_state,
+            commands::session_state::list_session_states,
+            commands::session_state::clear_session_state,
+            // Updates
+            commands::updater::get_updater_config,
+            commands::updater::update_updater_config,
+            commands::updater::get_update_status,
+            commands::updater::check_for_updates,
+            commands::updater::install_update,
+            commands::updater::restart_to_update,
+            // Settings Sync
+            commands::settings_sync::get_sync_config,
+            commands::settings_sync::update_sync_config,
+            commands::settings_sync::sync_now,
+            commands::settings_sync::sync_status,
+            // Profile Backup
+            commands::profile_archive::export_profile,
+            commands::profile_archive::import_profile,
+            // Telemetry
+            commands::telemetry::get_telemetry_config,
+            commands::telemetry::update_telemetry_config,
+            commands::telemetry::preview_telemetry_payload,
+            commands::telemetry::send_telemetry,
+            commands::telemetry::reset_telemetry,
+            // Redaction
+            commands::redaction::get_redaction_config,
+            commands::redaction::update_redaction_config,
+            commands::redaction::preview_redaction,
+            // Crash Reports
+            commands::crash_reports::list_crash_reports,
+            commands::crash_reports::delete_crash_report,
+            // Hook Metrics
+            commands::hook_metrics::get_hook_metrics,
+            commands::hook_metrics::reset_hook_metrics,
+            // Command Policy
+            commands::command_policy::get_command_policy,
+            commands::command_policy::save_command_policy,
+            commands::command_policy::remove_command_policy,
+            commands::command_policy::check_command_policy,
+            commands::command_policy::list_command_audit,
+            // Process Audit
+            commands::process_audit::get_process_audit,
+            commands::process_audit::clear_process_audit,
+            commands::process_audit::get_process_audit_enabled,
+            commands::process_audit::set_process_audit_enabled,
+            // Terminals
+            commands::terminal::create_terminal,
+            commands::terminal::write_to_terminal,
+            commands::terminal::resize_terminal,
+            commands::terminal::close_terminal,
+            commands::terminal::list_terminals,
+            // Cancellable Operations
+            commands::operations::cancel_operation,
+            commands::operations::list_operations,
+            // Claude Extensions (Plugins, Subagents & Skills)
+            list_plugins,
+            list_subagents,
+            list_agent_skills,
+            read_subagent,
+            read_skill,
+            open_plugins_directory,
+            open_agents_directory,
+            open_skills_directory,
+            commands::extensions::list_agents,
+            commands::extensions::validate_agent,
+            commands::extensions::save_agent,
+            commands::extensions::duplicate_agent,
+            commands::extensions::delete_agent,
+            // File Operations
+            open_directory_in_explorer,
+            open_file_with_default_app,
+            commands::file_operations::read_project_file,
+            commands::project_tree::list_project_tree,
+            commands::project_search::search_in_project,
+            // Git Statistics
+            get_git_diff_stats,
+            get_session_code_changes,
+            commands::git_backend::get_git_status,
+            commands::git_backend::list_git_branches,
+            commands::git_backend::get_git_blame,
+            // Notifications
+            commands::notifications::notify,
+            commands::notifications::get_notification_config,
+            commands::notifications::update_notification_config,
+            commands::audio::speak_text,
+            commands::audio::play_system_sound,
+            // Idle Detection
+            commands::idle::get_idle_config,
+            commands::idle::update_idle_config,
+            commands::idle::get_idle_status,
+            // Network Status
+            commands::network::get_network_status,
+            commands::network::get_network_config,
+            commands::network::update_network_config,
+            // Workspace Persistence
+            commands::workspace::get_workspace_state,
+            commands::workspace::save_workspace_state,
+            commands::workspace::clear_workspace_state,
+        ]))
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                commands::terminal::close_all(app);
+                commands::crash_reports::mark_clean_exit(app);
+            }
+        });
+}
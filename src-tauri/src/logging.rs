@@ -0,0 +1,311 @@
+use log::kv::{self, Key, VisitSource};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+/// Structured application logging
+///
+/// Keeps env_logger's console output and additionally records every relevant log
+/// entry (level, module, session id, key-value fields) to a rotating JSON-lines file
+/// and the `app_logs` SQLite table, streaming each one to the frontend as `log-stream`.
+///
+/// Structured fields use the `log` key-value syntax; a `session_id` key is lifted
+/// into its own column:
+/// `log::warn!(session_id = sid.as_str(), exit_code = 1; "Hook failed")`
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+/// Module prefix of this crate; its info logs are stored, dependencies only from warn
+const CRATE_TARGET: &str = "claude_workbench";
+
+/// Rotate the log file once it grows past this size
+const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Number of rotated files kept next to the active one
+const ROTATED_FILES: usize = 3;
+
+/// Rows kept in the `app_logs` table
+const MAX_DB_RECORDS: i64 = 50_000;
+
+/// Prune the table every this many inserts
+const PRUNE_INTERVAL: u64 = 1_000;
+
+/// Most recent log lines kept in memory for crash reports
+const RECENT_LINES: usize = 200;
+
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// A single structured log entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogRecord {
+    /// Row id once stored in the database
+    pub id: Option<i64>,
+    /// RFC 3339 timestamp
+    pub timestamp: String,
+    /// "ERROR", "WARN", "INFO", "DEBUG" or "TRACE"
+    pub level: String,
+    /// Module path that emitted the record
+    pub module: String,
+    pub session_id: Option<String>,
+    pub message: String,
+    /// Remaining key-value pairs attached to the record
+    pub fields: Map<String, Value>,
+}
+
+impl LogRecord {
+    fn from_record(record: &Record) -> Self {
+        let mut collector = FieldCollector {
+            fields: Map::new(),
+            session_id: None,
+        };
+        let _ = record.key_values().visit(&mut collector);
+
+        Self {
+            id: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            level: record.level().to_string(),
+            module: record
+                .module_path()
+                .unwrap_or_else(|| record.target())
+                .to_string(),
+            session_id: collector.session_id,
+            message: record.args().to_string(),
+            fields: collector.fields,
+        }
+    }
+}
+
+/// Collects `log` key-value pairs into JSON
+struct FieldCollector {
+    fields: Map<String, Value>,
+    session_id: Option<String>,
+}
+
+impl<'kvs> VisitSource<'kvs> for FieldCollector {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: kv::Value<'kvs>) -> Result<(), kv::Error> {
+        if key.as_str() == "session_id" {
+            self.session_id = Some(value.to_string());
+            return Ok(());
+        }
+
+        let json = if let Some(b) = value.to_bool() {
+            Value::Bool(b)
+        } else if let Some(i) = value.to_i64() {
+            Value::from(i)
+        } else if let Some(u) = value.to_u64() {
+            Value::from(u)
+        } else if let Some(f) = value.to_f64() {
+            Value::from(f)
+        } else {
+            Value::String(value.to_string())
+        };
+        self.fields.insert(key.as_str().to_string(), json);
+        Ok(())
+    }
+}
+
+/// `log` backend that fans records out to the console and the structured store
+struct StructuredLogger {
+    console: env_logger::Logger,
+    sender: Sender<LogRecord>,
+}
+
+/// Whether a record is worth persisting
+fn should_store(metadata: &Metadata) -> bool {
+    if metadata.target().starts_with(CRATE_TARGET) {
+        metadata.level() <= Level::Info
+    } else {
+        metadata.level() <= Level::Warn
+    }
+}
+
+impl Log for StructuredLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.console.enabled(metadata) || should_store(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        let console = self.console.matches(record);
+        if console {
+            self.console.log(record);
+        }
+        let store = should_store(record.metadata());
+        if console || store {
+            remember(record);
+        }
+        if store {
+            // Records queue up here until the writer starts during app setup
+            let _ = self.sender.send(LogRecord::from_record(record));
+        }
+    }
+
+    fn flush(&self) {
+        self.console.flush();
+    }
+}
+
+/// Keep a plain-text copy of the record in the in-memory tail
+fn remember(record: &Record) {
+    // A panic while logging must not deadlock the crash handler
+    if let Ok(mut recent) = RECENT.try_lock() {
+        if recent.len() == RECENT_LINES {
+            recent.pop_front();
+        }
+        recent.push_back(format!(
+            "{} {:5} {}: {}",
+            chrono::Utc::now().to_rfc3339(),
+            record.level(),
+            record.target(),
+            record.args()
+        ));
+    }
+}
+
+/// The last log lines of this run, oldest first
+pub fn recent_lines() -> Vec<String> {
+    RECENT
+        .try_lock()
+        .map(|recent| recent.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Install the logger. The returned receiver is handed to `start_writer` once the
+/// app data directory is known.
+pub fn init() -> Receiver<LogRecord> {
+    let (sender, receiver) = mpsc::channel();
+    let console = env_logger::Builder::from_default_env().build();
+    let max_level = std::cmp::max(console.filter(), LevelFilter::Info);
+
+    let logger = StructuredLogger { console, sender };
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(max_level);
+    }
+    receiver
+}
+
+/// JSON-lines file that rotates by size
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf) -> std::io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self { path, file, size })
+    }
+
+    fn rotated_path(path: &Path, index: usize) -> PathBuf {
+        path.with_extension(format!("{}.jsonl", index))
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        for index in (1..ROTATED_FILES).rev() {
+            let from = Self::rotated_path(&self.path, index);
+            if from.exists() {
+                fs::rename(&from, Self::rotated_path(&self.path, index + 1))?;
+            }
+        }
+        fs::rename(&self.path, Self::rotated_path(&self.path, 1))?;
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        if self.size + line.len() as u64 > MAX_FILE_BYTES {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.size += line.len() as u64 + 1;
+        Ok(())
+    }
+}
+
+/// Store a record and return its row id
+fn insert_record(conn: &rusqlite::Connection, record: &LogRecord) -> rusqlite::Result<i64> {
+    conn.execute(
+        "INSERT INTO app_logs (timestamp, level, module, session_id, message, fields)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        rusqlite::params![
+            record.timestamp,
+            record.level,
+            record.module,
+            record.session_id,
+            record.message,
+            Value::Object(record.fields.clone()).to_string()
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Drain queued records into the file, the database and the `log-stream` event.
+///
+/// Errors here go to stderr rather than `log`, which would feed back into this loop.
+pub fn start_writer(app: AppHandle, receiver: Receiver<LogRecord>) {
+    let log_file = crate::paths::app_log_dir(&app)
+        .map(|dir| dir.join("workbench.jsonl"))
+        .map_err(|e| e.to_string())
+        .and_then(|path| RotatingFile::open(path).map_err(|e| e.to_string()));
+    let mut log_file = match log_file {
+        Ok(file) => Some(file),
+        Err(e) => {
+            eprintln!("Failed to open log file: {}", e);
+            None
+        }
+    };
+
+    let db = match crate::commands::storage::open_database_connection(&app) {
+        Ok(conn) => Some(conn),
+        Err(e) => {
+            eprintln!("Failed to open log database: {}", e);
+            None
+        }
+    };
+
+    let spawned = std::thread::Builder::new()
+        .name("log-writer".to_string())
+        .spawn(move || {
+            let mut inserted: u64 = 0;
+            for mut record in receiver {
+                if let Some(conn) = &db {
+                    match insert_record(conn, &record) {
+                        Ok(id) => record.id = Some(id),
+                        Err(e) => eprintln!("Failed to store log record: {}", e),
+                    }
+                    inserted += 1;
+                    if inserted.is_multiple_of(PRUNE_INTERVAL) {
+                        let _ = conn.execute(
+                            "DELETE FROM app_logs WHERE id <= (SELECT MAX(id) FROM app_logs) - ?1",
+                            [MAX_DB_RECORDS],
+                        );
+                    }
+                }
+
+                if let Some(file) = log_file.as_mut() {
+                    let line = serde_json::to_string(&record).unwrap_or_default();
+                    if let Err(e) = file.write_line(&line) {
+                        eprintln!("Failed to write log file: {}", e);
+                    }
+                }
+
+                let _ = app.emit("log-stream", &record);
+            }
+        });
+
+    if let Err(e) = spawned {
+        eprintln!("Failed to start log writer: {}", e);
+    }
+}
@@ -0,0 +1,179 @@
+//! Where the workbench keeps its state
+//!
+//! Every directory the backend writes to is resolved here, in one of three
+//! layouts:
+//!
+//! - default: the platform app-data, log and config directories, the system
+//!   temp directory and `~/.claude`
+//! - data root (see `commands::data_location`): app data under
+//!   `<root>/workbench` and Claude's data under `<root>/claude`
+//! - portable: everything under `data/` next to the executable, in `workbench`,
+//!   `claude`, `logs`, `config` and `cache`. It is on when a file named
+//!   `portable` sits next to the executable or the app is started with
+//!   `--portable`. The keychain is not used and the URL scheme is not
+//!   registered, and paths the workbench stores are kept relative to the
+//!   executable's directory so the folder can move between machines.
+//!
+//! The webview's own storage and the window-state plugin's file are managed by
+//! Tauri and stay in the platform directories in every layout.
+
+use log::warn;
+use once_cell::sync::{Lazy, OnceCell};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{Manager, Runtime};
+
+/// File next to the executable that turns on portable mode
+const PORTABLE_MARKER: &str = "portable";
+const PORTABLE_FLAG: &str = "--portable";
+const PORTABLE_DIR: &str = "data";
+
+/// Directories under a data root or the portable directory
+pub(crate) const APP_DATA_SUBDIR: &str = "workbench";
+pub(crate) const CLAUDE_SUBDIR: &str = "claude";
+const LOGS_SUBDIR: &str = "logs";
+const CONFIG_SUBDIR: &str = "config";
+const CACHE_SUBDIR: &str = "cache";
+
+/// The Claude CLI's settings file, next to `~/.claude` by default and inside
+/// `CLAUDE_CONFIG_DIR` when that is set
+pub(crate) const CLAUDE_JSON: &str = ".claude.json";
+
+/// Directory holding the executable, when running portable
+static PORTABLE_BASE: Lazy<Option<PathBuf>> = Lazy::new(|| {
+    let exe = std::env::current_exe().ok()?;
+    let base = exe.parent()?.to_path_buf();
+    let flagged = std::env::args().any(|arg| arg == PORTABLE_FLAG);
+    (flagged || base.join(PORTABLE_MARKER).is_file()).then_some(base)
+});
+
+/// Data root in effect for this run, set once at startup
+static DATA_ROOT: OnceCell<PathBuf> = OnceCell::new();
+
+pub fn is_portable() -> bool {
+    PORTABLE_BASE.is_some()
+}
+
+/// The `data` directory next to the executable, when running portable
+pub fn portable_root() -> Option<PathBuf> {
+    PORTABLE_BASE.as_ref().map(|base| base.join(PORTABLE_DIR))
+}
+
+/// Use `root` for app and Claude data for the rest of this run; ignored when
+/// running portable
+pub(crate) fn set_data_root(root: PathBuf) {
+    if !is_portable() {
+        let _ = DATA_ROOT.set(root);
+    }
+}
+
+/// Data root in use, None for the defaults and in portable mode
+pub fn data_root() -> Option<PathBuf> {
+    DATA_ROOT.get().cloned()
+}
+
+/// Root holding the app and Claude data directories, if not the defaults
+fn relocated_root() -> Option<PathBuf> {
+    portable_root().or_else(data_root)
+}
+
+/// Directory of the app database and files
+pub fn app_data_dir<R: Runtime>(app: &impl Manager<R>) -> tauri::Result<PathBuf> {
+    match relocated_root() {
+        Some(root) => Ok(root.join(APP_DATA_SUBDIR)),
+        None => app.path().app_data_dir(),
+    }
+}
+
+/// Directory of log files and crash reports
+pub fn app_log_dir<R: Runtime>(app: &impl Manager<R>) -> tauri::Result<PathBuf> {
+    match portable_root() {
+        Some(root) => Ok(root.join(LOGS_SUBDIR)),
+        None => app.path().app_log_dir(),
+    }
+}
+
+/// Directory of settings that must be readable before the database is opened
+pub fn app_config_dir<R: Runtime>(app: &impl Manager<R>) -> tauri::Result<PathBuf> {
+    match portable_root() {
+        Some(root) => Ok(root.join(CONFIG_SUBDIR)),
+        None => app.path().app_config_dir(),
+    }
+}
+
+/// Directory for files the app can recreate or lose, private to the app
+pub fn app_cache_dir<R: Runtime>(app: &impl Manager<R>) -> tauri::Result<PathBuf> {
+    match portable_root() {
+        Some(root) => Ok(root.join(CACHE_SUBDIR)),
+        None => app.path().app_cache_dir(),
+    }
+}
+
+/// Directory for scratch files and caches; created when running portable
+pub fn temp_dir() -> PathBuf {
+    match portable_root() {
+        Some(root) => {
+            let dir = root.join(CACHE_SUBDIR);
+            if let Err(e) = fs::create_dir_all(&dir) {
+                warn!("Failed to create {}: {}", dir.display(), e);
+            }
+            dir
+        }
+        None => std::env::temp_dir(),
+    }
+}
+
+/// Claude's data directory when it is not `~/.claude`
+pub fn claude_dir_override() -> Option<PathBuf> {
+    relocated_root().map(|root| root.join(CLAUDE_SUBDIR))
+}
+
+/// Claude's data directory, `~/.claude` by default
+pub fn claude_dir() -> Result<PathBuf, String> {
+    match claude_dir_override() {
+        Some(dir) => Ok(dir),
+        None => dirs::home_dir()
+            .map(|home| home.join(".claude"))
+            .ok_or_else(|| "Could not find home directory".to_string()),
+    }
+}
+
+/// The Claude CLI's `.claude.json`, which lives inside Claude's directory
+/// when that is moved
+pub fn claude_json_path() -> Result<PathBuf, String> {
+    match claude_dir_override() {
+        Some(dir) => Ok(dir.join(CLAUDE_JSON)),
+        None => dirs::home_dir()
+            .map(|home| home.join(CLAUDE_JSON))
+            .ok_or_else(|| "Could not find home directory".to_string()),
+    }
+}
+
+/// Environment for Claude CLI processes so they use the moved data
+pub fn claude_env() -> Option<(&'static str, PathBuf)> {
+    claude_dir_override().map(|dir| ("CLAUDE_CONFIG_DIR", dir))
+}
+
+/// A path as it should be stored: `./`-relative to the executable's directory
+/// when running portable and the path is inside it, unchanged otherwise
+pub fn to_stored(path: &str) -> String {
+    let Some(base) = PORTABLE_BASE.as_ref() else {
+        return path.to_string();
+    };
+    match Path::new(path).strip_prefix(base) {
+        Ok(relative) => Path::new(".").join(relative).to_string_lossy().to_string(),
+        Err(_) => path.to_string(),
+    }
+}
+
+/// A stored path made absolute again; the inverse of `to_stored`. Only the
+/// `./` form is resolved, so plain names such as `claude` pass through.
+pub fn from_stored(stored: &str) -> String {
+    let relative = stored
+        .strip_prefix("./")
+        .or_else(|| stored.strip_prefix(".\\"));
+    match (PORTABLE_BASE.as_ref(), relative) {
+        (Some(base), Some(relative)) => base.join(relative).to_string_lossy().to_string(),
+        _ => stored.to_string(),
+    }
+}
@@ -0,0 +1,332 @@
+//! Process audit
+//!
+//! Every external program the app runs (git, the Claude CLI, hooks, MCP servers,
+//! system helpers) goes through one of the `Audited*` extension methods below,
+//! which record the binary, arguments, working directory, duration and outcome
+//! in `process_audit`. Long-running children get a `ProcessAudit` ticket that is
+//! finished when they exit; a ticket dropped before that is recorded as
+//! `detached`.
+//!
+//! Rows are handed to a writer thread, so spawning never waits for the
+//! database. Spawns before `init` (binary discovery during startup) are queued
+//! and written once the database is available. Auditing can be switched off with
+//! `set_process_audit_enabled`.
+
+use log::{debug, warn};
+use once_cell::sync::Lazy;
+use rusqlite::params;
+use std::ffi::OsStr;
+use std::io;
+use std::process::{Child, Command, ExitStatus, Output};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Mutex;
+use std::time::Instant;
+use tauri::{AppHandle, Manager};
+
+use crate::commands::storage::AgentDb;
+
+/// `app_settings` key of the on/off switch
+pub const SETTINGS_KEY: &str = "process_audit_enabled";
+/// Arguments longer than this (prompts, inline scripts) are cut
+const MAX_ARG_CHARS: usize = 500;
+/// Rows kept; older ones are pruned as new ones arrive
+const MAX_ROWS: i64 = 20_000;
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Finished records on their way to the writer thread; `init` takes the receiver
+type AuditQueue = (Mutex<Sender<AuditRow>>, Mutex<Option<Receiver<AuditRow>>>);
+
+static QUEUE: Lazy<AuditQueue> = Lazy::new(|| {
+    let (tx, rx) = channel();
+    (Mutex::new(tx), Mutex::new(Some(rx)))
+});
+
+struct AuditRow {
+    source: &'static str,
+    program: String,
+    args: Vec<String>,
+    cwd: Option<String>,
+    pid: Option<u32>,
+    started_at: String,
+    duration_ms: Option<u64>,
+    exit_code: Option<i32>,
+    /// "exited", "failed_to_start", "signaled" or "detached"
+    outcome: &'static str,
+    error: Option<String>,
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Start the writer thread; rows queued before this are written first
+pub fn init(app: &AppHandle) {
+    if let Some(db) = app.try_state::<AgentDb>() {
+        if let Ok(conn) = db.0.lock() {
+            let stored: Option<String> = conn
+                .query_row(
+                    "SELECT value FROM app_settings WHERE key = ?1",
+                    [SETTINGS_KEY],
+                    |row| row.get(0),
+                )
+                .ok();
+            if let Some(stored) = stored {
+                set_enabled(stored != "false");
+            }
+        }
+    }
+
+    let rx = match QUEUE.1.lock().ok().and_then(|mut rx| rx.take()) {
+        Some(rx) => rx,
+        None => return,
+    };
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let mut written = 0u64;
+        for row in rx {
+            let db = match app.try_state::<AgentDb>() {
+                Some(db) => db,
+                None => continue,
+            };
+            let conn = match db.0.lock() {
+                Ok(conn) => conn,
+                Err(_) => continue,
+            };
+            let args = serde_json::to_string(&row.args).unwrap_or_default();
+            if let Err(e) = conn.execute(
+                "INSERT INTO process_audit (source, program, args, cwd, pid, started_at, duration_ms, exit_code, outcome, error)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    row.source,
+                    row.program,
+                    args,
+                    row.cwd,
+                    row.pid,
+                    row.started_at,
+                    row.duration_ms.map(|ms| ms as i64),
+                    row.exit_code,
+                    row.outcome,
+                    row.error,
+                ],
+            ) {
+                warn!("Failed to record process audit entry: {}", e);
+                continue;
+            }
+            written += 1;
+            if written.is_multiple_of(500) {
+                let _ = conn.execute(
+                    "DELETE FROM process_audit WHERE id <= (SELECT MAX(id) FROM process_audit) - ?1",
+                    [MAX_ROWS],
+                );
+            }
+        }
+    });
+}
+
+fn clip(value: &OsStr) -> String {
+    let value = value.to_string_lossy();
+    match value.char_indices().nth(MAX_ARG_CHARS) {
+        Some((end, _)) => format!("{}…", &value[..end]),
+        None => value.to_string(),
+    }
+}
+
+/// Record of one process, written when finished or dropped
+pub struct ProcessAudit {
+    row: Option<AuditRow>,
+    started: Instant,
+}
+
+impl ProcessAudit {
+    /// Start recording `cmd`, which is about to be spawned
+    pub fn start(source: &'static str, cmd: &Command) -> Self {
+        if !is_enabled() {
+            return Self {
+                row: None,
+                started: Instant::now(),
+            };
+        }
+        let row = AuditRow {
+            source,
+            program: cmd.get_program().to_string_lossy().to_string(),
+            args: cmd.get_args().map(clip).collect(),
+            cwd: cmd
+                .get_current_dir()
+                .map(|dir| dir.to_string_lossy().to_string()),
+            pid: None,
+            started_at: chrono::Utc::now().to_rfc3339(),
+            duration_ms: None,
+            exit_code: None,
+            outcome: "detached",
+            error: None,
+        };
+        Self {
+            row: Some(row),
+            started: Instant::now(),
+        }
+    }
+
+    /// Start recording a program run by other means, e.g. on a pseudo-terminal
+    pub fn start_program(
+        source: &'static str,
+        program: &str,
+        args: &[&str],
+        cwd: Option<&str>,
+    ) -> Self {
+        let mut cmd = Command::new(program);
+        cmd.args(args);
+        if let Some(cwd) = cwd {
+            cmd.current_dir(cwd);
+        }
+        Self::start(source, &cmd)
+    }
+
+    pub fn set_pid(&mut self, pid: Option<u32>) {
+        if let Some(row) = self.row.as_mut() {
+            row.pid = pid;
+        }
+    }
+
+    /// Record how the process ended
+    pub fn finish(mut self, status: &io::Result<ExitStatus>) {
+        if let Some(row) = self.row.as_mut() {
+            match status {
+                Ok(status) => {
+                    row.exit_code = status.code();
+                    row.outcome = if status.code().is_some() {
+                        "exited"
+                    } else {
+                        "signaled"
+                    };
+                }
+                Err(e) => {
+                    row.outcome = "failed_to_start";
+                    row.error = Some(e.to_string());
+                }
+            }
+        }
+        self.submit();
+    }
+
+    /// Record the outcome of `output()` or `wait_with_output()`
+    pub fn finish_output(self, output: &io::Result<Output>) {
+        self.finish(&output.as_ref().map(|o| o.status).map_err(clone_error));
+    }
+
+    /// Record an exit code obtained without an `ExitStatus`
+    pub fn finish_code(mut self, exit_code: Option<i32>) {
+        if let Some(row) = self.row.as_mut() {
+            row.exit_code = exit_code;
+            row.outcome = "exited";
+        }
+        self.submit();
+    }
+
+    fn submit(&mut self) {
+        let mut row = match self.row.take() {
+            Some(row) => row,
+            None => return,
+        };
+        if row.outcome != "detached" {
+            row.duration_ms = Some(self.started.elapsed().as_millis() as u64);
+        }
+        debug!(
+            "Process audit: [{}] {} -> {}",
+            row.source, row.program, row.outcome
+        );
+        if let Ok(tx) = QUEUE.0.lock() {
+            let _ = tx.send(row);
+        }
+    }
+}
+
+impl Drop for ProcessAudit {
+    fn drop(&mut self) {
+        self.submit();
+    }
+}
+
+/// Audited versions of `std::process::Command`'s spawning methods
+pub trait AuditedCommand {
+    fn audited_output(&mut self, source: &'static str) -> io::Result<Output>;
+    fn audited_status(&mut self, source: &'static str) -> io::Result<ExitStatus>;
+    /// Spawn a child whose exit the caller reports through the returned ticket;
+    /// dropping the ticket records the process as `detached`
+    fn audited_spawn(&mut self, source: &'static str) -> io::Result<(Child, ProcessAudit)>;
+}
+
+impl AuditedCommand for Command {
+    fn audited_output(&mut self, source: &'static str) -> io::Result<Output> {
+        let audit = ProcessAudit::start(source, self);
+        let output = self.output();
+        audit.finish_output(&output);
+        output
+    }
+
+    fn audited_status(&mut self, source: &'static str) -> io::Result<ExitStatus> {
+        let audit = ProcessAudit::start(source, self);
+        let status = self.status();
+        audit.finish(&status.as_ref().copied().map_err(clone_error));
+        status
+    }
+
+    fn audited_spawn(&mut self, source: &'static str) -> io::Result<(Child, ProcessAudit)> {
+        let mut audit = ProcessAudit::start(source, self);
+        match self.spawn() {
+            Ok(child) => {
+                audit.set_pid(Some(child.id()));
+                Ok((child, audit))
+            }
+            Err(e) => {
+                audit.finish(&Err(clone_error(&e)));
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Audited versions of `tokio::process::Command`'s spawning methods
+pub(crate) trait AuditedAsyncCommand {
+    async fn audited_output(&mut self, source: &'static str) -> io::Result<Output>;
+    /// Spawn a child whose exit the caller reports through the returned ticket
+    fn audited_spawn(
+        &mut self,
+        source: &'static str,
+    ) -> io::Result<(tokio::process::Child, ProcessAudit)>;
+}
+
+impl AuditedAsyncCommand for tokio::process::Command {
+    async fn audited_output(&mut self, source: &'static str) -> io::Result<Output> {
+        let audit = ProcessAudit::start(source, self.as_std());
+        let output = self.output().await;
+        audit.finish_output(&output);
+        output
+    }
+
+    fn audited_spawn(
+        &mut self,
+        source: &'static str,
+    ) -> io::Result<(tokio::process::Child, ProcessAudit)> {
+        let mut audit = ProcessAudit::start(source, self.as_std());
+        match self.spawn() {
+            Ok(child) => {
+                audit.set_pid(child.id());
+                Ok((child, audit))
+            }
+            Err(e) => {
+                audit.finish(&Err(clone_error(&e)));
+                Err(e)
+            }
+        }
+    }
+}
+
+fn clone_error(e: &io::Error) -> io::Error {
+    io::Error::new(e.kind(), e.to_string())
+}
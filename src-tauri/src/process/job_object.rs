@@ -100,6 +100,41 @@ pub mod windows_job {
             }
         }
 
+        /// Cap the CPU time and memory of each process in the job
+        pub fn set_resource_limits(
+            &self,
+            cpu_seconds: Option<u64>,
+            memory_mb: Option<u64>,
+        ) -> Result<(), String> {
+            unsafe {
+                let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+
+                // Keep kill-on-close, which this call would otherwise clear
+                info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+                if let Some(seconds) = cpu_seconds {
+                    info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_PROCESS_TIME;
+                    // Measured in 100-nanosecond ticks
+                    info.BasicLimitInformation.PerProcessUserTimeLimit =
+                        (seconds * 10_000_000) as i64;
+                }
+                if let Some(mb) = memory_mb {
+                    info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_PROCESS_MEMORY;
+                    info.ProcessMemoryLimit = (mb * 1024 * 1024) as usize;
+                }
+
+                SetInformationJobObject(
+                    self.handle,
+                    JobObjectExtendedLimitInformation,
+                    &info as *const _ as *const _,
+                    std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+                )
+                .map_err(|e| format!("Failed to set job resource limits: {:?}", e))?;
+
+                debug!("Set job object resource limits");
+                Ok(())
+            }
+        }
+
         /// Terminate all processes in the job
         #[allow(dead_code)]
         pub fn terminate_all(&self, exit_code: u32) -> Result<(), String> {
@@ -156,6 +191,15 @@ pub mod windows_job {
             // No-op on non-Windows platforms
             Ok(())
         }
+
+        pub fn set_resource_limits(
+            &self,
+            _cpu_seconds: Option<u64>,
+            _memory_mb: Option<u64>,
+        ) -> Result<(), String> {
+            // No-op on non-Windows platforms
+            Ok(())
+        }
     }
 }
 
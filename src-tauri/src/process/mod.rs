@@ -1,3 +1,4 @@
+pub mod audit;
 pub mod job_object;
 pub mod registry;
 
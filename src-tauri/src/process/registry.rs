@@ -1,5 +1,7 @@
+use super::audit::AuditedCommand;
 use super::JobObject;
 use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
@@ -10,6 +12,8 @@ use tokio::process::Child;
 pub enum ProcessType {
     AgentRun { agent_id: i64, agent_name: String },
     ClaudeSession { session_id: String },
+    Hook { event: String },
+    McpServer { server_name: String },
 }
 
 /// Information about a running agent process
@@ -34,10 +38,25 @@ pub struct ProcessHandle {
     pub job_object: Option<Arc<JobObject>>, // Job object for automatic cleanup on Windows
 }
 
+/// A process left running by a previous app instance (e.g. after a crash)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrphanedProcess {
+    /// Journal row id, used to kill or dismiss the orphan
+    pub id: i64,
+    pub process_type: ProcessType,
+    pub pid: u32,
+    /// Executable name recorded at spawn time
+    pub process_name: String,
+    pub project_path: String,
+    pub started_at: String,
+}
+
 /// Registry for tracking active agent processes
 pub struct ProcessRegistry {
     processes: Arc<Mutex<HashMap<i64, ProcessHandle>>>, // run_id -> ProcessHandle
     next_id: Arc<Mutex<i64>>, // Auto-incrementing ID for non-agent processes
+    journal: Arc<Mutex<Option<Connection>>>, // Persistent record of spawned processes
+    instance_id: String,      // Distinguishes this app run in the journal
 }
 
 impl ProcessRegistry {
@@ -45,7 +64,154 @@ impl ProcessRegistry {
         Self {
             processes: Arc::new(Mutex::new(HashMap::new())),
             next_id: Arc::new(Mutex::new(1000000)), // Start at high number to avoid conflicts
+            journal: Arc::new(Mutex::new(None)),
+            instance_id: uuid::Uuid::new_v4().to_string(),
+        }
+    }
+
+    /// Persist registrations to the `process_journal` table so orphans survive a crash
+    pub fn attach_journal(&self, conn: Connection) -> Result<(), String> {
+        let mut journal = self.journal.lock().map_err(|e| e.to_string())?;
+        *journal = Some(conn);
+        Ok(())
+    }
+
+    /// Record a newly registered process in the journal
+    fn journal_insert(&self, info: &ProcessInfo) {
+        // Spawns ps/tasklist, so keep it out of the journal lock
+        let process_name = process_name(info.pid).unwrap_or_default();
+        let journal = match self.journal.lock() {
+            Ok(journal) => journal,
+            Err(_) => return,
+        };
+        let conn = match journal.as_ref() {
+            Some(conn) => conn,
+            None => return,
+        };
+
+        let process_type = serde_json::to_string(&info.process_type).unwrap_or_default();
+        if let Err(e) = conn.execute(
+            "INSERT INTO process_journal (instance_id, run_id, process_type, pid, process_name, project_path, started_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                self.instance_id,
+                info.run_id,
+                process_type,
+                info.pid,
+                process_name,
+                info.project_path,
+                info.started_at.to_rfc3339()
+            ],
+        ) {
+            log::warn!("Failed to journal process {}: {}", info.pid, e);
+        }
+    }
+
+    /// Drop a finished process of this instance from the journal
+    fn journal_remove(&self, run_id: i64) {
+        if let Ok(journal) = self.journal.lock() {
+            if let Some(conn) = journal.as_ref() {
+                let _ = conn.execute(
+                    "DELETE FROM process_journal WHERE instance_id = ?1 AND run_id = ?2",
+                    params![self.instance_id, run_id],
+                );
+            }
+        }
+    }
+
+    /// Find processes journaled by earlier app instances that are still alive.
+    /// Entries whose process has exited (or whose PID was reused) are pruned.
+    pub fn find_orphans(&self) -> Result<Vec<OrphanedProcess>, String> {
+        let journal = self.journal.lock().map_err(|e| e.to_string())?;
+        let conn = match journal.as_ref() {
+            Some(conn) => conn,
+            None => return Ok(Vec::new()),
+        };
+
+        type JournalRow = (i64, String, u32, Option<String>, Option<String>, String);
+        let rows: Vec<JournalRow> = {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT id, process_type, pid, process_name, project_path, started_at
+                     FROM process_journal WHERE instance_id != ?1 ORDER BY id",
+                )
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map(params![self.instance_id], |row| {
+                    Ok((
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                        row.get(5)?,
+                    ))
+                })
+                .map_err(|e| e.to_string())?;
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?
+        };
+
+        let mut orphans = Vec::new();
+        for (id, process_type, pid, recorded_name, project_path, started_at) in rows {
+            let recorded_name = recorded_name.unwrap_or_default();
+            // A different executable under the same PID means the PID was reused
+            let still_ours = match process_name(pid) {
+                Some(name) => recorded_name.is_empty() || name == recorded_name,
+                None => false,
+            };
+
+            match (
+                still_ours,
+                serde_json::from_str::<ProcessType>(&process_type),
+            ) {
+                (true, Ok(process_type)) => orphans.push(OrphanedProcess {
+                    id,
+                    process_type,
+                    pid,
+                    process_name: recorded_name,
+                    project_path: project_path.unwrap_or_default(),
+                    started_at,
+                }),
+                _ => {
+                    let _ = conn.execute("DELETE FROM process_journal WHERE id = ?1", params![id]);
+                }
+            }
+        }
+
+        Ok(orphans)
+    }
+
+    /// Kill an orphan found by `find_orphans` and remove it from the journal
+    pub fn kill_orphan(&self, id: i64) -> Result<bool, String> {
+        let orphan = self.find_orphans()?.into_iter().find(|o| o.id == id);
+        let killed = match orphan {
+            Some(orphan) => {
+                log::info!(
+                    "Killing orphaned process {} (PID: {})",
+                    orphan.process_name,
+                    orphan.pid
+                );
+                let _ = self.kill_child_processes(orphan.pid);
+                terminate_pid(orphan.pid)
+            }
+            None => false,
+        };
+        self.dismiss_orphan(id)?;
+        Ok(killed)
+    }
+
+    /// Forget an orphan without killing it
+    pub fn dismiss_orphan(&self, id: i64) -> Result<(), String> {
+        let journal = self.journal.lock().map_err(|e| e.to_string())?;
+        if let Some(conn) = journal.as_ref() {
+            conn.execute(
+                "DELETE FROM process_journal WHERE id = ?1 AND instance_id != ?2",
+                params![id, self.instance_id],
+            )
+            .map_err(|e| e.to_string())?;
         }
+        Ok(())
     }
 
     /// Generate a unique ID for non-agent processes
@@ -136,6 +302,8 @@ impl ProcessRegistry {
             }
         };
 
+        self.journal_insert(&process_info);
+
         let process_handle = ProcessHandle {
             info: process_info,
             child: Arc::new(Mutex::new(None)), // No child handle for Claude sessions
@@ -148,6 +316,42 @@ impl ProcessRegistry {
         Ok(run_id)
     }
 
+    /// Register a short-lived helper process (hook command, MCP server) whose
+    /// child handle is owned by the caller. Call `unregister_process` when it exits.
+    pub fn register_tracked_process(
+        &self,
+        process_type: ProcessType,
+        pid: u32,
+        project_path: String,
+        task: String,
+    ) -> Result<i64, String> {
+        let run_id = self.generate_id()?;
+        let process_info = ProcessInfo {
+            run_id,
+            process_type,
+            pid,
+            started_at: Utc::now(),
+            project_path,
+            task,
+            model: String::new(),
+        };
+
+        self.journal_insert(&process_info);
+
+        let mut processes = self.processes.lock().map_err(|e| e.to_string())?;
+        processes.insert(
+            run_id,
+            ProcessHandle {
+                info: process_info,
+                child: Arc::new(Mutex::new(None)),
+                live_output: Arc::new(Mutex::new(String::new())),
+                #[cfg(windows)]
+                job_object: None,
+            },
+        );
+        Ok(run_id)
+    }
+
     /// Internal method to register any process
     #[allow(dead_code)]
     fn register_process_internal(
@@ -186,6 +390,8 @@ impl ProcessRegistry {
             }
         };
 
+        self.journal_insert(&process_info);
+
         let process_handle = ProcessHandle {
             info: process_info,
             child: Arc::new(Mutex::new(Some(child))),
@@ -230,11 +436,11 @@ impl ProcessRegistry {
     pub fn unregister_process(&self, run_id: i64) -> Result<(), String> {
         let mut processes = self.processes.lock().map_err(|e| e.to_string())?;
         processes.remove(&run_id);
+        self.journal_remove(run_id);
         Ok(())
     }
 
     /// Get all running processes
-    #[allow(dead_code)]
     pub fn get_running_processes(&self) -> Result<Vec<ProcessInfo>, String> {
         let processes = self.processes.lock().map_err(|e| e.to_string())?;
         Ok(processes
@@ -416,7 +622,7 @@ impl ProcessRegistry {
                     "ProcessId",
                 ])
                 .creation_flags(0x08000000) // CREATE_NO_WINDOW
-                .output();
+                .audited_output("system");
 
             if let Ok(output) = output {
                 let stdout = String::from_utf8_lossy(&output.stdout);
@@ -431,7 +637,7 @@ impl ProcessRegistry {
                             let _ = std::process::Command::new("taskkill")
                                 .args(["/F", "/T", "/PID", &child_pid.to_string()])
                                 .creation_flags(0x08000000)
-                                .output();
+                                .audited_output("system");
                         }
                     }
                 }
@@ -445,7 +651,7 @@ impl ProcessRegistry {
 
             let output = std::process::Command::new("pgrep")
                 .args(["-P", &parent_pid.to_string()])
-                .output();
+                .audited_output("system");
 
             if let Ok(output) = output {
                 let stdout = String::from_utf8_lossy(&output.stdout);
@@ -457,7 +663,7 @@ impl ProcessRegistry {
                             // Kill child process
                             let _ = std::process::Command::new("kill")
                                 .args(["-KILL", &child_pid.to_string()])
-                                .output();
+                                .audited_output("system");
                         }
                     }
                 }
@@ -483,7 +689,7 @@ impl ProcessRegistry {
                 std::process::Command::new("taskkill")
                     .args(["/F", "/T", "/PID", &pid.to_string()]) // Added /T to kill process tree
                     .creation_flags(0x08000000) // CREATE_NO_WINDOW
-                    .output()
+                    .audited_output("system")
             }
             #[cfg(not(target_os = "windows"))]
             {
@@ -491,7 +697,7 @@ impl ProcessRegistry {
                 // but is needed for compilation on non-Windows platforms
                 std::process::Command::new("kill")
                     .args(["-KILL", &pid.to_string()])
-                    .output()
+                    .audited_output("system")
             }
         } else {
             // On Unix, kill the entire process group
@@ -499,7 +705,7 @@ impl ProcessRegistry {
             let pgid = format!("-{}", pid); // Negative PID targets the process group
             let term_result = std::process::Command::new("kill")
                 .args(["-TERM", &pgid])
-                .output();
+                .audited_output("system");
 
             match &term_result {
                 Ok(output) if output.status.success() => {
@@ -510,7 +716,7 @@ impl ProcessRegistry {
                     // Check if still running
                     let check_result = std::process::Command::new("kill")
                         .args(["-0", &pid.to_string()])
-                        .output();
+                        .audited_output("system");
 
                     if let Ok(output) = check_result {
                         if output.status.success() {
@@ -521,7 +727,7 @@ impl ProcessRegistry {
                             );
                             std::process::Command::new("kill")
                                 .args(["-KILL", &pgid])
-                                .output()
+                                .audited_output("system")
                         } else {
                             term_result
                         }
@@ -538,7 +744,7 @@ impl ProcessRegistry {
                     let pgid = format!("-{}", pid);
                     std::process::Command::new("kill")
                         .args(["-KILL", &pgid])
-                        .output()
+                        .audited_output("system")
                 }
             }
         };
@@ -644,6 +850,7 @@ impl ProcessRegistry {
             let mut processes = processes_lock.lock().map_err(|e| e.to_string())?;
             for run_id in &finished_runs {
                 processes.remove(run_id);
+                self.journal_remove(*run_id);
             }
         }
 
@@ -665,7 +872,7 @@ impl ProcessRegistry {
             let _ = std::process::Command::new("taskkill")
                 .args(["/F", "/IM", "claude.exe"])
                 .creation_flags(0x08000000)
-                .output();
+                .audited_output("system");
 
             // Kill any remaining node.exe processes that might be spawned by claude
             // Note: This is aggressive and might kill unrelated node processes
@@ -678,7 +885,7 @@ impl ProcessRegistry {
             // Kill remaining claude processes
             let _ = std::process::Command::new("pkill")
                 .args(["-9", "claude"])
-                .output();
+                .audited_output("system");
 
             info!("Cleaned up any orphaned claude processes");
         }
@@ -740,6 +947,70 @@ impl ProcessRegistry {
     }
 }
 
+/// Executable name of a live process, or None if no such process exists
+fn process_name(pid: u32) -> Option<String> {
+    #[cfg(target_os = "windows")]
+    let output = {
+        use std::os::windows::process::CommandExt;
+        std::process::Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+            .creation_flags(0x08000000) // CREATE_NO_WINDOW
+            .audited_output("system")
+            .ok()?
+    };
+    #[cfg(not(target_os = "windows"))]
+    let output = std::process::Command::new("ps")
+        .args(["-p", &pid.to_string(), "-o", "comm="])
+        .audited_output("system")
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.lines().next()?.trim();
+
+    // tasklist prints an INFO line (no quotes) when nothing matches
+    let name = if cfg!(target_os = "windows") {
+        line.strip_prefix('"')?.split('"').next()?
+    } else {
+        line.rsplit('/').next()?
+    };
+
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
+/// Terminate a single PID that is not tracked by this registry
+fn terminate_pid(pid: u32) -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        std::process::Command::new("taskkill")
+            .args(["/F", "/T", "/PID", &pid.to_string()])
+            .creation_flags(0x08000000) // CREATE_NO_WINDOW
+            .audited_output("system")
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = std::process::Command::new("kill")
+            .args(["-TERM", &pid.to_string()])
+            .audited_output("system");
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        if process_name(pid).is_some() {
+            let _ = std::process::Command::new("kill")
+                .args(["-KILL", &pid.to_string()])
+                .audited_output("system");
+        }
+        process_name(pid).is_none()
+    }
+}
+
 impl Default for ProcessRegistry {
     fn default() -> Self {
         Self::new()
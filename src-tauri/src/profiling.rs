@@ -0,0 +1,265 @@
+//! Timings of hot paths
+//!
+//! Hook execution, git commands, usage indexing and the Claude output stream
+//! run inside `tracing` spans. The layer installed by `init` times each span
+//! from creation to close and keeps, per span name, totals and the most recent
+//! timings with their fields (hook command, git subcommand, session id), which
+//! `get_performance_snapshot` returns. That turns "hooks are slow" or "this repo
+//! is slow" into numbers.
+//!
+//! The app's own logging stays on `log`. Installing a `tracing` subscriber stops
+//! `tracing` from handing dependency events to `log` by itself, so the layer
+//! forwards them.
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt::{self, Write as _};
+use std::sync::Mutex;
+use std::time::Instant;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Timings kept per span name
+const RECENT_PER_SPAN: usize = 200;
+
+/// Characters of a field value kept
+const MAX_FIELD_CHARS: usize = 200;
+
+/// Slowest recent timings listed per span name
+const SLOWEST: usize = 5;
+
+/// One closed span
+#[derive(Debug, Clone, Serialize)]
+pub struct SpanTiming {
+    pub name: String,
+    pub target: String,
+    pub fields: BTreeMap<String, String>,
+    pub duration_ms: f64,
+    pub ended_at: String,
+    /// Name of the enclosing span, e.g. the hook chain a hook ran in
+    pub parent: Option<String>,
+}
+
+/// Timings of one span name; percentiles cover the recent timings only
+#[derive(Debug, Clone, Serialize)]
+pub struct SpanStats {
+    pub name: String,
+    pub count: u64,
+    pub total_ms: f64,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub slowest: Vec<SpanTiming>,
+    /// Most recent first
+    pub recent: Vec<SpanTiming>,
+}
+
+/// Result of `get_performance_snapshot`, most total time first
+#[derive(Debug, Clone, Serialize)]
+pub struct PerformanceSnapshot {
+    /// When timing started
+    pub since: String,
+    pub spans: Vec<SpanStats>,
+}
+
+#[derive(Default)]
+struct SpanRecord {
+    count: u64,
+    total_ms: f64,
+    max_ms: f64,
+    recent: VecDeque<SpanTiming>,
+}
+
+struct Timings {
+    since: String,
+    spans: HashMap<String, SpanRecord>,
+}
+
+static TIMINGS: Lazy<Mutex<Timings>> = Lazy::new(|| {
+    Mutex::new(Timings {
+        since: chrono::Utc::now().to_rfc3339(),
+        spans: HashMap::new(),
+    })
+});
+
+/// Collects field values as strings
+struct FieldVisitor<'a>(&'a mut BTreeMap<String, String>);
+
+impl FieldVisitor<'_> {
+    fn insert(&mut self, field: &Field, mut value: String) {
+        if let Some((cut, _)) = value.char_indices().nth(MAX_FIELD_CHARS) {
+            value.truncate(cut);
+        }
+        self.0.insert(field.name().to_string(), value);
+    }
+}
+
+impl Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, format!("{:?}", value));
+    }
+}
+
+/// Start time and fields of an open span
+struct OpenSpan {
+    start: Instant,
+    fields: BTreeMap<String, String>,
+}
+
+struct SpanTimer;
+
+impl<S> Layer<S> for SpanTimer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = BTreeMap::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        span.extensions_mut().insert(OpenSpan {
+            start: Instant::now(),
+            fields,
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(open) = span.extensions_mut().get_mut::<OpenSpan>() {
+                values.record(&mut FieldVisitor(&mut open.fields));
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(open) = span.extensions_mut().remove::<OpenSpan>() else {
+            return;
+        };
+        record(SpanTiming {
+            name: span.name().to_string(),
+            target: span.metadata().target().to_string(),
+            fields: open.fields,
+            duration_ms: open.start.elapsed().as_secs_f64() * 1000.0,
+            ended_at: chrono::Utc::now().to_rfc3339(),
+            parent: span.parent().map(|parent| parent.name().to_string()),
+        });
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let level = match *metadata.level() {
+            Level::ERROR => log::Level::Error,
+            Level::WARN => log::Level::Warn,
+            Level::INFO => log::Level::Info,
+            Level::DEBUG => log::Level::Debug,
+            Level::TRACE => log::Level::Trace,
+        };
+        if !log::log_enabled!(target: metadata.target(), level) {
+            return;
+        }
+        let mut fields = BTreeMap::new();
+        event.record(&mut FieldVisitor(&mut fields));
+        let mut message = fields.remove("message").unwrap_or_default();
+        for (name, value) in &fields {
+            let _ = write!(message, " {}={}", name, value);
+        }
+        log::logger().log(
+            &log::Record::builder()
+                .level(level)
+                .target(metadata.target())
+                .module_path(metadata.module_path())
+                .file(metadata.file())
+                .line(metadata.line())
+                .args(format_args!("{}", message))
+                .build(),
+        );
+    }
+}
+
+fn record(timing: SpanTiming) {
+    let Ok(mut timings) = TIMINGS.lock() else {
+        return;
+    };
+    let record = timings.spans.entry(timing.name.clone()).or_default();
+    record.count += 1;
+    record.total_ms += timing.duration_ms;
+    record.max_ms = record.max_ms.max(timing.duration_ms);
+    if record.recent.len() == RECENT_PER_SPAN {
+        record.recent.pop_front();
+    }
+    record.recent.push_back(timing);
+}
+
+/// Install the timing layer as the global `tracing` subscriber
+pub fn init() {
+    let subscriber = tracing_subscriber::registry().with(SpanTimer);
+    if let Err(e) = tracing::subscriber::set_global_default(subscriber) {
+        log::warn!("Failed to install span timing: {}", e);
+    }
+}
+
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let index = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted[index]
+}
+
+/// Statistics of every span name, or of one; each lists up to `recent` of its
+/// latest timings
+pub fn snapshot(name: Option<&str>, recent: usize) -> PerformanceSnapshot {
+    let timings = match TIMINGS.lock() {
+        Ok(timings) => timings,
+        Err(e) => e.into_inner(),
+    };
+    let mut spans: Vec<SpanStats> = timings
+        .spans
+        .iter()
+        .filter(|(span_name, _)| name.is_none_or(|n| n == span_name.as_str()))
+        .map(|(span_name, record)| {
+            let mut durations: Vec<f64> = record.recent.iter().map(|t| t.duration_ms).collect();
+            durations.sort_by(f64::total_cmp);
+            let mut slowest: Vec<SpanTiming> = record.recent.iter().cloned().collect();
+            slowest.sort_by(|a, b| b.duration_ms.total_cmp(&a.duration_ms));
+            slowest.truncate(SLOWEST);
+            SpanStats {
+                name: span_name.clone(),
+                count: record.count,
+                total_ms: record.total_ms,
+                mean_ms: record.total_ms / record.count.max(1) as f64,
+                max_ms: record.max_ms,
+                p50_ms: percentile(&durations, 0.5),
+                p95_ms: percentile(&durations, 0.95),
+                slowest,
+                recent: record.recent.iter().rev().take(recent).cloned().collect(),
+            }
+        })
+        .collect();
+    spans.sort_by(|a, b| b.total_ms.total_cmp(&a.total_ms));
+    PerformanceSnapshot {
+        since: timings.since.clone(),
+        spans,
+    }
+}
+
+/// Forget all timings
+pub fn reset() {
+    if let Ok(mut timings) = TIMINGS.lock() {
+        timings.since = chrono::Utc::now().to_rfc3339();
+        timings.spans.clear();
+    }
+}
@@ -1,4 +1,4 @@
-import { useState, useEffect } from 'react';
+import { useState, useEffect, useRef } from 'react';
 import { motion, AnimatePresence } from 'framer-motion';
 import {
   ArrowLeft,
@@ -42,6 +42,8 @@ export function EnhancedHooksManager({ onBack, projectPath }: EnhancedHooksManag
   const [activeTab, setActiveTab] = useState('overview');
   const [saving, setSaving] = useState(false);
   const [modified, setModified] = useState(false);
+  // Hash of the settings file saves go to, as loaded
+  const baseHash = useRef<string>('');
 
   const [testEvent, setTestEvent] = useState<HookEvent | null>(null);
   const [testContext, setTestContext] = useState<HookContext>({
@@ -63,9 +65,12 @@ export function EnhancedHooksManager({ onBack, projectPath }: EnhancedHooksManag
       setLoading(true);
       setError(null);
 
+      // Saves go to the local settings in a project, the user settings otherwise
+      const target = await api.getHooksConfig(projectPath ? 'local' : 'user', projectPath);
+      baseHash.current = target.hash;
       const config = projectPath
         ? await api.getMergedHooksConfig(projectPath)
-        : await api.getHooksConfig('user');
+        : target.hooks;
 
       const enhancedConfig = convertToEnhanced(config);
       setHooksConfig(enhancedConfig);
@@ -87,7 +92,8 @@ export function EnhancedHooksManager({ onBack, projectPath }: EnhancedHooksManag
 
       const originalConfig = convertFromEnhanced(hooksConfig);
       const scope = projectPath ? 'local' : 'user';
-      await api.updateHooksConfig(scope, originalConfig, projectPath);
+      const saved = await api.updateHooksConfig(scope, originalConfig, baseHash.current, projectPath);
+      baseHash.current = saved.hash;
 
       setModified(false);
     } catch (err) {
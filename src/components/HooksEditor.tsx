@@ -66,7 +66,8 @@ interface HooksEditorProps {
   scope: 'project' | 'local' | 'user';
   readOnly?: boolean;
   className?: string;
-  onChange?: (hasChanges: boolean, getHooks: () => HooksConfiguration) => void;
+  /** `save` writes the edited hooks, for parents that hide the editor's own actions */
+  onChange?: (hasChanges: boolean, save: () => Promise<void>) => void;
   hideActions?: boolean;
 }
 
@@ -159,6 +160,8 @@ export const HooksEditor: React.FC<HooksEditorProps> = ({
   const [isLoading, setIsLoading] = useState(false);
   const [loadError, setLoadError] = useState<string | null>(null);
   const [hooks, setHooks] = useState<HooksConfiguration>({});
+  // Hash of the settings file version being edited, checked on save
+  const baseHash = React.useRef<string>('');
 
   // All events use the same HookMatcher[] format according to Claude Code docs
   const allEvents = ['PreToolUse', 'PostToolUse', 'Notification', 'UserPromptSubmit', 'Stop', 'SubagentStop', 'PreCompact', 'SessionStart', 'SessionEnd'] as const;
@@ -186,9 +189,10 @@ export const HooksEditor: React.FC<HooksEditorProps> = ({
       console.log('[HooksEditor] Loading hooks config:', { scope, projectPath });
 
       api.getHooksConfig(scope, projectPath)
-        .then((config) => {
+        .then(({ hooks: config, hash }) => {
           console.log('[HooksEditor] Loaded hooks config:', config);
           console.log('[HooksEditor] Config type:', typeof config, 'is empty:', Object.keys(config || {}).length === 0);
+          baseHash.current = hash;
           setHooks(config || {});
           setHasUnsavedChanges(false);
         })
@@ -254,36 +258,8 @@ export const HooksEditor: React.FC<HooksEditorProps> = ({
     setHasUnsavedChanges(true);
   }, [editableHooks]);
 
-  // Notify parent of changes
-  useEffect(() => {
-    if (onChange) {
-      const getHooks = () => {
-        const newHooks: HooksConfiguration = {};
-
-        // Handle all events using the same logic
-        allEvents.forEach(event => {
-          const matchers = editableHooks[event];
-          if (matchers.length > 0) {
-            newHooks[event] = matchers.map(({ id, expanded, ...matcher }) => ({
-              ...matcher,
-              hooks: matcher.hooks.map(({ id, ...hook }) => hook)
-            }));
-          }
-        });
-
-        return newHooks;
-      };
-
-      onChange(hasUnsavedChanges, getHooks);
-    }
-  }, [hasUnsavedChanges, editableHooks, onChange]);
-
-  // Save function to be called explicitly
-  const handleSave = async () => {
-    if (scope !== 'user' && !projectPath) return;
-
-    setIsSaving(true);
-
+  // Hooks as edited, in the settings file format
+  const collectHooks = (): HooksConfiguration => {
     const newHooks: HooksConfiguration = {};
 
     // Handle all events using the same logic
@@ -297,10 +273,33 @@ export const HooksEditor: React.FC<HooksEditorProps> = ({
       }
     });
 
+    return newHooks;
+  };
+
+  // Save against the version that was loaded; the result may include changes
+  // merged in from disk and is the base for the next save
+  const saveHooks = async (newHooks: HooksConfiguration) => {
+    const saved = await api.updateHooksConfig(scope, newHooks, baseHash.current, projectPath);
+    baseHash.current = saved.hash;
+    setHooks(saved.hooks || {});
+    setHasUnsavedChanges(false);
+  };
+
+  // Notify parent of changes
+  useEffect(() => {
+    if (onChange) {
+      onChange(hasUnsavedChanges, () => saveHooks(collectHooks()));
+    }
+  }, [hasUnsavedChanges, editableHooks, onChange]);
+
+  // Save function to be called explicitly
+  const handleSave = async () => {
+    if (scope !== 'user' && !projectPath) return;
+
+    setIsSaving(true);
+
     try {
-      await api.updateHooksConfig(scope, newHooks, projectPath);
-      setHooks(newHooks);
-      setHasUnsavedChanges(false);
+      await saveHooks(collectHooks());
     } catch (error) {
       console.error('Failed to save hooks:', error);
       setLoadError(error instanceof Error ? error.message : 'Failed to save hooks');
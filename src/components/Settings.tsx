@@ -102,7 +102,7 @@ export const Settings: React.FC<SettingsProps> = ({
   
   // Hooks state
   const [userHooksChanged, setUserHooksChanged] = useState(false);
-  const getUserHooks = React.useRef<(() => any) | null>(null);
+  const saveUserHooks = React.useRef<(() => Promise<void>) | null>(null);
   
   // 挂载时加载设置
   // Load settings on mount
@@ -282,9 +282,8 @@ export const Settings: React.FC<SettingsProps> = ({
       }
 
       // Save user hooks if changed
-      if (userHooksChanged && getUserHooks.current) {
-        const hooks = getUserHooks.current();
-        await api.updateHooksConfig('user', hooks);
+      if (userHooksChanged && saveUserHooks.current) {
+        await saveUserHooks.current();
         setUserHooksChanged(false);
       }
 
@@ -917,9 +916,9 @@ export const Settings: React.FC<SettingsProps> = ({
                     scope="user"
                     className="border-0"
                     hideActions={true}
-                    onChange={(hasChanges, getHooks) => {
+                    onChange={(hasChanges, save) => {
                       setUserHooksChanged(hasChanges);
-                      getUserHooks.current = getHooks;
+                      saveUserHooks.current = save;
                     }}
                   />
                 </div>
@@ -86,6 +86,17 @@ export interface ClaudeMdFile {
   modified: number;
 }
 
+/**
+ * Hooks of one settings file, with the hash of the version they were read from
+ */
+export interface HooksConfigSnapshot {
+  hooks: HooksConfiguration;
+  /** Passed back to updateHooksConfig as the version an edit is based on */
+  hash: string;
+  /** Set when a save was merged with changes made on disk */
+  merged: boolean;
+}
+
 /**
  * Represents a file or directory entry
  */
@@ -1191,11 +1202,11 @@ export const api = {
    * Get hooks configuration for a specific scope
    * @param scope - The configuration scope: 'user', 'project', or 'local'
    * @param projectPath - Project path (required for project and local scopes)
-   * @returns Promise resolving to the hooks configuration
+   * @returns Promise resolving to the hooks configuration and the hash of the file it was read from
    */
-  async getHooksConfig(scope: 'user' | 'project' | 'local', projectPath?: string): Promise<HooksConfiguration> {
+  async getHooksConfig(scope: 'user' | 'project' | 'local', projectPath?: string): Promise<HooksConfigSnapshot> {
     try {
-      return await invoke<HooksConfiguration>("get_hooks_config", { scope, projectPath });
+      return await invoke<HooksConfigSnapshot>("get_hooks_config", { scope, projectPath });
     } catch (error) {
       console.error("Failed to get hooks config:", error);
       throw error;
@@ -1206,16 +1217,18 @@ export const api = {
    * Update hooks configuration for a specific scope
    * @param scope - The configuration scope: 'user', 'project', or 'local'
    * @param hooks - The hooks configuration to save
+   * @param baseHash - Hash returned when the edited configuration was loaded
    * @param projectPath - Project path (required for project and local scopes)
-   * @returns Promise resolving to success message
+   * @returns Promise resolving to the saved configuration and its new hash
    */
   async updateHooksConfig(
     scope: 'user' | 'project' | 'local',
     hooks: HooksConfiguration,
+    baseHash: string,
     projectPath?: string
-  ): Promise<string> {
+  ): Promise<HooksConfigSnapshot> {
     try {
-      return await invoke<string>("update_hooks_config", { scope, projectPath, hooks });
+      return await invoke<HooksConfigSnapshot>("update_hooks_config", { scope, projectPath, hooks, baseHash });
     } catch (error) {
       console.error("Failed to update hooks config:", error);
       throw error;
@@ -1251,7 +1264,7 @@ export const api = {
 
       // Import HooksManager for merging
       const { HooksManager } = await import('@/lib/hooksManager');
-      return HooksManager.mergeConfigs(userHooks, projectHooks, localHooks);
+      return HooksManager.mergeConfigs(userHooks.hooks, projectHooks.hooks, localHooks.hooks);
     } catch (error) {
       console.error("Failed to get merged hooks config:", error);
       throw error;